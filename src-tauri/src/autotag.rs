@@ -0,0 +1,308 @@
+//! Optional auto-tagging with a local WD14-style ONNX tagger.
+//!
+//! The model lives in `<app data>/models/autotag/` as `model.onnx` plus the
+//! `selected_tags.csv` label file that ships with the SmilingWolf taggers.
+//! Inference needs the `autotag` cargo feature (ONNX Runtime); without it the
+//! commands report that auto-tagging is unavailable. Predicted tags are stored
+//! with the `auto:` prefix so they can be told apart from prompt tags and
+//! replaced on the next run.
+
+use crate::error::{AppError, AppResult};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub const AUTO_TAG_PREFIX: &str = "auto:";
+pub const MODEL_FILE: &str = "model.onnx";
+pub const LABELS_FILE: &str = "selected_tags.csv";
+pub const DEFAULT_THRESHOLD: f32 = 0.35;
+
+/// WD14 label category for `general`, `sensitive`, ... rating outputs.
+const RATING_CATEGORY: i64 = 9;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutotagStatus {
+    /// Whether this build includes the ONNX runtime.
+    pub available: bool,
+    pub model_dir: String,
+    /// Whether both model and label files are present.
+    pub model_present: bool,
+}
+
+pub fn status(model_dir: &Path) -> AutotagStatus {
+    AutotagStatus {
+        available: cfg!(feature = "autotag"),
+        model_dir: model_dir.to_string_lossy().to_string(),
+        model_present: model_dir.join(MODEL_FILE).is_file()
+            && model_dir.join(LABELS_FILE).is_file(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggerLabel {
+    pub name: String,
+    pub category: i64,
+}
+
+/// Parses `selected_tags.csv` (`tag_id,name,category,count`, with header).
+pub fn parse_labels(bytes: &[u8]) -> AppResult<Vec<TaggerLabel>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(bytes);
+    let mut labels = Vec::new();
+    for row in reader.records() {
+        let row = row.map_err(|error| {
+            AppError::InvalidInput(format!("Invalid tagger label file: {}", error))
+        })?;
+        let name = row.get(1).unwrap_or("").trim();
+        let category = row
+            .get(2)
+            .and_then(|category| category.trim().parse::<i64>().ok())
+            .unwrap_or(0);
+        labels.push(TaggerLabel {
+            name: name.to_string(),
+            category,
+        });
+    }
+    if labels.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Tagger label file has no labels".to_string(),
+        ));
+    }
+    Ok(labels)
+}
+
+/// Turns model scores into `auto:` tags, most confident first. Rating
+/// outputs are skipped; label underscores become spaces to match prompt tags.
+pub fn select_tags(labels: &[TaggerLabel], scores: &[f32], threshold: f32) -> Vec<String> {
+    let mut picked: Vec<(f32, &TaggerLabel)> = labels
+        .iter()
+        .zip(scores)
+        .filter(|(label, score)| {
+            label.category != RATING_CATEGORY && !label.name.is_empty() && **score >= threshold
+        })
+        .map(|(label, score)| (*score, label))
+        .collect();
+    picked.sort_by(|a, b| b.0.total_cmp(&a.0));
+    picked
+        .into_iter()
+        .map(|(_, label)| {
+            let name = if label.name.chars().count() <= 3 {
+                label.name.to_lowercase()
+            } else {
+                label.name.to_lowercase().replace('_', " ")
+            };
+            format!("{}{}", AUTO_TAG_PREFIX, name)
+        })
+        .collect()
+}
+
+/// NSFW likelihood from the rating outputs: `questionable` plus `explicit`.
+/// `None` when the model has no rating labels.
+pub fn rating_score(labels: &[TaggerLabel], scores: &[f32]) -> Option<f32> {
+    let mut found = false;
+    let mut total = 0.0f32;
+    for (label, score) in labels.iter().zip(scores) {
+        if label.category != RATING_CATEGORY {
+            continue;
+        }
+        found = true;
+        if matches!(label.name.as_str(), "questionable" | "explicit") {
+            total += score;
+        }
+    }
+    found.then(|| total.clamp(0.0, 1.0))
+}
+
+/// Replaces previous `auto:` tags in `existing` with `predicted`.
+pub fn merge_tags(existing: &[String], predicted: &[String]) -> Vec<String> {
+    existing
+        .iter()
+        .filter(|tag| !tag.starts_with(AUTO_TAG_PREFIX))
+        .chain(predicted)
+        .cloned()
+        .collect()
+}
+
+pub fn validate_threshold(threshold: Option<f32>) -> AppResult<f32> {
+    let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD);
+    if !(threshold > 0.0 && threshold < 1.0) {
+        return Err(AppError::InvalidInput(format!(
+            "Auto-tag threshold must be between 0 and 1, got {}",
+            threshold
+        )));
+    }
+    Ok(threshold)
+}
+
+pub struct TaggerPrediction {
+    pub tags: Vec<String>,
+    pub nsfw_score: Option<f32>,
+}
+
+pub struct Tagger {
+    #[cfg(feature = "autotag")]
+    labels: Vec<TaggerLabel>,
+    #[cfg(feature = "autotag")]
+    session: ort::session::Session,
+    #[cfg(feature = "autotag")]
+    input_name: String,
+    #[cfg(feature = "autotag")]
+    input_size: u32,
+}
+
+#[cfg(not(feature = "autotag"))]
+impl Tagger {
+    pub fn load(_model_dir: &Path) -> AppResult<Self> {
+        Err(AppError::InvalidInput(
+            "Auto-tagging is not available in this build (enable the `autotag` feature)"
+                .to_string(),
+        ))
+    }
+
+    pub fn predict(&mut self, _path: &Path, _threshold: f32) -> AppResult<TaggerPrediction> {
+        Err(AppError::Internal(
+            "Auto-tagging is not available".to_string(),
+        ))
+    }
+}
+
+#[cfg(feature = "autotag")]
+impl Tagger {
+    /// Loads the ONNX session and labels from `model_dir`.
+    pub fn load(model_dir: &Path) -> AppResult<Self> {
+        use ort::session::{builder::GraphOptimizationLevel, Session};
+
+        let model_path = model_dir.join(MODEL_FILE);
+        let labels_path = model_dir.join(LABELS_FILE);
+        for path in [&model_path, &labels_path] {
+            if !path.is_file() {
+                return Err(AppError::file_missing(path).context("Auto-tag model is not installed"));
+            }
+        }
+        let labels = parse_labels(&std::fs::read(&labels_path)?)?;
+
+        let ort_error = |error: ort::Error| {
+            AppError::Internal(format!("Failed to load auto-tag model: {}", error))
+        };
+        let threads = std::thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(4)
+            .min(8);
+        let session = Session::builder()
+            .map_err(ort_error)?
+            .with_optimization_level(GraphOptimizationLevel::Level3)
+            .map_err(ort_error)?
+            .with_intra_threads(threads)
+            .map_err(ort_error)?
+            .commit_from_file(&model_path)
+            .map_err(ort_error)?;
+
+        let input = session
+            .inputs
+            .first()
+            .ok_or_else(|| AppError::InvalidInput("Auto-tag model has no inputs".to_string()))?;
+        // WD14 taggers take NHWC input, e.g. [-1, 448, 448, 3].
+        let input_size = input
+            .input_type
+            .tensor_dimensions()
+            .and_then(|dims| dims.get(1).copied())
+            .filter(|size| *size > 0)
+            .unwrap_or(448) as u32;
+        let input_name = input.name.clone();
+
+        Ok(Self {
+            labels,
+            session,
+            input_name,
+            input_size,
+        })
+    }
+
+    pub fn predict(&mut self, path: &Path, threshold: f32) -> AppResult<TaggerPrediction> {
+        let image = crate::image_decode::open_image(path)?;
+        let input = preprocess(&image, self.input_size);
+        let size = self.input_size as usize;
+
+        let ort_error =
+            |error: ort::Error| AppError::Internal(format!("Auto-tag inference failed: {}", error));
+        let tensor =
+            ort::value::Tensor::from_array(([1usize, size, size, 3], input)).map_err(ort_error)?;
+        let outputs = self
+            .session
+            .run(ort::inputs![self.input_name.as_str() => tensor].map_err(ort_error)?)
+            .map_err(ort_error)?;
+        let (_, scores) = outputs[0]
+            .try_extract_raw_tensor::<f32>()
+            .map_err(ort_error)?;
+        Ok(TaggerPrediction {
+            tags: select_tags(&self.labels, scores, threshold),
+            nsfw_score: rating_score(&self.labels, scores),
+        })
+    }
+}
+
+/// Pads to a white square, resizes and converts to BGR floats in 0-255,
+/// the layout the WD14 taggers were trained on.
+#[cfg(feature = "autotag")]
+fn preprocess(image: &image::DynamicImage, size: u32) -> Vec<f32> {
+    use image::{imageops, Rgb, RgbImage};
+
+    let rgb = image.to_rgb8();
+    let side = rgb.width().max(rgb.height());
+    let mut square = RgbImage::from_pixel(side, side, Rgb([255, 255, 255]));
+    imageops::overlay(
+        &mut square,
+        &rgb,
+        ((side - rgb.width()) / 2) as i64,
+        ((side - rgb.height()) / 2) as i64,
+    );
+    let resized = imageops::resize(&square, size, size, imageops::FilterType::CatmullRom);
+
+    let mut data = Vec::with_capacity((size * size * 3) as usize);
+    for pixel in resized.pixels() {
+        data.extend([pixel[2] as f32, pixel[1] as f32, pixel[0] as f32]);
+    }
+    data
+}
+
+/// Default model location under the app data directory.
+pub fn model_dir(app_data: &Path) -> PathBuf {
+    app_data.join("models").join("autotag")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_confident_non_rating_tags_with_prefix() {
+        let labels = parse_labels(
+            b"tag_id,name,category,count\n\
+              9999999,general,9,100\n\
+              470575,1girl,0,4000000\n\
+              16751,long_hair,0,3000000\n\
+              212816,^_^,0,20000\n\
+              1,hatsune_miku,4,100000\n",
+        )
+        .unwrap();
+        let tags = select_tags(&labels, &[0.99, 0.6, 0.9, 0.1, 0.4], 0.35);
+        assert_eq!(
+            tags,
+            vec!["auto:long hair", "auto:1girl", "auto:hatsune miku"]
+        );
+
+        let existing = vec!["castle".to_string(), "auto:old".to_string()];
+        assert_eq!(
+            merge_tags(&existing, &tags[..1]),
+            vec!["castle".to_string(), "auto:long hair".to_string()]
+        );
+        assert!(validate_threshold(Some(1.5)).is_err());
+        assert_eq!(
+            rating_score(&labels, &[0.99, 0.6, 0.9, 0.1, 0.4]),
+            Some(0.0)
+        );
+        assert_eq!(rating_score(&labels[1..], &[0.6, 0.9, 0.1, 0.4]), None);
+        assert_eq!(validate_threshold(None).unwrap(), DEFAULT_THRESHOLD);
+    }
+}
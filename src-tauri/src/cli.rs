@@ -0,0 +1,491 @@
+//! Headless command-line mode for scripted library maintenance.
+//!
+//! Runs against the same SQLite library as the desktop app without starting
+//! the UI, e.g. `forge-meta-link --scan D:\outputs --prune --export out.json`.
+//! Operations run in a fixed order: scan, prune, dedupe, export, stats.
+
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use crate::volumes::VolumeTracker;
+use crate::{image_processing, scanner, StorageProfile};
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const APP_IDENTIFIER: &str = "com.forgemetalink.app";
+const DATABASE_FILE: &str = "ForgeMetaLink.db";
+/// Files parsed per rayon batch; mirrors the desktop scan's bulk chunk size.
+const CLI_SCAN_CHUNK_SIZE: usize = 1_000;
+/// Ids per DELETE statement; stays under SQLite's bound-parameter limit.
+const PRUNE_DELETE_CHUNK: usize = 900;
+
+const USAGE: &str = "\
+Usage: forge-meta-link [OPTIONS]
+
+Without options the desktop app starts. Any option below runs headless.
+
+Options:
+  --scan <DIR>        Index a directory recursively (repeatable)
+  --prune             Drop library entries whose files no longer exist
+  --dedupe            List duplicate groups detected by quick hash
+  --export <FILE>     Export all metadata; format from extension (.json/.csv)
+  --stats             Print library statistics
+  --db <FILE>         Library database (defaults to the desktop app's library)
+  --profile <hdd|ssd> Storage profile for connection sizing (default: hdd)
+  -h, --help          Show this help
+";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct CliOptions {
+    pub scan_dirs: Vec<PathBuf>,
+    pub prune: bool,
+    pub dedupe: bool,
+    pub export_path: Option<PathBuf>,
+    pub stats: bool,
+    pub db_path: Option<PathBuf>,
+    pub profile: StorageProfile,
+    pub help: bool,
+}
+
+/// Returns true when the process was started with CLI arguments rather than
+/// as a plain desktop launch. macOS process serial numbers (`-psn_*`) are
+/// passed by Finder and do not count.
+pub fn is_cli_invocation(args: &[String]) -> bool {
+    args.iter()
+        .any(|arg| arg.starts_with('-') && !arg.starts_with("-psn_"))
+}
+
+/// Connects stdout and stderr to the console the app was started from.
+/// Release builds use the Windows GUI subsystem, which starts without one,
+/// so CLI output would otherwise go nowhere. Does nothing when launched
+/// from Explorer, where there is no parent console.
+#[cfg(target_os = "windows")]
+pub fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // SAFETY: AttachConsole takes a plain process id and has no
+    // preconditions; failure just leaves the process without a console.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn attach_parent_console() {}
+
+/// Parses CLI arguments (without the program name).
+pub fn parse_args(args: &[String]) -> AppResult<CliOptions> {
+    let mut options = CliOptions::default();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        let mut value_for = |flag: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} requires a value", flag))
+        };
+        match arg.as_str() {
+            "--scan" => options.scan_dirs.push(PathBuf::from(value_for("--scan")?)),
+            "--prune" => options.prune = true,
+            "--dedupe" => options.dedupe = true,
+            "--export" => options.export_path = Some(PathBuf::from(value_for("--export")?)),
+            "--stats" => options.stats = true,
+            "--db" => options.db_path = Some(PathBuf::from(value_for("--db")?)),
+            "--profile" => {
+                options.profile = match value_for("--profile")?.to_ascii_lowercase().as_str() {
+                    "hdd" => StorageProfile::Hdd,
+                    "ssd" => StorageProfile::Ssd,
+                    other => {
+                        return Err(AppError::InvalidInput(format!(
+                            "Unknown storage profile: {}",
+                            other
+                        )))
+                    }
+                }
+            }
+            "-h" | "--help" => options.help = true,
+            other => {
+                return Err(AppError::InvalidInput(format!(
+                    "Unknown argument: {}",
+                    other
+                )))
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+/// Runs the CLI and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, USAGE);
+            return 2;
+        }
+    };
+    if options.help {
+        print!("{}", USAGE);
+        return 0;
+    }
+
+    match execute(&options) {
+        Ok(()) => 0,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            1
+        }
+    }
+}
+
+fn execute(options: &CliOptions) -> AppResult<()> {
+    let db_path = match &options.db_path {
+        Some(path) => path.clone(),
+        None => default_app_data_dir()
+            .ok_or_else(|| "Could not determine the app data directory; pass --db".to_string())?
+            .join(DATABASE_FILE),
+    };
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|error| {
+            AppError::from(error).context(format!("Failed to create {}", parent.display()))
+        })?;
+    }
+    let db = Database::new(&db_path, options.profile).map_err(|error| {
+        AppError::from(error).context(format!("Failed to open {}", db_path.display()))
+    })?;
+    eprintln!("Library: {}", db_path.display());
+
+    // Thumbnails of edited images are cleared from the app's cache.
+    let cache_dir = db_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("thumbnails");
+    for dir in &options.scan_dirs {
+        scan(&db, dir, &cache_dir)?;
+    }
+    if options.prune {
+        // The desktop app keeps its settings beside the library.
+        let settings_path = db_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join(crate::BACKGROUND_SETTINGS_FILE);
+        let scan_roots = crate::load_background_settings(&settings_path).scan_roots;
+        prune(&db, &scan_roots)?;
+    }
+    if options.dedupe {
+        dedupe(&db)?;
+    }
+    if let Some(export_path) = &options.export_path {
+        export(&db, export_path)?;
+    }
+    if options.stats {
+        stats(&db)?;
+    }
+    Ok(())
+}
+
+fn scan(db: &Database, dir: &Path, cache_dir: &Path) -> AppResult<()> {
+    if !dir.is_dir() {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid directory: {}",
+            dir.display()
+        )));
+    }
+
+    let started = std::time::Instant::now();
+    let files = scanner::scan_directory(dir);
+    let existing_mtimes = db.get_all_file_mtimes().unwrap_or_default();
+    let pending: Vec<scanner::ScannedFile> = files
+        .iter()
+        .filter(|scanned| {
+            let filepath = scanned.path.to_string_lossy();
+            !matches!(
+                (scanned.file_mtime, existing_mtimes.get(filepath.as_ref())),
+                (Some(current), Some(existing)) if current == *existing
+            )
+        })
+        .cloned()
+        .collect();
+    for scanned in &pending {
+        if existing_mtimes.contains_key(scanned.path.to_string_lossy().as_ref()) {
+            image_processing::discard_thumbnail(&scanned.path, cache_dir);
+        }
+    }
+
+    let mut indexed = 0usize;
+    let mut errors = 0usize;
+    for chunk in pending.chunks(CLI_SCAN_CHUNK_SIZE) {
+        let records: Vec<_> = chunk
+            .par_iter()
+            .map(|scanned| {
+                crate::commands::build_bulk_record(
+                    &scanned.path,
+                    scanned.file_mtime,
+                    scanned.file_size,
+                    dir,
+                )
+            })
+            .collect();
+        match db.bulk_upsert_with_tags(&records) {
+            Ok(count) => indexed += count,
+            Err(error) => {
+                eprintln!("warning: bulk upsert failed: {}", error);
+                errors += records.len();
+            }
+        }
+        eprintln!("  indexed {}/{}", indexed + errors, pending.len());
+    }
+
+    println!(
+        "Scanned {}: {} files, {} indexed, {} unchanged, {} errors ({:.1}s)",
+        dir.display(),
+        files.len(),
+        indexed,
+        files.len() - pending.len(),
+        errors,
+        started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+/// Archived rows are left alone: their file was moved into the archive on
+/// purpose and unarchiving needs the row. So are rows under any of
+/// `scan_roots` that is offline, since an unplugged drive or unmounted share
+/// makes every file on it look missing.
+fn prune(db: &Database, scan_roots: &[String]) -> AppResult<()> {
+    let (statuses, _) = VolumeTracker::new().check(scan_roots, Duration::ZERO);
+    let offline: Vec<String> = statuses
+        .into_iter()
+        .filter(|status| !status.online)
+        .map(|status| status.root)
+        .collect();
+    for root in &offline {
+        eprintln!("Skipping offline root {}", root);
+    }
+    db.set_offline_roots(&offline);
+
+    let locations = db.get_unarchived_image_locations()?;
+    let missing: Vec<i64> = locations
+        .par_iter()
+        .filter(|(_, filepath)| !db.is_offline_path(filepath) && !Path::new(filepath).exists())
+        .map(|(id, _)| *id)
+        .collect();
+    let mut removed = 0usize;
+    for chunk in missing.chunks(PRUNE_DELETE_CHUNK) {
+        removed += db.delete_images_by_ids(chunk)?;
+    }
+    println!(
+        "Pruned {} missing entries ({} checked)",
+        removed,
+        locations.len()
+    );
+    Ok(())
+}
+
+fn dedupe(db: &Database) -> AppResult<()> {
+    let groups = db.find_quick_hash_duplicates()?;
+    let redundant: usize = groups.iter().map(|group| group.ids.len() - 1).sum();
+    for group in &groups {
+        println!("{}", group.quick_hash);
+        for filepath in &group.filepaths {
+            println!("  {}", filepath);
+        }
+    }
+    println!(
+        "{} duplicate groups, {} redundant copies",
+        groups.len(),
+        redundant
+    );
+    Ok(())
+}
+
+fn export(db: &Database, output_path: &Path) -> AppResult<()> {
+    let format = output_path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_else(|| "json".to_string());
+    let ids: Vec<i64> = db
+        .get_all_image_locations()?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    let exported =
+        crate::commands::write_metadata_export(db, &ids, &format, output_path, |_, _| true)?;
+    println!("Exported {} images to {}", exported, output_path.display());
+    Ok(())
+}
+
+fn stats(db: &Database) -> AppResult<()> {
+    let total = db.get_total_count()?;
+    let directories = db.get_unique_directories()?;
+    let models = db.get_unique_models()?;
+    let top_tags = db.get_top_tags(10)?;
+
+    println!("Images:      {}", total);
+    println!("Directories: {}", directories.len());
+    println!("Models:      {}", models.len());
+    for model in models.iter().take(10) {
+        println!("  {:>8}  {}", model.count, model.model_name);
+    }
+    println!("Top tags:");
+    for tag in &top_tags {
+        println!("  {:>8}  {}", tag.count, tag.tag);
+    }
+    Ok(())
+}
+
+/// Mirrors Tauri's `app_data_dir()` so the CLI opens the desktop library.
+fn default_app_data_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| {
+            PathBuf::from(home)
+                .join("Library")
+                .join("Application Support")
+        })
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .map(|home| PathBuf::from(home).join(".local").join("share"))
+            })
+    };
+    base.map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_args_collects_operations() {
+        let options = parse_args(&args(&[
+            "--scan",
+            "a",
+            "--scan",
+            "b",
+            "--export",
+            "out.csv",
+            "--prune",
+            "--profile",
+            "ssd",
+        ]))
+        .expect("args should parse");
+        assert_eq!(
+            options.scan_dirs,
+            vec![PathBuf::from("a"), PathBuf::from("b")]
+        );
+        assert_eq!(options.export_path, Some(PathBuf::from("out.csv")));
+        assert!(options.prune);
+        assert!(!options.dedupe);
+        assert_eq!(options.profile, StorageProfile::Ssd);
+    }
+
+    #[test]
+    fn parse_args_rejects_missing_values_and_unknown_flags() {
+        assert!(parse_args(&args(&["--scan"])).is_err());
+        assert!(parse_args(&args(&["--frobnicate"])).is_err());
+    }
+
+    #[test]
+    fn prune_keeps_archived_images_restorable() {
+        let dir = std::env::temp_dir().join(format!("cli_prune_test_{}", std::process::id()));
+        let (library, cold) = (dir.join("library"), dir.join("cold"));
+        std::fs::create_dir_all(&library).unwrap();
+        std::fs::create_dir_all(&cold).unwrap();
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).unwrap();
+        let upsert = |name: &str| {
+            let path = library.join(name);
+            std::fs::write(&path, name).unwrap();
+            db.upsert_image(
+                &path.to_string_lossy(),
+                name,
+                &library.to_string_lossy(),
+                &crate::parser::GenerationParams::default(),
+                Some(1),
+            )
+            .unwrap()
+        };
+        let archived = upsert("archived.png");
+        let gone = upsert("gone.png");
+        std::fs::remove_file(library.join("gone.png")).unwrap();
+        crate::commands::archive_records(&db, &[archived], &cold, true, false).unwrap();
+
+        prune(&db, &[]).unwrap();
+        assert!(db.get_images_by_ids(&[gone]).unwrap().is_empty());
+        let restored = crate::commands::unarchive_records(&db, &[archived]).unwrap();
+        assert_eq!(restored.restored_ids, vec![archived]);
+        assert!(library.join("archived.png").exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn prune_skips_images_on_offline_roots() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).unwrap();
+        let unplugged = std::env::temp_dir().join(format!("cli_unplugged_{}", std::process::id()));
+        let filepath = unplugged.join("render.png");
+        let id = db
+            .upsert_image(
+                &filepath.to_string_lossy(),
+                "render.png",
+                &unplugged.to_string_lossy(),
+                &crate::parser::GenerationParams::default(),
+                Some(1),
+            )
+            .unwrap();
+
+        prune(&db, &[unplugged.to_string_lossy().to_string()]).unwrap();
+        assert_eq!(db.get_images_by_ids(&[id]).unwrap().len(), 1);
+        prune(&db, &[]).unwrap();
+        assert!(db.get_images_by_ids(&[id]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rescanning_an_edited_image_discards_its_thumbnail() {
+        let dir = std::env::temp_dir().join(format!("cli_rescan_test_{}", std::process::id()));
+        let (library, cache_dir) = (dir.join("library"), dir.join("thumbnails"));
+        std::fs::create_dir_all(&library).unwrap();
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).unwrap();
+        let source = library.join("edited.png");
+        let set_mtime = |secs: u64| {
+            std::fs::File::options()
+                .write(true)
+                .open(&source)
+                .unwrap()
+                .set_modified(std::time::UNIX_EPOCH + Duration::from_secs(secs))
+                .unwrap()
+        };
+        image::RgbImage::new(4, 4).save(&source).unwrap();
+        set_mtime(2_000);
+        scan(&db, &library, &cache_dir).unwrap();
+        let thumb = image_processing::ensure_thumbnail(&source, &cache_dir, StorageProfile::Hdd)
+            .0
+            .unwrap();
+
+        scan(&db, &library, &cache_dir).unwrap();
+        assert!(thumb.exists(), "an unchanged image keeps its thumbnail");
+        set_mtime(1_000);
+        scan(&db, &library, &cache_dir).unwrap();
+        assert!(!thumb.exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn plain_launch_is_not_cli() {
+        assert!(!is_cli_invocation(&[]));
+        assert!(is_cli_invocation(&args(&["--stats"])));
+    }
+}
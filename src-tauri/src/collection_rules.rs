@@ -0,0 +1,332 @@
+//! Rules that keep automatic collections filled.
+//!
+//! A rule is a list of conditions such as `model_family = flux` and
+//! `rating >= 4`, joined with AND or OR. Rules are stored as JSON next to
+//! their collection and compiled to a SQL condition over `images`, so a
+//! scan or edit only has to re-check the images it touched.
+
+use crate::error::{AppError, AppResult};
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleField {
+    ModelFamily,
+    Model,
+    Sampler,
+    Scheduler,
+    SourceTool,
+    GenerationType,
+    Directory,
+    Prompt,
+    WorkflowStatus,
+    /// Has a tag, from the prompt or a sidecar.
+    Tag,
+    /// Sidecar star rating.
+    Rating,
+    Steps,
+    CfgScale,
+    Width,
+    Height,
+    NsfwScore,
+    Sharpness,
+    Favorite,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Text,
+    Tag,
+    Number,
+    Flag,
+}
+
+impl RuleField {
+    fn kind(self) -> FieldKind {
+        match self {
+            Self::Tag => FieldKind::Tag,
+            Self::Rating
+            | Self::Steps
+            | Self::CfgScale
+            | Self::Width
+            | Self::Height
+            | Self::NsfwScore
+            | Self::Sharpness => FieldKind::Number,
+            Self::Favorite => FieldKind::Flag,
+            _ => FieldKind::Text,
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            Self::ModelFamily => "images.model_family",
+            Self::Model => "images.model_name",
+            Self::Sampler => "images.sampler",
+            Self::Scheduler => "images.schedule_type",
+            Self::SourceTool => "images.source_tool",
+            Self::GenerationType => "images.generation_type",
+            Self::Directory => "images.directory",
+            Self::Prompt => "images.prompt",
+            Self::WorkflowStatus => "images.workflow_status",
+            Self::Tag => "tags.tag",
+            Self::Rating => "images.rating",
+            // Stored as the text the metadata carried.
+            Self::Steps => "CAST(images.steps AS REAL)",
+            Self::CfgScale => "CAST(images.cfg_scale AS REAL)",
+            Self::Width => "images.width",
+            Self::Height => "images.height",
+            Self::NsfwScore => "images.nsfw_score",
+            Self::Sharpness => "images.sharpness",
+            Self::Favorite => "images.is_favorite",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleOp {
+    #[serde(alias = "=")]
+    Eq,
+    #[serde(alias = "!=")]
+    Ne,
+    #[serde(alias = ">")]
+    Gt,
+    #[serde(alias = ">=")]
+    Gte,
+    #[serde(alias = "<")]
+    Lt,
+    #[serde(alias = "<=")]
+    Lte,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RuleValue {
+    Flag(bool),
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleCondition {
+    pub field: RuleField,
+    pub op: RuleOp,
+    pub value: RuleValue,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleMatch {
+    /// Every condition holds.
+    #[default]
+    All,
+    /// At least one condition holds.
+    Any,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CollectionRule {
+    #[serde(rename = "match")]
+    pub match_mode: RuleMatch,
+    pub conditions: Vec<RuleCondition>,
+}
+
+impl CollectionRule {
+    /// Checks every condition's operator suits its field and converts
+    /// values to the field's type, so `"4"` works for a rating and `5` for
+    /// a model name.
+    pub fn normalized(mut self) -> AppResult<Self> {
+        if self.conditions.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Add at least one condition to the rule".to_string(),
+            ));
+        }
+        for condition in &mut self.conditions {
+            let kind = condition.field.kind();
+            let allowed = match kind {
+                FieldKind::Text | FieldKind::Tag => {
+                    matches!(condition.op, RuleOp::Eq | RuleOp::Ne | RuleOp::Contains)
+                }
+                FieldKind::Number => condition.op != RuleOp::Contains,
+                FieldKind::Flag => matches!(condition.op, RuleOp::Eq | RuleOp::Ne),
+            };
+            if !allowed {
+                return Err(AppError::InvalidInput(format!(
+                    "{} can't be compared with '{}'",
+                    label(&condition.field),
+                    label(&condition.op)
+                )));
+            }
+            condition.value = match (kind, &condition.value) {
+                (FieldKind::Text | FieldKind::Tag, RuleValue::Text(text)) => {
+                    RuleValue::Text(text.trim().to_string())
+                }
+                (FieldKind::Text | FieldKind::Tag, RuleValue::Number(number)) => {
+                    RuleValue::Text(number.to_string())
+                }
+                (FieldKind::Number, RuleValue::Number(number)) if number.is_finite() => {
+                    RuleValue::Number(*number)
+                }
+                (FieldKind::Number, RuleValue::Text(text)) => text
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|number| number.is_finite())
+                    .map(RuleValue::Number)
+                    .ok_or_else(|| {
+                        AppError::InvalidInput(format!(
+                            "{} needs a number, not '{}'",
+                            label(&condition.field),
+                            text
+                        ))
+                    })?,
+                (FieldKind::Flag, RuleValue::Flag(flag)) => RuleValue::Flag(*flag),
+                _ => {
+                    return Err(AppError::InvalidInput(format!(
+                        "{} can't be compared with {}",
+                        label(&condition.field),
+                        serde_json::to_string(&condition.value).unwrap_or_default()
+                    )))
+                }
+            };
+            if condition.value == RuleValue::Text(String::new()) {
+                return Err(AppError::InvalidInput(format!(
+                    "Give {} a value to compare with",
+                    label(&condition.field)
+                )));
+            }
+        }
+        Ok(self)
+    }
+
+    /// SQL condition over `images` matching the rule, with its parameters
+    /// appended to `params`. Expects a [`normalized`](Self::normalized)
+    /// rule. Images missing a compared value only match `ne` conditions.
+    pub fn to_sql(&self, params: &mut Vec<Value>) -> String {
+        let joiner = match self.match_mode {
+            RuleMatch::All => " AND ",
+            RuleMatch::Any => " OR ",
+        };
+        let clauses: Vec<String> = self
+            .conditions
+            .iter()
+            .map(|condition| condition_sql(condition, params))
+            .collect();
+        if clauses.is_empty() {
+            return "0".to_string();
+        }
+        format!("({})", clauses.join(joiner))
+    }
+}
+
+/// The name a field or operator has in stored rules.
+fn label(value: &impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+fn condition_sql(condition: &RuleCondition, params: &mut Vec<Value>) -> String {
+    let column = condition.field.column();
+    let kind = condition.field.kind();
+    let positive = match (&condition.value, condition.op) {
+        (RuleValue::Text(text), RuleOp::Contains) => {
+            params.push(Value::Text(text.to_lowercase()));
+            format!("instr(lower({}), ?) > 0", column)
+        }
+        (RuleValue::Text(text), _) if kind == FieldKind::Tag => {
+            params.push(Value::Text(text.to_lowercase()));
+            format!("{} = ?", column)
+        }
+        (RuleValue::Text(text), _) => {
+            params.push(Value::Text(text.clone()));
+            format!("{} = ? COLLATE NOCASE", column)
+        }
+        (RuleValue::Number(number), op) => {
+            params.push(Value::Real(*number));
+            let operator = match op {
+                RuleOp::Gt => ">",
+                RuleOp::Gte => ">=",
+                RuleOp::Lt => "<",
+                RuleOp::Lte => "<=",
+                _ => "=",
+            };
+            format!("{} {} ?", column, operator)
+        }
+        (RuleValue::Flag(flag), _) => {
+            params.push(Value::Integer(*flag as i64));
+            format!("{} = ?", column)
+        }
+    };
+    let positive = if kind == FieldKind::Tag {
+        format!(
+            "EXISTS(SELECT 1 FROM image_tags JOIN tags ON tags.id = image_tags.tag_id
+                    WHERE image_tags.image_id = images.id AND {})",
+            positive
+        )
+    } else {
+        positive
+    };
+    if condition.op == RuleOp::Ne {
+        format!("NOT COALESCE({}, 0)", positive)
+    } else {
+        format!("COALESCE({}, 0)", positive)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_parse_normalize_and_compile() {
+        let rule: CollectionRule = serde_json::from_str(
+            r#"{"match": "all", "conditions": [
+                {"field": "model_family", "op": "=", "value": " flux "},
+                {"field": "rating", "op": ">=", "value": "4"},
+                {"field": "tag", "op": "!=", "value": "NSFW"}
+            ]}"#,
+        )
+        .unwrap();
+        let rule = rule.normalized().unwrap();
+        assert_eq!(
+            rule.conditions[0].value,
+            RuleValue::Text("flux".to_string())
+        );
+        assert_eq!(rule.conditions[1].value, RuleValue::Number(4.0));
+
+        let mut params = Vec::new();
+        let sql = rule.to_sql(&mut params);
+        assert!(sql.contains("images.rating >= ?"), "{sql}");
+        assert!(sql.contains(" AND "), "{sql}");
+        assert_eq!(
+            params,
+            [
+                Value::Text("flux".to_string()),
+                Value::Real(4.0),
+                Value::Text("nsfw".to_string()),
+            ]
+        );
+
+        let invalid = |json: &str| {
+            serde_json::from_str::<CollectionRule>(json)
+                .unwrap()
+                .normalized()
+                .is_err()
+        };
+        assert!(invalid(r#"{"conditions": []}"#));
+        assert!(invalid(
+            r#"{"conditions": [{"field": "rating", "op": "contains", "value": 4}]}"#
+        ));
+        assert!(invalid(
+            r#"{"conditions": [{"field": "rating", "op": "gt", "value": "lots"}]}"#
+        ));
+        assert!(invalid(
+            r#"{"conditions": [{"field": "favorite", "op": "eq", "value": "yes"}]}"#
+        ));
+    }
+}
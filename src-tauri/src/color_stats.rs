@@ -0,0 +1,190 @@
+//! Per-image colour statistics for the hue and brightness sorts.
+//!
+//! Stats are taken from the freshly rendered thumbnail, so they cost one
+//! extra pass over a small copy rather than another decode. The dominant
+//! hue is a circular mean of pixel hues weighted by chroma: a red image with
+//! a grey background still reads as red, while an image with no real colour
+//! has no hue at all and sorts with the greys.
+//!
+//! Colour search compares stats as points in a cylinder: hue is the angle,
+//! saturation the radius and brightness the height, so greys of the same
+//! brightness match whatever hue they would have had.
+
+use image::{imageops::FilterType, DynamicImage};
+use serde::{Deserialize, Serialize};
+
+/// Long side of the copy the stats are computed on.
+const ANALYSIS_SIZE: u32 = 64;
+/// Below this mean chroma an image counts as greyscale.
+const MIN_COLORFULNESS: f32 = 0.06;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ColorStats {
+    /// Dominant hue in degrees, `None` for greyscale images.
+    pub hue: Option<f32>,
+    /// Mean chroma, 0–1.
+    pub saturation: f32,
+    /// Mean luma, 0–1.
+    pub brightness: f32,
+}
+
+pub fn compute(image: &DynamicImage) -> Option<ColorStats> {
+    if image.width() == 0 || image.height() == 0 {
+        return None;
+    }
+    let small = image
+        .resize(ANALYSIS_SIZE, ANALYSIS_SIZE, FilterType::Triangle)
+        .to_rgb8();
+    let pixels = (small.width() * small.height()) as f32;
+
+    let (mut luma, mut chroma, mut hue_x, mut hue_y) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+    for pixel in small.pixels() {
+        let (pixel_luma, delta, angle) = pixel_components(pixel.0);
+        luma += pixel_luma;
+        chroma += delta;
+        if let Some(angle) = angle {
+            hue_x += delta * angle.cos();
+            hue_y += delta * angle.sin();
+        }
+    }
+
+    let saturation = chroma / pixels;
+    let hue =
+        (saturation >= MIN_COLORFULNESS).then(|| hue_y.atan2(hue_x).to_degrees().rem_euclid(360.0));
+    Some(ColorStats {
+        hue: hue.map(round),
+        saturation: round(saturation),
+        brightness: round(luma / pixels),
+    })
+}
+
+/// Luma, chroma and hue angle in radians (`None` for greys) of one pixel.
+fn pixel_components(rgb: [u8; 3]) -> (f32, f32, Option<f32>) {
+    let [r, g, b] = rgb.map(|channel| channel as f32 / 255.0);
+    let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+    if delta <= f32::EPSILON {
+        return (luma, delta, None);
+    }
+    let sector = if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    (luma, delta, Some((sector * 60.0).to_radians()))
+}
+
+impl ColorStats {
+    /// Stats of an image filled with one colour.
+    pub fn from_rgb(rgb: [u8; 3]) -> Self {
+        let (luma, chroma, angle) = pixel_components(rgb);
+        Self {
+            hue: angle
+                .filter(|_| chroma >= MIN_COLORFULNESS)
+                .map(|angle| round(angle.to_degrees().rem_euclid(360.0))),
+            saturation: round(chroma),
+            brightness: round(luma),
+        }
+    }
+
+    /// How far apart two colours are, from 0 (same) to 1 (a saturated hue
+    /// and its opposite at opposite brightness).
+    pub fn distance(&self, other: &ColorStats) -> f32 {
+        let plane = |stats: &ColorStats| match stats.hue {
+            Some(hue) => {
+                let angle = hue.to_radians();
+                (
+                    stats.saturation * angle.cos(),
+                    stats.saturation * angle.sin(),
+                )
+            }
+            None => (0.0, 0.0),
+        };
+        let (ax, ay) = plane(self);
+        let (bx, by) = plane(other);
+        let chroma_gap = ((ax - bx).powi(2) + (ay - by).powi(2)).sqrt() / 2.0;
+        let brightness_gap = self.brightness - other.brightness;
+        ((chroma_gap.powi(2) + brightness_gap.powi(2)) / 2.0).sqrt()
+    }
+}
+
+/// Parses `#rrggbb` or `#rgb`, with or without the `#`.
+pub fn parse_hex(hex: &str) -> Option<[u8; 3]> {
+    let digits = hex.trim().trim_start_matches('#');
+    if !digits.is_ascii() {
+        return None;
+    }
+    let channel = |text: &str| u8::from_str_radix(text, 16).ok();
+    match digits.len() {
+        6 => Some([
+            channel(&digits[0..2])?,
+            channel(&digits[2..4])?,
+            channel(&digits[4..6])?,
+        ]),
+        3 => {
+            let mut rgb = [0u8; 3];
+            for (index, digit) in digits.chars().enumerate() {
+                rgb[index] = channel(&digit.to_string())? * 17;
+            }
+            Some(rgb)
+        }
+        _ => None,
+    }
+}
+
+fn round(value: f32) -> f32 {
+    (value * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn stats_pick_the_dominant_hue_and_leave_greys_without_one() {
+        // Mostly grey with a large red block.
+        let mut mostly_red = RgbImage::from_pixel(100, 100, Rgb([128, 128, 128]));
+        for y in 0..60 {
+            for x in 0..100 {
+                mostly_red.put_pixel(x, y, Rgb([200, 30, 40]));
+            }
+        }
+        let stats = compute(&DynamicImage::ImageRgb8(mostly_red)).unwrap();
+        let hue = stats.hue.unwrap();
+        assert!(!(15.0..=345.0).contains(&hue), "{stats:?}");
+
+        let blue = RgbImage::from_pixel(32, 32, Rgb([20, 60, 220]));
+        let hue = compute(&DynamicImage::ImageRgb8(blue))
+            .unwrap()
+            .hue
+            .unwrap();
+        assert!((220.0..=235.0).contains(&hue), "{hue}");
+
+        let grey = RgbImage::from_pixel(32, 32, Rgb([230, 230, 230]));
+        let stats = compute(&DynamicImage::ImageRgb8(grey)).unwrap();
+        assert_eq!(stats.hue, None);
+        assert!(stats.brightness > 0.89, "{stats:?}");
+    }
+
+    #[test]
+    fn colour_distance_ranks_near_hues_first() {
+        assert_eq!(parse_hex("#008080"), Some([0, 128, 128]));
+        assert_eq!(parse_hex("f80"), Some([255, 136, 0]));
+        assert_eq!(parse_hex("#12345"), None);
+        assert_eq!(parse_hex("zz0000"), None);
+
+        let teal = ColorStats::from_rgb(parse_hex("#008080").unwrap());
+        let cyan = ColorStats::from_rgb([0, 140, 150]);
+        let orange = ColorStats::from_rgb([255, 140, 0]);
+        let grey = ColorStats::from_rgb([75, 75, 75]);
+        assert_eq!(grey.hue, None);
+        assert_eq!(teal.distance(&teal), 0.0);
+        assert!(teal.distance(&cyan) < 0.05);
+        assert!(teal.distance(&grey) < teal.distance(&orange));
+        assert!(teal.distance(&orange) <= 1.0);
+    }
+}
@@ -1,7 +1,32 @@
 use crate::{
-    database::{BulkRecord, CursorPage, DirectoryEntry, ImageRecord, ModelEntry, TagCount},
-    forge_api, image_decode, image_processing, parser, scanner, sidecar, AppState, ExportResult,
-    ScanResult, StorageProfile,
+    autotag,
+    collection_rules::CollectionRule,
+    concurrency::{self, ResizablePool},
+    database::{
+        ActivityEntry, ActivityFilter, ActivityKind, ArchivedImage, BulkRecord, Collection,
+        CollectionMembership, ColorMatch, CursorPage, Database, DirectoryEntry, DiskUsageReport,
+        DuplicateGroup, EmbeddingUsage, ExtraParamKey, FieldEdit, FieldEditUndo, FieldUpdate,
+        FilterCounts, ImageDetail, ImageRecord, KeywordTrends, LoraUsage, MetadataCoverage,
+        ModelDefaults, ModelEntry, ModelUsage, OperationKind, OperationStep, PendingOperation,
+        PromptCluster, ResolutionBucket, SamplerEntry, SchedulerEntry, SimilarImage,
+        SourceToolEntry, TagCount, TagSuggestion, ThumbnailFailureRecord, TrendBucket,
+        UserProperty, UserPropertyKey, WorkflowStatus, WorkflowStatusCount,
+    },
+    embed_metadata,
+    error::{AppError, AppResult},
+    events::EventSink,
+    filename_template::FilenameTemplate,
+    focal_point::{self, FocalPoint},
+    forge_api,
+    forge_presets::ForgePreset,
+    forge_queue::ForgePriority,
+    hooks::{HookAction, HookConfig, HookEvent, HookRunResult},
+    image_decode, image_processing, ocr, parser, perf,
+    prompt_styles::PromptStyle,
+    scanner, sidecar, tag_dictionary,
+    tasks::{TaskKind, TaskSnapshot},
+    volumes::VolumeStatus,
+    AppState, ExportResult, ScanResult, StorageProfile,
 };
 use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
 use rayon::prelude::*;
@@ -9,9 +34,9 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::OnceLock;
+use std::sync::RwLock;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::Emitter;
+use tauri::{Emitter, Listener, Manager};
 use walkdir::WalkDir;
 
 /// Mapping between a source filepath and its resolved thumbnail path.
@@ -19,6 +44,9 @@ use walkdir::WalkDir;
 pub struct ThumbnailMapping {
     pub filepath: String,
     pub thumbnail_path: String,
+    /// Where `object-fit: cover` should centre the crop, once detected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focal_point: Option<FocalPoint>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -99,6 +127,9 @@ const HDD_FRIENDLY_SCAN_THREADS: usize = 4;
 const SSD_FRIENDLY_SCAN_THREADS: usize = 12;
 
 fn scan_threads(profile: StorageProfile) -> usize {
+    if let Some(threads) = concurrency::scan_threads_override() {
+        return threads;
+    }
     if let Ok(raw) = std::env::var("FORGE_SCAN_THREADS") {
         if let Ok(parsed) = raw.parse::<usize>() {
             return parsed.clamp(1, 32);
@@ -114,24 +145,15 @@ fn scan_threads(profile: StorageProfile) -> usize {
     }
 }
 
-fn scan_pool(profile: StorageProfile) -> &'static rayon::ThreadPool {
-    static HDD_POOL: OnceLock<rayon::ThreadPool> = OnceLock::new();
-    static SSD_POOL: OnceLock<rayon::ThreadPool> = OnceLock::new();
+fn scan_pool(profile: StorageProfile) -> std::sync::Arc<rayon::ThreadPool> {
+    static HDD_POOL: ResizablePool = ResizablePool::new("scan-io-hdd");
+    static SSD_POOL: ResizablePool = ResizablePool::new("scan-io-ssd");
 
     let pool = match profile {
         StorageProfile::Hdd => &HDD_POOL,
         StorageProfile::Ssd => &SSD_POOL,
     };
-
-    pool.get_or_init(move || {
-        let threads = scan_threads(profile);
-        let profile_name = profile_label(profile);
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(threads)
-            .thread_name(move |idx| format!("scan-io-{}-{}", profile_name, idx))
-            .build()
-            .expect("failed to create scan threadpool")
-    })
+    pool.get(scan_threads(profile))
 }
 
 fn profile_label(profile: StorageProfile) -> &'static str {
@@ -163,57 +185,83 @@ fn scan_thumbnail_chunk_size(profile: StorageProfile) -> usize {
 }
 
 #[tauri::command]
-pub fn get_storage_profile(state: tauri::State<'_, AppState>) -> Result<StorageProfile, String> {
+pub fn get_storage_profile(state: tauri::State<'_, AppState>) -> AppResult<StorageProfile> {
     state
         .storage_profile
         .read()
         .map(|profile| *profile)
-        .map_err(|_| "Failed to read storage profile".to_string())
+        .map_err(|_| AppError::Internal("Failed to read storage profile".to_string()))
 }
 
 #[tauri::command]
 pub fn set_storage_profile(
     profile: StorageProfile,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     {
         let mut lock = state
             .storage_profile
             .write()
-            .map_err(|_| "Failed to update storage profile".to_string())?;
+            .map_err(|_| AppError::Internal("Failed to update storage profile".to_string()))?;
         *lock = profile;
     }
 
     crate::persist_storage_profile(&state.storage_profile_path, profile)?;
     log::info!("Storage profile set to {}", profile_label(profile));
+    log_settings_change(
+        &state.db,
+        "storage_profile",
+        format!("Set the storage profile to {}", profile_label(profile)),
+    );
     Ok(())
 }
 
 #[tauri::command]
-pub fn get_forge_api_key(state: tauri::State<'_, AppState>) -> Result<String, String> {
+pub fn get_forge_api_key(state: tauri::State<'_, AppState>) -> AppResult<String> {
     state
         .forge_api_key
         .read()
         .map(|api_key| api_key.clone())
-        .map_err(|_| "Failed to read Forge API key".to_string())
+        .map_err(|_| AppError::Internal("Failed to read Forge API key".to_string()))
 }
 
 #[tauri::command]
-pub fn set_forge_api_key(api_key: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
+pub fn set_forge_api_key(api_key: String, state: tauri::State<'_, AppState>) -> AppResult<()> {
     {
         let mut lock = state
             .forge_api_key
             .write()
-            .map_err(|_| "Failed to update Forge API key".to_string())?;
+            .map_err(|_| AppError::Internal("Failed to update Forge API key".to_string()))?;
         *lock = api_key.clone();
     }
 
     crate::persist_forge_api_key(&state.forge_api_key_path, &api_key)?;
+    log_settings_change(
+        &state.db,
+        "forge_api_key",
+        "Changed the Forge API key".to_string(),
+    );
     Ok(())
 }
 
+/// Empty scratch directory for a test in `suite`, unique to this process.
+#[cfg(test)]
+fn test_temp_dir(suite: &str, name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "forge_{}_test_{}_{}",
+        suite,
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
 include!("commands/scan.rs");
 
+include!("commands/import.rs");
+
 include!("commands/queries.rs");
 
 include!("commands/thumbnails.rs");
@@ -224,6 +272,70 @@ include!("commands/export.rs");
 
 include!("commands/forge.rs");
 
+include!("commands/forge_presets.rs");
+
+include!("commands/forge_watcher.rs");
+
+include!("commands/watch_folders.rs");
+
+include!("commands/model_defaults.rs");
+
 include!("commands/sidecar.rs");
 
+include!("commands/user_properties.rs");
+
 include!("commands/delete.rs");
+
+include!("commands/delete_safety.rs");
+
+include!("commands/operation_journal.rs");
+
+include!("commands/duplicates.rs");
+
+include!("commands/slideshow.rs");
+
+include!("commands/hooks.rs");
+
+include!("commands/activity.rs");
+
+include!("commands/background.rs");
+
+include!("commands/tasks.rs");
+
+include!("commands/performance.rs");
+
+include!("commands/selection.rs");
+
+include!("commands/tag_dictionary.rs");
+
+include!("commands/prompt_styles.rs");
+
+include!("commands/prompt_edit.rs");
+
+include!("commands/prompt_clusters.rs");
+
+include!("commands/collections.rs");
+
+include!("commands/field_edit.rs");
+
+include!("commands/workflow.rs");
+
+include!("commands/autotag.rs");
+
+include!("commands/ocr.rs");
+
+include!("commands/nsfw.rs");
+
+include!("commands/private.rs");
+
+include!("commands/projects.rs");
+
+include!("commands/stats.rs");
+
+include!("commands/archive.rs");
+include!("commands/volumes.rs");
+include!("commands/view_state.rs");
+include!("commands/focal_points.rs");
+include!("commands/best_of_batch.rs");
+include!("commands/image_diff.rs");
+include!("commands/thumbnail_strip.rs");
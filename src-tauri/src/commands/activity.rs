@@ -0,0 +1,51 @@
+// ────────────────────────── Activity log ──────────────────────────
+
+/// Adds an entry to the activity log. Logging is best effort: the
+/// operation it describes already happened, so a failure is only warned
+/// about.
+fn log_activity(
+    db: &Database,
+    kind: ActivityKind,
+    summary: String,
+    detail: serde_json::Value,
+    filepaths: &[String],
+) {
+    if let Err(error) = db.record_activity(kind, &summary, &detail, filepaths) {
+        log::warn!("Failed to log {} activity: {}", kind.as_str(), error);
+    }
+}
+
+/// Logs that `setting` was saved. Values are left out since some, like
+/// the Forge API key or passcode, are secrets.
+fn log_settings_change(db: &Database, setting: &str, summary: String) {
+    log_activity(
+        db,
+        ActivityKind::Settings,
+        summary,
+        serde_json::json!({ "setting": setting }),
+        &[],
+    );
+}
+
+/// Logged events, newest first, so users can find what deleted, moved or
+/// exported files and when.
+#[tauri::command]
+pub async fn get_activity_log(
+    filter: Option<ActivityFilter>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<ActivityEntry>> {
+    let db = state.db.clone();
+    let filter = filter.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || db.get_activity_log(&filter))
+        .await
+        .map_err(|error| error.to_string())?
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn get_activity_files(
+    entry_id: i64,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<String>> {
+    Ok(state.db.get_activity_files(entry_id)?)
+}
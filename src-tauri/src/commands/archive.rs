@@ -0,0 +1,530 @@
+// ────────────────────────── Archive ──────────────────────────
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveImagesRequest {
+    pub ids: Vec<i64>,
+    /// Existing directory the files (or the zip) go into.
+    pub target: String,
+    /// Pack everything into one zip instead of moving files individually.
+    #[serde(default)]
+    pub compress: bool,
+    /// Archive locked images too instead of skipping them.
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ArchiveImagesResult {
+    pub requested: usize,
+    pub archived_ids: Vec<i64>,
+    /// The zip that was written when compressing.
+    pub archive_file: Option<String>,
+    pub already_archived: usize,
+    pub skipped_locked: usize,
+    pub skipped_locked_ids: Vec<i64>,
+    pub skipped_missing: usize,
+    pub failed_paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UnarchiveImagesResult {
+    pub requested: usize,
+    pub restored_ids: Vec<i64>,
+    pub not_archived: usize,
+    pub failed_paths: Vec<String>,
+}
+
+/// The archive must exist and sit outside the scanned folders, otherwise the
+/// next scan would index the archived copies as new images.
+fn validate_archive_target(target: &str, scan_roots: &[String]) -> AppResult<PathBuf> {
+    let target = target.trim();
+    if target.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Archive location is required.".to_string(),
+        ));
+    }
+    let target_path = PathBuf::from(target);
+    if !target_path.is_dir() {
+        return Err(AppError::FileMissing(format!(
+            "Archive location is not an existing directory: {}",
+            target_path.display()
+        )));
+    }
+    if let Some(root) = scan_roots
+        .iter()
+        .find(|root| target_path.starts_with(Path::new(root)))
+    {
+        return Err(AppError::InvalidInput(format!(
+            "Archive location must be outside the scanned library folder {}",
+            root
+        )));
+    }
+    Ok(target_path)
+}
+
+/// `name.ext`, or `name_N.ext` when `taken` says the name is in use.
+fn free_file_name(filename: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(filename) {
+        return filename.to_string();
+    }
+    let path = Path::new(filename);
+    let stem = path
+        .file_stem()
+        .map(|value| value.to_string_lossy().to_string())
+        .unwrap_or_else(|| "image".to_string());
+    let extension = path
+        .extension()
+        .map(|value| format!(".{}", value.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|suffix| format!("{}_{}{}", stem, suffix, extension))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| filename.to_string())
+}
+
+fn archive_by_moving(
+    records: &[ImageRecord],
+    target: &Path,
+    result: &mut ArchiveImagesResult,
+) -> Vec<ArchivedImage> {
+    let mut archived = Vec::new();
+    for record in records {
+        let source = Path::new(&record.filepath);
+        let filename = free_file_name(&record.filename, |name| target.join(name).exists());
+        let destination = target.join(filename);
+        if let Err(error) = move_file_with_fallback(source, &destination) {
+            result
+                .failed_paths
+                .push(format!("{} ({})", record.filepath, error));
+            continue;
+        }
+        move_known_sidecars(source, &destination);
+        archived.push(ArchivedImage {
+            id: record.id,
+            filepath: record.filepath.clone(),
+            archive_path: destination.to_string_lossy().to_string(),
+            archive_entry: None,
+        });
+    }
+    archived
+}
+
+/// Writes every file (and its sidecars) into one zip, then removes the
+/// originals that made it in.
+fn archive_to_zip(
+    records: &[ImageRecord],
+    target: &Path,
+    result: &mut ArchiveImagesResult,
+) -> AppResult<Vec<ArchivedImage>> {
+    use std::io::BufWriter;
+
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let zip_name = free_file_name(&format!("forge-archive-{}.zip", stamp), |name| {
+        target.join(name).exists()
+    });
+    let zip_path = target.join(zip_name);
+    let file = std::fs::File::create(&zip_path).map_err(|error| {
+        AppError::from(error).context(format!("Failed to create {}", zip_path.display()))
+    })?;
+    let mut zip = zip::ZipWriter::new(BufWriter::with_capacity(256 * 1024, file));
+    // Generated images are already compressed; storing avoids burning CPU for ~0%.
+    let options =
+        zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    let mut used_stems = std::collections::HashSet::<String>::new();
+    let mut written = Vec::new();
+    for record in records {
+        let source = Path::new(&record.filepath);
+        let mut input = match std::fs::File::open(source) {
+            Ok(input) => input,
+            Err(error) => {
+                result
+                    .failed_paths
+                    .push(format!("{} ({})", record.filepath, error));
+                continue;
+            }
+        };
+        // Sidecars share the entry's stem, so stems must be unique, not just names.
+        let entry = free_file_name(&record.filename, |name| {
+            let stem = Path::new(name).file_stem().unwrap_or_default();
+            used_stems.contains(&stem.to_string_lossy().to_string())
+        });
+        used_stems.insert(
+            Path::new(&entry)
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+        );
+        zip.start_file(entry.as_str(), options)?;
+        std::io::copy(&mut input, &mut zip)?;
+        for ext in KNOWN_SIDECAR_EXTENSIONS {
+            let sidecar = source.with_extension(ext);
+            let Ok(mut sidecar_input) = std::fs::File::open(&sidecar) else {
+                continue;
+            };
+            let sidecar_entry = Path::new(&entry).with_extension(ext);
+            zip.start_file(sidecar_entry.to_string_lossy().as_ref(), options)?;
+            std::io::copy(&mut sidecar_input, &mut zip)?;
+        }
+        written.push((record, entry));
+    }
+    zip.finish()?;
+
+    let archive_path = zip_path.to_string_lossy().to_string();
+    result.archive_file = Some(archive_path.clone());
+    let mut archived = Vec::with_capacity(written.len());
+    for (record, entry) in written {
+        let source = Path::new(&record.filepath);
+        if let Err(error) = std::fs::remove_file(source) {
+            // The zip holds a copy; leave the original indexed as-is.
+            result.failed_paths.push(format!(
+                "{} (archived copy written but original not removed: {})",
+                record.filepath, error
+            ));
+            continue;
+        }
+        remove_known_sidecars(source);
+        archived.push(ArchivedImage {
+            id: record.id,
+            filepath: record.filepath.clone(),
+            archive_path: archive_path.clone(),
+            archive_entry: Some(entry),
+        });
+    }
+    Ok(archived)
+}
+
+pub(crate) fn archive_records(
+    db: &crate::database::Database,
+    ids: &[i64],
+    target: &Path,
+    compress: bool,
+    force: bool,
+) -> AppResult<ArchiveImagesResult> {
+    let mut result = ArchiveImagesResult {
+        requested: ids.len(),
+        ..Default::default()
+    };
+    let already: std::collections::HashSet<i64> = db
+        .get_archived_images(ids)?
+        .into_iter()
+        .map(|image| image.id)
+        .collect();
+    result.already_archived = already.len();
+
+    let mut candidates = Vec::new();
+    for record in db.get_images_by_ids(ids)? {
+        if already.contains(&record.id) {
+            continue;
+        }
+        if record.is_locked && !force {
+            result.skipped_locked += 1;
+            result.skipped_locked_ids.push(record.id);
+        } else if !Path::new(&record.filepath).exists() {
+            result.skipped_missing += 1;
+        } else {
+            candidates.push(record);
+        }
+    }
+    if candidates.is_empty() {
+        return Ok(result);
+    }
+
+    let archived = if compress {
+        archive_to_zip(&candidates, target, &mut result)?
+    } else {
+        archive_by_moving(&candidates, target, &mut result)
+    };
+    db.mark_archived(&archived)
+        .map_err(|error| AppError::from(error).context("Failed to mark images as archived"))?;
+    result.archived_ids = archived.iter().map(|image| image.id).collect();
+    let archived_paths: Vec<String> = archived.into_iter().map(|image| image.filepath).collect();
+    log_activity(
+        db,
+        ActivityKind::Archive,
+        format!(
+            "Archived {} image(s) to {}",
+            archived_paths.len(),
+            result
+                .archive_file
+                .clone()
+                .unwrap_or_else(|| target.display().to_string())
+        ),
+        serde_json::json!({
+            "target": target.to_string_lossy(),
+            "archive_file": result.archive_file,
+            "failed": result.failed_paths.len(),
+            "skipped_locked": result.skipped_locked,
+        }),
+        &archived_paths,
+    );
+    Ok(result)
+}
+
+fn extract_zip_entry(
+    archive: &mut zip::ZipArchive<std::fs::File>,
+    entry: &str,
+    destination: &Path,
+) -> AppResult<()> {
+    let mut input = archive.by_name(entry)?;
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut output = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(destination)?;
+    std::io::copy(&mut input, &mut output)?;
+    Ok(())
+}
+
+fn restore_archived_image(
+    image: &ArchivedImage,
+    zips: &mut std::collections::HashMap<String, zip::ZipArchive<std::fs::File>>,
+) -> AppResult<()> {
+    let destination = Path::new(&image.filepath);
+    if destination.exists() {
+        return Err(AppError::InvalidInput(
+            "original location is occupied".to_string(),
+        ));
+    }
+    let Some(entry) = &image.archive_entry else {
+        let source = Path::new(&image.archive_path);
+        move_file_with_fallback(source, destination)?;
+        move_known_sidecars(source, destination);
+        return Ok(());
+    };
+
+    if !zips.contains_key(&image.archive_path) {
+        let file = std::fs::File::open(&image.archive_path)
+            .map_err(|error| AppError::from(error).context("Failed to open archive"))?;
+        zips.insert(image.archive_path.clone(), zip::ZipArchive::new(file)?);
+    }
+    let archive = zips
+        .get_mut(&image.archive_path)
+        .ok_or_else(|| AppError::Internal("Archive handle missing".to_string()))?;
+    extract_zip_entry(archive, entry, destination)?;
+    for ext in KNOWN_SIDECAR_EXTENSIONS {
+        let sidecar_entry = Path::new(entry).with_extension(ext);
+        let sidecar_entry = sidecar_entry.to_string_lossy();
+        if archive.index_for_name(&sidecar_entry).is_some() {
+            let sidecar = destination.with_extension(ext);
+            if let Err(error) = extract_zip_entry(archive, &sidecar_entry, &sidecar) {
+                log::warn!("Failed to restore sidecar {}: {}", sidecar.display(), error);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn unarchive_records(
+    db: &crate::database::Database,
+    ids: &[i64],
+) -> AppResult<UnarchiveImagesResult> {
+    let archived = db.get_archived_images(ids)?;
+    let mut result = UnarchiveImagesResult {
+        requested: ids.len(),
+        not_archived: ids.len().saturating_sub(archived.len()),
+        ..Default::default()
+    };
+    let mut zips = std::collections::HashMap::new();
+    let mut restored_paths = Vec::new();
+    for image in &archived {
+        match restore_archived_image(image, &mut zips) {
+            Ok(()) => {
+                result.restored_ids.push(image.id);
+                restored_paths.push(image.filepath.clone());
+            }
+            Err(error) => result
+                .failed_paths
+                .push(format!("{} ({})", image.filepath, error)),
+        }
+    }
+    db.clear_archived(&result.restored_ids)
+        .map_err(|error| AppError::from(error).context("Failed to clear archived flag"))?;
+    if !result.restored_ids.is_empty() || !result.failed_paths.is_empty() {
+        log_activity(
+            db,
+            ActivityKind::Unarchive,
+            format!("Restored {} archived image(s)", restored_paths.len()),
+            serde_json::json!({ "failed": result.failed_paths.len() }),
+            &restored_paths,
+        );
+    }
+    Ok(result)
+}
+
+/// Moves files to cold storage (optionally as one zip). Archived images keep
+/// their thumbnails and metadata but only show in the "archived" view.
+#[tauri::command]
+pub async fn archive_images(
+    request: ArchiveImagesRequest,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ArchiveImagesResult> {
+    let mut ids = request.ids;
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() {
+        return Ok(ArchiveImagesResult::default());
+    }
+    let scan_roots = state
+        .background_settings
+        .read()
+        .map(|settings| settings.scan_roots.clone())
+        .unwrap_or_default();
+    let target = validate_archive_target(&request.target, &scan_roots)?;
+
+    let db = state.db.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        archive_records(&db, &ids, &target, request.compress, request.force)
+    })
+    .await
+    .map_err(|error| error.to_string())??;
+    log::info!(
+        "Archived {} of {} images ({} failed)",
+        result.archived_ids.len(),
+        result.requested,
+        result.failed_paths.len()
+    );
+    Ok(result)
+}
+
+/// Restores archived files to their original locations.
+#[tauri::command]
+pub async fn unarchive_images(
+    ids: Vec<i64>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<UnarchiveImagesResult> {
+    let mut ids = ids;
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() {
+        return Ok(UnarchiveImagesResult::default());
+    }
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || unarchive_records(&db, &ids))
+        .await
+        .map_err(|error| error.to_string())?
+}
+
+#[cfg(test)]
+mod archive_tests {
+    use super::*;
+    use crate::database::CursorQueryOptions;
+    use crate::parser::GenerationParams;
+
+    #[test]
+    fn zip_archive_round_trip_restores_files_and_sidecars() {
+        let library = test_temp_dir("archive", "library");
+        let cold = test_temp_dir("archive", "cold");
+        let db = crate::database::Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("db init failed");
+
+        let mut ids = Vec::new();
+        for (name, body) in [("a.png", "first"), ("b.png", "second")] {
+            let path = library.join(name);
+            std::fs::write(&path, body).unwrap();
+            let filepath = path.to_string_lossy().to_string();
+            ids.push(
+                db.upsert_image(
+                    &filepath,
+                    name,
+                    &library.to_string_lossy(),
+                    &GenerationParams::default(),
+                    Some(1),
+                )
+                .unwrap(),
+            );
+        }
+        std::fs::write(library.join("a.yaml"), "tags: [x]").unwrap();
+
+        assert!(validate_archive_target(
+            &cold.to_string_lossy(),
+            &[library.to_string_lossy().to_string()]
+        )
+        .is_ok());
+        assert!(validate_archive_target(
+            &library.to_string_lossy(),
+            &[library.to_string_lossy().to_string()]
+        )
+        .is_err());
+
+        let result = archive_records(&db, &ids, &cold, true, false).unwrap();
+        assert_eq!(result.archived_ids.len(), 2);
+        assert!(result.archive_file.is_some());
+        assert!(!library.join("a.png").exists());
+        assert!(!library.join("a.yaml").exists());
+        // Archived rows leave normal views but stay in the archived one.
+        let normal = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(normal.items.is_empty());
+        let archived_view = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                generation_types: Some(&["archived".to_string()]),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(archived_view.items.len(), 2);
+
+        let again = archive_records(&db, &ids, &cold, false, false).unwrap();
+        assert_eq!(again.already_archived, 2);
+
+        let restored = unarchive_records(&db, &ids).unwrap();
+        assert_eq!(restored.restored_ids.len(), 2);
+        assert!(restored.failed_paths.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(library.join("b.png")).unwrap(),
+            "second"
+        );
+        assert!(library.join("a.yaml").exists());
+        let normal = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(normal.items.len(), 2);
+
+        let _ = std::fs::remove_dir_all(&library);
+        let _ = std::fs::remove_dir_all(&cold);
+    }
+
+    #[test]
+    fn locked_images_are_skipped_unless_forced() {
+        let library = test_temp_dir("archive", "locked_library");
+        let cold = test_temp_dir("archive", "locked_cold");
+        let db = crate::database::Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("db init failed");
+        let path = library.join("keep.png");
+        std::fs::write(&path, "locked").unwrap();
+        let id = db
+            .upsert_image(
+                &path.to_string_lossy(),
+                "keep.png",
+                &library.to_string_lossy(),
+                &GenerationParams::default(),
+                Some(1),
+            )
+            .unwrap();
+        db.set_image_locked(id, true).unwrap();
+
+        let skipped = archive_records(&db, &[id], &cold, false, false).unwrap();
+        assert_eq!(skipped.skipped_locked, 1);
+        assert_eq!(skipped.skipped_locked_ids, vec![id]);
+        assert!(path.exists());
+
+        let forced = archive_records(&db, &[id], &cold, false, true).unwrap();
+        assert_eq!(forced.archived_ids, vec![id]);
+        assert!(!path.exists());
+
+        let _ = std::fs::remove_dir_all(&library);
+        let _ = std::fs::remove_dir_all(&cold);
+    }
+}
@@ -0,0 +1,179 @@
+// ────────────────────────── Auto-tagging ──────────────────────────
+
+#[derive(Clone, Serialize)]
+struct AutotagProgress {
+    task_id: u64,
+    current: usize,
+    total: usize,
+    tagged: usize,
+    failed: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct AutotagComplete {
+    task_id: u64,
+    total: usize,
+    tagged: usize,
+    failed: usize,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+#[tauri::command]
+pub fn get_autotag_status(state: tauri::State<'_, AppState>) -> autotag::AutotagStatus {
+    autotag::status(&state.autotag_model_dir)
+}
+
+/// Tags one image and writes the `auto:` tags to the index and its sidecar.
+fn autotag_one(
+    tagger: &mut autotag::Tagger,
+    db: &crate::database::Database,
+    record: &ImageRecord,
+    threshold: f32,
+) -> AppResult<()> {
+    let path = Path::new(&record.filepath);
+    if !path.exists() {
+        return Err(AppError::file_missing(path));
+    }
+    let predicted = tagger.predict(path, threshold)?;
+
+    let existing = db.get_tags_for_image(record.id)?;
+    db.replace_image_tags(record.id, &autotag::merge_tags(&existing, &predicted.tags))?;
+    if let Some(score) = predicted.nsfw_score {
+        db.raise_nsfw_scores(&[(record.id, score)])?;
+    }
+
+    // Sidecar tags are re-read on rescan, so the predictions survive it.
+    let mut sidecar_data = sidecar::read_sidecar(path).unwrap_or_default();
+    sidecar_data.tags = autotag::merge_tags(&sidecar_data.tags, &predicted.tags);
+    sidecar::write_sidecar(path, &sidecar_data)?;
+    Ok(())
+}
+
+/// Runs the local tagger over `ids` in the background and returns the task id.
+///
+/// Predicted tags replace earlier `auto:` tags on each image. Emits to the
+/// calling window:
+/// - `autotag-progress`
+/// - `autotag-complete`
+#[tauri::command]
+pub fn autotag_images(
+    ids: Vec<i64>,
+    threshold: Option<f32>,
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<u64> {
+    if ids.is_empty() {
+        return Err(AppError::InvalidInput(
+            "No images selected for auto-tagging".to_string(),
+        ));
+    }
+    let threshold = autotag::validate_threshold(threshold)?;
+    let status = autotag::status(&state.autotag_model_dir);
+    if !status.available {
+        return Err(AppError::InvalidInput(
+            "Auto-tagging is not available in this build".to_string(),
+        ));
+    }
+    if !status.model_present {
+        return Err(AppError::FileMissing(format!(
+            "Auto-tag model not found; place {} and {} in {}",
+            autotag::MODEL_FILE,
+            autotag::LABELS_FILE,
+            status.model_dir
+        )));
+    }
+
+    let db = state.db.clone();
+    let model_dir = state.autotag_model_dir.clone();
+    let task = state.tasks.start(
+        TaskKind::Autotag,
+        format!("Auto-tag ({} images)", ids.len()),
+    );
+    let task_id = task.id();
+    let events = EventSink::for_window(&app, &window);
+
+    std::thread::Builder::new()
+        .name("autotag".into())
+        .spawn(move || {
+            let fail = |message: String| {
+                log::error!("Auto-tagging failed: {}", message);
+                task.fail(message.clone());
+                let _ = events.emit(
+                    "autotag-complete",
+                    AutotagComplete {
+                        task_id,
+                        total: 0,
+                        tagged: 0,
+                        failed: 0,
+                        cancelled: false,
+                        error: Some(message),
+                    },
+                );
+            };
+
+            task.set_message("Loading tagger model");
+            let mut tagger = match autotag::Tagger::load(&model_dir) {
+                Ok(tagger) => tagger,
+                Err(error) => return fail(error.to_string()),
+            };
+            let records = match db.get_images_by_ids(&ids) {
+                Ok(records) => records,
+                Err(error) => return fail(error.to_string()),
+            };
+
+            let total = records.len();
+            let mut tagged = 0usize;
+            let mut failed = 0usize;
+            let mut cancelled = false;
+            task.set_progress(0, total);
+            task.set_message(format!("Threshold {:.2}", threshold));
+            for (index, record) in records.iter().enumerate() {
+                if !task.checkpoint() {
+                    cancelled = true;
+                    break;
+                }
+                match autotag_one(&mut tagger, &db, record, threshold) {
+                    Ok(()) => tagged += 1,
+                    Err(error) => {
+                        failed += 1;
+                        log::warn!("Auto-tag failed for {}: {}", record.filepath, error);
+                    }
+                }
+                task.set_progress(index + 1, total);
+                let _ = events.emit(
+                    "autotag-progress",
+                    AutotagProgress {
+                        task_id,
+                        current: index + 1,
+                        total,
+                        tagged,
+                        failed,
+                    },
+                );
+            }
+
+            log::info!(
+                "Auto-tagged {} of {} images ({} failed, cancelled={})",
+                tagged,
+                total,
+                failed,
+                cancelled
+            );
+            let _ = events.emit(
+                "autotag-complete",
+                AutotagComplete {
+                    task_id,
+                    total,
+                    tagged,
+                    failed,
+                    cancelled,
+                    error: None,
+                },
+            );
+        })
+        .map_err(|error| AppError::Internal(format!("Failed to start auto-tagging: {}", error)))?;
+
+    Ok(task_id)
+}
@@ -0,0 +1,91 @@
+// ────────────────────────── Background mode ──────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackgroundStatus {
+    pub close_to_tray: bool,
+    pub paused: bool,
+    pub scan_roots: Vec<String>,
+}
+
+#[tauri::command]
+pub fn get_background_status(state: tauri::State<'_, AppState>) -> AppResult<BackgroundStatus> {
+    let settings = state
+        .background_settings
+        .read()
+        .map_err(|_| AppError::Internal("Failed to read background settings".to_string()))?;
+    Ok(BackgroundStatus {
+        close_to_tray: settings.close_to_tray,
+        paused: state.background_paused.load(Ordering::Acquire),
+        scan_roots: settings.scan_roots.clone(),
+    })
+}
+
+#[tauri::command]
+pub fn set_close_to_tray(enabled: bool, state: tauri::State<'_, AppState>) -> AppResult<()> {
+    let mut settings = state
+        .background_settings
+        .write()
+        .map_err(|_| AppError::Internal("Failed to update background settings".to_string()))?;
+    settings.close_to_tray = enabled;
+    crate::persist_background_settings(&state.background_settings_path, &settings)?;
+    log_settings_change(
+        &state.db,
+        "close_to_tray",
+        format!(
+            "Turned {} closing to the tray",
+            if enabled { "on" } else { "off" }
+        ),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_background_paused(paused: bool, app: tauri::AppHandle) -> AppResult<()> {
+    crate::tray::set_background_paused(&app, paused);
+    Ok(())
+}
+
+/// Remembers a scanned directory so the tray can rescan it later.
+fn remember_scan_root(state: &AppState, directory: &str) {
+    if Path::new(directory).starts_with(&state.inbox_dir) {
+        return;
+    }
+    let Ok(mut settings) = state.background_settings.write() else {
+        return;
+    };
+    if settings.add_scan_root(directory) {
+        if let Err(error) =
+            crate::persist_background_settings(&state.background_settings_path, &settings)
+        {
+            log::warn!("{}", error);
+        }
+    }
+}
+
+/// Starts a scan of every remembered library root (tray "Rescan now").
+pub(crate) fn rescan_library_roots(app: &tauri::AppHandle) {
+    let roots = app
+        .state::<AppState>()
+        .background_settings
+        .read()
+        .map(|settings| settings.scan_roots.clone())
+        .unwrap_or_default();
+    if roots.is_empty() {
+        log::info!("Rescan requested but no library roots have been scanned yet");
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for root in roots {
+            if !Path::new(&root).is_dir() {
+                log::warn!("Skipping rescan of unavailable root {}", root);
+                continue;
+            }
+            let state = app.state::<AppState>();
+            if let Err(error) = run_scan(root.clone(), EventSink::broadcast(&app), state).await {
+                log::warn!("Rescan of {} failed: {}", root, error);
+            }
+        }
+    });
+}
@@ -0,0 +1,170 @@
+// ────────────────────────── Best of batch ──────────────────────────
+
+/// Weights of the keeper score; they sum to one.
+const BEST_OF_BATCH_SHARPNESS_WEIGHT: f32 = 0.6;
+const BEST_OF_BATCH_FACE_WEIGHT: f32 = 0.25;
+const BEST_OF_BATCH_SAFE_WEIGHT: f32 = 0.15;
+/// Share of a batch pre-highlighted as likely keepers, at least one image.
+const BEST_OF_BATCH_KEEP_FRACTION: f32 = 0.25;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchSuggestion {
+    pub id: i64,
+    pub filepath: String,
+    /// 0–1, higher is a likelier keeper.
+    pub score: f32,
+    /// Laplacian variance; `None` when no pixels could be read.
+    pub sharpness: Option<f32>,
+    pub face_confidence: Option<f32>,
+    pub nsfw_score: f32,
+    pub suggested: bool,
+}
+
+/// Signals measured for one batch member.
+struct BatchSignals {
+    id: i64,
+    filepath: String,
+    sharpness: Option<f32>,
+    face_confidence: Option<f32>,
+    nsfw_score: f32,
+}
+
+/// Reads the cached thumbnail, or the original when there is none.
+fn measure_batch_member(filepath: &str, cache_dir: &Path) -> AppResult<(f32, f32)> {
+    let source = Path::new(filepath);
+    let thumbnail = image_processing::get_thumbnail_cache_path(source, cache_dir);
+    let image = if thumbnail.is_file() {
+        image_decode::open_image(&thumbnail)?
+    } else {
+        if !source.exists() {
+            return Err(AppError::file_missing(source));
+        }
+        image_decode::open_image(source)?
+    };
+    Ok((
+        crate::image_quality::sharpness(&image),
+        focal_point::face_confidence(&image),
+    ))
+}
+
+/// Scores members best first. Sharpness counts relative to the batch's
+/// sharpest image, since members share a prompt and differ mostly in how
+/// cleanly they rendered.
+fn rank_batch(members: Vec<BatchSignals>) -> Vec<BatchSuggestion> {
+    let sharpest = members
+        .iter()
+        .filter_map(|member| member.sharpness)
+        .fold(0.0f32, f32::max);
+    let mut ranked: Vec<BatchSuggestion> = members
+        .into_iter()
+        .map(|member| {
+            let relative_sharpness = match member.sharpness {
+                Some(sharpness) if sharpest > 0.0 => sharpness / sharpest,
+                _ => 0.0,
+            };
+            let score = BEST_OF_BATCH_SHARPNESS_WEIGHT * relative_sharpness
+                + BEST_OF_BATCH_FACE_WEIGHT * member.face_confidence.unwrap_or(0.0)
+                + BEST_OF_BATCH_SAFE_WEIGHT * (1.0 - member.nsfw_score.clamp(0.0, 1.0));
+            BatchSuggestion {
+                id: member.id,
+                filepath: member.filepath,
+                score: (score * 1000.0).round() / 1000.0,
+                sharpness: member.sharpness,
+                face_confidence: member.face_confidence,
+                nsfw_score: member.nsfw_score,
+                suggested: false,
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.id.cmp(&b.id)));
+
+    let keep = ((ranked.len() as f32 * BEST_OF_BATCH_KEEP_FRACTION).ceil() as usize).max(1);
+    for suggestion in ranked
+        .iter_mut()
+        .filter(|suggestion| suggestion.sharpness.is_some())
+        .take(keep)
+    {
+        suggestion.suggested = true;
+    }
+    ranked
+}
+
+/// Ranks the batch `batch_group` (any member's id, as for
+/// `get_batch_members`) by sharpness, face confidence and NSFW score, best
+/// first, flagging the top quarter as likely keepers.
+#[tauri::command]
+pub async fn suggest_best_of_batch(
+    batch_group: i64,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<BatchSuggestion>> {
+    let db = state.db.clone();
+    let cache_dir = state.cache_dir.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let members = db.get_batch_members(batch_group)?;
+        if members.is_empty() {
+            return Err(AppError::NotFound(format!(
+                "Image not found: {}",
+                batch_group
+            )));
+        }
+        let ids: Vec<i64> = members.iter().map(|member| member.id).collect();
+        let nsfw_scores = db.nsfw_scores(&ids)?;
+        let signals: Vec<BatchSignals> = members
+            .into_par_iter()
+            .map(|member| {
+                let measured = measure_batch_member(&member.filepath, &cache_dir)
+                    .map_err(|error| {
+                        log::warn!("Could not score {}: {}", member.filepath, error);
+                    })
+                    .ok();
+                BatchSignals {
+                    id: member.id,
+                    nsfw_score: nsfw_scores.get(&member.id).copied().unwrap_or(0.0),
+                    sharpness: measured.map(|(sharpness, _)| sharpness),
+                    face_confidence: measured.map(|(_, face)| face),
+                    filepath: member.filepath,
+                }
+            })
+            .collect();
+        Ok(rank_batch(signals))
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+#[cfg(test)]
+mod best_of_batch_tests {
+    use super::*;
+
+    fn signals(id: i64, sharpness: Option<f32>, face: f32, nsfw: f32) -> BatchSignals {
+        BatchSignals {
+            id,
+            filepath: format!("{id}.png"),
+            sharpness,
+            face_confidence: sharpness.map(|_| face),
+            nsfw_score: nsfw,
+        }
+    }
+
+    #[test]
+    fn sharp_faces_rank_first_and_unread_images_are_never_suggested() {
+        let ranked = rank_batch(vec![
+            signals(1, Some(50.0), 0.2, 0.0),
+            signals(2, Some(400.0), 0.9, 0.0),
+            signals(3, Some(380.0), 0.9, 0.9),
+            signals(4, None, 0.0, 0.0),
+            signals(5, Some(10.0), 0.0, 0.0),
+        ]);
+        let order: Vec<i64> = ranked.iter().map(|suggestion| suggestion.id).collect();
+        assert_eq!(order, [2, 3, 1, 5, 4]);
+        let suggested: Vec<i64> = ranked
+            .iter()
+            .filter(|suggestion| suggestion.suggested)
+            .map(|suggestion| suggestion.id)
+            .collect();
+        assert_eq!(suggested, [2, 3]);
+
+        let unreadable = rank_batch(vec![signals(7, None, 0.0, 0.0)]);
+        assert!(!unreadable[0].suggested);
+    }
+}
@@ -0,0 +1,60 @@
+// ────────────────────────── Collections ──────────────────────────
+
+#[tauri::command]
+pub fn get_collections(state: tauri::State<'_, AppState>) -> AppResult<Vec<Collection>> {
+    state.db.get_collections().map_err(AppError::from)
+}
+
+/// Creates a rule-based collection, or updates collection `id`, and fills
+/// it from the library. Scans and edits keep it current from then on.
+#[tauri::command]
+pub fn save_collection(
+    id: Option<i64>,
+    name: String,
+    rule: CollectionRule,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Collection> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Give the collection a name".to_string(),
+        ));
+    }
+    let rule = rule.normalized()?;
+    let existing = state.db.get_collections()?;
+    if id.is_some_and(|id| !existing.iter().any(|collection| collection.id == id)) {
+        return Err(AppError::NotFound(format!(
+            "Collection not found: {}",
+            id.unwrap_or_default()
+        )));
+    }
+    if existing
+        .iter()
+        .any(|collection| collection.name.eq_ignore_ascii_case(&name) && Some(collection.id) != id)
+    {
+        return Err(AppError::InvalidInput(format!(
+            "A collection named '{}' already exists",
+            name
+        )));
+    }
+
+    let id = state
+        .db
+        .save_collection(id, &name, &rule)
+        .map_err(|error| AppError::from(error).context("Failed to save collection"))?;
+    state
+        .db
+        .get_collections()?
+        .into_iter()
+        .find(|collection| collection.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Collection not found: {}", id)))
+}
+
+/// Removes a collection; its images stay in the library.
+#[tauri::command]
+pub fn delete_collection(id: i64, state: tauri::State<'_, AppState>) -> AppResult<()> {
+    if !state.db.delete_collection(id)? {
+        return Err(AppError::NotFound(format!("Collection not found: {}", id)));
+    }
+    Ok(())
+}
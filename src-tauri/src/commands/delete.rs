@@ -7,11 +7,28 @@ pub enum DeleteMode {
     Trash,
 }
 
+impl DeleteMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Permanent => "permanent",
+            Self::Trash => "trash",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeleteImagesRequest {
     pub ids: Vec<i64>,
     pub mode: DeleteMode,
+    /// Delete locked and favorite images too instead of reporting them as
+    /// blocked.
+    #[serde(default)]
+    pub force: bool,
+    /// From `preview_delete_images`; required when it reported the
+    /// deletion as risky.
+    #[serde(default)]
+    pub confirmation_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -78,15 +95,15 @@ fn remove_thumbnail_cache_file(
     }
 }
 
-fn delete_file_with_mode(path: &Path, mode: DeleteMode) -> Result<(), String> {
+fn delete_file_with_mode(path: &Path, mode: DeleteMode) -> AppResult<()> {
     match mode {
-        DeleteMode::Permanent => std::fs::remove_file(path).map_err(|error| error.to_string()),
+        DeleteMode::Permanent => std::fs::remove_file(path).map_err(AppError::from),
         DeleteMode::Trash => move_to_trash(path),
     }
 }
 
 #[cfg(target_os = "windows")]
-fn move_to_trash(path: &Path) -> Result<(), String> {
+fn move_to_trash(path: &Path) -> AppResult<()> {
     let escaped_path = path.to_string_lossy().replace('\'', "''");
     let script = format!(
         "Add-Type -AssemblyName Microsoft.VisualBasic; \
@@ -109,16 +126,14 @@ fn move_to_trash(path: &Path) -> Result<(), String> {
             }
             Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
             Err(error) => {
-                return Err(format!(
-                    "Failed to invoke {} recycle-bin delete: {}",
-                    shell, error
-                ));
+                return Err(AppError::from(error)
+                    .context(format!("Failed to invoke {} recycle-bin delete", shell)));
             }
         }
     }
 
     let output = output_result.ok_or_else(|| {
-        "Neither powershell nor pwsh was found for recycle-bin delete.".to_string()
+        AppError::Io("Neither powershell nor pwsh was found for recycle-bin delete.".to_string())
     })?;
 
     if output.status.success() {
@@ -127,57 +142,53 @@ fn move_to_trash(path: &Path) -> Result<(), String> {
 
     let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
     if stderr.is_empty() {
-        Err(format!(
+        Err(AppError::Io(format!(
             "PowerShell recycle-bin delete failed with status {}",
             output.status
-        ))
+        )))
     } else {
-        Err(stderr)
+        Err(AppError::Io(stderr))
     }
 }
 
-fn move_file_with_fallback(source: &Path, destination: &Path) -> Result<(), String> {
+fn move_file_with_fallback(source: &Path, destination: &Path) -> AppResult<()> {
     let destination_parent = destination.parent().ok_or_else(|| {
-        format!(
+        AppError::InvalidInput(format!(
             "Destination path {} has no parent directory.",
             destination.display()
-        )
+        ))
     })?;
     std::fs::create_dir_all(destination_parent).map_err(|error| {
-        format!(
-            "Failed to create destination directory {}: {}",
-            destination_parent.display(),
-            error
-        )
+        AppError::from(error).context(format!(
+            "Failed to create destination directory {}",
+            destination_parent.display()
+        ))
     })?;
 
     match std::fs::rename(source, destination) {
         Ok(_) => Ok(()),
         Err(rename_error) => {
             if !matches!(rename_error.raw_os_error(), Some(17) | Some(18)) {
-                return Err(format!(
-                    "Failed to move {} to {}: {}",
+                return Err(AppError::from(rename_error).context(format!(
+                    "Failed to move {} to {}",
                     source.display(),
-                    destination.display(),
-                    rename_error
-                ));
+                    destination.display()
+                )));
             }
 
             std::fs::copy(source, destination).map_err(|copy_error| {
-                format!(
-                    "Failed to copy {} to {} after cross-device move failure: {}",
+                AppError::from(copy_error).context(format!(
+                    "Failed to copy {} to {} after cross-device move failure",
                     source.display(),
-                    destination.display(),
-                    copy_error
-                )
+                    destination.display()
+                ))
             })?;
             std::fs::remove_file(source).map_err(|remove_error| {
-                format!(
-                    "Failed to remove {} after copying to {}: {}",
+                AppError::from(remove_error).context(format!(
+                    "Failed to remove {} after copying to {}",
                     source.display(),
-                    destination.display(),
-                    remove_error
-                )
+                    destination.display()
+                ))
             })
         }
     }
@@ -242,7 +253,7 @@ fn resolve_move_destination_path(
     destination_directory: &Path,
     image_id: i64,
     state: &tauri::State<'_, AppState>,
-) -> Result<PathBuf, String> {
+) -> AppResult<PathBuf> {
     let original_filename = source_path.file_name().ok_or_else(|| {
         format!(
             "Failed to resolve destination for {} (missing filename).",
@@ -278,7 +289,9 @@ fn resolve_move_destination_path(
         let existing_id = state
             .db
             .get_image_id_by_filepath(&candidate_string)
-            .map_err(|error| format!("Failed to validate move destination path: {}", error))?;
+            .map_err(|error| {
+                AppError::from(error).context("Failed to validate move destination path")
+            })?;
         if let Some(existing_id) = existing_id {
             if existing_id != image_id {
                 continue;
@@ -288,33 +301,36 @@ fn resolve_move_destination_path(
         return Ok(candidate);
     }
 
-    Err(format!(
+    Err(AppError::Io(format!(
         "Failed to find a free destination filename in {} for {}.",
         destination_directory.display(),
         source_path.display()
-    ))
+    )))
 }
 
 #[cfg(not(target_os = "windows"))]
-fn move_to_trash(path: &Path) -> Result<(), String> {
-    let home = std::env::var("HOME")
-        .map_err(|error| format!("Failed to resolve HOME for trash path: {}", error))?;
+fn move_to_trash(path: &Path) -> AppResult<()> {
+    let home = std::env::var("HOME").map_err(|error| {
+        AppError::Io(format!("Failed to resolve HOME for trash path: {}", error))
+    })?;
     #[cfg(target_os = "macos")]
     let trash_dir = PathBuf::from(home).join(".Trash");
     #[cfg(not(target_os = "macos"))]
     let trash_dir = PathBuf::from(home).join(".local/share/Trash/files");
 
     std::fs::create_dir_all(&trash_dir).map_err(|error| {
-        format!(
-            "Failed to create trash directory {}: {}",
-            trash_dir.display(),
-            error
-        )
+        AppError::from(error).context(format!(
+            "Failed to create trash directory {}",
+            trash_dir.display()
+        ))
     })?;
 
-    let file_name = path
-        .file_name()
-        .ok_or_else(|| format!("Invalid file name for trash move: {}", path.display()))?;
+    let file_name = path.file_name().ok_or_else(|| {
+        AppError::InvalidInput(format!(
+            "Invalid file name for trash move: {}",
+            path.display()
+        ))
+    })?;
     let mut target_path = trash_dir.join(file_name);
     if target_path.exists() {
         let millis = std::time::SystemTime::now()
@@ -330,29 +346,26 @@ fn move_to_trash(path: &Path) -> Result<(), String> {
         Err(rename_error) => {
             const EXDEV: i32 = 18;
             if rename_error.raw_os_error() != Some(EXDEV) {
-                return Err(format!(
-                    "Failed to move {} to trash {}: {}",
+                return Err(AppError::from(rename_error).context(format!(
+                    "Failed to move {} to trash {}",
                     path.display(),
-                    target_path.display(),
-                    rename_error
-                ));
+                    target_path.display()
+                )));
             }
 
             std::fs::copy(path, &target_path).map_err(|copy_error| {
-                format!(
-                    "Failed to copy {} to trash {} after cross-device move failure: {}",
+                AppError::from(copy_error).context(format!(
+                    "Failed to copy {} to trash {} after cross-device move failure",
                     path.display(),
-                    target_path.display(),
-                    copy_error
-                )
+                    target_path.display()
+                ))
             })?;
             std::fs::remove_file(path).map_err(|remove_error| {
-                format!(
-                    "Failed to remove {} after copying to trash {}: {}",
+                AppError::from(remove_error).context(format!(
+                    "Failed to remove {} after copying to trash {}",
                     path.display(),
-                    target_path.display(),
-                    remove_error
-                )
+                    target_path.display()
+                ))
             })
         }
     }
@@ -360,12 +373,14 @@ fn move_to_trash(path: &Path) -> Result<(), String> {
 
 /// Deletes image files from disk and removes corresponding DB rows.
 ///
-/// Images that fail to delete on disk are left in the database.
+/// Images that fail to delete on disk are left in the database. Deletions
+/// `preview_delete_images` flags as risky are refused unless the request
+/// carries the confirmation token it returned.
 #[tauri::command]
 pub fn delete_images(
     request: DeleteImagesRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<DeleteImagesResult, String> {
+) -> AppResult<DeleteImagesResult> {
     if request.ids.is_empty() {
         return Ok(DeleteImagesResult {
             requested: 0,
@@ -390,7 +405,7 @@ pub fn delete_images(
     let records = state
         .db
         .get_images_by_ids(&unique_ids)
-        .map_err(|error| format!("Failed to resolve images for deletion: {}", error))?;
+        .map_err(|error| AppError::from(error).context("Failed to resolve images for deletion"))?;
 
     if records.is_empty() {
         return Ok(DeleteImagesResult {
@@ -408,6 +423,20 @@ pub fn delete_images(
         });
     }
 
+    let safety = deletion_safety_report(&state.db, &records, requested, request.force)?;
+    if safety.is_risky(request.force)
+        && !request.confirmation_token.as_deref().is_some_and(|token| {
+            state
+                .delete_confirmations
+                .redeem(token, &unique_ids, request.force)
+        })
+    {
+        return Err(AppError::InvalidInput(format!(
+            "Deleting these images needs confirmation ({}); preview the deletion first",
+            safety.risks(request.force)
+        )));
+    }
+
     let mut deleted_files = 0usize;
     let mut missing_files = 0usize;
     let mut failed_files = 0usize;
@@ -415,8 +444,20 @@ pub fn delete_images(
     let mut deletable = Vec::<(i64, String)>::new();
     let mut blocked_protected_ids = Vec::<i64>::new();
 
+    let mut journal = OperationJournal::begin(
+        &state.db,
+        OperationKind::Delete,
+        serde_json::json!({ "mode": request.mode.as_str() }),
+    )?;
+    let steps: Vec<(i64, &str, Option<&str>)> = records
+        .iter()
+        .filter(|record| request.force || !(record.is_locked || record.is_favorite))
+        .map(|record| (record.id, record.filepath.as_str(), None))
+        .collect();
+    journal.intend(&steps)?;
+
     for record in &records {
-        if record.is_locked || record.is_favorite {
+        if (record.is_locked || record.is_favorite) && !request.force {
             blocked_protected_ids.push(record.id);
             continue;
         }
@@ -427,6 +468,7 @@ pub fn delete_images(
                 Ok(_) => {
                     deleted_files += 1;
                     deletable.push((record.id, record.filepath.clone()));
+                    journal.done(record.id);
                 }
                 Err(error) => {
                     failed_files += 1;
@@ -436,6 +478,7 @@ pub fn delete_images(
         } else {
             missing_files += 1;
             deletable.push((record.id, record.filepath.clone()));
+            journal.done(record.id);
         }
     }
 
@@ -459,7 +502,35 @@ pub fn delete_images(
     let removed_from_db = state
         .db
         .delete_images_by_ids(&deleted_ids)
-        .map_err(|error| format!("Failed to remove deleted images from database: {}", error))?;
+        .map_err(|error| {
+            AppError::from(error).context("Failed to remove deleted images from database")
+        })?;
+
+    if !deletable.is_empty() || failed_files > 0 {
+        let deleted_paths: Vec<String> = deletable
+            .iter()
+            .map(|(_, filepath)| filepath.clone())
+            .collect();
+        let destination = match request.mode {
+            DeleteMode::Permanent => "permanently",
+            DeleteMode::Trash => "to the trash",
+        };
+        log_activity(
+            &state.db,
+            ActivityKind::Delete,
+            format!("Deleted {} image(s) {}", deleted_paths.len(), destination),
+            serde_json::json!({
+                "mode": request.mode.as_str(),
+                "force": request.force,
+                "requested": requested,
+                "deleted_files": deleted_files,
+                "missing_files": missing_files,
+                "failed_files": failed_files,
+                "blocked_protected": blocked_protected_ids.len(),
+            }),
+            &deleted_paths,
+        );
+    }
 
     Ok(DeleteImagesResult {
         requested,
@@ -481,6 +552,9 @@ pub fn delete_images(
 pub struct MoveImagesRequest {
     pub ids: Vec<i64>,
     pub destination_directory: String,
+    /// Move locked images too instead of skipping them.
+    #[serde(default)]
+    pub force: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -500,6 +574,8 @@ pub struct MoveImagesResult {
     pub moved_items: Vec<MovedImageRecord>,
     pub skipped_missing: usize,
     pub skipped_same_directory: usize,
+    pub skipped_locked: usize,
+    pub skipped_locked_ids: Vec<i64>,
     pub failed: usize,
     pub failed_paths: Vec<String>,
 }
@@ -508,7 +584,7 @@ pub struct MoveImagesResult {
 pub fn move_images_to_directory(
     request: MoveImagesRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<MoveImagesResult, String> {
+) -> AppResult<MoveImagesResult> {
     if request.ids.is_empty() {
         return Ok(MoveImagesResult {
             requested: 0,
@@ -518,6 +594,8 @@ pub fn move_images_to_directory(
             moved_items: Vec::new(),
             skipped_missing: 0,
             skipped_same_directory: 0,
+            skipped_locked: 0,
+            skipped_locked_ids: Vec::new(),
             failed: 0,
             failed_paths: Vec::new(),
         });
@@ -525,19 +603,21 @@ pub fn move_images_to_directory(
 
     let destination_directory = PathBuf::from(request.destination_directory.trim());
     if request.destination_directory.trim().is_empty() {
-        return Err("Destination directory is required.".to_string());
+        return Err(AppError::InvalidInput(
+            "Destination directory is required.".to_string(),
+        ));
     }
     if !destination_directory.exists() {
-        return Err(format!(
+        return Err(AppError::FileMissing(format!(
             "Destination directory does not exist: {}",
             destination_directory.display()
-        ));
+        )));
     }
     if !destination_directory.is_dir() {
-        return Err(format!(
+        return Err(AppError::InvalidInput(format!(
             "Destination path is not a directory: {}",
             destination_directory.display()
-        ));
+        )));
     }
 
     let mut unique_ids = request.ids;
@@ -548,7 +628,7 @@ pub fn move_images_to_directory(
     let records = state
         .db
         .get_images_by_ids(&unique_ids)
-        .map_err(|error| format!("Failed to resolve images for moving: {}", error))?;
+        .map_err(|error| AppError::from(error).context("Failed to resolve images for moving"))?;
     if records.is_empty() {
         return Ok(MoveImagesResult {
             requested,
@@ -558,18 +638,32 @@ pub fn move_images_to_directory(
             moved_items: Vec::new(),
             skipped_missing: 0,
             skipped_same_directory: 0,
+            skipped_locked: 0,
+            skipped_locked_ids: Vec::new(),
             failed: 0,
             failed_paths: Vec::new(),
         });
     }
 
+    let mut journal = OperationJournal::begin(
+        &state.db,
+        OperationKind::Move,
+        serde_json::json!({ "destination": destination_directory.to_string_lossy() }),
+    )?;
     let mut moved_ids = Vec::<i64>::new();
     let mut moved_items = Vec::<MovedImageRecord>::new();
+    let mut moved_sources = Vec::<String>::new();
     let mut skipped_missing = 0usize;
     let mut skipped_same_directory = 0usize;
+    let mut skipped_locked_ids = Vec::<i64>::new();
     let mut failed_paths = Vec::<String>::new();
 
     for record in records {
+        if record.is_locked && !request.force {
+            skipped_locked_ids.push(record.id);
+            continue;
+        }
+
         let source_path = PathBuf::from(&record.filepath);
         if !source_path.exists() {
             skipped_missing += 1;
@@ -589,6 +683,11 @@ pub fn move_images_to_directory(
             continue;
         }
 
+        journal.intend(&[(
+            record.id,
+            &record.filepath,
+            Some(&destination_path.to_string_lossy()),
+        )])?;
         if let Err(error) = move_file_with_fallback(&source_path, &destination_path) {
             failed_paths.push(format!("{} ({})", record.filepath, error));
             continue;
@@ -618,6 +717,8 @@ pub fn move_images_to_directory(
         {
             Ok(true) => {
                 moved_ids.push(record.id);
+                moved_sources.push(record.filepath.clone());
+                journal.done(record.id);
                 moved_items.push(MovedImageRecord {
                     id: record.id,
                     filepath: new_filepath.clone(),
@@ -663,6 +764,25 @@ pub fn move_images_to_directory(
     }
 
     let moved_files = moved_ids.len();
+    if moved_files > 0 || !failed_paths.is_empty() {
+        log_activity(
+            &state.db,
+            ActivityKind::Move,
+            format!(
+                "Moved {} image(s) to {}",
+                moved_files,
+                destination_directory.display()
+            ),
+            serde_json::json!({
+                "destination": destination_directory.to_string_lossy(),
+                "requested": requested,
+                "moved": moved_files,
+                "failed": failed_paths.len(),
+                "skipped_locked": skipped_locked_ids.len(),
+            }),
+            &moved_sources,
+        );
+    }
     Ok(MoveImagesResult {
         requested,
         moved_files,
@@ -671,8 +791,145 @@ pub fn move_images_to_directory(
         moved_items,
         skipped_missing,
         skipped_same_directory,
+        skipped_locked: skipped_locked_ids.len(),
+        skipped_locked_ids,
+        failed: failed_paths.len(),
+        failed_paths,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarantineResult {
+    pub requested: usize,
+    pub quarantined: usize,
+    pub quarantined_ids: Vec<i64>,
+    pub skipped_locked: usize,
+    pub failed: usize,
+    pub failed_paths: Vec<String>,
+    pub quarantine_dir: String,
+}
+
+/// Moves corrupt images, all of them or just `ids`, with their sidecars
+/// into the app's quarantine folder and drops them from the library, so
+/// they stop failing on every scan but can still be recovered by hand.
+/// Locked images and files that are offline are left where they are.
+#[tauri::command]
+pub fn quarantine_corrupt_images(
+    ids: Option<Vec<i64>>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<QuarantineResult> {
+    let corrupt = state
+        .db
+        .get_corrupt_images()
+        .map_err(|error| AppError::from(error).context("Failed to list corrupt images"))?;
+    let wanted: Option<std::collections::HashSet<i64>> = ids.map(|ids| ids.into_iter().collect());
+    let corrupt_ids: Vec<i64> = corrupt
+        .iter()
+        .map(|failure| failure.image_id)
+        .filter(|id| wanted.as_ref().is_none_or(|wanted| wanted.contains(id)))
+        .collect();
+    let quarantine_dir = state.quarantine_dir.to_string_lossy().to_string();
+    let records = state
+        .db
+        .get_images_by_ids(&corrupt_ids)
+        .map_err(|error| AppError::from(error).context("Failed to resolve corrupt images"))?;
+    if records.is_empty() {
+        return Ok(QuarantineResult {
+            requested: corrupt_ids.len(),
+            quarantined: 0,
+            quarantined_ids: Vec::new(),
+            skipped_locked: 0,
+            failed: 0,
+            failed_paths: Vec::new(),
+            quarantine_dir,
+        });
+    }
+    std::fs::create_dir_all(&state.quarantine_dir).map_err(|error| {
+        AppError::Io(format!(
+            "Failed to create quarantine folder {}: {}",
+            state.quarantine_dir.display(),
+            error
+        ))
+    })?;
+
+    let mut journal = OperationJournal::begin(
+        &state.db,
+        OperationKind::Quarantine,
+        serde_json::json!({ "destination": quarantine_dir }),
+    )?;
+    let mut quarantined = Vec::<(i64, String)>::new();
+    let mut skipped_locked = 0usize;
+    let mut failed_paths = Vec::<String>::new();
+    for record in &records {
+        if record.is_locked {
+            skipped_locked += 1;
+            continue;
+        }
+        let source_path = PathBuf::from(&record.filepath);
+        if !source_path.exists() {
+            failed_paths.push(format!("{} (file is offline)", record.filepath));
+            continue;
+        }
+        let destination_path =
+            resolve_move_destination_path(&source_path, &state.quarantine_dir, record.id, &state)?;
+        journal.intend(&[(
+            record.id,
+            &record.filepath,
+            Some(&destination_path.to_string_lossy()),
+        )])?;
+        if let Err(error) = move_file_with_fallback(&source_path, &destination_path) {
+            failed_paths.push(format!("{} ({})", record.filepath, error));
+            continue;
+        }
+        move_known_sidecars(&source_path, &destination_path);
+        remove_thumbnail_cache_file(&source_path, &state.cache_dir, &state.thumbnail_index);
+        quarantined.push((record.id, record.filepath.clone()));
+        journal.done(record.id);
+    }
+
+    if let Ok(mut failed_thumbnail_sources) = state.failed_thumbnail_sources.write() {
+        for (_, filepath) in &quarantined {
+            failed_thumbnail_sources.remove(filepath);
+        }
+    }
+    let quarantined_ids: Vec<i64> = quarantined.iter().map(|(id, _)| *id).collect();
+    state
+        .db
+        .delete_images_by_ids(&quarantined_ids)
+        .map_err(|error| {
+            AppError::from(error).context("Failed to remove quarantined images from database")
+        })?;
+    log::info!(
+        "Moved {} corrupt image(s) to {}",
+        quarantined_ids.len(),
+        quarantine_dir
+    );
+    if !quarantined.is_empty() {
+        let quarantined_paths: Vec<String> = quarantined
+            .iter()
+            .map(|(_, filepath)| filepath.clone())
+            .collect();
+        log_activity(
+            &state.db,
+            ActivityKind::Quarantine,
+            format!("Quarantined {} corrupt image(s)", quarantined_paths.len()),
+            serde_json::json!({
+                "destination": quarantine_dir,
+                "failed": failed_paths.len(),
+                "skipped_locked": skipped_locked,
+            }),
+            &quarantined_paths,
+        );
+    }
+
+    Ok(QuarantineResult {
+        requested: corrupt_ids.len(),
+        quarantined: quarantined_ids.len(),
+        quarantined_ids,
+        skipped_locked,
         failed: failed_paths.len(),
         failed_paths,
+        quarantine_dir,
     })
 }
 
@@ -694,7 +951,7 @@ pub struct SetImagesLockedRequest {
 pub fn set_images_favorite(
     request: SetImagesFavoriteRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<usize, String> {
+) -> AppResult<usize> {
     if request.ids.is_empty() {
         return Ok(0);
     }
@@ -704,14 +961,14 @@ pub fn set_images_favorite(
     state
         .db
         .set_images_favorite(&unique_ids, request.is_favorite)
-        .map_err(|error| format!("Failed to update selected favorites: {}", error))
+        .map_err(|error| AppError::from(error).context("Failed to update selected favorites"))
 }
 
 #[tauri::command]
 pub fn set_images_locked(
     request: SetImagesLockedRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<usize, String> {
+) -> AppResult<usize> {
     if request.ids.is_empty() {
         return Ok(0);
     }
@@ -721,7 +978,33 @@ pub fn set_images_locked(
     state
         .db
         .set_images_locked(&unique_ids, request.is_locked)
-        .map_err(|error| format!("Failed to update selected lock state: {}", error))
+        .map_err(|error| AppError::from(error).context("Failed to update selected lock state"))
+}
+
+/// Favourites every image the filter matches in one statement, without
+/// paging the ids through the frontend.
+#[tauri::command]
+pub fn set_favorite_by_filter(
+    filter: ImageFilterRequest,
+    value: bool,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<usize> {
+    state
+        .db
+        .set_favorite_by_filter(filter.params(), value)
+        .map_err(|error| AppError::from(error).context("Failed to update filtered favorites"))
+}
+
+#[tauri::command]
+pub fn set_locked_by_filter(
+    filter: ImageFilterRequest,
+    value: bool,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<usize> {
+    state
+        .db
+        .set_locked_by_filter(filter.params(), value)
+        .map_err(|error| AppError::from(error).context("Failed to update filtered lock state"))
 }
 
 #[tauri::command]
@@ -729,11 +1012,11 @@ pub fn set_image_favorite(
     image_id: i64,
     is_favorite: bool,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     state
         .db
         .set_image_favorite(image_id, is_favorite)
-        .map_err(|error| format!("Failed to update favorite state: {}", error))
+        .map_err(|error| AppError::from(error).context("Failed to update favorite state"))
 }
 
 #[tauri::command]
@@ -741,9 +1024,9 @@ pub fn set_image_locked(
     image_id: i64,
     is_locked: bool,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     state
         .db
         .set_image_locked(image_id, is_locked)
-        .map_err(|error| format!("Failed to update lock state: {}", error))
+        .map_err(|error| AppError::from(error).context("Failed to update lock state"))
 }
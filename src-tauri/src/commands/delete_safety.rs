@@ -0,0 +1,235 @@
+// ────────────────────────── Deletion safety ──────────────────────────
+
+/// Deletions this large need confirming even when nothing in them is
+/// flagged.
+const LARGE_DELETE_THRESHOLD: usize = 100;
+/// How long a token from [`preview_delete_images`] can be redeemed.
+const DELETE_CONFIRMATION_TTL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// What a `delete_images` call with the same ids and `force` would put at
+/// risk.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeletionSafetyReport {
+    pub requested: usize,
+    pub favorites: usize,
+    pub locked: usize,
+    /// Favorites and locked images that will be skipped because `force` is
+    /// off. The counts below leave them out.
+    pub skipped_protected: usize,
+    /// Images that belong to at least one collection.
+    pub in_collections: usize,
+    pub collections: Vec<CollectionMembership>,
+    /// Images that other indexed images were made from; see
+    /// `Database::lineage_parents`.
+    pub lineage_parent_ids: Vec<i64>,
+    pub large_selection: bool,
+    /// Pass to `delete_images` as `confirmation_token`. Set only when the
+    /// deletion is risky and `delete_images` would refuse it without one.
+    pub confirmation_token: Option<String>,
+}
+
+impl DeletionSafetyReport {
+    fn is_risky(&self, force: bool) -> bool {
+        (force && self.favorites + self.locked > 0)
+            || self.in_collections > 0
+            || !self.lineage_parent_ids.is_empty()
+            || self.large_selection
+    }
+
+    /// Short list of what makes the deletion risky, for error messages.
+    fn risks(&self, force: bool) -> String {
+        let mut risks = Vec::new();
+        if force && self.favorites > 0 {
+            risks.push(format!("{} favorite(s)", self.favorites));
+        }
+        if force && self.locked > 0 {
+            risks.push(format!("{} locked", self.locked));
+        }
+        if self.in_collections > 0 {
+            risks.push(format!("{} in collections", self.in_collections));
+        }
+        if !self.lineage_parent_ids.is_empty() {
+            risks.push(format!(
+                "{} with derived images",
+                self.lineage_parent_ids.len()
+            ));
+        }
+        if self.large_selection {
+            risks.push(format!("{} images in one go", self.requested));
+        }
+        risks.join(", ")
+    }
+}
+
+/// Builds the report for `records`, without a token.
+fn deletion_safety_report(
+    db: &Database,
+    records: &[ImageRecord],
+    requested: usize,
+    force: bool,
+) -> AppResult<DeletionSafetyReport> {
+    let is_protected = |record: &ImageRecord| record.is_locked || record.is_favorite;
+    let deletable: Vec<i64> = records
+        .iter()
+        .filter(|record| force || !is_protected(record))
+        .map(|record| record.id)
+        .collect();
+    let context = "Failed to check the images for deletion";
+    Ok(DeletionSafetyReport {
+        requested,
+        favorites: records.iter().filter(|record| record.is_favorite).count(),
+        locked: records.iter().filter(|record| record.is_locked).count(),
+        skipped_protected: records.len() - deletable.len(),
+        in_collections: db
+            .count_images_in_collections(&deletable)
+            .map_err(|error| AppError::from(error).context(context))?,
+        collections: db
+            .collections_holding(&deletable)
+            .map_err(|error| AppError::from(error).context(context))?,
+        lineage_parent_ids: db
+            .lineage_parents(&deletable)
+            .map_err(|error| AppError::from(error).context(context))?,
+        large_selection: deletable.len() >= LARGE_DELETE_THRESHOLD,
+        confirmation_token: None,
+    })
+}
+
+struct DeleteConfirmation {
+    ids: std::collections::HashSet<i64>,
+    force: bool,
+    issued_at: std::time::Instant,
+}
+
+/// Confirmation tokens handed out by [`preview_delete_images`]. Each is
+/// good for one `delete_images` call over the previewed ids or a subset of
+/// them, so the UI can still drop images it skips.
+#[derive(Default)]
+pub struct DeleteConfirmations {
+    pending: std::sync::Mutex<std::collections::HashMap<String, DeleteConfirmation>>,
+}
+
+impl DeleteConfirmations {
+    fn issue(&self, ids: &[i64], force: bool) -> String {
+        let now = std::time::Instant::now();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(nanos.to_le_bytes());
+        hasher.update(std::process::id().to_le_bytes());
+        for id in ids {
+            hasher.update(id.to_le_bytes());
+        }
+        let mut token = format!("{:x}", hasher.finalize());
+        token.truncate(32);
+
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.retain(|_, confirmation| {
+                now.duration_since(confirmation.issued_at) < DELETE_CONFIRMATION_TTL
+            });
+            pending.insert(
+                token.clone(),
+                DeleteConfirmation {
+                    ids: ids.iter().copied().collect(),
+                    force,
+                    issued_at: now,
+                },
+            );
+        }
+        token
+    }
+
+    /// Uses up `token` if it covers `ids` and `force` and has not expired.
+    fn redeem(&self, token: &str, ids: &[i64], force: bool) -> bool {
+        self.redeem_at(token, ids, force, std::time::Instant::now())
+    }
+
+    fn redeem_at(&self, token: &str, ids: &[i64], force: bool, now: std::time::Instant) -> bool {
+        let Ok(mut pending) = self.pending.lock() else {
+            return false;
+        };
+        let Some(confirmation) = pending.get(token) else {
+            return false;
+        };
+        let valid = now.duration_since(confirmation.issued_at) < DELETE_CONFIRMATION_TTL
+            && (confirmation.force || !force)
+            && ids.iter().all(|id| confirmation.ids.contains(id));
+        if valid {
+            pending.remove(token);
+        }
+        valid
+    }
+}
+
+/// Reports what deleting `ids` would remove beyond the files themselves:
+/// favorites, locked images, collection members and images others were
+/// derived from. Risky deletions come with the token `delete_images` needs
+/// before it will run them.
+#[tauri::command]
+pub fn preview_delete_images(
+    ids: Vec<i64>,
+    force: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<DeletionSafetyReport> {
+    let force = force.unwrap_or(false);
+    let mut unique_ids = ids;
+    unique_ids.sort_unstable();
+    unique_ids.dedup();
+    let records = state
+        .db
+        .get_images_by_ids(&unique_ids)
+        .map_err(|error| AppError::from(error).context("Failed to resolve images for deletion"))?;
+    let mut report = deletion_safety_report(&state.db, &records, unique_ids.len(), force)?;
+    if report.is_risky(force) {
+        report.confirmation_token = Some(state.delete_confirmations.issue(&unique_ids, force));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod delete_safety_tests {
+    use super::*;
+
+    #[test]
+    fn confirmation_tokens_cover_previewed_ids_once() {
+        let confirmations = DeleteConfirmations::default();
+        let token = confirmations.issue(&[1, 2, 3], false);
+
+        assert!(!confirmations.redeem(&token, &[1, 4], false));
+        assert!(!confirmations.redeem(&token, &[1], true));
+        assert!(!confirmations.redeem("unknown", &[1], false));
+        assert!(confirmations.redeem(&token, &[1, 3], false));
+        assert!(!confirmations.redeem(&token, &[1, 3], false));
+
+        let forced = confirmations.issue(&[5], true);
+        let later = std::time::Instant::now() + DELETE_CONFIRMATION_TTL;
+        assert!(!confirmations.redeem_at(&forced, &[5], false, later));
+        assert!(confirmations.redeem(&forced, &[5], false));
+    }
+
+    #[test]
+    fn risky_deletions_are_the_flagged_ones() {
+        let report = DeletionSafetyReport {
+            requested: 2,
+            favorites: 1,
+            locked: 0,
+            skipped_protected: 1,
+            in_collections: 0,
+            collections: Vec::new(),
+            lineage_parent_ids: Vec::new(),
+            large_selection: false,
+            confirmation_token: None,
+        };
+        assert!(!report.is_risky(false));
+        assert!(report.is_risky(true));
+        assert_eq!(report.risks(true), "1 favorite(s)");
+
+        let parent = DeletionSafetyReport {
+            lineage_parent_ids: vec![7],
+            ..report
+        };
+        assert!(parent.is_risky(false));
+        assert_eq!(parent.risks(false), "1 with derived images");
+    }
+}
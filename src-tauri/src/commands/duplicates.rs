@@ -0,0 +1,101 @@
+// ────────────────────────── Duplicates ──────────────────────────
+
+/// Groups of images that are copies of each other, each with the copy to
+/// keep, for a "keep one, delete the rest" pass. Matching is by quick hash;
+/// `verify` also reads every candidate in full and splits groups whose
+/// bytes differ, which is slower but rules out collisions. Copies that
+/// can't be read, such as ones on an unplugged drive, drop out of verified
+/// groups.
+#[tauri::command]
+pub async fn find_duplicate_images(
+    verify: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<DuplicateGroup>> {
+    let db = state.db.clone();
+    let verify = verify.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || -> AppResult<Vec<DuplicateGroup>> {
+        let groups = db.find_quick_hash_duplicates()?;
+        if !verify {
+            return Ok(groups);
+        }
+        Ok(groups
+            .into_iter()
+            .flat_map(|group| {
+                split_by_content(group, |filepath| {
+                    scanner::compute_full_hash(Path::new(filepath))
+                })
+            })
+            .collect())
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+/// Splits a quick-hash group into groups whose members hash the same with
+/// `full_hash`, keeping the group's chosen copy where it lands.
+fn split_by_content(
+    group: DuplicateGroup,
+    mut full_hash: impl FnMut(&str) -> Option<String>,
+) -> Vec<DuplicateGroup> {
+    let mut verified: Vec<DuplicateGroup> = Vec::new();
+    for (id, filepath) in group.ids.into_iter().zip(group.filepaths) {
+        let Some(sha256) = full_hash(&filepath) else {
+            continue;
+        };
+        match verified
+            .iter_mut()
+            .find(|candidate| candidate.sha256.as_deref() == Some(sha256.as_str()))
+        {
+            Some(candidate) => {
+                candidate.ids.push(id);
+                candidate.filepaths.push(filepath);
+            }
+            None => verified.push(DuplicateGroup {
+                quick_hash: group.quick_hash.clone(),
+                sha256: Some(sha256),
+                ids: vec![id],
+                filepaths: vec![filepath],
+                keep_id: id,
+            }),
+        }
+    }
+    verified.retain(|candidate| candidate.ids.len() > 1);
+    for candidate in &mut verified {
+        if candidate.ids.contains(&group.keep_id) {
+            candidate.keep_id = group.keep_id;
+        }
+    }
+    verified
+}
+
+#[cfg(test)]
+mod duplicates_tests {
+    use super::*;
+
+    #[test]
+    fn verification_splits_collisions_and_keeps_the_chosen_copy() {
+        let group = DuplicateGroup {
+            quick_hash: "abc".to_string(),
+            sha256: None,
+            ids: vec![1, 2, 3, 4, 5],
+            filepaths: ["a", "b", "c", "d", "offline"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            keep_id: 3,
+        };
+        let contents = |filepath: &str| match filepath {
+            "a" | "c" => Some("same".to_string()),
+            "b" => Some("different".to_string()),
+            "d" => Some("other".to_string()),
+            _ => None,
+        };
+
+        let verified = split_by_content(group, contents);
+        assert_eq!(verified.len(), 1);
+        assert_eq!(verified[0].ids, [1, 3]);
+        assert_eq!(verified[0].filepaths, ["a", "c"]);
+        assert_eq!(verified[0].sha256.as_deref(), Some("same"));
+        assert_eq!(verified[0].keep_id, 3);
+    }
+}
@@ -1,62 +1,228 @@
 // ────────────────────────── Export ──────────────────────────
 
+#[derive(Clone, Serialize)]
+struct ExportProgress {
+    task_id: u64,
+    current: usize,
+    total: usize,
+}
+
+/// Exports either the explicit `ids` or, when `filter` is given, every image
+/// it matches.
+///
+/// Rows are streamed to the file as a cancellable background task that emits
+/// `export-progress` to the calling window.
 #[tauri::command]
-pub fn export_images(
-    ids: Vec<i64>,
+pub async fn export_images(
+    ids: Option<Vec<i64>>,
+    filter: Option<ImageFilterRequest>,
     format: String,
     output_path: String,
-    state: tauri::State<AppState>,
-) -> Result<ExportResult, String> {
-    let records = state
-        .db
-        .get_images_by_ids(&ids)
-        .map_err(|e| e.to_string())?;
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ExportResult> {
+    let ids = resolve_export_ids(&state.db, ids, filter)?;
+    let db = state.db.clone();
+    let task = state.tasks.start(
+        TaskKind::Export,
+        format!("Metadata export ({} images)", ids.len()),
+    );
+    let events = EventSink::for_window(&app, &window);
+    let path = PathBuf::from(&output_path);
+    let format_label = format.clone();
+    let exported_count = tauri::async_runtime::spawn_blocking(move || {
+        let task_id = task.id();
+        let result = write_metadata_export(&db, &ids, &format, &path, |current, total| {
+            task.set_progress(current, total);
+            let _ = events.emit(
+                "export-progress",
+                ExportProgress {
+                    task_id,
+                    current,
+                    total,
+                },
+            );
+            task.checkpoint()
+        });
+        if let Err(error) = &result {
+            if !matches!(error, AppError::Cancelled(_)) {
+                task.fail(error.to_string());
+            }
+        }
+        result
+    })
+    .await
+    .map_err(|error| error.to_string())??;
+    log_activity(
+        &state.db,
+        ActivityKind::Export,
+        format!(
+            "Exported metadata of {} image(s) to {}",
+            exported_count, output_path
+        ),
+        serde_json::json!({ "format": format_label, "output_path": output_path }),
+        &[],
+    );
+    Ok(ExportResult {
+        exported_count,
+        output_path,
+    })
+}
+
+/// Ids per lookup when loading export rows; stays under SQLite's bound-parameter limit.
+const EXPORT_LOOKUP_CHUNK: usize = 900;
+
+/// Large results are exported by filter so their ids never cross IPC; the
+/// filter wins when both are sent.
+fn resolve_export_ids(
+    db: &crate::database::Database,
+    ids: Option<Vec<i64>>,
+    filter: Option<ImageFilterRequest>,
+) -> AppResult<Vec<i64>> {
+    match (filter, ids) {
+        (Some(filter), _) => Ok(db.get_ids_by_filter(filter.params())?),
+        (None, Some(ids)) => Ok(ids),
+        (None, None) => Err(AppError::InvalidInput(
+            "Export needs either ids or a filter".to_string(),
+        )),
+    }
+}
+
+fn load_export_records(db: &crate::database::Database, ids: &[i64]) -> AppResult<Vec<ImageRecord>> {
+    let mut records = Vec::with_capacity(ids.len());
+    for chunk in ids.chunks(EXPORT_LOOKUP_CHUNK) {
+        records.extend(db.get_images_by_ids(chunk)?);
+    }
     if records.is_empty() {
-        return Err("No images found for the requested ids".to_string());
+        return Err(AppError::NotFound(
+            "No images found for the requested ids".to_string(),
+        ));
     }
+    Ok(records)
+}
 
-    let mut export_records = Vec::new();
-    for record in &records {
-        let tags = state
-            .db
-            .get_tags_for_image(record.id)
-            .map_err(|e| e.to_string())?;
-        export_records.push(ExportImage {
-            id: record.id,
-            filepath: record.filepath.clone(),
-            filename: record.filename.clone(),
-            directory: record.directory.clone(),
-            prompt: record.prompt.clone(),
-            negative_prompt: record.negative_prompt.clone(),
-            steps: record.steps.clone(),
-            sampler: record.sampler.clone(),
-            cfg_scale: record.cfg_scale.clone(),
-            seed: record.seed.clone(),
-            width: record.width,
-            height: record.height,
-            model_hash: record.model_hash.clone(),
-            model_name: record.model_name.clone(),
-            raw_metadata: record.raw_metadata.clone(),
-            tags,
-        });
+/// Writes a JSON or CSV metadata export for the given ids and returns the row count.
+///
+/// Rows are loaded a chunk at a time and written straight to the file, so
+/// memory stays flat however large the export is. `on_progress` is called
+/// before each chunk with the ids processed so far; returning false cancels
+/// the export. The file is written beside `output_path` and renamed into
+/// place once complete, so a cancelled or failed export leaves no partial
+/// file behind and an earlier export at that path untouched.
+pub(crate) fn write_metadata_export(
+    db: &crate::database::Database,
+    ids: &[i64],
+    format: &str,
+    output_path: &Path,
+    mut on_progress: impl FnMut(usize, usize) -> bool,
+) -> AppResult<usize> {
+    let normalized_format = format.trim().to_ascii_lowercase();
+    if normalized_format != "json" && normalized_format != "csv" {
+        return Err(AppError::InvalidInput(
+            "Unsupported export format. Use 'json' or 'csv'.".to_string(),
+        ));
     }
 
-    let content = match format.trim().to_ascii_lowercase().as_str() {
-        "json" => serde_json::to_string_pretty(&export_records).map_err(|e| e.to_string())?,
-        "csv" => build_csv_export(&export_records).map_err(|e| e.to_string())?,
-        _ => return Err("Unsupported export format. Use 'json' or 'csv'.".to_string()),
+    let mut temp_name = output_path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".part");
+    let temp_path = output_path.with_file_name(temp_name);
+    let file = std::fs::File::create(&temp_path)
+        .map_err(|e| AppError::from(e).context("Failed to create output file"))?;
+    let mut out = std::io::BufWriter::with_capacity(256 * 1024, file);
+    let result = if normalized_format == "json" {
+        stream_json_export(db, ids, &mut out, &mut on_progress)
+    } else {
+        stream_csv_export(db, ids, &mut out, &mut on_progress)
     };
+    let result = result.and_then(|count| {
+        use std::io::Write;
+        out.flush()?;
+        if count == 0 {
+            return Err(AppError::NotFound(
+                "No images found for the requested ids".to_string(),
+            ));
+        }
+        Ok(count)
+    });
+    drop(out);
+    let result = result.and_then(|count| {
+        std::fs::rename(&temp_path, output_path)
+            .map_err(|e| AppError::from(e).context("Failed to save output file"))?;
+        Ok(count)
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
 
-    std::fs::write(&output_path, content).map_err(|e| e.to_string())?;
+/// Visits the export rows in id order, a lookup chunk at a time.
+fn for_each_export_image(
+    db: &crate::database::Database,
+    ids: &[i64],
+    on_progress: &mut dyn FnMut(usize, usize) -> bool,
+    mut visit: impl FnMut(ExportImage) -> AppResult<()>,
+) -> AppResult<usize> {
+    let mut processed = 0usize;
+    let mut written = 0usize;
+    for chunk in ids.chunks(EXPORT_LOOKUP_CHUNK) {
+        if !on_progress(processed, ids.len()) {
+            return Err(AppError::Cancelled("Export cancelled".to_string()));
+        }
+        for record in db.get_images_by_ids(chunk)? {
+            let tags = db.get_tags_for_image(record.id)?;
+            visit(ExportImage {
+                id: record.id,
+                filepath: record.filepath,
+                filename: record.filename,
+                directory: record.directory,
+                prompt: record.prompt,
+                negative_prompt: record.negative_prompt,
+                steps: record.steps,
+                sampler: record.sampler,
+                cfg_scale: record.cfg_scale,
+                seed: record.seed,
+                width: record.width,
+                height: record.height,
+                model_hash: record.model_hash,
+                model_name: record.model_name,
+                raw_metadata: record.raw_metadata,
+                tags,
+            })?;
+            written += 1;
+        }
+        processed += chunk.len();
+    }
+    on_progress(processed, ids.len());
+    Ok(written)
+}
 
-    Ok(ExportResult {
-        exported_count: export_records.len(),
-        output_path,
-    })
+/// Same output as pretty-printing the whole array, one element at a time.
+fn stream_json_export(
+    db: &crate::database::Database,
+    ids: &[i64],
+    out: &mut impl std::io::Write,
+    on_progress: &mut dyn FnMut(usize, usize) -> bool,
+) -> AppResult<usize> {
+    use serde::ser::{SerializeSeq, Serializer as _};
+
+    let mut serializer = serde_json::Serializer::pretty(out);
+    let mut seq = serializer.serialize_seq(None)?;
+    let count = for_each_export_image(db, ids, on_progress, |image| {
+        Ok(seq.serialize_element(&image)?)
+    })?;
+    seq.end()?;
+    Ok(count)
 }
 
-fn build_csv_export(records: &[ExportImage]) -> Result<String, csv::Error> {
-    let mut wtr = csv::Writer::from_writer(Vec::new());
+fn stream_csv_export(
+    db: &crate::database::Database,
+    ids: &[i64],
+    out: &mut impl std::io::Write,
+    on_progress: &mut dyn FnMut(usize, usize) -> bool,
+) -> AppResult<usize> {
+    let mut wtr = csv::Writer::from_writer(out);
 
     wtr.write_record([
         "id",
@@ -77,7 +243,7 @@ fn build_csv_export(records: &[ExportImage]) -> Result<String, csv::Error> {
         "tags",
     ])?;
 
-    for record in records {
+    let count = for_each_export_image(db, ids, on_progress, |record| {
         wtr.write_record([
             &record.id.to_string(),
             &record.filepath,
@@ -96,10 +262,10 @@ fn build_csv_export(records: &[ExportImage]) -> Result<String, csv::Error> {
             &record.raw_metadata,
             &record.tags.join("|"),
         ])?;
-    }
-
-    let bytes = wtr.into_inner().map_err(|e| e.into_error())?;
-    Ok(String::from_utf8(bytes).unwrap_or_default())
+        Ok(())
+    })?;
+    wtr.flush()?;
+    Ok(count)
 }
 
 // ────────────────────────── Export as Files (ZIP) ──────────────────────────
@@ -111,6 +277,116 @@ pub struct FileExportResult {
     pub total_bytes: u64,
 }
 
+/// Optional reshaping of converted exports, e.g. a 1:1 center crop for a
+/// dataset or a 2048px long edge for web uploads. The crop happens first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportTransform {
+    /// Longest edge in pixels; smaller images are left at their size.
+    pub max_edge: Option<u32>,
+    /// Aspect ratio to center crop to, as `W:H`.
+    pub crop_aspect: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileExportOptions {
+    pub transform: Option<ExportTransform>,
+    /// Names for the files inside the ZIP, e.g. `{seed}_{model}_{index}`;
+    /// see `EXPORT_FILENAME_TOKENS`. Names that come out the same get `_1`,
+    /// `_2`, ... appended. The original stem is kept when unset.
+    pub name_template: Option<String>,
+}
+
+/// A validated [`ExportTransform`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ParsedExportTransform {
+    max_edge: Option<u32>,
+    crop_aspect: Option<(u32, u32)>,
+}
+
+impl ExportTransform {
+    /// `None` when there is nothing to do.
+    fn parse(&self) -> AppResult<Option<ParsedExportTransform>> {
+        let max_edge = match self.max_edge {
+            Some(0) => {
+                return Err(AppError::InvalidInput(
+                    "Export long edge must be at least 1 pixel".to_string(),
+                ))
+            }
+            edge => edge,
+        };
+        let crop_aspect = match self.crop_aspect.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(aspect) => {
+                let parsed = aspect.split_once(':').and_then(|(width, height)| {
+                    let width = width.trim().parse::<u32>().ok()?;
+                    let height = height.trim().parse::<u32>().ok()?;
+                    (width > 0 && height > 0).then_some((width, height))
+                });
+                Some(parsed.ok_or_else(|| {
+                    AppError::InvalidInput(format!(
+                        "Crop aspect ratio must look like 1:1 or 3:2, got '{}'",
+                        aspect
+                    ))
+                })?)
+            }
+        };
+        if max_edge.is_none() && crop_aspect.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(ParsedExportTransform {
+            max_edge,
+            crop_aspect,
+        }))
+    }
+}
+
+impl ParsedExportTransform {
+    fn apply(&self, mut image: image::DynamicImage) -> image::DynamicImage {
+        if let Some((aspect_width, aspect_height)) = self.crop_aspect {
+            let (width, height) = (image.width() as u64, image.height() as u64);
+            let (aspect_width, aspect_height) = (aspect_width as u64, aspect_height as u64);
+            let (crop_width, crop_height) = if width * aspect_height > height * aspect_width {
+                ((height * aspect_width / aspect_height).max(1), height)
+            } else {
+                (width, (width * aspect_height / aspect_width).max(1))
+            };
+            if (crop_width, crop_height) != (width, height) {
+                image = image.crop_imm(
+                    ((width - crop_width) / 2) as u32,
+                    ((height - crop_height) / 2) as u32,
+                    crop_width as u32,
+                    crop_height as u32,
+                );
+            }
+        }
+        if let Some(max_edge) = self.max_edge {
+            if image.width().max(image.height()) > max_edge {
+                image = image.resize(max_edge, max_edge, image::imageops::FilterType::Lanczos3);
+            }
+        }
+        image
+    }
+}
+
+/// Resize and crop only apply to converted files; `original` copies bytes.
+fn parse_export_transform(
+    transform: Option<&ExportTransform>,
+    fmt: &str,
+) -> AppResult<Option<ParsedExportTransform>> {
+    let parsed = match transform {
+        Some(transform) => transform.parse()?,
+        None => None,
+    };
+    if parsed.is_some() && fmt == "original" {
+        return Err(AppError::InvalidInput(
+            "Resizing or cropping needs a conversion format, not 'original'".to_string(),
+        ));
+    }
+    Ok(parsed)
+}
+
 fn encode_dynamic_image_as_webp(image: &image::DynamicImage, quality: u8) -> Vec<u8> {
     if image.color().has_alpha() {
         let rgba = image.to_rgba8();
@@ -125,36 +401,123 @@ fn encode_dynamic_image_as_webp(image: &image::DynamicImage, quality: u8) -> Vec
         .to_vec()
 }
 
-fn encode_image_as_webp(source: &Path, quality: u8) -> Result<Vec<u8>, String> {
-    let image = image_decode::open_image(source)
-        .map_err(|error| format!("Failed to open {}: {}", source.display(), error))?;
-    Ok(encode_dynamic_image_as_webp(&image, quality))
-}
-
-fn encode_image_as_jxl(source: &Path) -> Result<Vec<u8>, String> {
+/// Lossless; 16-bit sources stay 16-bit.
+fn encode_image_as_jxl(image: &image::DynamicImage) -> AppResult<Vec<u8>> {
     use zune_core::bit_depth::BitDepth;
     use zune_core::colorspace::ColorSpace;
     use zune_core::options::EncoderOptions;
     use zune_jpegxl::JxlSimpleEncoder;
 
-    let image = image_decode::open_image(source)
-        .map_err(|error| format!("Failed to open {}: {}", source.display(), error))?;
-    let rgba = image.to_rgba8();
+    let (pixels, depth) = if matches!(
+        image.color(),
+        image::ColorType::L16
+            | image::ColorType::La16
+            | image::ColorType::Rgb16
+            | image::ColorType::Rgba16
+    ) {
+        // The encoder takes 16-bit samples as native-endian bytes.
+        let rgba = image.to_rgba16();
+        let bytes: Vec<u8> = rgba
+            .as_raw()
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect();
+        (bytes, BitDepth::Sixteen)
+    } else {
+        (image.to_rgba8().into_raw(), BitDepth::Eight)
+    };
     let options = EncoderOptions::new(
-        rgba.width() as usize,
-        rgba.height() as usize,
+        image.width() as usize,
+        image.height() as usize,
         ColorSpace::RGBA,
-        BitDepth::Eight,
+        depth,
     );
-    let encoder = JxlSimpleEncoder::new(rgba.as_raw(), options);
+    let encoder = JxlSimpleEncoder::new(&pixels, options);
     let mut encoded = Vec::new();
     encoder
         .encode(&mut encoded)
-        .map_err(|error| format!("JPEG XL encode error: {}", error))?;
+        .map_err(|error| AppError::Image(format!("JPEG XL encode error: {}", error)))?;
+    Ok(encoded)
+}
+
+/// Re-encodes `source` as `format` (`png`, `jpeg`/`jpg`, `webp` or `jxl`),
+/// cropped and resized first when a `transform` is given. Non-empty
+/// `parameters` are embedded in the result so the converted file still
+/// carries its generation settings.
+fn convert_for_export(
+    source: &Path,
+    format: &str,
+    quality: u8,
+    parameters: &str,
+    transform: Option<&ParsedExportTransform>,
+) -> AppResult<Vec<u8>> {
+    let mut image = image_decode::open_image(source).map_err(|error| {
+        AppError::from(error).context(format!("Failed to open {}", source.display()))
+    })?;
+    if let Some(transform) = transform {
+        image = transform.apply(image);
+    }
+    // None of the targets store linear float; 16-bit PNG and JPEG XL do
+    // keep 16-bit integer sources at full depth.
+    if image_decode::is_float_image(&image) {
+        image = image_decode::to_display_8bit(image);
+    }
+    let parameters = Some(parameters.trim()).filter(|text| !text.is_empty());
+
+    let encoded = match format {
+        "png" => {
+            let mut buf = Vec::new();
+            image
+                .write_to(&mut std::io::Cursor::new(&mut buf), image::ImageFormat::Png)
+                .map_err(|e| AppError::from(e).context("PNG encode error"))?;
+            match parameters {
+                Some(text) => embed_metadata::png_with_parameters(&buf, text),
+                None => buf,
+            }
+        }
+        "jpeg" | "jpg" => {
+            let rgb = image_decode::to_display_8bit(image).to_rgb8();
+            let mut buf = Vec::new();
+            let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buf, quality);
+            encoder
+                .encode(
+                    rgb.as_raw(),
+                    rgb.width(),
+                    rgb.height(),
+                    image::ExtendedColorType::Rgb8,
+                )
+                .map_err(|e| AppError::from(e).context("JPEG encode error"))?;
+            match parameters {
+                Some(text) => embed_metadata::jpeg_with_parameters(&buf, text),
+                None => buf,
+            }
+        }
+        "webp" => {
+            let (width, height) = (image.width(), image.height());
+            let buf = encode_dynamic_image_as_webp(&image_decode::to_display_8bit(image), quality);
+            match parameters {
+                Some(text) => embed_metadata::webp_with_parameters(&buf, text, width, height),
+                None => buf,
+            }
+        }
+        "jxl" => {
+            let buf = encode_image_as_jxl(&image)?;
+            match parameters {
+                Some(text) => embed_metadata::jxl_with_parameters(&buf, text),
+                None => buf,
+            }
+        }
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Unsupported format '{}'. Use 'original', 'png', 'jpeg', 'webp', or 'jxl'.",
+                format
+            )));
+        }
+    };
     Ok(encoded)
 }
 
-/// Exports selected images as a ZIP file.
+/// Exports selected images, or every image `filter` matches, as a ZIP file.
 ///
 /// Supported `format` values:
 /// - `"original"` -- copies the source files as-is into the ZIP
@@ -162,52 +525,148 @@ fn encode_image_as_jxl(source: &Path) -> Result<Vec<u8>, String> {
 /// - `"jpeg"` -- converts each image to JPEG at the given `quality` (1-100)
 /// - `"webp"` -- converts each image to lossy WebP at the given `quality` (1-100)
 /// - `"jxl"` -- converts each image to JPEG XL (lossless)
+///
+/// Converted files keep the source's generation parameters (see
+/// [`convert_for_export`]); `options` can crop, resize and rename them.
 #[tauri::command]
 pub fn export_images_as_files(
-    ids: Vec<i64>,
+    ids: Option<Vec<i64>>,
+    filter: Option<ImageFilterRequest>,
     format: String,
     quality: Option<u8>,
+    options: Option<FileExportOptions>,
     output_path: String,
     state: tauri::State<AppState>,
-) -> Result<FileExportResult, String> {
-    use std::io::{BufWriter, Write};
-
-    let records = state
-        .db
-        .get_images_by_ids(&ids)
-        .map_err(|e| e.to_string())?;
-    if records.is_empty() {
-        return Err("No images found for the requested ids".to_string());
-    }
-
+) -> AppResult<FileExportResult> {
+    let options = options.unwrap_or_default();
     let fmt = format.trim().to_ascii_lowercase();
     let quality = quality.unwrap_or(85).clamp(1, 100);
+    let (transform, name_template) = parse_file_export_options(&options, &fmt)?;
 
-    let file = std::fs::File::create(&output_path)
-        .map_err(|e| format!("Failed to create output file: {}", e))?;
-    let writer = BufWriter::with_capacity(256 * 1024, file);
-    let mut zip = zip::ZipWriter::new(writer);
-    let zip_options = zip::write::SimpleFileOptions::default()
-        .compression_method(zip::CompressionMethod::Deflated)
-        .compression_level(Some(6));
+    let ids = resolve_export_ids(&state.db, ids, filter)?;
+    let records = load_export_records(&state.db, &ids)?;
+    let storage_profile = state
+        .storage_profile
+        .read()
+        .map(|profile| *profile)
+        .unwrap_or(StorageProfile::Hdd);
 
-    let mut exported = 0usize;
-    let mut seen_names = std::collections::HashSet::<String>::new();
+    let journal = OperationJournal::begin(
+        &state.db,
+        OperationKind::Convert,
+        serde_json::json!({
+            "format": fmt,
+            "quality": quality,
+            "options": options,
+            "output_path": output_path,
+        }),
+    )?;
+    let steps: Vec<(i64, &str, Option<&str>)> = records
+        .iter()
+        .map(|record| (record.id, record.filepath.as_str(), None))
+        .collect();
+    journal.intend(&steps)?;
+    let exported = write_files_zip(
+        &records,
+        &fmt,
+        quality,
+        transform,
+        name_template.as_ref(),
+        Path::new(&output_path),
+        &scan_pool(storage_profile),
+    )?;
 
-    for record in &records {
+    let total_bytes = std::fs::metadata(&output_path)
+        .map(|m| m.len())
+        .unwrap_or(0);
+    let source_paths: Vec<String> = records
+        .iter()
+        .map(|record| record.filepath.clone())
+        .collect();
+    log_activity(
+        &state.db,
+        ActivityKind::Export,
+        format!(
+            "Exported {} image(s) as {} to {}",
+            exported, fmt, output_path
+        ),
+        serde_json::json!({
+            "format": fmt,
+            "output_path": output_path,
+            "total_bytes": total_bytes,
+        }),
+        &source_paths,
+    );
+
+    Ok(FileExportResult {
+        exported_count: exported,
+        output_path,
+        total_bytes,
+    })
+}
+
+/// Validates the crop, resize and naming options of a file export.
+fn parse_file_export_options(
+    options: &FileExportOptions,
+    fmt: &str,
+) -> AppResult<(Option<ParsedExportTransform>, Option<FilenameTemplate>)> {
+    let transform = parse_export_transform(options.transform.as_ref(), fmt)?;
+    let name_template = options
+        .name_template
+        .as_deref()
+        .filter(|template| !template.trim().is_empty())
+        .map(|template| FilenameTemplate::parse(template, EXPORT_FILENAME_TOKENS))
+        .transpose()?;
+    Ok((transform, name_template))
+}
+
+const EXPORT_FILENAME_TOKENS: &[&str] = &[
+    "stem", "id", "index", "seed", "model", "sampler", "steps", "width", "height", "folder",
+];
+
+/// Files encoded together before they are handed to the ZIP writer. With
+/// one chunk being written and one queued, memory stays at a few dozen
+/// encoded images however large the export is.
+const EXPORT_ENCODE_CHUNK: usize = 32;
+
+/// One ZIP entry: where it comes from and the name it gets.
+struct FileExportEntry<'a> {
+    record: &'a ImageRecord,
+    zip_name: String,
+}
+
+/// Names each existing source in the ZIP, numbering repeated names in
+/// record order so the result doesn't depend on encode timing. Without a
+/// template the original stem is kept.
+fn plan_file_export<'a>(
+    records: &'a [ImageRecord],
+    fmt: &str,
+    template: Option<&FilenameTemplate>,
+) -> Vec<FileExportEntry<'a>> {
+    let index_width = records.len().to_string().len();
+    let mut seen_names = std::collections::HashSet::<String>::new();
+    let mut entries = Vec::with_capacity(records.len());
+    for record in records {
         let source = Path::new(&record.filepath);
         if !source.exists() {
             log::warn!("Export: source file missing, skipping: {}", record.filepath);
             continue;
         }
 
-        let base_name = record.filename.clone();
-        let stem = match base_name.rsplit_once('.') {
-            Some((s, _)) => s.to_string(),
-            None => base_name.clone(),
+        let original_stem = match record.filename.rsplit_once('.') {
+            Some((s, _)) => s,
+            None => record.filename.as_str(),
         };
-
-        let target_ext = match fmt.as_str() {
+        let stem = match template {
+            Some(template) => {
+                let index = entries.len() + 1;
+                template.render(|token| {
+                    export_name_token(record, original_stem, token, index, index_width)
+                })
+            }
+            None => original_stem.to_string(),
+        };
+        let target_ext = match fmt {
             "png" => "png",
             "jpeg" | "jpg" => "jpg",
             "webp" => "webp",
@@ -222,88 +681,228 @@ pub fn export_images_as_files(
             counter += 1;
         }
         seen_names.insert(zip_name.clone());
+        entries.push(FileExportEntry { record, zip_name });
+    }
+    entries
+}
 
-        match fmt.as_str() {
-            "original" => {
-                let raw = std::fs::read(source)
-                    .map_err(|e| format!("Failed to read {}: {}", record.filepath, e))?;
-                zip.start_file(&zip_name, zip_options)
-                    .map_err(|e| format!("ZIP write error: {}", e))?;
-                zip.write_all(&raw)
-                    .map_err(|e| format!("ZIP write error: {}", e))?;
-            }
-            "png" => {
-                let img = image_decode::open_image(source)
-                    .map_err(|e| format!("Failed to open {}: {}", record.filepath, e))?;
-                let mut buf = Vec::new();
-                img.write_to(&mut std::io::Cursor::new(&mut buf), image::ImageFormat::Png)
-                    .map_err(|e| format!("PNG encode error: {}", e))?;
-                zip.start_file(&zip_name, zip_options)
-                    .map_err(|e| format!("ZIP write error: {}", e))?;
-                zip.write_all(&buf)
-                    .map_err(|e| format!("ZIP write error: {}", e))?;
-            }
-            "jpeg" | "jpg" => {
-                let img = image_decode::open_image(source)
-                    .map_err(|e| format!("Failed to open {}: {}", record.filepath, e))?;
-                let rgb = img.to_rgb8();
-                let mut buf = Vec::new();
-                let mut encoder =
-                    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buf, quality);
-                encoder
-                    .encode(
-                        rgb.as_raw(),
-                        rgb.width(),
-                        rgb.height(),
-                        image::ExtendedColorType::Rgb8,
-                    )
-                    .map_err(|e| format!("JPEG encode error: {}", e))?;
-                zip.start_file(&zip_name, zip_options)
-                    .map_err(|e| format!("ZIP write error: {}", e))?;
-                zip.write_all(&buf)
-                    .map_err(|e| format!("ZIP write error: {}", e))?;
-            }
-            "webp" => {
-                let buf = encode_image_as_webp(source, quality)?;
-                zip.start_file(&zip_name, zip_options)
-                    .map_err(|e| format!("ZIP write error: {}", e))?;
-                zip.write_all(&buf)
-                    .map_err(|e| format!("ZIP write error: {}", e))?;
-            }
-            "jxl" => {
-                let buf = encode_image_as_jxl(source)?;
-                zip.start_file(&zip_name, zip_options)
-                    .map_err(|e| format!("ZIP write error: {}", e))?;
-                zip.write_all(&buf)
-                    .map_err(|e| format!("ZIP write error: {}", e))?;
-            }
-            _ => {
-                return Err(format!(
-                    "Unsupported format '{}'. Use 'original', 'png', 'jpeg', 'webp', or 'jxl'.",
-                    fmt
-                ));
-            }
-        }
+/// Value of one [`EXPORT_FILENAME_TOKENS`] entry for `record`. `{index}` is
+/// the 1-based position in the export, zero-padded so the names sort.
+fn export_name_token(
+    record: &ImageRecord,
+    stem: &str,
+    token: &str,
+    index: usize,
+    index_width: usize,
+) -> Option<String> {
+    let field = |value: &Option<String>| value.as_deref().map(str::to_string);
+    match token {
+        "stem" => Some(stem.to_string()),
+        "id" => Some(record.id.to_string()),
+        "index" => Some(format!("{:0width$}", index, width = index_width)),
+        "seed" => field(&record.seed),
+        "model" => record
+            .model_name
+            .as_deref()
+            .map(model_token)
+            .or_else(|| field(&record.model_hash)),
+        "sampler" => field(&record.sampler),
+        "steps" => field(&record.steps),
+        "width" => record.width.map(|width| width.to_string()),
+        "height" => record.height.map(|height| height.to_string()),
+        "folder" => Path::new(&record.directory)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string()),
+        _ => None,
+    }
+}
 
-        exported += 1;
+fn encode_export_entry(
+    entry: &FileExportEntry,
+    fmt: &str,
+    quality: u8,
+    transform: Option<&ParsedExportTransform>,
+) -> AppResult<Vec<u8>> {
+    let source = Path::new(&entry.record.filepath);
+    if fmt == "original" {
+        std::fs::read(source).map_err(|e| {
+            AppError::from(e).context(format!("Failed to read {}", entry.record.filepath))
+        })
+    } else {
+        convert_for_export(source, fmt, quality, &entry.record.raw_metadata, transform)
     }
+}
+
+/// Writes `records` into a ZIP at `output_path` and returns how many went in.
+///
+/// Decoding and encoding run on `pool` a chunk at a time while this thread
+/// writes the previous chunk; entries land in the ZIP in record order. The
+/// first file that fails to convert aborts the export.
+fn write_files_zip(
+    records: &[ImageRecord],
+    fmt: &str,
+    quality: u8,
+    transform: Option<ParsedExportTransform>,
+    name_template: Option<&FilenameTemplate>,
+    output_path: &Path,
+    pool: &rayon::ThreadPool,
+) -> AppResult<usize> {
+    use std::io::{BufWriter, Write};
+
+    let entries = plan_file_export(records, fmt, name_template);
+
+    let file = std::fs::File::create(output_path)
+        .map_err(|e| AppError::from(e).context("Failed to create output file"))?;
+    let writer = BufWriter::with_capacity(256 * 1024, file);
+    let mut zip = zip::ZipWriter::new(writer);
+    let zip_options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .compression_level(Some(6));
+
+    let exported = std::thread::scope(|scope| -> AppResult<usize> {
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<AppResult<Vec<u8>>>>(1);
+        let entries = &entries;
+        scope.spawn(move || {
+            for chunk in entries.chunks(EXPORT_ENCODE_CHUNK) {
+                let encoded = pool.install(|| {
+                    chunk
+                        .par_iter()
+                        .map(|entry| encode_export_entry(entry, fmt, quality, transform.as_ref()))
+                        .collect()
+                });
+                // The writer hung up after an error; stop encoding.
+                if sender.send(encoded).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut exported = 0usize;
+        for (entry, buf) in entries.iter().zip(receiver.iter().flatten()) {
+            let buf = buf?;
+            zip.start_file(&entry.zip_name, zip_options)
+                .map_err(|e| AppError::from(e).context("ZIP write error"))?;
+            zip.write_all(&buf)
+                .map_err(|e| AppError::from(e).context("ZIP write error"))?;
+            exported += 1;
+        }
+        Ok(exported)
+    })?;
 
     let mut inner = zip
         .finish()
-        .map_err(|e| format!("Failed to finalize ZIP: {}", e))?;
+        .map_err(|e| AppError::from(e).context("Failed to finalize ZIP"))?;
     inner
         .flush()
-        .map_err(|e| format!("Failed to flush ZIP: {}", e))?;
+        .map_err(|e| AppError::from(e).context("Failed to flush ZIP"))?;
+    Ok(exported)
+}
 
-    let total_bytes = std::fs::metadata(&output_path)
-        .map(|m| m.len())
-        .unwrap_or(0);
+/// Trial encodes per estimate; enough to span small and large sources
+/// without making the estimate itself slow.
+const EXPORT_ESTIMATE_SAMPLES: usize = 6;
 
-    Ok(FileExportResult {
-        exported_count: exported,
-        output_path,
-        total_bytes,
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportEstimate {
+    /// Images that would be exported (sources that still exist).
+    pub image_count: usize,
+    pub sampled: usize,
+    pub source_bytes: u64,
+    /// Projected size of the converted files, before ZIP compression.
+    pub estimated_bytes: u64,
+    pub average_bytes: u64,
+    /// Output size relative to the source across the samples.
+    pub size_ratio: f64,
+    pub average_encode_ms: u64,
+}
+
+/// Indices of up to `count` sources spread evenly by size, smallest and
+/// largest included, so the samples cover the spread of the export.
+fn pick_estimate_samples(sizes: &[u64], count: usize) -> Vec<usize> {
+    let mut by_size: Vec<usize> = (0..sizes.len()).collect();
+    by_size.sort_by_key(|&index| sizes[index]);
+    if by_size.len() <= count {
+        return by_size;
+    }
+    let last = by_size.len() - 1;
+    let mut picked: Vec<usize> = (0..count)
+        .map(|step| by_size[step * last / (count - 1).max(1)])
+        .collect();
+    picked.dedup();
+    picked
+}
+
+/// Projects the output size of an export by trial-encoding a few
+/// representative images with the same `format`, `quality` and `transform`.
+#[tauri::command]
+pub async fn estimate_export(
+    ids: Option<Vec<i64>>,
+    filter: Option<ImageFilterRequest>,
+    format: String,
+    quality: Option<u8>,
+    transform: Option<ExportTransform>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ExportEstimate> {
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let fmt = format.trim().to_ascii_lowercase();
+        let quality = quality.unwrap_or(85).clamp(1, 100);
+        let transform = parse_export_transform(transform.as_ref(), &fmt)?;
+        let ids = resolve_export_ids(&db, ids, filter)?;
+        let records = load_export_records(&db, &ids)?;
+
+        let sources: Vec<(&ImageRecord, u64)> = records
+            .iter()
+            .filter_map(|record| {
+                let size = std::fs::metadata(&record.filepath).ok()?.len();
+                Some((record, size))
+            })
+            .collect();
+        let sizes: Vec<u64> = sources.iter().map(|(_, size)| *size).collect();
+        let source_bytes: u64 = sizes.iter().sum();
+
+        let mut sampled_source = 0u64;
+        let mut sampled_output = 0u64;
+        let mut sampled = 0usize;
+        let started = std::time::Instant::now();
+        for index in pick_estimate_samples(&sizes, EXPORT_ESTIMATE_SAMPLES) {
+            let (record, size) = sources[index];
+            let output = if fmt == "original" {
+                size
+            } else {
+                let source = Path::new(&record.filepath);
+                convert_for_export(
+                    source,
+                    &fmt,
+                    quality,
+                    &record.raw_metadata,
+                    transform.as_ref(),
+                )?
+                .len() as u64
+            };
+            sampled_source += size;
+            sampled_output += output;
+            sampled += 1;
+        }
+
+        let size_ratio = if sampled_source > 0 {
+            sampled_output as f64 / sampled_source as f64
+        } else {
+            1.0
+        };
+        let estimated_bytes = (source_bytes as f64 * size_ratio).round() as u64;
+        Ok(ExportEstimate {
+            image_count: sources.len(),
+            sampled,
+            source_bytes,
+            estimated_bytes,
+            average_bytes: estimated_bytes / sources.len().max(1) as u64,
+            size_ratio,
+            average_encode_ms: (started.elapsed().as_millis() / sampled.max(1) as u128) as u64,
+        })
     })
+    .await
+    .map_err(|error| error.to_string())?
 }
 
 #[cfg(test)]
@@ -345,4 +944,280 @@ mod tests {
             lossless.len()
         );
     }
+
+    #[test]
+    fn estimate_samples_span_small_to_large_sources() {
+        let sizes = [500, 10, 9_000, 40, 700, 3_000, 60, 80];
+        let picked = pick_estimate_samples(&sizes, 3);
+        assert_eq!(picked, vec![1, 7, 2]);
+        assert_eq!(pick_estimate_samples(&sizes[..2], 6), vec![1, 0]);
+        assert!(pick_estimate_samples(&[], 6).is_empty());
+    }
+
+    #[test]
+    fn export_transforms_center_crop_then_fit_the_long_edge() {
+        let transform = |max_edge, crop_aspect: Option<&str>| {
+            ExportTransform {
+                max_edge,
+                crop_aspect: crop_aspect.map(str::to_string),
+            }
+            .parse()
+        };
+        let image = image::DynamicImage::new_rgb8(3000, 2000);
+
+        let square = transform(Some(1024), Some("1:1")).unwrap().unwrap();
+        let cropped = square.apply(image.clone());
+        assert_eq!((cropped.width(), cropped.height()), (1024, 1024));
+
+        let web = transform(Some(2048), None).unwrap().unwrap();
+        let resized = web.apply(image.clone());
+        assert_eq!((resized.width(), resized.height()), (2048, 1365));
+        let small = web.apply(image::DynamicImage::new_rgb8(640, 480));
+        assert_eq!((small.width(), small.height()), (640, 480));
+
+        let portrait = transform(None, Some(" 2 : 3 ")).unwrap().unwrap();
+        let cropped = portrait.apply(image);
+        assert_eq!((cropped.width(), cropped.height()), (1333, 2000));
+
+        assert_eq!(transform(None, Some("")).unwrap(), None);
+        assert!(transform(None, Some("square")).is_err());
+        assert!(transform(None, Some("0:1")).is_err());
+        assert!(transform(Some(0), None).is_err());
+        assert!(parse_export_transform(
+            Some(&ExportTransform {
+                max_edge: Some(512),
+                crop_aspect: None,
+            }),
+            "original",
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn png_exports_keep_sixteen_bit_sources_at_full_depth() {
+        let dir = std::env::temp_dir().join(format!("forge_depth_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("deep.png");
+        image::ImageBuffer::from_fn(16, 4, |x, _| image::Rgb([x as u16 * 4_000 + 7, 0, 0]))
+            .save(&source)
+            .unwrap();
+
+        let converted = convert_for_export(&source, "png", 85, "", None).unwrap();
+        let decoded = image::load_from_memory(&converted).unwrap();
+        assert_eq!(decoded.color(), image::ColorType::Rgb16);
+        assert_eq!(decoded.to_rgb16().get_pixel(3, 0).0[0], 12_007);
+
+        let jpeg = convert_for_export(&source, "jpeg", 85, "", None).unwrap();
+        assert_eq!(
+            image::load_from_memory(&jpeg).unwrap().color(),
+            image::ColorType::Rgb8
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn converted_exports_keep_generation_parameters() {
+        let dir = std::env::temp_dir().join(format!("forge_convert_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("source.png");
+        image::RgbaImage::from_pixel(16, 12, image::Rgba([200, 40, 90, 255]))
+            .save(&source)
+            .unwrap();
+        let parameters = "castle, dusk\nSteps: 20, Sampler: Euler a, Seed: 42";
+        let utf16: Vec<u8> = parameters
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect();
+
+        for format in ["png", "jpeg", "webp"] {
+            let converted = convert_for_export(&source, format, 85, parameters, None).unwrap();
+            let path = dir.join(format!("converted.{}", format));
+            std::fs::write(&path, &converted).unwrap();
+            let decoded = image_decode::open_image(&path)
+                .unwrap_or_else(|error| panic!("{} no longer decodes: {}", format, error));
+            assert_eq!((decoded.width(), decoded.height()), (16, 12));
+            if format == "png" {
+                assert_eq!(
+                    scanner::extract_metadata(&path).unwrap().as_deref(),
+                    Some(parameters)
+                );
+            } else {
+                assert!(
+                    converted.windows(utf16.len()).any(|window| window == utf16),
+                    "{} is missing the UserComment",
+                    format
+                );
+            }
+        }
+
+        let bare = convert_for_export(&source, "jpeg", 85, "  ", None).unwrap();
+        assert!(!bare.windows(4).any(|window| window == b"Exif"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn metadata_export_streams_rows_and_removes_cancelled_output() {
+        let db = crate::database::Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("db init failed");
+        let ids: Vec<i64> = (0..3)
+            .map(|index| {
+                let params = crate::parser::GenerationParams {
+                    prompt: format!("prompt {index}"),
+                    ..Default::default()
+                };
+                let name = format!("{index}.png");
+                db.upsert_image(&format!("/img/{name}"), &name, "/img", &params, Some(1))
+                    .unwrap()
+            })
+            .collect();
+        let dir = std::env::temp_dir().join(format!("forge_export_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let json_path = dir.join("export.json");
+        let mut calls = Vec::new();
+        let count = write_metadata_export(&db, &ids, "json", &json_path, |current, total| {
+            calls.push((current, total));
+            true
+        })
+        .unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(calls.last(), Some(&(3, 3)));
+        let rows: Vec<serde_json::Value> =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0]["prompt"], "prompt 2");
+
+        let csv_path = dir.join("export.csv");
+        write_metadata_export(&db, &ids, "csv", &csv_path, |_, _| true).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&csv_path).unwrap().lines().count(),
+            4
+        );
+
+        let cancelled_path = dir.join("cancelled.json");
+        let error =
+            write_metadata_export(&db, &ids, "json", &cancelled_path, |_, _| false).unwrap_err();
+        assert!(matches!(error, AppError::Cancelled(_)));
+        assert!(!cancelled_path.exists());
+
+        // Cancelling a re-export keeps the previous file as it was.
+        let previous = std::fs::read_to_string(&csv_path).unwrap();
+        write_metadata_export(&db, &ids, "csv", &csv_path, |_, _| false).unwrap_err();
+        assert_eq!(std::fs::read_to_string(&csv_path).unwrap(), previous);
+        assert!(!dir.join("export.csv.part").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Library row for a small PNG written to `folder/name`.
+    fn saved_record(id: i64, folder: &Path, name: &str) -> ImageRecord {
+        let filepath = folder.join(name);
+        image::RgbImage::from_pixel(4 + id as u32, 4, image::Rgb([id as u8, 0, 0]))
+            .save(&filepath)
+            .unwrap();
+        ImageRecord {
+            id,
+            filepath: filepath.to_string_lossy().to_string(),
+            filename: name.to_string(),
+            directory: folder.to_string_lossy().to_string(),
+            prompt: String::new(),
+            negative_prompt: String::new(),
+            steps: None,
+            sampler: None,
+            cfg_scale: None,
+            seed: None,
+            width: None,
+            height: None,
+            model_hash: None,
+            model_name: None,
+            raw_metadata: String::new(),
+            is_favorite: false,
+            is_locked: false,
+        }
+    }
+
+    #[test]
+    fn parallel_zip_export_keeps_record_order() {
+        let dir = std::env::temp_dir().join(format!("forge_zip_order_test_{}", std::process::id()));
+        let nested = dir.join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        let mut records: Vec<ImageRecord> = (0..EXPORT_ENCODE_CHUNK as i64 + 5)
+            .map(|id| saved_record(id, &dir, &format!("{:03}.png", id)))
+            .collect();
+        records.insert(3, saved_record(99, &nested, "001.png"));
+        let missing = saved_record(100, &dir, "gone.png");
+        std::fs::remove_file(&missing.filepath).unwrap();
+        records.insert(5, missing);
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let output = dir.join("export.zip");
+        let exported = write_files_zip(&records, "jpeg", 80, None, None, &output, &pool).unwrap();
+        assert_eq!(exported, records.len() - 1);
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&output).unwrap()).unwrap();
+        let mut in_order = Vec::new();
+        for index in 0..archive.len() {
+            in_order.push(archive.by_index(index).unwrap().name().to_string());
+        }
+        assert_eq!(in_order.len(), exported);
+        assert_eq!(
+            &in_order[..5],
+            ["000.jpg", "001.jpg", "002.jpg", "001_1.jpg", "003.jpg"]
+        );
+        assert_eq!(
+            in_order.last().unwrap(),
+            &format!("{:03}.jpg", EXPORT_ENCODE_CHUNK + 4)
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn export_name_templates_fill_metadata_and_number_clashes() {
+        let dir = std::env::temp_dir().join(format!("forge_zip_names_test_{}", std::process::id()));
+        let batch = dir.join("batch-b");
+        std::fs::create_dir_all(&batch).unwrap();
+        let mut first = saved_record(1, &dir, "00001.png");
+        first.seed = Some("1234".to_string());
+        first.model_name = Some("sdxl/juggernautXL_v9.safetensors [abc123]".to_string());
+        let mut second = saved_record(2, &batch, "00001.png");
+        second.seed = Some("1234".to_string());
+        second.model_name = first.model_name.clone();
+        let third = saved_record(3, &batch, "00002.png");
+        let records = vec![first, second, third];
+
+        let template =
+            FilenameTemplate::parse("{seed}_{model}_{index}", EXPORT_FILENAME_TOKENS).unwrap();
+        let names: Vec<String> = plan_file_export(&records, "webp", Some(&template))
+            .into_iter()
+            .map(|entry| entry.zip_name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "1234_juggernautXL_v9_1.webp",
+                "1234_juggernautXL_v9_2.webp",
+                "3.webp"
+            ]
+        );
+
+        let template = FilenameTemplate::parse("{folder}_{stem}", EXPORT_FILENAME_TOKENS).unwrap();
+        let names: Vec<String> = plan_file_export(&records, "original", Some(&template))
+            .into_iter()
+            .map(|entry| entry.zip_name)
+            .collect();
+        assert_eq!(names[1..], ["batch-b_00001.png", "batch-b_00002.png"]);
+
+        let names: Vec<String> = plan_file_export(&records[..2], "png", None)
+            .into_iter()
+            .map(|entry| entry.zip_name)
+            .collect();
+        assert_eq!(names, ["00001.png", "00001_1.png"]);
+        assert!(FilenameTemplate::parse("{seed}_{n}", EXPORT_FILENAME_TOKENS).is_err());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
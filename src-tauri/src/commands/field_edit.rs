@@ -0,0 +1,259 @@
+// ────────────────────────── Bulk field edits ──────────────────────────
+
+/// Journal entries listed when the caller gives no limit.
+const DEFAULT_FIELD_EDIT_HISTORY: u32 = 50;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BulkUpdateFieldsResult {
+    pub requested: usize,
+    pub updated: usize,
+    pub updated_ids: Vec<i64>,
+    pub skipped_locked_ids: Vec<i64>,
+    /// Journal entry to pass to `undo_field_edit`; `None` when nothing
+    /// was edited.
+    pub edit_id: Option<i64>,
+    /// Sidecars whose notes could not be written.
+    pub failed_paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UndoFieldEditResult {
+    pub restored: usize,
+    pub failed_paths: Vec<String>,
+}
+
+/// Rejects numeric fields that would not parse back, so a typo cannot
+/// break sorting or the Forge round-trip. Empty values clear a field and
+/// are always allowed.
+fn validate_field_update(update: &FieldUpdate) -> AppResult<()> {
+    if update.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Choose at least one field to change".to_string(),
+        ));
+    }
+    for (column, value) in update.columns() {
+        let Some(value) = value else {
+            continue;
+        };
+        let valid = match column {
+            "seed" => value.parse::<i64>().is_ok(),
+            "steps" => value.parse::<u32>().is_ok_and(|steps| steps > 0),
+            "cfg_scale" => value.parse::<f64>().is_ok_and(f64::is_finite),
+            _ => true,
+        };
+        if !valid {
+            return Err(AppError::InvalidInput(format!(
+                "{} is not a valid {}",
+                value, column
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Sets or clears the notes in `filepath`'s sidecar, keeping its tags and
+/// rating. Nothing is written to clear notes on an image without one.
+fn write_sidecar_notes(filepath: &str, notes: Option<String>) -> AppResult<()> {
+    let path = Path::new(filepath);
+    let existing = sidecar::read_sidecar(path);
+    if existing.is_none() && notes.is_none() {
+        return Ok(());
+    }
+    let mut data = existing.unwrap_or_default();
+    data.notes = notes;
+    sidecar::write_sidecar(path, &data)?;
+    Ok(())
+}
+
+pub(crate) fn bulk_update_field_records(
+    db: &crate::database::Database,
+    ids: &[i64],
+    update: &FieldUpdate,
+) -> AppResult<BulkUpdateFieldsResult> {
+    let mut result = BulkUpdateFieldsResult {
+        requested: ids.len(),
+        ..Default::default()
+    };
+    let mut records = Vec::new();
+    for chunk in ids.chunks(EXPORT_LOOKUP_CHUNK) {
+        for record in db.get_images_by_ids(chunk)? {
+            if record.is_locked {
+                result.skipped_locked_ids.push(record.id);
+            } else {
+                records.push(record);
+            }
+        }
+    }
+    if records.is_empty() {
+        return Ok(result);
+    }
+
+    let previous_notes: std::collections::HashMap<i64, Option<String>> = if update.notes.is_some() {
+        records
+            .iter()
+            .map(|record| {
+                let notes =
+                    sidecar::read_sidecar(Path::new(&record.filepath)).and_then(|data| data.notes);
+                (record.id, notes)
+            })
+            .collect()
+    } else {
+        std::collections::HashMap::new()
+    };
+    let edit_ids: Vec<i64> = records.iter().map(|record| record.id).collect();
+    let edit = db
+        .bulk_update_fields(&edit_ids, update, &previous_notes)
+        .map_err(|error| AppError::from(error).context("Failed to save edited fields"))?;
+    result.edit_id = Some(edit.id);
+    result.updated = edit.image_count as usize;
+    result.updated_ids = edit_ids;
+
+    if update.notes.is_some() {
+        let notes = update.notes_value();
+        for record in &records {
+            if db.is_offline_path(&record.filepath) {
+                result.failed_paths.push(record.filepath.clone());
+                continue;
+            }
+            if let Err(error) = write_sidecar_notes(&record.filepath, notes.clone()) {
+                log::warn!("Failed to write notes for {}: {}", record.filepath, error);
+                result.failed_paths.push(record.filepath.clone());
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Overwrites typed metadata fields (model, hash, seed, steps, sampler,
+/// scheduler, CFG, sidecar notes) of the selected images, e.g. to assign
+/// the right checkpoint where the hash did not resolve. Fields left out are
+/// untouched and empty values clear a field. The old values go to the undo
+/// journal; locked images are left alone.
+#[tauri::command]
+pub async fn bulk_update_fields(
+    ids: Vec<i64>,
+    fields: FieldUpdate,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<BulkUpdateFieldsResult> {
+    validate_field_update(&fields)?;
+    let mut ids = ids;
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() {
+        return Ok(BulkUpdateFieldsResult::default());
+    }
+    refresh_volume_status(&state, false);
+    let db = state.db.clone();
+    let result =
+        tauri::async_runtime::spawn_blocking(move || bulk_update_field_records(&db, &ids, &fields))
+            .await
+            .map_err(|error| error.to_string())??;
+    log::info!(
+        "Edited fields of {} of {} images ({} locked)",
+        result.updated,
+        result.requested,
+        result.skipped_locked_ids.len()
+    );
+    Ok(result)
+}
+
+/// Reverts a bulk field edit, sidecar notes included. Refused with a
+/// `conflict` error while any of its images has been changed since, e.g.
+/// by a later edit that has to be undone first.
+#[tauri::command]
+pub async fn undo_field_edit(
+    edit_id: i64,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<UndoFieldEditResult> {
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || undo_field_edit_records(&db, edit_id))
+        .await
+        .map_err(|error| error.to_string())?
+}
+
+pub(crate) fn undo_field_edit_records(
+    db: &crate::database::Database,
+    edit_id: i64,
+) -> AppResult<UndoFieldEditResult> {
+    let context = "Failed to undo field edit";
+    let edits_notes = db
+        .get_field_edit(edit_id)
+        .map_err(|error| AppError::from(error).context(context))?
+        .is_some_and(|edit| edit.fields.iter().any(|field| field == "notes"));
+    let mut current_notes = std::collections::HashMap::new();
+    if edits_notes {
+        let ids = db
+            .field_edit_image_ids(edit_id)
+            .map_err(|error| AppError::from(error).context(context))?;
+        for chunk in ids.chunks(EXPORT_LOOKUP_CHUNK) {
+            for record in db.get_images_by_ids(chunk)? {
+                if !db.is_offline_path(&record.filepath) {
+                    let notes = sidecar::read_sidecar(Path::new(&record.filepath))
+                        .and_then(|data| data.notes);
+                    current_notes.insert(record.id, notes);
+                }
+            }
+        }
+    }
+
+    let rows = match db
+        .undo_field_edit(edit_id, &current_notes)
+        .map_err(|error| AppError::from(error).context(context))?
+    {
+        FieldEditUndo::Restored(rows) => rows,
+        FieldEditUndo::Conflict(ids) => {
+            return Err(AppError::Conflict(format!(
+                "{} image(s) changed after field edit {}; undo the later changes first",
+                ids.len(),
+                edit_id
+            )))
+        }
+        FieldEditUndo::Missing => {
+            return Err(AppError::InvalidInput(format!(
+                "Field edit {} does not exist or was already undone",
+                edit_id
+            )))
+        }
+    };
+    let mut result = UndoFieldEditResult {
+        restored: rows.len(),
+        ..Default::default()
+    };
+
+    let notes: std::collections::HashMap<i64, Option<String>> = rows
+        .into_iter()
+        .filter_map(|mut row| Some((row.image_id, row.previous.remove("notes")?)))
+        .collect();
+    if notes.is_empty() {
+        return Ok(result);
+    }
+    let ids: Vec<i64> = notes.keys().copied().collect();
+    let mut records = Vec::new();
+    for chunk in ids.chunks(EXPORT_LOOKUP_CHUNK) {
+        records.extend(db.get_images_by_ids(chunk)?);
+    }
+    for record in records {
+        let previous = notes.get(&record.id).cloned().flatten();
+        if db.is_offline_path(&record.filepath) {
+            result.failed_paths.push(record.filepath);
+            continue;
+        }
+        if let Err(error) = write_sidecar_notes(&record.filepath, previous) {
+            log::warn!("Failed to restore notes for {}: {}", record.filepath, error);
+            result.failed_paths.push(record.filepath);
+        }
+    }
+    Ok(result)
+}
+
+/// Lists recent bulk field edits, newest first, for an undo menu.
+#[tauri::command]
+pub fn get_field_edits(
+    limit: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<FieldEdit>> {
+    state
+        .db
+        .get_field_edits(limit.unwrap_or(DEFAULT_FIELD_EDIT_HISTORY))
+        .map_err(AppError::from)
+}
@@ -0,0 +1,165 @@
+// ────────────────────────── Thumbnail focal points ──────────────────────────
+
+/// Images written per database transaction during a focal point pass.
+const FOCAL_POINT_BATCH: usize = 64;
+
+#[derive(Clone, Serialize)]
+struct FocalPointProgress {
+    task_id: u64,
+    current: usize,
+    total: usize,
+    detected: usize,
+    failed: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct FocalPointComplete {
+    task_id: u64,
+    total: usize,
+    detected: usize,
+    failed: usize,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+/// Detects the focal point from the cached thumbnail when there is one,
+/// since it is far cheaper to decode than the original.
+fn detect_focal_point(filepath: &str, cache_dir: &Path) -> AppResult<FocalPoint> {
+    let source = Path::new(filepath);
+    let thumbnail = image_processing::get_thumbnail_cache_path(source, cache_dir);
+    let image = if thumbnail.is_file() {
+        image_decode::open_image(&thumbnail)?
+    } else {
+        if !source.exists() {
+            return Err(AppError::file_missing(source));
+        }
+        image_decode::open_image(source)?
+    };
+    Ok(focal_point::detect(&image))
+}
+
+/// Finds where each image's subject sits so grid thumbnails can crop toward
+/// it, and returns the background task id.
+///
+/// Runs over `ids`, or over every image without a focal point when `ids` is
+/// omitted. Emits to the calling window:
+/// - `focal-points-progress`
+/// - `focal-points-complete`
+#[tauri::command]
+pub fn detect_focal_points(
+    ids: Option<Vec<i64>>,
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<u64> {
+    let images: Vec<(i64, String)> = match ids {
+        Some(ids) if ids.is_empty() => {
+            return Err(AppError::InvalidInput(
+                "No images selected for focal point detection".to_string(),
+            ));
+        }
+        Some(ids) => state
+            .db
+            .get_images_by_ids(&ids)?
+            .into_iter()
+            .map(|record| (record.id, record.filepath))
+            .collect(),
+        None => state.db.images_missing_focal_point()?,
+    };
+
+    let db = state.db.clone();
+    let cache_dir = state.cache_dir.clone();
+    let task = state.tasks.start(
+        TaskKind::FocalPoints,
+        format!("Thumbnail focal points ({} images)", images.len()),
+    );
+    let task_id = task.id();
+    let events = EventSink::for_window(&app, &window);
+
+    std::thread::Builder::new()
+        .name("focal-points".into())
+        .spawn(move || {
+            let total = images.len();
+            let mut detected = 0usize;
+            let mut failed = 0usize;
+            let mut cancelled = false;
+            let mut error = None;
+            let mut pending: Vec<(i64, FocalPoint)> = Vec::with_capacity(FOCAL_POINT_BATCH);
+            task.set_progress(0, total);
+
+            for (index, (id, filepath)) in images.iter().enumerate() {
+                if !task.checkpoint() {
+                    cancelled = true;
+                    break;
+                }
+                if db.is_offline_path(filepath) {
+                    failed += 1;
+                } else {
+                    match detect_focal_point(filepath, &cache_dir) {
+                        Ok(point) => pending.push((*id, point)),
+                        Err(error) => {
+                            failed += 1;
+                            log::warn!("Focal point detection failed for {}: {}", filepath, error);
+                        }
+                    }
+                }
+                if pending.len() >= FOCAL_POINT_BATCH || index + 1 == total {
+                    if let Err(save_error) = db.set_focal_points(&pending) {
+                        error = Some(save_error.to_string());
+                        break;
+                    }
+                    detected += pending.len();
+                    pending.clear();
+                }
+                task.set_progress(index + 1, total);
+                let _ = events.emit(
+                    "focal-points-progress",
+                    FocalPointProgress {
+                        task_id,
+                        current: index + 1,
+                        total,
+                        detected,
+                        failed,
+                    },
+                );
+            }
+            // A cancelled run keeps what it already detected.
+            if error.is_none() {
+                if let Err(save_error) = db.set_focal_points(&pending) {
+                    error = Some(save_error.to_string());
+                } else {
+                    detected += pending.len();
+                }
+            }
+
+            match &error {
+                Some(message) => {
+                    log::error!("Focal point detection failed: {}", message);
+                    task.fail(message.clone());
+                }
+                None => log::info!(
+                    "Detected focal points for {} of {} images ({} failed, cancelled={})",
+                    detected,
+                    total,
+                    failed,
+                    cancelled
+                ),
+            }
+            let _ = events.emit(
+                "focal-points-complete",
+                FocalPointComplete {
+                    task_id,
+                    total,
+                    detected,
+                    failed,
+                    cancelled,
+                    error,
+                },
+            );
+        })
+        .map_err(|error| {
+            AppError::Internal(format!("Failed to start focal point detection: {}", error))
+        })?;
+
+    Ok(task_id)
+}
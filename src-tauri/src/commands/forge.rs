@@ -4,14 +4,39 @@
 pub async fn forge_test_connection(
     base_url: String,
     api_key: Option<String>,
-) -> Result<forge_api::ForgeStatus, String> {
-    forge_api::test_connection(&base_url, api_key.as_deref())
-        .await
-        .map_err(|e| e.to_string())
+) -> AppResult<forge_api::ForgeStatus> {
+    forge_api::test_connection(&base_url, api_key.as_deref()).await
+}
+
+/// Stats younger than this are served from the cache unless the caller
+/// asks for fresher ones.
+const FORGE_STATS_MAX_AGE_MS: u64 = 2_000;
+
+/// VRAM/RAM usage and activity of the Forge server, with warnings when it
+/// is close to running out of memory.
+#[tauri::command]
+pub async fn forge_get_server_stats(
+    base_url: String,
+    api_key: Option<String>,
+    max_age_ms: Option<u64>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<forge_api::ForgeServerStats> {
+    let max_age = std::time::Duration::from_millis(max_age_ms.unwrap_or(FORGE_STATS_MAX_AGE_MS));
+    if let Some(stats) = state.forge_server_stats.get(&base_url, max_age) {
+        return Ok(stats);
+    }
+    let stats = forge_api::fetch_server_stats(&base_url, api_key.as_deref()).await?;
+    state.forge_server_stats.store(&base_url, &stats);
+    Ok(stats)
 }
 
 const DEFAULT_FORGE_OUTPUT_DIR: &str = "forge-outputs";
 const DEFAULT_ADETAILER_FACE_MODEL: &str = "face_yolov8n.pt";
+/// Reproduces the original `{stem}_forge_{stamp}_{n}` naming.
+const DEFAULT_FORGE_FILENAME_TEMPLATE: &str = "{stem}_forge_{variant}_{stamp}_{n}";
+const FORGE_FILENAME_TOKENS: &[&str] = &[
+    "stem", "seed", "model", "date", "time", "variant", "stamp", "n",
+];
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ForgeOptionsResult {
@@ -19,6 +44,8 @@ pub struct ForgeOptionsResult {
     pub loras: Vec<String>,
     pub samplers: Vec<String>,
     pub schedulers: Vec<String>,
+    /// Saved prompt styles from the WebUI's `styles.csv`.
+    pub styles: Vec<String>,
     pub models_scan_dir: Option<String>,
     pub loras_scan_dir: Option<String>,
     pub warnings: Vec<String>,
@@ -36,6 +63,12 @@ pub struct ForgePayloadOverridesInput {
     pub width: Option<String>,
     pub height: Option<String>,
     pub model_name: Option<String>,
+    /// `None` keeps the source image's hires pass, if it had one.
+    pub enable_hr: Option<bool>,
+    pub hr_scale: Option<String>,
+    pub hr_upscaler: Option<String>,
+    pub hr_second_pass_steps: Option<String>,
+    pub denoising_strength: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -44,12 +77,18 @@ pub struct ForgeSendOptionsRequest {
     pub base_url: String,
     pub api_key: Option<String>,
     pub output_dir: Option<String>,
+    /// See `FORGE_FILENAME_TOKENS`; defaults to `DEFAULT_FORGE_FILENAME_TEMPLATE`.
+    pub filename_template: Option<String>,
     pub include_seed: Option<bool>,
     pub adetailer_face_enabled: Option<bool>,
     pub adetailer_face_model: Option<String>,
     pub lora_tokens: Option<Vec<String>>,
     pub lora_weight: Option<f32>,
     pub overrides: Option<ForgePayloadOverridesInput>,
+    /// Prompt style names; Forge merges them into the prompts server-side.
+    pub styles: Option<Vec<String>>,
+    /// Name of a saved `ForgePreset`; fields set in `overrides` still win.
+    pub preset: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -57,6 +96,8 @@ pub struct ForgeSendOptionsRequest {
 pub struct ForgeSendToImageRequest {
     pub image_id: i64,
     pub options: ForgeSendOptionsRequest,
+    /// Defaults to high so a single send jumps queued batches.
+    pub priority: Option<ForgePriority>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -64,6 +105,10 @@ pub struct ForgeSendToImageRequest {
 pub struct ForgeSendToImagesRequest {
     pub image_ids: Vec<i64>,
     pub options: ForgeSendOptionsRequest,
+    /// Defaults to normal.
+    pub priority: Option<ForgePriority>,
+    /// Only send while the local time is inside this window, e.g. overnight.
+    pub run_window: Option<crate::forge_queue::RunWindow>,
 }
 
 #[derive(Debug, Clone)]
@@ -71,24 +116,40 @@ struct NormalizedForgeSendOptions {
     base_url: String,
     api_key: Option<String>,
     output_dir: PathBuf,
+    filename_template: FilenameTemplate,
     include_seed: bool,
     adetailer_face_enabled: bool,
     adetailer_face_model: String,
     lora_tokens: Option<Vec<String>>,
     lora_weight: f32,
     overrides: Option<ForgePayloadOverridesInput>,
+    styles: Option<Vec<String>>,
 }
 
 struct ForgeSendContext<'a> {
     base_url: &'a str,
     api_key: Option<&'a str>,
     output_dir: &'a Path,
+    filename_template: &'a FilenameTemplate,
     include_seed: bool,
     adetailer_face_enabled: bool,
     adetailer_face_model: &'a str,
     lora_tokens: Option<&'a [String]>,
     lora_weight: f32,
     overrides: Option<&'a ForgePayloadOverridesInput>,
+    styles: Option<&'a [String]>,
+    resources: &'a forge_api::ForgeServerResources,
+    model_defaults: &'a [ModelDefaults],
+    /// Library checked for outputs identical to an existing image.
+    db: &'a Database,
+}
+
+/// A generated image that was byte-identical to a library image, e.g. a
+/// reproduction with the same seed and server, and so was not saved again.
+#[derive(Debug, Clone, Serialize)]
+pub struct ForgeSkippedDuplicate {
+    pub image_id: i64,
+    pub filepath: String,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -98,6 +159,10 @@ pub struct ForgeSendOutput {
     pub output_dir: String,
     pub generated_count: usize,
     pub saved_paths: Vec<String>,
+    /// Referenced model/LoRAs/embeddings the server doesn't have; Forge
+    /// would otherwise ignore them silently.
+    pub warnings: Vec<forge_api::ForgeResourceWarning>,
+    pub skipped_duplicates: Vec<ForgeSkippedDuplicate>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -108,6 +173,8 @@ pub struct ForgeBatchItemOutput {
     pub message: String,
     pub generated_count: usize,
     pub saved_paths: Vec<String>,
+    pub warnings: Vec<forge_api::ForgeResourceWarning>,
+    pub skipped_duplicates: Vec<ForgeSkippedDuplicate>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -130,7 +197,7 @@ fn default_forge_output_base_dir(cache_dir: &Path) -> PathBuf {
 fn resolve_forge_output_dir(
     configured: Option<&str>,
     default_base_dir: &Path,
-) -> Result<PathBuf, String> {
+) -> AppResult<PathBuf> {
     let configured = configured.unwrap_or("").trim();
     let normalized_configured = configured
         .replace('\\', "/")
@@ -150,11 +217,10 @@ fn resolve_forge_output_dir(
     }
 
     std::fs::create_dir_all(&output_dir).map_err(|error| {
-        format!(
-            "Failed to create Forge output directory {}: {}",
-            output_dir.display(),
-            error
-        )
+        AppError::from(error).context(format!(
+            "Failed to create Forge output directory {}",
+            output_dir.display()
+        ))
     })?;
 
     Ok(output_dir)
@@ -181,10 +247,59 @@ fn resolve_forge_loras_dir(configured: Option<&str>) -> Option<PathBuf> {
     resolve_forge_models_dir(configured)
 }
 
+/// Trimmed, de-duplicated style names; `None` when nothing is selected.
+fn normalize_style_names(styles: Option<Vec<String>>) -> Option<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    for style in styles.unwrap_or_default() {
+        let style = style.trim();
+        if !style.is_empty() && !names.iter().any(|name| name == style) {
+            names.push(style.to_string());
+        }
+    }
+    (!names.is_empty()).then_some(names)
+}
+
+/// Overrides with the preset's settings filled in wherever the request
+/// leaves a field unset. The send form posts blank strings for untouched
+/// fields, so those count as unset too.
+fn apply_forge_preset(
+    preset: &ForgePreset,
+    overrides: Option<ForgePayloadOverridesInput>,
+) -> ForgePayloadOverridesInput {
+    fn or_preset(value: Option<String>, preset: &Option<String>) -> Option<String> {
+        match preset {
+            Some(preset) if value.as_deref().is_none_or(|value| value.trim().is_empty()) => {
+                Some(preset.clone())
+            }
+            _ => value,
+        }
+    }
+
+    let overrides = overrides.unwrap_or_default();
+    ForgePayloadOverridesInput {
+        steps: or_preset(overrides.steps, &preset.steps),
+        sampler_name: or_preset(overrides.sampler_name, &preset.sampler_name),
+        scheduler: or_preset(overrides.scheduler, &preset.scheduler),
+        cfg_scale: or_preset(overrides.cfg_scale, &preset.cfg_scale),
+        width: or_preset(overrides.width, &preset.width),
+        height: or_preset(overrides.height, &preset.height),
+        enable_hr: overrides.enable_hr.or(preset.enable_hr),
+        hr_scale: or_preset(overrides.hr_scale, &preset.hr_scale),
+        hr_upscaler: or_preset(overrides.hr_upscaler, &preset.hr_upscaler),
+        hr_second_pass_steps: or_preset(
+            overrides.hr_second_pass_steps,
+            &preset.hr_second_pass_steps,
+        ),
+        denoising_strength: or_preset(overrides.denoising_strength, &preset.denoising_strength),
+        ..overrides
+    }
+}
+
 fn normalize_forge_send_options(
     options: ForgeSendOptionsRequest,
     default_output_base: &Path,
-) -> Result<NormalizedForgeSendOptions, String> {
+    presets: &[ForgePreset],
+) -> AppResult<NormalizedForgeSendOptions> {
     let include_seed = options.include_seed.unwrap_or(true);
     let adetailer_face_enabled = options.adetailer_face_enabled.unwrap_or(false);
     let adetailer_face_model = options
@@ -196,36 +311,61 @@ fn normalize_forge_send_options(
         .to_string();
     let lora_weight = options.lora_weight.unwrap_or(1.0);
     if !lora_weight.is_finite() {
-        return Err("Invalid LoRA weight value".to_string());
+        return Err(AppError::InvalidInput(
+            "Invalid LoRA weight value".to_string(),
+        ));
     }
 
+    let filename_template = FilenameTemplate::parse(
+        options
+            .filename_template
+            .as_deref()
+            .filter(|template| !template.trim().is_empty())
+            .unwrap_or(DEFAULT_FORGE_FILENAME_TEMPLATE),
+        FORGE_FILENAME_TOKENS,
+    )?;
     let output_dir = resolve_forge_output_dir(options.output_dir.as_deref(), default_output_base)?;
+    let overrides = match options
+        .preset
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        Some(name) => {
+            let preset = crate::forge_presets::find(presets, name)
+                .ok_or_else(|| AppError::NotFound(format!("Forge preset not found: {}", name)))?;
+            Some(apply_forge_preset(preset, options.overrides))
+        }
+        None => options.overrides,
+    };
 
     Ok(NormalizedForgeSendOptions {
         base_url: options.base_url,
         api_key: options.api_key,
         output_dir,
+        filename_template,
         include_seed,
         adetailer_face_enabled,
         adetailer_face_model,
         lora_tokens: options.lora_tokens,
         lora_weight,
-        overrides: options.overrides,
+        overrides,
+        styles: normalize_style_names(options.styles),
     })
 }
 
 fn scan_relevant_forge_models(
     models_dir: &Path,
     include_subfolders: bool,
-) -> Result<Vec<String>, String> {
+) -> AppResult<Vec<String>> {
     if !models_dir.exists() {
         return Ok(Vec::new());
     }
     if !models_dir.is_dir() {
-        return Err(format!(
+        return Err(AppError::InvalidInput(format!(
             "Forge models path is not a directory: {}",
             models_dir.display()
-        ));
+        )));
     }
 
     let blocked_segments = [
@@ -324,18 +464,15 @@ fn strip_known_model_extension(value: &str) -> String {
     value.to_string()
 }
 
-fn scan_relevant_forge_loras(
-    loras_dir: &Path,
-    include_subfolders: bool,
-) -> Result<Vec<String>, String> {
+fn scan_relevant_forge_loras(loras_dir: &Path, include_subfolders: bool) -> AppResult<Vec<String>> {
     if !loras_dir.exists() {
         return Ok(Vec::new());
     }
     if !loras_dir.is_dir() {
-        return Err(format!(
+        return Err(AppError::InvalidInput(format!(
             "Forge LoRA path is not a directory: {}",
             loras_dir.display()
-        ));
+        )));
     }
 
     let mut loras = std::collections::BTreeSet::new();
@@ -388,7 +525,7 @@ pub async fn forge_get_options(
     scan_subfolders: Option<bool>,
     loras_dir: Option<String>,
     loras_scan_subfolders: Option<bool>,
-) -> Result<ForgeOptionsResult, String> {
+) -> AppResult<ForgeOptionsResult> {
     let include_subfolders = scan_subfolders.unwrap_or(true);
     let models_path = resolve_forge_models_dir(models_dir.as_deref());
     let include_lora_subfolders = loras_scan_subfolders.unwrap_or(true);
@@ -397,8 +534,7 @@ pub async fn forge_get_options(
         tauri::async_runtime::spawn_blocking(move || {
             scan_relevant_forge_models(&models_path, include_subfolders)
         })
-        .await
-        .map_err(|error| error.to_string())??
+        .await??
     } else {
         Vec::new()
     };
@@ -406,8 +542,7 @@ pub async fn forge_get_options(
         tauri::async_runtime::spawn_blocking(move || {
             scan_relevant_forge_loras(&loras_path, include_lora_subfolders)
         })
-        .await
-        .map_err(|error| error.to_string())??
+        .await??
     } else {
         Vec::new()
     };
@@ -438,6 +573,14 @@ pub async fn forge_get_options(
         }
     };
 
+    let styles = match forge_api::list_styles(&base_url, api_key.as_deref()).await {
+        Ok(values) => values,
+        Err(error) => {
+            warnings.push(format!("Prompt style list unavailable: {}", error));
+            Vec::new()
+        }
+    };
+
     if let Some(models_path) = models_path.as_ref() {
         if !models_path.exists() {
             warnings.push(format!(
@@ -473,6 +616,7 @@ pub async fn forge_get_options(
         loras: scanned_loras,
         samplers,
         schedulers,
+        styles,
         models_scan_dir: models_path.map(|path| path.to_string_lossy().to_string()),
         loras_scan_dir: loras_path.map(|path| path.to_string_lossy().to_string()),
         warnings,
@@ -550,16 +694,16 @@ fn extension_from_image_bytes(bytes: &[u8]) -> &'static str {
     }
 }
 
-fn decode_forge_image_payload(payload: &str) -> Result<(Vec<u8>, &'static str), String> {
+fn decode_forge_image_payload(payload: &str) -> AppResult<(Vec<u8>, &'static str)> {
     let trimmed = payload.trim();
     if trimmed.is_empty() {
-        return Err("empty image payload".to_string());
+        return Err(AppError::InvalidInput("empty image payload".to_string()));
     }
 
     let (mime, b64_data) = if let Some(rest) = trimmed.strip_prefix("data:") {
         let (mime, b64) = rest
             .split_once(";base64,")
-            .ok_or_else(|| "malformed data URI payload".to_string())?;
+            .ok_or_else(|| AppError::InvalidInput("malformed data URI payload".to_string()))?;
         (Some(mime), b64)
     } else {
         (None, trimmed)
@@ -568,9 +712,11 @@ fn decode_forge_image_payload(payload: &str) -> Result<(Vec<u8>, &'static str),
     let normalized_b64: String = b64_data.chars().filter(|ch| !ch.is_whitespace()).collect();
     let decoded = BASE64_STANDARD
         .decode(normalized_b64.as_bytes())
-        .map_err(|error| format!("base64 decode failed: {}", error))?;
+        .map_err(|error| AppError::InvalidInput(format!("base64 decode failed: {}", error)))?;
     if decoded.is_empty() {
-        return Err("decoded payload is empty".to_string());
+        return Err(AppError::InvalidInput(
+            "decoded payload is empty".to_string(),
+        ));
     }
 
     let ext = mime
@@ -580,7 +726,7 @@ fn decode_forge_image_payload(payload: &str) -> Result<(Vec<u8>, &'static str),
     Ok((decoded, ext))
 }
 
-fn validate_optional_u32(field: &str, value: Option<&str>) -> Result<(), String> {
+fn validate_optional_u32(field: &str, value: Option<&str>) -> AppResult<()> {
     let Some(raw) = value else {
         return Ok(());
     };
@@ -591,10 +737,10 @@ fn validate_optional_u32(field: &str, value: Option<&str>) -> Result<(), String>
     trimmed
         .parse::<u32>()
         .map(|_| ())
-        .map_err(|_| format!("Invalid {} value: {}", field, raw))
+        .map_err(|_| AppError::InvalidInput(format!("Invalid {} value: {}", field, raw)))
 }
 
-fn validate_optional_i64(field: &str, value: Option<&str>) -> Result<(), String> {
+fn validate_optional_i64(field: &str, value: Option<&str>) -> AppResult<()> {
     let Some(raw) = value else {
         return Ok(());
     };
@@ -605,10 +751,10 @@ fn validate_optional_i64(field: &str, value: Option<&str>) -> Result<(), String>
     trimmed
         .parse::<i64>()
         .map(|_| ())
-        .map_err(|_| format!("Invalid {} value: {}", field, raw))
+        .map_err(|_| AppError::InvalidInput(format!("Invalid {} value: {}", field, raw)))
 }
 
-fn validate_optional_f32(field: &str, value: Option<&str>) -> Result<(), String> {
+fn validate_optional_f32(field: &str, value: Option<&str>) -> AppResult<()> {
     let Some(raw) = value else {
         return Ok(());
     };
@@ -619,10 +765,10 @@ fn validate_optional_f32(field: &str, value: Option<&str>) -> Result<(), String>
     trimmed
         .parse::<f32>()
         .map(|_| ())
-        .map_err(|_| format!("Invalid {} value: {}", field, raw))
+        .map_err(|_| AppError::InvalidInput(format!("Invalid {} value: {}", field, raw)))
 }
 
-fn parse_optional_u32_override(field: &str, raw: &str) -> Result<Option<u32>, String> {
+fn parse_optional_u32_override(field: &str, raw: &str) -> AppResult<Option<u32>> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
         return Ok(None);
@@ -630,7 +776,7 @@ fn parse_optional_u32_override(field: &str, raw: &str) -> Result<Option<u32>, St
     trimmed
         .parse::<u32>()
         .map(Some)
-        .map_err(|_| format!("Invalid {} value: {}", field, raw))
+        .map_err(|_| AppError::InvalidInput(format!("Invalid {} value: {}", field, raw)))
 }
 
 fn normalize_lora_token_for_prompt(value: &str) -> Option<String> {
@@ -746,15 +892,209 @@ fn apply_custom_loras_to_prompt(
     }
 }
 
+fn parse_optional_f32_override(field: &str, raw: &str) -> AppResult<Option<f32>> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    trimmed
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .map(Some)
+        .ok_or_else(|| AppError::InvalidInput(format!("Invalid {} value: {}", field, raw)))
+}
+
+/// Hires-fix settings recorded in the source image's A1111/Forge parameters.
+fn original_hires_fix(raw_metadata: &str) -> Option<forge_api::ForgeHiresFix> {
+    let params = parser::parse_generation_metadata(raw_metadata);
+    let extra = |key: &str| {
+        params
+            .extra_params
+            .get(key)
+            .map(|value| value.trim().trim_matches('"'))
+            .filter(|value| !value.is_empty())
+    };
+    let scale = extra("Hires upscale").and_then(|value| value.parse::<f32>().ok());
+    let upscaler = extra("Hires upscaler").map(str::to_string);
+    let second_pass_steps = extra("Hires steps").and_then(|value| value.parse::<u32>().ok());
+    if scale.is_none()
+        && upscaler.is_none()
+        && second_pass_steps.is_none()
+        && extra("Hires resize").is_none()
+    {
+        return None;
+    }
+    Some(forge_api::ForgeHiresFix {
+        scale,
+        upscaler,
+        second_pass_steps,
+        denoising_strength: extra("Denoising strength").and_then(|value| value.parse::<f32>().ok()),
+    })
+}
+
+/// Source image's hires pass with any overrides applied; `None` disables it.
+fn resolve_hires_fix(
+    image: &ImageRecord,
+    overrides: Option<&ForgePayloadOverridesInput>,
+) -> AppResult<Option<forge_api::ForgeHiresFix>> {
+    let original = original_hires_fix(&image.raw_metadata);
+    let enabled = overrides
+        .and_then(|o| o.enable_hr)
+        .unwrap_or(original.is_some());
+    if !enabled {
+        return Ok(None);
+    }
+    let mut hires = original.unwrap_or_default();
+    let Some(overrides) = overrides else {
+        return Ok(Some(hires));
+    };
+
+    if let Some(raw) = overrides.hr_scale.as_deref() {
+        if let Some(scale) = parse_optional_f32_override("hires scale", raw)? {
+            if !(1.0..=8.0).contains(&scale) {
+                return Err(AppError::InvalidInput(format!(
+                    "Hires scale must be between 1 and 8, got {}",
+                    raw
+                )));
+            }
+            hires.scale = Some(scale);
+        }
+    }
+    if let Some(raw) = overrides.hr_upscaler.as_deref() {
+        let upscaler = raw.trim();
+        if !upscaler.is_empty() {
+            hires.upscaler = Some(upscaler.to_string());
+        }
+    }
+    if let Some(raw) = overrides.hr_second_pass_steps.as_deref() {
+        if let Some(steps) = parse_optional_u32_override("hires steps", raw)? {
+            hires.second_pass_steps = Some(steps);
+        }
+    }
+    if let Some(raw) = overrides.denoising_strength.as_deref() {
+        if let Some(strength) = parse_optional_f32_override("denoising strength", raw)? {
+            if !(0.0..=1.0).contains(&strength) {
+                return Err(AppError::InvalidInput(format!(
+                    "Denoising strength must be between 0 and 1, got {}",
+                    raw
+                )));
+            }
+            hires.denoising_strength = Some(strength);
+        }
+    }
+    Ok(Some(hires))
+}
+
+/// `<lora:name:weight>` names in a prompt, as written.
+fn prompt_lora_names(prompt: &str) -> Vec<String> {
+    let lower = prompt.to_ascii_lowercase();
+    let mut names = Vec::new();
+    let mut cursor = 0usize;
+    while let Some(found) = lower[cursor..].find("<lora:") {
+        let start = cursor + found;
+        let Some(end) = prompt[start..].find('>') else {
+            break;
+        };
+        if let Some(name) = normalize_lora_token_for_prompt(&prompt[start..=start + end]) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        cursor = start + end + 1;
+    }
+    names
+}
+
+/// Model, LoRAs and embeddings the payload relies on. Embeddings come from
+/// `embedding:name` prompt words and the source image's `TI hashes`, kept
+/// only while the name still appears in the prompts.
+fn referenced_resources(
+    payload: &forge_api::ForgePayload,
+    raw_metadata: &str,
+) -> forge_api::ForgeResourceRefs {
+    let model = payload
+        .override_settings
+        .as_ref()
+        .and_then(|settings| settings.get("sd_model_checkpoint"))
+        .and_then(|name| name.as_str())
+        .map(str::to_string);
+
+    let mut loras = prompt_lora_names(&payload.prompt);
+    for name in prompt_lora_names(&payload.negative_prompt) {
+        if !loras.contains(&name) {
+            loras.push(name);
+        }
+    }
+
+    let prompts = format!("{}\n{}", payload.prompt, payload.negative_prompt).to_lowercase();
+    let mut embeddings: Vec<String> = Vec::new();
+    let mut push_embedding = |name: &str| {
+        let name = name.trim();
+        if !name.is_empty()
+            && !embeddings
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(name))
+        {
+            embeddings.push(name.to_string());
+        }
+    };
+    for word in prompts.split(|ch: char| ch.is_whitespace() || ch == ',') {
+        if let Some(name) = word.strip_prefix("embedding:") {
+            push_embedding(
+                name.trim_matches(|ch: char| !ch.is_alphanumeric() && ch != '_' && ch != '-'),
+            );
+        }
+    }
+    let params = parser::parse_generation_metadata(raw_metadata);
+    if let Some(hashes) = params.extra_params.get("TI hashes") {
+        for entry in hashes.trim_matches('"').split(',') {
+            let name = entry.split(':').next().unwrap_or("").trim();
+            if !name.is_empty() && prompts.contains(&name.to_lowercase()) {
+                push_embedding(name);
+            }
+        }
+    }
+
+    forge_api::ForgeResourceRefs {
+        model,
+        loras,
+        embeddings,
+    }
+}
+
+/// One generation setting for a send. An override the user typed wins; a
+/// blank one means the source lacked the value, so the model default fills
+/// in. After a model switch the new model's default also replaces values
+/// carried over from the source (the form is prefilled with them).
+fn pick_generation_setting(
+    override_value: Option<&str>,
+    source: Option<&str>,
+    default: Option<&str>,
+    switched_model: bool,
+) -> Option<String> {
+    let edited = override_value
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let carried_over = |value: &str| source.is_some_and(|source| source.trim() == value);
+    let chosen = match edited {
+        Some(value) if switched_model && carried_over(value) => default.or(Some(value)),
+        Some(value) => Some(value),
+        None if override_value.is_some() => default,
+        None if switched_model => default.or(source),
+        None => source.or(default),
+    };
+    chosen.map(str::to_string)
+}
+
+/// `adetailer_face_enabled` is separate from the context's so the
+/// unprocessed variant of an ADetailer send can be built without it.
 fn build_payload_for_image(
     image: &ImageRecord,
-    include_seed: bool,
+    context: &ForgeSendContext<'_>,
     adetailer_face_enabled: bool,
-    adetailer_face_model: Option<&str>,
-    lora_tokens: Option<&[String]>,
-    lora_weight: f32,
-    overrides: Option<&ForgePayloadOverridesInput>,
-) -> Result<forge_api::ForgePayload, String> {
+) -> AppResult<forge_api::ForgePayload> {
+    let overrides = context.overrides;
     let override_prompt = overrides.and_then(|o| o.prompt.as_deref());
     let override_negative_prompt = overrides.and_then(|o| o.negative_prompt.as_deref());
     let override_steps = overrides.and_then(|o| o.steps.as_deref());
@@ -770,56 +1110,171 @@ fn build_payload_for_image(
     validate_optional_f32("cfg scale", override_cfg_scale)?;
     validate_optional_i64("seed", override_seed)?;
 
-    let width = match override_width {
-        Some(raw) => parse_optional_u32_override("width", raw)?,
-        None => image.width,
+    let model_name = override_model.or(image.model_name.as_deref());
+    let switched_model = override_model
+        .map(str::trim)
+        .filter(|model| !model.is_empty())
+        .is_some_and(|model| {
+            image
+                .model_name
+                .as_deref()
+                .is_none_or(|source| !model_token(source).eq_ignore_ascii_case(&model_token(model)))
+        });
+    let defaults = model_name.and_then(|name| ModelDefaults::lookup(context.model_defaults, name));
+    let default_steps = defaults.and_then(|d| d.steps).map(|v| v.to_string());
+    let default_cfg_scale = defaults.and_then(|d| d.cfg_scale).map(|v| v.to_string());
+    let default_width = defaults.and_then(|d| d.width).map(|v| v.to_string());
+    let default_height = defaults.and_then(|d| d.height).map(|v| v.to_string());
+    let source_width = image.width.map(|v| v.to_string());
+    let source_height = image.height.map(|v| v.to_string());
+
+    let width = pick_generation_setting(
+        override_width,
+        source_width.as_deref(),
+        default_width.as_deref(),
+        switched_model,
+    );
+    let width = match width {
+        Some(raw) => parse_optional_u32_override("width", &raw)?,
+        None => None,
     };
-    let height = match override_height {
-        Some(raw) => parse_optional_u32_override("height", raw)?,
-        None => image.height,
+    let height = pick_generation_setting(
+        override_height,
+        source_height.as_deref(),
+        default_height.as_deref(),
+        switched_model,
+    );
+    let height = match height {
+        Some(raw) => parse_optional_u32_override("height", &raw)?,
+        None => None,
     };
 
     let base_prompt = override_prompt.unwrap_or(image.prompt.as_str());
-    let prompt = apply_custom_loras_to_prompt(base_prompt, lora_tokens, lora_weight);
+    let prompt =
+        apply_custom_loras_to_prompt(base_prompt, context.lora_tokens, context.lora_weight);
     let negative_prompt = override_negative_prompt.unwrap_or(image.negative_prompt.as_str());
-    let steps = override_steps.or(image.steps.as_deref());
-    let sampler = override_sampler.or(image.sampler.as_deref());
-    let scheduler = override_scheduler;
-    let cfg_scale = override_cfg_scale.or(image.cfg_scale.as_deref());
+    let steps = pick_generation_setting(
+        override_steps,
+        image.steps.as_deref(),
+        default_steps.as_deref(),
+        switched_model,
+    );
+    let sampler = pick_generation_setting(
+        override_sampler,
+        image.sampler.as_deref(),
+        defaults.and_then(|d| d.sampler.as_deref()),
+        switched_model,
+    );
+    let scheduler = pick_generation_setting(
+        override_scheduler,
+        None,
+        defaults.and_then(|d| d.scheduler.as_deref()),
+        switched_model,
+    );
+    let cfg_scale = pick_generation_setting(
+        override_cfg_scale,
+        image.cfg_scale.as_deref(),
+        default_cfg_scale.as_deref(),
+        switched_model,
+    );
     let seed = override_seed.or(image.seed.as_deref());
-    let model_name = override_model.or(image.model_name.as_deref());
+    let hires_fix = resolve_hires_fix(image, overrides)?;
 
     Ok(forge_api::build_payload_from_image_record(
         forge_api::ForgePayloadBuildInput {
             prompt: &prompt,
             negative_prompt,
-            steps,
-            sampler,
-            scheduler,
-            cfg_scale,
+            steps: steps.as_deref(),
+            sampler: sampler.as_deref(),
+            scheduler: scheduler.as_deref(),
+            cfg_scale: cfg_scale.as_deref(),
             seed,
             width,
             height,
             model_name,
-            include_seed,
+            include_seed: context.include_seed,
             adetailer_face_enabled,
-            adetailer_face_model,
+            adetailer_face_model: Some(context.adetailer_face_model),
+            hires_fix,
         },
     ))
 }
 
+/// Per-image seeds from Forge's `info` JSON (`all_seeds`, else `seed`).
+fn seeds_from_forge_info(info: Option<&str>) -> Vec<i64> {
+    let Some(info) = info.and_then(|raw| serde_json::from_str::<serde_json::Value>(raw).ok())
+    else {
+        return Vec::new();
+    };
+    if let Some(all_seeds) = info.get("all_seeds").and_then(|value| value.as_array()) {
+        let seeds: Vec<i64> = all_seeds.iter().filter_map(|seed| seed.as_i64()).collect();
+        if !seeds.is_empty() {
+            return seeds;
+        }
+    }
+    info.get("seed")
+        .and_then(|seed| seed.as_i64())
+        .into_iter()
+        .collect()
+}
+
+/// Checkpoint name without folder, extension or ` [hash]` suffix.
+fn model_token(model_name: &str) -> String {
+    let name = model_name
+        .split(" [")
+        .next()
+        .unwrap_or(model_name)
+        .replace('\\', "/");
+    let name = name.rsplit('/').next().unwrap_or(&name);
+    strip_known_model_extension(name.trim())
+}
+
+/// Template values shared by every image of one Forge response.
+struct ForgeOutputNaming<'a> {
+    template: &'a FilenameTemplate,
+    source_filename: &'a str,
+    variant_label: Option<&'a str>,
+    model_name: Option<&'a str>,
+    /// Seed sent with the request; used when Forge doesn't report seeds.
+    requested_seed: Option<i64>,
+}
+
+/// Outputs of one Forge request.
+#[derive(Debug, Default)]
+struct ForgeSavedImages {
+    saved_paths: Vec<String>,
+    skipped_duplicates: Vec<ForgeSkippedDuplicate>,
+}
+
+/// Library image with exactly these bytes. The quick hash only samples the
+/// file, so candidates are compared in full before counting as a match.
+fn find_library_duplicate(db: &Database, bytes: &[u8]) -> Option<ForgeSkippedDuplicate> {
+    let quick_hash = scanner::quick_hash_bytes(bytes)?;
+    let candidates = db
+        .find_images_by_quick_hash(&quick_hash)
+        .map_err(|error| log::warn!("Duplicate check for Forge output failed: {}", error))
+        .ok()?;
+    candidates.into_iter().find_map(|(image_id, filepath)| {
+        let existing = std::fs::read(&filepath).ok()?;
+        (existing == bytes).then_some(ForgeSkippedDuplicate { image_id, filepath })
+    })
+}
+
 fn save_generated_images(
     payloads: &[String],
+    info: Option<&str>,
     output_dir: &Path,
-    source_filename: &str,
-    variant_label: Option<&str>,
-) -> Result<Vec<String>, String> {
+    naming: &ForgeOutputNaming<'_>,
+    find_duplicate: impl Fn(&[u8]) -> Option<ForgeSkippedDuplicate>,
+) -> AppResult<ForgeSavedImages> {
+    let source_filename = naming.source_filename;
     let stem = Path::new(source_filename)
         .file_stem()
         .and_then(|value| value.to_str())
         .map(sanitize_stem)
         .unwrap_or_else(|| "image".to_string());
-    let variant = variant_label
+    let variant = naming
+        .variant_label
         .map(str::trim)
         .filter(|value| !value.is_empty())
         .map(sanitize_stem);
@@ -827,8 +1282,15 @@ fn save_generated_images(
         .duration_since(UNIX_EPOCH)
         .map(|duration| duration.as_millis())
         .unwrap_or(0);
+    let now = chrono::Local::now();
+    let seeds = if naming.template.uses("seed") {
+        seeds_from_forge_info(info)
+    } else {
+        Vec::new()
+    };
+    let model = naming.model_name.map(model_token);
 
-    let mut saved_paths = Vec::with_capacity(payloads.len());
+    let mut saved = ForgeSavedImages::default();
     let mut decode_failures = 0usize;
 
     for (index, payload) in payloads.iter().enumerate() {
@@ -846,21 +1308,40 @@ fn save_generated_images(
                 continue;
             }
         };
+        if let Some(duplicate) = find_duplicate(&bytes) {
+            log::info!(
+                "Forge output {} for {} matches {}; not saving a copy",
+                index + 1,
+                source_filename,
+                duplicate.filepath
+            );
+            saved.skipped_duplicates.push(duplicate);
+            continue;
+        }
 
         let sequence = index + 1;
+        let seed = seeds
+            .get(index)
+            .or(seeds.last())
+            .copied()
+            .or(naming.requested_seed.filter(|seed| *seed >= 0));
+        let base_name = naming.template.render(|token| match token {
+            "stem" => Some(stem.clone()),
+            "seed" => seed.map(|seed| seed.to_string()),
+            "model" => model.clone(),
+            "date" => Some(now.format("%Y-%m-%d").to_string()),
+            "time" => Some(now.format("%H%M%S").to_string()),
+            "variant" => variant.clone(),
+            "stamp" => Some(stamp.to_string()),
+            "n" => Some(sequence.to_string()),
+            _ => None,
+        });
         let mut counter = 0usize;
         let output_path = loop {
-            let suffix = if counter == 0 {
-                String::new()
+            let candidate_name = if counter == 0 {
+                format!("{}.{}", base_name, ext)
             } else {
-                format!("_{}", counter)
-            };
-            let candidate_name = match variant.as_ref() {
-                Some(variant) => format!(
-                    "{}_forge_{}_{}_{}{}.{}",
-                    stem, variant, stamp, sequence, suffix, ext
-                ),
-                None => format!("{}_forge_{}_{}{}.{}", stem, stamp, sequence, suffix, ext),
+                format!("{}_{}.{}", base_name, counter, ext)
             };
             let candidate = output_dir.join(candidate_name);
             if !candidate.exists() {
@@ -870,95 +1351,102 @@ fn save_generated_images(
         };
 
         std::fs::write(&output_path, &bytes).map_err(|error| {
-            format!(
-                "Failed saving generated image to {}: {}",
-                output_path.display(),
-                error
-            )
+            AppError::from(error).context(format!(
+                "Failed saving generated image to {}",
+                output_path.display()
+            ))
         })?;
-        saved_paths.push(output_path.to_string_lossy().to_string());
+        saved
+            .saved_paths
+            .push(output_path.to_string_lossy().to_string());
     }
 
-    if saved_paths.is_empty() {
+    if saved.saved_paths.is_empty() && saved.skipped_duplicates.is_empty() {
         if decode_failures > 0 {
-            return Err("Forge returned image payloads, but none could be decoded".to_string());
+            return Err(AppError::Forge(
+                "Forge returned image payloads, but none could be decoded".to_string(),
+            ));
         }
-        return Err("Forge returned no images in response payload".to_string());
+        return Err(AppError::Forge(
+            "Forge returned no images in response payload".to_string(),
+        ));
     }
 
-    Ok(saved_paths)
+    Ok(saved)
 }
 
 async fn send_payload_and_save(
     payload: &forge_api::ForgePayload,
-    base_url: &str,
-    api_key: Option<&str>,
-    output_dir: &Path,
+    context: &ForgeSendContext<'_>,
     source_filename: &str,
     variant_label: Option<&str>,
-) -> Result<Vec<String>, String> {
-    let api_result = forge_api::send_to_forge(payload, base_url, api_key)
-        .await
-        .map_err(|e| e.to_string())?;
+) -> AppResult<ForgeSavedImages> {
+    let api_result = forge_api::send_to_forge(payload, context.base_url, context.api_key).await?;
 
     if !api_result.ok {
-        return Err(api_result.message);
+        return Err(AppError::Forge(api_result.message));
     }
 
+    let model_name = payload
+        .override_settings
+        .as_ref()
+        .and_then(|settings| settings.get("sd_model_checkpoint"))
+        .and_then(|name| name.as_str());
     save_generated_images(
         &api_result.images,
-        output_dir,
-        source_filename,
-        variant_label,
+        api_result.info.as_deref(),
+        context.output_dir,
+        &ForgeOutputNaming {
+            template: context.filename_template,
+            source_filename,
+            variant_label,
+            model_name,
+            requested_seed: payload.seed,
+        },
+        |bytes| find_library_duplicate(context.db, bytes),
     )
 }
 
 async fn send_image_record_to_forge(
     image: &ImageRecord,
     context: &ForgeSendContext<'_>,
-) -> Result<ForgeSendOutput, String> {
+) -> AppResult<ForgeSendOutput> {
     let mut saved_paths = Vec::new();
+    let mut skipped_duplicates = Vec::new();
     let mut failures = Vec::new();
     let mut unprocessed_count = 0usize;
     let mut processed_count = 0usize;
 
     if context.adetailer_face_enabled {
-        let unprocessed_payload = build_payload_for_image(
-            image,
-            context.include_seed,
-            false,
-            Some(context.adetailer_face_model),
-            context.lora_tokens,
-            context.lora_weight,
-            context.overrides,
-        )?;
+        let mut unprocessed_payload = build_payload_for_image(image, context, false)?;
+        unprocessed_payload.styles = context.styles.map(<[String]>::to_vec);
         match send_payload_and_save(
             &unprocessed_payload,
-            context.base_url,
-            context.api_key,
-            context.output_dir,
+            context,
             &image.filename,
             Some("unprocessed"),
         )
         .await
         {
-            Ok(paths) => {
-                unprocessed_count = paths.len();
-                saved_paths.extend(paths);
+            Ok(saved) => {
+                unprocessed_count = saved.saved_paths.len();
+                saved_paths.extend(saved.saved_paths);
+                skipped_duplicates.extend(saved.skipped_duplicates);
             }
             Err(error) => failures.push(format!("Unprocessed request failed: {}", error)),
         }
     }
 
-    let processed_payload = build_payload_for_image(
-        image,
-        context.include_seed,
-        context.adetailer_face_enabled,
-        Some(context.adetailer_face_model),
-        context.lora_tokens,
-        context.lora_weight,
-        context.overrides,
-    )?;
+    let mut processed_payload =
+        build_payload_for_image(image, context, context.adetailer_face_enabled)?;
+    processed_payload.styles = context.styles.map(<[String]>::to_vec);
+    let warnings = forge_api::find_missing_resources(
+        &referenced_resources(&processed_payload, &image.raw_metadata),
+        context.resources,
+    );
+    for warning in &warnings {
+        log::warn!("{}: {}", image.filename, warning.message);
+    }
     let processed_variant = if context.adetailer_face_enabled {
         Some("adetailer")
     } else {
@@ -966,29 +1454,36 @@ async fn send_image_record_to_forge(
     };
     match send_payload_and_save(
         &processed_payload,
-        context.base_url,
-        context.api_key,
-        context.output_dir,
+        context,
         &image.filename,
         processed_variant,
     )
     .await
     {
-        Ok(paths) => {
-            processed_count = paths.len();
-            saved_paths.extend(paths);
+        Ok(saved) => {
+            processed_count = saved.saved_paths.len();
+            saved_paths.extend(saved.saved_paths);
+            skipped_duplicates.extend(saved.skipped_duplicates);
         }
         Err(error) => {
             if context.adetailer_face_enabled {
                 failures.push(format!("ADetailer request failed: {}", error));
             } else {
-                failures.push(error);
+                failures.push(error.to_string());
             }
         }
     }
 
     let generated_count = saved_paths.len();
     let output_dir_display = context.output_dir.to_string_lossy().to_string();
+    let duplicate_note = match skipped_duplicates.len() {
+        0 => String::new(),
+        count => format!(
+            " (skipped {} identical to existing library image{})",
+            count,
+            if count == 1 { "" } else { "s" }
+        ),
+    };
     if !failures.is_empty() {
         let summary = if context.adetailer_face_enabled {
             format!(
@@ -1007,31 +1502,36 @@ async fn send_image_record_to_forge(
         return Ok(ForgeSendOutput {
             ok: false,
             message: format!(
-                "{} to {}, but some requests failed: {}",
+                "{} to {}{}, but some requests failed: {}",
                 summary,
                 context.output_dir.display(),
+                duplicate_note,
                 failures.join(" | ")
             ),
             output_dir: output_dir_display,
             generated_count,
             saved_paths,
+            warnings,
+            skipped_duplicates,
         });
     }
 
     let message = if context.adetailer_face_enabled {
         format!(
-            "Saved {} unprocessed and {} ADetailer image{} to {}",
+            "Saved {} unprocessed and {} ADetailer image{} to {}{}",
             unprocessed_count,
             processed_count,
             if generated_count == 1 { "" } else { "s" },
-            context.output_dir.display()
+            context.output_dir.display(),
+            duplicate_note
         )
     } else {
         format!(
-            "Saved {} generated image{} to {}",
+            "Saved {} generated image{} to {}{}",
             processed_count,
             if processed_count == 1 { "" } else { "s" },
-            context.output_dir.display()
+            context.output_dir.display(),
+            duplicate_note
         )
     };
 
@@ -1041,34 +1541,72 @@ async fn send_image_record_to_forge(
         output_dir: output_dir_display,
         generated_count,
         saved_paths,
+        warnings,
+        skipped_duplicates,
     })
 }
 
+/// Waits on the blocking pool for a turn in the Forge queue; `None` when
+/// `cancelled` fired first.
+async fn wait_for_forge_turn(
+    queue: &std::sync::Arc<crate::forge_queue::ForgeQueue>,
+    priority: ForgePriority,
+    sequence: u64,
+    cancelled: impl Fn() -> bool + Send + 'static,
+) -> Option<crate::forge_queue::ForgeQueueGuard> {
+    let queue = std::sync::Arc::clone(queue);
+    tauri::async_runtime::spawn_blocking(move || queue.acquire(priority, sequence, cancelled))
+        .await
+        .ok()
+        .flatten()
+}
+
 #[tauri::command]
 pub async fn forge_send_to_image(
     request: ForgeSendToImageRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<ForgeSendOutput, String> {
-    let ForgeSendToImageRequest { image_id, options } = request;
-    let _queue_guard = state.forge_send_queue.lock().await;
+) -> AppResult<ForgeSendOutput> {
+    let ForgeSendToImageRequest {
+        image_id,
+        options,
+        priority,
+    } = request;
+    let sequence = state.forge_send_queue.next_sequence();
+    let _turn = wait_for_forge_turn(
+        &state.forge_send_queue,
+        priority.unwrap_or(ForgePriority::High),
+        sequence,
+        || false,
+    )
+    .await
+    .ok_or_else(|| AppError::Internal("Forge queue is unavailable".to_string()))?;
     let default_output_base = default_forge_output_base_dir(&state.cache_dir);
-    let normalized = normalize_forge_send_options(options, &default_output_base)?;
+    let presets = read_forge_presets(&state)?;
+    let normalized = normalize_forge_send_options(options, &default_output_base, &presets)?;
     let image = state
         .db
-        .get_image_by_id(image_id)
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| format!("Image not found: {}", image_id))?;
+        .get_image_by_id(image_id)?
+        .ok_or_else(|| AppError::NotFound(format!("Image not found: {}", image_id)))?;
 
+    let resources =
+        forge_api::fetch_server_resources(&normalized.base_url, normalized.api_key.as_deref())
+            .await;
+    let model_defaults = state.db.list_model_defaults()?;
     let context = ForgeSendContext {
         base_url: &normalized.base_url,
         api_key: normalized.api_key.as_deref(),
         output_dir: &normalized.output_dir,
+        filename_template: &normalized.filename_template,
         include_seed: normalized.include_seed,
         adetailer_face_enabled: normalized.adetailer_face_enabled,
         adetailer_face_model: &normalized.adetailer_face_model,
         lora_tokens: normalized.lora_tokens.as_deref(),
         lora_weight: normalized.lora_weight,
         overrides: normalized.overrides.as_ref(),
+        styles: normalized.styles.as_deref(),
+        resources: &resources,
+        model_defaults: &model_defaults,
+        db: &state.db,
     };
 
     send_image_record_to_forge(&image, &context).await
@@ -1078,38 +1616,81 @@ pub async fn forge_send_to_image(
 pub async fn forge_send_to_images(
     request: ForgeSendToImagesRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<ForgeBatchSendOutput, String> {
-    let ForgeSendToImagesRequest { image_ids, options } = request;
+) -> AppResult<ForgeBatchSendOutput> {
+    let ForgeSendToImagesRequest {
+        image_ids,
+        options,
+        priority,
+        run_window,
+    } = request;
     if image_ids.is_empty() {
-        return Err("No selected images for Forge queue".to_string());
+        return Err(AppError::InvalidInput(
+            "No selected images for Forge queue".to_string(),
+        ));
     }
-
-    let _queue_guard = state.forge_send_queue.lock().await;
+    let run_window = run_window
+        .as_ref()
+        .map(|window| window.parse())
+        .transpose()?;
+    let priority = priority.unwrap_or_default();
+
+    let requested = image_ids.len();
+    let task = state.tasks.start(
+        TaskKind::ForgeBatch,
+        format!("Forge batch ({} images)", requested),
+    );
+    task.set_progress(0, requested);
+    let sequence = state.forge_send_queue.next_sequence();
     let default_output_base = default_forge_output_base_dir(&state.cache_dir);
-    let normalized = normalize_forge_send_options(options, &default_output_base)?;
+    let normalized = match read_forge_presets(&state)
+        .and_then(|presets| normalize_forge_send_options(options, &default_output_base, &presets))
+    {
+        Ok(normalized) => normalized,
+        Err(error) => {
+            task.fail(error.to_string());
+            return Err(error);
+        }
+    };
     let output_dir_display = normalized.output_dir.to_string_lossy().to_string();
 
-    let mut items = Vec::with_capacity(image_ids.len());
+    let mut items = Vec::with_capacity(requested);
     let mut succeeded = 0usize;
 
+    let resources =
+        forge_api::fetch_server_resources(&normalized.base_url, normalized.api_key.as_deref())
+            .await;
+    let model_defaults = state.db.list_model_defaults()?;
     let context = ForgeSendContext {
         base_url: &normalized.base_url,
         api_key: normalized.api_key.as_deref(),
         output_dir: &normalized.output_dir,
+        filename_template: &normalized.filename_template,
         include_seed: normalized.include_seed,
         adetailer_face_enabled: normalized.adetailer_face_enabled,
         adetailer_face_model: &normalized.adetailer_face_model,
         lora_tokens: normalized.lora_tokens.as_deref(),
         lora_weight: normalized.lora_weight,
         overrides: normalized.overrides.as_ref(),
+        styles: normalized.styles.as_deref(),
+        resources: &resources,
+        model_defaults: &model_defaults,
+        db: &state.db,
     };
 
-    for image_id in image_ids {
-        let image = match state
-            .db
-            .get_image_by_id(image_id)
-            .map_err(|e| e.to_string())?
-        {
+    // One batched read up front instead of a query per queued image.
+    let mut records: std::collections::HashMap<i64, ImageRecord> = state
+        .db
+        .get_images_by_ids(&image_ids)?
+        .into_iter()
+        .map(|record| (record.id, record))
+        .collect();
+
+    for (index, image_id) in image_ids.into_iter().enumerate() {
+        if !task.checkpoint_async().await {
+            break;
+        }
+        task.set_progress(index, requested);
+        let image = match records.remove(&image_id) {
             Some(image) => image,
             None => {
                 items.push(ForgeBatchItemOutput {
@@ -1119,11 +1700,39 @@ pub async fn forge_send_to_images(
                     message: format!("Image not found: {}", image_id),
                     generated_count: 0,
                     saved_paths: Vec::new(),
+                    warnings: Vec::new(),
+                    skipped_duplicates: Vec::new(),
                 });
                 continue;
             }
         };
 
+        // A cancel while waiting is recorded by the checkpoint at the top of
+        // the loop, so both waits just move on to it.
+        if let Some(window) = run_window.filter(|window| !window.is_open()) {
+            task.set_message(format!("Waiting for run window {}", window.label()));
+            let cancelled = task.cancel_check();
+            let opened =
+                tauri::async_runtime::spawn_blocking(move || window.wait_until_open(cancelled))
+                    .await
+                    .unwrap_or(false);
+            if !opened {
+                continue;
+            }
+        }
+        task.set_message("Waiting for Forge queue");
+        let Some(_turn) = wait_for_forge_turn(
+            &state.forge_send_queue,
+            priority,
+            sequence,
+            task.cancel_check(),
+        )
+        .await
+        else {
+            continue;
+        };
+        task.set_message(format!("Sending image {}", image_id));
+
         match send_image_record_to_forge(&image, &context).await {
             Ok(result) => {
                 if result.ok {
@@ -1136,6 +1745,8 @@ pub async fn forge_send_to_images(
                     message: result.message,
                     generated_count: result.generated_count,
                     saved_paths: result.saved_paths,
+                    warnings: result.warnings,
+                    skipped_duplicates: result.skipped_duplicates,
                 });
             }
             Err(error) => {
@@ -1143,9 +1754,11 @@ pub async fn forge_send_to_images(
                     image_id: image.id,
                     filename: image.filename.clone(),
                     ok: false,
-                    message: error,
+                    message: error.to_string(),
                     generated_count: 0,
                     saved_paths: Vec::new(),
+                    warnings: Vec::new(),
+                    skipped_duplicates: Vec::new(),
                 });
             }
         }
@@ -1153,9 +1766,46 @@ pub async fn forge_send_to_images(
 
     let total = items.len();
     let failed = total.saturating_sub(succeeded);
-    let message = format!(
-        "Forge queue completed: {}/{} succeeded ({} failed). Output: {}",
-        succeeded, total, failed, output_dir_display
+    task.set_progress(total, requested);
+    let message = if task.is_cancelled() {
+        format!(
+            "Forge queue cancelled after {}/{} images: {} succeeded ({} failed). Output: {}",
+            total, requested, succeeded, failed, output_dir_display
+        )
+    } else {
+        format!(
+            "Forge queue completed: {}/{} succeeded ({} failed). Output: {}",
+            succeeded, total, failed, output_dir_display
+        )
+    };
+    task.set_message(message.clone());
+    let saved_paths: Vec<String> = items
+        .iter()
+        .flat_map(|item| item.saved_paths.iter().cloned())
+        .collect();
+    log_activity(
+        &state.db,
+        ActivityKind::ForgeBatch,
+        message.clone(),
+        serde_json::json!({
+            "total": total,
+            "succeeded": succeeded,
+            "failed": failed,
+            "output_dir": output_dir_display,
+        }),
+        &saved_paths,
+    );
+    fire_hooks(
+        &state.hooks,
+        HookEvent::ForgeBatchFinished,
+        serde_json::json!({
+            "total": total,
+            "succeeded": succeeded,
+            "failed": failed,
+            "output_dir": output_dir_display,
+            "message": message,
+            "saved_paths": saved_paths,
+        }),
     );
 
     Ok(ForgeBatchSendOutput {
@@ -1167,3 +1817,75 @@ pub async fn forge_send_to_images(
         items,
     })
 }
+
+#[cfg(test)]
+mod forge_tests {
+    use super::*;
+
+    #[test]
+    fn outputs_identical_to_library_images_are_not_saved_again() {
+        let dir = std::env::temp_dir().join(format!("forge_dedup_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let template = FilenameTemplate::parse("{stem}_{n}", FORGE_FILENAME_TOKENS).unwrap();
+        let naming = ForgeOutputNaming {
+            template: &template,
+            source_filename: "castle.png",
+            variant_label: None,
+            model_name: None,
+            requested_seed: None,
+        };
+        let payloads = [
+            BASE64_STANDARD.encode(b"known image"),
+            BASE64_STANDARD.encode(b"new image"),
+        ];
+        let find_duplicate = |bytes: &[u8]| {
+            (bytes == b"known image").then(|| ForgeSkippedDuplicate {
+                image_id: 7,
+                filepath: "/library/castle.png".to_string(),
+            })
+        };
+
+        let saved = save_generated_images(&payloads, None, &dir, &naming, find_duplicate).unwrap();
+        assert_eq!(saved.saved_paths.len(), 1);
+        assert!(saved.saved_paths[0].ends_with("castle_2.png"));
+        assert_eq!(saved.skipped_duplicates.len(), 1);
+        assert_eq!(saved.skipped_duplicates[0].image_id, 7);
+
+        // All duplicates is not an error: nothing new was generated.
+        let saved =
+            save_generated_images(&payloads[..1], None, &dir, &naming, find_duplicate).unwrap();
+        assert!(saved.saved_paths.is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn generation_settings_prefer_edits_then_model_defaults() {
+        // Same model: the source value wins, defaults only fill gaps.
+        assert_eq!(
+            pick_generation_setting(Some("20"), Some("20"), Some("30"), false).as_deref(),
+            Some("20")
+        );
+        assert_eq!(
+            pick_generation_setting(Some(" "), None, Some("30"), false).as_deref(),
+            Some("30")
+        );
+        assert_eq!(
+            pick_generation_setting(None, Some("20"), Some("30"), false).as_deref(),
+            Some("20")
+        );
+        // Switched model: carried-over values give way, edits don't.
+        assert_eq!(
+            pick_generation_setting(Some("20"), Some("20"), Some("30"), true).as_deref(),
+            Some("30")
+        );
+        assert_eq!(
+            pick_generation_setting(Some("25"), Some("20"), Some("30"), true).as_deref(),
+            Some("25")
+        );
+        assert_eq!(
+            pick_generation_setting(Some("20"), Some("20"), None, true).as_deref(),
+            Some("20")
+        );
+    }
+}
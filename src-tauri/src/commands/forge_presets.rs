@@ -0,0 +1,86 @@
+// ────────────────────────── Forge presets ──────────────────────────
+
+fn read_forge_presets(state: &AppState) -> AppResult<Vec<ForgePreset>> {
+    state
+        .forge_presets
+        .read()
+        .map(|presets| presets.clone())
+        .map_err(|_| AppError::Internal("Failed to read Forge presets".to_string()))
+}
+
+fn update_forge_presets(
+    state: &AppState,
+    update: impl FnOnce(&mut Vec<ForgePreset>) -> AppResult<()>,
+) -> AppResult<Vec<ForgePreset>> {
+    let mut lock = state
+        .forge_presets
+        .write()
+        .map_err(|_| AppError::Internal("Failed to update Forge presets".to_string()))?;
+    let mut presets = lock.clone();
+    update(&mut presets)?;
+    crate::persist_forge_presets(&state.forge_presets_path, &presets)?;
+    log_settings_change(
+        &state.db,
+        "forge_presets",
+        format!("Saved {} Forge preset(s)", presets.len()),
+    );
+    *lock = presets.clone();
+    Ok(presets)
+}
+
+#[tauri::command]
+pub fn list_forge_presets(state: tauri::State<'_, AppState>) -> AppResult<Vec<ForgePreset>> {
+    read_forge_presets(&state)
+}
+
+/// Creates or replaces a preset. Pass `previous_name` to rename one; the
+/// new name must not clash with another preset.
+#[tauri::command]
+pub fn save_forge_preset(
+    preset: ForgePreset,
+    previous_name: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<ForgePreset>> {
+    let preset = preset.normalized()?;
+    update_forge_presets(&state, |presets| {
+        let renamed_from = previous_name
+            .as_deref()
+            .map(str::trim)
+            .filter(|previous| !previous.eq_ignore_ascii_case(&preset.name));
+        if let Some(previous) = renamed_from {
+            if crate::forge_presets::find(presets, &preset.name).is_some() {
+                return Err(AppError::InvalidInput(format!(
+                    "A preset named '{}' already exists",
+                    preset.name
+                )));
+            }
+            if let Some(existing) = presets
+                .iter_mut()
+                .find(|existing| existing.name.eq_ignore_ascii_case(previous))
+            {
+                *existing = preset;
+                return Ok(());
+            }
+        }
+        crate::forge_presets::upsert(presets, preset);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn delete_forge_preset(
+    name: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<ForgePreset>> {
+    update_forge_presets(&state, |presets| {
+        let before = presets.len();
+        presets.retain(|preset| !preset.name.eq_ignore_ascii_case(name.trim()));
+        if presets.len() == before {
+            return Err(AppError::NotFound(format!(
+                "Forge preset not found: {}",
+                name
+            )));
+        }
+        Ok(())
+    })
+}
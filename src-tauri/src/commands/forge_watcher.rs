@@ -0,0 +1,181 @@
+// ────────────────────────── Forge outputs watcher ──────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForgeWatcherStatus {
+    pub enabled: bool,
+    pub outputs_dir: Option<String>,
+    pub running: bool,
+    /// Images indexed since the watcher last started.
+    pub indexed: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct ForgeOutputsIndexed {
+    count: usize,
+    filepaths: Vec<String>,
+    /// `YYYY-MM-DD` days the new images were tagged with.
+    sessions: Vec<String>,
+}
+
+fn forge_watcher_status(state: &AppState) -> AppResult<ForgeWatcherStatus> {
+    let settings = state
+        .forge_watcher_settings
+        .read()
+        .map_err(|_| AppError::Internal("Failed to read Forge watcher settings".to_string()))?;
+    Ok(ForgeWatcherStatus {
+        enabled: settings.enabled,
+        outputs_dir: settings.outputs_dir.clone(),
+        running: state.forge_watcher.is_running(),
+        indexed: state.forge_watcher.indexed(),
+    })
+}
+
+#[tauri::command]
+pub fn get_forge_watcher_status(
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ForgeWatcherStatus> {
+    forge_watcher_status(&state)
+}
+
+/// Turns the outputs watcher on or off. `outputs_dir` may be the WebUI's
+/// outputs folder, its install folder or a folder inside it such as the
+/// models folder; the outputs folder is found from there.
+#[tauri::command]
+pub fn set_forge_watcher(
+    enabled: bool,
+    outputs_dir: Option<String>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ForgeWatcherStatus> {
+    let resolved = match outputs_dir.as_deref().map(str::trim) {
+        Some(chosen) if !chosen.is_empty() => {
+            let outputs =
+                crate::forge_watcher::resolve_outputs_dir(Path::new(chosen)).ok_or_else(|| {
+                    AppError::FileMissing(format!(
+                        "No Forge WebUI outputs folder found at or above {}",
+                        chosen
+                    ))
+                })?;
+            Some(outputs.to_string_lossy().to_string())
+        }
+        _ => None,
+    };
+
+    let settings = {
+        let mut settings = state.forge_watcher_settings.write().map_err(|_| {
+            AppError::Internal("Failed to update Forge watcher settings".to_string())
+        })?;
+        let mut updated = settings.clone();
+        updated.enabled = enabled;
+        if resolved.is_some() {
+            updated.outputs_dir = resolved;
+        }
+        if updated.enabled && updated.outputs_dir.is_none() {
+            return Err(AppError::InvalidInput(
+                "Choose the Forge WebUI folder or its outputs folder first".to_string(),
+            ));
+        }
+        crate::persist_forge_watcher_settings(&state.forge_watcher_settings_path, &updated)?;
+        log_settings_change(
+            &state.db,
+            "forge_watcher",
+            match updated.outputs_dir.as_deref().filter(|_| updated.enabled) {
+                Some(outputs_dir) => {
+                    format!("Turned on the Forge outputs watcher for {}", outputs_dir)
+                }
+                None => "Turned off the Forge outputs watcher".to_string(),
+            },
+        );
+        *settings = updated.clone();
+        updated
+    };
+
+    match settings.outputs_dir.filter(|_| settings.enabled) {
+        Some(outputs_dir) => start_forge_watcher(&app, &state, PathBuf::from(outputs_dir)),
+        None => state.forge_watcher.stop(),
+    }
+    forge_watcher_status(&state)
+}
+
+/// Resumes the watcher at startup when it was left on.
+pub(crate) fn start_forge_watcher_from_settings(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let outputs_dir = state
+        .forge_watcher_settings
+        .read()
+        .ok()
+        .filter(|settings| settings.enabled)
+        .and_then(|settings| settings.outputs_dir.clone());
+    if let Some(outputs_dir) = outputs_dir {
+        start_forge_watcher(app, &state, PathBuf::from(outputs_dir));
+    }
+}
+
+fn start_forge_watcher(app: &tauri::AppHandle, state: &AppState, root: PathBuf) {
+    let known = {
+        let app = app.clone();
+        let root = root.clone();
+        move || indexed_files_under(&app.state::<AppState>(), &root)
+    };
+    let app = app.clone();
+    state.forge_watcher.start(
+        root,
+        known,
+        state.background_paused.clone(),
+        move |root, paths| index_forge_outputs(&app, root, &paths),
+    );
+}
+
+/// Indexes new outputs, tags them with their session day and makes their
+/// thumbnails. Returns how many were indexed.
+fn index_forge_outputs(app: &tauri::AppHandle, root: &Path, paths: &[PathBuf]) -> usize {
+    let state = app.state::<AppState>();
+    let mut sessions = std::collections::BTreeSet::new();
+    let records: Vec<BulkRecord> = paths
+        .iter()
+        .filter_map(|path| scanner::scan_file(path))
+        .map(|scanned| {
+            let mut record =
+                build_bulk_record(&scanned.path, scanned.file_mtime, scanned.file_size, root);
+            if let Some(date) =
+                crate::forge_watcher::session_date(&scanned.path, scanned.file_mtime)
+            {
+                record.tags.push(crate::forge_watcher::session_tag(&date));
+                sessions.insert(date);
+            }
+            record
+        })
+        .collect();
+    if records.is_empty() || !index_new_images(&state, &records, paths) {
+        return 0;
+    }
+
+    let filepaths: Vec<String> = records
+        .iter()
+        .map(|record| record.filepath.clone())
+        .collect();
+    log::info!(
+        "Indexed {} new Forge output(s) from {}",
+        filepaths.len(),
+        root.display()
+    );
+    let _ = EventSink::broadcast(app).emit(
+        "forge-outputs-indexed",
+        ForgeOutputsIndexed {
+            count: filepaths.len(),
+            filepaths: filepaths.clone(),
+            sessions: sessions.into_iter().collect(),
+        },
+    );
+    fire_hooks(
+        &state.hooks,
+        HookEvent::ImagesIndexed,
+        serde_json::json!({
+            "source": "forge_watcher",
+            "directory": root.to_string_lossy(),
+            "count": filepaths.len(),
+            "filepaths": filepaths,
+        }),
+    );
+    records.len()
+}
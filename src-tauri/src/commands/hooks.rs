@@ -0,0 +1,84 @@
+// ────────────────────────── Hooks ──────────────────────────
+
+fn fire_hooks(hooks: &RwLock<Vec<HookConfig>>, event: HookEvent, payload: serde_json::Value) {
+    match hooks.read() {
+        Ok(hooks) => crate::hooks::dispatch(&hooks, event, payload),
+        Err(_) => log::warn!("Skipping {} hooks: hook list is poisoned", event.as_str()),
+    }
+}
+
+#[tauri::command]
+pub fn get_hooks(state: tauri::State<'_, AppState>) -> AppResult<Vec<HookConfig>> {
+    state
+        .hooks
+        .read()
+        .map(|hooks| hooks.clone())
+        .map_err(|_| AppError::Internal("Failed to read hooks".to_string()))
+}
+
+#[tauri::command]
+pub fn set_hooks(hooks: Vec<HookConfig>, state: tauri::State<'_, AppState>) -> AppResult<()> {
+    for hook in &hooks {
+        if hook.id.trim().is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "Hook '{}' is missing an id",
+                hook.name
+            )));
+        }
+        match &hook.action {
+            HookAction::Webhook { url } if url.trim().is_empty() => {
+                return Err(AppError::InvalidInput(format!(
+                    "Hook '{}' has no webhook URL",
+                    hook.name
+                )));
+            }
+            HookAction::Script { program, .. } if program.trim().is_empty() => {
+                return Err(AppError::InvalidInput(format!(
+                    "Hook '{}' has no program to run",
+                    hook.name
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    crate::persist_hooks(&state.hooks_path, &hooks)?;
+    log_settings_change(&state.db, "hooks", format!("Saved {} hook(s)", hooks.len()));
+    let mut lock = state
+        .hooks
+        .write()
+        .map_err(|_| AppError::Internal("Failed to update hooks".to_string()))?;
+    *lock = hooks;
+    Ok(())
+}
+
+/// Runs a hook once with a sample payload so users can check their setup.
+#[tauri::command]
+pub async fn test_hook(hook: HookConfig, event: Option<HookEvent>) -> AppResult<HookRunResult> {
+    let event = event
+        .or_else(|| hook.events.first().copied())
+        .unwrap_or(HookEvent::ScanComplete);
+    let payload = match event {
+        HookEvent::ScanComplete => serde_json::json!({
+            "directory": "/example/outputs",
+            "total_files": 42,
+            "indexed": 3,
+            "errors": 0,
+            "skipped": 39,
+        }),
+        HookEvent::ImagesIndexed => serde_json::json!({
+            "source": "scan",
+            "directory": "/example/outputs",
+            "count": 3,
+        }),
+        HookEvent::ForgeBatchFinished => serde_json::json!({
+            "total": 2,
+            "succeeded": 2,
+            "failed": 0,
+            "output_dir": "/example/forge",
+            "message": "Forge queue completed: 2/2 succeeded (0 failed).",
+            "saved_paths": [],
+        }),
+    };
+    crate::hooks::run_hook(&hook, event, &payload).await
+}
@@ -0,0 +1,109 @@
+// ────────────────────────── Image comparison ──────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageDiffResult {
+    /// PNG overlay in the display cache, sized like the first image (scaled
+    /// down when it is very large).
+    pub heatmap_path: String,
+    pub width: u32,
+    pub height: u32,
+    /// Mean absolute pixel difference, 0 (identical) to 1.
+    pub score: f32,
+    /// Share of pixels that visibly changed, 0 to 1.
+    pub changed_fraction: f32,
+    /// The images had different dimensions and the second was scaled to fit.
+    pub resized: bool,
+}
+
+fn diff_source_path(state: &AppState, id: i64) -> AppResult<PathBuf> {
+    let record = state
+        .db
+        .get_image_by_id(id)?
+        .ok_or_else(|| AppError::NotFound(format!("Image not found: {}", id)))?;
+    if state.db.is_offline_path(&record.filepath) {
+        return Err(AppError::FileMissing(format!(
+            "{} is on a disconnected drive",
+            record.filepath
+        )));
+    }
+    let path = PathBuf::from(record.filepath);
+    if !path.is_file() {
+        return Err(AppError::file_missing(&path));
+    }
+    Ok(path)
+}
+
+fn render_image_diff(a: &Path, b: &Path, cache_dir: &Path) -> AppResult<ImageDiffResult> {
+    let image_a = image_decode::open_image(a)?;
+    let image_b = image_decode::open_image(b)?;
+    let diff = crate::image_diff::diff(&image_a, &image_b);
+
+    // Keyed like display proxies so an edited file gets a fresh overlay.
+    let mut hasher = Sha256::new();
+    for path in [a, b] {
+        hasher.update(
+            display_proxy_path(path, cache_dir)?
+                .to_string_lossy()
+                .as_bytes(),
+        );
+    }
+    let display_cache_dir = display_cache_directory(cache_dir);
+    std::fs::create_dir_all(&display_cache_dir).map_err(|error| {
+        AppError::from(error).context(format!(
+            "Failed to create display cache directory {}",
+            display_cache_dir.display()
+        ))
+    })?;
+    let heatmap_path = display_cache_dir.join(format!("diff-{:x}.png", hasher.finalize()));
+    diff.heatmap
+        .save_with_format(&heatmap_path, image::ImageFormat::Png)
+        .map_err(|error| {
+            AppError::from(error).context(format!(
+                "Failed to write diff heatmap {}",
+                heatmap_path.display()
+            ))
+        })?;
+
+    Ok(ImageDiffResult {
+        heatmap_path: heatmap_path.to_string_lossy().to_string(),
+        width: diff.heatmap.width(),
+        height: diff.heatmap.height(),
+        score: diff.score,
+        changed_fraction: diff.changed_fraction,
+        resized: diff.resized,
+    })
+}
+
+/// Compares two library images pixel by pixel, e.g. neighbouring seeds,
+/// ADetailer before/after, or the same prompt on two models.
+#[tauri::command]
+pub async fn diff_images(
+    id_a: i64,
+    id_b: i64,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ImageDiffResult> {
+    if id_a == id_b {
+        return Err(AppError::InvalidInput(
+            "Choose two different images to compare".to_string(),
+        ));
+    }
+    refresh_volume_status(&state, false);
+    let path_a = diff_source_path(&state, id_a)?;
+    let path_b = diff_source_path(&state, id_b)?;
+    let cache_dir = state.cache_dir.clone();
+    let started = std::time::Instant::now();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        render_image_diff(&path_a, &path_b, &cache_dir)
+    })
+    .await
+    .map_err(|error| AppError::Internal(error.to_string()))??;
+    log::info!(
+        "Diffed images {} and {} in {:?}: score={:.4}, changed={:.1}%",
+        id_a,
+        id_b,
+        started.elapsed(),
+        result.score,
+        result.changed_fraction * 100.0
+    );
+    Ok(result)
+}
@@ -0,0 +1,141 @@
+// ────────────────────────── Clipboard import ──────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardImportResult {
+    pub id: i64,
+    pub filepath: String,
+    pub filename: String,
+    pub has_metadata: bool,
+    pub tags: Vec<String>,
+}
+
+/// Saves pasted image bytes into the library inbox and indexes them.
+///
+/// Accepts raw base64 or a `data:` URI. Bytes are written untouched so embedded
+/// PNG text chunks (A1111/Comfy parameters) survive and get parsed like a scan.
+#[tauri::command]
+pub async fn import_from_clipboard(
+    base64: String,
+    suggested_name: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ClipboardImportResult> {
+    let db = state.db.clone();
+    let inbox_dir = state.inbox_dir.clone();
+    let cache_dir = state.cache_dir.clone();
+    let thumbnail_index = state.thumbnail_index.clone();
+    let hooks = state.hooks.clone();
+    let storage_profile = state
+        .storage_profile
+        .read()
+        .map(|profile| *profile)
+        .unwrap_or(StorageProfile::Hdd);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (bytes, ext) = decode_forge_image_payload(&base64)
+            .map_err(|error| error.context("Clipboard image is not valid"))?;
+        if mime_from_image_bytes(&bytes) == "application/octet-stream" {
+            return Err(AppError::InvalidInput(
+                "Clipboard data is not a supported image format".to_string(),
+            ));
+        }
+
+        std::fs::create_dir_all(&inbox_dir).map_err(|error| {
+            AppError::from(error).context(format!(
+                "Failed to create inbox directory {}",
+                inbox_dir.display()
+            ))
+        })?;
+
+        let stem = suggested_name
+            .as_deref()
+            .map(|name| {
+                Path::new(name.trim())
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            })
+            .filter(|stem| !stem.is_empty())
+            .map(|stem| sanitize_stem(&stem))
+            .unwrap_or_else(|| "clipboard".to_string());
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let target = unique_inbox_path(&inbox_dir, &format!("{}_{}", stem, stamp), ext);
+
+        std::fs::write(&target, &bytes).map_err(|error| {
+            AppError::from(error).context(format!("Failed to write {}", target.display()))
+        })?;
+
+        let scanned = scanner::scan_file(&target)
+            .ok_or_else(|| format!("Failed to stat imported file {}", target.display()))?;
+        let record = build_bulk_record(
+            &scanned.path,
+            scanned.file_mtime,
+            scanned.file_size,
+            &inbox_dir,
+        );
+        let has_metadata = !record.params.raw_metadata.trim().is_empty();
+        let filepath = record.filepath.clone();
+        let filename = record.filename.clone();
+        let tags = record.tags.clone();
+
+        db.bulk_upsert_with_tags(std::slice::from_ref(&record))
+            .map_err(|error| AppError::from(error).context("Failed to index imported image"))?;
+        let id = db
+            .get_image_id_by_filepath(&filepath)
+            .map_err(|error| AppError::from(error).context("Failed to look up imported image"))?
+            .ok_or_else(|| format!("Imported image was not indexed: {}", filepath))?;
+
+        let (thumbnail, results) =
+            image_processing::ensure_thumbnail(&target, &cache_dir, storage_profile);
+        save_thumbnail_results(&db, results);
+        match thumbnail {
+            Ok(thumb_path) => {
+                if let Ok(mut index) = thumbnail_index.write() {
+                    index.insert(thumb_path.to_string_lossy().to_string());
+                }
+            }
+            Err(error) => log::warn!("Thumbnail for imported {} failed: {}", filepath, error),
+        }
+
+        log::info!(
+            "Imported clipboard image {} (metadata={})",
+            filepath,
+            has_metadata
+        );
+        log_activity(
+            &db,
+            ActivityKind::Import,
+            format!("Imported {} from the clipboard", filename),
+            serde_json::json!({ "source": "clipboard" }),
+            std::slice::from_ref(&filepath),
+        );
+        fire_hooks(
+            &hooks,
+            HookEvent::ImagesIndexed,
+            serde_json::json!({
+                "source": "clipboard",
+                "directory": inbox_dir.to_string_lossy(),
+                "count": 1,
+                "filepath": filepath,
+            }),
+        );
+        Ok(ClipboardImportResult {
+            id,
+            filepath,
+            filename,
+            has_metadata,
+            tags,
+        })
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+fn unique_inbox_path(inbox_dir: &Path, stem: &str, ext: &str) -> PathBuf {
+    let mut candidate = inbox_dir.join(format!("{}.{}", stem, ext));
+    let mut counter = 1usize;
+    while candidate.exists() {
+        candidate = inbox_dir.join(format!("{}_{}.{}", stem, counter, ext));
+        counter += 1;
+    }
+    candidate
+}
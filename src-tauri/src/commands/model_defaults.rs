@@ -0,0 +1,71 @@
+// ────────────────────────── Model defaults ──────────────────────────
+
+/// Trims text fields and rejects values Forge could not use.
+fn normalize_model_defaults(mut defaults: ModelDefaults) -> AppResult<ModelDefaults> {
+    defaults.model = defaults.model.trim().to_string();
+    if defaults.model.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Choose a model or model family".to_string(),
+        ));
+    }
+    for value in [&mut defaults.sampler, &mut defaults.scheduler] {
+        *value = value
+            .take()
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty());
+    }
+    if defaults.steps == Some(0) {
+        return Err(AppError::InvalidInput(
+            "Default steps must be at least 1".to_string(),
+        ));
+    }
+    if defaults
+        .cfg_scale
+        .is_some_and(|cfg| !cfg.is_finite() || cfg < 0.0)
+    {
+        return Err(AppError::InvalidInput(
+            "Default CFG scale must be a positive number".to_string(),
+        ));
+    }
+    for (field, size) in [("width", defaults.width), ("height", defaults.height)] {
+        if size.is_some_and(|size| !(64..=8192).contains(&size)) {
+            return Err(AppError::InvalidInput(format!(
+                "Default {} must be between 64 and 8192 pixels",
+                field
+            )));
+        }
+    }
+    Ok(defaults)
+}
+
+#[tauri::command]
+pub fn list_model_defaults(state: tauri::State<'_, AppState>) -> AppResult<Vec<ModelDefaults>> {
+    Ok(state.db.list_model_defaults()?)
+}
+
+/// Saves defaults for a checkpoint name or a family such as `sdxl`. Forge
+/// sends fill in these settings when the source image lacks them, and prefer
+/// them over the source's when regenerating with a different model.
+#[tauri::command]
+pub fn set_model_defaults(
+    defaults: ModelDefaults,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<ModelDefaults>> {
+    let defaults = normalize_model_defaults(defaults)?;
+    state.db.set_model_defaults(&defaults)?;
+    Ok(state.db.list_model_defaults()?)
+}
+
+#[tauri::command]
+pub fn delete_model_defaults(
+    model: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<ModelDefaults>> {
+    if !state.db.delete_model_defaults(&model)? {
+        return Err(AppError::NotFound(format!(
+            "No defaults saved for {}",
+            model
+        )));
+    }
+    Ok(state.db.list_model_defaults()?)
+}
@@ -0,0 +1,35 @@
+// ────────────────────────── NSFW filter ──────────────────────────
+
+#[tauri::command]
+pub fn get_nsfw_settings(state: tauri::State<'_, AppState>) -> AppResult<crate::NsfwSettings> {
+    let settings = *state
+        .nsfw_settings
+        .read()
+        .map_err(|_| AppError::Internal("Failed to read NSFW settings".to_string()))?;
+    Ok(settings)
+}
+
+/// Saves the "hide NSFW" setting and applies it to queries immediately.
+#[tauri::command]
+pub fn set_nsfw_settings(
+    settings: crate::NsfwSettings,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<crate::NsfwSettings> {
+    settings.validate()?;
+    {
+        let mut lock = state
+            .nsfw_settings
+            .write()
+            .map_err(|_| AppError::Internal("Failed to update NSFW settings".to_string()))?;
+        *lock = settings;
+    }
+    crate::persist_nsfw_settings(&state.nsfw_settings_path, &settings)?;
+    log_settings_change(&state.db, "nsfw", "Changed the NSFW settings".to_string());
+    state.db.set_nsfw_filter(settings.filter_threshold());
+    log::info!(
+        "NSFW settings updated: hide_nsfw={}, threshold={}",
+        settings.hide_nsfw,
+        settings.threshold
+    );
+    Ok(settings)
+}
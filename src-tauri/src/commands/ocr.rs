@@ -0,0 +1,184 @@
+// ────────────────────────── Text in images (OCR) ──────────────────────────
+
+/// Images written per database transaction during an OCR pass.
+const OCR_BATCH: usize = 32;
+
+#[derive(Clone, Serialize)]
+struct OcrProgress {
+    task_id: u64,
+    current: usize,
+    total: usize,
+    read: usize,
+    failed: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct OcrComplete {
+    task_id: u64,
+    total: usize,
+    read: usize,
+    failed: usize,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+#[tauri::command]
+pub fn get_ocr_status(state: tauri::State<'_, AppState>) -> ocr::OcrStatus {
+    ocr::status(&state.ocr_model_dir)
+}
+
+/// Reads the visible text of images so captions and signatures become
+/// searchable, and returns the background task id.
+///
+/// Runs over `ids`, or over every image not read yet when `ids` is omitted.
+/// Text found replaces what an earlier pass stored. Emits to the calling
+/// window:
+/// - `ocr-progress`
+/// - `ocr-complete`
+#[tauri::command]
+pub fn ocr_images(
+    ids: Option<Vec<i64>>,
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<u64> {
+    let status = ocr::status(&state.ocr_model_dir);
+    if !status.available {
+        return Err(AppError::InvalidInput(
+            "Text recognition is not available in this build".to_string(),
+        ));
+    }
+    if !status.model_present {
+        return Err(AppError::FileMissing(format!(
+            "OCR model not found; place {}, {} and {} in {}",
+            ocr::DETECTOR_FILE,
+            ocr::RECOGNIZER_FILE,
+            ocr::KEYS_FILE,
+            status.model_dir
+        )));
+    }
+    let images: Vec<(i64, String)> = match ids {
+        Some(ids) if ids.is_empty() => {
+            return Err(AppError::InvalidInput(
+                "No images selected for text recognition".to_string(),
+            ));
+        }
+        Some(ids) => state
+            .db
+            .get_images_by_ids(&ids)?
+            .into_iter()
+            .map(|record| (record.id, record.filepath))
+            .collect(),
+        None => state.db.images_missing_ocr_text()?,
+    };
+
+    let db = state.db.clone();
+    let model_dir = state.ocr_model_dir.clone();
+    let task = state.tasks.start(
+        TaskKind::Ocr,
+        format!("Read image text ({} images)", images.len()),
+    );
+    let task_id = task.id();
+    let events = EventSink::for_window(&app, &window);
+
+    std::thread::Builder::new()
+        .name("ocr".into())
+        .spawn(move || {
+            let total = images.len();
+            let mut read = 0usize;
+            let mut failed = 0usize;
+            let mut cancelled = false;
+            let mut error = None;
+            let mut pending: Vec<(i64, String)> = Vec::with_capacity(OCR_BATCH);
+
+            task.set_message("Loading OCR models");
+            let mut engine = match ocr::OcrEngine::load(&model_dir) {
+                Ok(engine) => Some(engine),
+                Err(load_error) => {
+                    error = Some(load_error.to_string());
+                    None
+                }
+            };
+            task.set_progress(0, total);
+            task.set_message("");
+
+            for (index, (id, filepath)) in images.iter().enumerate() {
+                let Some(engine) = engine.as_mut() else {
+                    break;
+                };
+                if !task.checkpoint() {
+                    cancelled = true;
+                    break;
+                }
+                let path = Path::new(filepath);
+                if db.is_offline_path(filepath) || !path.exists() {
+                    failed += 1;
+                } else {
+                    match engine.read_text(path) {
+                        Ok(text) => pending.push((*id, text)),
+                        Err(read_error) => {
+                            failed += 1;
+                            log::warn!("Text recognition failed for {}: {}", filepath, read_error);
+                        }
+                    }
+                }
+                if pending.len() >= OCR_BATCH {
+                    if let Err(save_error) = db.set_ocr_text(&pending) {
+                        error = Some(save_error.to_string());
+                        break;
+                    }
+                    read += pending.len();
+                    pending.clear();
+                }
+                task.set_progress(index + 1, total);
+                let _ = events.emit(
+                    "ocr-progress",
+                    OcrProgress {
+                        task_id,
+                        current: index + 1,
+                        total,
+                        read,
+                        failed,
+                    },
+                );
+            }
+            // A cancelled run keeps what it already read.
+            if error.is_none() {
+                if let Err(save_error) = db.set_ocr_text(&pending) {
+                    error = Some(save_error.to_string());
+                } else {
+                    read += pending.len();
+                }
+            }
+
+            match &error {
+                Some(message) => {
+                    log::error!("Text recognition failed: {}", message);
+                    task.fail(message.clone());
+                }
+                None => log::info!(
+                    "Read text of {} of {} images ({} failed, cancelled={})",
+                    read,
+                    total,
+                    failed,
+                    cancelled
+                ),
+            }
+            let _ = events.emit(
+                "ocr-complete",
+                OcrComplete {
+                    task_id,
+                    total,
+                    read,
+                    failed,
+                    cancelled,
+                    error,
+                },
+            );
+        })
+        .map_err(|error| {
+            AppError::Internal(format!("Failed to start text recognition: {}", error))
+        })?;
+
+    Ok(task_id)
+}
@@ -0,0 +1,504 @@
+// ────────────────────────── Operation journal ──────────────────────────
+
+/// Completed steps held back before being marked done in one write.
+const JOURNAL_DONE_BATCH: usize = 64;
+
+/// Journal of one running bulk operation. Each step is written before the
+/// file is touched; dropping the journal closes it, so only a crash or a
+/// panic leaves steps behind for [`resume_pending_operations`].
+struct OperationJournal<'a> {
+    db: &'a Database,
+    id: i64,
+    /// Completed steps not yet marked done. Resuming re-checks every step
+    /// against the disk, so a lost mark only costs a redundant check.
+    done: Vec<i64>,
+}
+
+impl<'a> OperationJournal<'a> {
+    fn begin(db: &'a Database, kind: OperationKind, detail: serde_json::Value) -> AppResult<Self> {
+        let id = db
+            .begin_operation(kind, &detail)
+            .map_err(|error| AppError::from(error).context("Failed to journal the operation"))?;
+        Ok(Self {
+            db,
+            id,
+            done: Vec::new(),
+        })
+    }
+
+    fn intend(&self, steps: &[(i64, &str, Option<&str>)]) -> AppResult<()> {
+        self.db
+            .journal_operation_steps(self.id, steps)
+            .map_err(|error| AppError::from(error).context("Failed to journal the operation"))
+    }
+
+    fn done(&mut self, image_id: i64) {
+        self.done.push(image_id);
+        if self.done.len() >= JOURNAL_DONE_BATCH {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Err(error) = self.db.mark_operation_steps_done(self.id, &self.done) {
+            log::warn!("Failed to update operation journal {}: {}", self.id, error);
+        }
+        self.done.clear();
+    }
+}
+
+impl Drop for OperationJournal<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.flush();
+            self.db.release_operation(self.id);
+            return;
+        }
+        if let Err(error) = self.db.finish_operation(self.id) {
+            log::warn!("Failed to close operation journal {}: {}", self.id, error);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResumeOperationResult {
+    pub operation_id: i64,
+    pub kind: OperationKind,
+    pub rolled_back: bool,
+    /// Steps brought to their finished state, or back to their original
+    /// one when rolling back.
+    pub resolved: usize,
+    pub failed_paths: Vec<String>,
+}
+
+fn filename_and_directory(path: &Path) -> (String, String) {
+    (
+        path.file_name()
+            .map(|value| value.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path.parent()
+            .map(|value| value.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    )
+}
+
+/// Points the library at `to` if it still lists `image_id` at `from`.
+fn relocate_if_at(db: &Database, image_id: i64, from: &str, to: &Path) -> AppResult<()> {
+    if db.get_image_id_by_filepath(from)? == Some(image_id) {
+        let (filename, directory) = filename_and_directory(to);
+        db.update_image_location(image_id, &to.to_string_lossy(), &filename, &directory)?;
+    }
+    Ok(())
+}
+
+/// Brings one move or quarantine step to its finished state, or back to
+/// where it started. Returns true when the image's row should be dropped.
+fn resume_transfer_step(
+    db: &Database,
+    kind: OperationKind,
+    step: &OperationStep,
+    destination: &str,
+    roll_back: bool,
+    cache_dir: &Path,
+    thumbnail_index: &std::sync::Arc<std::sync::RwLock<std::collections::HashSet<String>>>,
+) -> AppResult<bool> {
+    let source = Path::new(&step.source);
+    let target = Path::new(destination);
+    let mut at_target = target.exists();
+    if at_target && source.exists() {
+        // A cross-device move removes the source only after the copy
+        // completes, so the copy may be partial and the source wins.
+        std::fs::remove_file(target)?;
+        at_target = false;
+    }
+
+    if roll_back {
+        if at_target {
+            move_file_with_fallback(target, source)?;
+        }
+        move_known_sidecars(target, source);
+        if kind == OperationKind::Move {
+            move_thumbnail_cache_file(target, source, cache_dir, thumbnail_index);
+            relocate_if_at(db, step.image_id, destination, source)?;
+        }
+        return Ok(false);
+    }
+
+    if !at_target {
+        if !source.exists() {
+            return Err(AppError::FileMissing(
+                "Neither the original nor the moved file exists".to_string(),
+            ));
+        }
+        move_file_with_fallback(source, target)?;
+    }
+    move_known_sidecars(source, target);
+    if kind == OperationKind::Move {
+        move_thumbnail_cache_file(source, target, cache_dir, thumbnail_index);
+        relocate_if_at(db, step.image_id, &step.source, target)?;
+        Ok(false)
+    } else {
+        remove_thumbnail_cache_file(source, cache_dir, thumbnail_index);
+        Ok(true)
+    }
+}
+
+/// Writes an interrupted export's ZIP again from the settings it was
+/// journaled with, or removes the partial one when rolling back.
+fn resume_convert(
+    db: &Database,
+    operation: &PendingOperation,
+    steps: &[OperationStep],
+    roll_back: bool,
+    storage_profile: StorageProfile,
+) -> AppResult<()> {
+    let detail = &operation.detail;
+    let output_path = Path::new(detail["output_path"].as_str().unwrap_or_default());
+    if roll_back {
+        if output_path.exists() {
+            std::fs::remove_file(output_path)?;
+        }
+        return Ok(());
+    }
+    let fmt = detail["format"].as_str().unwrap_or("original");
+    let quality = detail["quality"].as_u64().unwrap_or(85).clamp(1, 100) as u8;
+    let options: FileExportOptions =
+        serde_json::from_value(detail["options"].clone()).unwrap_or_default();
+    let (transform, name_template) = parse_file_export_options(&options, fmt)?;
+    let ids: Vec<i64> = steps.iter().map(|step| step.image_id).collect();
+    let records = load_export_records(db, &ids)?;
+    write_files_zip(
+        &records,
+        fmt,
+        quality,
+        transform,
+        name_template.as_ref(),
+        output_path,
+        &scan_pool(storage_profile),
+    )?;
+    Ok(())
+}
+
+/// Finishes, or rolls back, one pending operation and closes its journal.
+/// Files that are already deleted can't come back, so rolling back a
+/// delete keeps the files still on disk and drops the rows of the rest.
+fn resume_operation(
+    db: &Database,
+    operation: &PendingOperation,
+    roll_back: bool,
+    cache_dir: &Path,
+    thumbnail_index: &std::sync::Arc<std::sync::RwLock<std::collections::HashSet<String>>>,
+    storage_profile: StorageProfile,
+) -> AppResult<ResumeOperationResult> {
+    let steps = db.get_operation_steps(operation.id)?;
+    let delete_mode = match operation.detail["mode"].as_str() {
+        Some("permanent") => DeleteMode::Permanent,
+        _ => DeleteMode::Trash,
+    };
+    let mut resolved_paths = Vec::new();
+    let mut dropped_ids = Vec::new();
+    let mut failed_paths = Vec::new();
+    if operation.kind == OperationKind::Convert {
+        match resume_convert(db, operation, &steps, roll_back, storage_profile) {
+            Ok(()) => resolved_paths.extend(steps.iter().map(|step| step.source.clone())),
+            Err(error) => failed_paths.push(format!(
+                "{} ({})",
+                operation.detail["output_path"].as_str().unwrap_or_default(),
+                error
+            )),
+        }
+    } else {
+        for step in &steps {
+            if step.done && operation.kind == OperationKind::Move && !roll_back {
+                continue;
+            }
+            let outcome = match (operation.kind, step.destination.as_deref()) {
+                (OperationKind::Delete, _) => {
+                    let source = Path::new(&step.source);
+                    if source.exists() && roll_back {
+                        Ok(false)
+                    } else {
+                        let deleted = if source.exists() {
+                            delete_file_with_mode(source, delete_mode)
+                        } else {
+                            Ok(())
+                        };
+                        deleted.map(|_| {
+                            remove_known_sidecars(source);
+                            remove_thumbnail_cache_file(source, cache_dir, thumbnail_index);
+                            true
+                        })
+                    }
+                }
+                (kind, Some(destination)) => resume_transfer_step(
+                    db,
+                    kind,
+                    step,
+                    destination,
+                    roll_back,
+                    cache_dir,
+                    thumbnail_index,
+                ),
+                (_, None) => Err(AppError::Internal(
+                    "Journaled step has no destination".to_string(),
+                )),
+            };
+            match outcome {
+                Ok(drop_row) => {
+                    if drop_row {
+                        dropped_ids.push(step.image_id);
+                    }
+                    resolved_paths.push(step.source.clone());
+                }
+                Err(error) => failed_paths.push(format!("{} ({})", step.source, error)),
+            }
+        }
+    }
+    db.delete_images_by_ids(&dropped_ids)?;
+    db.finish_operation(operation.id)?;
+
+    let (activity, noun) = match operation.kind {
+        OperationKind::Move => (ActivityKind::Move, "move"),
+        OperationKind::Quarantine => (ActivityKind::Quarantine, "quarantine"),
+        OperationKind::Delete => (ActivityKind::Delete, "delete"),
+        OperationKind::Convert => (ActivityKind::Export, "export"),
+    };
+    log_activity(
+        db,
+        activity,
+        format!(
+            "{} an interrupted {} of {} image(s)",
+            if roll_back { "Rolled back" } else { "Finished" },
+            noun,
+            resolved_paths.len()
+        ),
+        serde_json::json!({
+            "resumed": true,
+            "rolled_back": roll_back,
+            "failed": failed_paths.len(),
+        }),
+        &resolved_paths,
+    );
+    Ok(ResumeOperationResult {
+        operation_id: operation.id,
+        kind: operation.kind,
+        rolled_back: roll_back,
+        resolved: resolved_paths.len(),
+        failed_paths,
+    })
+}
+
+/// Bulk moves, quarantines, deletes and converting exports a crash left
+/// half done.
+#[tauri::command]
+pub fn get_pending_operations(
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<PendingOperation>> {
+    Ok(state.db.get_pending_operations()?)
+}
+
+/// Finishes every pending operation, or with `roll_back` puts moved files
+/// back where they were, so the library and the disk agree again.
+#[tauri::command]
+pub fn resume_pending_operations(
+    roll_back: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<ResumeOperationResult>> {
+    let roll_back = roll_back.unwrap_or(false);
+    let storage_profile = state
+        .storage_profile
+        .read()
+        .map(|profile| *profile)
+        .unwrap_or(StorageProfile::Hdd);
+    let mut results = Vec::new();
+    for operation in state.db.get_pending_operations()? {
+        let result = resume_operation(
+            &state.db,
+            &operation,
+            roll_back,
+            &state.cache_dir,
+            &state.thumbnail_index,
+            storage_profile,
+        )
+        .map_err(|error| error.context(format!("Failed to resume operation {}", operation.id)))?;
+        log::info!(
+            "Resumed {} operation {}: {} step(s) resolved, {} failed",
+            operation.kind.as_str(),
+            operation.id,
+            result.resolved,
+            result.failed_paths.len()
+        );
+        results.push(result);
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod operation_journal_tests {
+    use super::*;
+    use crate::parser::GenerationParams;
+
+    #[test]
+    fn interrupted_operations_finish_or_roll_back_from_the_disk_state() {
+        let library = test_temp_dir("journal", "library");
+        let target = test_temp_dir("journal", "target");
+        let cache = test_temp_dir("journal", "cache");
+        let thumbnail_index =
+            std::sync::Arc::new(std::sync::RwLock::new(std::collections::HashSet::new()));
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        let mut ids = std::collections::HashMap::new();
+        for name in ["a.png", "b.png", "c.png", "d.png", "e.png", "f.png"] {
+            let path = library.join(name);
+            std::fs::write(&path, name).unwrap();
+            let id = db
+                .upsert_image(
+                    &path.to_string_lossy(),
+                    name,
+                    &library.to_string_lossy(),
+                    &GenerationParams::default(),
+                    Some(1),
+                )
+                .unwrap();
+            ids.insert(name, id);
+        }
+        let source = |name: &str| library.join(name).to_string_lossy().to_string();
+        let moved = |name: &str| target.join(name).to_string_lossy().to_string();
+        let resume = |roll_back: bool| {
+            let pending = db.get_pending_operations().unwrap();
+            assert_eq!(pending.len(), 1);
+            resume_operation(
+                &db,
+                &pending[0],
+                roll_back,
+                &cache,
+                &thumbnail_index,
+                StorageProfile::Hdd,
+            )
+            .unwrap()
+        };
+
+        // A journal that is dropped normally leaves nothing to resume, and a
+        // running one isn't pending.
+        {
+            let journal =
+                OperationJournal::begin(&db, OperationKind::Move, serde_json::json!({})).unwrap();
+            journal
+                .intend(&[(ids["a.png"], &source("a.png"), None)])
+                .unwrap();
+            assert!(db.get_pending_operations().unwrap().is_empty());
+        }
+        assert!(db.get_pending_operations().unwrap().is_empty());
+
+        // Crashed after moving a.png but before updating its row, and
+        // before touching b.png: finishing completes both.
+        let crashed = db
+            .begin_operation(OperationKind::Move, &serde_json::json!({}))
+            .unwrap();
+        db.journal_operation_steps(
+            crashed,
+            &[
+                (ids["a.png"], &source("a.png"), Some(&moved("a.png"))),
+                (ids["b.png"], &source("b.png"), Some(&moved("b.png"))),
+            ],
+        )
+        .unwrap();
+        std::fs::rename(source("a.png"), moved("a.png")).unwrap();
+        db.release_operation(crashed);
+        let pending = db.get_pending_operations().unwrap();
+        assert_eq!((pending[0].total_steps, pending[0].done_steps), (2, 0));
+        let finished = resume(false);
+        assert_eq!(finished.resolved, 2);
+        assert!(Path::new(&moved("b.png")).exists());
+        assert_eq!(
+            db.get_image_id_by_filepath(&moved("a.png")).unwrap(),
+            Some(ids["a.png"])
+        );
+        assert_eq!(
+            db.get_image_id_by_filepath(&moved("b.png")).unwrap(),
+            Some(ids["b.png"])
+        );
+        assert!(db.get_pending_operations().unwrap().is_empty());
+
+        // Rolling back undoes a finished step and drops a partial copy.
+        let crashed = db
+            .begin_operation(OperationKind::Move, &serde_json::json!({}))
+            .unwrap();
+        db.journal_operation_steps(
+            crashed,
+            &[
+                (ids["c.png"], &source("c.png"), Some(&moved("c.png"))),
+                (ids["d.png"], &source("d.png"), Some(&moved("d.png"))),
+            ],
+        )
+        .unwrap();
+        std::fs::rename(source("c.png"), moved("c.png")).unwrap();
+        relocate_if_at(&db, ids["c.png"], &source("c.png"), &target.join("c.png")).unwrap();
+        db.mark_operation_steps_done(crashed, &[ids["c.png"]])
+            .unwrap();
+        std::fs::write(moved("d.png"), "d.p").unwrap();
+        db.release_operation(crashed);
+        let rolled_back = resume(true);
+        assert_eq!(rolled_back.resolved, 2);
+        assert!(Path::new(&source("c.png")).exists());
+        assert!(!Path::new(&moved("d.png")).exists());
+        assert_eq!(std::fs::read_to_string(source("d.png")).unwrap(), "d.png");
+        assert_eq!(
+            db.get_image_id_by_filepath(&source("c.png")).unwrap(),
+            Some(ids["c.png"])
+        );
+
+        // A delete can't be undone: rolling back keeps the file still on
+        // disk and drops the row of the one already gone.
+        let crashed = db
+            .begin_operation(
+                OperationKind::Delete,
+                &serde_json::json!({ "mode": "permanent" }),
+            )
+            .unwrap();
+        db.journal_operation_steps(
+            crashed,
+            &[
+                (ids["e.png"], &source("e.png"), None),
+                (ids["f.png"], &source("f.png"), None),
+            ],
+        )
+        .unwrap();
+        std::fs::remove_file(source("e.png")).unwrap();
+        db.release_operation(crashed);
+        resume(true);
+        assert_eq!(db.get_image_id_by_filepath(&source("e.png")).unwrap(), None);
+        assert_eq!(
+            db.get_image_id_by_filepath(&source("f.png")).unwrap(),
+            Some(ids["f.png"])
+        );
+        assert!(Path::new(&source("f.png")).exists());
+
+        // An interrupted export is written again from its settings, or its
+        // partial ZIP removed.
+        let zip_path = target.join("export.zip");
+        let export = serde_json::json!({
+            "format": "original",
+            "quality": 85,
+            "options": {},
+            "output_path": zip_path.to_string_lossy(),
+        });
+        for roll_back in [false, true] {
+            let crashed = db.begin_operation(OperationKind::Convert, &export).unwrap();
+            db.journal_operation_steps(crashed, &[(ids["f.png"], &source("f.png"), None)])
+                .unwrap();
+            std::fs::write(&zip_path, "partial").unwrap();
+            db.release_operation(crashed);
+            assert_eq!(resume(roll_back).resolved, 1);
+            if roll_back {
+                assert!(!zip_path.exists());
+            } else {
+                let zip = zip::ZipArchive::new(std::fs::File::open(&zip_path).unwrap()).unwrap();
+                assert_eq!(zip.len(), 1);
+            }
+        }
+
+        for dir in [library, target, cache] {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// ────────────────────────── Performance ──────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PerformanceReport {
+    pub storage_profile: StorageProfile,
+    pub cpu_count: usize,
+    pub scan_threads: usize,
+    pub thumbnail_threads: usize,
+    pub db_pool_size: u32,
+    #[serde(flatten)]
+    pub counters: perf::CounterReport,
+}
+
+/// Timing counters collected since startup, alongside the concurrency
+/// settings they were measured under. Everything stays local.
+#[tauri::command]
+pub fn get_performance_report(state: tauri::State<'_, AppState>) -> AppResult<PerformanceReport> {
+    let storage_profile = state
+        .storage_profile
+        .read()
+        .map(|profile| *profile)
+        .unwrap_or(StorageProfile::Hdd);
+    Ok(PerformanceReport {
+        storage_profile,
+        cpu_count: std::thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1),
+        scan_threads: scan_threads(storage_profile),
+        thumbnail_threads: image_processing::io_threads(storage_profile),
+        db_pool_size: state.db.pool_size(),
+        counters: perf::snapshot(),
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PerformanceSettingsState {
+    pub settings: crate::PerformanceSettings,
+    pub effective_scan_threads: usize,
+    pub effective_thumbnail_threads: usize,
+    pub active_db_pool_size: u32,
+    /// The saved pool size differs from the running pool.
+    pub restart_required: bool,
+}
+
+fn performance_settings_state(
+    state: &AppState,
+    settings: crate::PerformanceSettings,
+) -> PerformanceSettingsState {
+    let storage_profile = state
+        .storage_profile
+        .read()
+        .map(|profile| *profile)
+        .unwrap_or(StorageProfile::Hdd);
+    let active_db_pool_size = state.db.pool_size();
+    PerformanceSettingsState {
+        settings,
+        effective_scan_threads: scan_threads(storage_profile),
+        effective_thumbnail_threads: image_processing::io_threads(storage_profile),
+        active_db_pool_size,
+        restart_required: crate::database::db_pool_size(storage_profile, settings.db_pool_size)
+            != active_db_pool_size,
+    }
+}
+
+#[tauri::command]
+pub fn get_performance_settings(
+    state: tauri::State<'_, AppState>,
+) -> AppResult<PerformanceSettingsState> {
+    let settings = *state
+        .performance_settings
+        .read()
+        .map_err(|_| AppError::Internal("Failed to read performance settings".to_string()))?;
+    Ok(performance_settings_state(&state, settings))
+}
+
+/// Saves concurrency overrides. Scan and thumbnail threads apply to the next
+/// batch of work; the DB pool size applies after a restart.
+#[tauri::command]
+pub fn set_performance_settings(
+    settings: crate::PerformanceSettings,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<PerformanceSettingsState> {
+    settings.validate()?;
+    crate::persist_performance_settings(&state.performance_settings_path, &settings)?;
+    {
+        let mut lock = state
+            .performance_settings
+            .write()
+            .map_err(|_| AppError::Internal("Failed to update performance settings".to_string()))?;
+        *lock = settings;
+    }
+    log_settings_change(
+        &state.db,
+        "performance",
+        "Changed the performance settings".to_string(),
+    );
+    settings.apply_overrides();
+    log::info!(
+        "Performance settings updated: scan_threads={:?}, thumbnail_threads={:?}, db_pool_size={:?}, preview_budget_megapixels={:?}, decode_memory_budget_mb={:?}",
+        settings.scan_threads,
+        settings.thumbnail_threads,
+        settings.db_pool_size,
+        settings.preview_budget_megapixels,
+        settings.decode_memory_budget_mb
+    );
+    Ok(performance_settings_state(&state, settings))
+}
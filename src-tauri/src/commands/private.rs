@@ -0,0 +1,254 @@
+// ────────────────────────── Private folders ──────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrivateStatus {
+    has_passcode: bool,
+    unlocked: bool,
+    /// Private directory paths; empty while locked so their names stay hidden.
+    directories: Vec<String>,
+    directory_count: usize,
+}
+
+fn private_status(state: &AppState) -> AppResult<PrivateStatus> {
+    let settings = state
+        .private_settings
+        .read()
+        .map_err(|_| AppError::Internal("Failed to read private settings".to_string()))?;
+    let unlocked = state.private_unlocked.load(Ordering::SeqCst);
+    Ok(PrivateStatus {
+        has_passcode: settings.has_passcode(),
+        unlocked,
+        directories: if unlocked {
+            settings.directories.clone()
+        } else {
+            Vec::new()
+        },
+        directory_count: settings.directories.len(),
+    })
+}
+
+/// Pushes the current lock state down to the query layer.
+fn apply_private_filter(state: &AppState, settings: &crate::PrivateSettings) {
+    if state.private_unlocked.load(Ordering::SeqCst) {
+        state.db.set_private_directories(&[]);
+    } else {
+        state.db.set_private_directories(&settings.directories);
+    }
+}
+
+/// Wrong passcodes allowed before attempts start being delayed.
+const FREE_PASSCODE_ATTEMPTS: u32 = 5;
+const PASSCODE_BACKOFF_START: std::time::Duration = std::time::Duration::from_secs(30);
+const PASSCODE_BACKOFF_MAX: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+#[derive(Default)]
+struct PasscodeFailures {
+    count: u32,
+    retry_at: Option<std::time::Instant>,
+}
+
+/// Slows down passcode guessing: after a few misses each attempt has to
+/// wait, twice as long after every further miss. A correct passcode resets
+/// it.
+#[derive(Default)]
+pub struct PasscodeThrottle {
+    failures: std::sync::Mutex<PasscodeFailures>,
+}
+
+impl PasscodeThrottle {
+    /// Runs `verify` unless attempts are on hold, and counts a miss.
+    fn attempt(&self, verify: impl FnOnce() -> bool) -> AppResult<()> {
+        self.attempt_at(std::time::Instant::now(), verify)
+    }
+
+    fn attempt_at(&self, now: std::time::Instant, verify: impl FnOnce() -> bool) -> AppResult<()> {
+        let mut failures = self
+            .failures
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to read passcode attempts".to_string()))?;
+        if let Some(retry_at) = failures.retry_at.filter(|retry_at| *retry_at > now) {
+            return Err(AppError::PermissionDenied(format!(
+                "Too many incorrect passcodes; try again in {} seconds",
+                (retry_at - now).as_secs().max(1)
+            )));
+        }
+        if verify() {
+            *failures = PasscodeFailures::default();
+            return Ok(());
+        }
+        failures.count += 1;
+        if failures.count >= FREE_PASSCODE_ATTEMPTS {
+            let doublings = (failures.count - FREE_PASSCODE_ATTEMPTS).min(16);
+            let delay = PASSCODE_BACKOFF_START
+                .saturating_mul(1 << doublings)
+                .min(PASSCODE_BACKOFF_MAX);
+            failures.retry_at = Some(now + delay);
+        }
+        Err(AppError::PermissionDenied("Incorrect passcode".to_string()))
+    }
+}
+
+#[tauri::command]
+pub fn get_private_status(state: tauri::State<'_, AppState>) -> AppResult<PrivateStatus> {
+    private_status(&state)
+}
+
+/// Sets or changes the passcode. Changing it requires the current one.
+#[tauri::command]
+pub fn set_private_passcode(
+    passcode: String,
+    current_passcode: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<PrivateStatus> {
+    {
+        let mut settings = state
+            .private_settings
+            .write()
+            .map_err(|_| AppError::Internal("Failed to update private settings".to_string()))?;
+        if settings.has_passcode() {
+            state
+                .passcode_throttle
+                .attempt(|| settings.verify_passcode(current_passcode.as_deref().unwrap_or("")))
+                .map_err(|error| error.context("Current passcode was not accepted"))?;
+        }
+        let mut updated = settings.clone();
+        updated.set_passcode(&passcode)?;
+        crate::persist_private_settings(&state.private_settings_path, &updated)?;
+        *settings = updated;
+    }
+    log::info!("Private folder passcode updated");
+    log_settings_change(
+        &state.db,
+        "private_passcode",
+        "Changed the private folder passcode".to_string(),
+    );
+    private_status(&state)
+}
+
+/// Marks a directory (and everything below it) private, or clears the flag.
+/// Clearing requires an unlocked session.
+#[tauri::command]
+pub fn set_directory_private(
+    directory: String,
+    private: bool,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<PrivateStatus> {
+    let directory = directory.trim().to_string();
+    if directory.is_empty() {
+        return Err(AppError::InvalidInput("Directory is required".to_string()));
+    }
+    {
+        let mut settings = state
+            .private_settings
+            .write()
+            .map_err(|_| AppError::Internal("Failed to update private settings".to_string()))?;
+        if !settings.has_passcode() {
+            return Err(AppError::InvalidInput(
+                "Set a passcode before marking folders private".to_string(),
+            ));
+        }
+        let mut updated = settings.clone();
+        if private {
+            if !updated.directories.contains(&directory) {
+                updated.directories.push(directory.clone());
+            }
+        } else {
+            if !state.private_unlocked.load(Ordering::SeqCst) {
+                return Err(AppError::PermissionDenied(
+                    "Unlock private folders first".to_string(),
+                ));
+            }
+            updated.directories.retain(|entry| *entry != directory);
+        }
+        crate::persist_private_settings(&state.private_settings_path, &updated)?;
+        *settings = updated;
+        apply_private_filter(&state, &settings);
+    }
+    // Neither log names the folder: both can be read while private folders
+    // are locked.
+    log::info!("Private folders updated (private={})", private);
+    log_settings_change(
+        &state.db,
+        "private_folders",
+        if private {
+            "Marked a folder private".to_string()
+        } else {
+            "Made a private folder public again".to_string()
+        },
+    );
+    private_status(&state)
+}
+
+/// Shows private folders for the rest of this session.
+#[tauri::command]
+pub fn unlock_private(
+    passcode: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<PrivateStatus> {
+    {
+        let mut settings = state
+            .private_settings
+            .write()
+            .map_err(|_| AppError::Internal("Failed to read private settings".to_string()))?;
+        if let Err(error) = state
+            .passcode_throttle
+            .attempt(|| settings.verify_passcode(&passcode))
+        {
+            log::warn!("Rejected private folder unlock attempt");
+            return Err(error);
+        }
+        if settings.passcode_needs_rehash() {
+            // Upgrade a hash saved before Argon2 while the passcode is at hand.
+            let mut updated = settings.clone();
+            updated.set_passcode(&passcode)?;
+            match crate::persist_private_settings(&state.private_settings_path, &updated) {
+                Ok(()) => *settings = updated,
+                Err(error) => log::warn!("Failed to upgrade the passcode hash: {}", error),
+            }
+        }
+        state.private_unlocked.store(true, Ordering::SeqCst);
+        apply_private_filter(&state, &settings);
+    }
+    private_status(&state)
+}
+
+#[tauri::command]
+pub fn lock_private(state: tauri::State<'_, AppState>) -> AppResult<PrivateStatus> {
+    {
+        let settings = state
+            .private_settings
+            .read()
+            .map_err(|_| AppError::Internal("Failed to read private settings".to_string()))?;
+        state.private_unlocked.store(false, Ordering::SeqCst);
+        apply_private_filter(&state, &settings);
+    }
+    private_status(&state)
+}
+
+#[cfg(test)]
+mod private_tests {
+    use super::*;
+
+    #[test]
+    fn passcode_attempts_back_off_after_repeated_misses() {
+        let throttle = PasscodeThrottle::default();
+        let start = std::time::Instant::now();
+        for _ in 0..FREE_PASSCODE_ATTEMPTS {
+            assert!(throttle.attempt_at(start, || false).is_err());
+        }
+        // Even the right passcode is turned away until the delay has passed.
+        assert!(throttle.attempt_at(start, || true).is_err());
+
+        let retry = start + PASSCODE_BACKOFF_START;
+        assert!(throttle.attempt_at(retry, || false).is_err());
+        let doubled = retry + PASSCODE_BACKOFF_START * 2;
+        assert!(throttle
+            .attempt_at(doubled - std::time::Duration::from_secs(1), || true)
+            .is_err());
+        assert!(throttle.attempt_at(doubled, || true).is_ok());
+
+        // A success starts the count over.
+        assert!(throttle.attempt_at(doubled, || false).is_err());
+        assert!(throttle.attempt_at(doubled, || true).is_ok());
+    }
+}
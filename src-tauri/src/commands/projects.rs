@@ -0,0 +1,131 @@
+// ────────────────────────── Projects ──────────────────────────
+
+/// Applies `update` and saves the result, then scopes queries to whichever
+/// project is active.
+fn update_project_settings(
+    state: &AppState,
+    update: impl FnOnce(&mut crate::projects::ProjectSettings) -> AppResult<()>,
+) -> AppResult<crate::projects::ProjectSettings> {
+    let mut lock = state
+        .project_settings
+        .write()
+        .map_err(|_| AppError::Internal("Failed to update projects".to_string()))?;
+    let mut settings = lock.clone();
+    update(&mut settings)?;
+    crate::persist_project_settings(&state.project_settings_path, &settings)?;
+    log_settings_change(&state.db, "projects", "Changed the projects".to_string());
+    state
+        .db
+        .set_project_directories(&settings.active_directories());
+    *lock = settings.clone();
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn list_projects(
+    state: tauri::State<'_, AppState>,
+) -> AppResult<crate::projects::ProjectSettings> {
+    state
+        .project_settings
+        .read()
+        .map(|settings| settings.clone())
+        .map_err(|_| AppError::Internal("Failed to read projects".to_string()))
+}
+
+/// Creates or replaces a project. Pass `previous_name` to rename one; the
+/// new name must not clash with another project.
+#[tauri::command]
+pub fn save_project(
+    project: crate::projects::Project,
+    previous_name: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<crate::projects::ProjectSettings> {
+    let mut project = project.normalized()?;
+    update_project_settings(&state, |settings| {
+        let target = previous_name
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or(&project.name)
+            .to_string();
+        let clash = settings.projects.iter().any(|existing| {
+            existing.name.eq_ignore_ascii_case(&project.name)
+                && !existing.name.eq_ignore_ascii_case(&target)
+        });
+        if clash {
+            return Err(AppError::InvalidInput(format!(
+                "A project named '{}' already exists",
+                project.name
+            )));
+        }
+        match settings
+            .projects
+            .iter_mut()
+            .find(|existing| existing.name.eq_ignore_ascii_case(&target))
+        {
+            Some(existing) => {
+                project.created_at = existing.created_at;
+                *existing = project.clone();
+            }
+            None => {
+                project.created_at = chrono::Utc::now().timestamp();
+                settings.projects.push(project.clone());
+            }
+        }
+        if settings
+            .active
+            .as_deref()
+            .is_some_and(|active| active.eq_ignore_ascii_case(&target))
+        {
+            settings.active = Some(project.name.clone());
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn delete_project(
+    name: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<crate::projects::ProjectSettings> {
+    update_project_settings(&state, |settings| {
+        let name = name.trim();
+        let before = settings.projects.len();
+        settings
+            .projects
+            .retain(|project| !project.name.eq_ignore_ascii_case(name));
+        if settings.projects.len() == before {
+            return Err(AppError::NotFound(format!("Project not found: {}", name)));
+        }
+        if settings.active_project().is_none() {
+            settings.active = None;
+        }
+        Ok(())
+    })
+}
+
+/// Makes `name` the active project, confining the gallery, facets and stats
+/// to its folders. `None` goes back to the whole library.
+#[tauri::command]
+pub fn switch_project(
+    name: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<crate::projects::ProjectSettings> {
+    let settings = update_project_settings(&state, |settings| {
+        settings.active = match name.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(name) => Some(
+                settings
+                    .find(name)
+                    .ok_or_else(|| AppError::NotFound(format!("Project not found: {}", name)))?
+                    .name
+                    .clone(),
+            ),
+        };
+        Ok(())
+    })?;
+    log::info!(
+        "Active project: {}",
+        settings.active.as_deref().unwrap_or("(whole library)")
+    );
+    Ok(settings)
+}
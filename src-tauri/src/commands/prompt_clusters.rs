@@ -0,0 +1,126 @@
+// ────────────────────────── Prompt clusters ──────────────────────────
+
+#[derive(Clone, Serialize)]
+struct PromptClustersComplete {
+    task_id: u64,
+    /// Images given a cluster.
+    clustered: usize,
+    clusters: usize,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+/// Groups the library's prompts into themes in the background and returns
+/// the task id. Each image gets the cluster of its prompt, replacing the
+/// previous run; `cluster_count` defaults to one scaled to the number of
+/// distinct prompts. Emits `prompt-clusters-complete` to the calling window.
+#[tauri::command]
+pub fn cluster_prompts(
+    cluster_count: Option<u32>,
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<u64> {
+    if let Some(count) = cluster_count {
+        if !(2..=crate::prompt_clusters::MAX_CLUSTERS as u32).contains(&count) {
+            return Err(AppError::InvalidInput(format!(
+                "Cluster count must be between 2 and {}, got {}",
+                crate::prompt_clusters::MAX_CLUSTERS,
+                count
+            )));
+        }
+    }
+
+    let db = state.db.clone();
+    let task = state
+        .tasks
+        .start(TaskKind::PromptClusters, "Cluster prompts");
+    let task_id = task.id();
+    let events = EventSink::for_window(&app, &window);
+
+    std::thread::Builder::new()
+        .name("prompt-clusters".into())
+        .spawn(move || {
+            let complete =
+                |clustered: usize, clusters: usize, cancelled: bool, error: Option<String>| {
+                    let _ = events.emit(
+                        "prompt-clusters-complete",
+                        PromptClustersComplete {
+                            task_id,
+                            clustered,
+                            clusters,
+                            cancelled,
+                            error,
+                        },
+                    );
+                };
+            let fail = |message: String| {
+                log::error!("Prompt clustering failed: {}", message);
+                task.fail(message.clone());
+                complete(0, 0, false, Some(message));
+            };
+
+            task.set_message("Reading prompts");
+            let images = match db.prompts_for_clustering() {
+                Ok(images) => images,
+                Err(error) => return fail(error.to_string()),
+            };
+            let mut prompt_index: std::collections::HashMap<&str, usize> =
+                std::collections::HashMap::new();
+            let mut prompts: Vec<(String, u32)> = Vec::new();
+            let image_prompts: Vec<usize> = images
+                .iter()
+                .map(|(_, prompt)| {
+                    let index = *prompt_index.entry(prompt.as_str()).or_insert_with(|| {
+                        prompts.push((prompt.clone(), 0));
+                        prompts.len() - 1
+                    });
+                    prompts[index].1 += 1;
+                    index
+                })
+                .collect();
+
+            let count = cluster_count
+                .map(|count| count as usize)
+                .unwrap_or_else(|| crate::prompt_clusters::default_cluster_count(prompts.len()));
+            task.set_message(format!(
+                "{} distinct prompts into {} clusters",
+                prompts.len(),
+                count
+            ));
+            let Some(clustering) =
+                crate::prompt_clusters::cluster_prompts(&prompts, count, || task.checkpoint())
+            else {
+                log::info!("Prompt clustering cancelled");
+                return complete(0, 0, true, None);
+            };
+
+            let assignments: Vec<(i64, usize)> = images
+                .iter()
+                .zip(&image_prompts)
+                .filter_map(|((id, _), prompt)| Some((*id, clustering.assignments[*prompt]?)))
+                .collect();
+            if let Err(error) = db.replace_prompt_clusters(&clustering.clusters, &assignments) {
+                return fail(error.to_string());
+            }
+            log::info!(
+                "Clustered {} of {} images into {} prompt clusters",
+                assignments.len(),
+                images.len(),
+                clustering.clusters.len()
+            );
+            complete(assignments.len(), clustering.clusters.len(), false, None);
+        })
+        .map_err(|error| {
+            AppError::Internal(format!("Failed to start prompt clustering: {}", error))
+        })?;
+
+    Ok(task_id)
+}
+
+/// Clusters from the last `cluster_prompts` run, largest first, for
+/// browsing the library by theme.
+#[tauri::command]
+pub fn get_prompt_clusters(state: tauri::State<'_, AppState>) -> AppResult<Vec<PromptCluster>> {
+    state.db.get_prompt_clusters().map_err(AppError::from)
+}
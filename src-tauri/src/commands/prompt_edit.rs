@@ -0,0 +1,290 @@
+// ────────────────────────── Bulk prompt edits ──────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BulkEditPromptsResult {
+    pub requested: usize,
+    pub updated: usize,
+    pub updated_ids: Vec<i64>,
+    pub skipped_locked_ids: Vec<i64>,
+    /// PNGs whose embedded `parameters` were rewritten to match.
+    pub files_rewritten: usize,
+    /// Files that could not be rewritten: not PNG, metadata in a format
+    /// other than WebUI parameters, or a write error.
+    pub failed_paths: Vec<String>,
+}
+
+struct PlannedPromptEdit {
+    rewrite: crate::database::PromptRewrite,
+    filepath: String,
+    /// Whether `raw_metadata` was patched, i.e. the file can be rewritten too.
+    parameters_patched: bool,
+}
+
+fn plan_prompt_edit(
+    record: ImageRecord,
+    edit: &crate::prompt_edit::PromptEdit,
+    include_negative: bool,
+) -> Option<PlannedPromptEdit> {
+    let prompt = edit.apply(&record.prompt);
+    let negative_prompt = if include_negative {
+        edit.apply(&record.negative_prompt)
+    } else {
+        None
+    };
+    if prompt.is_none() && negative_prompt.is_none() {
+        return None;
+    }
+    let prompt = prompt.unwrap_or_else(|| record.prompt.clone());
+    let negative_prompt = negative_prompt.unwrap_or_else(|| record.negative_prompt.clone());
+    let patched = crate::prompt_edit::rewrite_parameters(
+        &record.raw_metadata,
+        &record.prompt,
+        &prompt,
+        &record.negative_prompt,
+        &negative_prompt,
+    );
+    Some(PlannedPromptEdit {
+        parameters_patched: patched.is_some(),
+        rewrite: crate::database::PromptRewrite {
+            id: record.id,
+            old_prompt: record.prompt,
+            prompt,
+            negative_prompt,
+            raw_metadata: patched.unwrap_or(record.raw_metadata),
+        },
+        filepath: record.filepath,
+    })
+}
+
+pub(crate) fn bulk_edit_prompt_records(
+    db: &crate::database::Database,
+    ids: &[i64],
+    edit: &crate::prompt_edit::PromptEdit,
+    include_negative: bool,
+    rewrite_files: bool,
+) -> AppResult<BulkEditPromptsResult> {
+    let mut result = BulkEditPromptsResult {
+        requested: ids.len(),
+        ..Default::default()
+    };
+    let mut planned = Vec::new();
+    for chunk in ids.chunks(EXPORT_LOOKUP_CHUNK) {
+        for record in db.get_images_by_ids(chunk)? {
+            if record.is_locked {
+                result.skipped_locked_ids.push(record.id);
+                continue;
+            }
+            planned.extend(plan_prompt_edit(record, edit, include_negative));
+        }
+    }
+
+    let rewrites: Vec<_> = planned.iter().map(|plan| plan.rewrite.clone()).collect();
+    result.updated = db
+        .rewrite_prompts(&rewrites)
+        .map_err(|error| AppError::from(error).context("Failed to save edited prompts"))?;
+    result.updated_ids = rewrites.iter().map(|rewrite| rewrite.id).collect();
+
+    if rewrite_files {
+        for plan in &planned {
+            let path = Path::new(&plan.filepath);
+            let is_png = path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+            if !is_png || !plan.parameters_patched || db.is_offline_path(&plan.filepath) {
+                result.failed_paths.push(plan.filepath.clone());
+                continue;
+            }
+            match scanner::replace_png_text_chunk(path, "parameters", &plan.rewrite.raw_metadata) {
+                Ok(()) => result.files_rewritten += 1,
+                Err(error) => {
+                    log::warn!(
+                        "Failed to rewrite parameters in {}: {}",
+                        plan.filepath,
+                        error
+                    );
+                    result.failed_paths.push(plan.filepath.clone());
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Finds and replaces text in the stored prompts of the selected images,
+/// e.g. to drop an old trigger word. `regex` switches `find` to a regular
+/// expression; `include_negative` edits negative prompts as well. With
+/// `rewrite_files`, PNGs get their embedded parameters rewritten so a
+/// rescan keeps the edit. Locked images are left alone.
+#[tauri::command]
+pub async fn bulk_edit_prompts(
+    ids: Vec<i64>,
+    find: String,
+    replace: String,
+    regex: bool,
+    include_negative: Option<bool>,
+    rewrite_files: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<BulkEditPromptsResult> {
+    let edit = crate::prompt_edit::PromptEdit::new(&find, &replace, regex)?;
+    let mut ids = ids;
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() {
+        return Ok(BulkEditPromptsResult::default());
+    }
+    refresh_volume_status(&state, false);
+    let db = state.db.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        bulk_edit_prompt_records(
+            &db,
+            &ids,
+            &edit,
+            include_negative.unwrap_or(false),
+            rewrite_files.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|error| error.to_string())??;
+    log::info!(
+        "Edited prompts of {} of {} images ({} files rewritten, {} locked)",
+        result.updated,
+        result.requested,
+        result.files_rewritten,
+        result.skipped_locked_ids.len()
+    );
+    Ok(result)
+}
+
+/// Before/after pairs returned by a preview; enough to eyeball a pattern
+/// without shipping every match to the UI.
+const PROMPT_EDIT_PREVIEW_SAMPLES: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptEditSample {
+    pub id: i64,
+    pub filename: String,
+    /// `"prompt"` or `"negative_prompt"`.
+    pub field: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PromptEditPreview {
+    /// Images with a prompt that were checked.
+    pub scanned: usize,
+    /// Images the edit would change.
+    pub affected: usize,
+    pub affected_prompts: usize,
+    pub affected_negative_prompts: usize,
+    /// Of `affected`, images that are locked and would be skipped.
+    pub locked: usize,
+    pub samples: Vec<PromptEditSample>,
+}
+
+pub(crate) fn preview_prompt_edit_records(
+    db: &crate::database::Database,
+    edit: &crate::prompt_edit::PromptEdit,
+    include_negative: bool,
+) -> AppResult<PromptEditPreview> {
+    let mut preview = PromptEditPreview::default();
+    preview.scanned = db.for_each_prompt(|stored| {
+        let prompt = edit.apply(&stored.prompt);
+        let negative_prompt = if include_negative {
+            edit.apply(&stored.negative_prompt)
+        } else {
+            None
+        };
+        if prompt.is_none() && negative_prompt.is_none() {
+            return;
+        }
+        preview.affected += 1;
+        preview.affected_prompts += usize::from(prompt.is_some());
+        preview.affected_negative_prompts += usize::from(negative_prompt.is_some());
+        preview.locked += usize::from(stored.is_locked);
+        for (field, before, after) in [
+            ("prompt", &stored.prompt, prompt),
+            ("negative_prompt", &stored.negative_prompt, negative_prompt),
+        ] {
+            let Some(after) = after else { continue };
+            if preview.samples.len() < PROMPT_EDIT_PREVIEW_SAMPLES {
+                preview.samples.push(PromptEditSample {
+                    id: stored.id,
+                    filename: stored.filename.clone(),
+                    field,
+                    before: before.clone(),
+                    after,
+                });
+            }
+        }
+    })?;
+    Ok(preview)
+}
+
+/// Dry run of [`bulk_edit_prompts`] over the whole library: counts the
+/// images a find/replace would touch and returns a few before/after samples,
+/// so a risky regex can be checked before anything is written. `replace`
+/// defaults to removing the match.
+#[tauri::command]
+pub async fn preview_prompt_edit(
+    find: String,
+    replace: Option<String>,
+    regex: bool,
+    include_negative: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<PromptEditPreview> {
+    let edit =
+        crate::prompt_edit::PromptEdit::new(&find, replace.as_deref().unwrap_or_default(), regex)?;
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        preview_prompt_edit_records(&db, &edit, include_negative.unwrap_or(false))
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+#[cfg(test)]
+mod prompt_edit_tests {
+    use super::*;
+    use crate::parser::GenerationParams;
+
+    #[test]
+    fn preview_counts_matches_without_writing() {
+        let db = crate::database::Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("db init failed");
+        for (index, (prompt, negative_prompt)) in [
+            ("a cat, <lora:ink:0.6>", "blurry"),
+            ("a dog", "<lora:bad:0.3>"),
+            ("a bird", "lowres"),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let params = GenerationParams {
+                prompt: prompt.to_string(),
+                negative_prompt: negative_prompt.to_string(),
+                ..Default::default()
+            };
+            let name = format!("{index}.png");
+            db.upsert_image(&format!("/img/{name}"), &name, "/img", &params, Some(1))
+                .unwrap();
+        }
+
+        let edit = crate::prompt_edit::PromptEdit::new(r"<lora:\w+:0\.\d+>", "", true).unwrap();
+        let preview = preview_prompt_edit_records(&db, &edit, false).unwrap();
+        assert_eq!((preview.scanned, preview.affected), (3, 1));
+        assert_eq!(preview.samples[0].before, "a cat, <lora:ink:0.6>");
+        assert_eq!(preview.samples[0].after, "a cat");
+
+        let preview = preview_prompt_edit_records(&db, &edit, true).unwrap();
+        assert_eq!(preview.affected, 2);
+        assert_eq!(preview.affected_negative_prompts, 1);
+        assert_eq!(preview.samples.len(), 2);
+        // Newest first, so the dog's negative prompt leads.
+        assert_eq!(preview.samples[0].field, "negative_prompt");
+        assert_eq!(preview.samples[0].after, "");
+
+        let unchanged = db.get_images_by_ids(&[1]).unwrap();
+        assert_eq!(unchanged[0].prompt, "a cat, <lora:ink:0.6>");
+    }
+}
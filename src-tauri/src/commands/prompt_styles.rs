@@ -0,0 +1,86 @@
+// ────────────────────────── Prompt styles ──────────────────────────
+
+/// styles.csv is a few KB in practice; anything this large is the wrong file.
+const MAX_STYLES_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptStyleImportResult {
+    pub imported: usize,
+    /// Rows that were malformed or had no name.
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedPromptStyle {
+    pub prompt: String,
+    pub negative_prompt: String,
+}
+
+/// Loads the WebUI's styles.csv, replacing any previously imported styles.
+#[tauri::command]
+pub async fn import_styles(
+    path: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<PromptStyleImportResult> {
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = PathBuf::from(path.trim());
+        let size = std::fs::metadata(&path)
+            .map_err(|error| {
+                AppError::from(error).context(format!("Failed to read {}", path.display()))
+            })?
+            .len();
+        if size > MAX_STYLES_BYTES {
+            return Err(AppError::InvalidInput(format!(
+                "{} is too large to be a styles file",
+                path.display()
+            )));
+        }
+        let bytes = std::fs::read(&path).map_err(|error| {
+            AppError::from(error).context(format!("Failed to read {}", path.display()))
+        })?;
+        let (styles, skipped) = crate::prompt_styles::parse_csv(&bytes);
+        if styles.is_empty() {
+            return Err(AppError::InvalidInput(
+                "No styles found; expected rows of name,prompt,negative_prompt".to_string(),
+            ));
+        }
+        let imported = db
+            .replace_prompt_styles(&styles)
+            .map_err(|error| AppError::from(error).context("Failed to store prompt styles"))?;
+        log::info!(
+            "Imported {} prompt styles from {} ({} rows skipped)",
+            imported,
+            path.display(),
+            skipped
+        );
+        Ok(PromptStyleImportResult { imported, skipped })
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+/// Imported styles in the order they appear in styles.csv.
+#[tauri::command]
+pub fn list_prompt_styles(state: tauri::State<'_, AppState>) -> AppResult<Vec<PromptStyle>> {
+    Ok(state.db.list_prompt_styles()?)
+}
+
+/// Returns `prompt` and `negative_prompt` with the named style merged in.
+#[tauri::command]
+pub fn apply_prompt_style(
+    name: String,
+    prompt: String,
+    negative_prompt: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<AppliedPromptStyle> {
+    let style = state
+        .db
+        .get_prompt_style(&name)?
+        .ok_or_else(|| AppError::NotFound(format!("Prompt style not found: {}", name)))?;
+    let (prompt, negative_prompt) = style.apply(&prompt, &negative_prompt);
+    Ok(AppliedPromptStyle {
+        prompt,
+        negative_prompt,
+    })
+}
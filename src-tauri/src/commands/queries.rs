@@ -1,6 +1,27 @@
 // ────────────────────────── Image queries ──────────────────────────
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImagesCursorRequest {
+    pub cursor: Option<String>,
+    pub limit: u32,
+    pub generation_types: Option<Vec<String>>,
+    pub sort_by: Option<String>,
+    pub model_filter: Option<String>,
+    pub model_family_filters: Option<Vec<String>>,
+    pub scheduler_filters: Option<Vec<String>>,
+    pub resolution_buckets: Option<Vec<String>>,
+    pub source_tools: Option<Vec<String>>,
+    pub workflow_statuses: Option<Vec<String>>,
+    pub prompt_clusters: Option<Vec<i64>>,
+    pub collections: Option<Vec<i64>>,
+    pub max_sharpness: Option<f32>,
+    pub failed_renders_only: Option<bool>,
+    pub include_hidden: Option<bool>,
+    pub collapse_duplicates: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchImagesCursorRequest {
     pub query: String,
@@ -10,9 +31,19 @@ pub struct SearchImagesCursorRequest {
     pub sort_by: Option<String>,
     pub model_filter: Option<String>,
     pub model_family_filters: Option<Vec<String>>,
+    pub scheduler_filters: Option<Vec<String>>,
+    pub resolution_buckets: Option<Vec<String>>,
+    pub source_tools: Option<Vec<String>>,
+    pub workflow_statuses: Option<Vec<String>>,
+    pub prompt_clusters: Option<Vec<i64>>,
+    pub collections: Option<Vec<i64>>,
+    pub max_sharpness: Option<f32>,
+    pub failed_renders_only: Option<bool>,
+    pub include_hidden: Option<bool>,
+    pub collapse_duplicates: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FilterImagesCursorRequest {
     pub tags_include: Vec<String>,
@@ -24,31 +55,231 @@ pub struct FilterImagesCursorRequest {
     pub sort_by: Option<String>,
     pub model_filter: Option<String>,
     pub model_family_filters: Option<Vec<String>>,
+    pub scheduler_filters: Option<Vec<String>>,
+    pub resolution_buckets: Option<Vec<String>>,
+    pub source_tools: Option<Vec<String>>,
+    pub workflow_statuses: Option<Vec<String>>,
+    pub prompt_clusters: Option<Vec<i64>>,
+    pub collections: Option<Vec<i64>>,
+    pub max_sharpness: Option<f32>,
+    pub failed_renders_only: Option<bool>,
+    pub include_hidden: Option<bool>,
+    pub collapse_duplicates: Option<bool>,
+    /// Secondary term narrowing the results of `query`.
+    pub refine: Option<String>,
+    /// `key=value` pairs matched against unmapped A1111 parameters.
+    #[serde(default)]
+    pub extra_params: Vec<crate::database::ExtraParamFilter>,
+    /// Embeddings every image must use, e.g. to find where a deleted one
+    /// was used.
+    #[serde(default)]
+    pub embeddings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoraImagesCursorRequest {
+    pub lora: String,
+    pub min_weight: Option<f64>,
+    pub max_weight: Option<f64>,
+    pub cursor: Option<String>,
+    pub limit: u32,
+    pub generation_types: Option<Vec<String>>,
+    pub sort_by: Option<String>,
+    pub model_filter: Option<String>,
+    pub model_family_filters: Option<Vec<String>>,
+    pub scheduler_filters: Option<Vec<String>>,
+    pub resolution_buckets: Option<Vec<String>>,
+    pub source_tools: Option<Vec<String>>,
+    pub workflow_statuses: Option<Vec<String>>,
+    pub prompt_clusters: Option<Vec<i64>>,
+    pub collections: Option<Vec<i64>>,
+    pub max_sharpness: Option<f32>,
+    pub failed_renders_only: Option<bool>,
+    pub include_hidden: Option<bool>,
+    pub collapse_duplicates: Option<bool>,
+}
+
+impl LoraImagesCursorRequest {
+    fn params<'a>(&'a self, cursor: Option<&'a str>) -> crate::database::LoraCursorParams<'a> {
+        crate::database::LoraCursorParams {
+            lora: &self.lora,
+            min_weight: self.min_weight,
+            max_weight: self.max_weight,
+            options: crate::database::CursorQueryOptions {
+                cursor,
+                limit: self.limit,
+                sort_by: self.sort_by.as_deref(),
+                generation_types: self.generation_types.as_deref(),
+                model_filter: self.model_filter.as_deref(),
+                model_family_filters: self.model_family_filters.as_deref(),
+                scheduler_filters: self.scheduler_filters.as_deref(),
+                resolution_buckets: self.resolution_buckets.as_deref(),
+                source_tools: self.source_tools.as_deref(),
+                workflow_statuses: self.workflow_statuses.as_deref(),
+                prompt_clusters: self.prompt_clusters.as_deref(),
+                collections: self.collections.as_deref(),
+                max_sharpness: self.max_sharpness,
+                failed_renders_only: self.failed_renders_only.unwrap_or(false),
+                include_hidden: self.include_hidden.unwrap_or(false),
+                collapse_duplicates: self.collapse_duplicates.unwrap_or(false),
+            },
+        }
+    }
+}
+
+/// The request behind the gallery's last page, replayed by
+/// `hint_scroll_position` to list the pages that follow.
+#[derive(Debug, Clone)]
+pub enum GalleryListing {
+    Images(ImagesCursorRequest),
+    Search(SearchImagesCursorRequest),
+    Filter(FilterImagesCursorRequest),
+    Lora(LoraImagesCursorRequest),
+}
+
+impl GalleryListing {
+    /// The page of this listing that starts at `cursor`.
+    pub fn page(&self, db: &Database, cursor: &str) -> AppResult<CursorPage> {
+        let page = match self {
+            GalleryListing::Images(request) => {
+                db.get_images_cursor(crate::database::CursorQueryOptions {
+                    cursor: Some(cursor),
+                    limit: request.limit,
+                    sort_by: request.sort_by.as_deref(),
+                    generation_types: request.generation_types.as_deref(),
+                    model_filter: request.model_filter.as_deref(),
+                    model_family_filters: request.model_family_filters.as_deref(),
+                    scheduler_filters: request.scheduler_filters.as_deref(),
+                    resolution_buckets: request.resolution_buckets.as_deref(),
+                    source_tools: request.source_tools.as_deref(),
+                    workflow_statuses: request.workflow_statuses.as_deref(),
+                    prompt_clusters: request.prompt_clusters.as_deref(),
+                    collections: request.collections.as_deref(),
+                    max_sharpness: request.max_sharpness,
+                    failed_renders_only: request.failed_renders_only.unwrap_or(false),
+                    include_hidden: request.include_hidden.unwrap_or(false),
+                    collapse_duplicates: request.collapse_duplicates.unwrap_or(false),
+                })
+            }
+            GalleryListing::Search(request) => {
+                let options = crate::database::CursorQueryOptions {
+                    cursor: Some(cursor),
+                    limit: request.limit,
+                    sort_by: request.sort_by.as_deref(),
+                    generation_types: request.generation_types.as_deref(),
+                    model_filter: request.model_filter.as_deref(),
+                    model_family_filters: request.model_family_filters.as_deref(),
+                    scheduler_filters: request.scheduler_filters.as_deref(),
+                    resolution_buckets: request.resolution_buckets.as_deref(),
+                    source_tools: request.source_tools.as_deref(),
+                    workflow_statuses: request.workflow_statuses.as_deref(),
+                    prompt_clusters: request.prompt_clusters.as_deref(),
+                    collections: request.collections.as_deref(),
+                    max_sharpness: request.max_sharpness,
+                    failed_renders_only: request.failed_renders_only.unwrap_or(false),
+                    include_hidden: request.include_hidden.unwrap_or(false),
+                    collapse_duplicates: request.collapse_duplicates.unwrap_or(false),
+                };
+                if request.query.trim().is_empty() {
+                    db.get_images_cursor(options)
+                } else {
+                    db.search_cursor(crate::database::SearchCursorParams {
+                        query: &request.query,
+                        options,
+                    })
+                }
+            }
+            GalleryListing::Filter(request) => {
+                db.filter_images_cursor(crate::database::FilterCursorParams {
+                    query: request.query.as_deref(),
+                    include_tags: &request.tags_include,
+                    exclude_tags: &request.tags_exclude,
+                    refine: request.refine.as_deref(),
+                    extra_params: &request.extra_params,
+                    embeddings: &request.embeddings,
+                    options: crate::database::CursorQueryOptions {
+                        cursor: Some(cursor),
+                        limit: request.limit,
+                        sort_by: request.sort_by.as_deref(),
+                        generation_types: request.generation_types.as_deref(),
+                        model_filter: request.model_filter.as_deref(),
+                        model_family_filters: request.model_family_filters.as_deref(),
+                        scheduler_filters: request.scheduler_filters.as_deref(),
+                        resolution_buckets: request.resolution_buckets.as_deref(),
+                        source_tools: request.source_tools.as_deref(),
+                        workflow_statuses: request.workflow_statuses.as_deref(),
+                        prompt_clusters: request.prompt_clusters.as_deref(),
+                        collections: request.collections.as_deref(),
+                        max_sharpness: request.max_sharpness,
+                        failed_renders_only: request.failed_renders_only.unwrap_or(false),
+                        include_hidden: request.include_hidden.unwrap_or(false),
+                        collapse_duplicates: request.collapse_duplicates.unwrap_or(false),
+                    },
+                })
+            }
+            GalleryListing::Lora(request) => {
+                db.get_lora_images_cursor(request.params(Some(cursor)))
+            }
+        };
+        Ok(page?)
+    }
+}
+
+fn remember_gallery_listing(state: &AppState, listing: GalleryListing) {
+    if let Ok(mut current) = state.gallery_listing.write() {
+        *current = Some(listing);
+    }
 }
 
 /// Cursor-based pagination for infinite scroll with optional sorting.
 #[tauri::command]
 pub fn get_images_cursor(
-    cursor: Option<String>,
-    limit: u32,
-    sort_by: Option<String>,
-    generation_types: Option<Vec<String>>,
-    model_filter: Option<String>,
-    model_family_filters: Option<Vec<String>>,
+    request: ImagesCursorRequest,
     state: tauri::State<AppState>,
-) -> Result<CursorPage, String> {
+) -> AppResult<CursorPage> {
+    remember_gallery_listing(&state, GalleryListing::Images(request.clone()));
+    let ImagesCursorRequest {
+        cursor,
+        limit,
+        sort_by,
+        generation_types,
+        model_filter,
+        model_family_filters,
+        scheduler_filters,
+        resolution_buckets,
+        source_tools,
+        workflow_statuses,
+        prompt_clusters,
+        collections,
+        max_sharpness,
+        failed_renders_only,
+        include_hidden,
+        collapse_duplicates,
+    } = request;
     let started = std::time::Instant::now();
     let result = state
         .db
-        .get_images_cursor(
-            cursor.as_deref(),
+        .get_images_cursor(crate::database::CursorQueryOptions {
+            cursor: cursor.as_deref(),
             limit,
-            sort_by.as_deref(),
-            generation_types.as_deref(),
-            model_filter.as_deref(),
-            model_family_filters.as_deref(),
-        );
+            sort_by: sort_by.as_deref(),
+            generation_types: generation_types.as_deref(),
+            model_filter: model_filter.as_deref(),
+            model_family_filters: model_family_filters.as_deref(),
+            scheduler_filters: scheduler_filters.as_deref(),
+            resolution_buckets: resolution_buckets.as_deref(),
+            source_tools: source_tools.as_deref(),
+            workflow_statuses: workflow_statuses.as_deref(),
+            prompt_clusters: prompt_clusters.as_deref(),
+            collections: collections.as_deref(),
+            max_sharpness,
+            failed_renders_only: failed_renders_only.unwrap_or(false),
+            include_hidden: include_hidden.unwrap_or(false),
+            collapse_duplicates: collapse_duplicates.unwrap_or(false),
+        });
     let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    perf::record_query("get_images_cursor", started.elapsed(), result.is_ok());
     match &result {
         Ok(page) => log::info!(
             "Query get_images_cursor returned {} items in {:.1} ms (limit={}, sort={})",
@@ -63,7 +294,39 @@ pub fn get_images_cursor(
             error
         ),
     }
-    result.map_err(|e| e.to_string())
+    with_offline_flags(&state, result)
+}
+
+/// Images using one LoRA, optionally within a weight range, for the
+/// browse-by-LoRA view. Takes the same filters and sorts as
+/// `get_images_cursor`.
+#[tauri::command]
+pub fn get_lora_images_cursor(
+    request: LoraImagesCursorRequest,
+    state: tauri::State<AppState>,
+) -> AppResult<CursorPage> {
+    if let (Some(min), Some(max)) = (request.min_weight, request.max_weight) {
+        if min > max {
+            return Err(AppError::InvalidInput(format!(
+                "LoRA weight range is empty ({} to {})",
+                min, max
+            )));
+        }
+    }
+    remember_gallery_listing(&state, GalleryListing::Lora(request.clone()));
+    let started = std::time::Instant::now();
+    let result = state
+        .db
+        .get_lora_images_cursor(request.params(request.cursor.as_deref()));
+    perf::record_query("get_lora_images_cursor", started.elapsed(), result.is_ok());
+    if let Err(error) = &result {
+        log::warn!(
+            "Query get_lora_images_cursor failed for LoRA {}: {}",
+            request.lora,
+            error
+        );
+    }
+    with_offline_flags(&state, result)
 }
 
 /// Cursor-based search.
@@ -71,7 +334,8 @@ pub fn get_images_cursor(
 pub fn search_images_cursor(
     request: SearchImagesCursorRequest,
     state: tauri::State<AppState>,
-) -> Result<CursorPage, String> {
+) -> AppResult<CursorPage> {
+    remember_gallery_listing(&state, GalleryListing::Search(request.clone()));
     let SearchImagesCursorRequest {
         query,
         cursor,
@@ -80,20 +344,41 @@ pub fn search_images_cursor(
         sort_by,
         model_filter,
         model_family_filters,
+        scheduler_filters,
+        resolution_buckets,
+        source_tools,
+        workflow_statuses,
+        prompt_clusters,
+        collections,
+        max_sharpness,
+        failed_renders_only,
+        include_hidden,
+        collapse_duplicates,
     } = request;
     let started = std::time::Instant::now();
     if query.trim().is_empty() {
         let result = state
             .db
-            .get_images_cursor(
-                cursor.as_deref(),
+            .get_images_cursor(crate::database::CursorQueryOptions {
+                cursor: cursor.as_deref(),
                 limit,
-                sort_by.as_deref(),
-                generation_types.as_deref(),
-                model_filter.as_deref(),
-                model_family_filters.as_deref(),
-            );
+                sort_by: sort_by.as_deref(),
+                generation_types: generation_types.as_deref(),
+                model_filter: model_filter.as_deref(),
+                model_family_filters: model_family_filters.as_deref(),
+                scheduler_filters: scheduler_filters.as_deref(),
+                resolution_buckets: resolution_buckets.as_deref(),
+                source_tools: source_tools.as_deref(),
+                workflow_statuses: workflow_statuses.as_deref(),
+                prompt_clusters: prompt_clusters.as_deref(),
+                collections: collections.as_deref(),
+                max_sharpness,
+                failed_renders_only: failed_renders_only.unwrap_or(false),
+                include_hidden: include_hidden.unwrap_or(false),
+                collapse_duplicates: collapse_duplicates.unwrap_or(false),
+            });
         let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+        perf::record_query("search_images_cursor", started.elapsed(), result.is_ok());
         match &result {
             Ok(page) => log::info!(
                 "Query search_images_cursor(empty) returned {} items in {:.1} ms (limit={})",
@@ -107,7 +392,7 @@ pub fn search_images_cursor(
                 error
             ),
         }
-        return result.map_err(|e| e.to_string());
+        return with_offline_flags(&state, result);
     }
 
     let result = state
@@ -121,9 +406,20 @@ pub fn search_images_cursor(
                 generation_types: generation_types.as_deref(),
                 model_filter: model_filter.as_deref(),
                 model_family_filters: model_family_filters.as_deref(),
+                scheduler_filters: scheduler_filters.as_deref(),
+                resolution_buckets: resolution_buckets.as_deref(),
+                source_tools: source_tools.as_deref(),
+                workflow_statuses: workflow_statuses.as_deref(),
+                prompt_clusters: prompt_clusters.as_deref(),
+                collections: collections.as_deref(),
+                max_sharpness,
+                failed_renders_only: failed_renders_only.unwrap_or(false),
+                include_hidden: include_hidden.unwrap_or(false),
+                collapse_duplicates: collapse_duplicates.unwrap_or(false),
             },
         });
     let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    perf::record_query("search_images_cursor", started.elapsed(), result.is_ok());
     match &result {
         Ok(page) => log::info!(
             "Query search_images_cursor returned {} items in {:.1} ms (limit={}, query_len={})",
@@ -138,7 +434,7 @@ pub fn search_images_cursor(
             error
         ),
     }
-    result.map_err(|e| e.to_string())
+    with_offline_flags(&state, result)
 }
 
 /// Cursor-based filtering.
@@ -146,7 +442,8 @@ pub fn search_images_cursor(
 pub fn filter_images_cursor(
     request: FilterImagesCursorRequest,
     state: tauri::State<AppState>,
-) -> Result<CursorPage, String> {
+) -> AppResult<CursorPage> {
+    remember_gallery_listing(&state, GalleryListing::Filter(request.clone()));
     let FilterImagesCursorRequest {
         tags_include,
         tags_exclude,
@@ -157,6 +454,19 @@ pub fn filter_images_cursor(
         sort_by,
         model_filter,
         model_family_filters,
+        scheduler_filters,
+        resolution_buckets,
+        source_tools,
+        workflow_statuses,
+        prompt_clusters,
+        collections,
+        max_sharpness,
+        failed_renders_only,
+        include_hidden,
+        collapse_duplicates,
+        refine,
+        extra_params,
+        embeddings,
     } = request;
     let started = std::time::Instant::now();
     let result = state
@@ -165,6 +475,9 @@ pub fn filter_images_cursor(
             query: query.as_deref(),
             include_tags: &tags_include,
             exclude_tags: &tags_exclude,
+            refine: refine.as_deref(),
+            extra_params: &extra_params,
+            embeddings: &embeddings,
             options: crate::database::CursorQueryOptions {
                 cursor: cursor.as_deref(),
                 limit,
@@ -172,18 +485,30 @@ pub fn filter_images_cursor(
                 generation_types: generation_types.as_deref(),
                 model_filter: model_filter.as_deref(),
                 model_family_filters: model_family_filters.as_deref(),
+                scheduler_filters: scheduler_filters.as_deref(),
+                resolution_buckets: resolution_buckets.as_deref(),
+                source_tools: source_tools.as_deref(),
+                workflow_statuses: workflow_statuses.as_deref(),
+                prompt_clusters: prompt_clusters.as_deref(),
+                collections: collections.as_deref(),
+                max_sharpness,
+                failed_renders_only: failed_renders_only.unwrap_or(false),
+                include_hidden: include_hidden.unwrap_or(false),
+                collapse_duplicates: collapse_duplicates.unwrap_or(false),
             },
         });
     let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    perf::record_query("filter_images_cursor", started.elapsed(), result.is_ok());
     match &result {
         Ok(page) => log::info!(
-            "Query filter_images_cursor returned {} items in {:.1} ms (limit={}, include_tags={}, exclude_tags={}, query={})",
+            "Query filter_images_cursor returned {} items in {:.1} ms (limit={}, include_tags={}, exclude_tags={}, query={}, refine={})",
             page.items.len(),
             elapsed_ms,
             limit,
             tags_include.len(),
             tags_exclude.len(),
-            query.as_deref().unwrap_or("").len()
+            query.as_deref().unwrap_or("").len(),
+            refine.as_deref().unwrap_or("").len()
         ),
         Err(error) => log::warn!(
             "Query filter_images_cursor failed in {:.1} ms: {}",
@@ -191,23 +516,93 @@ pub fn filter_images_cursor(
             error
         ),
     }
-    result.map_err(|e| e.to_string())
+    with_offline_flags(&state, result)
 }
 
 // ────────────────────────── Tag queries ──────────────────────────
 
+/// The gallery filter state without paging, for commands that act on every
+/// image a filter matches.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImageFilterRequest {
+    pub tags_include: Vec<String>,
+    pub tags_exclude: Vec<String>,
+    pub query: Option<String>,
+    pub generation_types: Option<Vec<String>>,
+    pub model_filter: Option<String>,
+    pub model_family_filters: Option<Vec<String>>,
+    pub scheduler_filters: Option<Vec<String>>,
+    pub resolution_buckets: Option<Vec<String>>,
+    pub source_tools: Option<Vec<String>>,
+    pub workflow_statuses: Option<Vec<String>>,
+    pub prompt_clusters: Option<Vec<i64>>,
+    pub collections: Option<Vec<i64>>,
+    pub max_sharpness: Option<f32>,
+    pub failed_renders_only: bool,
+    pub include_hidden: bool,
+    pub refine: Option<String>,
+    pub extra_params: Vec<crate::database::ExtraParamFilter>,
+    pub embeddings: Vec<String>,
+}
+
+impl ImageFilterRequest {
+    pub fn params(&self) -> crate::database::FilterCursorParams<'_> {
+        crate::database::FilterCursorParams {
+            query: self.query.as_deref(),
+            include_tags: &self.tags_include,
+            exclude_tags: &self.tags_exclude,
+            refine: self.refine.as_deref(),
+            extra_params: &self.extra_params,
+            embeddings: &self.embeddings,
+            options: crate::database::CursorQueryOptions {
+                generation_types: self.generation_types.as_deref(),
+                model_filter: self.model_filter.as_deref(),
+                model_family_filters: self.model_family_filters.as_deref(),
+                scheduler_filters: self.scheduler_filters.as_deref(),
+                resolution_buckets: self.resolution_buckets.as_deref(),
+                source_tools: self.source_tools.as_deref(),
+                workflow_statuses: self.workflow_statuses.as_deref(),
+                prompt_clusters: self.prompt_clusters.as_deref(),
+                collections: self.collections.as_deref(),
+                max_sharpness: self.max_sharpness,
+                failed_renders_only: self.failed_renders_only,
+                include_hidden: self.include_hidden,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Sidebar badge counts per generation type and model family for the active
+/// filter, in a single query.
+#[tauri::command]
+pub fn get_filter_counts(
+    request: ImageFilterRequest,
+    state: tauri::State<AppState>,
+) -> AppResult<FilterCounts> {
+    let started = std::time::Instant::now();
+    let result = state
+        .db
+        .get_filter_counts(request.params())
+        .map_err(AppError::from);
+    perf::record_query("get_filter_counts", started.elapsed(), result.is_ok());
+    result
+}
+
 #[tauri::command]
 pub fn list_tags(
     prefix: Option<String>,
     limit: u32,
     state: tauri::State<AppState>,
-) -> Result<Vec<String>, String> {
+) -> AppResult<Vec<String>> {
     let started = std::time::Instant::now();
     let result = state
         .db
         .list_tags(prefix.as_deref(), limit)
-        .map_err(|e| e.to_string());
+        .map_err(AppError::from);
     let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    perf::record_query("list_tags", started.elapsed(), result.is_ok());
     if let Ok(tags) = &result {
         log::info!(
             "Query list_tags returned {} tags in {:.1} ms (prefix={}, limit={})",
@@ -221,10 +616,11 @@ pub fn list_tags(
 }
 
 #[tauri::command]
-pub fn get_top_tags(limit: u32, state: tauri::State<AppState>) -> Result<Vec<TagCount>, String> {
+pub fn get_top_tags(limit: u32, state: tauri::State<AppState>) -> AppResult<Vec<TagCount>> {
     let started = std::time::Instant::now();
-    let result = state.db.get_top_tags(limit).map_err(|e| e.to_string());
+    let result = state.db.get_top_tags(limit).map_err(AppError::from);
     let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    perf::record_query("get_top_tags", started.elapsed(), result.is_ok());
     if let Ok(tags) = &result {
         log::info!(
             "Query get_top_tags returned {} tags in {:.1} ms (limit={})",
@@ -236,34 +632,358 @@ pub fn get_top_tags(limit: u32, state: tauri::State<AppState>) -> Result<Vec<Tag
     result
 }
 
+/// Facet of unmapped A1111 parameters (`VAE`, `ADetailer model`, ...) for
+/// building `extra_params` filters.
 #[tauri::command]
-pub fn get_image_tags(id: i64, state: tauri::State<AppState>) -> Result<Vec<String>, String> {
-    state.db.get_tags_for_image(id).map_err(|e| e.to_string())
+pub fn get_extra_param_keys(
+    limit: u32,
+    state: tauri::State<AppState>,
+) -> AppResult<Vec<ExtraParamKey>> {
+    let started = std::time::Instant::now();
+    let result = state.db.get_extra_param_keys(limit).map_err(AppError::from);
+    perf::record_query("get_extra_param_keys", started.elapsed(), result.is_ok());
+    result
+}
+
+#[tauri::command]
+pub fn get_image_tags(id: i64, state: tauri::State<AppState>) -> AppResult<Vec<String>> {
+    state.db.get_tags_for_image(id).map_err(AppError::from)
+}
+
+/// An image record plus where each parsed field came from in its raw
+/// metadata.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageRecordWithSources {
+    #[serde(flatten)]
+    pub record: ImageRecord,
+    /// From re-parsing `raw_metadata` with the current parser, so it also
+    /// shows what a rescan would pick up.
+    pub field_sources: Vec<parser::FieldSource>,
 }
 
 #[tauri::command]
 pub fn get_image_detail(
     id: i64,
     state: tauri::State<AppState>,
-) -> Result<Option<ImageRecord>, String> {
-    state.db.get_image_by_id(id).map_err(|e| e.to_string())
+) -> AppResult<Option<ImageRecordWithSources>> {
+    let Some(record) = state.db.get_image_by_id(id)? else {
+        return Ok(None);
+    };
+    let (_, field_sources) = parser::parse_generation_metadata_with_sources(&record.raw_metadata);
+    Ok(Some(ImageRecordWithSources {
+        record,
+        field_sources,
+    }))
+}
+
+/// Full records for several ids in one call, in the requested order.
+/// Unknown ids are skipped.
+#[tauri::command]
+pub fn get_images_by_ids_detail(
+    ids: Vec<i64>,
+    state: tauri::State<AppState>,
+) -> AppResult<Vec<ImageRecord>> {
+    let started = std::time::Instant::now();
+    let result = state.db.get_images_by_ids(&ids);
+    perf::record_query(
+        "get_images_by_ids_detail",
+        started.elapsed(),
+        result.is_ok(),
+    );
+    let mut by_id: std::collections::HashMap<i64, ImageRecord> = result?
+        .into_iter()
+        .map(|record| (record.id, record))
+        .collect();
+    Ok(ids.iter().filter_map(|id| by_id.remove(id)).collect())
+}
+
+/// Records plus tags for a multi-selection in one query, in the requested
+/// order. Replaces a `get_image_detail` + `get_image_tags` pair per image.
+#[tauri::command]
+pub fn get_image_details(
+    ids: Vec<i64>,
+    state: tauri::State<AppState>,
+) -> AppResult<Vec<ImageDetail>> {
+    let started = std::time::Instant::now();
+    let result = state.db.get_image_details(&ids);
+    perf::record_query("get_image_details", started.elapsed(), result.is_ok());
+    let mut by_id: std::collections::HashMap<i64, ImageDetail> = result?
+        .into_iter()
+        .map(|detail| (detail.record.id, detail))
+        .collect();
+    Ok(ids.iter().filter_map(|id| by_id.remove(id)).collect())
+}
+
+/// Individual renders behind a grid image, oldest first. Empty when the
+/// image is not a grid or its cells are not indexed.
+#[tauri::command]
+pub fn get_grid_members(
+    grid_id: i64,
+    state: tauri::State<AppState>,
+) -> AppResult<Vec<ImageRecord>> {
+    let started = std::time::Instant::now();
+    let result = state.db.get_grid_members(grid_id);
+    perf::record_query("get_grid_members", started.elapsed(), result.is_ok());
+    result?.ok_or_else(|| AppError::NotFound(format!("Image not found: {}", grid_id)))
+}
+
+/// Images from the same batch run as `image_id`, in generation order, for
+/// showing bursts as one stack.
+#[tauri::command]
+pub fn get_batch_members(
+    image_id: i64,
+    state: tauri::State<AppState>,
+) -> AppResult<Vec<crate::database::GalleryImageRecord>> {
+    let started = std::time::Instant::now();
+    let result = state.db.get_batch_members(image_id);
+    perf::record_query("get_batch_members", started.elapsed(), result.is_ok());
+    let mut members = result?;
+    if members.is_empty() {
+        return Err(AppError::NotFound(format!("Image not found: {}", image_id)));
+    }
+    refresh_volume_status(&state, false);
+    state.db.mark_offline(&mut members);
+    Ok(members)
+}
+
+/// Seed distance used when the caller doesn't pass a window.
+const SEED_WINDOW_DEFAULT: u32 = 10;
+/// Widest seed window accepted by `get_seed_neighbors`.
+const SEED_WINDOW_MAX: u32 = 200;
+
+/// Same prompt and model as `image_id` with nearby seeds or the same
+/// variation seed, sorted by seed for flipping through a sweep.
+#[tauri::command]
+pub fn get_seed_neighbors(
+    image_id: i64,
+    window: Option<u32>,
+    state: tauri::State<AppState>,
+) -> AppResult<Vec<crate::database::GalleryImageRecord>> {
+    let window = window
+        .unwrap_or(SEED_WINDOW_DEFAULT)
+        .min(SEED_WINDOW_MAX);
+    let started = std::time::Instant::now();
+    let result = state.db.get_seed_neighbors(image_id, window);
+    perf::record_query("get_seed_neighbors", started.elapsed(), result.is_ok());
+    let mut neighbors = result?;
+    if neighbors.is_empty() {
+        return Err(AppError::NotFound(format!("Image not found: {}", image_id)));
+    }
+    refresh_volume_status(&state, false);
+    state.db.mark_offline(&mut neighbors);
+    Ok(neighbors)
 }
 
+/// Colour distance accepted when the caller doesn't pass a tolerance.
+const COLOR_TOLERANCE_DEFAULT: f32 = 0.15;
+/// Matches returned when the caller doesn't pass a limit.
+const COLOR_MATCH_LIMIT_DEFAULT: u32 = 200;
+/// Most matches `search_by_color` returns.
+const COLOR_MATCH_LIMIT_MAX: u32 = 2000;
+
+/// Images whose dominant colour is close to `hex` (`#rrggbb` or `#rgb`),
+/// closest first, for pulling together a visually cohesive set. `tolerance`
+/// runs from 0 (that exact colour) to 1 (anything).
 #[tauri::command]
-pub fn get_total_count(state: tauri::State<AppState>) -> Result<u32, String> {
-    state.db.get_total_count().map_err(|e| e.to_string())
+pub fn search_by_color(
+    hex: String,
+    tolerance: Option<f32>,
+    limit: Option<u32>,
+    state: tauri::State<AppState>,
+) -> AppResult<Vec<ColorMatch>> {
+    let rgb = crate::color_stats::parse_hex(&hex)
+        .ok_or_else(|| AppError::InvalidInput(format!("{} is not a hex colour", hex.trim())))?;
+    let tolerance = tolerance.unwrap_or(COLOR_TOLERANCE_DEFAULT);
+    if !(0.0..=1.0).contains(&tolerance) {
+        return Err(AppError::InvalidInput(format!(
+            "Colour tolerance must be between 0 and 1, got {}",
+            tolerance
+        )));
+    }
+    let limit = limit
+        .unwrap_or(COLOR_MATCH_LIMIT_DEFAULT)
+        .min(COLOR_MATCH_LIMIT_MAX);
+    let target = crate::color_stats::ColorStats::from_rgb(rgb);
+
+    let started = std::time::Instant::now();
+    let result = state.db.search_by_color(&target, tolerance, limit);
+    perf::record_query("search_by_color", started.elapsed(), result.is_ok());
+    let mut matches = result?;
+    refresh_volume_status(&state, false);
+    for found in &mut matches {
+        found.record.offline = state.db.is_offline_path(&found.record.filepath);
+    }
+    Ok(matches)
+}
+
+/// Hash distance accepted when the caller doesn't pass one. Resizes,
+/// upscales and light edits of one image usually stay within it.
+const SIMILAR_DISTANCE_DEFAULT: u32 = 10;
+/// Past this many differing bits out of 64, matches are mostly unrelated.
+const SIMILAR_DISTANCE_MAX: u32 = 32;
+/// Matches returned when the caller doesn't pass a limit.
+const SIMILAR_MATCH_LIMIT_DEFAULT: u32 = 100;
+/// Most matches `find_similar_images` returns.
+const SIMILAR_MATCH_LIMIT_MAX: u32 = 500;
+
+/// Images that look like `image_id` by perceptual hash, closest first, for
+/// finding near-duplicate generations and upscaled variants. Hashes are
+/// computed with the thumbnail, so images without one can't be searched
+/// from or matched yet.
+#[tauri::command]
+pub fn find_similar_images(
+    image_id: i64,
+    max_distance: Option<u32>,
+    limit: Option<u32>,
+    state: tauri::State<AppState>,
+) -> AppResult<Vec<SimilarImage>> {
+    let max_distance = max_distance.unwrap_or(SIMILAR_DISTANCE_DEFAULT);
+    if max_distance > SIMILAR_DISTANCE_MAX {
+        return Err(AppError::InvalidInput(format!(
+            "Hash distance must be at most {}, got {}",
+            SIMILAR_DISTANCE_MAX, max_distance
+        )));
+    }
+    let limit = limit
+        .unwrap_or(SIMILAR_MATCH_LIMIT_DEFAULT)
+        .min(SIMILAR_MATCH_LIMIT_MAX);
+
+    let started = std::time::Instant::now();
+    let result = state.db.find_similar_images(image_id, max_distance, limit);
+    perf::record_query("find_similar_images", started.elapsed(), result.is_ok());
+    let mut matches = result?.ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Image {} not found or not hashed yet; hashes are computed with thumbnails",
+            image_id
+        ))
+    })?;
+    refresh_volume_status(&state, false);
+    for found in &mut matches {
+        found.record.offline = state.db.is_offline_path(&found.record.filepath);
+    }
+    Ok(matches)
+}
+
+#[tauri::command]
+pub fn get_total_count(state: tauri::State<AppState>) -> AppResult<u32> {
+    state.db.get_total_count().map_err(AppError::from)
 }
 
 // ────────────────────────── Group-by queries ──────────────────────────
 
 /// Returns unique directories with image counts for group-by view.
 #[tauri::command]
-pub fn get_directories(state: tauri::State<AppState>) -> Result<Vec<DirectoryEntry>, String> {
-    state.db.get_unique_directories().map_err(|e| e.to_string())
+pub fn get_directories(state: tauri::State<AppState>) -> AppResult<Vec<DirectoryEntry>> {
+    state.db.get_unique_directories().map_err(AppError::from)
+}
+
+/// Longest accepted directory alias, in characters.
+const DIRECTORY_ALIAS_MAX_CHARS: usize = 80;
+
+fn require_directory(directory: &str) -> AppResult<&str> {
+    let directory = directory.trim();
+    if directory.is_empty() {
+        return Err(AppError::InvalidInput("Directory is required".to_string()));
+    }
+    Ok(directory)
+}
+
+/// Pins or unpins a directory in the group-by view.
+#[tauri::command]
+pub fn set_directory_pinned(
+    directory: String,
+    pinned: bool,
+    state: tauri::State<AppState>,
+) -> AppResult<()> {
+    let directory = require_directory(&directory)?;
+    state
+        .db
+        .set_directory_pinned(directory, pinned)
+        .map_err(AppError::from)
+}
+
+/// Hides or shows a directory's images in the gallery.
+#[tauri::command]
+pub fn set_directory_hidden(
+    directory: String,
+    hidden: bool,
+    state: tauri::State<AppState>,
+) -> AppResult<()> {
+    let directory = require_directory(&directory)?;
+    state
+        .db
+        .set_directory_hidden(directory, hidden)
+        .map_err(AppError::from)
+}
+
+/// Assigns a display alias to a directory; an empty alias clears it.
+#[tauri::command]
+pub fn set_directory_alias(
+    directory: String,
+    alias: Option<String>,
+    state: tauri::State<AppState>,
+) -> AppResult<()> {
+    let directory = require_directory(&directory)?;
+    let alias = alias
+        .as_deref()
+        .map(str::trim)
+        .filter(|alias| !alias.is_empty());
+    if alias.is_some_and(|alias| alias.chars().count() > DIRECTORY_ALIAS_MAX_CHARS) {
+        return Err(AppError::InvalidInput(format!(
+            "Directory alias must be at most {} characters",
+            DIRECTORY_ALIAS_MAX_CHARS
+        )));
+    }
+    state
+        .db
+        .set_directory_alias(directory, alias)
+        .map_err(AppError::from)
 }
 
 /// Returns unique model names with image counts for group-by view.
 #[tauri::command]
-pub fn get_models(state: tauri::State<AppState>) -> Result<Vec<ModelEntry>, String> {
-    state.db.get_unique_models().map_err(|e| e.to_string())
+pub fn get_models(state: tauri::State<AppState>) -> AppResult<Vec<ModelEntry>> {
+    state.db.get_unique_models().map_err(AppError::from)
+}
+
+/// Returns LoRAs with image counts, weight spread and last use, for the
+/// browse-by-LoRA view.
+#[tauri::command]
+pub fn get_lora_usage(state: tauri::State<AppState>) -> AppResult<Vec<LoraUsage>> {
+    state.db.get_lora_usage().map_err(AppError::from)
+}
+
+/// Returns textual-inversion embeddings with image counts per prompt side,
+/// e.g. to see what breaks before deleting one.
+#[tauri::command]
+pub fn get_embedding_usage(state: tauri::State<AppState>) -> AppResult<Vec<EmbeddingUsage>> {
+    state.db.get_embedding_usage().map_err(AppError::from)
+}
+
+/// Returns generator tools (ComfyUI, Forge, NovelAI, ...) with image
+/// counts, for splitting mixed libraries.
+#[tauri::command]
+pub fn get_source_tools(state: tauri::State<AppState>) -> AppResult<Vec<SourceToolEntry>> {
+    state.db.get_source_tools().map_err(AppError::from)
+}
+
+/// Returns samplers (Euler a, DPM++ 2M, ...) with image counts.
+#[tauri::command]
+pub fn get_samplers(state: tauri::State<AppState>) -> AppResult<Vec<SamplerEntry>> {
+    state.db.get_unique_samplers().map_err(AppError::from)
+}
+
+/// Returns schedulers (Karras, Exponential, ...) with image counts.
+#[tauri::command]
+pub fn get_schedulers(state: tauri::State<AppState>) -> AppResult<Vec<SchedulerEntry>> {
+    state.db.get_unique_schedulers().map_err(AppError::from)
+}
+
+/// Returns 64px-snapped resolution buckets with image counts and their size
+/// class, so SD1.5 and SDXL native output can be told apart.
+#[tauri::command]
+pub fn get_resolution_buckets(
+    state: tauri::State<AppState>,
+) -> AppResult<Vec<ResolutionBucket>> {
+    state.db.get_resolution_buckets().map_err(AppError::from)
 }
@@ -9,34 +9,90 @@
 ///   4. Parallel metadata extraction (Rayon par_iter)
 ///   5. Chunked bulk-upsert with tags (single transaction per chunk)
 ///   6. Chunked thumbnail generation with progress events
+///
+/// Progress events go to the calling window only.
 #[tauri::command]
 pub async fn scan_directory(
     directory: String,
     app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<()> {
+    run_scan(directory, EventSink::for_window(&app, &window), state).await
+}
+
+/// Removes a directory from `AppState::active_scans` when its scan ends.
+struct ActiveScanGuard {
+    scans: std::sync::Arc<RwLock<std::collections::HashSet<String>>>,
+    key: String,
+}
+
+impl Drop for ActiveScanGuard {
+    fn drop(&mut self) {
+        if let Ok(mut scans) = self.scans.write() {
+            scans.remove(&self.key);
+        }
+    }
+}
+
+pub(crate) async fn run_scan(
+    directory: String,
+    events: EventSink,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let dir_path = PathBuf::from(&directory);
     if !dir_path.exists() || !dir_path.is_dir() {
-        return Err(format!("Invalid directory: {}", directory));
+        return Err(AppError::InvalidInput(format!(
+            "Invalid directory: {}",
+            directory
+        )));
+    }
+
+    let scan_key = directory
+        .replace('\\', "/")
+        .trim_end_matches('/')
+        .to_string();
+    {
+        let mut scans = state
+            .active_scans
+            .write()
+            .map_err(|_| AppError::Internal("Failed to lock active scans".to_string()))?;
+        if !scans.insert(scan_key.clone()) {
+            return Err(AppError::InvalidInput(format!(
+                "{} is already being scanned",
+                directory
+            )));
+        }
     }
+    let scan_guard = ActiveScanGuard {
+        scans: state.active_scans.clone(),
+        key: scan_key,
+    };
+
+    remember_scan_root(&state, &directory);
 
     let db = state.db.clone();
     let cache_dir = state.cache_dir.clone();
     let thumbnail_index = state.thumbnail_index.clone();
     let failed_thumbnail_sources = state.failed_thumbnail_sources.clone();
+    let hooks = state.hooks.clone();
+    let tasks = state.tasks.clone();
     let storage_profile = state
         .storage_profile
         .read()
         .map(|profile| *profile)
         .unwrap_or(StorageProfile::Hdd);
-    let app_handle = app.clone();
+    let precache_running = state.thumbnail_precache_running.clone();
+    let scroll_warmup = state.scroll_warmup.clone();
 
     tauri::async_runtime::spawn_blocking(move || {
+        let _scan_guard = scan_guard;
+        let task = tasks.start(TaskKind::Scan, directory.clone());
         let total_timer = std::time::Instant::now();
 
         // ── Stage 1: Walk filesystem ─────────────────────────────────
         let discovery_timer = std::time::Instant::now();
-        let _ = app_handle.emit(
+        let _ = events.emit(
             "scan-progress",
             ScanProgress {
                 current: 0,
@@ -56,7 +112,7 @@ pub async fn scan_directory(
                 dir_path.display(),
                 discovery_elapsed.as_secs_f64() * 1000.0
             );
-            let _ = app_handle.emit(
+            let _ = events.emit(
                 "scan-complete",
                 ScanResult {
                     total_files: 0,
@@ -64,6 +120,18 @@ pub async fn scan_directory(
                     errors: 0,
                 },
             );
+            fire_hooks(
+                &hooks,
+                HookEvent::ScanComplete,
+                serde_json::json!({
+                    "directory": directory,
+                    "total_files": 0,
+                    "indexed": 0,
+                    "errors": 0,
+                    "skipped": 0,
+                    "cancelled": false,
+                }),
+            );
             return;
         }
 
@@ -72,17 +140,24 @@ pub async fn scan_directory(
         let existing_mtimes = db.get_all_file_mtimes().unwrap_or_default();
 
         // Filter to only changed or new files (and capture mtimes once).
+        let mut changed_files: Vec<PathBuf> = Vec::new();
         let mut files_to_process: Vec<PendingFile> = image_files
             .into_iter()
             .filter_map(|scanned| {
                 let filepath_str = scanned.path.to_string_lossy();
+                let existing = existing_mtimes.get(filepath_str.as_ref()).copied();
                 let is_unchanged = matches!(
-                    (scanned.file_mtime, existing_mtimes.get(filepath_str.as_ref())),
-                    (Some(cur), Some(existing)) if cur == *existing
+                    (scanned.file_mtime, existing),
+                    (Some(cur), Some(existing)) if cur == existing
                 );
                 if is_unchanged {
                     None
                 } else {
+                    if existing.is_some() {
+                        // Edited in place: any mtime change, even to an
+                        // older one, means the cached thumbnail is stale.
+                        changed_files.push(scanned.path.clone());
+                    }
                     Some(PendingFile {
                         path: scanned.path,
                         file_mtime: scanned.file_mtime,
@@ -91,6 +166,7 @@ pub async fn scan_directory(
                 }
             })
             .collect();
+        discard_changed_thumbnails(&changed_files, &cache_dir, &thumbnail_index);
         files_to_process.sort_unstable_by(|a, b| a.path.cmp(&b.path));
 
         let files_to_process_count = files_to_process.len();
@@ -104,7 +180,7 @@ pub async fn scan_directory(
             files_to_process_count
         );
 
-        let _ = app_handle.emit(
+        let _ = events.emit(
             "scan-progress",
             ScanProgress {
                 current: 0,
@@ -123,13 +199,17 @@ pub async fn scan_directory(
         let mut write_batch_idx = 0usize;
 
         for file_chunk in files_to_process.chunks(METADATA_PARSE_CHUNK_SIZE) {
+            if !task.checkpoint() {
+                break;
+            }
             let records: Vec<BulkRecord> = scan_pool(storage_profile).install(|| {
                 file_chunk
                     .par_iter()
                     .filter_map(|pending| {
                         let done = progress_counter.fetch_add(1, Ordering::Relaxed) + 1;
+                        task.set_progress(done, files_to_process_count);
                         if done.is_multiple_of(64) || done == files_to_process_count {
-                            let _ = app_handle.emit(
+                            let _ = events.emit(
                                 "scan-progress",
                                 ScanProgress {
                                     current: done,
@@ -147,47 +227,12 @@ pub async fn scan_directory(
                             );
                         }
 
-                        let raw_metadata = extract_parameters_metadata(&pending.path);
-                        let params = if raw_metadata.trim().is_empty() {
-                            parser::GenerationParams {
-                                raw_metadata: String::new(),
-                                ..Default::default()
-                            }
-                        } else {
-                            parser::parse_generation_metadata(&raw_metadata)
-                        };
-                        let mut tags = parser::extract_tags(&params.prompt);
-
-                        if let Some(sidecar_data) = sidecar::read_sidecar(&pending.path) {
-                            tags.extend(sidecar_data.tags);
-                        }
-
-                        let filepath = pending.path.to_string_lossy().to_string();
-                        let quick_hash =
-                            scanner::compute_quick_hash(&pending.path, pending.file_size);
-                        let filename = pending
-                            .path
-                            .file_name()
-                            .unwrap_or_default()
-                            .to_string_lossy()
-                            .to_string();
-                        let directory = pending
-                            .path
-                            .parent()
-                            .unwrap_or(&dir_path)
-                            .to_string_lossy()
-                            .to_string();
-
-                        Some(BulkRecord {
-                            filepath,
-                            filename,
-                            directory,
-                            params,
-                            file_mtime: pending.file_mtime,
-                            file_size: pending.file_size,
-                            quick_hash,
-                            tags,
-                        })
+                        Some(build_bulk_record(
+                            &pending.path,
+                            pending.file_mtime,
+                            pending.file_size,
+                            &dir_path,
+                        ))
                     })
                     .collect()
             });
@@ -197,7 +242,7 @@ pub async fn scan_directory(
                     Ok(count) => {
                         indexed += count;
                         write_batch_idx += 1;
-                        let _ = app_handle.emit(
+                        let _ = events.emit(
                             "scan-progress",
                             ScanProgress {
                                 current: progress_counter
@@ -221,8 +266,11 @@ pub async fn scan_directory(
 
         // ── Stage 5: Chunked thumbnail generation with progress ──────
         let thumbnail_timer = std::time::Instant::now();
-        let immediate_thumb_count =
-            files_to_process_count.min(immediate_thumb_budget(storage_profile));
+        let immediate_thumb_count = if task.is_cancelled() {
+            0
+        } else {
+            files_to_process_count.min(immediate_thumb_budget(storage_profile))
+        };
         let immediate_thumb_chunk_size = scan_thumbnail_chunk_size(storage_profile).max(1);
         let split_at = files_to_process_count.saturating_sub(immediate_thumb_count);
         let (remaining_pending, immediate_pending) = files_to_process.split_at(split_at);
@@ -233,7 +281,7 @@ pub async fn scan_directory(
             .collect();
 
         if immediate_thumb_count > 0 {
-            let _ = app_handle.emit(
+            let _ = events.emit(
                 "scan-progress",
                 ScanProgress {
                     current: 0,
@@ -247,8 +295,13 @@ pub async fn scan_directory(
                 .chunks(immediate_thumb_chunk_size)
                 .enumerate()
             {
+                if !task.checkpoint() {
+                    break;
+                }
                 let generated =
                     image_processing::generate_thumbnails(chunk, &cache_dir, storage_profile);
+                save_thumbnail_results(&db, generated.results);
+                let generated = generated.thumbnails;
                 if !generated.is_empty() {
                     if let Ok(mut index) = thumbnail_index.write() {
                         for (_, thumb_path) in &generated {
@@ -264,7 +317,8 @@ pub async fn scan_directory(
 
                 let done =
                     ((chunk_idx + 1) * immediate_thumb_chunk_size).min(immediate_thumb_count);
-                let _ = app_handle.emit(
+                task.set_message(format!("Thumbnails {}/{}", done, immediate_thumb_count));
+                let _ = events.emit(
                     "scan-progress",
                     ScanProgress {
                         current: done,
@@ -288,17 +342,72 @@ pub async fn scan_directory(
             immediate_thumb_count as f64
         };
 
-        let _ = app_handle.emit(
-            "scan-complete",
-            ScanResult {
-                total_files,
-                indexed,
-                errors: errors + db_errors,
-            },
+        perf::record_scan(&perf::ScanTimings {
+            files_processed: files_to_process_count,
+            discovery: discovery_elapsed,
+            filter: filter_elapsed,
+            metadata: metadata_elapsed,
+            thumbnails: thumbnail_elapsed,
+            total: total_timer.elapsed(),
+        });
+        let cancelled = task.is_cancelled();
+        let scan_result = ScanResult {
+            total_files,
+            indexed,
+            errors: errors + db_errors,
+        };
+        let _ = events.emit("scan-complete", scan_result.clone());
+        fire_hooks(
+            &hooks,
+            HookEvent::ScanComplete,
+            serde_json::json!({
+                "directory": directory,
+                "total_files": scan_result.total_files,
+                "indexed": scan_result.indexed,
+                "errors": scan_result.errors,
+                "skipped": skipped,
+                "cancelled": cancelled,
+            }),
+        );
+        log_activity(
+            &db,
+            ActivityKind::Scan,
+            format!(
+                "{} {}: {} indexed, {} errors",
+                if cancelled {
+                    "Cancelled scan of"
+                } else {
+                    "Scanned"
+                },
+                directory,
+                scan_result.indexed,
+                scan_result.errors
+            ),
+            serde_json::json!({
+                "directory": directory,
+                "total_files": scan_result.total_files,
+                "indexed": scan_result.indexed,
+                "errors": scan_result.errors,
+                "skipped": skipped,
+                "cancelled": cancelled,
+            }),
+            &[],
         );
+        if indexed > 0 {
+            fire_hooks(
+                &hooks,
+                HookEvent::ImagesIndexed,
+                serde_json::json!({
+                    "source": "scan",
+                    "directory": directory,
+                    "count": indexed,
+                }),
+            );
+        }
 
         log::info!(
-            "Scan complete: {} total, {} indexed, {} errors, {} skipped (unchanged)",
+            "Scan {}: {} total, {} indexed, {} errors, {} skipped (unchanged)",
+            if cancelled { "cancelled" } else { "complete" },
             total_files,
             indexed,
             errors + db_errors,
@@ -317,29 +426,64 @@ pub async fn scan_directory(
             total_timer.elapsed().as_secs_f64() * 1000.0
         );
 
+        if cancelled {
+            return;
+        }
+        drop(task);
+
         let remaining_thumb_paths: Vec<PathBuf> = remaining_pending
             .iter()
             .rev()
             .map(|pending| pending.path.clone())
             .collect();
-        if !remaining_thumb_paths.is_empty() {
+        // A library-wide pre-cache (possibly from another window) already
+        // covers these files.
+        if !remaining_thumb_paths.is_empty() && precache_running.load(Ordering::Acquire) {
+            log::info!(
+                "Skipping thumbnail warmup for {}: library pre-cache is running",
+                directory
+            );
+        } else if !remaining_thumb_paths.is_empty() {
+            let db_bg = db.clone();
             let cache_dir_bg = cache_dir.clone();
             let thumbnail_index_bg = thumbnail_index.clone();
             let failed_thumbnail_sources_bg = failed_thumbnail_sources.clone();
             let remaining = remaining_thumb_paths.len();
+            let warmup_task = tasks.start(TaskKind::ThumbnailWarmup, directory.clone());
+            warmup_task.set_progress(0, remaining);
             let _ = std::thread::Builder::new()
                 .name("thumbnail-warmup".into())
                 .spawn(move || {
                     let warmup_timer = std::time::Instant::now();
                     let warmup_chunk_size = precache_chunk_size(storage_profile).max(1);
                     let mut generated_total = 0usize;
+                    let mut processed = 0usize;
                     for chunk in remaining_thumb_paths.chunks(warmup_chunk_size) {
+                        if !warmup_task.checkpoint() {
+                            break;
+                        }
+                        // Pages the gallery is about to show go first.
+                        let hinted = scroll_warmup.take(warmup_chunk_size);
+                        if !hinted.is_empty() {
+                            rerender_thumbnails(
+                                &db_bg,
+                                &hinted,
+                                &cache_dir_bg,
+                                &thumbnail_index_bg,
+                                &failed_thumbnail_sources_bg,
+                                storage_profile,
+                            );
+                        }
                         let generated = image_processing::generate_thumbnails(
                             chunk,
                             &cache_dir_bg,
                             storage_profile,
                         );
+                        save_thumbnail_results(&db_bg, generated.results);
+                        let generated = generated.thumbnails;
                         generated_total += generated.len();
+                        processed += chunk.len();
+                        warmup_task.set_progress(processed, remaining);
                         if !generated.is_empty() {
                             if let Ok(mut index) = thumbnail_index_bg.write() {
                                 for (_, thumb_path) in &generated {
@@ -360,7 +504,8 @@ pub async fn scan_directory(
                         generated_total as f64
                     };
                     log::info!(
-                        "Background thumbnail warmup complete ({} files, {} generated, {:.1} images/s, chunk={})",
+                        "Background thumbnail warmup {} ({} files, {} generated, {:.1} images/s, chunk={})",
+                        if warmup_task.is_cancelled() { "cancelled" } else { "complete" },
                         remaining,
                         generated_total,
                         throughput,
@@ -373,13 +518,73 @@ pub async fn scan_directory(
     Ok(())
 }
 
-fn extract_parameters_metadata(path: &Path) -> String {
-    match scanner::extract_metadata(path) {
-        Ok(Some(parameters)) => parameters,
-        Ok(None) => read_sidecar_txt(path),
+/// Parses embedded metadata, prompt tags and sidecar tags for one image file.
+pub(crate) fn build_bulk_record(
+    path: &Path,
+    file_mtime: Option<i64>,
+    file_size: Option<i64>,
+    fallback_directory: &Path,
+) -> BulkRecord {
+    let (raw_metadata, workflow, source_tool) = extract_parameters_metadata(path);
+    let params = if raw_metadata.trim().is_empty() {
+        parser::GenerationParams {
+            raw_metadata: String::new(),
+            ..Default::default()
+        }
+    } else {
+        parser::GenerationParams {
+            source_tool: source_tool.map(str::to_string),
+            ..parser::parse_generation_metadata(&raw_metadata)
+        }
+    };
+    let mut tags = parser::extract_tags(&params.prompt);
+    let mut rating = None;
+
+    if let Some(sidecar_data) = sidecar::read_sidecar(path) {
+        tags.extend(sidecar_data.tags);
+        rating = sidecar_data.rating;
+    }
+
+    let filepath = path.to_string_lossy().to_string();
+    let quick_hash = scanner::compute_quick_hash(path, file_size);
+    let filename = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let directory = path
+        .parent()
+        .unwrap_or(fallback_directory)
+        .to_string_lossy()
+        .to_string();
+
+    BulkRecord {
+        filepath,
+        filename,
+        directory,
+        params,
+        file_mtime,
+        file_size,
+        quick_hash,
+        tags,
+        workflow,
+        rating,
+    }
+}
+
+/// The metadata text, for ComfyUI images the editor workflow chunk, and
+/// the generator when the PNG names it outside the text.
+fn extract_parameters_metadata(path: &Path) -> (String, Option<String>, Option<&'static str>) {
+    match scanner::extract_metadata_and_workflow(path) {
+        Ok(scanner::PngMetadata {
+            metadata: Some(parameters),
+            workflow,
+            source_tool,
+        }) => (parameters, workflow, source_tool),
+        Ok(_) => (read_sidecar_txt(path), None, None),
         Err(err) => {
             log::warn!("PNG metadata read failed for {}: {}", path.display(), err);
-            read_sidecar_txt(path)
+            (read_sidecar_txt(path), None, None)
         }
     }
 }
@@ -391,3 +596,53 @@ fn read_sidecar_txt(path: &Path) -> String {
     }
     String::new()
 }
+
+/// Re-reads one image's metadata from disk and re-indexes it with the current
+/// parser, replacing its parsed fields, search text and prompt tags. Lets a
+/// misread record pick up parser fixes without rescanning its folder.
+#[tauri::command]
+pub async fn reparse_image(
+    image_id: i64,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ImageRecord> {
+    refresh_volume_status(&state, false);
+    let db = state.db.clone();
+    let cache_dir = state.cache_dir.clone();
+    let thumbnail_index = state.thumbnail_index.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let existing = db
+            .get_image_by_id(image_id)?
+            .ok_or_else(|| AppError::NotFound(format!("Image not found: {}", image_id)))?;
+        if db.is_offline_path(&existing.filepath) {
+            return Err(AppError::FileMissing(format!(
+                "{} is on a disconnected drive",
+                existing.filepath
+            )));
+        }
+        let path = PathBuf::from(&existing.filepath);
+        let scanned = scanner::scan_file(&path).ok_or_else(|| AppError::file_missing(&path))?;
+        let directory = PathBuf::from(&existing.directory);
+        let indexed_mtime = db.get_file_mtime(&existing.filepath)?;
+        let record = build_bulk_record(
+            &scanned.path,
+            scanned.file_mtime,
+            scanned.file_size,
+            &directory,
+        );
+        db.bulk_upsert_with_tags(std::slice::from_ref(&record))
+            .map_err(|error| AppError::from(error).context("Failed to re-index image"))?;
+        if indexed_mtime.is_some() && indexed_mtime != scanned.file_mtime {
+            discard_changed_thumbnails(&[path], &cache_dir, &thumbnail_index);
+        }
+        log::info!(
+            "Re-parsed {} (metadata={}, tags={})",
+            existing.filepath,
+            !record.params.raw_metadata.trim().is_empty(),
+            record.tags.len()
+        );
+        db.get_image_by_id(image_id)?
+            .ok_or_else(|| AppError::NotFound(format!("Image not found: {}", image_id)))
+    })
+    .await
+    .map_err(|error| AppError::Internal(error.to_string()))?
+}
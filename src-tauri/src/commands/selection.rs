@@ -0,0 +1,174 @@
+// ────────────────────────── Selection summaries ──────────────────────────
+
+/// One distinct value of a parameter and how many selected images use it.
+/// `value` is `None` for images without that parameter.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SelectionValueCount {
+    pub value: Option<String>,
+    pub count: usize,
+}
+
+/// How one generation parameter looks across a selection.
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectionParameter {
+    /// "model" | "sampler" | "steps" | "cfg_scale" | "size"
+    pub field: String,
+    /// True when every selected image has the same value (including "none").
+    pub shared: bool,
+    /// The common value when `shared`, otherwise `None`.
+    pub value: Option<String>,
+    /// Distinct values, most common first.
+    pub values: Vec<SelectionValueCount>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectionSummary {
+    /// Images found for the requested ids.
+    pub count: usize,
+    pub parameters: Vec<SelectionParameter>,
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+fn summarize_parameter(
+    field: &str,
+    records: &[ImageRecord],
+    value_of: impl Fn(&ImageRecord) -> Option<String>,
+) -> SelectionParameter {
+    let mut values: Vec<SelectionValueCount> = Vec::new();
+    for record in records {
+        let value = value_of(record);
+        match values.iter_mut().find(|entry| entry.value == value) {
+            Some(entry) => entry.count += 1,
+            None => values.push(SelectionValueCount { value, count: 1 }),
+        }
+    }
+    // Stable sort keeps first-seen order among equally common values.
+    values.sort_by_key(|entry| std::cmp::Reverse(entry.count));
+
+    let shared = values.len() == 1;
+    SelectionParameter {
+        field: field.to_string(),
+        shared,
+        value: if shared {
+            values[0].value.clone()
+        } else {
+            None
+        },
+        values,
+    }
+}
+
+fn summarize_records(records: &[ImageRecord]) -> SelectionSummary {
+    let parameters = if records.is_empty() {
+        Vec::new()
+    } else {
+        vec![
+            summarize_parameter("model", records, |record| {
+                non_empty(record.model_name.as_deref())
+                    .or_else(|| non_empty(record.model_hash.as_deref()))
+            }),
+            summarize_parameter("sampler", records, |record| {
+                non_empty(record.sampler.as_deref())
+            }),
+            summarize_parameter("steps", records, |record| {
+                non_empty(record.steps.as_deref())
+            }),
+            summarize_parameter("cfg_scale", records, |record| {
+                non_empty(record.cfg_scale.as_deref())
+            }),
+            summarize_parameter("size", records, |record| {
+                match (record.width, record.height) {
+                    (Some(width), Some(height)) => Some(format!("{}x{}", width, height)),
+                    _ => None,
+                }
+            }),
+        ]
+    };
+    SelectionSummary {
+        count: records.len(),
+        parameters,
+    }
+}
+
+/// Which of model, sampler, steps, CFG and size are identical across the
+/// selected images and which vary. Unknown ids are ignored.
+#[tauri::command]
+pub fn summarize_selection(
+    ids: Vec<i64>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<SelectionSummary> {
+    let started = std::time::Instant::now();
+    let result = state.db.get_images_by_ids(&ids);
+    perf::record_query("summarize_selection", started.elapsed(), result.is_ok());
+    Ok(summarize_records(&result?))
+}
+
+#[cfg(test)]
+mod selection_tests {
+    use super::*;
+
+    fn record(id: i64, sampler: &str, steps: &str, width: u32) -> ImageRecord {
+        ImageRecord {
+            id,
+            filepath: format!("/library/{}.png", id),
+            filename: format!("{}.png", id),
+            directory: "/library".to_string(),
+            prompt: String::new(),
+            negative_prompt: String::new(),
+            steps: Some(steps.to_string()),
+            sampler: Some(sampler.to_string()),
+            cfg_scale: Some("7".to_string()),
+            seed: None,
+            width: Some(width),
+            height: Some(512),
+            model_hash: Some("abc123".to_string()),
+            model_name: None,
+            raw_metadata: String::new(),
+            is_favorite: false,
+            is_locked: false,
+        }
+    }
+
+    #[test]
+    fn summary_separates_shared_and_varying_parameters() {
+        let records = vec![
+            record(1, "Euler a", "20", 512),
+            record(2, "DPM++ 2M", "20", 512),
+            record(3, "DPM++ 2M", "20", 768),
+        ];
+        let summary = summarize_records(&records);
+        assert_eq!(summary.count, 3);
+
+        let field = |name: &str| {
+            summary
+                .parameters
+                .iter()
+                .find(|parameter| parameter.field == name)
+                .unwrap()
+        };
+        assert!(field("steps").shared);
+        assert_eq!(field("steps").value.as_deref(), Some("20"));
+        assert_eq!(field("model").value.as_deref(), Some("abc123"));
+        assert!(field("cfg_scale").shared);
+
+        let sampler = field("sampler");
+        assert!(!sampler.shared);
+        assert_eq!(sampler.value, None);
+        assert_eq!(
+            sampler.values[0],
+            SelectionValueCount {
+                value: Some("DPM++ 2M".to_string()),
+                count: 2
+            }
+        );
+        assert_eq!(field("size").values.len(), 2);
+
+        assert!(summarize_records(&[]).parameters.is_empty());
+    }
+}
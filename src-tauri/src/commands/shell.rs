@@ -12,10 +12,10 @@ pub fn directory_exists(path: String) -> bool {
 
 /// Opens the native file explorer with the given file selected.
 #[tauri::command]
-pub async fn open_file_location(filepath: String) -> Result<(), String> {
+pub async fn open_file_location(filepath: String) -> AppResult<()> {
     let path = PathBuf::from(&filepath);
     if !path.exists() {
-        return Err(format!("File not found: {}", filepath));
+        return Err(AppError::file_missing(&path));
     }
 
     #[cfg(target_os = "windows")]
@@ -24,7 +24,7 @@ pub async fn open_file_location(filepath: String) -> Result<(), String> {
             .arg("/select,")
             .arg(&filepath)
             .spawn()
-            .map_err(|e| format!("Failed to open explorer: {}", e))?;
+            .map_err(|e| AppError::from(e).context("Failed to open explorer"))?;
     }
 
     #[cfg(target_os = "macos")]
@@ -33,7 +33,7 @@ pub async fn open_file_location(filepath: String) -> Result<(), String> {
             .arg("-R")
             .arg(&filepath)
             .spawn()
-            .map_err(|e| format!("Failed to open Finder: {}", e))?;
+            .map_err(|e| AppError::from(e).context("Failed to open Finder"))?;
     }
 
     #[cfg(target_os = "linux")]
@@ -43,9 +43,34 @@ pub async fn open_file_location(filepath: String) -> Result<(), String> {
             std::process::Command::new("xdg-open")
                 .arg(parent)
                 .spawn()
-                .map_err(|e| format!("Failed to open file manager: {}", e))?;
+                .map_err(|e| AppError::from(e).context("Failed to open file manager"))?;
         }
     }
 
     Ok(())
 }
+
+/// Puts the selected originals on the clipboard as files, so they paste into
+/// a file manager or chat app as-is. Returns how many files were copied.
+#[tauri::command]
+pub async fn copy_files_to_clipboard(
+    ids: Vec<i64>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<usize> {
+    let records = state.db.get_images_by_ids(&ids)?;
+    let paths: Vec<PathBuf> = records
+        .into_iter()
+        .map(|record| PathBuf::from(record.filepath))
+        .filter(|path| path.is_file())
+        .collect();
+    if paths.is_empty() {
+        return Err(AppError::FileMissing(
+            "None of the selected files are available".to_string(),
+        ));
+    }
+    let copied = paths.len();
+    tauri::async_runtime::spawn_blocking(move || crate::file_clipboard::copy_files(&paths))
+        .await
+        .map_err(|error| error.to_string())??;
+    Ok(copied)
+}
@@ -12,10 +12,10 @@ pub fn save_sidecar_tags(
     tags: Vec<String>,
     notes: Option<String>,
     state: tauri::State<AppState>,
-) -> Result<String, String> {
+) -> AppResult<String> {
     let file_path = PathBuf::from(&filepath);
     if !file_path.exists() {
-        return Err(format!("File not found: {}", filepath));
+        return Err(AppError::file_missing(&file_path));
     }
 
     // Preserve existing data (like ratings) if any
@@ -25,16 +25,37 @@ pub fn save_sidecar_tags(
 
     sidecar::write_sidecar(&file_path, &data)?;
 
-    if let Some(image_id) = state
-        .db
-        .get_image_id_by_filepath(&filepath)
-        .map_err(|e| e.to_string())?
-    {
-        state
-            .db
-            .replace_image_tags(image_id, &data.tags)
-            .map_err(|e| e.to_string())?;
+    if let Some(image_id) = state.db.get_image_id_by_filepath(&filepath)? {
+        state.db.replace_image_tags(image_id, &data.tags)?;
     }
 
     Ok("Sidecar saved".to_string())
 }
+
+/// Sets or clears (`None`) an image's 1-5 star rating in its sidecar and
+/// the index, so rating rules pick it up.
+#[tauri::command]
+pub fn set_image_rating(
+    filepath: String,
+    rating: Option<u8>,
+    state: tauri::State<AppState>,
+) -> AppResult<()> {
+    if rating.is_some_and(|rating| !(1..=5).contains(&rating)) {
+        return Err(AppError::InvalidInput(
+            "Ratings go from 1 to 5 stars".to_string(),
+        ));
+    }
+    let file_path = PathBuf::from(&filepath);
+    if !file_path.exists() {
+        return Err(AppError::file_missing(&file_path));
+    }
+
+    let mut data = sidecar::read_sidecar(&file_path).unwrap_or_default();
+    data.rating = rating;
+    sidecar::write_sidecar(&file_path, &data)?;
+
+    if let Some(image_id) = state.db.get_image_id_by_filepath(&filepath)? {
+        state.db.set_image_rating(image_id, rating)?;
+    }
+    Ok(())
+}
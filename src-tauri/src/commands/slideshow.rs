@@ -0,0 +1,252 @@
+// ────────────────────────── Slideshow ──────────────────────────
+
+/// Upper bound for a single slideshow batch request.
+const SLIDESHOW_MAX_BATCH: u32 = 200;
+/// Display proxies (JPEG XL, EXR) generated inline before a batch is returned; the rest are
+/// handed to the background prefetcher so the first slide shows immediately.
+const SLIDESHOW_EAGER_PROXIES: usize = 2;
+/// Maximum number of proxies warmed per prefetch request.
+const DISPLAY_PREFETCH_LIMIT: usize = 32;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SlideshowFilter {
+    pub query: Option<String>,
+    pub tags_include: Vec<String>,
+    pub tags_exclude: Vec<String>,
+    pub cursor: Option<String>,
+    pub generation_types: Option<Vec<String>>,
+    pub sort_by: Option<String>,
+    pub model_filter: Option<String>,
+    pub model_family_filters: Option<Vec<String>>,
+    pub scheduler_filters: Option<Vec<String>>,
+    pub resolution_buckets: Option<Vec<String>>,
+    pub source_tools: Option<Vec<String>>,
+    pub workflow_statuses: Option<Vec<String>>,
+    pub prompt_clusters: Option<Vec<i64>>,
+    pub collections: Option<Vec<i64>>,
+    pub max_sharpness: Option<f32>,
+    pub failed_renders_only: bool,
+    pub include_hidden: bool,
+    pub refine: Option<String>,
+    pub extra_params: Vec<crate::database::ExtraParamFilter>,
+    pub embeddings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlideshowItem {
+    pub id: i64,
+    pub filepath: String,
+    pub filename: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub display_path: String,
+    /// False while the display proxy is still being generated in the background.
+    pub display_ready: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlideshowBatch {
+    pub items: Vec<SlideshowItem>,
+    /// Cursor for the next sequential batch; always `None` when shuffling.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DisplayPrefetchRequest {
+    filepaths: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct DisplayProxyReady {
+    filepath: String,
+    display_path: String,
+}
+
+/// Returns the next batch of slideshow images honoring the gallery filters,
+/// with display paths already resolved.
+///
+/// Display proxies beyond the first few are warmed in the background and
+/// announced through `display-proxy-ready` events.
+#[tauri::command]
+pub async fn get_slideshow_batch(
+    filter: SlideshowFilter,
+    count: u32,
+    shuffle: bool,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<SlideshowBatch> {
+    let db = state.db.clone();
+    let cache_dir = state.cache_dir.clone();
+    let inflight = state.display_proxy_inflight.clone();
+    let limit = count.clamp(1, SLIDESHOW_MAX_BATCH);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let query = filter
+            .query
+            .as_deref()
+            .filter(|value| !value.trim().is_empty());
+        let params = crate::database::FilterCursorParams {
+            query,
+            include_tags: &filter.tags_include,
+            exclude_tags: &filter.tags_exclude,
+            refine: filter.refine.as_deref(),
+            extra_params: &filter.extra_params,
+            embeddings: &filter.embeddings,
+            options: crate::database::CursorQueryOptions {
+                cursor: filter.cursor.as_deref(),
+                limit,
+                sort_by: filter.sort_by.as_deref(),
+                generation_types: filter.generation_types.as_deref(),
+                model_filter: filter.model_filter.as_deref(),
+                model_family_filters: filter.model_family_filters.as_deref(),
+                scheduler_filters: filter.scheduler_filters.as_deref(),
+                resolution_buckets: filter.resolution_buckets.as_deref(),
+                source_tools: filter.source_tools.as_deref(),
+                workflow_statuses: filter.workflow_statuses.as_deref(),
+                prompt_clusters: filter.prompt_clusters.as_deref(),
+                collections: filter.collections.as_deref(),
+                max_sharpness: filter.max_sharpness,
+                failed_renders_only: filter.failed_renders_only,
+                include_hidden: filter.include_hidden,
+                collapse_duplicates: false,
+            },
+        };
+        let (records, next_cursor) = if shuffle {
+            (db.sample_filtered_images(params), None)
+        } else {
+            match db.filter_images_cursor(params) {
+                Ok(page) => (Ok(page.items), page.next_cursor),
+                Err(error) => (Err(error), None),
+            }
+        };
+        let records = records.map_err(AppError::from)?;
+
+        let mut items = Vec::with_capacity(records.len());
+        let mut deferred = Vec::<String>::new();
+        let mut eager_budget = SLIDESHOW_EAGER_PROXIES;
+        for record in records {
+            let source = Path::new(&record.filepath);
+            let (display_path, display_ready) = if !needs_display_proxy(source) {
+                (record.filepath.clone(), true)
+            } else {
+                match display_proxy_path(source, &cache_dir) {
+                    Ok(proxy) if proxy.exists() => (proxy.to_string_lossy().to_string(), true),
+                    Ok(_) if eager_budget > 0 => {
+                        eager_budget -= 1;
+                        match resolve_display_path(&record.filepath, &cache_dir) {
+                            Ok(path) => (path, true),
+                            Err(error) => {
+                                log::warn!("Slideshow proxy failed: {}", error);
+                                continue;
+                            }
+                        }
+                    }
+                    Ok(proxy) => {
+                        deferred.push(record.filepath.clone());
+                        (proxy.to_string_lossy().to_string(), false)
+                    }
+                    // Missing on disk; skip rather than showing a broken slide.
+                    Err(_) => continue,
+                }
+            };
+
+            items.push(SlideshowItem {
+                id: record.id,
+                filepath: record.filepath,
+                filename: record.filename,
+                width: record.width,
+                height: record.height,
+                display_path,
+                display_ready,
+            });
+        }
+
+        if !deferred.is_empty() {
+            spawn_display_prefetch(app, cache_dir, inflight, deferred);
+        }
+
+        Ok(SlideshowBatch { items, next_cursor })
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+/// Listens for `slideshow-prefetch` events (`{ filepaths: [...] }`) from the
+/// frontend and warms the listed display proxies off the UI thread.
+pub fn register_display_prefetch_listener(app: &tauri::AppHandle) {
+    let app_handle = app.clone();
+    app.listen("slideshow-prefetch", move |event| {
+        let request = match serde_json::from_str::<DisplayPrefetchRequest>(event.payload()) {
+            Ok(request) => request,
+            Err(error) => {
+                log::warn!("Ignoring malformed slideshow-prefetch payload: {}", error);
+                return;
+            }
+        };
+        let Some(state) = app_handle.try_state::<AppState>() else {
+            return;
+        };
+        spawn_display_prefetch(
+            app_handle.clone(),
+            state.cache_dir.clone(),
+            state.display_proxy_inflight.clone(),
+            request.filepaths,
+        );
+    });
+}
+
+fn spawn_display_prefetch(
+    app: tauri::AppHandle,
+    cache_dir: PathBuf,
+    inflight: std::sync::Arc<std::sync::RwLock<std::collections::HashSet<String>>>,
+    filepaths: Vec<String>,
+) {
+    let pending: Vec<String> = {
+        let Ok(mut inflight_set) = inflight.write() else {
+            return;
+        };
+        filepaths
+            .into_iter()
+            .filter(|filepath| needs_display_proxy(Path::new(filepath)))
+            .filter(|filepath| inflight_set.insert(filepath.clone()))
+            .take(DISPLAY_PREFETCH_LIMIT)
+            .collect()
+    };
+    if pending.is_empty() {
+        return;
+    }
+
+    let inflight_for_worker = inflight.clone();
+    let pending_for_worker = pending.clone();
+    let spawn_result = std::thread::Builder::new()
+        .name("display-prefetch".into())
+        .spawn(move || {
+            for filepath in pending_for_worker {
+                match resolve_display_path(&filepath, &cache_dir) {
+                    Ok(display_path) => {
+                        let _ = app.emit(
+                            "display-proxy-ready",
+                            DisplayProxyReady {
+                                filepath: filepath.clone(),
+                                display_path,
+                            },
+                        );
+                    }
+                    Err(error) => log::warn!("Display proxy prefetch failed: {}", error),
+                }
+                if let Ok(mut inflight_set) = inflight_for_worker.write() {
+                    inflight_set.remove(&filepath);
+                }
+            }
+        });
+
+    if let Err(error) = spawn_result {
+        log::warn!("Failed to spawn display prefetch worker: {}", error);
+        if let Ok(mut inflight_set) = inflight.write() {
+            for filepath in &pending {
+                inflight_set.remove(filepath);
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+// ────────────────────────── Statistics ──────────────────────────
+
+/// Per-week or per-month usage of prompt terms/tags, for trend charts.
+#[tauri::command]
+pub async fn get_keyword_trends(
+    terms: Vec<String>,
+    bucket: TrendBucket,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<KeywordTrends> {
+    if terms.iter().all(|term| term.trim().is_empty()) {
+        return Err(AppError::InvalidInput(
+            "At least one keyword is required".to_string(),
+        ));
+    }
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = std::time::Instant::now();
+        let result = db.get_keyword_trends(&terms, bucket);
+        perf::record_query("get_keyword_trends", started.elapsed(), result.is_ok());
+        result.map_err(AppError::from)
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+/// Which checkpoints were used when, with their average settings.
+#[tauri::command]
+pub async fn get_model_usage(
+    bucket: TrendBucket,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ModelUsage> {
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = std::time::Instant::now();
+        let result = db.get_model_usage(bucket);
+        perf::record_query("get_model_usage", started.elapsed(), result.is_ok());
+        result.map_err(AppError::from)
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+/// Default and maximum rows per disk-usage grouping.
+const DISK_USAGE_DEFAULT_LIMIT: u32 = 25;
+const DISK_USAGE_MAX_LIMIT: u32 = 500;
+
+/// Where library disk space goes, to help decide what to archive or delete.
+#[tauri::command]
+pub async fn get_disk_usage_report(
+    limit: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<DiskUsageReport> {
+    let limit = limit
+        .unwrap_or(DISK_USAGE_DEFAULT_LIMIT)
+        .clamp(1, DISK_USAGE_MAX_LIMIT);
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = std::time::Instant::now();
+        let result = db.get_disk_usage_report(limit);
+        perf::record_query("get_disk_usage_report", started.elapsed(), result.is_ok());
+        result.map_err(AppError::from)
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+/// Directories listed in the metadata coverage report.
+const METADATA_COVERAGE_DEFAULT_LIMIT: u32 = 25;
+
+/// Prompt, model and seed coverage across the library. The incomplete images
+/// themselves are listed by the `missing_metadata` generation type filter.
+#[tauri::command]
+pub async fn get_metadata_coverage(
+    limit: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<MetadataCoverage> {
+    let limit = limit
+        .unwrap_or(METADATA_COVERAGE_DEFAULT_LIMIT)
+        .clamp(1, DISK_USAGE_MAX_LIMIT);
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = std::time::Instant::now();
+        let result = db.get_metadata_coverage(limit);
+        perf::record_query("get_metadata_coverage", started.elapsed(), result.is_ok());
+        result.map_err(AppError::from)
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
@@ -0,0 +1,59 @@
+// ────────────────────────── Tag dictionary ──────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TagDictionaryImportResult {
+    pub imported: usize,
+    /// Rows that were malformed or had no tag.
+    pub skipped: usize,
+}
+
+/// Loads a danbooru-style tag CSV from a local path or http(s) URL, replacing
+/// any previously imported dictionary.
+#[tauri::command]
+pub async fn import_tag_dictionary(
+    source: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<TagDictionaryImportResult> {
+    let bytes = tag_dictionary::read_source(&source).await?;
+    let db = state.db.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (entries, skipped) = tag_dictionary::parse_csv(&bytes);
+        if entries.is_empty() {
+            return Err(AppError::InvalidInput(
+                "No tags found; expected rows of tag,category,post_count".to_string(),
+            ));
+        }
+        let imported = db
+            .replace_tag_dictionary(&entries)
+            .map_err(|error| AppError::from(error).context("Failed to store tag dictionary"))?;
+        log::info!(
+            "Imported {} dictionary tags ({} rows skipped)",
+            imported,
+            skipped
+        );
+        Ok(TagDictionaryImportResult { imported, skipped })
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+/// Removes the imported dictionary; autocomplete falls back to library tags.
+#[tauri::command]
+pub fn clear_tag_dictionary(state: tauri::State<'_, AppState>) -> AppResult<()> {
+    state.db.replace_tag_dictionary(&[])?;
+    Ok(())
+}
+
+/// Autocomplete entries with category and popularity for coloring.
+#[tauri::command]
+pub fn list_tag_suggestions(
+    prefix: Option<String>,
+    limit: u32,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<TagSuggestion>> {
+    let started = std::time::Instant::now();
+    let result = state.db.list_tag_suggestions(prefix.as_deref(), limit);
+    perf::record_query("list_tag_suggestions", started.elapsed(), result.is_ok());
+    result.map_err(AppError::from)
+}
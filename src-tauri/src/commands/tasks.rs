@@ -0,0 +1,30 @@
+// ────────────────────────── Background tasks ──────────────────────────
+
+/// Lists running and recently finished background tasks for the activity panel.
+#[tauri::command]
+pub fn list_background_tasks(state: tauri::State<'_, AppState>) -> AppResult<Vec<TaskSnapshot>> {
+    Ok(state.tasks.list())
+}
+
+/// Pauses or resumes a single task; it stops at its next checkpoint.
+#[tauri::command]
+pub fn pause_background_task(
+    task_id: u64,
+    paused: bool,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<()> {
+    state.tasks.set_paused(task_id, paused)
+}
+
+/// Requests cancellation; work already done (indexed rows, thumbnails,
+/// Forge outputs) is kept.
+#[tauri::command]
+pub fn cancel_background_task(task_id: u64, state: tauri::State<'_, AppState>) -> AppResult<()> {
+    state.tasks.cancel(task_id)
+}
+
+#[tauri::command]
+pub fn clear_finished_background_tasks(state: tauri::State<'_, AppState>) -> AppResult<()> {
+    state.tasks.clear_finished();
+    Ok(())
+}
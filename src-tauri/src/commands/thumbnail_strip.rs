@@ -0,0 +1,190 @@
+// ────────────────────────── Thumbnail strip ──────────────────────────
+
+/// Strip heights outside this range are either unreadable or huge.
+const STRIP_HEIGHT_RANGE: std::ops::RangeInclusive<u32> = 16..=2048;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StripFrame {
+    pub id: i64,
+    pub filename: String,
+    pub x: u32,
+    pub width: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThumbnailStripResult {
+    pub output_path: String,
+    /// Frame offsets as JSON, next to the strip with a `.json` extension.
+    pub offsets_path: String,
+    pub width: u32,
+    pub height: u32,
+    pub frames: Vec<StripFrame>,
+    /// Selected images that were missing, offline or failed to decode.
+    pub skipped_ids: Vec<i64>,
+}
+
+#[derive(Serialize)]
+struct StripOffsets<'a> {
+    width: u32,
+    height: u32,
+    frames: &'a [StripFrame],
+}
+
+fn render_thumbnail_strip(
+    records: Vec<ImageRecord>,
+    height: u32,
+    output_path: &Path,
+    format: image::ImageFormat,
+) -> AppResult<ThumbnailStripResult> {
+    let scaled: Vec<(ImageRecord, Option<image::RgbImage>)> = records
+        .into_par_iter()
+        .map(|record| {
+            let frame = image_decode::open_image(Path::new(&record.filepath))
+                .map(|image| crate::thumbnail_strip::scale_frame(&image, height))
+                .map_err(|error| {
+                    log::warn!("Thumbnail strip: skipping {}: {}", record.filepath, error);
+                })
+                .ok();
+            (record, frame)
+        })
+        .collect();
+
+    let mut kept = Vec::with_capacity(scaled.len());
+    let mut frames = Vec::with_capacity(scaled.len());
+    let mut skipped_ids = Vec::new();
+    for (record, frame) in scaled {
+        match frame {
+            Some(frame) => {
+                kept.push(record);
+                frames.push(frame);
+            }
+            None => skipped_ids.push(record.id),
+        }
+    }
+    if frames.is_empty() {
+        return Err(AppError::FileMissing(
+            "None of the selected images could be read".to_string(),
+        ));
+    }
+    let total_width: u64 = frames.iter().map(|frame| frame.width() as u64).sum();
+    if total_width > crate::thumbnail_strip::MAX_STRIP_WIDTH as u64 {
+        return Err(AppError::InvalidInput(format!(
+            "A {}px tall strip of {} images would be {}px wide; the limit is {}px",
+            height,
+            frames.len(),
+            total_width,
+            crate::thumbnail_strip::MAX_STRIP_WIDTH
+        )));
+    }
+
+    let (strip, offsets) = crate::thumbnail_strip::compose(&frames, height);
+    drop(frames);
+    strip
+        .save_with_format(output_path, format)
+        .map_err(|error| {
+            AppError::from(error).context(format!(
+                "Failed to write thumbnail strip {}",
+                output_path.display()
+            ))
+        })?;
+
+    let frames: Vec<StripFrame> = kept
+        .into_iter()
+        .zip(offsets)
+        .map(|(record, offset)| StripFrame {
+            id: record.id,
+            filename: record.filename,
+            x: offset.x,
+            width: offset.width,
+        })
+        .collect();
+    let offsets_path = output_path.with_extension("json");
+    let offsets_json = serde_json::to_string_pretty(&StripOffsets {
+        width: strip.width(),
+        height,
+        frames: &frames,
+    })?;
+    std::fs::write(&offsets_path, offsets_json).map_err(|error| {
+        AppError::from(error).context(format!("Failed to write {}", offsets_path.display()))
+    })?;
+
+    Ok(ThumbnailStripResult {
+        output_path: output_path.to_string_lossy().to_string(),
+        offsets_path: offsets_path.to_string_lossy().to_string(),
+        width: strip.width(),
+        height,
+        frames,
+        skipped_ids,
+    })
+}
+
+/// Renders the selected images, in selection order, into one horizontal
+/// strip `height` pixels tall for storyboards and video timelines. The strip
+/// is a PNG or JPEG depending on `output_path`'s extension.
+#[tauri::command]
+pub async fn export_thumbnail_strip(
+    ids: Vec<i64>,
+    height: u32,
+    output_path: String,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ThumbnailStripResult> {
+    if ids.is_empty() {
+        return Err(AppError::InvalidInput(
+            "No images selected for the strip".to_string(),
+        ));
+    }
+    if !STRIP_HEIGHT_RANGE.contains(&height) {
+        return Err(AppError::InvalidInput(format!(
+            "Strip height must be between {} and {} pixels",
+            STRIP_HEIGHT_RANGE.start(),
+            STRIP_HEIGHT_RANGE.end()
+        )));
+    }
+    let output_path = PathBuf::from(output_path);
+    let format = match image::ImageFormat::from_path(&output_path) {
+        Ok(format @ (image::ImageFormat::Png | image::ImageFormat::Jpeg)) => format,
+        _ => {
+            return Err(AppError::InvalidInput(
+                "Save the strip as .png or .jpg".to_string(),
+            ))
+        }
+    };
+
+    refresh_volume_status(&state, false);
+    let mut by_id: std::collections::HashMap<i64, ImageRecord> = state
+        .db
+        .get_images_by_ids(&ids)?
+        .into_iter()
+        .map(|record| (record.id, record))
+        .collect();
+    let mut records = Vec::with_capacity(by_id.len());
+    let mut skipped_ids = Vec::new();
+    for id in &ids {
+        let Some(record) = by_id.remove(id) else {
+            continue;
+        };
+        if state.db.is_offline_path(&record.filepath) || !Path::new(&record.filepath).is_file() {
+            skipped_ids.push(record.id);
+        } else {
+            records.push(record);
+        }
+    }
+
+    let started = std::time::Instant::now();
+    let mut result = tauri::async_runtime::spawn_blocking(move || {
+        render_thumbnail_strip(records, height, &output_path, format)
+    })
+    .await
+    .map_err(|error| AppError::Internal(error.to_string()))??;
+    skipped_ids.append(&mut result.skipped_ids);
+    result.skipped_ids = skipped_ids;
+    log::info!(
+        "Exported a {}x{} thumbnail strip of {} images in {:?} ({} skipped)",
+        result.width,
+        result.height,
+        result.frames.len(),
+        started.elapsed(),
+        result.skipped_ids.len()
+    );
+    Ok(result)
+}
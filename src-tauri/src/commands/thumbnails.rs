@@ -1,21 +1,100 @@
 // ────────────────────────── Thumbnails ──────────────────────────
 
+/// Saves what rendering thumbnails recorded: the colour stats behind the hue
+/// and brightness sorts, sharpness, perceptual hashes, and which sources
+/// failed and why.
+fn save_thumbnail_results(
+    db: &crate::database::Database,
+    results: image_processing::ThumbnailResults,
+) {
+    let stats = results.color_stats;
+    if let Err(error) = db.set_color_stats(&stats) {
+        log::warn!(
+            "Failed to save colour stats for {} thumbnails: {}",
+            stats.len(),
+            error
+        );
+    }
+    let quality = results.image_quality;
+    if let Err(error) = db.set_image_quality(&quality) {
+        log::warn!(
+            "Failed to save sharpness for {} thumbnails: {}",
+            quality.len(),
+            error
+        );
+    }
+    let phashes = results.phashes;
+    if let Err(error) = db.set_phashes(&phashes) {
+        log::warn!(
+            "Failed to save perceptual hashes for {} thumbnails: {}",
+            phashes.len(),
+            error
+        );
+    }
+
+    let outcomes = results.outcomes;
+    // A drive that went away is not a problem with the file.
+    let failed: Vec<_> = outcomes
+        .failed
+        .into_iter()
+        .filter(|failure| !db.is_offline_path(&failure.filepath))
+        .collect();
+    if let Err(error) = db
+        .record_thumbnail_failures(&failed)
+        .and_then(|_| db.clear_thumbnail_failures(&outcomes.rendered))
+    {
+        log::warn!("Failed to save thumbnail failures: {}", error);
+    }
+}
+
+/// Throws away the thumbnails of images whose file changed since they were
+/// indexed, so the next request renders them from the new pixels.
+pub(crate) fn discard_changed_thumbnails(
+    changed: &[PathBuf],
+    cache_dir: &Path,
+    thumbnail_index: &RwLock<std::collections::HashSet<String>>,
+) {
+    if changed.is_empty() {
+        return;
+    }
+    let discarded: Vec<String> = changed
+        .iter()
+        .map(|source| {
+            image_processing::discard_thumbnail(source, cache_dir)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    if let Ok(mut index) = thumbnail_index.write() {
+        for thumb_path in &discarded {
+            index.remove(thumb_path);
+        }
+    }
+    log::info!(
+        "Discarded {} thumbnails of images changed on disk",
+        discarded.len()
+    );
+}
+
 /// Starts a full-library thumbnail pre-cache pass in the background.
 ///
-/// Emits:
+/// Emits to the calling window:
 /// - `thumbnail-cache-progress`
 /// - `thumbnail-cache-complete`
 #[tauri::command]
 pub fn precache_all_thumbnails(
     app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     if state
         .thumbnail_precache_running
         .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
         .is_err()
     {
-        return Err("Thumbnail cache warmup is already running".to_string());
+        return Err(AppError::InvalidInput(
+            "Thumbnail cache warmup is already running".to_string(),
+        ));
     }
 
     let db = state.db.clone();
@@ -27,8 +106,11 @@ pub fn precache_all_thumbnails(
         .read()
         .map(|profile| *profile)
         .unwrap_or(StorageProfile::Hdd);
-    let app_handle = app.clone();
+    let events = EventSink::for_window(&app, &window);
     let running_flag = state.thumbnail_precache_running.clone();
+    let task = state
+        .tasks
+        .start(TaskKind::ThumbnailPrecache, "Library thumbnails");
     let running_flag_for_worker = running_flag.clone();
 
     std::thread::Builder::new()
@@ -51,7 +133,8 @@ pub fn precache_all_thumbnails(
 
             if let Err(error) = image_processing::prepare_cache_dir(&cache_dir) {
                 log::error!("Thumbnail pre-cache failed to prepare cache dir: {}", error);
-                let _ = app_handle.emit(
+                task.fail(error.to_string());
+                let _ = events.emit(
                     "thumbnail-cache-complete",
                     ThumbnailPrecacheComplete {
                         total: 0,
@@ -67,7 +150,8 @@ pub fn precache_all_thumbnails(
                 Ok(filepaths) => filepaths,
                 Err(error) => {
                     log::error!("Thumbnail pre-cache failed to read filepaths: {}", error);
-                    let _ = app_handle.emit(
+                    task.fail(error.to_string());
+                    let _ = events.emit(
                         "thumbnail-cache-complete",
                         ThumbnailPrecacheComplete {
                             total: 0,
@@ -87,7 +171,7 @@ pub fn precache_all_thumbnails(
             let mut pending_paths = Vec::<PathBuf>::new();
             let mut discovered_thumb_paths = Vec::<String>::new();
 
-            let _ = app_handle.emit(
+            let _ = events.emit(
                 "thumbnail-cache-progress",
                 ThumbnailPrecacheProgress {
                     current: 0,
@@ -100,7 +184,7 @@ pub fn precache_all_thumbnails(
             );
 
             if total == 0 {
-                let _ = app_handle.emit(
+                let _ = events.emit(
                     "thumbnail-cache-complete",
                     ThumbnailPrecacheComplete {
                         total,
@@ -112,28 +196,45 @@ pub fn precache_all_thumbnails(
                 return;
             }
 
+            task.set_message("Checking existing thumbnails");
             let index_snapshot = thumbnail_index
                 .read()
                 .map(|index| index.clone())
                 .unwrap_or_default();
+            // Thumbnails cached before colour stats, sharpness or perceptual
+            // hashes existed get them from the cached file at the end of the
+            // pass.
+            let mut missing_stats = db.filepaths_missing_color_stats().unwrap_or_default();
+            missing_stats.extend(db.filepaths_missing_image_quality().unwrap_or_default());
+            missing_stats.extend(db.filepaths_missing_phash().unwrap_or_default());
+            let mut stats_backfill = Vec::<String>::new();
             for (idx, filepath) in all_filepaths.into_iter().enumerate() {
                 let source = Path::new(&filepath);
                 let primary_path = image_processing::get_thumbnail_cache_path(source, &cache_dir);
                 let primary_key = primary_path.to_string_lossy().to_string();
 
                 // Skip if current-format thumbnail exists.
-                if index_snapshot.contains(&primary_key) {
-                    skipped += 1;
+                let cached = if index_snapshot.contains(&primary_key) {
+                    true
                 } else if primary_path.exists() {
-                    skipped += 1;
                     discovered_thumb_paths.push(primary_key);
+                    true
+                } else {
+                    false
+                };
+                if cached {
+                    skipped += 1;
+                    if missing_stats.contains(&filepath) {
+                        stats_backfill.push(filepath);
+                    }
                 } else {
                     pending_paths.push(PathBuf::from(filepath));
                 }
 
                 let current = idx + 1;
                 if current % 1_024 == 0 || current == total {
-                    let _ = app_handle.emit(
+                    task.set_progress(current, total);
+                    let _ = events.emit(
                         "thumbnail-cache-progress",
                         ThumbnailPrecacheProgress {
                             current,
@@ -158,11 +259,18 @@ pub fn precache_all_thumbnails(
             let chunk_size = precache_chunk_size(storage_profile).max(1);
             let mut processed = skipped;
 
+            task.set_message("Generating thumbnails");
             for chunk in pending_paths.chunks(chunk_size) {
-                let generated_chunk =
+                if !task.checkpoint() {
+                    break;
+                }
+                let rendered =
                     image_processing::generate_thumbnails(chunk, &cache_dir, storage_profile);
+                save_thumbnail_results(&db, rendered.results);
+                let generated_chunk = rendered.thumbnails;
                 generated += generated_chunk.len();
                 processed += chunk.len();
+                task.set_progress(processed, total);
 
                 let failed_in_chunk = chunk.len().saturating_sub(generated_chunk.len());
                 failed += failed_in_chunk;
@@ -191,7 +299,7 @@ pub fn precache_all_thumbnails(
                     }
                 }
 
-                let _ = app_handle.emit(
+                let _ = events.emit(
                     "thumbnail-cache-progress",
                     ThumbnailPrecacheProgress {
                         current: processed,
@@ -204,7 +312,29 @@ pub fn precache_all_thumbnails(
                 );
             }
 
-            let _ = app_handle.emit(
+            if !stats_backfill.is_empty() && !task.is_cancelled() {
+                task.set_message("Computing colour stats and sharpness");
+                let mut backfilled = 0usize;
+                for chunk in stats_backfill.chunks(chunk_size) {
+                    if !task.checkpoint() {
+                        break;
+                    }
+                    let (read, results) = image_processing::backfill_thumbnail_stats(
+                        chunk,
+                        &cache_dir,
+                        storage_profile,
+                    );
+                    save_thumbnail_results(&db, results);
+                    backfilled += read;
+                }
+                log::info!(
+                    "Computed colour stats, sharpness and hashes for {} of {} cached thumbnails",
+                    backfilled,
+                    stats_backfill.len()
+                );
+            }
+
+            let _ = events.emit(
                 "thumbnail-cache-complete",
                 ThumbnailPrecacheComplete {
                     total,
@@ -221,7 +351,8 @@ pub fn precache_all_thumbnails(
                 generated as f64
             };
             log::info!(
-                "Thumbnail pre-cache complete: total={}, generated={}, skipped={}, failed={}, profile={}, throughput={:.1} images/s",
+                "Thumbnail pre-cache {}: total={}, generated={}, skipped={}, failed={}, profile={}, throughput={:.1} images/s",
+                if task.is_cancelled() { "cancelled" } else { "complete" },
                 total,
                 generated,
                 skipped,
@@ -238,11 +369,12 @@ pub fn precache_all_thumbnails(
     Ok(())
 }
 
-fn is_jxl_path(path: &Path) -> bool {
+/// Formats the WebView can't show: JPEG XL, and OpenEXR, which also needs
+/// tone mapping.
+fn needs_display_proxy(path: &Path) -> bool {
     path.extension()
         .and_then(|value| value.to_str())
-        .map(|value| value.eq_ignore_ascii_case("jxl"))
-        .unwrap_or(false)
+        .is_some_and(|value| matches!(value.to_ascii_lowercase().as_str(), "jxl" | "exr"))
 }
 
 fn display_cache_directory(cache_dir: &Path) -> PathBuf {
@@ -253,110 +385,137 @@ fn display_cache_directory(cache_dir: &Path) -> PathBuf {
         .join("display-cache")
 }
 
-/// Returns a viewer-displayable path for a source image.
+/// Computes the cache location of the PNG display proxy for a source image.
 ///
-/// For JPEG XL files, this generates a cached PNG proxy so the frontend can render
-/// consistently even when platform WebView codec support is unavailable.
-#[tauri::command]
-pub async fn get_display_image_path(
-    filepath: String,
-    state: tauri::State<'_, AppState>,
-) -> Result<String, String> {
-    let cache_dir = state.cache_dir.clone();
-    tauri::async_runtime::spawn_blocking(move || {
-        let source = PathBuf::from(&filepath);
-        if !source.exists() {
-            return Err(format!("File not found: {}", filepath));
-        }
-        if !is_jxl_path(&source) {
-            return Ok(filepath);
-        }
+/// The key covers path, size and mtime so edited files get a fresh proxy.
+fn display_proxy_path(source: &Path, cache_dir: &Path) -> AppResult<PathBuf> {
+    let metadata = std::fs::metadata(source).map_err(|error| {
+        AppError::from(error).context(format!(
+            "Failed to read source metadata for {}",
+            source.display()
+        ))
+    })?;
 
-        let display_cache_dir = display_cache_directory(&cache_dir);
-        std::fs::create_dir_all(&display_cache_dir).map_err(|error| {
-            format!(
-                "Failed to create display cache directory {}: {}",
-                display_cache_dir.display(),
-                error
-            )
-        })?;
+    let modified_ns = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    let mut hasher = Sha256::new();
+    hasher.update(source.to_string_lossy().as_bytes());
+    hasher.update(metadata.len().to_le_bytes());
+    hasher.update(modified_ns.to_le_bytes());
+    let hash = format!("{:x}", hasher.finalize());
+    Ok(display_cache_directory(cache_dir).join(format!("{}.png", hash)))
+}
 
-        let metadata = std::fs::metadata(&source).map_err(|error| {
-            format!(
-                "Failed to read source metadata for {}: {}",
-                source.display(),
-                error
-            )
-        })?;
+/// Resolves the viewer-displayable path for a source image, generating the
+/// PNG proxy when it is not cached yet. Proxies are 8-bit: 16-bit sources
+/// are dithered and EXR is tone-mapped.
+fn resolve_display_path(filepath: &str, cache_dir: &Path) -> AppResult<String> {
+    let source = PathBuf::from(filepath);
+    if !source.exists() {
+        return Err(AppError::FileMissing(format!(
+            "File not found: {}",
+            filepath
+        )));
+    }
+    if !needs_display_proxy(&source) {
+        return Ok(filepath.to_string());
+    }
 
-        let modified_ns = metadata
-            .modified()
-            .ok()
-            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
-            .map(|duration| duration.as_nanos())
-            .unwrap_or(0);
-        let mut hasher = Sha256::new();
-        hasher.update(source.to_string_lossy().as_bytes());
-        hasher.update(metadata.len().to_le_bytes());
-        hasher.update(modified_ns.to_le_bytes());
-        let hash = format!("{:x}", hasher.finalize());
-        let cache_path = display_cache_dir.join(format!("{}.png", hash));
-
-        if cache_path.exists() {
-            return Ok(cache_path.to_string_lossy().to_string());
-        }
+    let display_cache_dir = display_cache_directory(cache_dir);
+    std::fs::create_dir_all(&display_cache_dir).map_err(|error| {
+        AppError::from(error).context(format!(
+            "Failed to create display cache directory {}",
+            display_cache_dir.display()
+        ))
+    })?;
+
+    let cache_path = display_proxy_path(&source, cache_dir)?;
+    let cached = cache_path.exists();
+    perf::record_display_cache(cached);
+    if cached {
+        return Ok(cache_path.to_string_lossy().to_string());
+    }
 
-        let image = image_decode::open_image(&source).map_err(|error| {
-            format!(
-                "Failed to decode JPEG XL image {}: {}",
-                source.display(),
-                error
+    let image = image_decode::open_image_with_profile(&source)
+        .map(|(image, icc_profile)| {
+            image_decode::convert_to_srgb(
+                image_decode::to_display_8bit(image),
+                icc_profile.as_deref(),
             )
+        })
+        .map_err(|error| {
+            AppError::from(error).context(format!("Failed to decode {}", source.display()))
         })?;
 
-        let mut encoded = Vec::new();
-        image
-            .write_to(
-                &mut std::io::Cursor::new(&mut encoded),
-                image::ImageFormat::Png,
-            )
-            .map_err(|error| {
-                format!(
-                    "Failed to encode display proxy for {}: {}",
-                    source.display(),
-                    error
-                )
-            })?;
-
-        std::fs::write(&cache_path, encoded).map_err(|error| {
-            format!(
-                "Failed to write display proxy {}: {}",
-                cache_path.display(),
-                error
-            )
+    let mut encoded = Vec::new();
+    image
+        .write_to(
+            &mut std::io::Cursor::new(&mut encoded),
+            image::ImageFormat::Png,
+        )
+        .map_err(|error| {
+            AppError::from(error).context(format!(
+                "Failed to encode display proxy for {}",
+                source.display()
+            ))
         })?;
 
-        Ok(cache_path.to_string_lossy().to_string())
-    })
-    .await
-    .map_err(|error| error.to_string())?
+    std::fs::write(&cache_path, encoded).map_err(|error| {
+        AppError::from(error).context(format!(
+            "Failed to write display proxy {}",
+            cache_path.display()
+        ))
+    })?;
+
+    Ok(cache_path.to_string_lossy().to_string())
 }
 
-/// Returns base64-encoded bytes + detected mime for clipboard-safe image loading.
+/// Returns a viewer-displayable path for a source image.
+///
+/// For JPEG XL and OpenEXR files, this generates a cached PNG proxy so the frontend
+/// can render consistently even when platform WebView codec support is unavailable.
 #[tauri::command]
-pub async fn get_image_clipboard_payload(
+pub async fn get_display_image_path(
     filepath: String,
-) -> Result<ClipboardImagePayload, String> {
+    state: tauri::State<'_, AppState>,
+) -> AppResult<String> {
+    refresh_volume_status(&state, false);
+    if state.db.is_offline_path(&filepath) {
+        return Err(AppError::FileMissing(format!(
+            "{} is on a disconnected drive",
+            filepath
+        )));
+    }
+    let cache_dir = state.cache_dir.clone();
+    tauri::async_runtime::spawn_blocking(move || resolve_display_path(&filepath, &cache_dir))
+        .await
+        .map_err(|error| error.to_string())?
+}
+
+/// Returns base64-encoded bytes + detected mime for clipboard-safe image loading.
+#[tauri::command]
+pub async fn get_image_clipboard_payload(filepath: String) -> AppResult<ClipboardImagePayload> {
     tauri::async_runtime::spawn_blocking(move || {
         let path = PathBuf::from(&filepath);
         if !path.exists() || !path.is_file() {
-            return Err(format!("File not found: {}", filepath));
+            return Err(AppError::FileMissing(format!(
+                "File not found: {}",
+                filepath
+            )));
         }
 
-        let bytes = std::fs::read(&path)
-            .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+        let bytes = std::fs::read(&path).map_err(|error| {
+            AppError::from(error).context(format!("Failed to read {}", path.display()))
+        })?;
         if bytes.is_empty() {
-            return Err(format!("File is empty: {}", path.display()));
+            return Err(AppError::InvalidInput(format!(
+                "File is empty: {}",
+                path.display()
+            )));
         }
 
         Ok(ClipboardImagePayload {
@@ -373,7 +532,8 @@ pub async fn get_image_clipboard_payload(
 pub async fn get_thumbnail_path(
     filepath: String,
     state: tauri::State<'_, AppState>,
-) -> Result<String, String> {
+) -> AppResult<String> {
+    let db = state.db.clone();
     let cache_dir = state.cache_dir.clone();
     let thumbnail_index = state.thumbnail_index.clone();
     let failed_thumbnail_sources = state.failed_thumbnail_sources.clone();
@@ -383,6 +543,19 @@ pub async fn get_thumbnail_path(
         .map(|profile| *profile)
         .unwrap_or(StorageProfile::Hdd);
     tauri::async_runtime::spawn_blocking(move || {
+        if db.is_private_path(&filepath) {
+            return Err(AppError::PermissionDenied(
+                "Image is in a locked private folder".to_string(),
+            ));
+        }
+        if db
+            .nsfw_hidden_filepaths(std::slice::from_ref(&filepath))?
+            .contains(&filepath)
+        {
+            return Err(AppError::PermissionDenied(
+                "Image is hidden by the NSFW filter".to_string(),
+            ));
+        }
         let source = Path::new(&filepath);
         if let Err(e) = image_processing::prepare_cache_dir(&cache_dir) {
             log::warn!("Thumbnail cache unavailable for {}: {}", filepath, e);
@@ -394,11 +567,13 @@ pub async fn get_thumbnail_path(
 
         if let Ok(index) = thumbnail_index.read() {
             if index.contains(&primary_key) {
+                perf::record_thumbnail_cache(1, 0);
                 return Ok(primary_key);
             }
         }
 
         if primary_path.exists() {
+            perf::record_thumbnail_cache(1, 0);
             if let Ok(mut index) = thumbnail_index.write() {
                 index.insert(primary_key.clone());
             }
@@ -408,13 +583,21 @@ pub async fn get_thumbnail_path(
             return Ok(primary_key);
         }
 
+        // Not remembered as a failure: the drive may come back.
+        if db.is_offline_path(&filepath) {
+            return Ok(filepath);
+        }
         if let Ok(failed) = failed_thumbnail_sources.read() {
             if failed.contains(&filepath) {
                 return Ok(filepath);
             }
         }
 
-        match image_processing::ensure_thumbnail(source, &cache_dir, storage_profile) {
+        perf::record_thumbnail_cache(0, 1);
+        let (thumbnail, results) =
+            image_processing::ensure_thumbnail(source, &cache_dir, storage_profile);
+        save_thumbnail_results(&db, results);
+        match thumbnail {
             Ok(generated) => {
                 let generated_key = generated.to_string_lossy().to_string();
                 if let Ok(mut index) = thumbnail_index.write() {
@@ -434,8 +617,7 @@ pub async fn get_thumbnail_path(
             }
         }
     })
-    .await
-    .map_err(|e| e.to_string())?
+    .await?
 }
 
 /// Batch-resolves thumbnail paths for multiple images in a single IPC call.
@@ -443,11 +625,12 @@ pub async fn get_thumbnail_path(
 pub async fn get_thumbnail_paths(
     filepaths: Vec<String>,
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<ThumbnailMapping>, String> {
+) -> AppResult<Vec<ThumbnailMapping>> {
     if filepaths.is_empty() {
         return Ok(Vec::new());
     }
 
+    let db = state.db.clone();
     let cache_dir = state.cache_dir.clone();
     let thumbnail_index = state.thumbnail_index.clone();
     let failed_thumbnail_sources = state.failed_thumbnail_sources.clone();
@@ -458,6 +641,13 @@ pub async fn get_thumbnail_paths(
         .unwrap_or(StorageProfile::Hdd);
     tauri::async_runtime::spawn_blocking(move || {
         let started = std::time::Instant::now();
+        // Hidden images get no mapping, so the grid never shows their pixels.
+        let mut hidden = db.nsfw_hidden_filepaths(&filepaths)?;
+        hidden.extend(db.private_filepaths(&filepaths));
+        let filepaths: Vec<String> = filepaths
+            .into_iter()
+            .filter(|filepath| !hidden.contains(filepath))
+            .collect();
         let mut resolved =
             std::collections::HashMap::<String, String>::with_capacity(filepaths.len());
         let mut missing: Vec<String> = Vec::new();
@@ -498,13 +688,28 @@ pub async fn get_thumbnail_paths(
         }
 
         drop(failed_guard);
+        perf::record_thumbnail_cache(
+            (filepaths.len() - missing.len()) as u64,
+            missing.len() as u64,
+        );
+
+        // Sources on an unplugged drive can't be generated now; leave them
+        // out of the failure set so they're retried once it's back.
+        missing.retain(|filepath| {
+            let offline = db.is_offline_path(filepath);
+            if offline {
+                resolved.insert(filepath.clone(), filepath.clone());
+            }
+            !offline
+        });
 
         if !missing.is_empty() {
             // HDD-friendly ordering: keep filesystem-near paths together for fewer seeks.
             missing.sort_unstable();
             missing.dedup();
-            let mappings =
+            let (mappings, results) =
                 image_processing::resolve_thumbnail_paths(&missing, &cache_dir, storage_profile);
+            save_thumbnail_results(&db, results);
             if let Ok(mut index) = thumbnail_index.write() {
                 for (source_path, thumbnail_path) in &mappings {
                     if thumbnail_path != source_path {
@@ -528,6 +733,12 @@ pub async fn get_thumbnail_paths(
                 resolved.insert(filepath, thumbnail_path);
             }
         }
+        let mut focal_points = db
+            .focal_points_for_filepaths(&filepaths)
+            .unwrap_or_else(|error| {
+                log::warn!("Failed to load thumbnail focal points: {}", error);
+                Default::default()
+            });
         let mappings = filepaths
             .into_iter()
             .map(|filepath| ThumbnailMapping {
@@ -535,6 +746,7 @@ pub async fn get_thumbnail_paths(
                     .get(&filepath)
                     .cloned()
                     .unwrap_or_else(|| filepath.clone()),
+                focal_point: focal_points.remove(&filepath),
                 filepath,
             })
             .collect::<Vec<ThumbnailMapping>>();
@@ -557,6 +769,352 @@ pub async fn get_thumbnail_paths(
 
         Ok(mappings)
     })
+    .await?
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThumbnailTiers {
+    pub filepath: String,
+    /// `data:` URI of a 64px JPEG to show blurred while the full thumbnail
+    /// loads; absent when the image has no thumbnail.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tiny: Option<String>,
+    pub thumbnail_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focal_point: Option<FocalPoint>,
+}
+
+/// Like `get_thumbnail_paths`, with an inline 64px placeholder per image
+/// so the grid can blur up without a second round of file reads.
+#[tauri::command]
+pub async fn get_thumbnail_tiers(
+    filepaths: Vec<String>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<ThumbnailTiers>> {
+    let storage_profile = state
+        .storage_profile
+        .read()
+        .map(|profile| *profile)
+        .unwrap_or(StorageProfile::Hdd);
+    let mappings = get_thumbnail_paths(filepaths, state).await?;
+    tauri::async_runtime::spawn_blocking(move || {
+        // Sources without a thumbnail map to themselves; they get no placeholder.
+        let rendered: Vec<PathBuf> = mappings
+            .iter()
+            .filter(|mapping| mapping.thumbnail_path != mapping.filepath)
+            .map(|mapping| PathBuf::from(&mapping.thumbnail_path))
+            .collect();
+        let mut tiny = image_processing::tiny_thumbnails(&rendered, storage_profile).into_iter();
+        Ok(mappings
+            .into_iter()
+            .map(|mapping| {
+                let placeholder = if mapping.thumbnail_path != mapping.filepath {
+                    tiny.next().flatten()
+                } else {
+                    None
+                };
+                ThumbnailTiers {
+                    tiny: placeholder.map(|bytes| {
+                        format!("data:image/jpeg;base64,{}", BASE64_STANDARD.encode(bytes))
+                    }),
+                    filepath: mapping.filepath,
+                    thumbnail_path: mapping.thumbnail_path,
+                    focal_point: mapping.focal_point,
+                }
+            })
+            .collect())
+    })
     .await
-    .map_err(|e| e.to_string())?
+    .map_err(|error| error.to_string())?
+}
+
+/// Reports where the gallery is scrolled to: `cursor` is the `next_cursor`
+/// of the last page on screen. Thumbnails for the next few pages of the
+/// listing it came from render in the background, ahead of scan warmup.
+/// Returns how many sources were queued.
+#[tauri::command]
+pub fn hint_scroll_position(cursor: String, state: tauri::State<'_, AppState>) -> AppResult<usize> {
+    if state.background_paused.load(Ordering::Acquire) {
+        return Ok(0);
+    }
+    let Some(listing) = state
+        .gallery_listing
+        .read()
+        .ok()
+        .and_then(|listing| listing.clone())
+    else {
+        return Ok(0);
+    };
+
+    let mut filepaths = Vec::new();
+    let mut next_cursor = Some(cursor);
+    for _ in 0..crate::scroll_warmup::LOOKAHEAD_PAGES {
+        let Some(cursor) = next_cursor.take() else {
+            break;
+        };
+        let page = listing.page(&state.db, &cursor)?;
+        filepaths.extend(page.items.into_iter().map(|item| item.filepath));
+        next_cursor = page.next_cursor;
+    }
+
+    // Same exclusions as `get_thumbnail_paths`, plus anything already done.
+    let mut skipped = state.db.nsfw_hidden_filepaths(&filepaths)?;
+    skipped.extend(state.db.private_filepaths(&filepaths));
+    if let Ok(failed) = state.failed_thumbnail_sources.read() {
+        skipped.extend(
+            filepaths
+                .iter()
+                .filter(|filepath| failed.contains(*filepath))
+                .cloned(),
+        );
+    }
+    let queued: Vec<PathBuf> = {
+        let index = state.thumbnail_index.read().ok();
+        filepaths
+            .into_iter()
+            .filter(|filepath| !skipped.contains(filepath) && !state.db.is_offline_path(filepath))
+            .map(PathBuf::from)
+            .filter(|source| {
+                let thumb_path =
+                    image_processing::get_thumbnail_cache_path(source, &state.cache_dir);
+                !index
+                    .as_ref()
+                    .is_some_and(|index| index.contains(thumb_path.to_string_lossy().as_ref()))
+            })
+            .collect()
+    };
+    let count = queued.len();
+    state.scroll_warmup.prioritize(queued);
+    if count > 0 {
+        start_scroll_warmup(&state);
+    }
+    Ok(count)
+}
+
+/// Drains the scroll warmup queue on its own thread unless one already is.
+fn start_scroll_warmup(state: &AppState) {
+    let warmup = state.scroll_warmup.clone();
+    if !warmup.try_start_worker() {
+        return;
+    }
+    let db = state.db.clone();
+    let cache_dir = state.cache_dir.clone();
+    let thumbnail_index = state.thumbnail_index.clone();
+    let failed_thumbnail_sources = state.failed_thumbnail_sources.clone();
+    let storage_profile = state
+        .storage_profile
+        .read()
+        .map(|profile| *profile)
+        .unwrap_or(StorageProfile::Hdd);
+    let worker_warmup = warmup.clone();
+    let spawned = std::thread::Builder::new()
+        .name("scroll-warmup".into())
+        .spawn(move || loop {
+            let batch = worker_warmup.take(precache_chunk_size(storage_profile).max(1));
+            if batch.is_empty() {
+                if worker_warmup.finish_if_idle() {
+                    break;
+                }
+                continue;
+            }
+            rerender_thumbnails(
+                &db,
+                &batch,
+                &cache_dir,
+                &thumbnail_index,
+                &failed_thumbnail_sources,
+                storage_profile,
+            );
+        });
+    if let Err(error) = spawned {
+        log::warn!("Failed to start scroll warmup: {}", error);
+        warmup.prioritize(Vec::new());
+        warmup.finish_if_idle();
+    }
+}
+
+/// Renders thumbnails for `paths` now, ignoring earlier failures, and
+/// updates the cache index and failure set. Returns how many rendered.
+fn rerender_thumbnails(
+    db: &crate::database::Database,
+    paths: &[PathBuf],
+    cache_dir: &Path,
+    thumbnail_index: &RwLock<std::collections::HashSet<String>>,
+    failed_thumbnail_sources: &RwLock<std::collections::HashSet<String>>,
+    storage_profile: StorageProfile,
+) -> usize {
+    let generated = image_processing::generate_thumbnails(paths, cache_dir, storage_profile);
+    save_thumbnail_results(db, generated.results);
+    let generated = generated.thumbnails;
+    if let Ok(mut index) = thumbnail_index.write() {
+        for (_, thumb_path) in &generated {
+            index.insert(thumb_path.to_string_lossy().to_string());
+        }
+    }
+    let rendered: std::collections::HashSet<&PathBuf> =
+        generated.iter().map(|(source, _)| source).collect();
+    if let Ok(mut failed) = failed_thumbnail_sources.write() {
+        for path in paths {
+            let key = path.to_string_lossy().to_string();
+            if rendered.contains(path) {
+                failed.remove(&key);
+            } else {
+                failed.insert(key);
+            }
+        }
+    }
+    generated.len()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThumbnailRetryResult {
+    pub retried: usize,
+    pub generated: usize,
+    /// Still failing; their new reasons are in `get_thumbnail_failures`.
+    pub failed: usize,
+}
+
+/// Images whose thumbnail could not be made, latest failure first, with
+/// the reason (missing, empty file, unsupported format, corrupt data, ...).
+#[tauri::command]
+pub async fn get_thumbnail_failures(
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<ThumbnailFailureRecord>> {
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || Ok(db.get_thumbnail_failures(None)?))
+        .await
+        .map_err(|error| error.to_string())?
+}
+
+/// Images that failed to decode so often they are treated as corrupt,
+/// latest failure first. `quarantine_corrupt_images` moves them aside.
+#[tauri::command]
+pub async fn get_corrupt_images(
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<ThumbnailFailureRecord>> {
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || Ok(db.get_corrupt_images()?))
+        .await
+        .map_err(|error| error.to_string())?
+}
+
+/// Tries the failed thumbnails again, all of them or just `ids`, after the
+/// user fixed or replaced the files. Sources that render show thumbnails
+/// again in the grid. Corrupt images are skipped; `regenerate_thumbnails`
+/// still renders them when asked for by id.
+#[tauri::command]
+pub async fn retry_failed_thumbnails(
+    ids: Option<Vec<i64>>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ThumbnailRetryResult> {
+    let db = state.db.clone();
+    let cache_dir = state.cache_dir.clone();
+    let thumbnail_index = state.thumbnail_index.clone();
+    let failed_thumbnail_sources = state.failed_thumbnail_sources.clone();
+    let storage_profile = state
+        .storage_profile
+        .read()
+        .map(|profile| *profile)
+        .unwrap_or(StorageProfile::Hdd);
+    tauri::async_runtime::spawn_blocking(move || {
+        let failures = db.get_thumbnail_failures(ids.as_deref())?;
+        let paths: Vec<PathBuf> = failures
+            .iter()
+            .filter(|failure| !failure.corrupt)
+            .map(|failure| PathBuf::from(&failure.filepath))
+            .collect();
+        let generated = rerender_thumbnails(
+            &db,
+            &paths,
+            &cache_dir,
+            &thumbnail_index,
+            &failed_thumbnail_sources,
+            storage_profile,
+        );
+        log::info!(
+            "Retried {} failed thumbnails: {} generated",
+            paths.len(),
+            generated
+        );
+        Ok(ThumbnailRetryResult {
+            retried: paths.len(),
+            generated,
+            failed: paths.len() - generated,
+        })
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThumbnailRegenerateResult {
+    pub requested: usize,
+    pub regenerated: usize,
+    pub failed: usize,
+}
+
+/// Throws away and re-renders the thumbnails and display proxies of `ids`,
+/// for images edited outside the app, without wiping the whole cache.
+#[tauri::command]
+pub async fn regenerate_thumbnails(
+    ids: Vec<i64>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<ThumbnailRegenerateResult> {
+    if ids.is_empty() {
+        return Err(AppError::InvalidInput(
+            "No images selected to regenerate thumbnails for".to_string(),
+        ));
+    }
+    let db = state.db.clone();
+    let cache_dir = state.cache_dir.clone();
+    let thumbnail_index = state.thumbnail_index.clone();
+    let failed_thumbnail_sources = state.failed_thumbnail_sources.clone();
+    let storage_profile = state
+        .storage_profile
+        .read()
+        .map(|profile| *profile)
+        .unwrap_or(StorageProfile::Hdd);
+    tauri::async_runtime::spawn_blocking(move || {
+        let records = db.get_images_by_ids(&ids)?;
+        let paths: Vec<PathBuf> = records
+            .iter()
+            .map(|record| PathBuf::from(&record.filepath))
+            .collect();
+
+        for path in &paths {
+            let thumb_path = image_processing::get_thumbnail_cache_path(path, &cache_dir);
+            if let Ok(mut index) = thumbnail_index.write() {
+                index.remove(&thumb_path.to_string_lossy().to_string());
+            }
+            if let Err(error) = std::fs::remove_file(&thumb_path) {
+                if error.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!(
+                        "Failed to remove thumbnail {}: {}",
+                        thumb_path.display(),
+                        error
+                    );
+                }
+            }
+            if needs_display_proxy(path) {
+                if let Ok(proxy_path) = display_proxy_path(path, &cache_dir) {
+                    let _ = std::fs::remove_file(proxy_path);
+                }
+            }
+        }
+        let regenerated = rerender_thumbnails(
+            &db,
+            &paths,
+            &cache_dir,
+            &thumbnail_index,
+            &failed_thumbnail_sources,
+            storage_profile,
+        );
+        Ok(ThumbnailRegenerateResult {
+            requested: ids.len(),
+            regenerated,
+            failed: paths.len() - regenerated,
+        })
+    })
+    .await
+    .map_err(|error| error.to_string())?
 }
@@ -0,0 +1,66 @@
+// ────────────────────────── User properties ──────────────────────────
+
+const MAX_PROPERTY_KEY_LEN: usize = 64;
+const MAX_PROPERTY_VALUE_LEN: usize = 2000;
+/// Keys listed when the caller gives no limit.
+const DEFAULT_PROPERTY_KEY_LIMIT: u32 = 100;
+
+/// Sets a custom property such as `client` or `upload status` on the
+/// selected images; an empty `value` removes it. Values are searchable
+/// like prompts. Returns how many images changed.
+#[tauri::command]
+pub fn set_user_property(
+    ids: Vec<i64>,
+    key: String,
+    value: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<usize> {
+    let key = key.trim();
+    if key.is_empty() || key.chars().count() > MAX_PROPERTY_KEY_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Property names must be 1 to {} characters",
+            MAX_PROPERTY_KEY_LEN
+        )));
+    }
+    let value = value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    if value.is_some_and(|value| value.chars().count() > MAX_PROPERTY_VALUE_LEN) {
+        return Err(AppError::InvalidInput(format!(
+            "Property values are limited to {} characters",
+            MAX_PROPERTY_VALUE_LEN
+        )));
+    }
+    let mut ids = ids;
+    ids.sort_unstable();
+    ids.dedup();
+    state
+        .db
+        .set_user_property(&ids, key, value)
+        .map_err(|error| AppError::from(error).context("Failed to save property"))
+}
+
+#[tauri::command]
+pub fn get_user_properties(
+    image_id: i64,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<UserProperty>> {
+    state
+        .db
+        .get_user_properties(image_id)
+        .map_err(AppError::from)
+}
+
+/// Property names in use with their most common values, for autocomplete
+/// and the sidebar.
+#[tauri::command]
+pub fn list_user_property_keys(
+    limit: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<UserPropertyKey>> {
+    state
+        .db
+        .list_user_property_keys(limit.unwrap_or(DEFAULT_PROPERTY_KEY_LIMIT))
+        .map_err(AppError::from)
+}
@@ -0,0 +1,32 @@
+// ────────────────────────── View state ──────────────────────────
+
+/// Last saved gallery filters, sort, cursor and selection. A selected image
+/// that was deleted (or is now hidden) since is dropped.
+#[tauri::command]
+pub fn load_view_state(state: tauri::State<'_, AppState>) -> AppResult<crate::ViewState> {
+    let mut view_state = state
+        .view_state
+        .read()
+        .map_err(|_| AppError::Internal("Failed to read view state".to_string()))?
+        .clone();
+    if let Some(image_id) = view_state.selected_image_id {
+        if state.db.get_image_by_id(image_id)?.is_none() {
+            view_state.selected_image_id = None;
+        }
+    }
+    Ok(view_state)
+}
+
+#[tauri::command]
+pub fn save_view_state(
+    view_state: crate::ViewState,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<()> {
+    crate::persist_view_state(&state.view_state_path, &view_state)?;
+    let mut lock = state
+        .view_state
+        .write()
+        .map_err(|_| AppError::Internal("Failed to update view state".to_string()))?;
+    *lock = view_state;
+    Ok(())
+}
@@ -0,0 +1,47 @@
+// ────────────────────────── Offline volumes ──────────────────────────
+
+/// How long a volume check is reused before roots are stat'ed again.
+const VOLUME_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Re-checks library roots (at most once per interval unless `force`) and
+/// tells the database which ones are offline.
+pub(crate) fn refresh_volume_status(state: &AppState, force: bool) -> Vec<VolumeStatus> {
+    let roots = state
+        .background_settings
+        .read()
+        .map(|settings| settings.scan_roots.clone())
+        .unwrap_or_default();
+    let max_age = if force {
+        std::time::Duration::ZERO
+    } else {
+        VOLUME_CHECK_INTERVAL
+    };
+    let (statuses, rechecked) = state.volumes.check(&roots, max_age);
+    if rechecked {
+        let offline: Vec<String> = statuses
+            .iter()
+            .filter(|status| !status.online)
+            .map(|status| status.root.clone())
+            .collect();
+        state.db.set_offline_roots(&offline);
+    }
+    statuses
+}
+
+/// Flags gallery rows whose drive is unplugged so the grid can show the
+/// cached thumbnail without trying to open the file.
+fn with_offline_flags<E>(state: &AppState, result: Result<CursorPage, E>) -> AppResult<CursorPage>
+where
+    AppError: From<E>,
+{
+    let mut page = result?;
+    refresh_volume_status(state, false);
+    state.db.mark_offline(&mut page.items);
+    Ok(page)
+}
+
+/// Availability of each library root, checked now.
+#[tauri::command]
+pub fn get_volume_status(state: tauri::State<'_, AppState>) -> Vec<VolumeStatus> {
+    refresh_volume_status(&state, true)
+}
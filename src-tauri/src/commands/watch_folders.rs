@@ -0,0 +1,248 @@
+// ────────────────────────── Watched folders ──────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchFolderStatus {
+    pub folder: String,
+    pub running: bool,
+    /// Images indexed since the folder's watcher last started.
+    pub indexed: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct LibraryUpdated {
+    directory: String,
+    count: usize,
+    filepaths: Vec<String>,
+}
+
+/// Files under `root` an earlier scan or watcher run already indexed, so a
+/// polled folder's first walk doesn't treat them as new.
+fn indexed_files_under(state: &AppState, root: &Path) -> Vec<PathBuf> {
+    match state.db.get_all_file_mtimes() {
+        Ok(mtimes) => mtimes
+            .into_keys()
+            .map(PathBuf::from)
+            .filter(|path| path.starts_with(root))
+            .collect(),
+        Err(error) => {
+            log::warn!(
+                "Could not list indexed files under {}: {}",
+                root.display(),
+                error
+            );
+            Vec::new()
+        }
+    }
+}
+
+/// Writes `records` and makes thumbnails for `paths`. Returns false when
+/// the write failed.
+fn index_new_images(state: &AppState, records: &[BulkRecord], paths: &[PathBuf]) -> bool {
+    if let Err(error) = state.db.bulk_upsert_with_tags(records) {
+        log::warn!("Failed to index new images: {}", error);
+        return false;
+    }
+
+    let storage_profile = state
+        .storage_profile
+        .read()
+        .map(|profile| *profile)
+        .unwrap_or(StorageProfile::Hdd);
+    let generated = image_processing::generate_thumbnails(paths, &state.cache_dir, storage_profile);
+    save_thumbnail_results(&state.db, generated.results);
+    if let Ok(mut index) = state.thumbnail_index.write() {
+        for (_, thumb_path) in &generated.thumbnails {
+            index.insert(thumb_path.to_string_lossy().to_string());
+        }
+    }
+    true
+}
+
+fn watch_folder_statuses(state: &AppState) -> AppResult<Vec<WatchFolderStatus>> {
+    let settings = state
+        .watch_folder_settings
+        .read()
+        .map_err(|_| AppError::Internal("Failed to read watched folders".to_string()))?;
+    Ok(settings
+        .folders
+        .iter()
+        .map(|folder| {
+            let (running, indexed) = state.watched_folders.status(folder);
+            WatchFolderStatus {
+                folder: folder.clone(),
+                running,
+                indexed,
+            }
+        })
+        .collect())
+}
+
+/// Saves the folder list, then starts watchers for new folders and stops
+/// the ones no longer listed.
+fn update_watch_folders(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    update: impl FnOnce(&mut crate::watch_folders::WatchFolderSettings) -> AppResult<()>,
+) -> AppResult<Vec<WatchFolderStatus>> {
+    let folders = {
+        let mut settings = state
+            .watch_folder_settings
+            .write()
+            .map_err(|_| AppError::Internal("Failed to update watched folders".to_string()))?;
+        let mut updated = settings.clone();
+        update(&mut updated)?;
+        crate::persist_watch_folder_settings(&state.watch_folder_settings_path, &updated)?;
+        *settings = updated;
+        settings.folders.clone()
+    };
+    state.watched_folders.retain(&folders);
+    for folder in &folders {
+        if !state.watched_folders.status(folder).0 {
+            start_watch_folder(app, state, folder);
+        }
+    }
+    watch_folder_statuses(state)
+}
+
+#[tauri::command]
+pub fn get_watch_folders(state: tauri::State<'_, AppState>) -> AppResult<Vec<WatchFolderStatus>> {
+    watch_folder_statuses(&state)
+}
+
+/// Watches a scanned folder and everything below it, indexing new images
+/// as they appear.
+#[tauri::command]
+pub fn add_watch_folder(
+    directory: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<WatchFolderStatus>> {
+    let directory = directory.trim().to_string();
+    let path = Path::new(&directory);
+    if !path.is_dir() {
+        return Err(AppError::file_missing(path));
+    }
+    let scanned = state
+        .background_settings
+        .read()
+        .map(|settings| {
+            settings
+                .scan_roots
+                .iter()
+                .any(|root| path.starts_with(Path::new(root)))
+        })
+        .unwrap_or(false);
+    if !scanned {
+        return Err(AppError::InvalidInput(format!(
+            "Scan {} before watching it",
+            directory
+        )));
+    }
+    let statuses = update_watch_folders(&app, &state, |settings| {
+        settings.add(&directory);
+        Ok(())
+    })?;
+    log_settings_change(
+        &state.db,
+        "watch_folders",
+        format!("Started watching {}", directory),
+    );
+    Ok(statuses)
+}
+
+#[tauri::command]
+pub fn remove_watch_folder(
+    directory: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<WatchFolderStatus>> {
+    let statuses = update_watch_folders(&app, &state, |settings| {
+        if settings.remove(directory.trim()) {
+            Ok(())
+        } else {
+            Err(AppError::NotFound(format!(
+                "Folder is not watched: {}",
+                directory
+            )))
+        }
+    })?;
+    log_settings_change(
+        &state.db,
+        "watch_folders",
+        format!("Stopped watching {}", directory.trim()),
+    );
+    Ok(statuses)
+}
+
+/// Resumes watching the saved folders at startup.
+pub(crate) fn start_watch_folders_from_settings(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let folders = state
+        .watch_folder_settings
+        .read()
+        .map(|settings| settings.folders.clone())
+        .unwrap_or_default();
+    for folder in &folders {
+        start_watch_folder(app, &state, folder);
+    }
+}
+
+fn start_watch_folder(app: &tauri::AppHandle, state: &AppState, folder: &str) {
+    let known = {
+        let app = app.clone();
+        let root = PathBuf::from(folder);
+        move || indexed_files_under(&app.state::<AppState>(), &root)
+    };
+    let app = app.clone();
+    state.watched_folders.start(
+        folder,
+        known,
+        state.background_paused.clone(),
+        move |root, paths| index_watched_images(&app, root, &paths),
+    );
+}
+
+/// Indexes images new to a watched folder and tells every window to
+/// refresh. Returns how many were indexed.
+fn index_watched_images(app: &tauri::AppHandle, root: &Path, paths: &[PathBuf]) -> usize {
+    let state = app.state::<AppState>();
+    let records: Vec<BulkRecord> = paths
+        .iter()
+        .filter_map(|path| scanner::scan_file(path))
+        .map(|scanned| {
+            build_bulk_record(&scanned.path, scanned.file_mtime, scanned.file_size, root)
+        })
+        .collect();
+    if records.is_empty() || !index_new_images(&state, &records, paths) {
+        return 0;
+    }
+
+    let filepaths: Vec<String> = records
+        .iter()
+        .map(|record| record.filepath.clone())
+        .collect();
+    log::info!(
+        "Indexed {} new image(s) from watched folder {}",
+        filepaths.len(),
+        root.display()
+    );
+    let _ = EventSink::broadcast(app).emit(
+        "library-updated",
+        LibraryUpdated {
+            directory: root.to_string_lossy().to_string(),
+            count: filepaths.len(),
+            filepaths: filepaths.clone(),
+        },
+    );
+    fire_hooks(
+        &state.hooks,
+        HookEvent::ImagesIndexed,
+        serde_json::json!({
+            "source": "watch_folder",
+            "directory": root.to_string_lossy(),
+            "count": filepaths.len(),
+            "filepaths": filepaths,
+        }),
+    );
+    records.len()
+}
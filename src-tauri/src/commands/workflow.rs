@@ -0,0 +1,43 @@
+// ────────────────────────── Workflow status ──────────────────────────
+
+/// Moves the selected images to a triage stage (inbox, picked, edited,
+/// published, rejected). Returns how many changed.
+#[tauri::command]
+pub fn set_workflow_status(
+    ids: Vec<i64>,
+    status: WorkflowStatus,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<usize> {
+    let mut ids = ids;
+    ids.sort_unstable();
+    ids.dedup();
+    state
+        .db
+        .set_workflow_status(&ids, status)
+        .map_err(|error| AppError::from(error).context("Failed to update workflow status"))
+}
+
+/// Moves every image the filter matches to `status` in one statement, e.g.
+/// to reject the rest of the inbox after picking.
+#[tauri::command]
+pub fn set_workflow_status_by_filter(
+    filter: ImageFilterRequest,
+    status: WorkflowStatus,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<usize> {
+    state
+        .db
+        .set_workflow_status_by_filter(filter.params(), status)
+        .map_err(|error| AppError::from(error).context("Failed to update filtered workflow status"))
+}
+
+/// Image counts per workflow status, for the board's column headers.
+#[tauri::command]
+pub fn get_workflow_status_counts(
+    state: tauri::State<'_, AppState>,
+) -> AppResult<Vec<WorkflowStatusCount>> {
+    state
+        .db
+        .get_workflow_status_counts()
+        .map_err(AppError::from)
+}
@@ -0,0 +1,101 @@
+//! Thread-count overrides and resizable Rayon pools.
+//!
+//! The scan and thumbnail pools default to a size derived from the storage
+//! profile (or the `FORGE_SCAN_THREADS` / `FORGE_IO_THREADS` env vars). Saved
+//! performance settings override both; changing them rebuilds the pool on its
+//! next use, while work already running finishes on the old pool.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+pub const MAX_THREADS: usize = 32;
+
+/// 0 means "no override".
+static SCAN_THREADS_OVERRIDE: AtomicUsize = AtomicUsize::new(0);
+static THUMBNAIL_THREADS_OVERRIDE: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_thread_overrides(scan_threads: Option<usize>, thumbnail_threads: Option<usize>) {
+    SCAN_THREADS_OVERRIDE.store(scan_threads.unwrap_or(0), Ordering::Release);
+    THUMBNAIL_THREADS_OVERRIDE.store(thumbnail_threads.unwrap_or(0), Ordering::Release);
+}
+
+pub fn scan_threads_override() -> Option<usize> {
+    read_override(&SCAN_THREADS_OVERRIDE)
+}
+
+pub fn thumbnail_threads_override() -> Option<usize> {
+    read_override(&THUMBNAIL_THREADS_OVERRIDE)
+}
+
+fn read_override(slot: &AtomicUsize) -> Option<usize> {
+    match slot.load(Ordering::Acquire) {
+        0 => None,
+        threads => Some(threads.min(MAX_THREADS)),
+    }
+}
+
+/// A named Rayon pool that is rebuilt when the requested size changes.
+pub struct ResizablePool {
+    name: &'static str,
+    pool: RwLock<Option<Arc<rayon::ThreadPool>>>,
+}
+
+impl ResizablePool {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            pool: RwLock::new(None),
+        }
+    }
+
+    pub fn get(&self, threads: usize) -> Arc<rayon::ThreadPool> {
+        let threads = threads.clamp(1, MAX_THREADS);
+        if let Ok(guard) = self.pool.read() {
+            if let Some(pool) = guard.as_ref() {
+                if pool.current_num_threads() == threads {
+                    return pool.clone();
+                }
+            }
+        }
+
+        let mut guard = self
+            .pool
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(pool) = guard.as_ref() {
+            if pool.current_num_threads() == threads {
+                return pool.clone();
+            }
+        }
+        let name = self.name;
+        let pool = Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(move |idx| format!("{}-{}", name, idx))
+                .build()
+                .unwrap_or_else(|error| panic!("failed to create {} threadpool: {}", name, error)),
+        );
+        if guard.is_some() {
+            log::info!("Resized {} pool to {} threads", name, threads);
+        }
+        *guard = Some(pool.clone());
+        pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resizable_pool_rebuilds_on_size_change() {
+        static POOL: ResizablePool = ResizablePool::new("test-pool");
+        let first = POOL.get(2);
+        assert_eq!(first.current_num_threads(), 2);
+        assert!(Arc::ptr_eq(&first, &POOL.get(2)));
+
+        let resized = POOL.get(3);
+        assert_eq!(resized.current_num_threads(), 3);
+        assert!(!Arc::ptr_eq(&first, &resized));
+    }
+}
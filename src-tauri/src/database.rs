@@ -1,5 +1,12 @@
 use crate::{
-    parser::{infer_generation_type, GenerationParams},
+    collection_rules::CollectionRule,
+    image_quality, nsfw,
+    parser::{
+        extract_embeddings, extract_loras, extract_tags, infer_generation_type, infer_source_tool,
+        workflow_search_text, GenerationParams,
+    },
+    prompt_styles::PromptStyle,
+    tag_dictionary::{self, DictionaryTag},
     StorageProfile,
 };
 use r2d2::Pool;
@@ -8,11 +15,24 @@ use rusqlite::{params, params_from_iter, types::Value, Connection, Result as Sql
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
 
 /// Thread-safe database wrapper backed by an r2d2 connection pool.
 #[derive(Clone)]
 pub struct Database {
     pool: Pool<SqliteConnectionManager>,
+    record_cache: Arc<Mutex<RecordCache>>,
+    /// When set, cursor queries skip images scoring at or above this NSFW score.
+    nsfw_filter: Arc<RwLock<Option<f32>>>,
+    /// `/`-terminated prefixes of private directories while they are locked.
+    private_directories: Arc<RwLock<Vec<String>>>,
+    /// `/`-terminated prefixes of the active project's folders.
+    project_directories: Arc<RwLock<Vec<String>>>,
+    /// `/`-terminated prefixes of library roots on disconnected drives.
+    offline_roots: Arc<RwLock<Vec<String>>>,
+    /// Journaled operations this process is still running, which aren't
+    /// pending resumption.
+    running_operations: Arc<Mutex<HashSet<i64>>>,
 }
 
 fn pool_error<E>(err: E) -> rusqlite::Error
@@ -25,7 +45,12 @@ where
 const HDD_FRIENDLY_DB_POOL_SIZE: u32 = 4;
 const SSD_FRIENDLY_DB_POOL_SIZE: u32 = 12;
 
-fn db_pool_size(profile: StorageProfile) -> u32 {
+/// Pool size for `profile`: an explicit setting wins over `FORGE_DB_POOL_SIZE`,
+/// which wins over the profile default.
+pub fn db_pool_size(profile: StorageProfile, configured: Option<u32>) -> u32 {
+    if let Some(size) = configured {
+        return size.clamp(1, 32);
+    }
     if let Ok(raw) = std::env::var("FORGE_DB_POOL_SIZE") {
         if let Ok(parsed) = raw.parse::<u32>() {
             return parsed.clamp(1, 32);
@@ -69,6 +94,60 @@ pub struct GalleryImageRecord {
     pub model_name: Option<String>,
     pub is_favorite: bool,
     pub is_locked: bool,
+    /// The file's drive is unplugged; only the cached thumbnail is available.
+    #[serde(default)]
+    pub offline: bool,
+    /// Group size when the query collapsed duplicates into this image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_count: Option<u32>,
+}
+
+/// A theme found by `cluster_prompts`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptCluster {
+    pub id: i64,
+    pub label: String,
+    /// Heaviest tags, label tags first.
+    pub terms: Vec<String>,
+    pub count: u32,
+}
+
+/// A rule-based collection as listed by `get_collections`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub rule: CollectionRule,
+    /// Visible members.
+    pub count: u32,
+}
+
+/// Collection holding some of the images a deletion would remove.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionMembership {
+    pub id: i64,
+    pub name: String,
+    /// How many of those images it holds.
+    pub count: u32,
+}
+
+/// Gallery row ranked by `search_by_color`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorMatch {
+    #[serde(flatten)]
+    pub record: GalleryImageRecord,
+    /// Distance from the searched colour, 0-1.
+    pub distance: f32,
+}
+
+/// Gallery row returned by `find_similar_images`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarImage {
+    #[serde(flatten)]
+    pub record: GalleryImageRecord,
+    /// Bits in which its perceptual hash differs from the searched image's,
+    /// 0-64.
+    pub distance: u32,
 }
 
 /// Full row used by detail/export workflows.
@@ -93,20 +172,339 @@ pub struct ImageRecord {
     pub is_locked: bool,
 }
 
+/// Full row plus its tags, used by the selection inspector.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageDetail {
+    #[serde(flatten)]
+    pub record: ImageRecord,
+    pub tags: Vec<String>,
+    pub workflow_status: WorkflowStatus,
+    /// Text read from the image by `ocr_images`; `None` until it has run.
+    pub ocr_text: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TagCount {
     pub tag: String,
     pub count: u32,
 }
 
+/// New prompt text for one image, as written by a bulk prompt edit.
+#[derive(Debug, Clone)]
+pub struct PromptRewrite {
+    pub id: i64,
+    /// The prompt before the edit, so tags derived from it can be dropped.
+    pub old_prompt: String,
+    pub prompt: String,
+    pub negative_prompt: String,
+    pub raw_metadata: String,
+}
+
+/// Typed fields a bulk metadata edit overwrites. `None` leaves a field
+/// alone and an empty string clears it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FieldUpdate {
+    pub model_name: Option<String>,
+    pub model_hash: Option<String>,
+    pub seed: Option<String>,
+    pub steps: Option<String>,
+    pub sampler: Option<String>,
+    pub schedule_type: Option<String>,
+    pub cfg_scale: Option<String>,
+    /// Kept in the image's sidecar rather than the database; the journal
+    /// still records the old value so an undo can put it back.
+    pub notes: Option<String>,
+}
+
+impl FieldUpdate {
+    /// The `images` columns to set, `None` meaning NULL.
+    pub fn columns(&self) -> Vec<(&'static str, Option<&str>)> {
+        [
+            ("model_name", &self.model_name),
+            ("model_hash", &self.model_hash),
+            ("seed", &self.seed),
+            ("steps", &self.steps),
+            ("sampler", &self.sampler),
+            ("schedule_type", &self.schedule_type),
+            ("cfg_scale", &self.cfg_scale),
+        ]
+        .into_iter()
+        .filter_map(|(column, value)| {
+            let value = value.as_deref()?.trim();
+            Some((column, Some(value).filter(|value| !value.is_empty())))
+        })
+        .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns().is_empty() && self.notes.is_none()
+    }
+
+    /// The notes to write, `None` meaning remove them. Only meaningful when
+    /// `notes` is set.
+    pub fn notes_value(&self) -> Option<String> {
+        Some(self.notes.as_deref()?.trim().to_string()).filter(|notes| !notes.is_empty())
+    }
+}
+
+/// A user-defined property on an image, e.g. `client = Acme`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserProperty {
+    pub key: String,
+    pub value: String,
+}
+
+/// A property key in use, with how many images carry it and its most
+/// common values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserPropertyKey {
+    pub key: String,
+    pub count: u32,
+    pub values: Vec<ExtraParamValue>,
+}
+
+/// A bulk field edit as recorded in the undo journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldEdit {
+    pub id: i64,
+    /// Unix seconds.
+    pub edited_at: i64,
+    /// Fields the edit set, `notes` included.
+    pub fields: Vec<String>,
+    pub image_count: u32,
+    pub undone: bool,
+}
+
+/// One image's values from before a field edit, keyed by field.
+#[derive(Debug, Clone)]
+pub struct FieldEditRow {
+    pub image_id: i64,
+    pub previous: HashMap<String, Option<String>>,
+}
+
+/// Outcome of `Database::undo_field_edit`.
+#[derive(Debug, Clone)]
+pub enum FieldEditUndo {
+    /// The values were put back; the rows say what they were.
+    Restored(Vec<FieldEditRow>),
+    /// These images were changed again after the edit, so nothing was
+    /// restored.
+    Conflict(Vec<i64>),
+    /// The edit does not exist or was already undone.
+    Missing,
+}
+
+/// What kind of event an activity log entry records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Scan,
+    Import,
+    Delete,
+    Move,
+    Quarantine,
+    Archive,
+    Unarchive,
+    Export,
+    ForgeBatch,
+    Settings,
+}
+
+impl ActivityKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Scan => "scan",
+            Self::Import => "import",
+            Self::Delete => "delete",
+            Self::Move => "move",
+            Self::Quarantine => "quarantine",
+            Self::Archive => "archive",
+            Self::Unarchive => "unarchive",
+            Self::Export => "export",
+            Self::ForgeBatch => "forge_batch",
+            Self::Settings => "settings",
+        }
+    }
+
+    /// Reads back `as_str`; `None` for kinds a newer version wrote.
+    pub fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "scan" => Self::Scan,
+            "import" => Self::Import,
+            "delete" => Self::Delete,
+            "move" => Self::Move,
+            "quarantine" => Self::Quarantine,
+            "archive" => Self::Archive,
+            "unarchive" => Self::Unarchive,
+            "export" => Self::Export,
+            "forge_batch" => Self::ForgeBatch,
+            "settings" => Self::Settings,
+            _ => return None,
+        })
+    }
+}
+
+/// A notable event, such as a scan, a delete or a settings change.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityEntry {
+    pub id: i64,
+    /// Unix seconds.
+    pub occurred_at: i64,
+    pub kind: ActivityKind,
+    pub summary: String,
+    /// Counts, modes and targets particular to the kind.
+    pub detail: serde_json::Value,
+    /// Files the event touched, listed by `get_activity_files`.
+    pub file_count: u32,
+}
+
+/// Narrows the activity log; empty fields don't filter.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ActivityFilter {
+    pub kinds: Vec<ActivityKind>,
+    /// Unix seconds, inclusive.
+    pub since: Option<i64>,
+    /// Unix seconds, inclusive.
+    pub until: Option<i64>,
+    /// Only events that touched one of these files.
+    pub filepaths: Vec<String>,
+    /// Only events that touched a file in this folder or below it.
+    pub directory: Option<String>,
+    /// Case-insensitive text in the summary.
+    pub search: Option<String>,
+    pub limit: Option<u32>,
+}
+
+/// A bulk file operation whose steps are journaled so it can be finished
+/// or rolled back after a crash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    Move,
+    Quarantine,
+    Delete,
+    /// A converting file export. Its ZIP can't be continued, so resuming
+    /// writes it again.
+    Convert,
+}
+
+impl OperationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Move => "move",
+            Self::Quarantine => "quarantine",
+            Self::Delete => "delete",
+            Self::Convert => "convert",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "move" => Some(Self::Move),
+            "quarantine" => Some(Self::Quarantine),
+            "delete" => Some(Self::Delete),
+            "convert" => Some(Self::Convert),
+            _ => None,
+        }
+    }
+}
+
+/// A journaled operation that never finished, usually because the app
+/// crashed or was closed midway.
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingOperation {
+    pub id: i64,
+    pub kind: OperationKind,
+    /// Unix seconds.
+    pub started_at: i64,
+    /// What the operation ran with, such as the delete mode.
+    pub detail: serde_json::Value,
+    pub total_steps: u32,
+    pub done_steps: u32,
+}
+
+/// One file of a journaled operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationStep {
+    pub image_id: i64,
+    pub source: String,
+    /// Where the file is going; `None` when it is being deleted.
+    pub destination: Option<String>,
+    pub done: bool,
+}
+
+/// One image's prompts, as streamed to a whole-library prompt edit preview.
+#[derive(Debug, Clone)]
+pub struct StoredPrompt {
+    pub id: i64,
+    pub filename: String,
+    pub prompt: String,
+    pub negative_prompt: String,
+    pub is_locked: bool,
+}
+
+/// Generation defaults for one checkpoint or a whole model family, used by
+/// Forge sends when the source image lacks a setting or the model changes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelDefaults {
+    /// A checkpoint name, or a family such as `sdxl`, `pony` or `flux`.
+    pub model: String,
+    pub sampler: Option<String>,
+    pub scheduler: Option<String>,
+    pub steps: Option<u32>,
+    pub cfg_scale: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// Matches images whose unmapped A1111 parameter `key` (e.g. `VAE` or
+/// `ADetailer model`) is exactly `value`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtraParamFilter {
+    pub key: String,
+    pub value: String,
+}
+
+/// An unmapped A1111 parameter key with its most common values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtraParamKey {
+    pub key: String,
+    pub count: u32,
+    pub values: Vec<ExtraParamValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtraParamValue {
+    pub value: String,
+    pub count: u32,
+}
+
+/// Autocomplete entry merging library tags with the imported tag dictionary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSuggestion {
+    pub tag: String,
+    /// Dictionary category name ("general", "artist", ...), if known.
+    pub category: Option<String>,
+    /// Dictionary popularity (booru post count), if known.
+    pub post_count: Option<i64>,
+    /// Images in this library using the tag.
+    pub library_count: u32,
+}
+
 /// A page of results with an opaque cursor for keyset pagination.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CursorPage {
     pub items: Vec<GalleryImageRecord>,
     pub next_cursor: Option<String>,
+    /// "Did you mean" corrections, only set when a search found nothing.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct CursorQueryOptions<'a> {
     pub cursor: Option<&'a str>,
     pub limit: u32,
@@ -114,6 +512,31 @@ pub struct CursorQueryOptions<'a> {
     pub generation_types: Option<&'a [String]>,
     pub model_filter: Option<&'a str>,
     pub model_family_filters: Option<&'a [String]>,
+    /// Scheduler names as listed by `get_unique_schedulers`, any of which
+    /// matches.
+    pub scheduler_filters: Option<&'a [String]>,
+    /// Generator tools (`comfyui`, `forge`, ...) as listed by
+    /// `get_source_tools`, any of which matches.
+    pub source_tools: Option<&'a [String]>,
+    /// Resolution buckets (`WIDTHxHEIGHT`) as listed by
+    /// `get_resolution_buckets`, any of which matches.
+    pub resolution_buckets: Option<&'a [String]>,
+    /// Workflow statuses (`inbox`, `picked`, ...), any of which matches.
+    pub workflow_statuses: Option<&'a [String]>,
+    /// Prompt clusters as listed by `get_prompt_clusters`, any of which
+    /// matches.
+    pub prompt_clusters: Option<&'a [i64]>,
+    /// Collections as listed by `get_collections`, any of which matches.
+    pub collections: Option<&'a [i64]>,
+    /// Only images measured at or below this sharpness, for culling blurry
+    /// renders.
+    pub max_sharpness: Option<f32>,
+    /// Only images that are almost entirely black or blown out.
+    pub failed_renders_only: bool,
+    /// Also return images from directories marked hidden.
+    pub include_hidden: bool,
+    /// Show one image per prompt+model+size group instead of every member.
+    pub collapse_duplicates: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -122,11 +545,26 @@ pub struct SearchCursorParams<'a> {
     pub options: CursorQueryOptions<'a>,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct LoraCursorParams<'a> {
+    pub lora: &'a str,
+    /// Inclusive weight bounds; either may be left open.
+    pub min_weight: Option<f64>,
+    pub max_weight: Option<f64>,
+    pub options: CursorQueryOptions<'a>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct FilterCursorParams<'a> {
     pub query: Option<&'a str>,
     pub include_tags: &'a [String],
     pub exclude_tags: &'a [String],
+    /// Secondary search applied on top of `query` ("search within results").
+    pub refine: Option<&'a str>,
+    /// Every pair must match the image's unmapped A1111 parameters.
+    pub extra_params: &'a [ExtraParamFilter],
+    /// Embeddings the image must use, in either prompt.
+    pub embeddings: &'a [String],
     pub options: CursorQueryOptions<'a>,
 }
 
@@ -135,6 +573,12 @@ pub struct FilterCursorParams<'a> {
 pub struct DirectoryEntry {
     pub directory: String,
     pub count: u32,
+    /// Pinned directories are listed first.
+    pub pinned: bool,
+    /// Display name shown instead of the path, e.g. "Forge txt2img".
+    pub alias: Option<String>,
+    /// Left out of cursor queries unless they ask for hidden directories.
+    pub hidden: bool,
 }
 
 /// Model entry with image count for grouping.
@@ -144,6 +588,272 @@ pub struct ModelEntry {
     pub count: u32,
 }
 
+/// A LoRA with how many images use it and at what weights, for the
+/// browse-by-LoRA view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoraUsage {
+    pub name: String,
+    pub count: u32,
+    pub avg_weight: f64,
+    pub min_weight: f64,
+    pub max_weight: f64,
+    /// Date (`YYYY-MM-DD`) of the newest image using it.
+    pub last_used: Option<String>,
+}
+
+/// A textual-inversion embedding and how many images use it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingUsage {
+    pub name: String,
+    /// Images using it in either prompt.
+    pub count: u32,
+    pub positive_count: u32,
+    pub negative_count: u32,
+    /// Date (`YYYY-MM-DD`) of the newest image using it.
+    pub last_used: Option<String>,
+}
+
+/// Result counts for the active filter, for the sidebar badges.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterCounts {
+    /// Images matching the filter as a whole.
+    pub total: u32,
+    /// Per generation type, keeping the model family selection.
+    pub generation_types: Vec<FilterCount>,
+    /// Per model family, keeping the generation type selection.
+    pub model_families: Vec<FilterCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterCount {
+    pub value: String,
+    pub count: u32,
+}
+
+/// Triage stage of an image. New images start in the inbox.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkflowStatus {
+    #[default]
+    Inbox,
+    Picked,
+    Edited,
+    Published,
+    Rejected,
+}
+
+impl WorkflowStatus {
+    pub const ALL: [WorkflowStatus; 5] = [
+        WorkflowStatus::Inbox,
+        WorkflowStatus::Picked,
+        WorkflowStatus::Edited,
+        WorkflowStatus::Published,
+        WorkflowStatus::Rejected,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WorkflowStatus::Inbox => "inbox",
+            WorkflowStatus::Picked => "picked",
+            WorkflowStatus::Edited => "edited",
+            WorkflowStatus::Published => "published",
+            WorkflowStatus::Rejected => "rejected",
+        }
+    }
+
+    /// Reads a stored status; anything unrecognised counts as the inbox.
+    pub fn from_stored(value: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str().eq_ignore_ascii_case(value.trim()))
+            .unwrap_or_default()
+    }
+}
+
+/// Images per workflow status, for the board's column headers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowStatusCount {
+    pub status: WorkflowStatus,
+    pub count: u32,
+}
+
+/// Tool that wrote an image's metadata, with image count.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceToolEntry {
+    pub source_tool: String,
+    pub count: u32,
+}
+
+/// Sampler with image count for grouping.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplerEntry {
+    pub sampler: String,
+    pub count: u32,
+}
+
+/// Scheduler (`Schedule type`) with image count for grouping.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerEntry {
+    pub scheduler: String,
+    pub count: u32,
+}
+
+/// Images whose size snaps to the same 64px grid cell, e.g. `1216x832`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolutionBucket {
+    /// `WIDTHxHEIGHT`, what `resolution_buckets` accepts.
+    pub bucket: String,
+    pub width: u32,
+    pub height: u32,
+    pub count: u32,
+    /// Native size class by pixel count: `sd15` (up to 768x768), `sdxl`
+    /// (around one megapixel) or `large` (upscaled or hires-fixed).
+    pub tier: String,
+}
+
+/// Images sharing the same sampled content fingerprint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub quick_hash: String,
+    /// Full-file SHA-256 when the group was verified byte for byte.
+    pub sha256: Option<String>,
+    pub ids: Vec<i64>,
+    pub filepaths: Vec<String>,
+    /// Copy to keep when resolving the group: a locked or favourite one if
+    /// any, else the oldest.
+    pub keep_id: i64,
+}
+
+/// Period length for time-series statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendBucket {
+    Week,
+    Month,
+}
+
+/// How often each term appears per period. `periods`, `totals` and every
+/// series' `counts` are index-aligned; periods with no images are omitted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordTrends {
+    pub bucket: TrendBucket,
+    /// Bucket start dates (`YYYY-MM-DD`), oldest first.
+    pub periods: Vec<String>,
+    /// All images in each period, for normalizing the series.
+    pub totals: Vec<u32>,
+    pub series: Vec<KeywordSeries>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordSeries {
+    pub term: String,
+    pub counts: Vec<u32>,
+}
+
+/// Per-model image counts over time plus average settings. `counts` is
+/// index-aligned with `periods`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub bucket: TrendBucket,
+    pub periods: Vec<String>,
+    /// Most-used model first.
+    pub models: Vec<ModelUsageSeries>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelUsageSeries {
+    pub model_name: String,
+    pub total: u32,
+    pub counts: Vec<u32>,
+    /// Dates (`YYYY-MM-DD`) of the oldest and newest image.
+    pub first_used: Option<String>,
+    pub last_used: Option<String>,
+    pub avg_steps: Option<f64>,
+    pub avg_cfg_scale: Option<f64>,
+    pub avg_width: Option<f64>,
+    pub avg_height: Option<f64>,
+}
+
+/// Bytes used by one directory, model or generation type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskUsageEntry {
+    pub key: String,
+    pub bytes: i64,
+    pub count: u32,
+}
+
+/// A single large file worth reviewing, e.g. an X/Y/Z grid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskUsageFile {
+    pub id: i64,
+    pub filepath: String,
+    pub bytes: i64,
+}
+
+/// Where library disk space goes. Groups are sorted largest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskUsageReport {
+    pub total_bytes: i64,
+    pub total_images: u32,
+    /// Images indexed without a recorded size (counted as 0 bytes).
+    pub unsized_images: u32,
+    pub by_directory: Vec<DiskUsageEntry>,
+    pub by_model: Vec<DiskUsageEntry>,
+    pub by_generation_type: Vec<DiskUsageEntry>,
+    pub largest_grids: Vec<DiskUsageFile>,
+}
+
+/// How much of the library carries usable generation metadata. Archived
+/// images are left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataCoverage {
+    pub total_images: u32,
+    pub with_prompt: u32,
+    pub with_model: u32,
+    pub with_seed: u32,
+    /// Images with a prompt, a model and a seed.
+    pub complete: u32,
+    /// Images indexed without any metadata text at all.
+    pub without_metadata: u32,
+    /// Directories with the most incomplete images, worst first.
+    pub incomplete_by_directory: Vec<MetadataGap>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataGap {
+    pub directory: String,
+    pub incomplete: u32,
+    pub total: u32,
+}
+
+/// Where an archived image's file now lives.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedImage {
+    pub id: i64,
+    /// Original location, restored on unarchive.
+    pub filepath: String,
+    /// Moved file, or the zip holding it.
+    pub archive_path: String,
+    /// Entry name inside `archive_path` when it is a zip.
+    pub archive_entry: Option<String>,
+}
+
+/// A library image whose thumbnail could not be made, and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThumbnailFailureRecord {
+    pub image_id: i64,
+    pub filepath: String,
+    pub kind: crate::image_processing::ThumbnailFailureKind,
+    pub message: String,
+    /// Failed attempts since the thumbnail last rendered.
+    pub attempts: i64,
+    /// Unix seconds of the latest failure.
+    pub failed_at: i64,
+    /// Failed to decode often enough to count as corrupt; left out of
+    /// retries until the file changes or is regenerated by hand.
+    pub corrupt: bool,
+}
+
 /// Record for bulk insert operations.
 pub struct BulkRecord {
     pub filepath: String,
@@ -154,23 +864,50 @@ pub struct BulkRecord {
     pub file_size: Option<i64>,
     pub quick_hash: Option<String>,
     pub tags: Vec<String>,
+    /// ComfyUI editor workflow JSON, when the file carries one besides
+    /// its metadata; only indexed for search.
+    pub workflow: Option<String>,
+    /// Star rating from the image's sidecar.
+    pub rating: Option<u8>,
 }
 
 impl Database {
     /// Opens or creates the SQLite database at the given path using a connection pool.
     pub fn new(db_path: &Path, storage_profile: StorageProfile) -> SqlResult<Self> {
+        Self::with_pool_size(db_path, storage_profile, None)
+    }
+
+    /// Like [`Database::new`], with an explicit connection pool size.
+    pub fn with_pool_size(
+        db_path: &Path,
+        storage_profile: StorageProfile,
+        pool_size: Option<u32>,
+    ) -> SqlResult<Self> {
         let manager =
             SqliteConnectionManager::file(db_path).with_init(|conn| apply_connection_pragmas(conn));
         let pool = Pool::builder()
-            .max_size(db_pool_size(storage_profile))
+            .max_size(db_pool_size(storage_profile, pool_size))
             .build(manager)
             .map_err(pool_error)?;
 
-        let db = Database { pool };
+        let db = Database {
+            pool,
+            record_cache: Arc::new(Mutex::new(RecordCache::new(RECORD_CACHE_CAPACITY))),
+            nsfw_filter: Arc::new(RwLock::new(None)),
+            private_directories: Arc::new(RwLock::new(Vec::new())),
+            project_directories: Arc::new(RwLock::new(Vec::new())),
+            offline_roots: Arc::new(RwLock::new(Vec::new())),
+            running_operations: Arc::new(Mutex::new(HashSet::new())),
+        };
         db.init_schema()?;
         Ok(db)
     }
 
+    /// Maximum number of pooled SQLite connections.
+    pub fn pool_size(&self) -> u32 {
+        self.pool.max_size()
+    }
+
     /// Initializes schema, indexes, and compatibility migrations.
     fn init_schema(&self) -> SqlResult<()> {
         let conn = self.pool.get().map_err(pool_error)?;
@@ -204,6 +941,12 @@ impl Database {
         )?;
         Self::ensure_optional_columns(&conn)?;
         Self::backfill_generation_types(&conn)?;
+        Self::backfill_nsfw_scores(&conn)?;
+        Self::backfill_model_families(&conn)?;
+        Self::backfill_source_tools(&conn)?;
+        // Before the backfill, so it doesn't feed the old indexes.
+        let rebuild_fts = Self::drop_outdated_fts(&conn)?;
+        Self::backfill_workflow_text(&conn)?;
 
         // ── Porter FTS (ranked word-boundary search) ──
         conn.execute_batch(
@@ -212,6 +955,9 @@ impl Database {
                 negative_prompt,
                 raw_metadata,
                 model_name,
+                workflow_text,
+                property_text,
+                ocr_text,
                 content='images',
                 content_rowid='id',
                 tokenize='porter unicode61'
@@ -220,22 +966,22 @@ impl Database {
 
         conn.execute_batch(
             "CREATE TRIGGER IF NOT EXISTS images_ai AFTER INSERT ON images BEGIN
-                INSERT INTO images_fts(rowid, prompt, negative_prompt, raw_metadata, model_name)
-                VALUES (new.id, new.prompt, new.negative_prompt, new.raw_metadata, new.model_name);
+                INSERT INTO images_fts(rowid, prompt, negative_prompt, raw_metadata, model_name, workflow_text, property_text, ocr_text)
+                VALUES (new.id, new.prompt, new.negative_prompt, new.raw_metadata, new.model_name, new.workflow_text, new.property_text, new.ocr_text);
             END;",
         )?;
         conn.execute_batch(
             "CREATE TRIGGER IF NOT EXISTS images_ad AFTER DELETE ON images BEGIN
-                INSERT INTO images_fts(images_fts, rowid, prompt, negative_prompt, raw_metadata, model_name)
-                VALUES ('delete', old.id, old.prompt, old.negative_prompt, old.raw_metadata, old.model_name);
+                INSERT INTO images_fts(images_fts, rowid, prompt, negative_prompt, raw_metadata, model_name, workflow_text, property_text, ocr_text)
+                VALUES ('delete', old.id, old.prompt, old.negative_prompt, old.raw_metadata, old.model_name, old.workflow_text, old.property_text, old.ocr_text);
             END;",
         )?;
         conn.execute_batch(
             "CREATE TRIGGER IF NOT EXISTS images_au AFTER UPDATE ON images BEGIN
-                INSERT INTO images_fts(images_fts, rowid, prompt, negative_prompt, raw_metadata, model_name)
-                VALUES ('delete', old.id, old.prompt, old.negative_prompt, old.raw_metadata, old.model_name);
-                INSERT INTO images_fts(rowid, prompt, negative_prompt, raw_metadata, model_name)
-                VALUES (new.id, new.prompt, new.negative_prompt, new.raw_metadata, new.model_name);
+                INSERT INTO images_fts(images_fts, rowid, prompt, negative_prompt, raw_metadata, model_name, workflow_text, property_text, ocr_text)
+                VALUES ('delete', old.id, old.prompt, old.negative_prompt, old.raw_metadata, old.model_name, old.workflow_text, old.property_text, old.ocr_text);
+                INSERT INTO images_fts(rowid, prompt, negative_prompt, raw_metadata, model_name, workflow_text, property_text, ocr_text)
+                VALUES (new.id, new.prompt, new.negative_prompt, new.raw_metadata, new.model_name, new.workflow_text, new.property_text, new.ocr_text);
             END;",
         )?;
 
@@ -246,6 +992,9 @@ impl Database {
                 negative_prompt,
                 raw_metadata,
                 model_name,
+                workflow_text,
+                property_text,
+                ocr_text,
                 content='images',
                 content_rowid='id',
                 tokenize='trigram'
@@ -254,29 +1003,36 @@ impl Database {
 
         conn.execute_batch(
             "CREATE TRIGGER IF NOT EXISTS images_ai_tri AFTER INSERT ON images BEGIN
-                INSERT INTO images_fts_tri(rowid, prompt, negative_prompt, raw_metadata, model_name)
-                VALUES (new.id, new.prompt, new.negative_prompt, new.raw_metadata, new.model_name);
+                INSERT INTO images_fts_tri(rowid, prompt, negative_prompt, raw_metadata, model_name, workflow_text, property_text, ocr_text)
+                VALUES (new.id, new.prompt, new.negative_prompt, new.raw_metadata, new.model_name, new.workflow_text, new.property_text, new.ocr_text);
             END;",
         )?;
         conn.execute_batch(
             "CREATE TRIGGER IF NOT EXISTS images_ad_tri AFTER DELETE ON images BEGIN
-                INSERT INTO images_fts_tri(images_fts_tri, rowid, prompt, negative_prompt, raw_metadata, model_name)
-                VALUES ('delete', old.id, old.prompt, old.negative_prompt, old.raw_metadata, old.model_name);
+                INSERT INTO images_fts_tri(images_fts_tri, rowid, prompt, negative_prompt, raw_metadata, model_name, workflow_text, property_text, ocr_text)
+                VALUES ('delete', old.id, old.prompt, old.negative_prompt, old.raw_metadata, old.model_name, old.workflow_text, old.property_text, old.ocr_text);
             END;",
         )?;
         conn.execute_batch(
             "CREATE TRIGGER IF NOT EXISTS images_au_tri AFTER UPDATE ON images BEGIN
-                INSERT INTO images_fts_tri(images_fts_tri, rowid, prompt, negative_prompt, raw_metadata, model_name)
-                VALUES ('delete', old.id, old.prompt, old.negative_prompt, old.raw_metadata, old.model_name);
-                INSERT INTO images_fts_tri(rowid, prompt, negative_prompt, raw_metadata, model_name)
-                VALUES (new.id, new.prompt, new.negative_prompt, new.raw_metadata, new.model_name);
+                INSERT INTO images_fts_tri(images_fts_tri, rowid, prompt, negative_prompt, raw_metadata, model_name, workflow_text, property_text, ocr_text)
+                VALUES ('delete', old.id, old.prompt, old.negative_prompt, old.raw_metadata, old.model_name, old.workflow_text, old.property_text, old.ocr_text);
+                INSERT INTO images_fts_tri(rowid, prompt, negative_prompt, raw_metadata, model_name, workflow_text, property_text, ocr_text)
+                VALUES (new.id, new.prompt, new.negative_prompt, new.raw_metadata, new.model_name, new.workflow_text, new.property_text, new.ocr_text);
             END;",
         )?;
 
+        if rebuild_fts {
+            conn.execute_batch(
+                "INSERT INTO images_fts(images_fts) VALUES ('rebuild');
+                 INSERT INTO images_fts_tri(images_fts_tri) VALUES ('rebuild');",
+            )?;
+        }
+
         // Backfill trigram FTS for any existing rows not yet indexed.
         conn.execute_batch(
-            "INSERT OR IGNORE INTO images_fts_tri(rowid, prompt, negative_prompt, raw_metadata, model_name)
-             SELECT id, prompt, negative_prompt, raw_metadata, model_name FROM images
+            "INSERT OR IGNORE INTO images_fts_tri(rowid, prompt, negative_prompt, raw_metadata, model_name, workflow_text, property_text, ocr_text)
+             SELECT id, prompt, negative_prompt, raw_metadata, model_name, workflow_text, property_text, ocr_text FROM images
              WHERE id NOT IN (SELECT rowid FROM images_fts_tri);",
         )?;
 
@@ -297,39 +1053,266 @@ impl Database {
             );",
         )?;
 
-        // ── Indexes ──
-        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_images_seed ON images(seed);")?;
-        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_images_sampler ON images(sampler);")?;
-        conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_images_model_hash ON images(model_hash);",
+        // ── LoRAs applied to each image, with their weights ──
+        let loras_exist: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'image_loras')",
+            [],
+            |row| row.get(0),
         )?;
         conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_images_directory ON images(directory);",
+            "CREATE TABLE IF NOT EXISTS image_loras (
+                image_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                weight REAL NOT NULL,
+                PRIMARY KEY (image_id, name),
+                FOREIGN KEY(image_id) REFERENCES images(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_image_loras_name_weight ON image_loras(name, weight);",
         )?;
-        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_tags_tag ON tags(tag);")?;
-        conn.execute_batch("DROP INDEX IF EXISTS idx_image_tags_tag_id;")?;
-        conn.execute_batch("DROP INDEX IF EXISTS idx_images_model_name;")?;
-        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_images_filename ON images(filename);")?;
-        conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_images_file_mtime ON images(file_mtime);",
+        if !loras_exist {
+            Self::backfill_image_loras(&conn)?;
+        }
+
+        // ── Textual-inversion embeddings each image's prompts use ──
+        let embeddings_exist: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'image_embeddings_used')",
+            [],
+            |row| row.get(0),
         )?;
         conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_images_file_size ON images(file_size);",
+            "CREATE TABLE IF NOT EXISTS image_embeddings_used (
+                image_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                negative INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (image_id, name, negative),
+                FOREIGN KEY(image_id) REFERENCES images(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_image_embeddings_used_name
+                ON image_embeddings_used(name);",
         )?;
+        if !embeddings_exist {
+            Self::backfill_image_embeddings(&conn)?;
+        }
+
+        // ── Free-form key/value properties users attach to images ──
         conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_images_quick_hash ON images(quick_hash);",
+            "CREATE TABLE IF NOT EXISTS user_properties (
+                image_id INTEGER NOT NULL,
+                key TEXT NOT NULL COLLATE NOCASE,
+                value TEXT NOT NULL,
+                PRIMARY KEY (image_id, key),
+                FOREIGN KEY(image_id) REFERENCES images(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_user_properties_key_value
+                ON user_properties(key, value);",
         )?;
-        conn.execute_batch("DROP INDEX IF EXISTS idx_images_generation_type;")?;
+
+        // ── Prompt themes found by the last clustering run ──
         conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_image_tags_tag_id_image_id ON image_tags(tag_id, image_id);",
+            "CREATE TABLE IF NOT EXISTS prompt_clusters (
+                id INTEGER PRIMARY KEY,
+                label TEXT NOT NULL,
+                terms TEXT NOT NULL DEFAULT ''
+            );",
         )?;
+
+        // ── Rule-based collections and their current members ──
         conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_images_model_name_nocase_id ON images(model_name COLLATE NOCASE, id DESC);",
+            "CREATE TABLE IF NOT EXISTS collections (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT UNIQUE NOT NULL COLLATE NOCASE,
+                rule TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS collection_images (
+                collection_id INTEGER NOT NULL,
+                image_id INTEGER NOT NULL,
+                PRIMARY KEY (collection_id, image_id),
+                FOREIGN KEY(collection_id) REFERENCES collections(id) ON DELETE CASCADE,
+                FOREIGN KEY(image_id) REFERENCES images(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_collection_images_image_id
+                ON collection_images(image_id);",
         )?;
+
+        // ── Undo journal for bulk field edits ──
         conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_images_generation_type_id ON images(generation_type, id DESC);",
+            "CREATE TABLE IF NOT EXISTS field_edits (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                edited_at INTEGER NOT NULL,
+                fields TEXT NOT NULL,
+                image_count INTEGER NOT NULL,
+                undone INTEGER NOT NULL DEFAULT 0,
+                written TEXT
+            );
+            CREATE TABLE IF NOT EXISTS field_edit_rows (
+                edit_id INTEGER NOT NULL,
+                image_id INTEGER NOT NULL,
+                previous TEXT NOT NULL,
+                PRIMARY KEY (edit_id, image_id),
+                FOREIGN KEY(edit_id) REFERENCES field_edits(id) ON DELETE CASCADE,
+                FOREIGN KEY(image_id) REFERENCES images(id) ON DELETE CASCADE
+            );",
         )?;
-
+        // What each edit wrote, so an undo can tell whether it is still the
+        // latest change. Journals from before it leave the column NULL.
+        let has_written: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info('field_edits')
+                           WHERE name = 'written')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_written {
+            conn.execute_batch("ALTER TABLE field_edits ADD COLUMN written TEXT;")?;
+        }
+
+        // ── Activity log: notable events and the files they touched ──
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS activity_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                occurred_at INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                detail TEXT NOT NULL DEFAULT '{}'
+            );
+            CREATE INDEX IF NOT EXISTS idx_activity_log_occurred_at
+                ON activity_log(occurred_at);
+            CREATE TABLE IF NOT EXISTS activity_files (
+                entry_id INTEGER NOT NULL,
+                filepath TEXT NOT NULL,
+                PRIMARY KEY (entry_id, filepath),
+                FOREIGN KEY(entry_id) REFERENCES activity_log(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_activity_files_filepath
+                ON activity_files(filepath);",
+        )?;
+
+        // ── Journal of bulk file operations, for resuming after a crash ──
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS operation_journal (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                detail TEXT NOT NULL DEFAULT '{}'
+            );
+            CREATE TABLE IF NOT EXISTS operation_journal_steps (
+                operation_id INTEGER NOT NULL,
+                image_id INTEGER NOT NULL,
+                source TEXT NOT NULL,
+                destination TEXT,
+                done INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (operation_id, image_id),
+                FOREIGN KEY(operation_id) REFERENCES operation_journal(id) ON DELETE CASCADE
+            );",
+        )?;
+
+        // ── Tag dictionary (imported autocomplete vocabulary) ──
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS tag_dictionary (
+                tag TEXT PRIMARY KEY,
+                category INTEGER NOT NULL DEFAULT 0,
+                post_count INTEGER NOT NULL DEFAULT 0
+            );",
+        )?;
+
+        // ── Prompt styles (imported from the WebUI's styles.csv) ──
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS prompt_styles (
+                name TEXT PRIMARY KEY,
+                prompt TEXT NOT NULL DEFAULT '',
+                negative_prompt TEXT NOT NULL DEFAULT '',
+                position INTEGER NOT NULL DEFAULT 0
+            );",
+        )?;
+
+        // ── Per-model generation defaults, keyed by family id or checkpoint token ──
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS model_defaults (
+                model_key TEXT PRIMARY KEY,
+                model TEXT NOT NULL,
+                sampler TEXT,
+                scheduler TEXT,
+                steps INTEGER,
+                cfg_scale REAL,
+                width INTEGER,
+                height INTEGER
+            );",
+        )?;
+
+        // ── Directory pins and display aliases ──
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS directory_meta (
+                directory TEXT PRIMARY KEY,
+                pinned INTEGER NOT NULL DEFAULT 0,
+                alias TEXT,
+                hidden INTEGER NOT NULL DEFAULT 0
+            );",
+        )?;
+
+        // ── Why thumbnails could not be made, for the diagnostics view ──
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS thumbnail_failures (
+                image_id INTEGER PRIMARY KEY,
+                kind TEXT NOT NULL,
+                message TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 1,
+                failed_at INTEGER NOT NULL,
+                FOREIGN KEY(image_id) REFERENCES images(id) ON DELETE CASCADE
+            );",
+        )?;
+
+        // ── Indexes ──
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_images_seed ON images(seed);")?;
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_images_sampler ON images(sampler);")?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_images_source_tool ON images(source_tool);",
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_images_workflow_status ON images(workflow_status);",
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_images_prompt_cluster ON images(prompt_cluster);",
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_images_model_hash ON images(model_hash);",
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_images_directory ON images(directory);",
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_images_model_family ON images(model_family);",
+        )?;
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_tags_tag ON tags(tag);")?;
+        conn.execute_batch("DROP INDEX IF EXISTS idx_image_tags_tag_id;")?;
+        conn.execute_batch("DROP INDEX IF EXISTS idx_images_model_name;")?;
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_images_filename ON images(filename);")?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_images_file_mtime ON images(file_mtime);",
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_images_file_size ON images(file_size);",
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_images_quick_hash ON images(quick_hash);",
+        )?;
+        conn.execute_batch("DROP INDEX IF EXISTS idx_images_generation_type;")?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_image_tags_tag_id_image_id ON image_tags(tag_id, image_id);",
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_images_model_name_nocase_id ON images(model_name COLLATE NOCASE, id DESC);",
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_images_generation_type_id ON images(generation_type, id DESC);",
+        )?;
+        for (index, key) in HOT_EXTRA_PARAM_KEYS.iter().enumerate() {
+            conn.execute_batch(&format!(
+                "CREATE INDEX IF NOT EXISTS idx_images_extra_param_{} ON images({});",
+                index,
+                extra_param_expr(key)
+            ))?;
+        }
+
         Ok(())
     }
 
@@ -349,6 +1332,28 @@ impl Database {
             ("generation_type", "TEXT"),
             ("is_favorite", "INTEGER NOT NULL DEFAULT 0"),
             ("is_locked", "INTEGER NOT NULL DEFAULT 0"),
+            ("nsfw_score", "REAL"),
+            ("archived", "INTEGER NOT NULL DEFAULT 0"),
+            ("archive_path", "TEXT"),
+            ("archive_entry", "TEXT"),
+            ("focal_x", "REAL"),
+            ("focal_y", "REAL"),
+            ("color_hue", "REAL"),
+            ("color_saturation", "REAL"),
+            ("color_brightness", "REAL"),
+            ("model_family", "TEXT"),
+            ("is_corrupt", "INTEGER NOT NULL DEFAULT 0"),
+            ("workflow_text", "TEXT"),
+            ("source_tool", "TEXT"),
+            ("property_text", "TEXT"),
+            ("workflow_status", "TEXT NOT NULL DEFAULT 'inbox'"),
+            ("sharpness", "REAL"),
+            ("dark_fraction", "REAL"),
+            ("bright_fraction", "REAL"),
+            ("ocr_text", "TEXT"),
+            ("prompt_cluster", "INTEGER"),
+            ("rating", "INTEGER"),
+            ("phash", "INTEGER"),
         ] {
             if existing_columns.contains(name) {
                 continue;
@@ -395,14 +1400,189 @@ impl Database {
         }
         Ok(())
     }
+
+    /// Drops both search indexes and their triggers when they predate the
+    /// newest searchable column, `ocr_text`; FTS5 tables can't gain
+    /// columns in place.
+    /// Returns whether they have to be rebuilt once recreated.
+    fn drop_outdated_fts(conn: &Connection) -> SqlResult<bool> {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'images_fts')",
+            [],
+            |row| row.get(0),
+        )?;
+        let current: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info('images_fts')
+                           WHERE name = 'ocr_text')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !exists || current {
+            return Ok(false);
+        }
+        log::info!("Rebuilding search indexes to cover new searchable columns");
+        conn.execute_batch(
+            "DROP TRIGGER IF EXISTS images_ai;
+             DROP TRIGGER IF EXISTS images_ad;
+             DROP TRIGGER IF EXISTS images_au;
+             DROP TRIGGER IF EXISTS images_ai_tri;
+             DROP TRIGGER IF EXISTS images_ad_tri;
+             DROP TRIGGER IF EXISTS images_au_tri;
+             DROP TABLE IF EXISTS images_fts;
+             DROP TABLE IF EXISTS images_fts_tri;",
+        )?;
+        Ok(true)
+    }
+
+    /// Fills `source_tool` for rows indexed before it existed, inferring
+    /// the generator from `raw_metadata` the way a scan would.
+    fn backfill_source_tools(conn: &Connection) -> SqlResult<()> {
+        let mut select_stmt =
+            conn.prepare("SELECT id, raw_metadata FROM images WHERE source_tool IS NULL")?;
+        let rows = select_stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut updates = Vec::<(i64, String)>::new();
+        for row in rows {
+            let (id, raw_metadata) = row?;
+            updates.push((id, infer_source_tool(&raw_metadata)));
+        }
+
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut update_stmt = tx.prepare("UPDATE images SET source_tool = ?1 WHERE id = ?2")?;
+            for (id, source_tool) in updates {
+                update_stmt.execute(params![source_tool, id])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Flattens the ComfyUI graphs of rows indexed before `workflow_text`
+    /// existed. Only the prompt graph in `raw_metadata` is available here;
+    /// editor titles arrive once the file is re-indexed (`reparse_image`).
+    fn backfill_workflow_text(conn: &Connection) -> SqlResult<()> {
+        let mut select_stmt =
+            conn.prepare("SELECT id, raw_metadata FROM images WHERE workflow_text IS NULL")?;
+        let rows = select_stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut updates = Vec::<(i64, String)>::new();
+        for row in rows {
+            let (id, raw_metadata) = row?;
+            updates.push((id, workflow_search_text(&raw_metadata, None)));
+        }
+
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut update_stmt =
+                tx.prepare("UPDATE images SET workflow_text = ?1 WHERE id = ?2")?;
+            for (id, text) in updates {
+                update_stmt.execute(params![text, id])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Scores rows indexed before `nsfw_score` existed.
+    fn backfill_nsfw_scores(conn: &Connection) -> SqlResult<()> {
+        let mut select_stmt =
+            conn.prepare("SELECT id, prompt FROM images WHERE nsfw_score IS NULL")?;
+        let rows = select_stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut updates = Vec::<(i64, f32)>::new();
+        for row in rows {
+            let (id, prompt) = row?;
+            updates.push((id, nsfw::prompt_score(&prompt)));
+        }
+
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut update_stmt = tx.prepare("UPDATE images SET nsfw_score = ?1 WHERE id = ?2")?;
+            for (id, score) in updates {
+                update_stmt.execute(params![score, id])?;
+            }
+        }
+        tx.commit()
+    }
 }
 
+mod activity_queries;
+mod archive_queries;
+mod batch_queries;
 mod bulk_operations;
+mod collection_queries;
+mod color_queries;
 mod cursor_queries;
+mod deletion_safety_queries;
+mod directory_meta_queries;
+mod embedding_queries;
+mod extra_param_queries;
+mod field_edit_queries;
+mod filter_count_queries;
+mod filter_update_queries;
+mod focal_queries;
+mod grid_queries;
+mod lora_queries;
+mod model_default_queries;
+mod model_family;
+mod nsfw_queries;
+mod ocr_queries;
+mod offline_queries;
+mod operation_journal_queries;
+mod phash_queries;
+mod private_queries;
+mod project_queries;
+mod prompt_cluster_queries;
+mod prompt_edit_queries;
+mod prompt_style_queries;
+mod quality_queries;
 mod read_queries;
+mod record_cache;
+mod seed_queries;
+mod stats_queries;
+mod suggestion_queries;
+mod tag_dictionary_queries;
+mod thumbnail_failure_queries;
+mod user_property_queries;
+mod workflow_queries;
+
+use record_cache::{RecordCache, RECORD_CACHE_CAPACITY};
 
 // ────────────────────── Sort configuration ──────────────────────
 
+/// Colour wheel order: 24 hue bands centred on red, each running dark to
+/// light, then the greyscale images dark to light. Fixed-width text so cursor
+/// values compare correctly; NULL until the thumbnail's colour stats are known.
+const HUE_SORT_KEY: &str = "CASE
+    WHEN color_brightness IS NULL THEN NULL
+    WHEN color_hue IS NULL THEN printf('1%.3f', color_brightness)
+    ELSE printf('0%02d%.3f', CAST((color_hue + 7.5) / 15 AS INTEGER) % 24, color_brightness)
+END";
+
+const BRIGHTNESS_SORT_KEY: &str =
+    "CASE WHEN color_brightness IS NOT NULL THEN printf('%.3f', color_brightness) END";
+
+/// Zero-padded so the text compares like the number; NULL until measured.
+const SHARPNESS_SORT_KEY: &str =
+    "CASE WHEN sharpness IS NOT NULL THEN printf('%012.3f', sharpness) END";
+
 struct SortConfig {
     descending: bool,
     field: &'static str,
@@ -431,6 +1611,26 @@ impl SortConfig {
                 field: "generation_type",
                 descending: false,
             },
+            "hue" => SortConfig {
+                field: HUE_SORT_KEY,
+                descending: false,
+            },
+            "brightness_asc" => SortConfig {
+                field: BRIGHTNESS_SORT_KEY,
+                descending: false,
+            },
+            "brightness_desc" => SortConfig {
+                field: BRIGHTNESS_SORT_KEY,
+                descending: true,
+            },
+            "sharpness_asc" => SortConfig {
+                field: SHARPNESS_SORT_KEY,
+                descending: false,
+            },
+            "sharpness_desc" => SortConfig {
+                field: SHARPNESS_SORT_KEY,
+                descending: true,
+            },
             _ => SortConfig {
                 field: "id",
                 descending: true,
@@ -487,6 +1687,41 @@ fn image_record_from_row(row: &Row<'_>) -> SqlResult<ImageRecord> {
     })
 }
 
+/// Columns read by `gallery_image_record_from_row`, in order.
+const GALLERY_COLUMNS: &str = "images.id, images.filepath, images.filename, images.directory,
+    images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked";
+
+/// Images with the same prompt, model and size form one duplicate group.
+/// Images without a prompt are never grouped.
+const DUPLICATE_GROUP_KEY: &str =
+    "COALESCE(NULLIF(images.prompt, ''), '#' || images.id), images.model_name, images.width, images.height";
+
+/// Turns the `FROM ... WHERE ...` part of a cursor query into a SELECT,
+/// adding `sort_value` for non-id sorts. With `collapse_duplicates` only the
+/// newest image of each duplicate group among the filtered rows is kept and
+/// the group size comes back as `duplicate_count`; conditions appended
+/// afterwards (the cursor) apply to the collapsed rows.
+fn cursor_select_sql(sort: &SortConfig, filtered: &str, collapse_duplicates: bool) -> String {
+    let sort_column = if sort.field == "id" {
+        String::new()
+    } else {
+        format!(", {} AS sort_value", sort.sort_expr())
+    };
+    if !collapse_duplicates {
+        return format!("SELECT {GALLERY_COLUMNS}{sort_column}{filtered}");
+    }
+    format!(
+        "SELECT {GALLERY_COLUMNS}{sort_column}, images.duplicate_count
+         FROM (
+             SELECT images.*,
+                    ROW_NUMBER() OVER (PARTITION BY {DUPLICATE_GROUP_KEY} ORDER BY images.id DESC) AS duplicate_rank,
+                    COUNT(*) OVER (PARTITION BY {DUPLICATE_GROUP_KEY}) AS duplicate_count
+             {filtered}
+         ) AS images
+         WHERE images.duplicate_rank = 1"
+    )
+}
+
 fn gallery_image_record_from_row(row: &Row<'_>) -> SqlResult<GalleryImageRecord> {
     Ok(GalleryImageRecord {
         id: row.get(0)?,
@@ -499,6 +1734,11 @@ fn gallery_image_record_from_row(row: &Row<'_>) -> SqlResult<GalleryImageRecord>
         model_name: row.get(7)?,
         is_favorite: row.get(8)?,
         is_locked: row.get(9)?,
+        offline: false,
+        duplicate_count: match row.as_ref().column_index("duplicate_count") {
+            Ok(index) => row.get(index)?,
+            Err(_) => None,
+        },
     })
 }
 
@@ -511,6 +1751,8 @@ fn normalize_generation_type(value: &str) -> Option<&'static str> {
         "grid" | "grids" => Some("grid"),
         "upscale" | "extras" => Some("upscale"),
         "unknown" => Some("unknown"),
+        "archived" | "archive" => Some("archived"),
+        "missing_metadata" | "incomplete" => Some("missing_metadata"),
         _ => None,
     }
 }
@@ -533,10 +1775,50 @@ fn normalize_generation_types(generation_types: Option<&[String]>) -> Vec<String
     normalized
 }
 
+/// Generation types with a sidebar badge; the pseudo types are left out.
+const COUNTED_GENERATION_TYPES: [&str; 6] = [
+    "txt2img", "img2img", "inpaint", "grid", "upscale", "unknown",
+];
+
+/// Images lacking a prompt, model or seed: usually indexed from a sidecar
+/// `.txt` or from a file whose metadata failed to parse.
+const MISSING_METADATA_CONDITION: &str = "(TRIM(COALESCE(images.prompt, '')) = ''
+    OR TRIM(COALESCE(images.model_name, '')) = ''
+    OR TRIM(COALESCE(images.seed, '')) = '')";
+
 fn append_generation_type_filter(
     sql: &mut String,
     params: &mut Vec<Value>,
     generation_types: &[String],
+) {
+    // Archived images only appear when "archived" is selected, which then
+    // narrows the other selected types to the archive.
+    let archived_view = generation_types.iter().any(|value| value == "archived");
+    sql.push_str(if archived_view {
+        " AND images.archived = 1"
+    } else {
+        " AND images.archived = 0"
+    });
+    // Like "archived", "missing_metadata" narrows rather than widens.
+    if generation_types
+        .iter()
+        .any(|value| value == "missing_metadata")
+    {
+        sql.push_str(&format!(" AND {}", MISSING_METADATA_CONDITION));
+    }
+    let generation_types: Vec<&String> = generation_types
+        .iter()
+        .filter(|value| *value != "archived" && *value != "missing_metadata")
+        .collect();
+    append_generation_type_choice(sql, params, &generation_types);
+}
+
+/// Matches any of `generation_types`, which must not include the
+/// "archived" and "missing_metadata" pseudo types.
+fn append_generation_type_choice(
+    sql: &mut String,
+    params: &mut Vec<Value>,
+    generation_types: &[&String],
 ) {
     if generation_types.is_empty() {
         return;
@@ -548,7 +1830,7 @@ fn append_generation_type_filter(
             sql.push_str(" OR ");
         }
 
-        if generation_type == "grid" {
+        if generation_type.as_str() == "grid" {
             // Some Forge/A1111 grid outputs are stored under *-grids folders and can be
             // misclassified in older scans. Keep grid filtering reliable by including
             // path/filename/metadata fallbacks.
@@ -578,7 +1860,7 @@ fn append_generation_type_filter(
             params.push(Value::Text("%y values:%".to_string()));
         } else {
             sql.push_str("images.generation_type = ?");
-            params.push(Value::Text(generation_type.clone()));
+            params.push(Value::Text(generation_type.to_string()));
         }
     }
     sql.push(')');
@@ -606,114 +1888,476 @@ fn append_model_filter(
     params.push(Value::Text(normalized.to_string()));
 }
 
-const FAMILY_PATTERNS_PONYXL: &[&str] = &["%ponyxl%", "%pony xl%", "%pony diffusion%", "%pony%"];
-const FAMILY_PATTERNS_SDXL: &[&str] = &["%sdxl%", "%stable diffusion xl%"];
-const FAMILY_PATTERNS_FLUX: &[&str] = &["%flux%"];
-const FAMILY_PATTERNS_ZIMAGE_TURBO: &[&str] =
-    &["%z-image turbo%", "%zimage turbo%", "%z-image%", "%zimage%"];
-const FAMILY_PATTERNS_SD15: &[&str] = &["%sd1.5%", "%sd15%", "%stable diffusion 1.5%"];
-const FAMILY_PATTERNS_SD21: &[&str] = &["%sd2.1%", "%sd21%", "%stable diffusion 2.1%"];
-const FAMILY_PATTERNS_CHROMA: &[&str] = &["%chroma%"];
-const FAMILY_PATTERNS_VACE: &[&str] = &["%vace%"];
-
-fn normalize_model_family(value: &str) -> Option<&'static str> {
-    let compact: String = value
-        .trim()
-        .to_ascii_lowercase()
-        .chars()
-        .filter(|ch| ch.is_ascii_alphanumeric())
-        .collect();
+/// Sampler label for images whose metadata names none, e.g. bare
+/// ComfyUI graphs.
+const UNKNOWN_SAMPLER: &str = "Unknown";
 
-    match compact.as_str() {
-        "pony" | "ponyxl" => Some("ponyxl"),
-        "sdxl" => Some("sdxl"),
-        "flux" => Some("flux"),
-        "zimage" | "zimageturbo" => Some("zimage_turbo"),
-        "sd15" | "stablediffusion15" | "sdv15" => Some("sd15"),
-        "sd21" | "stablediffusion21" | "sdv21" => Some("sd21"),
-        "chroma" => Some("chroma"),
-        "vace" => Some("vace"),
-        _ => None,
-    }
-}
+/// Scheduler label for images without a `Schedule type`: older A1111 output
+/// and Forge's "Automatic" both leave it out.
+const UNKNOWN_SCHEDULER: &str = "Unknown";
 
-fn normalize_model_family_filters(model_family_filters: Option<&[String]>) -> Vec<String> {
-    let Some(filters) = model_family_filters else {
-        return Vec::new();
+fn scheduler_label_sql(table_prefix: Option<&str>) -> String {
+    let column = match table_prefix {
+        Some(prefix) => format!("{}.schedule_type", prefix),
+        None => "schedule_type".to_string(),
     };
-
-    let mut seen = HashSet::<String>::new();
-    let mut normalized = Vec::new();
-    for filter in filters {
-        let Some(canonical) = normalize_model_family(filter) else {
-            continue;
-        };
-        let owned = canonical.to_string();
-        if seen.insert(owned.clone()) {
-            normalized.push(owned);
-        }
-    }
-    normalized
+    format!(
+        "COALESCE(NULLIF(TRIM({}), ''), '{}')",
+        column, UNKNOWN_SCHEDULER
+    )
 }
 
-fn family_patterns(family: &str) -> &'static [&'static str] {
-    match family {
-        "ponyxl" => FAMILY_PATTERNS_PONYXL,
-        "sdxl" => FAMILY_PATTERNS_SDXL,
-        "flux" => FAMILY_PATTERNS_FLUX,
-        "zimage_turbo" => FAMILY_PATTERNS_ZIMAGE_TURBO,
-        "sd15" => FAMILY_PATTERNS_SD15,
-        "sd21" => FAMILY_PATTERNS_SD21,
-        "chroma" => FAMILY_PATTERNS_CHROMA,
-        "vace" => FAMILY_PATTERNS_VACE,
-        _ => &[],
+fn append_scheduler_filter(
+    sql: &mut String,
+    params: &mut Vec<Value>,
+    scheduler_filters: Option<&[String]>,
+    table_prefix: Option<&str>,
+) {
+    let schedulers: Vec<&str> = scheduler_filters
+        .unwrap_or_default()
+        .iter()
+        .map(|scheduler| scheduler.trim())
+        .filter(|scheduler| !scheduler.is_empty())
+        .collect();
+    if schedulers.is_empty() {
+        return;
     }
+
+    sql.push_str(&format!(
+        " AND {} COLLATE NOCASE IN ({})",
+        scheduler_label_sql(table_prefix),
+        vec!["?"; schedulers.len()].join(", ")
+    ));
+    params.extend(
+        schedulers
+            .into_iter()
+            .map(|scheduler| Value::Text(scheduler.to_string())),
+    );
 }
 
-fn append_model_family_filter(
+fn append_source_tool_filter(
     sql: &mut String,
     params: &mut Vec<Value>,
-    model_family_filters: &[String],
+    source_tools: Option<&[String]>,
     table_prefix: Option<&str>,
 ) {
-    if model_family_filters.is_empty() {
+    let tools: Vec<String> = source_tools
+        .unwrap_or_default()
+        .iter()
+        .map(|tool| tool.trim().to_ascii_lowercase())
+        .filter(|tool| !tool.is_empty())
+        .collect();
+    if tools.is_empty() {
         return;
     }
 
-    let column = if let Some(prefix) = table_prefix {
-        format!("LOWER({}.model_name)", prefix)
-    } else {
-        "LOWER(model_name)".to_string()
+    let column = match table_prefix {
+        Some(prefix) => format!("{}.source_tool", prefix),
+        None => "source_tool".to_string(),
     };
+    sql.push_str(&format!(
+        " AND {} IN ({})",
+        column,
+        vec!["?"; tools.len()].join(", ")
+    ));
+    params.extend(tools.into_iter().map(Value::Text));
+}
 
-    let groups: Vec<&[&str]> = model_family_filters
+fn append_workflow_status_filter(
+    sql: &mut String,
+    params: &mut Vec<Value>,
+    workflow_statuses: Option<&[String]>,
+    table_prefix: Option<&str>,
+) {
+    let statuses: Vec<String> = workflow_statuses
+        .unwrap_or_default()
         .iter()
-        .map(|family| family_patterns(family))
-        .filter(|patterns| !patterns.is_empty())
+        .map(|status| status.trim().to_ascii_lowercase())
+        .filter(|status| !status.is_empty())
         .collect();
-    if groups.is_empty() {
+    if statuses.is_empty() {
         return;
     }
 
-    sql.push_str(" AND (");
-    for (group_idx, group_patterns) in groups.iter().enumerate() {
-        if group_idx > 0 {
-            sql.push_str(" OR ");
-        }
-        sql.push('(');
-        for (pattern_idx, pattern) in group_patterns.iter().enumerate() {
-            if pattern_idx > 0 {
-                sql.push_str(" OR ");
-            }
-            sql.push_str(&column);
-            sql.push_str(" LIKE ?");
+    let column = match table_prefix {
+        Some(prefix) => format!("{}.workflow_status", prefix),
+        None => "workflow_status".to_string(),
+    };
+    sql.push_str(&format!(
+        " AND {} IN ({})",
+        column,
+        vec!["?"; statuses.len()].join(", ")
+    ));
+    params.extend(statuses.into_iter().map(Value::Text));
+}
+
+fn append_prompt_cluster_filter(
+    sql: &mut String,
+    params: &mut Vec<Value>,
+    prompt_clusters: Option<&[i64]>,
+    table_prefix: Option<&str>,
+) {
+    let clusters = prompt_clusters.unwrap_or_default();
+    if clusters.is_empty() {
+        return;
+    }
+
+    let column = match table_prefix {
+        Some(prefix) => format!("{}.prompt_cluster", prefix),
+        None => "prompt_cluster".to_string(),
+    };
+    sql.push_str(&format!(
+        " AND {} IN ({})",
+        column,
+        vec!["?"; clusters.len()].join(", ")
+    ));
+    params.extend(clusters.iter().map(|cluster| Value::Integer(*cluster)));
+}
+
+fn append_collection_filter(
+    sql: &mut String,
+    params: &mut Vec<Value>,
+    collections: Option<&[i64]>,
+    table_prefix: Option<&str>,
+) {
+    let collections = collections.unwrap_or_default();
+    if collections.is_empty() {
+        return;
+    }
+
+    let column = match table_prefix {
+        Some(prefix) => format!("{}.id", prefix),
+        None => "id".to_string(),
+    };
+    sql.push_str(&format!(
+        " AND {} IN (SELECT image_id FROM collection_images WHERE collection_id IN ({}))",
+        column,
+        vec!["?"; collections.len()].join(", ")
+    ));
+    params.extend(
+        collections
+            .iter()
+            .map(|collection| Value::Integer(*collection)),
+    );
+}
+
+fn append_sharpness_filter(
+    sql: &mut String,
+    params: &mut Vec<Value>,
+    max_sharpness: Option<f32>,
+    table_prefix: Option<&str>,
+) {
+    let Some(max_sharpness) = max_sharpness.filter(|value| value.is_finite()) else {
+        return;
+    };
+    let column = match table_prefix {
+        Some(prefix) => format!("{}.sharpness", prefix),
+        None => "sharpness".to_string(),
+    };
+    sql.push_str(&format!(" AND {} <= ?", column));
+    params.push(Value::Real(max_sharpness as f64));
+}
+
+/// Sizes snap to the nearest multiple of 64, the latent grid, so a few
+/// pixels of cropping do not split a bucket.
+const RESOLUTION_BUCKET_STEP: u32 = 64;
+
+fn resolution_bucket_sql(table_prefix: Option<&str>) -> String {
+    let prefix = table_prefix
+        .map(|prefix| format!("{}.", prefix))
+        .unwrap_or_default();
+    let snap = |column: &str| {
+        format!(
+            "((({prefix}{column} + {half}) / {step}) * {step})",
+            half = RESOLUTION_BUCKET_STEP / 2,
+            step = RESOLUTION_BUCKET_STEP
+        )
+    };
+    format!("({} || 'x' || {})", snap("width"), snap("height"))
+}
+
+/// Size class of a bucket; the boundaries sit between 768x768 and
+/// SDXL's smallest native buckets, and well above its largest.
+fn resolution_tier(width: u32, height: u32) -> &'static str {
+    match u64::from(width) * u64::from(height) {
+        0..=600_000 => "sd15",
+        600_001..=1_300_000 => "sdxl",
+        _ => "large",
+    }
+}
+
+fn append_resolution_bucket_filter(
+    sql: &mut String,
+    params: &mut Vec<Value>,
+    resolution_buckets: Option<&[String]>,
+    table_prefix: Option<&str>,
+) {
+    let buckets: Vec<String> = resolution_buckets
+        .unwrap_or_default()
+        .iter()
+        .map(|bucket| bucket.trim().to_ascii_lowercase().replace(['×', '*'], "x"))
+        .filter(|bucket| !bucket.is_empty())
+        .collect();
+    if buckets.is_empty() {
+        return;
+    }
+
+    sql.push_str(&format!(
+        " AND {} IN ({})",
+        resolution_bucket_sql(table_prefix),
+        vec!["?"; buckets.len()].join(", ")
+    ));
+    params.extend(buckets.into_iter().map(Value::Text));
+}
+
+fn append_failed_render_filter(sql: &mut String, failed_renders_only: bool) {
+    if failed_renders_only {
+        sql.push_str(&format!(
+            " AND (images.dark_fraction >= {0} OR images.bright_fraction >= {0})",
+            image_quality::FAILED_EXPOSURE_FRACTION
+        ));
+    }
+}
+
+fn append_hidden_directory_filter(sql: &mut String, include_hidden: bool) {
+    if !include_hidden {
+        sql.push_str(
+            " AND images.directory NOT IN (SELECT directory FROM directory_meta WHERE hidden = 1)",
+        );
+    }
+}
+
+const FAMILY_PATTERNS_PONYXL: &[&str] = &["%ponyxl%", "%pony xl%", "%pony diffusion%", "%pony%"];
+const FAMILY_PATTERNS_SDXL: &[&str] = &["%sdxl%", "%stable diffusion xl%"];
+const FAMILY_PATTERNS_FLUX: &[&str] = &["%flux%"];
+const FAMILY_PATTERNS_ZIMAGE_TURBO: &[&str] =
+    &["%z-image turbo%", "%zimage turbo%", "%z-image%", "%zimage%"];
+const FAMILY_PATTERNS_SD15: &[&str] = &["%sd1.5%", "%sd15%", "%stable diffusion 1.5%"];
+const FAMILY_PATTERNS_SD21: &[&str] = &["%sd2.1%", "%sd21%", "%stable diffusion 2.1%"];
+const FAMILY_PATTERNS_CHROMA: &[&str] = &["%chroma%"];
+const FAMILY_PATTERNS_VACE: &[&str] = &["%vace%"];
+
+fn normalize_model_family(value: &str) -> Option<&'static str> {
+    let compact: String = value
+        .trim()
+        .to_ascii_lowercase()
+        .chars()
+        .filter(|ch| ch.is_ascii_alphanumeric())
+        .collect();
+
+    match compact.as_str() {
+        "pony" | "ponyxl" => Some("ponyxl"),
+        "sdxl" => Some("sdxl"),
+        "flux" => Some("flux"),
+        "zimage" | "zimageturbo" => Some("zimage_turbo"),
+        "sd15" | "stablediffusion15" | "sdv15" => Some("sd15"),
+        "sd21" | "stablediffusion21" | "sdv21" => Some("sd21"),
+        "chroma" => Some("chroma"),
+        "vace" => Some("vace"),
+        _ => None,
+    }
+}
+
+fn normalize_model_family_filters(model_family_filters: Option<&[String]>) -> Vec<String> {
+    let Some(filters) = model_family_filters else {
+        return Vec::new();
+    };
+
+    let mut seen = HashSet::<String>::new();
+    let mut normalized = Vec::new();
+    for filter in filters {
+        let Some(canonical) = normalize_model_family(filter) else {
+            continue;
+        };
+        let owned = canonical.to_string();
+        if seen.insert(owned.clone()) {
+            normalized.push(owned);
+        }
+    }
+    normalized
+}
+
+/// Every family `family_patterns` knows, in sidebar order.
+const MODEL_FAMILIES: [&str; 8] = [
+    "ponyxl",
+    "sdxl",
+    "flux",
+    "zimage_turbo",
+    "sd15",
+    "sd21",
+    "chroma",
+    "vace",
+];
+
+fn family_patterns(family: &str) -> &'static [&'static str] {
+    match family {
+        "ponyxl" => FAMILY_PATTERNS_PONYXL,
+        "sdxl" => FAMILY_PATTERNS_SDXL,
+        "flux" => FAMILY_PATTERNS_FLUX,
+        "zimage_turbo" => FAMILY_PATTERNS_ZIMAGE_TURBO,
+        "sd15" => FAMILY_PATTERNS_SD15,
+        "sd21" => FAMILY_PATTERNS_SD21,
+        "chroma" => FAMILY_PATTERNS_CHROMA,
+        "vace" => FAMILY_PATTERNS_VACE,
+        _ => &[],
+    }
+}
+
+/// Matches the stored `model_family`. Rows indexed before it existed are
+/// matched on model name patterns until the startup backfill reaches them.
+fn append_model_family_filter(
+    sql: &mut String,
+    params: &mut Vec<Value>,
+    model_family_filters: &[String],
+    table_prefix: Option<&str>,
+) {
+    if model_family_filters.is_empty() {
+        return;
+    }
+
+    let qualify = |column: &str| match table_prefix {
+        Some(prefix) => format!("{}.{}", prefix, column),
+        None => column.to_string(),
+    };
+    let name_column = format!("LOWER({})", qualify("model_name"));
+    let family_column = qualify("model_family");
+
+    let groups: Vec<(&String, &[&str])> = model_family_filters
+        .iter()
+        .map(|family| (family, family_patterns(family)))
+        .filter(|(_, patterns)| !patterns.is_empty())
+        .collect();
+    if groups.is_empty() {
+        return;
+    }
+
+    sql.push_str(" AND (");
+    for (group_idx, (family, group_patterns)) in groups.iter().enumerate() {
+        if group_idx > 0 {
+            sql.push_str(" OR ");
+        }
+        sql.push_str(&format!(
+            "({} = ? OR ({} IS NULL AND (",
+            family_column, family_column
+        ));
+        params.push(Value::Text((*family).clone()));
+        for (pattern_idx, pattern) in group_patterns.iter().enumerate() {
+            if pattern_idx > 0 {
+                sql.push_str(" OR ");
+            }
+            sql.push_str(&name_column);
+            sql.push_str(" LIKE ?");
             params.push(Value::Text((*pattern).to_string()));
         }
-        sql.push(')');
+        sql.push_str(")))");
     }
     sql.push(')');
 }
 
+fn append_tag_filters(
+    sql: &mut String,
+    params: &mut Vec<Value>,
+    include_tags: &[String],
+    exclude_tags: &[String],
+) {
+    for tag in include_tags {
+        sql.push_str(
+            " AND EXISTS (
+                SELECT 1 FROM image_tags it JOIN tags t ON t.id = it.tag_id
+                WHERE it.image_id = images.id AND t.tag = ?
+            )",
+        );
+        params.push(Value::Text(tag.trim().to_ascii_lowercase()));
+    }
+
+    for tag in exclude_tags {
+        sql.push_str(
+            " AND NOT EXISTS (
+                SELECT 1 FROM image_tags it JOIN tags t ON t.id = it.tag_id
+                WHERE it.image_id = images.id AND t.tag = ?
+            )",
+        );
+        params.push(Value::Text(tag.trim().to_ascii_lowercase()));
+    }
+}
+
+/// Unmapped A1111 parameters common enough in Forge output to be worth an
+/// expression index. Filters on other keys still work, by scanning. Append
+/// only: the index names are positional.
+const HOT_EXTRA_PARAM_KEYS: [&str; 6] = [
+    "VAE",
+    "Clip skip",
+    "Denoising strength",
+    "Hires upscaler",
+    "ADetailer model",
+    "Version",
+];
+
+/// SQL for one key of `extra_params`. The hot-key indexes are built from the
+/// same text, which SQLite needs in order to use them; rows whose
+/// `extra_params` is not valid JSON read as NULL instead of failing the query.
+fn extra_param_expr(key: &str) -> String {
+    extra_param_expr_on("extra_params", key)
+}
+
+/// `extra_param_expr` on a qualified column, for self-joins such as
+/// `anchor.extra_params`.
+fn extra_param_expr_on(column: &str, key: &str) -> String {
+    let path = format!("$.\"{}\"", key).replace('\'', "''");
+    format!("(CASE WHEN json_valid({column}) THEN json_extract({column}, '{path}') END)")
+}
+
+fn append_extra_param_filters(
+    sql: &mut String,
+    params: &mut Vec<Value>,
+    filters: &[ExtraParamFilter],
+) {
+    for filter in filters {
+        let key = filter.key.trim();
+        if key.is_empty() || key.contains('"') {
+            // Such keys cannot be addressed by a JSON path, so nothing matches.
+            sql.push_str(" AND 0");
+            continue;
+        }
+        sql.push_str(&format!(" AND {} = ?", extra_param_expr(key)));
+        params.push(Value::Text(filter.value.trim().to_string()));
+    }
+}
+
+fn append_embedding_filters(sql: &mut String, params: &mut Vec<Value>, embeddings: &[String]) {
+    for embedding in embeddings {
+        sql.push_str(
+            " AND images.id IN (SELECT image_id FROM image_embeddings_used WHERE name = ?)",
+        );
+        params.push(Value::Text(embedding.trim().to_ascii_lowercase()));
+    }
+}
+
+/// Narrows a filter to rows that also match `refine`, by word prefix or by
+/// substring, so a refinement behaves like the main search box.
+fn append_refinement_filter(sql: &mut String, params: &mut Vec<Value>, refine: Option<&str>) {
+    let Some(refine) = refine.map(str::trim).filter(|value| !value.is_empty()) else {
+        return;
+    };
+    let sanitized = sanitize_fts_query(refine);
+    let substring = contains_search_token(refine);
+    if sanitized.is_empty() && !substring {
+        return;
+    }
+
+    let mut clauses = Vec::new();
+    if !sanitized.is_empty() {
+        clauses.push("images.id IN (SELECT rowid FROM images_fts WHERE images_fts MATCH ?)");
+        params.push(Value::Text(sanitized));
+    }
+    if substring {
+        clauses
+            .push("images.id IN (SELECT rowid FROM images_fts_tri WHERE images_fts_tri MATCH ?)");
+        params.push(Value::Text(format!("\"{}\"", refine.replace('"', "\"\""))));
+    }
+    sql.push_str(&format!(" AND ({})", clauses.join(" OR ")));
+}
+
 /// Sanitizes a user query for FTS5 MATCH syntax with advanced features:
 /// - `"exact phrase"` -> kept as FTS5 phrase query
 /// - `word` -> `word*` (prefix matching)
@@ -752,194 +2396,2741 @@ fn sanitize_fts_query(query: &str) -> String {
             process_unquoted_words(remaining, &mut parts);
             break;
         }
-    }
-
-    parts.join(" ")
-}
-
-fn process_unquoted_words(text: &str, parts: &mut Vec<String>) {
-    for word in text.split_whitespace() {
-        let has_wildcard = word.contains('*');
-        let cleaned: String = word
-            .chars()
-            .filter(|ch| ch.is_alphanumeric() || *ch == '_' || *ch == '*')
-            .collect();
+    }
+
+    parts.join(" ")
+}
+
+fn process_unquoted_words(text: &str, parts: &mut Vec<String>) {
+    for word in text.split_whitespace() {
+        let has_wildcard = word.contains('*');
+        let cleaned: String = word
+            .chars()
+            .filter(|ch| ch.is_alphanumeric() || *ch == '_' || *ch == '*')
+            .collect();
+
+        if cleaned.is_empty() || cleaned == "*" {
+            continue;
+        }
+
+        if has_wildcard {
+            // Preserve explicit wildcard position
+            parts.push(cleaned.to_lowercase());
+        } else {
+            parts.push(format!("{}*", cleaned.to_lowercase()));
+        }
+    }
+}
+
+fn contains_search_token(text: &str) -> bool {
+    text.chars().any(|ch| ch.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert_with_prompt(db: &Database, filepath: &str, prompt: &str, tags: &[&str]) {
+        let params = GenerationParams {
+            prompt: prompt.to_string(),
+            raw_metadata: prompt.to_string(),
+            ..Default::default()
+        };
+
+        let image_id = db
+            .upsert_image(filepath, filepath, "c:\\images", &params, Some(1))
+            .expect("failed to insert image");
+
+        let normalized_tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
+        db.replace_image_tags(image_id, &normalized_tags)
+            .expect("failed to insert tags");
+    }
+
+    #[test]
+    fn test_sanitize_fts_query_builds_safe_prefix_terms() {
+        let query = r#" Model:XL cat++ seed:123 "#;
+        let sanitized = sanitize_fts_query(query);
+        assert_eq!(sanitized, "modelxl* cat* seed123*");
+    }
+
+    #[test]
+    fn test_sanitize_fts_query_all_special_chars_is_empty() {
+        let query = r#" ::: ??? +++  "#;
+        let sanitized = sanitize_fts_query(query);
+        assert!(sanitized.is_empty());
+    }
+
+    #[test]
+    fn test_sanitize_fts_query_quoted_phrase() {
+        let query = r#""best quality" cat"#;
+        let sanitized = sanitize_fts_query(query);
+        assert_eq!(sanitized, r#""best quality" cat*"#);
+    }
+
+    #[test]
+    fn test_sanitize_fts_query_wildcard_preserved() {
+        let query = "cat* dog";
+        let sanitized = sanitize_fts_query(query);
+        assert_eq!(sanitized, "cat* dog*");
+    }
+
+    #[test]
+    fn test_bulk_field_edit_reindexes_and_undoes() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat", &[]);
+        insert_with_prompt(&db, "b.png", "dog", &[]);
+        let ids: Vec<i64> = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|item| item.id)
+            .collect();
+        let search = |query: &str| {
+            db.search_cursor(SearchCursorParams {
+                query,
+                options: CursorQueryOptions {
+                    limit: 10,
+                    ..Default::default()
+                },
+            })
+            .unwrap()
+            .items
+            .len()
+        };
+
+        let update = FieldUpdate {
+            model_name: Some("dreamshaperSDXL_v2".to_string()),
+            seed: Some("42".to_string()),
+            notes: Some("fixed model".to_string()),
+            ..Default::default()
+        };
+        let previous_notes = HashMap::from([(ids[0], Some("old note".to_string()))]);
+        let edit = db
+            .bulk_update_fields(&ids, &update, &previous_notes)
+            .unwrap();
+        assert_eq!(edit.image_count, 2);
+        assert_eq!(edit.fields, ["model_name", "seed", "notes"]);
+        assert_eq!(search("dreamshapersdxl"), 2);
+        let records = db.get_images_by_ids(&ids).unwrap();
+        assert!(records
+            .iter()
+            .all(|record| record.seed.as_deref() == Some("42")));
+        let family: String = db
+            .pool
+            .get()
+            .unwrap()
+            .query_row(
+                "SELECT model_family FROM images WHERE id = ?1",
+                [ids[0]],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(family, "sdxl");
+
+        let FieldEditUndo::Restored(rows) = db.undo_field_edit(edit.id, &HashMap::new()).unwrap()
+        else {
+            panic!("edit should undo");
+        };
+        assert_eq!(rows.len(), 2);
+        let restored_note = rows
+            .iter()
+            .find(|row| row.image_id == ids[0])
+            .and_then(|row| row.previous.get("notes").cloned().flatten());
+        assert_eq!(restored_note.as_deref(), Some("old note"));
+        assert_eq!(search("dreamshapersdxl"), 0);
+        assert!(db
+            .get_images_by_ids(&ids)
+            .unwrap()
+            .iter()
+            .all(|record| record.seed.is_none()));
+        assert!(matches!(
+            db.undo_field_edit(edit.id, &HashMap::new()).unwrap(),
+            FieldEditUndo::Missing
+        ));
+        assert!(db.get_field_edits(10).unwrap()[0].undone);
+    }
+
+    #[test]
+    fn test_field_edit_undo_refuses_to_clobber_later_edits() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat", &[]);
+        let id = db.get_image_id_by_filepath("a.png").unwrap().unwrap();
+        let seed = |value: &str| FieldUpdate {
+            seed: Some(value.to_string()),
+            notes: Some(format!("seed {value}")),
+            ..Default::default()
+        };
+        let no_notes = HashMap::new();
+        let first = db.bulk_update_fields(&[id], &seed("1"), &no_notes).unwrap();
+        let second = db
+            .bulk_update_fields(
+                &[id],
+                &seed("2"),
+                &HashMap::from([(id, Some("seed 1".to_string()))]),
+            )
+            .unwrap();
+        let current_seed = || db.get_images_by_ids(&[id]).unwrap()[0].seed.clone();
+
+        let conflict = db.undo_field_edit(first.id, &no_notes).unwrap();
+        assert!(matches!(conflict, FieldEditUndo::Conflict(ids) if ids == [id]));
+        assert_eq!(current_seed().as_deref(), Some("2"));
+        assert!(!db.get_field_edit(first.id).unwrap().unwrap().undone);
+
+        // Notes changed by hand since the edit also count.
+        let edited_notes = HashMap::from([(id, Some("rewritten".to_string()))]);
+        assert!(matches!(
+            db.undo_field_edit(second.id, &edited_notes).unwrap(),
+            FieldEditUndo::Conflict(_)
+        ));
+
+        let current_notes = HashMap::from([(id, Some("seed 2".to_string()))]);
+        assert!(matches!(
+            db.undo_field_edit(second.id, &current_notes).unwrap(),
+            FieldEditUndo::Restored(_)
+        ));
+        assert_eq!(current_seed().as_deref(), Some("1"));
+        let current_notes = HashMap::from([(id, Some("seed 1".to_string()))]);
+        assert!(matches!(
+            db.undo_field_edit(first.id, &current_notes).unwrap(),
+            FieldEditUndo::Restored(_)
+        ));
+        assert_eq!(current_seed(), None);
+    }
+
+    #[test]
+    fn test_user_properties_are_searchable() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat", &[]);
+        insert_with_prompt(&db, "b.png", "dog", &[]);
+        let ids: Vec<i64> = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|item| item.id)
+            .collect();
+        let search = |query: &str| {
+            db.search_cursor(SearchCursorParams {
+                query,
+                options: CursorQueryOptions {
+                    limit: 10,
+                    ..Default::default()
+                },
+            })
+            .unwrap()
+            .items
+            .len()
+        };
+
+        assert_eq!(
+            db.set_user_property(&ids, "Client", Some("Acme")).unwrap(),
+            2
+        );
+        assert_eq!(
+            db.set_user_property(&ids, "client", Some("Acme")).unwrap(),
+            2
+        );
+        assert_eq!(
+            db.set_user_property(&ids, "client", Some("Acme")).unwrap(),
+            0
+        );
+        assert_eq!(
+            db.set_user_property(&ids[..1], "status", Some("uploaded"))
+                .unwrap(),
+            1
+        );
+        assert_eq!(search("acme"), 2);
+        assert_eq!(search("uploaded"), 1);
+        assert_eq!(
+            db.get_user_properties(ids[0]).unwrap(),
+            vec![
+                UserProperty {
+                    key: "client".to_string(),
+                    value: "Acme".to_string(),
+                },
+                UserProperty {
+                    key: "status".to_string(),
+                    value: "uploaded".to_string(),
+                },
+            ]
+        );
+        let keys = db.list_user_property_keys(10).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].key, "client");
+        assert_eq!(keys[0].count, 2);
+        assert_eq!(keys[0].values[0].value, "Acme");
+
+        assert_eq!(db.set_user_property(&ids, "CLIENT", None).unwrap(), 2);
+        assert_eq!(search("acme"), 0);
+        assert_eq!(search("uploaded"), 1);
+        assert_eq!(db.get_user_properties(ids[1]).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_search_uses_prefix_query_and_returns_expected_best_match() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat hero portrait", &["cat", "hero"]);
+        insert_with_prompt(&db, "b.png", "cat landscape", &["cat", "landscape"]);
+
+        let page = db
+            .search_cursor(SearchCursorParams {
+                query: "cat hero",
+                options: CursorQueryOptions {
+                    cursor: None,
+                    limit: 10,
+                    sort_by: None,
+                    generation_types: None,
+                    model_filter: None,
+                    model_family_filters: None,
+                    scheduler_filters: None,
+                    resolution_buckets: None,
+                    source_tools: None,
+                    workflow_statuses: None,
+                    prompt_clusters: None,
+                    collections: None,
+                    max_sharpness: None,
+                    failed_renders_only: false,
+                    include_hidden: false,
+                    collapse_duplicates: false,
+                },
+            })
+            .expect("search failed");
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].filepath, "a.png");
+    }
+
+    #[test]
+    fn test_search_finds_comfy_workflow_node_titles() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat portrait", &[]);
+        let raw =
+            r#"{"5": {"class_type": "FaceDetailerPipe", "inputs": {"text": "cat portrait"}}}"#;
+        db.bulk_upsert_with_tags(&[BulkRecord {
+            filepath: "comfy.png".to_string(),
+            filename: "comfy.png".to_string(),
+            directory: "c:\\images".to_string(),
+            params: crate::parser::parse_generation_metadata(raw),
+            file_mtime: Some(1),
+            file_size: Some(10),
+            quick_hash: None,
+            tags: Vec::new(),
+            workflow: Some(
+                r#"{"nodes": [{"type": "FaceDetailerPipe", "title": "Moonlit Refiner"}]}"#
+                    .to_string(),
+            ),
+            rating: None,
+        }])
+        .unwrap();
+
+        let search = |query: &str| {
+            db.search_cursor(SearchCursorParams {
+                query,
+                options: CursorQueryOptions {
+                    cursor: None,
+                    limit: 10,
+                    sort_by: None,
+                    generation_types: None,
+                    model_filter: None,
+                    model_family_filters: None,
+                    scheduler_filters: None,
+                    resolution_buckets: None,
+                    source_tools: None,
+                    workflow_statuses: None,
+                    prompt_clusters: None,
+                    collections: None,
+                    max_sharpness: None,
+                    failed_renders_only: false,
+                    include_hidden: false,
+                    collapse_duplicates: false,
+                },
+            })
+            .expect("search failed")
+            .items
+            .into_iter()
+            .map(|item| item.filepath)
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(search("moonlit refiner"), ["comfy.png"]);
+        assert_eq!(search("FaceDetailer"), ["comfy.png"]);
+    }
+
+    #[test]
+    fn test_lora_usage_and_weight_range_listing() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat <lora:Ink:0.4>", &[]);
+        insert_with_prompt(&db, "b.png", "dog <lora:ink:0.9> <lora:detail>", &[]);
+        insert_with_prompt(&db, "c.png", "bird", &[]);
+
+        let usage = db.get_lora_usage().unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].name, "ink");
+        assert_eq!(usage[0].count, 2);
+        assert!((usage[0].avg_weight - 0.65).abs() < 1e-9);
+        assert_eq!((usage[0].min_weight, usage[0].max_weight), (0.4, 0.9));
+        assert_eq!(usage[1].name, "detail");
+        assert_eq!(usage[1].max_weight, 1.0);
+
+        let listing = |min_weight: Option<f64>, max_weight: Option<f64>| {
+            db.get_lora_images_cursor(LoraCursorParams {
+                lora: "INK",
+                min_weight,
+                max_weight,
+                options: CursorQueryOptions {
+                    cursor: None,
+                    limit: 10,
+                    sort_by: None,
+                    generation_types: None,
+                    model_filter: None,
+                    model_family_filters: None,
+                    scheduler_filters: None,
+                    resolution_buckets: None,
+                    source_tools: None,
+                    workflow_statuses: None,
+                    prompt_clusters: None,
+                    collections: None,
+                    max_sharpness: None,
+                    failed_renders_only: false,
+                    include_hidden: false,
+                    collapse_duplicates: false,
+                },
+            })
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|item| item.filepath)
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(listing(None, None).len(), 2);
+        assert_eq!(listing(Some(0.5), None), ["b.png"]);
+        assert_eq!(listing(None, Some(0.5)), ["a.png"]);
+
+        // Re-indexing a changed prompt replaces its LoRAs.
+        insert_with_prompt(&db, "b.png", "dog", &[]);
+        assert_eq!(db.get_lora_usage().unwrap()[0].count, 1);
+    }
+
+    #[test]
+    fn test_embedding_usage_counts_and_filter() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        let negative = GenerationParams {
+            prompt: "cat".to_string(),
+            negative_prompt: "EasyNegative, blurry".to_string(),
+            extra_params: HashMap::from([(
+                "TI hashes".to_string(),
+                "\"EasyNegative: c74b4e810b03\"".to_string(),
+            )]),
+            ..Default::default()
+        };
+        db.upsert_image("a.png", "a.png", "c:\\images", &negative, Some(1))
+            .unwrap();
+        insert_with_prompt(&db, "b.png", "dog, embedding:EasyNegative", &[]);
+        insert_with_prompt(&db, "c.png", "bird (embedding:style_x:1.2)", &[]);
+
+        let usage = db.get_embedding_usage().unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].name, "easynegative");
+        assert_eq!(
+            (
+                usage[0].count,
+                usage[0].positive_count,
+                usage[0].negative_count
+            ),
+            (2, 1, 1)
+        );
+        assert_eq!(usage[1].name, "style_x");
+
+        let embeddings = vec!["EasyNegative".to_string()];
+        let page = db
+            .filter_images_cursor(FilterCursorParams {
+                query: None,
+                include_tags: &[],
+                exclude_tags: &[],
+                refine: None,
+                extra_params: &[],
+                embeddings: &embeddings,
+                options: CursorQueryOptions {
+                    cursor: None,
+                    limit: 10,
+                    sort_by: None,
+                    generation_types: None,
+                    model_filter: None,
+                    model_family_filters: None,
+                    scheduler_filters: None,
+                    resolution_buckets: None,
+                    source_tools: None,
+                    workflow_statuses: None,
+                    prompt_clusters: None,
+                    collections: None,
+                    max_sharpness: None,
+                    failed_renders_only: false,
+                    include_hidden: false,
+                    collapse_duplicates: false,
+                },
+            })
+            .unwrap();
+        let mut filepaths: Vec<String> = page.items.into_iter().map(|item| item.filepath).collect();
+        filepaths.sort();
+        assert_eq!(filepaths, ["a.png", "b.png"]);
+    }
+
+    #[test]
+    fn test_filter_images_by_include_and_exclude_tags() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat hero portrait", &["cat", "hero"]);
+        insert_with_prompt(&db, "b.png", "cat landscape", &["cat", "landscape"]);
+
+        let include = vec!["cat".to_string()];
+        let exclude = vec!["landscape".to_string()];
+        let page = db
+            .filter_images_cursor(FilterCursorParams {
+                query: None,
+                include_tags: &include,
+                exclude_tags: &exclude,
+                refine: None,
+                extra_params: &[],
+                embeddings: &[],
+                options: CursorQueryOptions {
+                    cursor: None,
+                    limit: 10,
+                    sort_by: None,
+                    generation_types: None,
+                    model_filter: None,
+                    model_family_filters: None,
+                    scheduler_filters: None,
+                    resolution_buckets: None,
+                    source_tools: None,
+                    workflow_statuses: None,
+                    prompt_clusters: None,
+                    collections: None,
+                    max_sharpness: None,
+                    failed_renders_only: false,
+                    include_hidden: false,
+                    collapse_duplicates: false,
+                },
+            })
+            .expect("filter failed");
+
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].filepath, "a.png");
+    }
+
+    #[test]
+    fn test_cursor_pagination_returns_correct_pages() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "first", &[]);
+        insert_with_prompt(&db, "b.png", "second", &[]);
+        insert_with_prompt(&db, "c.png", "third", &[]);
+
+        let page1 = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 2,
+                ..Default::default()
+            })
+            .expect("cursor query failed");
+        assert_eq!(page1.items.len(), 2);
+        assert!(page1.next_cursor.is_some());
+
+        let page2 = db
+            .get_images_cursor(CursorQueryOptions {
+                cursor: page1.next_cursor.as_deref(),
+                limit: 2,
+                ..Default::default()
+            })
+            .expect("cursor query failed");
+        assert_eq!(page2.items.len(), 1);
+    }
+
+    #[test]
+    fn test_keyword_trends_bucket_by_month_and_week() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        // 2024-01-10, 2024-01-20, 2024-03-05 (UTC)
+        for (name, prompt, mtime) in [
+            ("a.png", "portrait, Cinematic Lighting", 1_704_844_800),
+            ("b.png", "landscape", 1_705_708_800),
+            ("c.png", "cinematic lighting, city", 1_709_596_800),
+        ] {
+            let params = GenerationParams {
+                prompt: prompt.to_string(),
+                ..Default::default()
+            };
+            db.upsert_image(name, name, "c:\\images", &params, Some(mtime))
+                .expect("failed to insert image");
+        }
+
+        let terms = vec!["cinematic lighting".to_string(), "  ".to_string()];
+        let monthly = db
+            .get_keyword_trends(&terms, TrendBucket::Month)
+            .expect("trend query failed");
+        assert_eq!(monthly.periods, vec!["2024-01-01", "2024-03-01"]);
+        assert_eq!(monthly.totals, vec![2, 1]);
+        assert_eq!(monthly.series.len(), 1);
+        assert_eq!(monthly.series[0].counts, vec![1, 1]);
+
+        let weekly = db
+            .get_keyword_trends(&terms, TrendBucket::Week)
+            .expect("trend query failed");
+        // Weeks start on Monday.
+        assert_eq!(
+            weekly.periods,
+            vec!["2024-01-08", "2024-01-15", "2024-03-04"]
+        );
+        assert_eq!(weekly.series[0].counts, vec![1, 0, 1]);
+    }
+
+    #[test]
+    fn test_model_usage_counts_and_averages_per_model() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        // January: two on sdxl; March: one on sdxl, one on flux.
+        for (name, model, steps, mtime) in [
+            ("a.png", "sdxl", 20, 1_704_844_800),
+            ("b.png", "sdxl", 30, 1_705_708_800),
+            ("c.png", "sdxl", 40, 1_709_596_800),
+            ("d.png", "flux", 8, 1_709_596_800),
+        ] {
+            let params = GenerationParams {
+                model_name: Some(model.to_string()),
+                steps: Some(steps.to_string()),
+                ..Default::default()
+            };
+            db.upsert_image(name, name, "c:\\images", &params, Some(mtime))
+                .expect("failed to insert image");
+        }
+
+        let usage = db
+            .get_model_usage(TrendBucket::Month)
+            .expect("model usage query failed");
+        assert_eq!(usage.periods, vec!["2024-01-01", "2024-03-01"]);
+        let names: Vec<&str> = usage.models.iter().map(|m| m.model_name.as_str()).collect();
+        assert_eq!(names, vec!["sdxl", "flux"]);
+        assert_eq!(usage.models[0].counts, vec![2, 1]);
+        assert_eq!(usage.models[0].avg_steps, Some(30.0));
+        assert_eq!(usage.models[0].first_used.as_deref(), Some("2024-01-10"));
+        assert_eq!(usage.models[1].counts, vec![0, 1]);
+        assert_eq!(usage.models[1].last_used.as_deref(), Some("2024-03-05"));
+    }
+
+    #[test]
+    fn test_disk_usage_report_groups_by_directory_model_and_type() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        let records = [
+            (
+                "/out/txt2img-images/a.png",
+                "/out/txt2img-images",
+                "sdxl",
+                100,
+            ),
+            (
+                "/out/txt2img-images/b.png",
+                "/out/txt2img-images",
+                "sdxl",
+                300,
+            ),
+            (
+                "/out/txt2img-grids/grid-0001.png",
+                "/out/txt2img-grids",
+                "sdxl",
+                5_000,
+            ),
+            (
+                "/out/img2img-images/c.png",
+                "/out/img2img-images",
+                "flux",
+                200,
+            ),
+        ];
+        let bulk: Vec<BulkRecord> = records
+            .iter()
+            .map(|(filepath, directory, model, size)| BulkRecord {
+                filepath: filepath.to_string(),
+                filename: filepath.rsplit('/').next().unwrap().to_string(),
+                directory: directory.to_string(),
+                params: GenerationParams {
+                    model_name: Some(model.to_string()),
+                    ..Default::default()
+                },
+                file_mtime: Some(1),
+                file_size: Some(*size),
+                quick_hash: None,
+                tags: Vec::new(),
+                workflow: None,
+                rating: None,
+            })
+            .collect();
+        db.bulk_upsert_with_tags(&bulk).expect("bulk insert failed");
+
+        let report = db.get_disk_usage_report(10).expect("disk usage failed");
+        assert_eq!(report.total_bytes, 5_600);
+        assert_eq!(report.total_images, 4);
+        assert_eq!(report.unsized_images, 0);
+        assert_eq!(report.by_directory[0].key, "/out/txt2img-grids");
+        assert_eq!(report.by_directory[1].bytes, 400);
+        assert_eq!(report.by_model[0].key, "sdxl");
+        assert_eq!(report.by_model[0].count, 3);
+        assert_eq!(report.largest_grids.len(), 1);
+        assert_eq!(report.largest_grids[0].bytes, 5_000);
+    }
+
+    #[test]
+    fn test_private_directories_are_hidden_until_cleared() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        insert_with_prompt(&db, "c:\\images\\public.png", "castle", &[]);
+        insert_with_prompt(&db, "c:\\images\\secret\\a.png", "castle", &[]);
+        insert_with_prompt(&db, "c:\\images\\secret2\\b.png", "castle", &[]);
+        let all = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .expect("cursor query failed");
+        let ids: Vec<i64> = all.items.iter().map(|record| record.id).collect();
+        assert_eq!(ids.len(), 3);
+
+        db.set_private_directories(&["c:/images/secret/".to_string()]);
+        let page = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .expect("cursor query failed");
+        let visible: Vec<&str> = page.items.iter().map(|r| r.filepath.as_str()).collect();
+        assert_eq!(visible.len(), 2);
+        assert!(!visible.contains(&"c:\\images\\secret\\a.png"));
+        assert_eq!(db.get_images_by_ids(&ids).unwrap().len(), 2);
+        assert_eq!(db.get_total_count().unwrap(), 2);
+        assert!(db.is_private_path("c:\\images\\secret\\a.png"));
+        assert!(!db.is_private_path("c:\\images\\secret2\\b.png"));
+
+        db.set_private_directories(&[]);
+        assert_eq!(db.get_images_by_ids(&ids).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_private_directories_leave_tag_and_model_lists() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        for (filepath, model, tag) in [
+            ("c:\\images\\public.png", "publicModel", "castle"),
+            ("c:\\images\\secret\\a.png", "secretModel", "hidden tag"),
+        ] {
+            let params = GenerationParams {
+                prompt: tag.to_string(),
+                model_name: Some(model.to_string()),
+                ..Default::default()
+            };
+            let id = db
+                .upsert_image(filepath, filepath, "c:\\images", &params, Some(1))
+                .unwrap();
+            db.replace_image_tags(id, &[tag.to_string(), "shared".to_string()])
+                .unwrap();
+        }
+        let tags = |db: &Database| -> Vec<(String, u32)> {
+            db.get_top_tags(10)
+                .unwrap()
+                .into_iter()
+                .map(|tag| (tag.tag, tag.count))
+                .collect()
+        };
+        let models = |db: &Database| -> Vec<String> {
+            db.get_unique_models()
+                .unwrap()
+                .into_iter()
+                .map(|model| model.model_name)
+                .collect()
+        };
+        assert_eq!(tags(&db).len(), 3);
+        assert_eq!(models(&db).len(), 2);
+
+        db.set_private_directories(&["c:/images/secret/".to_string()]);
+        assert_eq!(
+            tags(&db),
+            [("castle".to_string(), 1), ("shared".to_string(), 1)]
+        );
+        assert_eq!(models(&db), ["publicModel"]);
+    }
+
+    #[test]
+    fn test_project_scope_limits_gallery_and_stats() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        insert_with_prompt(&db, "c:\\clients\\acme\\a.png", "castle", &[]);
+        insert_with_prompt(&db, "c:\\clients\\acme2\\b.png", "castle", &[]);
+        insert_with_prompt(&db, "d:/clients/globex/c.png", "castle", &[]);
+        let visible = |db: &Database| {
+            let mut paths: Vec<String> = db
+                .get_images_cursor(CursorQueryOptions {
+                    limit: 10,
+                    ..Default::default()
+                })
+                .expect("cursor query failed")
+                .items
+                .into_iter()
+                .map(|record| record.filepath)
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        db.set_project_directories(&[
+            "c:/clients/acme".to_string(),
+            "d:\\clients\\globex\\".to_string(),
+        ]);
+        assert_eq!(
+            visible(&db),
+            ["c:\\clients\\acme\\a.png", "d:/clients/globex/c.png"]
+        );
+        assert_eq!(db.get_total_count().unwrap(), 2);
+        let ids: Vec<i64> = db
+            .get_all_image_locations()
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(db.get_images_by_ids(&ids).unwrap().len(), 3);
+
+        db.set_project_directories(&[]);
+        assert_eq!(visible(&db).len(), 3);
+        assert_eq!(db.get_total_count().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_offline_roots_flag_gallery_rows() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        insert_with_prompt(&db, "e:\\usb\\a.png", "castle", &[]);
+        insert_with_prompt(&db, "c:\\images\\b.png", "castle", &[]);
+
+        db.set_offline_roots(&["e:\\usb\\".to_string()]);
+        let mut page = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .expect("cursor query failed");
+        assert_eq!(page.items.len(), 2);
+        db.mark_offline(&mut page.items);
+        for item in &page.items {
+            assert_eq!(item.offline, item.filepath.starts_with("e:"));
+        }
+        assert!(!db.is_offline_path("e:\\usb2\\c.png"));
+
+        db.set_offline_roots(&[]);
+        db.mark_offline(&mut page.items);
+        assert!(page.items.iter().all(|item| !item.offline));
+    }
+
+    #[test]
+    fn test_nsfw_filter_hides_scored_images_from_cursor_and_thumbnails() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        insert_with_prompt(&db, "c:\\images\\safe.png", "castle, sunset", &[]);
+        insert_with_prompt(&db, "c:\\images\\nsfw.png", "nsfw, 1girl", &[]);
+        let filepaths = vec![
+            "c:\\images\\safe.png".to_string(),
+            "c:\\images\\nsfw.png".to_string(),
+        ];
+
+        let page = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .expect("cursor query failed");
+        assert_eq!(page.items.len(), 2);
+        assert!(db.nsfw_hidden_filepaths(&filepaths).unwrap().is_empty());
+
+        db.set_nsfw_filter(Some(0.5));
+        let page = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .expect("cursor query failed");
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].filepath, "c:\\images\\safe.png");
+        let hidden = db.nsfw_hidden_filepaths(&filepaths).unwrap();
+        assert!(hidden.contains("c:\\images\\nsfw.png"));
+        assert_eq!(hidden.len(), 1);
+
+        // A tagger rating can hide an image whose prompt looked harmless.
+        let safe_id = page.items[0].id;
+        db.raise_nsfw_scores(&[(safe_id, 0.8)]).unwrap();
+        let page = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .expect("cursor query failed");
+        assert!(page.items.is_empty());
+    }
+
+    #[test]
+    fn test_trigram_search_finds_substring() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "concatenation is fun", &[]);
+        insert_with_prompt(&db, "b.png", "hello world", &[]);
+
+        let page = db
+            .search_cursor(SearchCursorParams {
+                query: "cat",
+                options: CursorQueryOptions {
+                    cursor: None,
+                    limit: 10,
+                    sort_by: None,
+                    generation_types: None,
+                    model_filter: None,
+                    model_family_filters: None,
+                    scheduler_filters: None,
+                    resolution_buckets: None,
+                    source_tools: None,
+                    workflow_statuses: None,
+                    prompt_clusters: None,
+                    collections: None,
+                    max_sharpness: None,
+                    failed_renders_only: false,
+                    include_hidden: false,
+                    collapse_duplicates: false,
+                },
+            })
+            .expect("trigram search failed");
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].filepath, "a.png");
+    }
+
+    #[test]
+    fn test_filter_images_falls_back_to_trigram_for_substring_queries() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "donald trump portrait", &["portrait"]);
+        insert_with_prompt(&db, "b.png", "landscape painting", &["landscape"]);
+
+        let include = vec!["portrait".to_string()];
+        let page = db
+            .filter_images_cursor(FilterCursorParams {
+                query: Some("ump"),
+                include_tags: &include,
+                exclude_tags: &[],
+                refine: None,
+                extra_params: &[],
+                embeddings: &[],
+                options: CursorQueryOptions {
+                    cursor: None,
+                    limit: 10,
+                    sort_by: None,
+                    generation_types: None,
+                    model_filter: None,
+                    model_family_filters: None,
+                    scheduler_filters: None,
+                    resolution_buckets: None,
+                    source_tools: None,
+                    workflow_statuses: None,
+                    prompt_clusters: None,
+                    collections: None,
+                    max_sharpness: None,
+                    failed_renders_only: false,
+                    include_hidden: false,
+                    collapse_duplicates: false,
+                },
+            })
+            .expect("filter failed");
+
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].filepath, "a.png");
+    }
+
+    #[test]
+    fn test_collapse_duplicates_keeps_newest_of_each_prompt_group() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for index in 0..3 {
+            insert_with_prompt(&db, &format!("batch_{index}.png"), "cat knight", &["cat"]);
+        }
+        insert_with_prompt(&db, "other.png", "dog knight", &["dog"]);
+        insert_with_prompt(&db, "blank_1.png", "", &[]);
+        insert_with_prompt(&db, "blank_2.png", "", &[]);
+
+        let options = CursorQueryOptions {
+            limit: 2,
+            collapse_duplicates: true,
+            ..Default::default()
+        };
+        let page1 = db.get_images_cursor(options).expect("cursor failed");
+        let page2 = db
+            .get_images_cursor(CursorQueryOptions {
+                cursor: page1.next_cursor.as_deref(),
+                ..options
+            })
+            .expect("cursor failed");
+        let rows: Vec<(String, Option<u32>)> = page1
+            .items
+            .into_iter()
+            .chain(page2.items)
+            .map(|item| (item.filepath, item.duplicate_count))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("blank_2.png".to_string(), Some(1)),
+                ("blank_1.png".to_string(), Some(1)),
+                ("other.png".to_string(), Some(1)),
+                ("batch_2.png".to_string(), Some(3)),
+            ]
+        );
+
+        let page = db
+            .filter_images_cursor(FilterCursorParams {
+                query: Some("knight"),
+                include_tags: &[],
+                exclude_tags: &[],
+                refine: None,
+                extra_params: &[],
+                embeddings: &[],
+                options: CursorQueryOptions {
+                    limit: 10,
+                    sort_by: Some("name_asc"),
+                    collapse_duplicates: true,
+                    ..Default::default()
+                },
+            })
+            .expect("filter failed");
+        let names: Vec<&str> = page
+            .items
+            .iter()
+            .map(|item| item.filename.as_str())
+            .collect();
+        assert_eq!(names, vec!["batch_2.png", "other.png"]);
+
+        let page = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .expect("cursor failed");
+        assert_eq!(page.items.len(), 6);
+        assert!(page.items.iter().all(|item| item.duplicate_count.is_none()));
+    }
+
+    #[test]
+    fn test_hue_and_brightness_sorts_page_through_colour_stats() {
+        use crate::color_stats::ColorStats;
+
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for name in ["blue", "red_dark", "red_light", "grey", "unknown"] {
+            insert_with_prompt(&db, &format!("{name}.png"), name, &[]);
+        }
+        let stats = |hue: Option<f32>, brightness: f32| ColorStats {
+            hue,
+            saturation: if hue.is_some() { 0.5 } else { 0.0 },
+            brightness,
+        };
+        let updated = db
+            .set_color_stats(&[
+                ("blue.png".to_string(), stats(Some(228.0), 0.3)),
+                ("red_dark.png".to_string(), stats(Some(355.0), 0.2)),
+                ("red_light.png".to_string(), stats(Some(2.0), 0.7)),
+                ("grey.png".to_string(), stats(None, 0.9)),
+                ("not_indexed.png".to_string(), stats(None, 0.5)),
+            ])
+            .expect("set colour stats failed");
+        assert_eq!(updated, 4);
+        assert_eq!(
+            db.filepaths_missing_color_stats().unwrap(),
+            HashSet::from(["unknown.png".to_string()])
+        );
+
+        let sorted = |sort_by: &str| -> Vec<String> {
+            let mut names = Vec::new();
+            let mut cursor = None::<String>;
+            loop {
+                let page = db
+                    .get_images_cursor(CursorQueryOptions {
+                        cursor: cursor.as_deref(),
+                        limit: 2,
+                        sort_by: Some(sort_by),
+                        ..Default::default()
+                    })
+                    .expect("cursor failed");
+                names.extend(page.items.into_iter().map(|item| item.filename));
+                cursor = page.next_cursor;
+                if cursor.is_none() {
+                    return names;
+                }
+            }
+        };
+        // 355 and 2 degrees share the red band, which wraps around zero.
+        assert_eq!(
+            sorted("hue"),
+            vec![
+                "red_dark.png",
+                "red_light.png",
+                "blue.png",
+                "grey.png",
+                "unknown.png"
+            ]
+        );
+        assert_eq!(
+            sorted("brightness_desc"),
+            vec![
+                "grey.png",
+                "red_light.png",
+                "blue.png",
+                "red_dark.png",
+                "unknown.png"
+            ]
+        );
+        assert_eq!(
+            sorted("brightness_asc"),
+            vec![
+                "red_dark.png",
+                "blue.png",
+                "red_light.png",
+                "grey.png",
+                "unknown.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_metadata_filter_and_coverage() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        let complete = GenerationParams {
+            prompt: "cat".to_string(),
+            seed: Some("42".to_string()),
+            model_name: Some("sdxl".to_string()),
+            raw_metadata: "cat, Seed: 42".to_string(),
+            ..Default::default()
+        };
+        db.upsert_image("/a/complete.png", "complete.png", "/a", &complete, Some(1))
+            .unwrap();
+        let no_seed = GenerationParams {
+            seed: None,
+            ..complete.clone()
+        };
+        db.upsert_image("/a/no_seed.png", "no_seed.png", "/a", &no_seed, Some(1))
+            .unwrap();
+        db.upsert_image(
+            "/b/bare.png",
+            "bare.png",
+            "/b",
+            &GenerationParams::default(),
+            Some(1),
+        )
+        .unwrap();
+
+        let types = ["missing_metadata".to_string()];
+        let page = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                generation_types: Some(&types),
+                ..Default::default()
+            })
+            .expect("cursor failed");
+        let mut names: Vec<String> = page.items.into_iter().map(|item| item.filename).collect();
+        names.sort();
+        assert_eq!(names, vec!["bare.png", "no_seed.png"]);
+
+        let coverage = db.get_metadata_coverage(10).expect("coverage failed");
+        assert_eq!(coverage.total_images, 3);
+        assert_eq!(coverage.with_prompt, 2);
+        assert_eq!(coverage.with_model, 2);
+        assert_eq!(coverage.with_seed, 1);
+        assert_eq!(coverage.complete, 1);
+        assert_eq!(coverage.without_metadata, 1);
+        let gaps: Vec<(String, u32, u32)> = coverage
+            .incomplete_by_directory
+            .into_iter()
+            .map(|gap| (gap.directory, gap.incomplete, gap.total))
+            .collect();
+        assert_eq!(
+            gaps,
+            vec![("/a".to_string(), 1, 2), ("/b".to_string(), 1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_extra_param_filters_and_key_facet() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for (name, vae, clip_skip) in [
+            ("a.png", "sdxl_vae.safetensors", Some("2")),
+            ("b.png", "sdxl_vae.safetensors", None),
+            ("c.png", "ae.safetensors", Some("2")),
+        ] {
+            let mut params = GenerationParams {
+                prompt: "cat".to_string(),
+                ..Default::default()
+            };
+            params
+                .extra_params
+                .insert("VAE".to_string(), vae.to_string());
+            if let Some(clip_skip) = clip_skip {
+                params
+                    .extra_params
+                    .insert("Clip skip".to_string(), clip_skip.to_string());
+            }
+            db.upsert_image(&format!("/img/{name}"), name, "/img", &params, Some(1))
+                .unwrap();
+        }
+
+        let filter = |pairs: &[(&str, &str)]| {
+            let extra_params: Vec<ExtraParamFilter> = pairs
+                .iter()
+                .map(|(key, value)| ExtraParamFilter {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+                .collect();
+            let page = db
+                .filter_images_cursor(FilterCursorParams {
+                    query: None,
+                    include_tags: &[],
+                    exclude_tags: &[],
+                    refine: None,
+                    extra_params: &extra_params,
+                    embeddings: &[],
+                    options: CursorQueryOptions {
+                        limit: 10,
+                        ..Default::default()
+                    },
+                })
+                .expect("filter failed");
+            let mut names: Vec<String> = page.items.into_iter().map(|item| item.filename).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            filter(&[("VAE", "sdxl_vae.safetensors")]),
+            vec!["a.png", "b.png"]
+        );
+        assert_eq!(
+            filter(&[("VAE", "sdxl_vae.safetensors"), ("Clip skip", "2")]),
+            vec!["a.png"]
+        );
+        assert!(filter(&[("Bad\"key", "2")]).is_empty());
+        assert!(filter(&[("O'Brien", "2")]).is_empty());
+
+        let keys = db.get_extra_param_keys(10).expect("facet failed");
+        let facet: Vec<(&str, u32)> = keys
+            .iter()
+            .map(|key| (key.key.as_str(), key.count))
+            .collect();
+        assert_eq!(facet, vec![("VAE", 3), ("Clip skip", 2)]);
+        let vae_values: Vec<(&str, u32)> = keys[0]
+            .values
+            .iter()
+            .map(|value| (value.value.as_str(), value.count))
+            .collect();
+        assert_eq!(
+            vae_values,
+            vec![("sdxl_vae.safetensors", 2), ("ae.safetensors", 1)]
+        );
+
+        let conn = db.pool.get().unwrap();
+        let plan: Vec<String> = conn
+            .prepare(&format!(
+                "EXPLAIN QUERY PLAN SELECT id FROM images WHERE {} = 'x'",
+                extra_param_expr("VAE")
+            ))
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(3))
+            .unwrap()
+            .collect::<SqlResult<_>>()
+            .unwrap();
+        assert!(
+            plan.iter()
+                .any(|step| step.contains("idx_images_extra_param_0")),
+            "{plan:?}"
+        );
+    }
+
+    #[test]
+    fn test_source_tool_facet_and_filter() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat\nSteps: 20, Version: f2.0.1v1.10.1", &[]);
+        insert_with_prompt(&db, "b.png", "dog\nSteps: 20, Version: f2.0.1v1.10.1", &[]);
+        insert_with_prompt(&db, "c.png", "bird\nSteps: 20, Version: v1.10.1", &[]);
+
+        let tools: Vec<(String, u32)> = db
+            .get_source_tools()
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.source_tool, entry.count))
+            .collect();
+        assert_eq!(tools, [("forge".to_string(), 2), ("a1111".to_string(), 1)]);
+
+        let tools = ["A1111".to_string()];
+        let page = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                source_tools: Some(&tools),
+                ..Default::default()
+            })
+            .expect("cursor failed");
+        let names: Vec<String> = page.items.into_iter().map(|item| item.filepath).collect();
+        assert_eq!(names, vec!["c.png"]);
+    }
+
+    #[test]
+    fn test_workflow_status_survives_rescans_and_filters() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat", &[]);
+        insert_with_prompt(&db, "b.png", "dog", &[]);
+        insert_with_prompt(&db, "c.png", "cat", &[]);
+        let with_status = |status: &str| {
+            let statuses = [status.to_string()];
+            let mut names: Vec<String> = db
+                .get_images_cursor(CursorQueryOptions {
+                    limit: 10,
+                    workflow_statuses: Some(&statuses),
+                    ..Default::default()
+                })
+                .expect("cursor failed")
+                .items
+                .into_iter()
+                .map(|item| item.filepath)
+                .collect();
+            names.sort();
+            names
+        };
+        let id_of = |name: &str| {
+            db.get_all_image_locations()
+                .unwrap()
+                .into_iter()
+                .find(|(_, filepath)| filepath == name)
+                .map(|(id, _)| id)
+                .unwrap()
+        };
+        assert_eq!(with_status("inbox").len(), 3);
+
+        let a = id_of("a.png");
+        assert_eq!(
+            db.set_workflow_status(&[a], WorkflowStatus::Picked)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            db.set_workflow_status(&[a], WorkflowStatus::Picked)
+                .unwrap(),
+            0
+        );
+        insert_with_prompt(&db, "a.png", "cat", &[]);
+        assert_eq!(with_status("Picked"), ["a.png"]);
+        assert_eq!(
+            db.get_image_details(&[a]).unwrap()[0].workflow_status,
+            WorkflowStatus::Picked
+        );
+
+        let inbox = ["inbox".to_string()];
+        let rejected = db
+            .set_workflow_status_by_filter(
+                FilterCursorParams {
+                    query: Some("cat"),
+                    include_tags: &[],
+                    exclude_tags: &[],
+                    refine: None,
+                    extra_params: &[],
+                    embeddings: &[],
+                    options: CursorQueryOptions {
+                        workflow_statuses: Some(&inbox),
+                        ..Default::default()
+                    },
+                },
+                WorkflowStatus::Rejected,
+            )
+            .unwrap();
+        assert_eq!(rejected, 1);
+        assert_eq!(with_status("rejected"), ["c.png"]);
+
+        let counts: Vec<(WorkflowStatus, u32)> = db
+            .get_workflow_status_counts()
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.status, entry.count))
+            .collect();
+        assert_eq!(
+            counts,
+            [
+                (WorkflowStatus::Inbox, 1),
+                (WorkflowStatus::Picked, 1),
+                (WorkflowStatus::Edited, 0),
+                (WorkflowStatus::Published, 0),
+                (WorkflowStatus::Rejected, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sharpness_sorts_and_filters_blurry_images() {
+        use crate::image_quality::ImageQuality;
+
+        let quality = |sharpness: f32| ImageQuality {
+            sharpness,
+            dark_fraction: 0.0,
+            bright_fraction: 0.0,
+        };
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for name in ["crisp.png", "soft.png", "smeared.png", "unmeasured.png"] {
+            insert_with_prompt(&db, name, "portrait", &[]);
+        }
+        let updated = db
+            .set_image_quality(&[
+                ("crisp.png".to_string(), quality(1250.0)),
+                ("soft.png".to_string(), quality(90.5)),
+                ("smeared.png".to_string(), quality(3.25)),
+                ("not-indexed.png".to_string(), quality(1.0)),
+            ])
+            .expect("set image quality failed");
+        assert_eq!(updated, 3);
+        assert_eq!(
+            db.filepaths_missing_image_quality().unwrap(),
+            HashSet::from(["unmeasured.png".to_string()])
+        );
+
+        let listed = |sort_by: &str, max_sharpness: Option<f32>| -> Vec<String> {
+            let mut names = Vec::new();
+            let mut cursor = None::<String>;
+            loop {
+                let page = db
+                    .get_images_cursor(CursorQueryOptions {
+                        cursor: cursor.as_deref(),
+                        limit: 2,
+                        sort_by: Some(sort_by),
+                        max_sharpness,
+                        ..Default::default()
+                    })
+                    .expect("cursor failed");
+                names.extend(page.items.into_iter().map(|item| item.filename));
+                cursor = page.next_cursor;
+                if cursor.is_none() {
+                    return names;
+                }
+            }
+        };
+        assert_eq!(
+            listed("sharpness_asc", None),
+            ["smeared.png", "soft.png", "crisp.png", "unmeasured.png"]
+        );
+        assert_eq!(
+            listed("sharpness_desc", None),
+            ["crisp.png", "soft.png", "smeared.png", "unmeasured.png"]
+        );
+        assert_eq!(
+            listed("sharpness_asc", Some(100.0)),
+            ["smeared.png", "soft.png"]
+        );
+    }
+
+    #[test]
+    fn test_failed_render_filter_finds_black_and_blown_frames() {
+        use crate::image_quality::ImageQuality;
+
+        let exposure = |dark_fraction: f32, bright_fraction: f32| ImageQuality {
+            sharpness: 10.0,
+            dark_fraction,
+            bright_fraction,
+        };
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for name in ["black.png", "blown.png", "night.png", "fine.png"] {
+            insert_with_prompt(&db, name, "landscape", &[]);
+        }
+        db.set_image_quality(&[
+            ("black.png".to_string(), exposure(0.98, 0.0)),
+            ("blown.png".to_string(), exposure(0.0, 0.93)),
+            ("night.png".to_string(), exposure(0.7, 0.02)),
+            ("fine.png".to_string(), exposure(0.01, 0.01)),
+        ])
+        .expect("set image quality failed");
+
+        let mut failed: Vec<String> = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                failed_renders_only: true,
+                ..Default::default()
+            })
+            .expect("cursor failed")
+            .items
+            .into_iter()
+            .map(|item| item.filename)
+            .collect();
+        failed.sort();
+        assert_eq!(failed, ["black.png", "blown.png"]);
+    }
+
+    #[test]
+    fn test_ocr_text_is_searchable_and_survives_rescans() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "meme.png", "cat", &[]);
+        insert_with_prompt(&db, "plain.png", "cat", &[]);
+        let search = |query: &str| -> Vec<String> {
+            db.search_cursor(SearchCursorParams {
+                query,
+                options: CursorQueryOptions {
+                    limit: 10,
+                    ..Default::default()
+                },
+            })
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|item| item.filename)
+            .collect()
+        };
+        let missing = db.images_missing_ocr_text().unwrap();
+        assert_eq!(missing.len(), 2);
+        let id_of = |name: &str| {
+            missing
+                .iter()
+                .find(|(_, filepath)| filepath == name)
+                .map(|(id, _)| *id)
+                .unwrap()
+        };
+        let (meme, plain) = (id_of("meme.png"), id_of("plain.png"));
+
+        db.set_ocr_text(&[
+            (meme, "I CAN HAS\nCHEEZBURGER".to_string()),
+            (plain, String::new()),
+        ])
+        .unwrap();
+        assert!(db.images_missing_ocr_text().unwrap().is_empty());
+        assert_eq!(search("cheezburger"), ["meme.png"]);
+
+        insert_with_prompt(&db, "meme.png", "cat", &[]);
+        assert_eq!(search("cheezburger"), ["meme.png"]);
+        assert_eq!(
+            db.get_image_details(&[meme]).unwrap()[0]
+                .ocr_text
+                .as_deref(),
+            Some("I CAN HAS\nCHEEZBURGER")
+        );
+    }
+
+    #[test]
+    fn test_search_by_color_ranks_close_colours_first() {
+        use crate::color_stats::ColorStats;
+
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for name in ["teal.png", "sea.png", "orange.png", "unknown.png"] {
+            insert_with_prompt(&db, name, "beach", &[]);
+        }
+        db.set_color_stats(&[
+            ("teal.png".to_string(), ColorStats::from_rgb([0, 128, 128])),
+            ("sea.png".to_string(), ColorStats::from_rgb([20, 120, 140])),
+            (
+                "orange.png".to_string(),
+                ColorStats::from_rgb([240, 130, 20]),
+            ),
+        ])
+        .expect("set colour stats failed");
+
+        let names = |tolerance: f32, limit: u32| -> Vec<String> {
+            db.search_by_color(&ColorStats::from_rgb([0, 128, 128]), tolerance, limit)
+                .expect("colour search failed")
+                .into_iter()
+                .map(|found| found.record.filename)
+                .collect()
+        };
+        assert_eq!(names(0.1, 10), ["teal.png", "sea.png"]);
+        assert_eq!(names(1.0, 10), ["teal.png", "sea.png", "orange.png"]);
+        assert_eq!(names(1.0, 1), ["teal.png"]);
+    }
+
+    #[test]
+    fn test_prompt_clusters_replace_and_filter() {
+        use crate::prompt_clusters::ClusterSummary;
+
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "castle_a.png", "castle, snow", &[]);
+        insert_with_prompt(&db, "castle_b.png", "castle, night", &[]);
+        insert_with_prompt(&db, "cat.png", "cat, garden", &[]);
+        let prompts = db.prompts_for_clustering().unwrap();
+        assert_eq!(prompts.len(), 3);
+        let id_of = |name: &str| {
+            db.get_all_image_locations()
+                .unwrap()
+                .into_iter()
+                .find(|(_, filepath)| filepath == name)
+                .map(|(id, _)| id)
+                .unwrap()
+        };
+        let summary = |label: &str| ClusterSummary {
+            label: label.to_string(),
+            terms: label.split(", ").map(str::to_string).collect(),
+        };
+        let in_cluster = |cluster: i64| {
+            let clusters = [cluster];
+            let mut names: Vec<String> = db
+                .get_images_cursor(CursorQueryOptions {
+                    limit: 10,
+                    prompt_clusters: Some(&clusters),
+                    ..Default::default()
+                })
+                .unwrap()
+                .items
+                .into_iter()
+                .map(|item| item.filename)
+                .collect();
+            names.sort();
+            names
+        };
+
+        db.replace_prompt_clusters(
+            &[summary("castle, snow"), summary("cat, garden")],
+            &[
+                (id_of("castle_a.png"), 0),
+                (id_of("castle_b.png"), 0),
+                (id_of("cat.png"), 1),
+            ],
+        )
+        .unwrap();
+        let clusters = db.get_prompt_clusters().unwrap();
+        assert_eq!(
+            clusters
+                .iter()
+                .map(|cluster| (cluster.id, cluster.label.as_str(), cluster.count))
+                .collect::<Vec<_>>(),
+            [(1, "castle, snow", 2), (2, "cat, garden", 1)]
+        );
+        assert_eq!(clusters[1].terms, ["cat", "garden"]);
+        assert_eq!(in_cluster(1), ["castle_a.png", "castle_b.png"]);
+
+        db.replace_prompt_clusters(&[summary("cat")], &[(id_of("cat.png"), 0)])
+            .unwrap();
+        assert_eq!(db.get_prompt_clusters().unwrap().len(), 1);
+        assert_eq!(in_cluster(1), ["cat.png"]);
+        assert!(in_cluster(2).is_empty());
+    }
+
+    #[test]
+    fn test_collection_rules_follow_scans_and_edits() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        let record = |filepath: &str, model: &str, rating: Option<u8>| BulkRecord {
+            filepath: filepath.to_string(),
+            filename: filepath.to_string(),
+            directory: "c:\\images".to_string(),
+            params: GenerationParams {
+                model_name: Some(model.to_string()),
+                ..Default::default()
+            },
+            file_mtime: Some(1),
+            file_size: Some(10),
+            quick_hash: None,
+            tags: Vec::new(),
+            workflow: None,
+            rating,
+        };
+        db.bulk_upsert_with_tags(&[
+            record("keeper.png", "flux1-dev", Some(5)),
+            record("meh.png", "flux1-dev", Some(2)),
+            record("sdxl.png", "sd_xl_base_1.0", Some(5)),
+        ])
+        .unwrap();
+
+        let rule: CollectionRule = serde_json::from_str(
+            r#"{"conditions": [
+                {"field": "model_family", "op": "=", "value": "flux"},
+                {"field": "rating", "op": ">=", "value": 4}
+            ]}"#,
+        )
+        .unwrap();
+        let collection = db
+            .save_collection(None, "Flux keepers", &rule.normalized().unwrap())
+            .unwrap();
+        let members = || {
+            let collections = [collection];
+            let mut names: Vec<String> = db
+                .get_images_cursor(CursorQueryOptions {
+                    limit: 10,
+                    collections: Some(&collections),
+                    ..Default::default()
+                })
+                .unwrap()
+                .items
+                .into_iter()
+                .map(|item| item.filename)
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(members(), ["keeper.png"]);
+
+        // A rescan picking up a new sidecar rating and a new image.
+        db.bulk_upsert_with_tags(&[
+            record("meh.png", "flux1-dev", Some(4)),
+            record("new.png", "flux1-schnell", Some(5)),
+        ])
+        .unwrap();
+        assert_eq!(members(), ["keeper.png", "meh.png", "new.png"]);
+
+        let keeper = db.get_image_id_by_filepath("keeper.png").unwrap().unwrap();
+        db.set_image_rating(keeper, None).unwrap();
+        assert_eq!(members(), ["meh.png", "new.png"]);
+        let collections = db.get_collections().unwrap();
+        assert_eq!(collections.len(), 1);
+        assert_eq!(
+            (collections[0].name.as_str(), collections[0].count),
+            ("Flux keepers", 2)
+        );
+
+        assert!(db.delete_collection(collection).unwrap());
+        assert!(db.get_collections().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_activity_log_finds_events_by_kind_file_and_folder() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        let deleted = vec![
+            "D:\\renders\\sdxl\\a.png".to_string(),
+            "D:\\renders\\sdxl\\b.png".to_string(),
+        ];
+        let delete_id = db
+            .record_activity(
+                ActivityKind::Delete,
+                "Deleted 2 image(s) to the trash",
+                &serde_json::json!({ "mode": "trash" }),
+                &deleted,
+            )
+            .unwrap();
+        db.record_activity(
+            ActivityKind::Settings,
+            "Saved 1 hook(s)",
+            &serde_json::json!({ "setting": "hooks" }),
+            &[],
+        )
+        .unwrap();
+
+        let all = db.get_activity_log(&ActivityFilter::default()).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].kind, ActivityKind::Settings, "newest first");
+        assert_eq!(all[1].file_count, 2);
+        assert_eq!(all[1].detail["mode"], "trash");
+
+        let find = |filter: ActivityFilter| -> Vec<i64> {
+            db.get_activity_log(&filter)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.id)
+                .collect()
+        };
+        assert_eq!(
+            find(ActivityFilter {
+                kinds: vec![ActivityKind::Delete, ActivityKind::Move],
+                ..Default::default()
+            }),
+            [delete_id]
+        );
+        assert_eq!(
+            find(ActivityFilter {
+                filepaths: vec![deleted[1].clone(), "D:/elsewhere.png".to_string()],
+                ..Default::default()
+            }),
+            [delete_id]
+        );
+        assert_eq!(
+            find(ActivityFilter {
+                directory: Some("D:/renders/".to_string()),
+                ..Default::default()
+            }),
+            [delete_id]
+        );
+        assert!(find(ActivityFilter {
+            directory: Some("D:/render".to_string()),
+            ..Default::default()
+        })
+        .is_empty());
+        assert_eq!(
+            find(ActivityFilter {
+                search: Some("HOOK".to_string()),
+                ..Default::default()
+            })
+            .len(),
+            1
+        );
+        assert!(find(ActivityFilter {
+            since: Some(chrono::Utc::now().timestamp() + 60),
+            ..Default::default()
+        })
+        .is_empty());
+
+        assert_eq!(db.get_activity_files(delete_id).unwrap(), deleted);
+        db.set_private_directories(&["D:/renders/sdxl".to_string()]);
+        assert!(db.get_activity_files(delete_id).unwrap().is_empty());
+        assert!(find(ActivityFilter {
+            directory: Some("D:/renders/sdxl".to_string()),
+            ..Default::default()
+        })
+        .is_empty());
+    }
+
+    #[test]
+    fn test_similar_images_are_ranked_by_hash_distance() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for name in ["base", "upscale", "variant", "unrelated", "unhashed"] {
+            insert_with_prompt(&db, &format!("{name}.png"), name, &[]);
+        }
+        // High bit set, so the stored value is negative.
+        let base = 0x8f3c_a5e1_0072_d94bu64;
+        let updated = db
+            .set_phashes(&[
+                ("base.png".to_string(), base),
+                ("upscale.png".to_string(), base ^ 0b1),
+                ("variant.png".to_string(), base ^ 0xf0),
+                ("unrelated.png".to_string(), !base),
+                ("not_indexed.png".to_string(), base),
+            ])
+            .expect("set phashes failed");
+        assert_eq!(updated, 4);
+        assert_eq!(
+            db.filepaths_missing_phash().unwrap(),
+            HashSet::from(["unhashed.png".to_string()])
+        );
+
+        let id = |name: &str| db.get_image_id_by_filepath(name).unwrap().unwrap();
+        let similar = db
+            .find_similar_images(id("base.png"), 10, 100)
+            .unwrap()
+            .expect("base has a hash");
+        let found: Vec<(&str, u32)> = similar
+            .iter()
+            .map(|image| (image.record.filename.as_str(), image.distance))
+            .collect();
+        assert_eq!(found, vec![("upscale.png", 1), ("variant.png", 4)]);
+        assert_eq!(
+            db.find_similar_images(id("base.png"), 64, 1)
+                .unwrap()
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(db
+            .find_similar_images(id("unhashed.png"), 10, 100)
+            .unwrap()
+            .is_none());
+        assert!(db.find_similar_images(-1, 10, 100).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_deletion_safety_finds_collection_members_and_lineage_parents() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        let insert = |filepath: &str, seed: &str, generation_type: &str, side: u32| {
+            let params = GenerationParams {
+                prompt: "lighthouse at dusk".to_string(),
+                seed: Some(seed.to_string()),
+                width: Some(side),
+                height: Some(side),
+                generation_type: Some(generation_type.to_string()),
+                ..Default::default()
+            };
+            db.upsert_image(filepath, filepath, "c:\\images", &params, Some(1))
+                .unwrap()
+        };
+        let base = insert("base.png", "42", "txt2img", 512);
+        insert("base_hires.png", "42", "upscale", 2048);
+        let sibling = insert("sibling.png", "43", "txt2img", 512);
+        let external = insert("external.png", "-1", "txt2img", 512);
+        insert("external_4x.png", "-1", "unknown", 2048);
+        db.set_phashes(&[
+            ("external.png".to_string(), 0xdead_beef_0000_ffff),
+            ("external_4x.png".to_string(), 0xdead_beef_0000_fff8),
+            ("sibling.png".to_string(), 0x1234_5678_9abc_def0),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            db.lineage_parents(&[base, sibling, external]).unwrap(),
+            vec![base, external]
+        );
+        // Deleting the derived copies along with the parent leaves nothing
+        // that depended on it.
+        let hires = db
+            .get_image_id_by_filepath("base_hires.png")
+            .unwrap()
+            .unwrap();
+        assert!(db.lineage_parents(&[base, hires]).unwrap().is_empty());
+
+        let rule: CollectionRule = serde_json::from_str(
+            r#"{"conditions": [{"field": "width", "op": ">=", "value": 2048}]}"#,
+        )
+        .unwrap();
+        let collection = db
+            .save_collection(None, "Upscales", &rule.normalized().unwrap())
+            .unwrap();
+        let held = db.collections_holding(&[base, hires, sibling]).unwrap();
+        assert_eq!(held.len(), 1);
+        assert_eq!((held[0].id, held[0].count), (collection, 1));
+        assert_eq!(
+            db.count_images_in_collections(&[base, hires, sibling])
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_sampler_facet_merges_case_and_blank_names() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for (name, sampler) in [
+            ("a.png", Some("Euler a")),
+            ("b.png", Some("euler a")),
+            ("c.png", Some("DPM++ 2M")),
+            ("d.png", Some(" ")),
+            ("e.png", None),
+        ] {
+            let params = GenerationParams {
+                prompt: "cat".to_string(),
+                sampler: sampler.map(str::to_string),
+                ..Default::default()
+            };
+            db.upsert_image(&format!("/img/{name}"), name, "/img", &params, Some(1))
+                .unwrap();
+        }
+
+        let samplers: Vec<(String, u32)> = db
+            .get_unique_samplers()
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.sampler.to_ascii_lowercase(), entry.count))
+            .collect();
+        assert_eq!(
+            samplers,
+            [
+                ("euler a".to_string(), 2),
+                ("unknown".to_string(), 2),
+                ("dpm++ 2m".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolution_buckets_and_filter() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for (name, width, height) in [
+            ("a.png", Some(512), Some(512)),
+            ("b.png", Some(1216), Some(832)),
+            ("c.png", Some(1214), Some(830)),
+            ("d.png", Some(2048), Some(2048)),
+            ("e.png", None, None),
+        ] {
+            let params = GenerationParams {
+                prompt: "cat".to_string(),
+                width,
+                height,
+                ..Default::default()
+            };
+            db.upsert_image(&format!("/img/{name}"), name, "/img", &params, Some(1))
+                .unwrap();
+        }
+
+        let buckets: Vec<(String, u32, String)> = db
+            .get_resolution_buckets()
+            .unwrap()
+            .into_iter()
+            .map(|bucket| (bucket.bucket, bucket.count, bucket.tier))
+            .collect();
+        assert_eq!(
+            buckets,
+            [
+                ("1216x832".to_string(), 2, "sdxl".to_string()),
+                ("2048x2048".to_string(), 1, "large".to_string()),
+                ("512x512".to_string(), 1, "sd15".to_string()),
+            ]
+        );
+
+        let buckets = ["1216×832".to_string(), "512x512".to_string()];
+        let page = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                resolution_buckets: Some(&buckets),
+                ..Default::default()
+            })
+            .expect("cursor failed");
+        let mut names: Vec<String> = page.items.into_iter().map(|item| item.filename).collect();
+        names.sort();
+        assert_eq!(names, vec!["a.png", "b.png", "c.png"]);
+    }
+
+    #[test]
+    fn test_scheduler_filter_and_facet() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for (name, scheduler) in [
+            ("a.png", Some("Karras")),
+            ("b.png", Some("karras")),
+            ("c.png", Some("Exponential")),
+            ("d.png", None),
+        ] {
+            let params = GenerationParams {
+                prompt: "cat".to_string(),
+                schedule_type: scheduler.map(str::to_string),
+                ..Default::default()
+            };
+            db.upsert_image(&format!("/img/{name}"), name, "/img", &params, Some(1))
+                .unwrap();
+        }
+
+        let schedulers: Vec<(String, u32)> = db
+            .get_unique_schedulers()
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.scheduler, entry.count))
+            .collect();
+        assert_eq!(schedulers[0].1, 2);
+        assert!(schedulers[0].0.eq_ignore_ascii_case("karras"));
+        assert_eq!(
+            schedulers[1..],
+            [("Exponential".to_string(), 1), ("Unknown".to_string(), 1)]
+        );
+
+        let names = |schedulers: &[String]| {
+            let page = db
+                .get_images_cursor(CursorQueryOptions {
+                    limit: 10,
+                    scheduler_filters: Some(schedulers),
+                    ..Default::default()
+                })
+                .expect("cursor failed");
+            let mut names: Vec<String> = page.items.into_iter().map(|item| item.filename).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&["KARRAS".to_string()]), vec!["a.png", "b.png"]);
+        assert_eq!(
+            names(&["Exponential".to_string(), "Unknown".to_string()]),
+            vec!["c.png", "d.png"]
+        );
+        assert_eq!(names(&[" ".to_string()]).len(), 4);
+    }
+
+    #[test]
+    fn test_directory_pins_and_aliases_join_directory_entries() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for (directory, name) in [("/big", "a.png"), ("/big", "b.png"), ("/small", "c.png")] {
+            let filepath = format!("{directory}/{name}");
+            db.upsert_image(
+                &filepath,
+                name,
+                directory,
+                &GenerationParams::default(),
+                Some(1),
+            )
+            .unwrap();
+        }
+
+        let order = |db: &Database| -> Vec<(String, bool, Option<String>)> {
+            db.get_unique_directories()
+                .unwrap()
+                .into_iter()
+                .map(|entry| (entry.directory, entry.pinned, entry.alias))
+                .collect()
+        };
+        assert_eq!(order(&db)[0].0, "/big");
+
+        db.set_directory_pinned("/small", true).unwrap();
+        db.set_directory_alias("/small", Some("Forge txt2img"))
+            .unwrap();
+        assert_eq!(
+            order(&db),
+            vec![
+                (
+                    "/small".to_string(),
+                    true,
+                    Some("Forge txt2img".to_string())
+                ),
+                ("/big".to_string(), false, None),
+            ]
+        );
+
+        db.set_directory_pinned("/small", false).unwrap();
+        db.set_directory_alias("/small", None).unwrap();
+        assert_eq!(order(&db)[1], ("/small".to_string(), false, None));
+        let conn = db.pool.get().unwrap();
+        let rows: u32 = conn
+            .query_row("SELECT COUNT(*) FROM directory_meta", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 0);
+    }
+
+    #[test]
+    fn test_hidden_directories_drop_out_of_cursor_queries() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for (directory, name) in [("/out", "a.png"), ("/out/grids", "grid.png")] {
+            let params = GenerationParams {
+                prompt: "cat".to_string(),
+                ..Default::default()
+            };
+            db.upsert_image(
+                &format!("{directory}/{name}"),
+                name,
+                directory,
+                &params,
+                Some(1),
+            )
+            .unwrap();
+        }
+        db.set_directory_hidden("/out/grids", true).unwrap();
+
+        let names = |options: CursorQueryOptions| {
+            let mut names: Vec<String> = db
+                .get_images_cursor(options)
+                .unwrap()
+                .items
+                .into_iter()
+                .map(|item| item.filename)
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            names(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            }),
+            vec!["a.png"]
+        );
+        assert_eq!(
+            names(CursorQueryOptions {
+                limit: 10,
+                include_hidden: true,
+                ..Default::default()
+            }),
+            vec!["a.png", "grid.png"]
+        );
+        let search = db
+            .search_cursor(SearchCursorParams {
+                query: "cat",
+                options: CursorQueryOptions {
+                    limit: 10,
+                    ..Default::default()
+                },
+            })
+            .unwrap();
+        assert_eq!(search.items.len(), 1);
+        let hidden: Vec<bool> = db
+            .get_unique_directories()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.hidden)
+            .collect();
+        assert_eq!(hidden.iter().filter(|hidden| **hidden).count(), 1);
+
+        db.set_directory_hidden("/out/grids", false).unwrap();
+        assert_eq!(
+            names(CursorQueryOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_filter_counts_facet_generation_types_and_families() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        for (name, prompt, model, generation_type) in [
+            ("a.png", "cat", "sdxl_base", "txt2img"),
+            ("b.png", "cat", "flux_dev", "txt2img"),
+            ("c.png", "cat", "flux_dev", "img2img"),
+            ("d.png", "dog", "flux_dev", "txt2img"),
+        ] {
+            let params = GenerationParams {
+                prompt: prompt.to_string(),
+                raw_metadata: prompt.to_string(),
+                model_name: Some(model.to_string()),
+                ..Default::default()
+            };
+            db.upsert_image(&format!("/img/{name}"), name, "/img", &params, Some(1))
+                .unwrap();
+            db.pool
+                .get()
+                .unwrap()
+                .execute(
+                    "UPDATE images SET generation_type = ?1 WHERE filename = ?2",
+                    params![generation_type, name],
+                )
+                .unwrap();
+        }
+
+        let counts = |generation_types: &[String], families: &[String]| {
+            let counts = db
+                .get_filter_counts(FilterCursorParams {
+                    query: Some("cat"),
+                    include_tags: &[],
+                    exclude_tags: &[],
+                    refine: None,
+                    extra_params: &[],
+                    embeddings: &[],
+                    options: CursorQueryOptions {
+                        generation_types: Some(generation_types),
+                        model_family_filters: Some(families),
+                        ..Default::default()
+                    },
+                })
+                .expect("counts failed");
+            let find = |entries: &[FilterCount], value: &str| {
+                entries
+                    .iter()
+                    .find(|entry| entry.value == value)
+                    .map(|entry| entry.count)
+                    .unwrap()
+            };
+            (
+                counts.total,
+                find(&counts.generation_types, "txt2img"),
+                find(&counts.generation_types, "img2img"),
+                find(&counts.model_families, "flux"),
+                find(&counts.model_families, "sdxl"),
+            )
+        };
+
+        assert_eq!(counts(&[], &[]), (3, 2, 1, 2, 1));
+        // Each facet keeps the other's selection but not its own.
+        assert_eq!(
+            counts(&["txt2img".to_string()], &["flux".to_string()]),
+            (1, 1, 1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_seed_neighbors_follow_seed_window_and_variation_seed() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        let mut ids = HashMap::new();
+        for (name, prompt, seed, variation) in [
+            ("s100.png", "cat", "100", None),
+            ("s103.png", "cat", "103", None),
+            ("s98.png", "cat", "98", None),
+            ("s200.png", "cat", "200", None),
+            ("v1.png", "cat", "5000", Some(("777", "0.2"))),
+            ("v2.png", "cat", "6000", Some(("777", "0.5"))),
+            ("dog.png", "dog", "101", None),
+        ] {
+            let mut params = GenerationParams {
+                prompt: prompt.to_string(),
+                seed: Some(seed.to_string()),
+                model_name: Some("sdxl".to_string()),
+                ..Default::default()
+            };
+            if let Some((variation_seed, strength)) = variation {
+                params
+                    .extra_params
+                    .insert("Variation seed".to_string(), variation_seed.to_string());
+                params
+                    .extra_params
+                    .insert("Variation seed strength".to_string(), strength.to_string());
+            }
+            let id = db
+                .upsert_image(&format!("/img/{name}"), name, "/img", &params, Some(1))
+                .unwrap();
+            ids.insert(name, id);
+        }
+
+        let names = |image_id: i64, window: u32| -> Vec<String> {
+            db.get_seed_neighbors(image_id, window)
+                .unwrap()
+                .into_iter()
+                .map(|record| record.filename)
+                .collect()
+        };
+        assert_eq!(
+            names(ids["s100.png"], 3),
+            vec!["s98.png", "s100.png", "s103.png"]
+        );
+        assert_eq!(names(ids["s100.png"], 0), vec!["s100.png"]);
+        // Images from the same variation seed form a series of their own.
+        assert_eq!(names(ids["v2.png"], 0), vec!["v1.png", "v2.png"]);
+        assert!(db.get_seed_neighbors(-1, 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_model_family_detection_uses_modules_hashes_and_size() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        let mut forge_flux = GenerationParams {
+            model_name: Some("myMerge_v3".to_string()),
+            model_hash: Some("aaaa111111".to_string()),
+            width: Some(896),
+            height: Some(1152),
+            ..Default::default()
+        };
+        forge_flux
+            .extra_params
+            .insert("Module 1".to_string(), "ae".to_string());
+        forge_flux
+            .extra_params
+            .insert("Module 2".to_string(), "clip_l".to_string());
+        forge_flux
+            .extra_params
+            .insert("Module 3".to_string(), "t5xxl_fp8_e4m3fn".to_string());
+        let renamed_flux = GenerationParams {
+            model_name: Some("renamed".to_string()),
+            model_hash: Some("aaaa111111".to_string()),
+            ..Default::default()
+        };
+        let known_hash = GenerationParams {
+            model_name: Some("base".to_string()),
+            model_hash: Some("31e35c80fc".to_string()),
+            ..Default::default()
+        };
+        let small = GenerationParams {
+            width: Some(512),
+            height: Some(768),
+            ..Default::default()
+        };
+        let unknown = GenerationParams {
+            model_name: Some("mystery".to_string()),
+            width: Some(1000),
+            height: Some(1000),
+            ..Default::default()
+        };
+        for (name, params) in [
+            ("flux.png", &forge_flux),
+            ("renamed.png", &renamed_flux),
+            ("known.png", &known_hash),
+            ("small.png", &small),
+            ("unknown.png", &unknown),
+        ] {
+            db.bulk_upsert_with_tags(&[BulkRecord {
+                filepath: format!("/img/{name}"),
+                filename: name.to_string(),
+                directory: "/img".to_string(),
+                params: params.clone(),
+                tags: Vec::new(),
+                file_mtime: Some(1),
+                file_size: None,
+                quick_hash: None,
+                workflow: None,
+                rating: None,
+            }])
+            .unwrap();
+        }
 
-        if cleaned.is_empty() || cleaned == "*" {
-            continue;
-        }
+        let family = |name: &str| -> String {
+            db.pool
+                .get()
+                .unwrap()
+                .query_row(
+                    "SELECT model_family FROM images WHERE filename = ?1",
+                    [name],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(family("flux.png"), "flux");
+        // Inherits the family of the classified image with the same hash.
+        assert_eq!(family("renamed.png"), "flux");
+        assert_eq!(family("known.png"), "sdxl");
+        assert_eq!(family("small.png"), "sd15");
+        assert_eq!(family("unknown.png"), "");
 
-        if has_wildcard {
-            // Preserve explicit wildcard position
-            parts.push(cleaned.to_lowercase());
-        } else {
-            parts.push(format!("{}*", cleaned.to_lowercase()));
-        }
+        let families = ["flux".to_string()];
+        let page = db
+            .get_images_cursor(CursorQueryOptions {
+                limit: 10,
+                model_family_filters: Some(&families),
+                ..Default::default()
+            })
+            .unwrap();
+        let mut names: Vec<String> = page.items.into_iter().map(|item| item.filename).collect();
+        names.sort();
+        assert_eq!(names, vec!["flux.png", "renamed.png"]);
     }
-}
 
-fn contains_search_token(text: &str) -> bool {
-    text.chars().any(|ch| ch.is_alphanumeric())
-}
+    #[test]
+    fn test_filter_wide_operations_touch_only_matches() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "red cat", &["cat"]);
+        insert_with_prompt(&db, "b.png", "blue cat", &["cat"]);
+        insert_with_prompt(&db, "c.png", "red dog", &["dog"]);
+        let flag_count = |column: &str| -> i64 {
+            db.pool
+                .get()
+                .unwrap()
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM images WHERE {column} = 1"),
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        let cat = vec!["cat".to_string()];
+        let everything = FilterCursorParams {
+            query: None,
+            include_tags: &[],
+            exclude_tags: &[],
+            refine: None,
+            extra_params: &[],
+            embeddings: &[],
+            options: CursorQueryOptions::default(),
+        };
+        let cats = FilterCursorParams {
+            include_tags: &cat,
+            ..everything
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let cat_ids = db.get_ids_by_filter(cats).unwrap();
+        assert_eq!(cat_ids.len(), 2);
+        assert!(cat_ids[0] > cat_ids[1]);
+        assert_eq!(db.set_favorite_by_filter(cats, true).unwrap(), 2);
+        assert_eq!(flag_count("is_favorite"), 2);
+        // Rows already in the requested state are not counted again.
+        assert_eq!(db.set_favorite_by_filter(cats, true).unwrap(), 0);
 
-    fn insert_with_prompt(db: &Database, filepath: &str, prompt: &str, tags: &[&str]) {
-        let params = GenerationParams {
-            prompt: prompt.to_string(),
-            raw_metadata: prompt.to_string(),
-            ..Default::default()
+        let red = FilterCursorParams {
+            query: Some("red"),
+            ..everything
+        };
+        assert_eq!(db.set_locked_by_filter(red, true).unwrap(), 2);
+        assert_eq!(flag_count("is_locked"), 2);
+        // Substring fallback when no whole word matches.
+        let substring = FilterCursorParams {
+            query: Some("lue ca"),
+            ..everything
         };
+        assert_eq!(db.set_favorite_by_filter(substring, false).unwrap(), 1);
+        assert_eq!(flag_count("is_favorite"), 1);
+    }
 
-        let image_id = db
-            .upsert_image(filepath, filepath, "c:\\images", &params, Some(1))
-            .expect("failed to insert image");
+    #[test]
+    fn test_prompt_styles_replace_and_keep_file_order() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        let style = |name: &str, prompt: &str| PromptStyle {
+            name: name.to_string(),
+            prompt: prompt.to_string(),
+            negative_prompt: String::new(),
+        };
+        db.replace_prompt_styles(&[style("Zeta", "z"), style("Alpha", "a")])
+            .unwrap();
+        let names: Vec<String> = db
+            .list_prompt_styles()
+            .unwrap()
+            .into_iter()
+            .map(|style| style.name)
+            .collect();
+        assert_eq!(names, vec!["Zeta", "Alpha"]);
+        assert_eq!(db.get_prompt_style("Alpha").unwrap().unwrap().prompt, "a");
 
-        let normalized_tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
-        db.replace_image_tags(image_id, &normalized_tags)
-            .expect("failed to insert tags");
+        db.replace_prompt_styles(&[style("Beta", "b")]).unwrap();
+        assert!(db.get_prompt_style("Alpha").unwrap().is_none());
+        assert_eq!(db.list_prompt_styles().unwrap().len(), 1);
     }
 
     #[test]
-    fn test_sanitize_fts_query_builds_safe_prefix_terms() {
-        let query = r#" Model:XL cat++ seed:123 "#;
-        let sanitized = sanitize_fts_query(query);
-        assert_eq!(sanitized, "modelxl* cat* seed123*");
+    fn test_rewrite_prompts_updates_search_and_derived_tags() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(
+            &db,
+            "a.png",
+            "oldtrigger, cat",
+            &["oldtrigger", "cat", "manual"],
+        );
+        let id = db.get_image_id_by_filepath("a.png").unwrap().unwrap();
+
+        let updated = db
+            .rewrite_prompts(&[PromptRewrite {
+                id,
+                old_prompt: "oldtrigger, cat".to_string(),
+                prompt: "cat".to_string(),
+                negative_prompt: String::new(),
+                raw_metadata: "cat".to_string(),
+            }])
+            .unwrap();
+        assert_eq!(updated, 1);
+        assert_eq!(db.get_image_by_id(id).unwrap().unwrap().prompt, "cat");
+        let mut tags = db.get_tags_for_image(id).unwrap();
+        tags.sort();
+        assert_eq!(tags, vec!["cat", "manual"]);
+
+        let search = |query: &str| {
+            db.filter_images_cursor(FilterCursorParams {
+                query: Some(query),
+                include_tags: &[],
+                exclude_tags: &[],
+                refine: None,
+                extra_params: &[],
+                embeddings: &[],
+                options: CursorQueryOptions {
+                    limit: 10,
+                    ..Default::default()
+                },
+            })
+            .unwrap()
+            .items
+            .len()
+        };
+        assert_eq!(search("oldtrigger"), 0);
+        assert_eq!(search("cat"), 1);
     }
 
     #[test]
-    fn test_sanitize_fts_query_all_special_chars_is_empty() {
-        let query = r#" ::: ??? +++  "#;
-        let sanitized = sanitize_fts_query(query);
-        assert!(sanitized.is_empty());
+    fn test_model_defaults_match_checkpoints_before_families() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        db.set_model_defaults(&ModelDefaults {
+            model: "SDXL".to_string(),
+            steps: Some(30),
+            width: Some(1024),
+            height: Some(1024),
+            ..Default::default()
+        })
+        .unwrap();
+        db.set_model_defaults(&ModelDefaults {
+            model: "juggernautXL_v9.safetensors".to_string(),
+            steps: Some(40),
+            ..Default::default()
+        })
+        .unwrap();
+        // Same checkpoint token, so this replaces the entry above.
+        db.set_model_defaults(&ModelDefaults {
+            model: "models/JuggernautXL_v9 [abc123]".to_string(),
+            steps: Some(45),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let defaults = db.list_model_defaults().unwrap();
+        assert_eq!(defaults.len(), 2);
+        let lookup = |model: &str| ModelDefaults::lookup(&defaults, model).and_then(|d| d.steps);
+        assert_eq!(lookup("juggernautXL_v9.safetensors [abc123]"), Some(45));
+        assert_eq!(lookup("animagine-sdxl-3.1"), Some(30));
+        assert_eq!(lookup("dreamshaper_8"), None);
+
+        assert!(db.delete_model_defaults("sdxl").unwrap());
+        assert!(!db.delete_model_defaults("sdxl").unwrap());
     }
 
     #[test]
-    fn test_sanitize_fts_query_quoted_phrase() {
-        let query = r#""best quality" cat"#;
-        let sanitized = sanitize_fts_query(query);
-        assert_eq!(sanitized, r#""best quality" cat*"#);
+    fn test_focal_points_round_trip_and_track_missing_images() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "portrait", &[]);
+        insert_with_prompt(&db, "b.png", "landscape", &[]);
+        let missing = db.images_missing_focal_point().unwrap();
+        assert_eq!(missing.len(), 2);
+
+        let (a_id, _) = missing.iter().find(|(_, path)| path == "a.png").unwrap();
+        db.set_focal_points(&[(*a_id, crate::focal_point::FocalPoint::new(0.5, 0.25))])
+            .unwrap();
+        let points = db
+            .focal_points_for_filepaths(&["a.png".to_string(), "b.png".to_string()])
+            .unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points["a.png"].y, 0.25);
+        assert_eq!(db.images_missing_focal_point().unwrap().len(), 1);
     }
 
     #[test]
-    fn test_sanitize_fts_query_wildcard_preserved() {
-        let query = "cat* dog";
-        let sanitized = sanitize_fts_query(query);
-        assert_eq!(sanitized, "cat* dog*");
+    fn test_batch_members_follow_consecutive_seeds() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        let insert = |filename: &str, prompt: &str, seed: &str, mtime: i64| {
+            let params = GenerationParams {
+                prompt: prompt.to_string(),
+                seed: Some(seed.to_string()),
+                steps: Some("20".to_string()),
+                ..Default::default()
+            };
+            db.upsert_image(filename, filename, "c:\\out", &params, Some(mtime))
+                .expect("failed to insert image")
+        };
+        // One batch of three, a second run with a random seed, and a batch
+        // of a different prompt interleaved with the first.
+        let first = insert("00010-100.png", "cat knight", "100", 1_000);
+        insert("00011-500.png", "dog knight", "500", 1_002);
+        insert("00012-102.png", "cat knight", "102", 1_004);
+        insert("00013-101.png", "cat knight", "101", 1_003);
+        insert("00014-900.png", "cat knight", "900", 1_060);
+        // Same seed sequence, but an hour later.
+        insert("00020-103.png", "cat knight", "103", 4_600);
+
+        let members: Vec<String> = db
+            .get_batch_members(first)
+            .expect("batch query failed")
+            .into_iter()
+            .map(|member| member.filename)
+            .collect();
+        assert_eq!(
+            members,
+            vec!["00010-100.png", "00013-101.png", "00012-102.png"]
+        );
+
+        let lone = insert("00030-7.png", "", "7", 9_000);
+        insert("00031-8.png", "", "8", 9_001);
+        assert_eq!(db.get_batch_members(lone).unwrap().len(), 1);
+        assert!(db.get_batch_members(9_999).unwrap().is_empty());
     }
 
     #[test]
-    fn test_search_uses_prefix_query_and_returns_expected_best_match() {
+    fn test_empty_search_returns_spelling_suggestions() {
         let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
             .expect("failed to create in-memory db");
-        insert_with_prompt(&db, "a.png", "cat hero portrait", &["cat", "hero"]);
-        insert_with_prompt(&db, "b.png", "cat landscape", &["cat", "landscape"]);
+        insert_with_prompt(&db, "a.png", "portrait", &["blonde hair", "portrait"]);
+        insert_with_prompt(&db, "b.png", "landscape", &["landscape"]);
 
+        let options = CursorQueryOptions {
+            cursor: None,
+            limit: 10,
+            sort_by: None,
+            generation_types: None,
+            model_filter: None,
+            model_family_filters: None,
+            scheduler_filters: None,
+            resolution_buckets: None,
+            source_tools: None,
+            workflow_statuses: None,
+            prompt_clusters: None,
+            collections: None,
+            max_sharpness: None,
+            failed_renders_only: false,
+            include_hidden: false,
+            collapse_duplicates: false,
+        };
         let page = db
             .search_cursor(SearchCursorParams {
-                query: "cat hero",
-                options: CursorQueryOptions {
-                    cursor: None,
-                    limit: 10,
-                    sort_by: None,
-                    generation_types: None,
-                    model_filter: None,
-                    model_family_filters: None,
-                },
+                query: "lanscape",
+                options,
             })
             .expect("search failed");
-        assert_eq!(page.items.len(), 1);
-        assert_eq!(page.items[0].filepath, "a.png");
-    }
-
-    #[test]
-    fn test_filter_images_by_include_and_exclude_tags() {
-        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
-            .expect("failed to create in-memory db");
-        insert_with_prompt(&db, "a.png", "cat hero portrait", &["cat", "hero"]);
-        insert_with_prompt(&db, "b.png", "cat landscape", &["cat", "landscape"]);
+        assert!(page.items.is_empty());
+        assert_eq!(page.suggestions, vec!["landscape"]);
 
-        let include = vec!["cat".to_string()];
-        let exclude = vec!["landscape".to_string()];
         let page = db
             .filter_images_cursor(FilterCursorParams {
-                query: None,
-                include_tags: &include,
-                exclude_tags: &exclude,
-                options: CursorQueryOptions {
-                    cursor: None,
-                    limit: 10,
-                    sort_by: None,
-                    generation_types: None,
-                    model_filter: None,
-                    model_family_filters: None,
-                },
+                query: Some("blnde hiar"),
+                include_tags: &[],
+                exclude_tags: &[],
+                refine: None,
+                extra_params: &[],
+                embeddings: &[],
+                options,
             })
             .expect("filter failed");
+        assert_eq!(page.suggestions, vec!["blonde hair"]);
 
+        let page = db
+            .search_cursor(SearchCursorParams {
+                query: "landscape",
+                options,
+            })
+            .expect("search failed");
         assert_eq!(page.items.len(), 1);
-        assert_eq!(page.items[0].filepath, "a.png");
+        assert!(page.suggestions.is_empty());
     }
 
     #[test]
-    fn test_cursor_pagination_returns_correct_pages() {
+    fn test_spelling_suggestions_skip_locked_directories() {
         let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
             .expect("failed to create in-memory db");
-        insert_with_prompt(&db, "a.png", "first", &[]);
-        insert_with_prompt(&db, "b.png", "second", &[]);
-        insert_with_prompt(&db, "c.png", "third", &[]);
-
-        let page1 = db
-            .get_images_cursor(None, 2, None, None, None, None)
-            .expect("cursor query failed");
-        assert_eq!(page1.items.len(), 2);
-        assert!(page1.next_cursor.is_some());
+        insert_with_prompt(&db, "c:/images/public.png", "castle", &["castle"]);
+        insert_with_prompt(&db, "c:/images/secret/a.png", "garden", &["secret garden"]);
+        assert_eq!(
+            db.search_suggestions("secret gardn").unwrap(),
+            vec!["secret garden"]
+        );
 
-        let page2 = db
-            .get_images_cursor(page1.next_cursor.as_deref(), 2, None, None, None, None)
-            .expect("cursor query failed");
-        assert_eq!(page2.items.len(), 1);
+        db.set_private_directories(&["c:/images/secret/".to_string()]);
+        assert!(db.search_suggestions("secret gardn").unwrap().is_empty());
+        assert_eq!(db.search_suggestions("castel").unwrap(), vec!["castle"]);
     }
 
     #[test]
-    fn test_trigram_search_finds_substring() {
+    fn test_filter_images_refine_narrows_existing_filter() {
         let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
             .expect("failed to create in-memory db");
-        insert_with_prompt(&db, "a.png", "concatenation is fun", &[]);
-        insert_with_prompt(&db, "b.png", "hello world", &[]);
+        insert_with_prompt(&db, "a.png", "cat knight in armor", &["cat"]);
+        insert_with_prompt(&db, "b.png", "cat wizard casting spells", &["cat"]);
+        insert_with_prompt(&db, "c.png", "dog knight", &["dog"]);
 
-        let page = db
-            .search_cursor(SearchCursorParams {
-                query: "cat",
-                options: CursorQueryOptions {
-                    cursor: None,
-                    limit: 10,
-                    sort_by: None,
-                    generation_types: None,
-                    model_filter: None,
-                    model_family_filters: None,
-                },
-            })
-            .expect("trigram search failed");
-        assert_eq!(page.items.len(), 1);
-        assert_eq!(page.items[0].filepath, "a.png");
+        let include = vec!["cat".to_string()];
+        let filter = |query: Option<&str>, refine: Option<&str>| {
+            let page = db
+                .filter_images_cursor(FilterCursorParams {
+                    query,
+                    include_tags: &include,
+                    exclude_tags: &[],
+                    refine,
+                    extra_params: &[],
+                    embeddings: &[],
+                    options: CursorQueryOptions {
+                        cursor: None,
+                        limit: 10,
+                        sort_by: None,
+                        generation_types: None,
+                        model_filter: None,
+                        model_family_filters: None,
+                        scheduler_filters: None,
+                        resolution_buckets: None,
+                        source_tools: None,
+                        workflow_statuses: None,
+                        prompt_clusters: None,
+                        collections: None,
+                        max_sharpness: None,
+                        failed_renders_only: false,
+                        include_hidden: false,
+                        collapse_duplicates: false,
+                    },
+                })
+                .expect("filter failed");
+            let mut paths: Vec<String> = page.items.into_iter().map(|item| item.filepath).collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(filter(Some("cat"), None), vec!["a.png", "b.png"]);
+        assert_eq!(filter(Some("cat"), Some("knight")), vec!["a.png"]);
+        // Substring refinements go through the trigram index.
+        assert_eq!(filter(Some("cat"), Some("izar")), vec!["b.png"]);
+        assert_eq!(filter(None, Some("knight")), vec!["a.png"]);
+        assert!(filter(Some("cat"), Some("dragon")).is_empty());
+        assert_eq!(filter(Some("cat"), Some("  ")), vec!["a.png", "b.png"]);
     }
 
     #[test]
-    fn test_filter_images_falls_back_to_trigram_for_substring_queries() {
+    fn test_sample_filtered_images_respects_tag_filters() {
         let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
             .expect("failed to create in-memory db");
-        insert_with_prompt(&db, "a.png", "donald trump portrait", &["portrait"]);
-        insert_with_prompt(&db, "b.png", "landscape painting", &["landscape"]);
+        insert_with_prompt(&db, "a.png", "cat hero portrait", &["cat", "hero"]);
+        insert_with_prompt(&db, "b.png", "cat landscape", &["cat", "landscape"]);
+        insert_with_prompt(&db, "c.png", "dog portrait", &["dog"]);
 
-        let include = vec!["portrait".to_string()];
-        let page = db
-            .filter_images_cursor(FilterCursorParams {
-                query: Some("ump"),
+        let include = vec!["cat".to_string()];
+        let sample = db
+            .sample_filtered_images(FilterCursorParams {
+                query: None,
                 include_tags: &include,
                 exclude_tags: &[],
+                refine: None,
+                extra_params: &[],
+                embeddings: &[],
                 options: CursorQueryOptions {
                     cursor: None,
                     limit: 10,
@@ -947,12 +5138,23 @@ mod tests {
                     generation_types: None,
                     model_filter: None,
                     model_family_filters: None,
+                    scheduler_filters: None,
+                    resolution_buckets: None,
+                    source_tools: None,
+                    workflow_statuses: None,
+                    prompt_clusters: None,
+                    collections: None,
+                    max_sharpness: None,
+                    failed_renders_only: false,
+                    include_hidden: false,
+                    collapse_duplicates: false,
                 },
             })
-            .expect("filter failed");
+            .expect("sample failed");
 
-        assert_eq!(page.items.len(), 1);
-        assert_eq!(page.items[0].filepath, "a.png");
+        let mut filepaths: Vec<&str> = sample.iter().map(|item| item.filepath.as_str()).collect();
+        filepaths.sort_unstable();
+        assert_eq!(filepaths, vec!["a.png", "b.png"]);
     }
 
     #[test]
@@ -994,7 +5196,11 @@ mod tests {
         .expect("failed to insert non-grid image");
 
         let page = db
-            .get_images_cursor(None, 50, None, Some(&["grid".to_string()]), None, None)
+            .get_images_cursor(CursorQueryOptions {
+                limit: 50,
+                generation_types: Some(&["grid".to_string()]),
+                ..Default::default()
+            })
             .expect("grid cursor query failed");
 
         assert_eq!(page.items.len(), 1);
@@ -1020,6 +5226,8 @@ mod tests {
                 file_size: Some(1000),
                 quick_hash: Some("aaaabbbbccccdddd11112222".to_string()),
                 tags: vec!["cat".to_string(), "portrait".to_string()],
+                workflow: None,
+                rating: None,
             },
             BulkRecord {
                 filepath: "b.png".to_string(),
@@ -1034,6 +5242,8 @@ mod tests {
                 file_size: Some(2000),
                 quick_hash: Some("eeeeffff0000111122223333".to_string()),
                 tags: vec!["dog".to_string(), "landscape".to_string()],
+                workflow: None,
+                rating: None,
             },
         ];
 
@@ -1044,6 +5254,43 @@ mod tests {
         assert_eq!(db.get_total_count().unwrap(), 2);
     }
 
+    #[test]
+    fn test_find_quick_hash_duplicates_groups_matching_hashes() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        let record = |filepath: &str, quick_hash: &str| BulkRecord {
+            filepath: filepath.to_string(),
+            filename: filepath.to_string(),
+            directory: "c:\\images".to_string(),
+            params: GenerationParams::default(),
+            file_mtime: Some(1),
+            file_size: Some(10),
+            quick_hash: Some(quick_hash.to_string()),
+            tags: Vec::new(),
+            workflow: None,
+            rating: None,
+        };
+        db.bulk_upsert_with_tags(&[
+            record("a.png", "hash-one"),
+            record("b.png", "hash-two"),
+            record("copy-of-a.png", "hash-one"),
+        ])
+        .expect("bulk upsert failed");
+
+        let groups = db
+            .find_quick_hash_duplicates()
+            .expect("duplicate query failed");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].quick_hash, "hash-one");
+        assert_eq!(groups[0].filepaths, vec!["a.png", "copy-of-a.png"]);
+        assert_eq!(groups[0].keep_id, groups[0].ids[0]);
+
+        // A favourite copy is kept over the older one.
+        db.set_image_favorite(groups[0].ids[1], true).unwrap();
+        let groups = db.find_quick_hash_duplicates().unwrap();
+        assert_eq!(groups[0].keep_id, groups[0].ids[1]);
+    }
+
     #[test]
     fn test_get_all_file_mtimes() {
         let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
@@ -1059,6 +5306,143 @@ mod tests {
         assert_eq!(mtimes.get("b.png"), Some(&1));
     }
 
+    #[test]
+    fn test_record_cache_serves_repeat_reads_and_invalidates_on_write() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat", &["cat"]);
+        insert_with_prompt(&db, "b.png", "dog", &["dog"]);
+        let id_a = db.get_image_id_by_filepath("a.png").unwrap().unwrap();
+        let id_b = db.get_image_id_by_filepath("b.png").unwrap().unwrap();
+
+        assert!(!db.get_image_by_id(id_a).unwrap().unwrap().is_favorite);
+        assert!(db.cached_record(id_a).is_some());
+
+        db.set_image_favorite(id_a, true).unwrap();
+        assert!(db.cached_record(id_a).is_none());
+        assert!(db.get_image_by_id(id_a).unwrap().unwrap().is_favorite);
+
+        let batch = db.get_images_by_ids(&[id_a, id_b, id_a]).unwrap();
+        assert_eq!(
+            batch.iter().map(|record| record.id).collect::<Vec<_>>(),
+            vec![id_b, id_a]
+        );
+
+        db.delete_images_by_ids(&[id_b]).unwrap();
+        assert!(db.get_image_by_id(id_b).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_get_image_details_joins_sorted_tags() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat", &["portrait", "cat, fluffy"]);
+        insert_with_prompt(&db, "b.png", "dog", &[]);
+        let id_a = db.get_image_id_by_filepath("a.png").unwrap().unwrap();
+        let id_b = db.get_image_id_by_filepath("b.png").unwrap().unwrap();
+
+        let details = db.get_image_details(&[id_a, id_b]).unwrap();
+        assert_eq!(details.len(), 2);
+        let detail_a = details.iter().find(|d| d.record.id == id_a).unwrap();
+        let detail_b = details.iter().find(|d| d.record.id == id_b).unwrap();
+        assert_eq!(detail_a.tags, vec!["cat, fluffy", "portrait"]);
+        assert!(detail_b.tags.is_empty());
+    }
+
+    #[test]
+    fn test_get_grid_members_links_cells_from_the_same_run() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        let grids = "/outputs/txt2img-grids/2025-09-16";
+        let images = "/outputs/txt2img-images/2025-09-16";
+        let insert = |directory: &str, filename: &str, prompt: &str, seed: &str, mtime: i64| {
+            let params = GenerationParams {
+                prompt: prompt.to_string(),
+                seed: Some(seed.to_string()),
+                raw_metadata: prompt.to_string(),
+                ..Default::default()
+            };
+            db.upsert_image(
+                &format!("{}/{}", directory, filename),
+                filename,
+                directory,
+                &params,
+                Some(mtime),
+            )
+            .expect("failed to insert image")
+        };
+
+        insert(images, "00001-100.png", "old run", "100", 1_000);
+        let old_grid = insert(grids, "grid-0001.png", "old run", "100", 1_010);
+        insert(images, "00002-500.png", "castle", "500", 2_000);
+        insert(images, "00003-501.png", "castle", "501", 2_005);
+        insert(images, "00004-9.png", "unrelated", "9", 2_006);
+        let grid = insert(grids, "grid-0002.png", "castle", "500", 2_010);
+
+        let members = db.get_grid_members(grid).unwrap().unwrap();
+        assert_eq!(
+            members
+                .iter()
+                .map(|record| record.filename.as_str())
+                .collect::<Vec<_>>(),
+            vec!["00002-500.png", "00003-501.png"]
+        );
+        assert_eq!(db.get_grid_members(old_grid).unwrap().unwrap().len(), 1);
+
+        let cell = db
+            .get_image_id_by_filepath(&members[0].filepath)
+            .unwrap()
+            .unwrap();
+        assert!(db.get_grid_members(cell).unwrap().unwrap().is_empty());
+        assert!(db.get_grid_members(-1).unwrap().is_none());
+
+        // Locked private folders stay out of grids, and their grids stay closed.
+        db.set_private_directories(&[images.to_string()]);
+        assert!(db.get_grid_members(grid).unwrap().unwrap().is_empty());
+        db.set_private_directories(&[grids.to_string()]);
+        assert!(db.get_grid_members(grid).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_tag_dictionary_suggestions_merge_with_library_tags() {
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd)
+            .expect("failed to create in-memory db");
+        insert_with_prompt(&db, "a.png", "cat", &["long hair", "lora:stylea"]);
+        assert_eq!(
+            db.list_tags(Some("lo"), 10).unwrap(),
+            vec!["long hair", "lora:stylea"]
+        );
+
+        let dictionary = [
+            ("long hair", 0, 4_500_000),
+            ("looking at viewer", 0, 3_800_000),
+            ("lowres", 5, 900_000),
+        ]
+        .map(|(tag, category, post_count)| DictionaryTag {
+            tag: tag.to_string(),
+            category,
+            post_count,
+        });
+        db.replace_tag_dictionary(&dictionary).unwrap();
+
+        let suggestions = db.list_tag_suggestions(Some("Lo"), 10).unwrap();
+        let tags: Vec<&str> = suggestions.iter().map(|s| s.tag.as_str()).collect();
+        assert_eq!(
+            tags,
+            vec!["long hair", "looking at viewer", "lowres", "lora:stylea"]
+        );
+        assert_eq!(suggestions[0].library_count, 1);
+        assert_eq!(suggestions[2].category.as_deref(), Some("meta"));
+        assert_eq!(suggestions[3].post_count, None);
+        assert_eq!(
+            db.list_tags(Some("lo"), 2).unwrap(),
+            vec!["long hair", "looking at viewer"]
+        );
+
+        db.replace_tag_dictionary(&[]).unwrap();
+        assert_eq!(db.list_tags(Some("lo"), 10).unwrap().len(), 2);
+    }
+
     fn explain_details(
         conn: &Connection,
         sql: &str,
@@ -1131,4 +5515,84 @@ mod tests {
             "expected top-tags query to use idx_image_tags_tag_id_image_id, got {top_tags_plan:?}"
         );
     }
+
+    #[test]
+    fn test_thumbnail_failures_count_retries_and_clear_once_rendered() {
+        use crate::image_processing::{ThumbnailFailure, ThumbnailFailureKind};
+
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        insert_with_prompt(&db, "a.png", "cat", &[]);
+        insert_with_prompt(&db, "b.png", "dog", &[]);
+        let failure = |filepath: &str, kind| ThumbnailFailure {
+            filepath: filepath.to_string(),
+            kind,
+            message: format!("{} failed", filepath),
+        };
+
+        let recorded = db
+            .record_thumbnail_failures(&[
+                failure("a.png", ThumbnailFailureKind::Decode),
+                failure("b.png", ThumbnailFailureKind::EmptyFile),
+                failure("not-indexed.png", ThumbnailFailureKind::Missing),
+            ])
+            .unwrap();
+        assert_eq!(recorded, 2);
+        db.record_thumbnail_failures(&[failure("a.png", ThumbnailFailureKind::Unsupported)])
+            .unwrap();
+
+        let failures = db.get_thumbnail_failures(None).unwrap();
+        assert_eq!(failures.len(), 2);
+        let a = failures.iter().find(|f| f.filepath == "a.png").unwrap();
+        assert_eq!(a.kind, ThumbnailFailureKind::Unsupported);
+        assert_eq!(a.attempts, 2);
+        let only_b = db
+            .get_thumbnail_failures(Some(&[failures[0].image_id.max(failures[1].image_id)]))
+            .unwrap();
+        assert_eq!(only_b.len(), 1);
+        assert_eq!(only_b[0].filepath, "b.png");
+
+        assert_eq!(
+            db.clear_thumbnail_failures(&["a.png".to_string()]).unwrap(),
+            1
+        );
+        let remaining = db.get_thumbnail_failures(None).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].kind, ThumbnailFailureKind::EmptyFile);
+    }
+
+    #[test]
+    fn test_repeated_decode_failures_mark_the_image_corrupt_until_it_renders() {
+        use crate::image_processing::{ThumbnailFailure, ThumbnailFailureKind};
+
+        let db = Database::new(Path::new(":memory:"), StorageProfile::Hdd).expect("db init failed");
+        insert_with_prompt(&db, "a.png", "cat", &[]);
+        insert_with_prompt(&db, "b.png", "dog", &[]);
+        let failure = |filepath: &str, kind| ThumbnailFailure {
+            filepath: filepath.to_string(),
+            kind,
+            message: format!("{} failed", filepath),
+        };
+
+        for _ in 0..2 {
+            db.record_thumbnail_failures(&[
+                failure("a.png", ThumbnailFailureKind::Decode),
+                failure("b.png", ThumbnailFailureKind::Missing),
+            ])
+            .unwrap();
+        }
+        assert!(db.get_corrupt_images().unwrap().is_empty());
+
+        db.record_thumbnail_failures(&[
+            failure("a.png", ThumbnailFailureKind::Decode),
+            failure("b.png", ThumbnailFailureKind::Missing),
+        ])
+        .unwrap();
+        let corrupt = db.get_corrupt_images().unwrap();
+        assert_eq!(corrupt.len(), 1);
+        assert_eq!(corrupt[0].filepath, "a.png");
+        assert!(corrupt[0].corrupt);
+
+        db.clear_thumbnail_failures(&["a.png".to_string()]).unwrap();
+        assert!(db.get_corrupt_images().unwrap().is_empty());
+    }
 }
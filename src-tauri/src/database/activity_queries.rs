@@ -0,0 +1,164 @@
+use super::private_queries::directory_prefix;
+use super::*;
+
+/// Entries older than this are dropped as new ones are recorded.
+const ACTIVITY_RETENTION_DAYS: i64 = 365;
+/// Entries returned when the filter sets no limit.
+const DEFAULT_ACTIVITY_LIMIT: u32 = 200;
+const MAX_ACTIVITY_LIMIT: u32 = 5_000;
+
+impl Database {
+    // ─────────────────────────── Activity log ───────────────────────────
+
+    /// Appends an event with the files it touched, pruning entries past the
+    /// retention window. Returns the new entry's id.
+    pub fn record_activity(
+        &self,
+        kind: ActivityKind,
+        summary: &str,
+        detail: &serde_json::Value,
+        filepaths: &[String],
+    ) -> SqlResult<i64> {
+        let occurred_at = chrono::Utc::now().timestamp();
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        let id: i64 = tx.query_row(
+            "INSERT INTO activity_log (occurred_at, kind, summary, detail)
+             VALUES (?1, ?2, ?3, ?4) RETURNING id",
+            params![occurred_at, kind.as_str(), summary, detail.to_string()],
+            |row| row.get(0),
+        )?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR IGNORE INTO activity_files (entry_id, filepath) VALUES (?1, ?2)",
+            )?;
+            for filepath in filepaths {
+                stmt.execute(params![id, filepath])?;
+            }
+        }
+        tx.execute(
+            "DELETE FROM activity_log WHERE occurred_at < ?1",
+            params![occurred_at - ACTIVITY_RETENTION_DAYS * 86_400],
+        )?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Entries matching `filter`, newest first. Files under a locked private
+    /// folder can't be looked up.
+    pub fn get_activity_log(&self, filter: &ActivityFilter) -> SqlResult<Vec<ActivityEntry>> {
+        let mut sql = String::from(
+            "SELECT id, occurred_at, kind, summary, detail,
+                    (SELECT COUNT(*) FROM activity_files WHERE entry_id = activity_log.id)
+             FROM activity_log WHERE 1 = 1",
+        );
+        let mut params_vec = Vec::new();
+        if !filter.kinds.is_empty() {
+            let kinds: Vec<&str> = filter.kinds.iter().map(|kind| kind.as_str()).collect();
+            sql.push_str(" AND kind IN (SELECT value FROM json_each(?))");
+            params_vec.push(Value::Text(
+                serde_json::to_string(&kinds).unwrap_or_default(),
+            ));
+        }
+        if let Some(since) = filter.since {
+            sql.push_str(" AND occurred_at >= ?");
+            params_vec.push(Value::Integer(since));
+        }
+        if let Some(until) = filter.until {
+            sql.push_str(" AND occurred_at <= ?");
+            params_vec.push(Value::Integer(until));
+        }
+        if let Some(search) = filter
+            .search
+            .as_deref()
+            .map(str::trim)
+            .filter(|search| !search.is_empty())
+        {
+            sql.push_str(" AND instr(lower(summary), ?) > 0");
+            params_vec.push(Value::Text(search.to_lowercase()));
+        }
+        if !filter.filepaths.is_empty() {
+            let filepaths: Vec<&String> = filter
+                .filepaths
+                .iter()
+                .filter(|filepath| !self.is_private_path(filepath))
+                .collect();
+            sql.push_str(
+                " AND EXISTS(SELECT 1 FROM activity_files
+                             WHERE entry_id = activity_log.id
+                               AND filepath IN (SELECT value FROM json_each(?)))",
+            );
+            params_vec.push(Value::Text(
+                serde_json::to_string(&filepaths).unwrap_or_default(),
+            ));
+        }
+        if let Some(directory) = filter
+            .directory
+            .as_deref()
+            .map(str::trim)
+            .filter(|directory| !directory.is_empty())
+        {
+            let prefix = directory_prefix(directory);
+            if self.is_private_path(&prefix) {
+                return Ok(Vec::new());
+            }
+            sql.push_str(
+                " AND EXISTS(SELECT 1 FROM activity_files
+                             WHERE entry_id = activity_log.id
+                               AND substr(REPLACE(filepath, char(92), '/'), 1, ?) = ?)",
+            );
+            params_vec.push(Value::Integer(prefix.chars().count() as i64));
+            params_vec.push(Value::Text(prefix));
+        }
+        sql.push_str(" ORDER BY occurred_at DESC, id DESC LIMIT ?");
+        params_vec.push(Value::Integer(
+            filter
+                .limit
+                .unwrap_or(DEFAULT_ACTIVITY_LIMIT)
+                .clamp(1, MAX_ACTIVITY_LIMIT) as i64,
+        ));
+
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, u32>(5)?,
+            ))
+        })?;
+        let mut entries = Vec::new();
+        for row in rows {
+            let (id, occurred_at, kind, summary, detail, file_count) = row?;
+            let Some(kind) = ActivityKind::parse(&kind) else {
+                continue;
+            };
+            entries.push(ActivityEntry {
+                id,
+                occurred_at,
+                kind,
+                summary,
+                detail: serde_json::from_str(&detail).unwrap_or_default(),
+                file_count,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Files an entry touched, by path, leaving out locked private ones.
+    pub fn get_activity_files(&self, entry_id: i64) -> SqlResult<Vec<String>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn
+            .prepare("SELECT filepath FROM activity_files WHERE entry_id = ?1 ORDER BY filepath")?;
+        let rows = stmt.query_map(params![entry_id], |row| row.get::<_, String>(0))?;
+        let mut filepaths = Vec::new();
+        for row in rows {
+            filepaths.push(row?);
+        }
+        self.retain_public(&mut filepaths, |filepath| filepath);
+        Ok(filepaths)
+    }
+}
@@ -0,0 +1,78 @@
+use super::*;
+
+impl Database {
+    // ────────────────────────── Archive ──────────────────────────
+
+    /// Flags images as archived and records where their files went.
+    /// `filepath` is left alone so thumbnails and search keep working.
+    pub fn mark_archived(&self, archived: &[ArchivedImage]) -> SqlResult<usize> {
+        if archived.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        let mut updated = 0usize;
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE images SET archived = 1, archive_path = ?1, archive_entry = ?2
+                 WHERE id = ?3",
+            )?;
+            for image in archived {
+                updated +=
+                    stmt.execute(params![image.archive_path, image.archive_entry, image.id])?;
+            }
+        }
+        tx.commit()?;
+        let ids: Vec<i64> = archived.iter().map(|image| image.id).collect();
+        self.invalidate_records(&ids);
+        Ok(updated)
+    }
+
+    pub fn clear_archived(&self, ids: &[i64]) -> SqlResult<usize> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let conn = self.pool.get().map_err(pool_error)?;
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!(
+            "UPDATE images SET archived = 0, archive_path = NULL, archive_entry = NULL
+             WHERE id IN ({})",
+            placeholders
+        );
+        let params: Vec<Value> = ids.iter().map(|id| Value::Integer(*id)).collect();
+        let updated = conn.execute(&sql, params_from_iter(params))?;
+        self.invalidate_records(ids);
+        Ok(updated)
+    }
+
+    /// Archive locations for the archived images among `ids`.
+    pub fn get_archived_images(&self, ids: &[i64]) -> SqlResult<Vec<ArchivedImage>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conn = self.pool.get().map_err(pool_error)?;
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!(
+            "SELECT id, filepath, archive_path, archive_entry FROM images
+             WHERE archived = 1 AND archive_path IS NOT NULL AND id IN ({})
+             ORDER BY id ASC",
+            placeholders
+        );
+        let params: Vec<Value> = ids.iter().map(|id| Value::Integer(*id)).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok(ArchivedImage {
+                id: row.get(0)?,
+                filepath: row.get(1)?,
+                archive_path: row.get(2)?,
+                archive_entry: row.get(3)?,
+            })
+        })?;
+        let mut archived = Vec::new();
+        for row in rows {
+            archived.push(row?);
+        }
+        self.retain_public(&mut archived, |image| &image.filepath);
+        Ok(archived)
+    }
+}
@@ -0,0 +1,131 @@
+use super::*;
+
+/// Largest gap between two neighbouring images of one batch run.
+const BATCH_GAP_SECS: i64 = 300;
+/// Same-parameter images considered around the requested one.
+const MAX_BATCH_CANDIDATES: u32 = 500;
+
+struct BatchCandidate {
+    record: GalleryImageRecord,
+    seed: Option<i64>,
+    counter: Option<u64>,
+    mtime: Option<i64>,
+}
+
+impl Database {
+    // ────────────────────────── Batch stacks ──────────────────────────
+
+    /// Images generated in the same batch run as `image_id`, in generation
+    /// order. Batch members share the directory and every generation
+    /// parameter and follow one another: consecutive seeds (Forge adds one
+    /// per image), or consecutive filename counters when seeds are missing,
+    /// saved at most a few minutes apart. Returns just the image when it has
+    /// no siblings and nothing when it is unknown or hidden.
+    pub fn get_batch_members(&self, image_id: i64) -> SqlResult<Vec<GalleryImageRecord>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = String::from(
+            "SELECT images.id, images.filepath, images.filename, images.directory,
+                    images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked,
+                    images.file_mtime
+             FROM images
+             JOIN images AS anchor ON anchor.id = ?
+             WHERE (images.id = anchor.id OR (
+                    anchor.prompt != ''
+                    AND images.directory = anchor.directory
+                    AND images.prompt = anchor.prompt
+                    AND images.negative_prompt = anchor.negative_prompt
+                    AND images.steps IS anchor.steps
+                    AND images.sampler IS anchor.sampler
+                    AND images.schedule_type IS anchor.schedule_type
+                    AND images.cfg_scale IS anchor.cfg_scale
+                    AND images.width IS anchor.width
+                    AND images.height IS anchor.height
+                    AND images.model_name IS anchor.model_name
+                    AND images.generation_type IS anchor.generation_type
+                    AND images.archived = anchor.archived))",
+        );
+        let mut params_vec = vec![Value::Integer(image_id)];
+        self.append_nsfw_filter(&mut sql, &mut params_vec);
+        self.append_private_filter(&mut sql, &mut params_vec);
+        sql.push_str(
+            " ORDER BY abs(COALESCE(images.file_mtime, 0) - COALESCE(anchor.file_mtime, 0)), images.id
+             LIMIT ?",
+        );
+        params_vec.push(Value::Integer(MAX_BATCH_CANDIDATES as i64));
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            let record = gallery_image_record_from_row(row)?;
+            Ok(BatchCandidate {
+                seed: record
+                    .seed
+                    .as_deref()
+                    .and_then(|seed| seed.trim().parse().ok()),
+                counter: filename_counter(&record.filename),
+                mtime: row.get(10)?,
+                record,
+            })
+        })?;
+        let mut candidates = Vec::new();
+        for row in rows {
+            candidates.push(row?);
+        }
+        let Some(anchor) = candidates
+            .iter()
+            .position(|candidate| candidate.record.id == image_id)
+        else {
+            return Ok(Vec::new());
+        };
+
+        // Walk outward from the image through sequential neighbours.
+        let mut in_batch = vec![false; candidates.len()];
+        in_batch[anchor] = true;
+        let mut pending = vec![anchor];
+        while let Some(current) = pending.pop() {
+            for next in 0..candidates.len() {
+                if !in_batch[next] && is_sequential(&candidates[current], &candidates[next]) {
+                    in_batch[next] = true;
+                    pending.push(next);
+                }
+            }
+        }
+
+        let mut members: Vec<BatchCandidate> = candidates
+            .into_iter()
+            .zip(in_batch)
+            .filter_map(|(candidate, member)| member.then_some(candidate))
+            .collect();
+        members.sort_by_key(|member| (member.seed, member.counter, member.mtime, member.record.id));
+        Ok(members.into_iter().map(|member| member.record).collect())
+    }
+}
+
+fn is_sequential(a: &BatchCandidate, b: &BatchCandidate) -> bool {
+    if let (Some(a_mtime), Some(b_mtime)) = (a.mtime, b.mtime) {
+        if (a_mtime - b_mtime).abs() > BATCH_GAP_SECS {
+            return false;
+        }
+    }
+    match (a.seed, b.seed) {
+        (Some(a_seed), Some(b_seed)) => a_seed.abs_diff(b_seed) == 1,
+        _ => matches!((a.counter, b.counter), (Some(x), Some(y)) if x.abs_diff(y) == 1),
+    }
+}
+
+/// The running number in names like `00012-1234.png` or `image_0012.png`.
+fn filename_counter(filename: &str) -> Option<u64> {
+    let stem = filename.rsplit_once('.').map_or(filename, |(stem, _)| stem);
+    let leading: String = stem.chars().take_while(char::is_ascii_digit).collect();
+    if !leading.is_empty() {
+        return leading.parse().ok();
+    }
+    let trailing: String = stem
+        .chars()
+        .rev()
+        .take_while(char::is_ascii_digit)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    trailing.parse().ok()
+}
@@ -32,13 +32,15 @@ impl Database {
         let mut conn = self.pool.get().map_err(pool_error)?;
         let tx = conn.transaction()?;
         let mut count = 0usize;
+        let mut written_ids = Vec::with_capacity(records.len());
         {
             let mut upsert_image_stmt = tx.prepare_cached(
                 "INSERT INTO images
                     (filepath, filename, directory, prompt, negative_prompt, steps, sampler,
                      schedule_type, cfg_scale, seed, width, height, model_hash, model_name,
-                     generation_type, raw_metadata, extra_params, file_mtime, file_size, quick_hash)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)
+                     generation_type, raw_metadata, extra_params, file_mtime, file_size, quick_hash,
+                     nsfw_score, model_family, workflow_text, source_tool, rating)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)
                  ON CONFLICT(filepath) DO UPDATE SET
                      filename=excluded.filename,
                      directory=excluded.directory,
@@ -58,7 +60,13 @@ impl Database {
                      extra_params=excluded.extra_params,
                      file_mtime=excluded.file_mtime,
                      file_size=excluded.file_size,
-                     quick_hash=excluded.quick_hash
+                     quick_hash=excluded.quick_hash,
+                     nsfw_score=excluded.nsfw_score,
+                     model_family=excluded.model_family,
+                     workflow_text=excluded.workflow_text,
+                     source_tool=excluded.source_tool,
+                     rating=excluded.rating,
+                     is_corrupt=0
                  RETURNING id",
             )?;
             let mut delete_image_tags_stmt =
@@ -103,10 +111,22 @@ impl Database {
                         record.file_mtime,
                         record.file_size,
                         record.quick_hash,
+                        nsfw::prompt_score(&record.params.prompt),
+                        model_family::detect_model_family(&record.params).unwrap_or_default(),
+                        workflow_search_text(
+                            &record.params.raw_metadata,
+                            record.workflow.as_deref(),
+                        ),
+                        record.params.source_tool.clone().unwrap_or_else(|| {
+                            infer_source_tool(&record.params.raw_metadata)
+                        }),
+                        record.rating,
                     ],
                     |row| row.get::<_, i64>(0),
                 )?;
 
+                written_ids.push(id);
+
                 // Replace tags within the same transaction
                 delete_image_tags_stmt.execute(params![id])?;
                 let mut seen_tags: HashSet<String> = HashSet::with_capacity(record.tags.len());
@@ -128,12 +148,21 @@ impl Database {
 
                     insert_image_tag_stmt.execute(params![id, tag_id])?;
                 }
+                lora_queries::replace_image_loras(&tx, id, &extract_loras(&record.params))?;
+                embedding_queries::replace_image_embeddings(
+                    &tx,
+                    id,
+                    &extract_embeddings(&record.params),
+                )?;
 
                 count += 1;
             }
         }
+        model_family::propagate_model_families_by_hash(&tx)?;
+        collection_queries::apply_collection_rules(&tx, &written_ids)?;
 
         tx.commit()?;
+        self.invalidate_records(&written_ids);
         Ok(count)
     }
 
@@ -155,12 +184,13 @@ impl Database {
             .clone()
             .unwrap_or_else(|| infer_generation_type(&params.raw_metadata));
 
-        conn.query_row(
+        let id = conn.query_row(
             "INSERT INTO images
                 (filepath, filename, directory, prompt, negative_prompt, steps, sampler,
                  schedule_type, cfg_scale, seed, width, height, model_hash, model_name,
-                 generation_type, raw_metadata, extra_params, file_mtime, file_size, quick_hash)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)
+                 generation_type, raw_metadata, extra_params, file_mtime, file_size, quick_hash,
+                 nsfw_score, model_family, workflow_text, source_tool)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24)
              ON CONFLICT(filepath) DO UPDATE SET
                  filename=excluded.filename,
                  directory=excluded.directory,
@@ -180,7 +210,12 @@ impl Database {
                  extra_params=excluded.extra_params,
                  file_mtime=excluded.file_mtime,
                  file_size=excluded.file_size,
-                 quick_hash=excluded.quick_hash
+                 quick_hash=excluded.quick_hash,
+                 nsfw_score=excluded.nsfw_score,
+                 model_family=excluded.model_family,
+                 workflow_text=excluded.workflow_text,
+                 source_tool=excluded.source_tool,
+                 is_corrupt=0
              RETURNING id",
             params![
                 filepath,
@@ -203,9 +238,21 @@ impl Database {
                 file_mtime,
                 Option::<i64>::None,
                 Option::<String>::None,
+                nsfw::prompt_score(&params.prompt),
+                model_family::detect_model_family(params).unwrap_or_default(),
+                workflow_search_text(&params.raw_metadata, None),
+                params
+                    .source_tool
+                    .clone()
+                    .unwrap_or_else(|| infer_source_tool(&params.raw_metadata)),
             ],
             |row| row.get::<_, i64>(0),
-        )
+        )?;
+        lora_queries::replace_image_loras(&conn, id, &extract_loras(params))?;
+        embedding_queries::replace_image_embeddings(&conn, id, &extract_embeddings(params))?;
+        collection_queries::apply_collection_rules(&conn, &[id])?;
+        self.invalidate_records(&[id]);
+        Ok(id)
     }
 
     /// Replaces image tags atomically.
@@ -239,6 +286,7 @@ impl Database {
                 insert_stmt.execute(params![image_id, tag_id])?;
             }
         }
+        collection_queries::apply_collection_rules(&tx, &[image_id])?;
 
         tx.commit()?;
         Ok(())
@@ -266,6 +314,7 @@ impl Database {
         )?;
 
         tx.commit()?;
+        self.invalidate_records(ids);
         Ok(deleted)
     }
 
@@ -302,6 +351,8 @@ impl Database {
             "UPDATE images SET is_favorite = ?1 WHERE id = ?2",
             params![is_favorite, image_id],
         )?;
+        collection_queries::apply_collection_rules(&conn, &[image_id])?;
+        self.invalidate_records(&[image_id]);
         Ok(())
     }
 
@@ -319,7 +370,10 @@ impl Database {
         let mut params: Vec<Value> = Vec::with_capacity(ids.len() + 1);
         params.push(Value::Integer(if is_favorite { 1 } else { 0 }));
         params.extend(ids.iter().map(|id| Value::Integer(*id)));
-        conn.execute(&sql, params_from_iter(params))
+        let updated = conn.execute(&sql, params_from_iter(params))?;
+        collection_queries::apply_collection_rules(&conn, ids)?;
+        self.invalidate_records(ids);
+        Ok(updated)
     }
 
     pub fn set_image_locked(&self, image_id: i64, is_locked: bool) -> SqlResult<()> {
@@ -328,6 +382,7 @@ impl Database {
             "UPDATE images SET is_locked = ?1 WHERE id = ?2",
             params![is_locked, image_id],
         )?;
+        self.invalidate_records(&[image_id]);
         Ok(())
     }
 
@@ -345,7 +400,9 @@ impl Database {
         let mut params: Vec<Value> = Vec::with_capacity(ids.len() + 1);
         params.push(Value::Integer(if is_locked { 1 } else { 0 }));
         params.extend(ids.iter().map(|id| Value::Integer(*id)));
-        conn.execute(&sql, params_from_iter(params))
+        let updated = conn.execute(&sql, params_from_iter(params))?;
+        self.invalidate_records(ids);
+        Ok(updated)
     }
 
     pub fn update_image_location(
@@ -362,6 +419,7 @@ impl Database {
              WHERE id = ?4",
             params![filepath, filename, directory, image_id],
         )?;
+        self.invalidate_records(&[image_id]);
         Ok(updated > 0)
     }
 }
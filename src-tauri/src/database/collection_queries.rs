@@ -0,0 +1,159 @@
+use super::*;
+
+/// Stored rules, skipping any that no longer parse.
+fn load_rules(conn: &Connection) -> SqlResult<Vec<(i64, CollectionRule)>> {
+    let mut stmt = conn.prepare_cached("SELECT id, name, rule FROM collections")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    let mut rules = Vec::new();
+    for row in rows {
+        let (id, name, rule) = row?;
+        match serde_json::from_str::<CollectionRule>(&rule) {
+            Ok(rule) => rules.push((id, rule)),
+            Err(err) => log::warn!(
+                "Skipping collection '{}' with unreadable rule: {}",
+                name,
+                err
+            ),
+        }
+    }
+    Ok(rules)
+}
+
+/// Re-checks `rule` for `image_ids`, or for every image when `None`.
+fn fill_collection(
+    conn: &Connection,
+    collection_id: i64,
+    rule: &CollectionRule,
+    image_ids: Option<&str>,
+) -> SqlResult<()> {
+    let mut params_vec = vec![Value::Integer(collection_id)];
+    let mut delete_sql = String::from("DELETE FROM collection_images WHERE collection_id = ?");
+    let mut insert_sql = String::from(
+        "INSERT OR IGNORE INTO collection_images (collection_id, image_id)
+         SELECT ?, images.id FROM images WHERE ",
+    );
+    if let Some(image_ids) = image_ids {
+        delete_sql.push_str(" AND image_id IN (SELECT value FROM json_each(?))");
+        insert_sql.push_str("images.id IN (SELECT value FROM json_each(?)) AND ");
+        params_vec.push(Value::Text(image_ids.to_string()));
+    }
+    conn.execute(&delete_sql, params_from_iter(params_vec.iter()))?;
+    insert_sql.push_str(&rule.to_sql(&mut params_vec));
+    conn.execute(&insert_sql, params_from_iter(params_vec))?;
+    Ok(())
+}
+
+/// Brings every collection up to date for images that were just written,
+/// adding the ones that now match and dropping the ones that stopped.
+pub(super) fn apply_collection_rules(conn: &Connection, image_ids: &[i64]) -> SqlResult<()> {
+    if image_ids.is_empty() {
+        return Ok(());
+    }
+    let rules = load_rules(conn)?;
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let image_ids = serde_json::to_string(image_ids).unwrap_or_default();
+    for (id, rule) in rules {
+        fill_collection(conn, id, &rule, Some(&image_ids))?;
+    }
+    Ok(())
+}
+
+impl Database {
+    // ─────────────────────────── Collections ───────────────────────────
+
+    /// Creates a collection, or replaces the name and rule of collection
+    /// `id`, and fills it from the whole library. Returns its id.
+    pub fn save_collection(
+        &self,
+        id: Option<i64>,
+        name: &str,
+        rule: &CollectionRule,
+    ) -> SqlResult<i64> {
+        let rule_json = serde_json::to_string(rule).unwrap_or_default();
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        let id = match id {
+            Some(id) => {
+                let updated = tx.execute(
+                    "UPDATE collections SET name = ?1, rule = ?2 WHERE id = ?3",
+                    params![name, rule_json, id],
+                )?;
+                if updated == 0 {
+                    return Err(rusqlite::Error::QueryReturnedNoRows);
+                }
+                id
+            }
+            None => tx.query_row(
+                "INSERT INTO collections (name, rule, created_at) VALUES (?1, ?2, ?3)
+                 RETURNING id",
+                params![name, rule_json, chrono::Utc::now().timestamp()],
+                |row| row.get(0),
+            )?,
+        };
+        fill_collection(&tx, id, rule, None)?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Removes a collection; its images stay in the library.
+    pub fn delete_collection(&self, id: i64) -> SqlResult<bool> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        Ok(conn.execute("DELETE FROM collections WHERE id = ?1", params![id])? > 0)
+    }
+
+    /// Collections by name with how many visible images each holds.
+    pub fn get_collections(&self) -> SqlResult<Vec<Collection>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = String::from(
+            "SELECT collections.id, collections.name, collections.rule, (
+                 SELECT COUNT(*) FROM collection_images
+                 JOIN images ON images.id = collection_images.image_id
+                 WHERE collection_images.collection_id = collections.id
+                   AND images.archived = 0",
+        );
+        let mut params_vec = Vec::new();
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+        sql.push_str(") FROM collections ORDER BY collections.name");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, u32>(3)?,
+            ))
+        })?;
+        let mut collections = Vec::new();
+        for row in rows {
+            let (id, name, rule, count) = row?;
+            collections.push(Collection {
+                id,
+                name,
+                rule: serde_json::from_str(&rule).unwrap_or_default(),
+                count,
+            });
+        }
+        Ok(collections)
+    }
+
+    /// Stores a sidecar rating change and re-checks the image's
+    /// collections.
+    pub fn set_image_rating(&self, image_id: i64, rating: Option<u8>) -> SqlResult<()> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        conn.execute(
+            "UPDATE images SET rating = ?1 WHERE id = ?2",
+            params![rating, image_id],
+        )?;
+        apply_collection_rules(&conn, &[image_id])
+    }
+}
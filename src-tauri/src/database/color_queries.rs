@@ -0,0 +1,101 @@
+use super::*;
+use crate::color_stats::ColorStats;
+
+impl Database {
+    // ────────────────────────── Colour stats ──────────────────────────
+
+    /// Saves thumbnail colour stats by source filepath. Paths that are not
+    /// indexed (yet) are ignored.
+    pub fn set_color_stats(&self, stats: &[(String, ColorStats)]) -> SqlResult<usize> {
+        if stats.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        let mut updated = 0;
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE images SET color_hue = ?1, color_saturation = ?2, color_brightness = ?3
+                 WHERE filepath = ?4",
+            )?;
+            for (filepath, color) in stats {
+                updated += stmt.execute(params![
+                    color.hue.map(f64::from),
+                    color.saturation as f64,
+                    color.brightness as f64,
+                    filepath
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    /// Filepaths of images whose colour stats have not been computed.
+    pub fn filepaths_missing_color_stats(&self) -> SqlResult<HashSet<String>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare(
+            "SELECT filepath FROM images WHERE color_brightness IS NULL AND archived = 0",
+        )?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut filepaths = HashSet::new();
+        for row in rows {
+            filepaths.insert(row?);
+        }
+        Ok(filepaths)
+    }
+
+    /// Images whose colour stats lie within `tolerance` of `target` (see
+    /// [`ColorStats::distance`]), closest first. Images without stats yet,
+    /// archived or in hidden folders are left out.
+    pub fn search_by_color(
+        &self,
+        target: &ColorStats,
+        tolerance: f32,
+        limit: u32,
+    ) -> SqlResult<Vec<ColorMatch>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        // A brightness gap alone puts images at least `gap / sqrt(2)` away.
+        let max_brightness_gap = (tolerance * std::f32::consts::SQRT_2) as f64;
+        let mut sql = format!(
+            "SELECT {GALLERY_COLUMNS}, images.color_hue, images.color_saturation, images.color_brightness
+             FROM images
+             WHERE images.archived = 0
+               AND images.color_brightness BETWEEN ? AND ?"
+        );
+        let mut params_vec = vec![
+            Value::Real(target.brightness as f64 - max_brightness_gap),
+            Value::Real(target.brightness as f64 + max_brightness_gap),
+        ];
+        append_hidden_directory_filter(&mut sql, false);
+        self.append_nsfw_filter(&mut sql, &mut params_vec);
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            let record = gallery_image_record_from_row(row)?;
+            let stats = ColorStats {
+                hue: row.get::<_, Option<f64>>(10)?.map(|hue| hue as f32),
+                saturation: row.get::<_, Option<f64>>(11)?.unwrap_or(0.0) as f32,
+                brightness: row.get::<_, f64>(12)? as f32,
+            };
+            Ok((record, stats))
+        })?;
+        let mut matches = Vec::new();
+        for row in rows {
+            let (record, stats) = row?;
+            let distance = target.distance(&stats);
+            if distance <= tolerance {
+                matches.push(ColorMatch { record, distance });
+            }
+        }
+        matches.sort_by(|a, b| {
+            a.distance
+                .total_cmp(&b.distance)
+                .then_with(|| b.record.id.cmp(&a.record.id))
+        });
+        matches.truncate(limit as usize);
+        Ok(matches)
+    }
+}
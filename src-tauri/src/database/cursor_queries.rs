@@ -5,15 +5,54 @@ impl Database {
 
     /// Gets images using keyset (cursor) pagination -- O(1) at any depth.
     /// Supports optional sort_by field for different orderings.
-    pub fn get_images_cursor(
+    pub fn get_images_cursor(&self, options: CursorQueryOptions<'_>) -> SqlResult<CursorPage> {
+        self.images_cursor_where(options, "", Vec::new())
+    }
+
+    /// Like [`Database::get_images_cursor`], limited to images using one
+    /// LoRA, optionally within a weight range.
+    pub fn get_lora_images_cursor(&self, params: LoraCursorParams<'_>) -> SqlResult<CursorPage> {
+        let mut clause =
+            String::from(" AND images.id IN (SELECT image_id FROM image_loras WHERE name = ?");
+        let mut clause_params = vec![Value::Text(params.lora.trim().to_ascii_lowercase())];
+        if let Some(min_weight) = params.min_weight {
+            clause.push_str(" AND weight >= ?");
+            clause_params.push(Value::Real(min_weight));
+        }
+        if let Some(max_weight) = params.max_weight {
+            clause.push_str(" AND weight <= ?");
+            clause_params.push(Value::Real(max_weight));
+        }
+        clause.push(')');
+        self.images_cursor_where(params.options, &clause, clause_params)
+    }
+
+    /// Cursor listing of all images matching the usual filters plus
+    /// `extra_clause` (an `AND ...` fragment) bound to `extra_params`.
+    fn images_cursor_where(
         &self,
-        cursor: Option<&str>,
-        limit: u32,
-        sort_by: Option<&str>,
-        generation_types: Option<&[String]>,
-        model_filter: Option<&str>,
-        model_family_filters: Option<&[String]>,
+        options: CursorQueryOptions<'_>,
+        extra_clause: &str,
+        extra_params: Vec<Value>,
     ) -> SqlResult<CursorPage> {
+        let CursorQueryOptions {
+            cursor,
+            limit,
+            sort_by,
+            generation_types,
+            model_filter,
+            model_family_filters,
+            scheduler_filters,
+            resolution_buckets,
+            source_tools,
+            workflow_statuses,
+            prompt_clusters,
+            collections,
+            max_sharpness,
+            failed_renders_only,
+            include_hidden,
+            collapse_duplicates,
+        } = options;
         let conn = self.pool.get().map_err(pool_error)?;
         let sort = SortConfig::from_str(sort_by.unwrap_or("newest"));
         let normalized_generation_types = normalize_generation_types(generation_types);
@@ -29,24 +68,25 @@ impl Database {
                 .map(|sort_value| sort_value.to_string())
         });
 
-        let mut sql = if sort.field == "id" {
-            String::from(
-                "SELECT id, filepath, filename, directory, seed, width, height, model_name, is_favorite, is_locked
-                 FROM images
-                 WHERE 1=1",
-            )
-        } else {
-            format!(
-                "SELECT id, filepath, filename, directory, seed, width, height, model_name, is_favorite, is_locked, {} AS sort_value
-                 FROM images
-                 WHERE 1=1",
-                sort.sort_expr()
-            )
-        };
-        let mut par = Vec::<Value>::new();
+        let mut sql = format!(" FROM images WHERE 1=1{extra_clause}");
+        let mut par = extra_params;
         append_generation_type_filter(&mut sql, &mut par, &normalized_generation_types);
         append_model_filter(&mut sql, &mut par, model_filter, None);
         append_model_family_filter(&mut sql, &mut par, &normalized_model_family_filters, None);
+        append_scheduler_filter(&mut sql, &mut par, scheduler_filters, None);
+        append_resolution_bucket_filter(&mut sql, &mut par, resolution_buckets, None);
+        append_source_tool_filter(&mut sql, &mut par, source_tools, None);
+        append_workflow_status_filter(&mut sql, &mut par, workflow_statuses, None);
+        append_prompt_cluster_filter(&mut sql, &mut par, prompt_clusters, None);
+        append_collection_filter(&mut sql, &mut par, collections, None);
+        append_sharpness_filter(&mut sql, &mut par, max_sharpness, None);
+        append_failed_render_filter(&mut sql, failed_renders_only);
+        append_hidden_directory_filter(&mut sql, include_hidden);
+        self.append_nsfw_filter(&mut sql, &mut par);
+        self.append_private_filter(&mut sql, &mut par);
+        self.append_project_filter(&mut sql, &mut par);
+
+        sql = cursor_select_sql(&sort, &sql, collapse_duplicates);
 
         if let Some(cid) = cursor_id {
             if sort.field == "id" {
@@ -98,7 +138,11 @@ impl Database {
             })
         };
 
-        Ok(CursorPage { items, next_cursor })
+        Ok(CursorPage {
+            items,
+            next_cursor,
+            suggestions: Vec::new(),
+        })
     }
 
     /// Cursor-based search: tries porter first, falls back to trigram.
@@ -107,7 +151,8 @@ impl Database {
         if !porter.items.is_empty() {
             return Ok(porter);
         }
-        self.search_cursor_trigram(params)
+        let trigram = self.search_cursor_trigram(params)?;
+        self.with_search_suggestions(trigram, Some(params.query), params.options.cursor)
     }
 
     fn search_cursor_porter(&self, params: SearchCursorParams<'_>) -> SqlResult<CursorPage> {
@@ -119,6 +164,16 @@ impl Database {
             generation_types,
             model_filter,
             model_family_filters,
+            scheduler_filters,
+            resolution_buckets,
+            source_tools,
+            workflow_statuses,
+            prompt_clusters,
+            collections,
+            max_sharpness,
+            failed_renders_only,
+            include_hidden,
+            collapse_duplicates,
         } = params.options;
         let conn = self.pool.get().map_err(pool_error)?;
 
@@ -127,6 +182,7 @@ impl Database {
             return Ok(CursorPage {
                 items: Vec::new(),
                 next_cursor: None,
+                suggestions: Vec::new(),
             });
         }
 
@@ -144,24 +200,11 @@ impl Database {
         let normalized_generation_types = normalize_generation_types(generation_types);
         let normalized_model_family_filters = normalize_model_family_filters(model_family_filters);
         let mut params_vec = vec![Value::Text(sanitized)];
-        let mut sql = if sort.field == "id" {
-            String::from(
-                "SELECT images.id, images.filepath, images.filename, images.directory,
-                        images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked
-                 FROM images
-                 JOIN images_fts ON images.id = images_fts.rowid
-                 WHERE images_fts MATCH ?",
-            )
-        } else {
-            format!(
-                "SELECT images.id, images.filepath, images.filename, images.directory,
-                        images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked, {} AS sort_value
-                 FROM images
-                 JOIN images_fts ON images.id = images_fts.rowid
-                 WHERE images_fts MATCH ?",
-                sort.sort_expr()
-            )
-        };
+        let mut sql = String::from(
+            " FROM images
+             JOIN images_fts ON images.id = images_fts.rowid
+             WHERE images_fts MATCH ?",
+        );
         append_generation_type_filter(&mut sql, &mut params_vec, &normalized_generation_types);
         append_model_filter(&mut sql, &mut params_vec, model_filter, Some("images"));
         append_model_family_filter(
@@ -170,6 +213,25 @@ impl Database {
             &normalized_model_family_filters,
             Some("images"),
         );
+        append_scheduler_filter(&mut sql, &mut params_vec, scheduler_filters, Some("images"));
+        append_resolution_bucket_filter(
+            &mut sql,
+            &mut params_vec,
+            resolution_buckets,
+            Some("images"),
+        );
+        append_source_tool_filter(&mut sql, &mut params_vec, source_tools, Some("images"));
+        append_workflow_status_filter(&mut sql, &mut params_vec, workflow_statuses, Some("images"));
+        append_prompt_cluster_filter(&mut sql, &mut params_vec, prompt_clusters, Some("images"));
+        append_collection_filter(&mut sql, &mut params_vec, collections, Some("images"));
+        append_sharpness_filter(&mut sql, &mut params_vec, max_sharpness, Some("images"));
+        append_failed_render_filter(&mut sql, failed_renders_only);
+        append_hidden_directory_filter(&mut sql, include_hidden);
+        self.append_nsfw_filter(&mut sql, &mut params_vec);
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+
+        sql = cursor_select_sql(&sort, &sql, collapse_duplicates);
 
         if let Some(cid) = cursor_id {
             if sort.field == "id" {
@@ -205,7 +267,11 @@ impl Database {
             let next_cursor = items
                 .last()
                 .map(|last| serde_json::json!({"id": last.id}).to_string());
-            Ok(CursorPage { items, next_cursor })
+            Ok(CursorPage {
+                items,
+                next_cursor,
+                suggestions: Vec::new(),
+            })
         } else {
             let rows = stmt.query_map(params_from_iter(params_vec), |row| {
                 Ok((
@@ -226,7 +292,11 @@ impl Database {
                 serde_json::json!({"id": id, "sort": sort_value}).to_string()
             });
 
-            Ok(CursorPage { items, next_cursor })
+            Ok(CursorPage {
+                items,
+                next_cursor,
+                suggestions: Vec::new(),
+            })
         }
     }
 
@@ -239,6 +309,16 @@ impl Database {
             generation_types,
             model_filter,
             model_family_filters,
+            scheduler_filters,
+            resolution_buckets,
+            source_tools,
+            workflow_statuses,
+            prompt_clusters,
+            collections,
+            max_sharpness,
+            failed_renders_only,
+            include_hidden,
+            collapse_duplicates,
         } = params.options;
         let conn = self.pool.get().map_err(pool_error)?;
 
@@ -247,6 +327,7 @@ impl Database {
             return Ok(CursorPage {
                 items: Vec::new(),
                 next_cursor: None,
+                suggestions: Vec::new(),
             });
         }
 
@@ -265,24 +346,11 @@ impl Database {
         });
         let normalized_model_family_filters = normalize_model_family_filters(model_family_filters);
 
-        let mut sql = if sort.field == "id" {
-            String::from(
-                "SELECT images.id, images.filepath, images.filename, images.directory,
-                        images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked
-                 FROM images
-                 JOIN images_fts_tri ON images.id = images_fts_tri.rowid
-                 WHERE images_fts_tri MATCH ?",
-            )
-        } else {
-            format!(
-                "SELECT images.id, images.filepath, images.filename, images.directory,
-                        images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked, {} AS sort_value
-                 FROM images
-                 JOIN images_fts_tri ON images.id = images_fts_tri.rowid
-                 WHERE images_fts_tri MATCH ?",
-                sort.sort_expr()
-            )
-        };
+        let mut sql = String::from(
+            " FROM images
+             JOIN images_fts_tri ON images.id = images_fts_tri.rowid
+             WHERE images_fts_tri MATCH ?",
+        );
         let mut params_vec = vec![Value::Text(match_expr)];
         append_generation_type_filter(&mut sql, &mut params_vec, &normalized_generation_types);
         append_model_filter(&mut sql, &mut params_vec, model_filter, Some("images"));
@@ -292,6 +360,25 @@ impl Database {
             &normalized_model_family_filters,
             Some("images"),
         );
+        append_scheduler_filter(&mut sql, &mut params_vec, scheduler_filters, Some("images"));
+        append_resolution_bucket_filter(
+            &mut sql,
+            &mut params_vec,
+            resolution_buckets,
+            Some("images"),
+        );
+        append_source_tool_filter(&mut sql, &mut params_vec, source_tools, Some("images"));
+        append_workflow_status_filter(&mut sql, &mut params_vec, workflow_statuses, Some("images"));
+        append_prompt_cluster_filter(&mut sql, &mut params_vec, prompt_clusters, Some("images"));
+        append_collection_filter(&mut sql, &mut params_vec, collections, Some("images"));
+        append_sharpness_filter(&mut sql, &mut params_vec, max_sharpness, Some("images"));
+        append_failed_render_filter(&mut sql, failed_renders_only);
+        append_hidden_directory_filter(&mut sql, include_hidden);
+        self.append_nsfw_filter(&mut sql, &mut params_vec);
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+        sql = cursor_select_sql(&sort, &sql, collapse_duplicates);
+
         if let Some(cid) = cursor_id {
             if sort.field == "id" {
                 sql.push_str(&format!(" AND images.id {} ?", sort.cursor_op()));
@@ -325,7 +412,11 @@ impl Database {
             let next_cursor = items
                 .last()
                 .map(|last| serde_json::json!({"id": last.id}).to_string());
-            Ok(CursorPage { items, next_cursor })
+            Ok(CursorPage {
+                items,
+                next_cursor,
+                suggestions: Vec::new(),
+            })
         } else {
             let rows = stmt.query_map(params_from_iter(params_vec), |row| {
                 Ok((
@@ -343,7 +434,11 @@ impl Database {
             let next_cursor = last_cursor.map(|(id, sort_value)| {
                 serde_json::json!({"id": id, "sort": sort_value}).to_string()
             });
-            Ok(CursorPage { items, next_cursor })
+            Ok(CursorPage {
+                items,
+                next_cursor,
+                suggestions: Vec::new(),
+            })
         }
     }
 
@@ -361,12 +456,16 @@ impl Database {
             return Ok(porter);
         }
 
-        self.filter_images_cursor_trigram(FilterCursorParams {
+        let trigram = self.filter_images_cursor_trigram(FilterCursorParams {
             query: Some(query),
             include_tags: params.include_tags,
             exclude_tags: params.exclude_tags,
+            refine: params.refine,
+            extra_params: params.extra_params,
+            embeddings: params.embeddings,
             options: params.options,
-        })
+        })?;
+        self.with_search_suggestions(trigram, Some(query), params.options.cursor)
     }
 
     fn filter_images_cursor_porter(&self, params: FilterCursorParams<'_>) -> SqlResult<CursorPage> {
@@ -380,6 +479,16 @@ impl Database {
             generation_types,
             model_filter,
             model_family_filters,
+            scheduler_filters,
+            resolution_buckets,
+            source_tools,
+            workflow_statuses,
+            prompt_clusters,
+            collections,
+            max_sharpness,
+            failed_renders_only,
+            include_hidden,
+            collapse_duplicates,
         } = params.options;
         let conn = self.pool.get().map_err(pool_error)?;
         let sort = SortConfig::from_str(sort_by.unwrap_or("newest"));
@@ -404,22 +513,7 @@ impl Database {
             ""
         };
 
-        let mut sql = if sort.field == "id" {
-            format!(
-                "SELECT images.id, images.filepath, images.filename, images.directory,
-                        images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked
-                 FROM images{}",
-                fts_join
-            )
-        } else {
-            format!(
-                "SELECT images.id, images.filepath, images.filename, images.directory,
-                        images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked, {} AS sort_value
-                 FROM images{}",
-                sort.sort_expr(),
-                fts_join
-            )
-        };
+        let mut sql = format!(" FROM images{}", fts_join);
 
         if let Some(q) = query {
             let sanitized = sanitize_fts_query(q);
@@ -427,6 +521,7 @@ impl Database {
                 return Ok(CursorPage {
                     items: Vec::new(),
                     next_cursor: None,
+                    suggestions: Vec::new(),
                 });
             }
             sql.push_str(" WHERE images_fts MATCH ?");
@@ -443,26 +538,30 @@ impl Database {
             &normalized_model_family_filters,
             Some("images"),
         );
-
-        for tag in include_tags {
-            sql.push_str(
-                " AND EXISTS (
-                    SELECT 1 FROM image_tags it JOIN tags t ON t.id = it.tag_id
-                    WHERE it.image_id = images.id AND t.tag = ?
-                )",
-            );
-            params_vec.push(Value::Text(tag.trim().to_ascii_lowercase()));
-        }
-
-        for tag in exclude_tags {
-            sql.push_str(
-                " AND NOT EXISTS (
-                    SELECT 1 FROM image_tags it JOIN tags t ON t.id = it.tag_id
-                    WHERE it.image_id = images.id AND t.tag = ?
-                )",
-            );
-            params_vec.push(Value::Text(tag.trim().to_ascii_lowercase()));
-        }
+        append_scheduler_filter(&mut sql, &mut params_vec, scheduler_filters, Some("images"));
+        append_resolution_bucket_filter(
+            &mut sql,
+            &mut params_vec,
+            resolution_buckets,
+            Some("images"),
+        );
+        append_source_tool_filter(&mut sql, &mut params_vec, source_tools, Some("images"));
+        append_workflow_status_filter(&mut sql, &mut params_vec, workflow_statuses, Some("images"));
+        append_prompt_cluster_filter(&mut sql, &mut params_vec, prompt_clusters, Some("images"));
+        append_collection_filter(&mut sql, &mut params_vec, collections, Some("images"));
+        append_sharpness_filter(&mut sql, &mut params_vec, max_sharpness, Some("images"));
+        append_failed_render_filter(&mut sql, failed_renders_only);
+        append_hidden_directory_filter(&mut sql, include_hidden);
+        self.append_nsfw_filter(&mut sql, &mut params_vec);
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+
+        append_tag_filters(&mut sql, &mut params_vec, include_tags, exclude_tags);
+        append_refinement_filter(&mut sql, &mut params_vec, params.refine);
+        append_extra_param_filters(&mut sql, &mut params_vec, params.extra_params);
+        append_embedding_filters(&mut sql, &mut params_vec, params.embeddings);
+
+        sql = cursor_select_sql(&sort, &sql, collapse_duplicates);
 
         if let Some(cid) = cursor_id {
             if sort.field == "id" {
@@ -498,7 +597,11 @@ impl Database {
             let next_cursor = items
                 .last()
                 .map(|last| serde_json::json!({"id": last.id}).to_string());
-            Ok(CursorPage { items, next_cursor })
+            Ok(CursorPage {
+                items,
+                next_cursor,
+                suggestions: Vec::new(),
+            })
         } else {
             let rows = stmt.query_map(params_from_iter(params_vec), |row| {
                 Ok((
@@ -516,7 +619,11 @@ impl Database {
             let next_cursor = last_cursor.map(|(id, sort_value)| {
                 serde_json::json!({"id": id, "sort": sort_value}).to_string()
             });
-            Ok(CursorPage { items, next_cursor })
+            Ok(CursorPage {
+                items,
+                next_cursor,
+                suggestions: Vec::new(),
+            })
         }
     }
 
@@ -534,6 +641,16 @@ impl Database {
             generation_types,
             model_filter,
             model_family_filters,
+            scheduler_filters,
+            resolution_buckets,
+            source_tools,
+            workflow_statuses,
+            prompt_clusters,
+            collections,
+            max_sharpness,
+            failed_renders_only,
+            include_hidden,
+            collapse_duplicates,
         } = params.options;
         let conn = self.pool.get().map_err(pool_error)?;
         let sanitized = query.trim();
@@ -541,6 +658,7 @@ impl Database {
             return Ok(CursorPage {
                 items: Vec::new(),
                 next_cursor: None,
+                suggestions: Vec::new(),
             });
         }
 
@@ -558,24 +676,11 @@ impl Database {
         let normalized_generation_types = normalize_generation_types(generation_types);
         let normalized_model_family_filters = normalize_model_family_filters(model_family_filters);
 
-        let mut sql = if sort.field == "id" {
-            String::from(
-                "SELECT images.id, images.filepath, images.filename, images.directory,
-                        images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked
-                 FROM images
-                 JOIN images_fts_tri ON images.id = images_fts_tri.rowid
-                 WHERE images_fts_tri MATCH ?",
-            )
-        } else {
-            format!(
-                "SELECT images.id, images.filepath, images.filename, images.directory,
-                        images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked, {} AS sort_value
-                 FROM images
-                 JOIN images_fts_tri ON images.id = images_fts_tri.rowid
-                 WHERE images_fts_tri MATCH ?",
-                sort.sort_expr()
-            )
-        };
+        let mut sql = String::from(
+            " FROM images
+             JOIN images_fts_tri ON images.id = images_fts_tri.rowid
+             WHERE images_fts_tri MATCH ?",
+        );
         let mut params_vec = vec![Value::Text(format!(
             "\"{}\"",
             sanitized.replace('"', "\"\"")
@@ -589,6 +694,30 @@ impl Database {
             &normalized_model_family_filters,
             Some("images"),
         );
+        append_scheduler_filter(&mut sql, &mut params_vec, scheduler_filters, Some("images"));
+        append_resolution_bucket_filter(
+            &mut sql,
+            &mut params_vec,
+            resolution_buckets,
+            Some("images"),
+        );
+        append_source_tool_filter(&mut sql, &mut params_vec, source_tools, Some("images"));
+        append_workflow_status_filter(&mut sql, &mut params_vec, workflow_statuses, Some("images"));
+        append_prompt_cluster_filter(&mut sql, &mut params_vec, prompt_clusters, Some("images"));
+        append_collection_filter(&mut sql, &mut params_vec, collections, Some("images"));
+        append_sharpness_filter(&mut sql, &mut params_vec, max_sharpness, Some("images"));
+        append_failed_render_filter(&mut sql, failed_renders_only);
+        append_hidden_directory_filter(&mut sql, include_hidden);
+        self.append_nsfw_filter(&mut sql, &mut params_vec);
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+
+        append_tag_filters(&mut sql, &mut params_vec, include_tags, exclude_tags);
+        append_refinement_filter(&mut sql, &mut params_vec, params.refine);
+        append_extra_param_filters(&mut sql, &mut params_vec, params.extra_params);
+        append_embedding_filters(&mut sql, &mut params_vec, params.embeddings);
+
+        sql = cursor_select_sql(&sort, &sql, collapse_duplicates);
 
         if let Some(cid) = cursor_id {
             if sort.field == "id" {
@@ -610,26 +739,6 @@ impl Database {
             }
         }
 
-        for tag in include_tags {
-            sql.push_str(
-                " AND EXISTS (
-                    SELECT 1 FROM image_tags it JOIN tags t ON t.id = it.tag_id
-                    WHERE it.image_id = images.id AND t.tag = ?
-                )",
-            );
-            params_vec.push(Value::Text(tag.trim().to_ascii_lowercase()));
-        }
-
-        for tag in exclude_tags {
-            sql.push_str(
-                " AND NOT EXISTS (
-                    SELECT 1 FROM image_tags it JOIN tags t ON t.id = it.tag_id
-                    WHERE it.image_id = images.id AND t.tag = ?
-                )",
-            );
-            params_vec.push(Value::Text(tag.trim().to_ascii_lowercase()));
-        }
-
         sql.push_str(&format!(" ORDER BY {} LIMIT ?", sort.order_clause()));
         params_vec.push(Value::Integer(limit as i64));
 
@@ -644,7 +753,11 @@ impl Database {
             let next_cursor = items
                 .last()
                 .map(|last| serde_json::json!({"id": last.id}).to_string());
-            Ok(CursorPage { items, next_cursor })
+            Ok(CursorPage {
+                items,
+                next_cursor,
+                suggestions: Vec::new(),
+            })
         } else {
             let rows = stmt.query_map(params_from_iter(params_vec), |row| {
                 Ok((
@@ -662,7 +775,123 @@ impl Database {
             let next_cursor = last_cursor.map(|(id, sort_value)| {
                 serde_json::json!({"id": id, "sort": sort_value}).to_string()
             });
-            Ok(CursorPage { items, next_cursor })
+            Ok(CursorPage {
+                items,
+                next_cursor,
+                suggestions: Vec::new(),
+            })
+        }
+    }
+
+    // ────────────────────── Random sampling ──────────────────────
+
+    /// Returns up to `options.limit` random images matching the filter set.
+    /// Cursor and sort options are ignored; each call draws a fresh sample.
+    pub fn sample_filtered_images(
+        &self,
+        params: FilterCursorParams<'_>,
+    ) -> SqlResult<Vec<GalleryImageRecord>> {
+        let query = params
+            .query
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        let Some(query) = query else {
+            return self.sample_filtered_images_with_match(params, None);
+        };
+
+        let sanitized = sanitize_fts_query(query);
+        if !sanitized.is_empty() {
+            let porter =
+                self.sample_filtered_images_with_match(params, Some(("images_fts", sanitized)))?;
+            if !porter.is_empty() {
+                return Ok(porter);
+            }
         }
+
+        if !contains_search_token(query) {
+            return Ok(Vec::new());
+        }
+        let match_expr = format!("\"{}\"", query.replace('"', "\"\""));
+        self.sample_filtered_images_with_match(params, Some(("images_fts_tri", match_expr)))
+    }
+
+    fn sample_filtered_images_with_match(
+        &self,
+        params: FilterCursorParams<'_>,
+        fts_match: Option<(&str, String)>,
+    ) -> SqlResult<Vec<GalleryImageRecord>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let options = params.options;
+        let normalized_generation_types = normalize_generation_types(options.generation_types);
+        let normalized_model_family_filters =
+            normalize_model_family_filters(options.model_family_filters);
+
+        let mut sql = String::from(
+            "SELECT images.id, images.filepath, images.filename, images.directory,
+                    images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked
+             FROM images",
+        );
+        let mut params_vec = Vec::<Value>::new();
+        if let Some((table, match_expr)) = fts_match {
+            sql.push_str(&format!(
+                " JOIN {table} ON images.id = {table}.rowid WHERE {table} MATCH ?"
+            ));
+            params_vec.push(Value::Text(match_expr));
+        } else {
+            sql.push_str(" WHERE 1=1");
+        }
+
+        append_generation_type_filter(&mut sql, &mut params_vec, &normalized_generation_types);
+        append_model_family_filter(
+            &mut sql,
+            &mut params_vec,
+            &normalized_model_family_filters,
+            Some("images"),
+        );
+        self.append_remaining_filters(&mut sql, &mut params_vec, params);
+
+        sql.push_str(" ORDER BY RANDOM() LIMIT ?");
+        params_vec.push(Value::Integer(options.limit as i64));
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), gallery_image_record_from_row)?;
+        let mut items = Vec::new();
+        for row in rows {
+            items.push(row?);
+        }
+        Ok(items)
+    }
+
+    /// Every filter of `params` except the query match, generation types and
+    /// model families, which callers handle in their own way.
+    pub(super) fn append_remaining_filters(
+        &self,
+        sql: &mut String,
+        params_vec: &mut Vec<Value>,
+        params: FilterCursorParams<'_>,
+    ) {
+        let options = params.options;
+        append_model_filter(sql, params_vec, options.model_filter, Some("images"));
+        append_scheduler_filter(sql, params_vec, options.scheduler_filters, Some("images"));
+        append_resolution_bucket_filter(
+            sql,
+            params_vec,
+            options.resolution_buckets,
+            Some("images"),
+        );
+        append_source_tool_filter(sql, params_vec, options.source_tools, Some("images"));
+        append_workflow_status_filter(sql, params_vec, options.workflow_statuses, Some("images"));
+        append_prompt_cluster_filter(sql, params_vec, options.prompt_clusters, Some("images"));
+        append_collection_filter(sql, params_vec, options.collections, Some("images"));
+        append_sharpness_filter(sql, params_vec, options.max_sharpness, Some("images"));
+        append_failed_render_filter(sql, options.failed_renders_only);
+        append_hidden_directory_filter(sql, options.include_hidden);
+        self.append_nsfw_filter(sql, params_vec);
+        self.append_private_filter(sql, params_vec);
+        self.append_project_filter(sql, params_vec);
+        append_tag_filters(sql, params_vec, params.include_tags, params.exclude_tags);
+        append_refinement_filter(sql, params_vec, params.refine);
+        append_extra_param_filters(sql, params_vec, params.extra_params);
+        append_embedding_filters(sql, params_vec, params.embeddings);
     }
 }
@@ -0,0 +1,143 @@
+use super::*;
+
+/// Most bits a larger copy's perceptual hash may differ by and still count
+/// as an upscale of the smaller image. Kept below 4 so that, split into
+/// four 16-bit quarters, a match shares at least one quarter exactly.
+const UPSCALE_PHASH_DISTANCE: u32 = 3;
+
+fn phash_quarters(phash: u64) -> impl Iterator<Item = (usize, u64)> {
+    (0..4).map(move |quarter| (quarter, (phash >> (quarter * 16)) & 0xffff))
+}
+
+impl Database {
+    // ───────────────────────── Deletion safety ─────────────────────────
+
+    /// Collections holding any of `image_ids`, by name, with how many of
+    /// them each holds.
+    pub fn collections_holding(&self, image_ids: &[i64]) -> SqlResult<Vec<CollectionMembership>> {
+        if image_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare(
+            "SELECT collections.id, collections.name, COUNT(*)
+             FROM collection_images
+             JOIN collections ON collections.id = collection_images.collection_id
+             WHERE collection_images.image_id IN (SELECT value FROM json_each(?1))
+             GROUP BY collections.id
+             ORDER BY collections.name",
+        )?;
+        let rows = stmt.query_map(
+            params![serde_json::to_string(image_ids).unwrap_or_default()],
+            |row| {
+                Ok(CollectionMembership {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    count: row.get(2)?,
+                })
+            },
+        )?;
+        let mut collections = Vec::new();
+        for row in rows {
+            collections.push(row?);
+        }
+        Ok(collections)
+    }
+
+    /// How many of `image_ids` belong to at least one collection.
+    pub fn count_images_in_collections(&self, image_ids: &[i64]) -> SqlResult<usize> {
+        if image_ids.is_empty() {
+            return Ok(0);
+        }
+        let conn = self.pool.get().map_err(pool_error)?;
+        conn.query_row(
+            "SELECT COUNT(DISTINCT image_id) FROM collection_images
+             WHERE image_id IN (SELECT value FROM json_each(?1))",
+            params![serde_json::to_string(image_ids).unwrap_or_default()],
+            |row| row.get(0),
+        )
+    }
+
+    /// Those of `image_ids` that another indexed image, outside the list,
+    /// was made from: an img2img, inpaint or upscale pass sharing the
+    /// image's prompt and seed, or a larger copy whose perceptual hash is
+    /// within [`UPSCALE_PHASH_DISTANCE`] bits. Ascending.
+    pub fn lineage_parents(&self, image_ids: &[i64]) -> SqlResult<Vec<i64>> {
+        if image_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids_json = serde_json::to_string(image_ids).unwrap_or_default();
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut parents = HashSet::new();
+
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT parent.id
+             FROM images AS parent
+             JOIN images AS child
+               ON child.seed = parent.seed AND child.prompt = parent.prompt
+             WHERE parent.id IN (SELECT value FROM json_each(?1))
+               AND TRIM(COALESCE(parent.seed, '')) NOT IN ('', '-1')
+               AND COALESCE(parent.generation_type, '') != 'upscale'
+               AND child.generation_type IN ('img2img', 'inpaint', 'upscale')
+               AND child.archived = 0
+               AND child.id NOT IN (SELECT value FROM json_each(?1))",
+        )?;
+        let rows = stmt.query_map(params![ids_json], |row| row.get::<_, i64>(0))?;
+        for row in rows {
+            parents.insert(row?);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT id, phash, COALESCE(width, 0) * COALESCE(height, 0) FROM images
+             WHERE id IN (SELECT value FROM json_each(?1)) AND phash IS NOT NULL",
+        )?;
+        let rows = stmt.query_map(params![ids_json], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)? as u64,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+        let mut hashed = Vec::new();
+        for row in rows {
+            let (id, phash, area) = row?;
+            if !parents.contains(&id) {
+                hashed.push((id, phash, area));
+            }
+        }
+        if !hashed.is_empty() {
+            // Candidates are only compared with images sharing a quarter.
+            let mut by_quarter: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+            for (index, (_, phash, _)) in hashed.iter().enumerate() {
+                for quarter in phash_quarters(*phash) {
+                    by_quarter.entry(quarter).or_default().push(index);
+                }
+            }
+            let mut stmt = conn.prepare(
+                "SELECT phash, COALESCE(width, 0) * COALESCE(height, 0) FROM images
+                 WHERE phash IS NOT NULL AND archived = 0
+                   AND id NOT IN (SELECT value FROM json_each(?1))",
+            )?;
+            let rows = stmt.query_map(params![ids_json], |row| {
+                Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i64>(1)?))
+            })?;
+            for row in rows {
+                let (phash, area) = row?;
+                for quarter in phash_quarters(phash) {
+                    for &index in by_quarter.get(&quarter).into_iter().flatten() {
+                        let (id, parent_phash, parent_area) = hashed[index];
+                        if area > parent_area
+                            && (phash ^ parent_phash).count_ones() <= UPSCALE_PHASH_DISTANCE
+                        {
+                            parents.insert(id);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut parents: Vec<i64> = parents.into_iter().collect();
+        parents.sort_unstable();
+        Ok(parents)
+    }
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+impl Database {
+    // ────────────────────────── Directory metadata ──────────────────────────
+
+    pub fn set_directory_pinned(&self, directory: &str, pinned: bool) -> SqlResult<()> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        conn.execute(
+            "INSERT INTO directory_meta (directory, pinned) VALUES (?1, ?2)
+             ON CONFLICT(directory) DO UPDATE SET pinned = excluded.pinned",
+            params![directory, pinned],
+        )?;
+        prune_directory_meta(&conn, directory)
+    }
+
+    /// Hides `directory` from the gallery; cursor queries skip it unless
+    /// `include_hidden` is set.
+    pub fn set_directory_hidden(&self, directory: &str, hidden: bool) -> SqlResult<()> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        conn.execute(
+            "INSERT INTO directory_meta (directory, hidden) VALUES (?1, ?2)
+             ON CONFLICT(directory) DO UPDATE SET hidden = excluded.hidden",
+            params![directory, hidden],
+        )?;
+        prune_directory_meta(&conn, directory)
+    }
+
+    /// Sets the display alias for `directory`; `None` shows the path again.
+    pub fn set_directory_alias(&self, directory: &str, alias: Option<&str>) -> SqlResult<()> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        conn.execute(
+            "INSERT INTO directory_meta (directory, alias) VALUES (?1, ?2)
+             ON CONFLICT(directory) DO UPDATE SET alias = excluded.alias",
+            params![directory, alias],
+        )?;
+        prune_directory_meta(&conn, directory)
+    }
+}
+
+/// Drops the row once it is back to the defaults.
+fn prune_directory_meta(conn: &Connection, directory: &str) -> SqlResult<()> {
+    conn.execute(
+        "DELETE FROM directory_meta WHERE directory = ?1 AND pinned = 0 AND hidden = 0 AND alias IS NULL",
+        params![directory],
+    )?;
+    Ok(())
+}
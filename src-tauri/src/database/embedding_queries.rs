@@ -0,0 +1,108 @@
+use super::stats_queries::IMAGE_TIMESTAMP;
+use super::*;
+use crate::parser::{extract_embeddings, EmbeddingUse};
+
+/// Replaces the embeddings recorded for `image_id`. Runs inside the
+/// caller's transaction.
+pub(super) fn replace_image_embeddings(
+    conn: &Connection,
+    image_id: i64,
+    embeddings: &[EmbeddingUse],
+) -> SqlResult<()> {
+    conn.prepare_cached("DELETE FROM image_embeddings_used WHERE image_id = ?1")?
+        .execute(params![image_id])?;
+    let mut insert_stmt = conn.prepare_cached(
+        "INSERT OR IGNORE INTO image_embeddings_used(image_id, name, negative)
+         VALUES (?1, ?2, ?3)",
+    )?;
+    for embedding in embeddings {
+        insert_stmt.execute(params![image_id, embedding.name, embedding.negative])?;
+    }
+    Ok(())
+}
+
+/// `extra_params` as stored, back into the map the parser reads `TI hashes`
+/// from. Missing or malformed JSON reads as empty.
+pub(super) fn stored_extra_params(json: Option<String>) -> HashMap<String, String> {
+    json.and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+impl Database {
+    // ─────────────────────────── Embeddings ───────────────────────────
+
+    /// Fills `image_embeddings_used` for rows indexed before the table
+    /// existed.
+    pub(super) fn backfill_image_embeddings(conn: &Connection) -> SqlResult<()> {
+        let mut select_stmt = conn.prepare(
+            "SELECT id, prompt, negative_prompt, extra_params FROM images
+             WHERE prompt LIKE '%embedding:%' OR negative_prompt LIKE '%embedding:%'
+                OR extra_params LIKE '%TI hashes%'",
+        )?;
+        let rows = select_stmt.query_map([], |row| {
+            let params = GenerationParams {
+                prompt: row.get(1)?,
+                negative_prompt: row.get(2)?,
+                extra_params: stored_extra_params(row.get(3)?),
+                ..Default::default()
+            };
+            Ok((row.get::<_, i64>(0)?, extract_embeddings(&params)))
+        })?;
+        let mut updates = Vec::<(i64, Vec<EmbeddingUse>)>::new();
+        for row in rows {
+            let (id, embeddings) = row?;
+            if !embeddings.is_empty() {
+                updates.push((id, embeddings));
+            }
+        }
+
+        if updates.is_empty() {
+            return Ok(());
+        }
+        let tx = conn.unchecked_transaction()?;
+        for (id, embeddings) in &updates {
+            replace_image_embeddings(&tx, *id, embeddings)?;
+        }
+        tx.commit()
+    }
+
+    /// Every embedding with how many images use it, split by prompt side,
+    /// most used first. Locked private folders are left out.
+    pub fn get_embedding_usage(&self) -> SqlResult<Vec<EmbeddingUsage>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = format!(
+            "SELECT image_embeddings_used.name,
+                    COUNT(DISTINCT image_embeddings_used.image_id),
+                    SUM(image_embeddings_used.negative = 0),
+                    SUM(image_embeddings_used.negative = 1),
+                    date(MAX({IMAGE_TIMESTAMP}), 'unixepoch')
+             FROM image_embeddings_used
+             JOIN images ON images.id = image_embeddings_used.image_id
+             WHERE 1=1"
+        );
+        let mut params_vec: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+        sql.push_str(
+            " GROUP BY image_embeddings_used.name
+              ORDER BY COUNT(DISTINCT image_embeddings_used.image_id) DESC,
+                       image_embeddings_used.name ASC",
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            Ok(EmbeddingUsage {
+                name: row.get(0)?,
+                count: row.get(1)?,
+                positive_count: row.get(2)?,
+                negative_count: row.get(3)?,
+                last_used: row.get(4)?,
+            })
+        })?;
+        let mut usage = Vec::new();
+        for row in rows {
+            usage.push(row?);
+        }
+        Ok(usage)
+    }
+}
@@ -0,0 +1,59 @@
+use super::*;
+
+/// Values listed per key; the rest only count towards the key's total.
+const MAX_VALUES_PER_KEY: usize = 10;
+/// Longer values are hashes or embedded JSON blobs, useless as a filter chip.
+const MAX_FACET_VALUE_LEN: usize = 120;
+
+impl Database {
+    // ────────────────────────── Extra parameters ──────────────────────────
+
+    /// Unmapped A1111 parameter keys across the library, most common first,
+    /// each with its most common values.
+    pub fn get_extra_param_keys(&self, limit: u32) -> SqlResult<Vec<ExtraParamKey>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = String::from(
+            "SELECT param.key, param.value, COUNT(*)
+             FROM images, json_each(
+                 CASE WHEN json_valid(images.extra_params) THEN images.extra_params ELSE '{}' END
+             ) AS param
+             WHERE images.archived = 0",
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(" GROUP BY param.key, param.value");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, u32>(2)?,
+            ))
+        })?;
+        let mut by_key: HashMap<String, ExtraParamKey> = HashMap::new();
+        for row in rows {
+            let (key, value, count) = row?;
+            let entry = by_key.entry(key.clone()).or_insert_with(|| ExtraParamKey {
+                key,
+                count: 0,
+                values: Vec::new(),
+            });
+            entry.count += count;
+            if let Some(value) = value.filter(|value| value.len() <= MAX_FACET_VALUE_LEN) {
+                entry.values.push(ExtraParamValue { value, count });
+            }
+        }
+
+        let mut keys: Vec<ExtraParamKey> = by_key.into_values().collect();
+        keys.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+        keys.truncate(limit as usize);
+        for key in &mut keys {
+            key.values
+                .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+            key.values.truncate(MAX_VALUES_PER_KEY);
+        }
+        Ok(keys)
+    }
+}
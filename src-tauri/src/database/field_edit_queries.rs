@@ -0,0 +1,354 @@
+use super::*;
+use rusqlite::OptionalExtension;
+
+/// Columns a field edit may write, and so the only ones an undo restores.
+const EDITABLE_COLUMNS: &[&str] = &[
+    "model_name",
+    "model_hash",
+    "seed",
+    "steps",
+    "sampler",
+    "schedule_type",
+    "cfg_scale",
+];
+
+/// Re-detects `model_family` after the model name or hash changed.
+fn refresh_model_family(conn: &Connection, image_id: i64) -> SqlResult<()> {
+    let params = conn
+        .prepare_cached(
+            "SELECT model_name, model_hash, width, height, extra_params FROM images WHERE id = ?1",
+        )?
+        .query_row(params![image_id], |row| {
+            Ok(GenerationParams {
+                model_name: row.get(0)?,
+                model_hash: row.get(1)?,
+                width: row.get(2)?,
+                height: row.get(3)?,
+                extra_params: embedding_queries::stored_extra_params(row.get(4)?),
+                ..Default::default()
+            })
+        })
+        .optional()?;
+    if let Some(params) = params {
+        conn.prepare_cached("UPDATE images SET model_family = ?1 WHERE id = ?2")?
+            .execute(params![
+                model_family::detect_model_family(&params).unwrap_or_default(),
+                image_id
+            ])?;
+    }
+    Ok(())
+}
+
+/// Writes `values` over `image_id`'s columns, skipping any column not in
+/// [`EDITABLE_COLUMNS`].
+fn write_columns(
+    conn: &Connection,
+    image_id: i64,
+    values: &[(&str, Option<&str>)],
+) -> SqlResult<()> {
+    let values: Vec<_> = values
+        .iter()
+        .filter(|(column, _)| EDITABLE_COLUMNS.contains(column))
+        .collect();
+    if values.is_empty() {
+        return Ok(());
+    }
+    let assignments: Vec<String> = values
+        .iter()
+        .map(|(column, _)| format!("{column} = ?"))
+        .collect();
+    let mut params_vec: Vec<Value> = values
+        .iter()
+        .map(|(_, value)| value.map_or(Value::Null, |value| Value::Text(value.to_string())))
+        .collect();
+    params_vec.push(Value::Integer(image_id));
+    conn.prepare_cached(&format!(
+        "UPDATE images SET {} WHERE id = ?",
+        assignments.join(", ")
+    ))?
+    .execute(params_from_iter(params_vec))?;
+
+    if values
+        .iter()
+        .any(|(column, _)| matches!(*column, "model_name" | "model_hash"))
+    {
+        refresh_model_family(conn, image_id)?;
+    }
+    Ok(())
+}
+
+/// Images in `rows` whose fields no longer hold the values in `written`.
+fn changed_since_edit(
+    conn: &Connection,
+    rows: &[FieldEditRow],
+    written: &HashMap<String, Option<String>>,
+    current_notes: &HashMap<i64, Option<String>>,
+) -> SqlResult<Vec<i64>> {
+    let columns: Vec<&str> = written
+        .keys()
+        .map(String::as_str)
+        .filter(|column| EDITABLE_COLUMNS.contains(column))
+        .collect();
+    let mut changed = Vec::new();
+    for row in rows {
+        let mut current = HashMap::new();
+        if !columns.is_empty() {
+            let values = conn
+                .prepare_cached(&format!(
+                    "SELECT {} FROM images WHERE id = ?1",
+                    columns.join(", ")
+                ))?
+                .query_row(params![row.image_id], |db_row| {
+                    columns
+                        .iter()
+                        .enumerate()
+                        .map(|(index, column)| {
+                            Ok((column.to_string(), db_row.get::<_, Option<String>>(index)?))
+                        })
+                        .collect::<SqlResult<HashMap<_, _>>>()
+                })
+                .optional()?;
+            // A deleted image has nothing left to clobber.
+            let Some(values) = values else {
+                continue;
+            };
+            current = values;
+        }
+        if let Some(notes) = current_notes.get(&row.image_id) {
+            current.insert("notes".to_string(), notes.clone());
+        }
+        if current
+            .iter()
+            .any(|(column, value)| written.get(column).is_some_and(|written| written != value))
+        {
+            changed.push(row.image_id);
+        }
+    }
+    Ok(changed)
+}
+
+impl Database {
+    // ─────────────────────────── Field edits ───────────────────────────
+
+    /// Overwrites the typed fields of `ids` in one transaction and journals
+    /// the old values, notes included from `previous_notes`, so the edit
+    /// can be undone. The FTS triggers re-index the rows.
+    pub fn bulk_update_fields(
+        &self,
+        ids: &[i64],
+        update: &FieldUpdate,
+        previous_notes: &HashMap<i64, Option<String>>,
+    ) -> SqlResult<FieldEdit> {
+        let columns = update.columns();
+        let mut fields: Vec<String> = columns
+            .iter()
+            .map(|(column, _)| column.to_string())
+            .collect();
+        if update.notes.is_some() {
+            fields.push("notes".to_string());
+        }
+        let edited_at = chrono::Utc::now().timestamp();
+
+        let mut written: HashMap<&str, Option<String>> = columns
+            .iter()
+            .map(|(column, value)| (*column, value.map(str::to_string)))
+            .collect();
+        if update.notes.is_some() {
+            written.insert("notes", update.notes_value());
+        }
+
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        let edit_id: i64 = tx.query_row(
+            "INSERT INTO field_edits (edited_at, fields, image_count, written)
+             VALUES (?1, ?2, 0, ?3)
+             RETURNING id",
+            params![
+                edited_at,
+                fields.join(","),
+                serde_json::to_string(&written).unwrap_or_default()
+            ],
+            |row| row.get(0),
+        )?;
+        let edited = {
+            let select_sql = format!(
+                "SELECT {} FROM images WHERE id = ?1",
+                columns
+                    .iter()
+                    .map(|(column, _)| *column)
+                    .chain(std::iter::once("id"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let mut select_stmt = tx.prepare(&select_sql)?;
+            let mut journal_stmt = tx.prepare(
+                "INSERT INTO field_edit_rows (edit_id, image_id, previous) VALUES (?1, ?2, ?3)",
+            )?;
+            let mut edited = Vec::with_capacity(ids.len());
+            for &id in ids {
+                let Some(mut previous) = select_stmt
+                    .query_row(params![id], |row| {
+                        let mut previous = HashMap::new();
+                        for (index, (column, _)) in columns.iter().enumerate() {
+                            previous
+                                .insert(column.to_string(), row.get::<_, Option<String>>(index)?);
+                        }
+                        Ok(previous)
+                    })
+                    .optional()?
+                else {
+                    continue;
+                };
+                if update.notes.is_some() {
+                    previous.insert(
+                        "notes".to_string(),
+                        previous_notes.get(&id).cloned().flatten(),
+                    );
+                }
+                journal_stmt.execute(params![
+                    edit_id,
+                    id,
+                    serde_json::to_string(&previous).unwrap_or_default()
+                ])?;
+                write_columns(&tx, id, &columns)?;
+                edited.push(id);
+            }
+            edited
+        };
+        tx.execute(
+            "UPDATE field_edits SET image_count = ?1 WHERE id = ?2",
+            params![edited.len() as i64, edit_id],
+        )?;
+        collection_queries::apply_collection_rules(&tx, &edited)?;
+        tx.commit()?;
+
+        self.invalidate_records(&edited);
+        Ok(FieldEdit {
+            id: edit_id,
+            edited_at,
+            fields,
+            image_count: edited.len() as u32,
+            undone: false,
+        })
+    }
+
+    /// Image ids a field edit changed.
+    pub fn field_edit_image_ids(&self, edit_id: i64) -> SqlResult<Vec<i64>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare("SELECT image_id FROM field_edit_rows WHERE edit_id = ?1")?;
+        let rows = stmt.query_map(params![edit_id], |row| row.get(0))?;
+        let mut ids = Vec::new();
+        for row in rows {
+            ids.push(row?);
+        }
+        Ok(ids)
+    }
+
+    /// Puts back the values `edit_id` overwrote and marks it undone, unless
+    /// one of its images no longer holds what the edit wrote: a later edit
+    /// would otherwise be silently reverted too. `current_notes` holds the
+    /// sidecar notes of the images whose notes could be read, since those
+    /// live outside the database. Returns the journaled rows so the caller
+    /// can restore notes.
+    pub fn undo_field_edit(
+        &self,
+        edit_id: i64,
+        current_notes: &HashMap<i64, Option<String>>,
+    ) -> SqlResult<FieldEditUndo> {
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        let edit: Option<(bool, Option<String>)> = tx
+            .query_row(
+                "SELECT undone, written FROM field_edits WHERE id = ?1",
+                params![edit_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((false, written)) = edit else {
+            return Ok(FieldEditUndo::Missing);
+        };
+        let written: Option<HashMap<String, Option<String>>> =
+            written.and_then(|written| serde_json::from_str(&written).ok());
+
+        let rows = {
+            let mut stmt =
+                tx.prepare("SELECT image_id, previous FROM field_edit_rows WHERE edit_id = ?1")?;
+            let rows = stmt.query_map(params![edit_id], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?;
+            let mut restored = Vec::new();
+            for row in rows {
+                let (image_id, previous) = row?;
+                restored.push(FieldEditRow {
+                    image_id,
+                    previous: serde_json::from_str(&previous).unwrap_or_default(),
+                });
+            }
+            restored
+        };
+        if let Some(written) = &written {
+            let conflicts = changed_since_edit(&tx, &rows, written, current_notes)?;
+            if !conflicts.is_empty() {
+                return Ok(FieldEditUndo::Conflict(conflicts));
+            }
+        }
+        for row in &rows {
+            let values: Vec<(&str, Option<&str>)> = row
+                .previous
+                .iter()
+                .map(|(column, value)| (column.as_str(), value.as_deref()))
+                .collect();
+            write_columns(&tx, row.image_id, &values)?;
+        }
+        tx.execute(
+            "UPDATE field_edits SET undone = 1 WHERE id = ?1",
+            params![edit_id],
+        )?;
+        let ids: Vec<i64> = rows.iter().map(|row| row.image_id).collect();
+        collection_queries::apply_collection_rules(&tx, &ids)?;
+        tx.commit()?;
+
+        self.invalidate_records(&ids);
+        Ok(FieldEditUndo::Restored(rows))
+    }
+
+    /// The most recent field edits, newest first.
+    pub fn get_field_edits(&self, limit: u32) -> SqlResult<Vec<FieldEdit>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, edited_at, fields, image_count, undone FROM field_edits
+             ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit], field_edit_from_row)?;
+        let mut edits = Vec::new();
+        for row in rows {
+            edits.push(row?);
+        }
+        Ok(edits)
+    }
+
+    pub fn get_field_edit(&self, edit_id: i64) -> SqlResult<Option<FieldEdit>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        conn.query_row(
+            "SELECT id, edited_at, fields, image_count, undone FROM field_edits WHERE id = ?1",
+            params![edit_id],
+            field_edit_from_row,
+        )
+        .optional()
+    }
+}
+
+fn field_edit_from_row(row: &rusqlite::Row<'_>) -> SqlResult<FieldEdit> {
+    Ok(FieldEdit {
+        id: row.get(0)?,
+        edited_at: row.get(1)?,
+        fields: row
+            .get::<_, String>(2)?
+            .split(',')
+            .filter(|field| !field.is_empty())
+            .map(str::to_string)
+            .collect(),
+        image_count: row.get(3)?,
+        undone: row.get(4)?,
+    })
+}
@@ -0,0 +1,130 @@
+use super::*;
+
+impl Database {
+    // ────────────────────────── Filter counts ──────────────────────────
+
+    /// Counts for the sidebar badges in one pass over the filtered rows.
+    /// Generation type and model family counts are faceted: each ignores its
+    /// own selection but keeps the other, so a badge shows what clicking it
+    /// would return. Like `filter_images_cursor`, a query without word
+    /// matches falls back to substring matching.
+    pub fn get_filter_counts(&self, params: FilterCursorParams<'_>) -> SqlResult<FilterCounts> {
+        let query = params
+            .query
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        let Some(query) = query else {
+            return self.filter_counts_with_match(params, None);
+        };
+
+        let sanitized = sanitize_fts_query(query);
+        if !sanitized.is_empty() {
+            let porter = self.filter_counts_with_match(params, Some(("images_fts", sanitized)))?;
+            if porter.total > 0 {
+                return Ok(porter);
+            }
+        }
+
+        if !contains_search_token(query) {
+            return Ok(FilterCounts::default());
+        }
+        let match_expr = format!("\"{}\"", query.replace('"', "\"\""));
+        self.filter_counts_with_match(params, Some(("images_fts_tri", match_expr)))
+    }
+
+    fn filter_counts_with_match(
+        &self,
+        params: FilterCursorParams<'_>,
+        fts_match: Option<(&str, String)>,
+    ) -> SqlResult<FilterCounts> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let options = params.options;
+        let normalized_generation_types = normalize_generation_types(options.generation_types);
+        let normalized_model_family_filters =
+            normalize_model_family_filters(options.model_family_filters);
+
+        // The pseudo types narrow every count, so they stay in the WHERE.
+        let (pseudo_types, selected_types): (Vec<String>, Vec<String>) =
+            normalized_generation_types
+                .into_iter()
+                .partition(|value| value == "archived" || value == "missing_metadata");
+        let selected_types: Vec<&String> = selected_types.iter().collect();
+
+        let mut select_params = Vec::<Value>::new();
+        let mut columns = Vec::new();
+
+        let mut total = String::from("COUNT(*) FILTER (WHERE 1=1");
+        append_generation_type_choice(&mut total, &mut select_params, &selected_types);
+        append_model_family_filter(
+            &mut total,
+            &mut select_params,
+            &normalized_model_family_filters,
+            Some("images"),
+        );
+        total.push(')');
+        columns.push(total);
+
+        for generation_type in COUNTED_GENERATION_TYPES {
+            let generation_type = generation_type.to_string();
+            let mut column = String::from("COUNT(*) FILTER (WHERE 1=1");
+            append_generation_type_choice(&mut column, &mut select_params, &[&generation_type]);
+            append_model_family_filter(
+                &mut column,
+                &mut select_params,
+                &normalized_model_family_filters,
+                Some("images"),
+            );
+            column.push(')');
+            columns.push(column);
+        }
+
+        for family in MODEL_FAMILIES {
+            let mut column = String::from("COUNT(*) FILTER (WHERE 1=1");
+            append_generation_type_choice(&mut column, &mut select_params, &selected_types);
+            append_model_family_filter(
+                &mut column,
+                &mut select_params,
+                &[family.to_string()],
+                Some("images"),
+            );
+            column.push(')');
+            columns.push(column);
+        }
+
+        let mut sql = format!("SELECT {} FROM images", columns.join(", "));
+        let mut params_vec = select_params;
+        if let Some((table, match_expr)) = fts_match {
+            sql.push_str(&format!(
+                " JOIN {table} ON images.id = {table}.rowid WHERE {table} MATCH ?"
+            ));
+            params_vec.push(Value::Text(match_expr));
+        } else {
+            sql.push_str(" WHERE 1=1");
+        }
+
+        append_generation_type_filter(&mut sql, &mut params_vec, &pseudo_types);
+        self.append_remaining_filters(&mut sql, &mut params_vec, params);
+
+        let counts = conn.query_row(&sql, params_from_iter(params_vec), |row| {
+            (0..columns.len())
+                .map(|index| row.get::<_, u32>(index))
+                .collect::<SqlResult<Vec<u32>>>()
+        })?;
+        let (type_counts, family_counts) = counts[1..].split_at(COUNTED_GENERATION_TYPES.len());
+        let entries = |values: &[&str], counts: &[u32]| {
+            values
+                .iter()
+                .zip(counts)
+                .map(|(value, count)| FilterCount {
+                    value: value.to_string(),
+                    count: *count,
+                })
+                .collect()
+        };
+        Ok(FilterCounts {
+            total: counts[0],
+            generation_types: entries(&COUNTED_GENERATION_TYPES, type_counts),
+            model_families: entries(&MODEL_FAMILIES, family_counts),
+        })
+    }
+}
@@ -0,0 +1,147 @@
+use super::*;
+
+/// Search table and MATCH expression for a filter's query, `None` without one.
+type FtsMatch = Option<(&'static str, String)>;
+
+impl Database {
+    // ────────────────────────── Filter-wide operations ──────────────────────────
+
+    /// Sets `is_favorite` on every image the filter matches, returning how
+    /// many changed.
+    pub fn set_favorite_by_filter(
+        &self,
+        params: FilterCursorParams<'_>,
+        is_favorite: bool,
+    ) -> SqlResult<usize> {
+        self.set_column_by_filter("is_favorite", params, Value::from(is_favorite))
+    }
+
+    /// Sets `is_locked` on every image the filter matches, returning how many
+    /// changed.
+    pub fn set_locked_by_filter(
+        &self,
+        params: FilterCursorParams<'_>,
+        is_locked: bool,
+    ) -> SqlResult<usize> {
+        self.set_column_by_filter("is_locked", params, Value::from(is_locked))
+    }
+
+    /// Ids of every image the filter matches, newest first.
+    pub fn get_ids_by_filter(&self, params: FilterCursorParams<'_>) -> SqlResult<Vec<i64>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let Some(fts_match) = self.resolve_filter_match(&conn, params)? else {
+            return Ok(Vec::new());
+        };
+        let (filtered, params_vec) = self.filtered_ids_sql(params, &fts_match);
+        let mut stmt = conn.prepare(&format!("{filtered} ORDER BY images.id DESC"))?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| row.get::<_, i64>(0))?;
+        let mut ids = Vec::new();
+        for row in rows {
+            ids.push(row?);
+        }
+        Ok(ids)
+    }
+
+    /// One UPDATE over the filtered rows.
+    pub(super) fn set_column_by_filter(
+        &self,
+        column: &'static str,
+        params: FilterCursorParams<'_>,
+        value: Value,
+    ) -> SqlResult<usize> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let Some(fts_match) = self.resolve_filter_match(&conn, params)? else {
+            return Ok(0);
+        };
+        let (filtered, filtered_params) = self.filtered_ids_sql(params, &fts_match);
+        let sql = format!(
+            "UPDATE images SET {column} = ? WHERE {column} != ? AND id IN ({filtered}) RETURNING id"
+        );
+        let mut params_vec = vec![value.clone(), value];
+        params_vec.extend(filtered_params);
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| row.get::<_, i64>(0))?;
+        let mut updated = Vec::new();
+        for row in rows {
+            updated.push(row?);
+        }
+        collection_queries::apply_collection_rules(&conn, &updated)?;
+        self.invalidate_records(&updated);
+        Ok(updated.len())
+    }
+
+    /// Picks the search table for the filter's query the way
+    /// `filter_images_cursor` does: by word when that finds anything, by
+    /// substring otherwise. `None` means the query can match nothing.
+    fn resolve_filter_match(
+        &self,
+        conn: &Connection,
+        params: FilterCursorParams<'_>,
+    ) -> SqlResult<Option<FtsMatch>> {
+        let Some(query) = params
+            .query
+            .map(str::trim)
+            .filter(|query| !query.is_empty())
+        else {
+            return Ok(Some(None));
+        };
+        let sanitized = sanitize_fts_query(query);
+        let porter = Some(("images_fts", sanitized.clone()));
+        if !sanitized.is_empty() && self.filter_matches_any(conn, params, &porter)? {
+            Ok(Some(porter))
+        } else if contains_search_token(query) {
+            Ok(Some(Some((
+                "images_fts_tri",
+                format!("\"{}\"", query.replace('"', "\"\"")),
+            ))))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn filter_matches_any(
+        &self,
+        conn: &Connection,
+        params: FilterCursorParams<'_>,
+        fts_match: &FtsMatch,
+    ) -> SqlResult<bool> {
+        let (filtered, params_vec) = self.filtered_ids_sql(params, fts_match);
+        conn.query_row(
+            &format!("SELECT EXISTS({filtered})"),
+            params_from_iter(params_vec),
+            |row| row.get(0),
+        )
+    }
+
+    /// `SELECT images.id ...` for the rows a filter matches.
+    fn filtered_ids_sql(
+        &self,
+        params: FilterCursorParams<'_>,
+        fts_match: &FtsMatch,
+    ) -> (String, Vec<Value>) {
+        let options = params.options;
+        let mut sql = String::from("SELECT images.id FROM images");
+        let mut params_vec = Vec::<Value>::new();
+        if let Some((table, match_expr)) = fts_match {
+            sql.push_str(&format!(
+                " JOIN {table} ON images.id = {table}.rowid WHERE {table} MATCH ?"
+            ));
+            params_vec.push(Value::Text(match_expr.clone()));
+        } else {
+            sql.push_str(" WHERE 1=1");
+        }
+        append_generation_type_filter(
+            &mut sql,
+            &mut params_vec,
+            &normalize_generation_types(options.generation_types),
+        );
+        append_model_family_filter(
+            &mut sql,
+            &mut params_vec,
+            &normalize_model_family_filters(options.model_family_filters),
+            Some("images"),
+        );
+        self.append_remaining_filters(&mut sql, &mut params_vec, params);
+        (sql, params_vec)
+    }
+}
@@ -0,0 +1,74 @@
+use super::*;
+use crate::focal_point::FocalPoint;
+
+impl Database {
+    // ────────────────────────── Focal points ──────────────────────────
+
+    /// Stored thumbnail focal points for `filepaths`; images without one are
+    /// left out.
+    pub fn focal_points_for_filepaths(
+        &self,
+        filepaths: &[String],
+    ) -> SqlResult<HashMap<String, FocalPoint>> {
+        if filepaths.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let conn = self.pool.get().map_err(pool_error)?;
+        let placeholders = vec!["?"; filepaths.len()].join(", ");
+        let sql = format!(
+            "SELECT filepath, focal_x, focal_y FROM images
+             WHERE filepath IN ({}) AND focal_x IS NOT NULL AND focal_y IS NOT NULL",
+            placeholders
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(filepaths.iter()), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                FocalPoint::new(row.get::<_, f64>(1)? as f32, row.get::<_, f64>(2)? as f32),
+            ))
+        })?;
+        let mut points = HashMap::new();
+        for row in rows {
+            let (filepath, point) = row?;
+            points.insert(filepath, point);
+        }
+        Ok(points)
+    }
+
+    /// Images that have no focal point yet, as `(id, filepath)`.
+    pub fn images_missing_focal_point(&self) -> SqlResult<Vec<(i64, String)>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = String::from(
+            "SELECT images.id, images.filepath FROM images
+             WHERE images.focal_x IS NULL AND images.archived = 0",
+        );
+        let mut params_vec = Vec::new();
+        self.append_private_filter(&mut sql, &mut params_vec);
+        sql.push_str(" ORDER BY images.id DESC");
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut images = Vec::new();
+        for row in rows {
+            images.push(row?);
+        }
+        Ok(images)
+    }
+
+    pub fn set_focal_points(&self, points: &[(i64, FocalPoint)]) -> SqlResult<()> {
+        if points.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        {
+            let mut stmt =
+                tx.prepare_cached("UPDATE images SET focal_x = ?1, focal_y = ?2 WHERE id = ?3")?;
+            for (id, point) in points {
+                stmt.execute(params![point.x as f64, point.y as f64, id])?;
+            }
+        }
+        tx.commit()
+    }
+}
@@ -0,0 +1,158 @@
+use super::*;
+use rusqlite::OptionalExtension;
+
+/// How far before a grid's mtime its cell images may have been written.
+/// Large X/Y/Z plots can take a long time to render, so this is generous;
+/// the previous grid in the same folder bounds the run more tightly.
+const GRID_RUN_WINDOW_SECS: i64 = 6 * 60 * 60;
+/// Cells are normally written before the grid; allow a little clock skew.
+const GRID_RUN_SLACK_SECS: i64 = 5;
+/// Upper bound on cells considered for one grid (and on a batch's seed range).
+const MAX_GRID_CELLS: i64 = 1_024;
+
+/// Same path/filename fallbacks the "grid" generation-type filter uses.
+fn is_grid_location(directory: &str, filename: &str) -> bool {
+    let directory = directory.to_ascii_lowercase().replace('\\', "/");
+    let filename = filename.to_ascii_lowercase();
+    directory.contains("txt2img-grids")
+        || directory.contains("img2img-grids")
+        || directory.contains("/grids/")
+        || directory.ends_with("/grids")
+        || filename.starts_with("grid-")
+        || filename.contains("_grid-")
+}
+
+/// Folder where A1111/Forge writes the individual images of a grid run:
+/// `outputs/txt2img-grids/<date>` pairs with `outputs/txt2img-images/<date>`.
+fn cell_directory_for(grid_directory: &str) -> Option<String> {
+    let lowered = grid_directory.to_ascii_lowercase();
+    ["txt2img", "img2img"].iter().find_map(|mode| {
+        let grids = format!("{}-grids", mode);
+        lowered.find(&grids).map(|start| {
+            format!(
+                "{}{}-images{}",
+                &grid_directory[..start],
+                &grid_directory[start..start + mode.len()],
+                &grid_directory[start + grids.len()..]
+            )
+        })
+    })
+}
+
+fn parse_seed(seed: Option<&str>) -> Option<i64> {
+    seed.and_then(|seed| seed.trim().parse::<i64>().ok())
+}
+
+impl Database {
+    /// Individual renders that make up a grid image, oldest first.
+    ///
+    /// Cells are matched on the run rather than stored links: same output
+    /// folder (or its `*-images` sibling), written shortly before the grid
+    /// and after the previous grid in that folder, and sharing either the
+    /// grid's prompt (X/Y/Z plots) or a seed in its batch range. Returns
+    /// `None` when `grid_id` does not exist and an empty list when the image
+    /// is not a grid.
+    pub fn get_grid_members(&self, grid_id: i64) -> SqlResult<Option<Vec<ImageRecord>>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let grid = conn
+            .query_row(
+                "SELECT directory, filename, prompt, seed, generation_type, file_mtime, filepath
+                 FROM images
+                 WHERE id = ?1",
+                params![grid_id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                        row.get::<_, Option<String>>(4)?,
+                        row.get::<_, Option<i64>>(5)?,
+                        row.get::<_, String>(6)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((directory, filename, prompt, seed, generation_type, file_mtime, filepath)) = grid
+        else {
+            return Ok(None);
+        };
+        if self.is_private_path(&filepath) {
+            return Ok(None);
+        }
+
+        let is_grid =
+            generation_type.as_deref() == Some("grid") || is_grid_location(&directory, &filename);
+        let Some(grid_mtime) = file_mtime.filter(|_| is_grid) else {
+            return Ok(Some(Vec::new()));
+        };
+
+        let previous_grid_mtime: Option<i64> = conn.query_row(
+            "SELECT MAX(file_mtime)
+             FROM images
+             WHERE directory = ?1
+               AND id != ?2
+               AND file_mtime < ?3
+               AND (generation_type = 'grid' OR LOWER(filename) LIKE 'grid-%')",
+            params![directory, grid_id, grid_mtime],
+            |row| row.get(0),
+        )?;
+        let earliest = previous_grid_mtime
+            .map(|mtime| mtime + 1)
+            .unwrap_or(i64::MIN)
+            .max(grid_mtime - GRID_RUN_WINDOW_SECS);
+
+        let cell_directory = cell_directory_for(&directory);
+        // Cells get the gallery's filters, so a grid can't surface renders
+        // from a locked private, hidden or NSFW-filtered folder.
+        let mut sql = String::from(
+            "SELECT images.id, images.filepath, images.filename, images.directory,
+                    images.prompt, images.negative_prompt, images.steps, images.sampler,
+                    images.cfg_scale, images.seed, images.width, images.height,
+                    images.model_hash, images.model_name, images.raw_metadata,
+                    images.is_favorite, images.is_locked
+             FROM images
+             WHERE (images.directory = ? OR images.directory = ?)
+               AND images.id != ?
+               AND images.file_mtime BETWEEN ? AND ?
+               AND COALESCE(images.generation_type, '') != 'grid'
+               AND images.archived = 0",
+        );
+        let mut params_vec = vec![
+            Value::Text(directory.clone()),
+            Value::Text(cell_directory.unwrap_or_else(|| directory.clone())),
+            Value::Integer(grid_id),
+            Value::Integer(earliest),
+            Value::Integer(grid_mtime + GRID_RUN_SLACK_SECS),
+        ];
+        append_hidden_directory_filter(&mut sql, false);
+        self.append_nsfw_filter(&mut sql, &mut params_vec);
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+        sql.push_str(" ORDER BY images.file_mtime ASC, images.id ASC LIMIT ?");
+        params_vec.push(Value::Integer(MAX_GRID_CELLS));
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), image_record_from_row)?;
+
+        let grid_seed = parse_seed(seed.as_deref());
+        let mut members = Vec::new();
+        for row in rows {
+            let record = row?;
+            if is_grid_location(&record.directory, &record.filename) {
+                continue;
+            }
+            let same_prompt = !prompt.is_empty() && record.prompt == prompt;
+            let in_seed_range = match (grid_seed, parse_seed(record.seed.as_deref())) {
+                (Some(first), Some(seed)) => {
+                    (first..first.saturating_add(MAX_GRID_CELLS)).contains(&seed)
+                }
+                _ => false,
+            };
+            if same_prompt || in_seed_range {
+                members.push(record);
+            }
+        }
+        Ok(Some(members))
+    }
+}
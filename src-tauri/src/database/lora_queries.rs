@@ -0,0 +1,92 @@
+use super::stats_queries::IMAGE_TIMESTAMP;
+use super::*;
+use crate::parser::LoraUse;
+
+/// Replaces the LoRAs recorded for `image_id`. Runs inside the caller's
+/// transaction.
+pub(super) fn replace_image_loras(
+    conn: &Connection,
+    image_id: i64,
+    loras: &[LoraUse],
+) -> SqlResult<()> {
+    conn.prepare_cached("DELETE FROM image_loras WHERE image_id = ?1")?
+        .execute(params![image_id])?;
+    let mut insert_stmt = conn.prepare_cached(
+        "INSERT OR IGNORE INTO image_loras(image_id, name, weight) VALUES (?1, ?2, ?3)",
+    )?;
+    for lora in loras {
+        insert_stmt.execute(params![image_id, lora.name, lora.weight])?;
+    }
+    Ok(())
+}
+
+impl Database {
+    // ────────────────────────────── LoRAs ──────────────────────────────
+
+    /// Fills `image_loras` for rows indexed before the table existed.
+    pub(super) fn backfill_image_loras(conn: &Connection) -> SqlResult<()> {
+        let mut select_stmt = conn.prepare(
+            "SELECT id, prompt, raw_metadata FROM images
+             WHERE prompt LIKE '%<lora:%' OR raw_metadata LIKE '%LoraLoader%'",
+        )?;
+        let rows = select_stmt.query_map([], |row| {
+            let params = GenerationParams {
+                prompt: row.get(1)?,
+                raw_metadata: row.get(2)?,
+                ..Default::default()
+            };
+            Ok((row.get::<_, i64>(0)?, extract_loras(&params)))
+        })?;
+        let mut updates = Vec::<(i64, Vec<LoraUse>)>::new();
+        for row in rows {
+            let (id, loras) = row?;
+            if !loras.is_empty() {
+                updates.push((id, loras));
+            }
+        }
+
+        if updates.is_empty() {
+            return Ok(());
+        }
+        let tx = conn.unchecked_transaction()?;
+        for (id, loras) in &updates {
+            replace_image_loras(&tx, *id, loras)?;
+        }
+        tx.commit()
+    }
+
+    /// Every LoRA with its image count, weight spread and last use, most
+    /// used first. Locked private folders are left out.
+    pub fn get_lora_usage(&self) -> SqlResult<Vec<LoraUsage>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = format!(
+            "SELECT image_loras.name, COUNT(*), AVG(image_loras.weight),
+                    MIN(image_loras.weight), MAX(image_loras.weight),
+                    date(MAX({IMAGE_TIMESTAMP}), 'unixepoch')
+             FROM image_loras
+             JOIN images ON images.id = image_loras.image_id
+             WHERE 1=1"
+        );
+        let mut params_vec: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+        sql.push_str(" GROUP BY image_loras.name ORDER BY COUNT(*) DESC, image_loras.name ASC");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            Ok(LoraUsage {
+                name: row.get(0)?,
+                count: row.get(1)?,
+                avg_weight: row.get(2)?,
+                min_weight: row.get(3)?,
+                max_weight: row.get(4)?,
+                last_used: row.get(5)?,
+            })
+        })?;
+        let mut usage = Vec::new();
+        for row in rows {
+            usage.push(row?);
+        }
+        Ok(usage)
+    }
+}
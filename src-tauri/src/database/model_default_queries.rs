@@ -0,0 +1,104 @@
+use super::*;
+
+const MODEL_EXTENSIONS: [&str; 3] = [".safetensors", ".ckpt", ".gguf"];
+
+/// Family id for family names (`SDXL`, `pony`), otherwise the checkpoint
+/// name lowercased without folder, extension or ` [hash]` suffix, so
+/// `models/juggernautXL.safetensors [abc123]` and `JuggernautXL` match.
+fn model_defaults_key(model: &str) -> String {
+    if let Some(family) = normalize_model_family(model) {
+        return family.to_string();
+    }
+    let name = model.split(" [").next().unwrap_or(model).replace('\\', "/");
+    let mut name = name
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    if let Some(extension) = MODEL_EXTENSIONS
+        .iter()
+        .find(|extension| name.ends_with(*extension))
+    {
+        name.truncate(name.len() - extension.len());
+    }
+    name
+}
+
+fn model_defaults_from_row(row: &Row<'_>) -> SqlResult<ModelDefaults> {
+    Ok(ModelDefaults {
+        model: row.get(0)?,
+        sampler: row.get(1)?,
+        scheduler: row.get(2)?,
+        steps: row.get(3)?,
+        cfg_scale: row.get(4)?,
+        width: row.get(5)?,
+        height: row.get(6)?,
+    })
+}
+
+impl ModelDefaults {
+    /// Defaults for `model_name`: an entry for the checkpoint itself wins
+    /// over one for the family its name points to.
+    pub fn lookup<'a>(defaults: &'a [ModelDefaults], model_name: &str) -> Option<&'a Self> {
+        let key = model_defaults_key(model_name);
+        if key.is_empty() {
+            return None;
+        }
+        if let Some(exact) = defaults
+            .iter()
+            .find(|entry| model_defaults_key(&entry.model) == key)
+        {
+            return Some(exact);
+        }
+        let family = model_family::family_from_name(model_name)?;
+        defaults
+            .iter()
+            .find(|entry| model_defaults_key(&entry.model) == family)
+    }
+}
+
+impl Database {
+    // ────────────────────────── Model defaults ──────────────────────────
+
+    pub fn list_model_defaults(&self) -> SqlResult<Vec<ModelDefaults>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT model, sampler, scheduler, steps, cfg_scale, width, height
+             FROM model_defaults ORDER BY model COLLATE NOCASE",
+        )?;
+        let rows = stmt.query_map([], model_defaults_from_row)?;
+        rows.collect()
+    }
+
+    /// Creates or replaces the defaults for `defaults.model`.
+    pub fn set_model_defaults(&self, defaults: &ModelDefaults) -> SqlResult<()> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO model_defaults
+                 (model_key, model, sampler, scheduler, steps, cfg_scale, width, height)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                model_defaults_key(&defaults.model),
+                defaults.model,
+                defaults.sampler,
+                defaults.scheduler,
+                defaults.steps,
+                defaults.cfg_scale,
+                defaults.width,
+                defaults.height
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Returns whether an entry was removed.
+    pub fn delete_model_defaults(&self, model: &str) -> SqlResult<bool> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let removed = conn.execute(
+            "DELETE FROM model_defaults WHERE model_key = ?1",
+            [model_defaults_key(model)],
+        )?;
+        Ok(removed > 0)
+    }
+}
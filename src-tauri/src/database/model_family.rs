@@ -0,0 +1,143 @@
+use super::*;
+
+/// AutoV2 hashes of widely shared base checkpoints, for images whose model
+/// was renamed past recognition.
+const KNOWN_MODEL_HASHES: &[(&str, &str)] = &[
+    ("31e35c80fc", "sdxl"),
+    ("6ce0161689", "sd15"),
+    ("67ab2fd8ec", "ponyxl"),
+];
+
+/// Official SDXL training buckets; SD 1.5 and 2.1 models break down at them.
+const SDXL_BUCKETS: &[(u32, u32)] = &[
+    (1024, 1024),
+    (1152, 896),
+    (1216, 832),
+    (1344, 768),
+    (1536, 640),
+];
+
+/// Guesses the model family from the parsed metadata, strongest signal
+/// first: the model name (a ComfyUI `unet_name` lands there too), Forge's
+/// text-encoder modules and Flux-only parameters, well-known hashes, and
+/// finally the resolution. The resolution is only trusted when there is no
+/// hash either, so images sharing a hash with a recognised one can still
+/// inherit its family (see `propagate_model_families_by_hash`).
+pub(super) fn detect_model_family(params: &GenerationParams) -> Option<&'static str> {
+    if let Some(family) = params.model_name.as_deref().and_then(family_from_name) {
+        return Some(family);
+    }
+
+    let modules: Vec<String> = params
+        .extra_params
+        .iter()
+        .filter(|(key, _)| key.starts_with("Module"))
+        .map(|(_, value)| value.to_ascii_lowercase())
+        .collect();
+    if let Some(family) = modules.iter().find_map(|module| family_from_name(module)) {
+        return Some(family);
+    }
+    let has_module = |needle: &str| modules.iter().any(|module| module.contains(needle));
+    if params.extra_params.contains_key("Distilled CFG Scale")
+        || (has_module("t5xxl") && has_module("clip_l"))
+    {
+        return Some("flux");
+    }
+
+    let hash = params
+        .model_hash
+        .as_deref()
+        .map(|hash| hash.trim().to_ascii_lowercase())
+        .filter(|hash| !hash.is_empty());
+    if let Some(hash) = hash {
+        return KNOWN_MODEL_HASHES
+            .iter()
+            .find(|(known, _)| hash.starts_with(known))
+            .map(|(_, family)| *family);
+    }
+
+    let upscaled = params
+        .extra_params
+        .keys()
+        .any(|key| key.starts_with("Hires"));
+    match (params.width, params.height) {
+        (Some(width), Some(height)) if !upscaled => family_from_size(width, height),
+        _ => None,
+    }
+}
+
+pub(super) fn family_from_name(name: &str) -> Option<&'static str> {
+    let name = name.to_ascii_lowercase();
+    MODEL_FAMILIES.into_iter().find(|family| {
+        family_patterns(family)
+            .iter()
+            .any(|pattern| name.contains(pattern.trim_matches('%')))
+    })
+}
+
+fn family_from_size(width: u32, height: u32) -> Option<&'static str> {
+    let (long, short) = (width.max(height), width.min(height));
+    if SDXL_BUCKETS.contains(&(long, short)) {
+        Some("sdxl")
+    } else if long <= 768 && short <= 512 {
+        Some("sd15")
+    } else {
+        None
+    }
+}
+
+/// Gives unclassified rows the family most rows with the same model hash
+/// have.
+pub(super) fn propagate_model_families_by_hash(conn: &Connection) -> SqlResult<usize> {
+    conn.execute(
+        "UPDATE images SET model_family = (
+             SELECT known.model_family FROM images AS known
+             WHERE known.model_hash = images.model_hash AND known.model_family != ''
+             GROUP BY known.model_family ORDER BY COUNT(*) DESC LIMIT 1)
+         WHERE model_family = '' AND COALESCE(model_hash, '') != ''
+           AND EXISTS (SELECT 1 FROM images AS known
+                       WHERE known.model_hash = images.model_hash AND known.model_family != '')",
+        [],
+    )
+}
+
+impl Database {
+    /// Classifies rows indexed before `model_family` existed. Undetected
+    /// rows store an empty family so they are not revisited on every start.
+    pub(super) fn backfill_model_families(conn: &Connection) -> SqlResult<()> {
+        let mut select_stmt = conn.prepare(
+            "SELECT id, model_name, model_hash, width, height, extra_params
+             FROM images WHERE model_family IS NULL",
+        )?;
+        let rows = select_stmt.query_map([], |row| {
+            let extra_params: Option<String> = row.get(5)?;
+            let params = GenerationParams {
+                model_name: row.get(1)?,
+                model_hash: row.get(2)?,
+                width: row.get(3)?,
+                height: row.get(4)?,
+                extra_params: extra_params
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+                ..Default::default()
+            };
+            Ok((row.get::<_, i64>(0)?, params))
+        })?;
+
+        let mut updates = Vec::<(i64, &'static str)>::new();
+        for row in rows {
+            let (id, params) = row?;
+            updates.push((id, detect_model_family(&params).unwrap_or_default()));
+        }
+
+        if !updates.is_empty() {
+            let mut update_stmt =
+                conn.prepare("UPDATE images SET model_family = ?1 WHERE id = ?2")?;
+            for (id, family) in updates {
+                update_stmt.execute(params![family, id])?;
+            }
+        }
+        propagate_model_families_by_hash(conn)?;
+        Ok(())
+    }
+}
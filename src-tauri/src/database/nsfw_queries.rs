@@ -0,0 +1,98 @@
+use super::*;
+
+impl Database {
+    // ────────────────────────── NSFW filtering ──────────────────────────
+
+    /// Hides images scoring at or above `threshold` from cursor queries and
+    /// thumbnail lookups; `None` shows everything.
+    pub fn set_nsfw_filter(&self, threshold: Option<f32>) {
+        if let Ok(mut filter) = self.nsfw_filter.write() {
+            *filter = threshold;
+        }
+    }
+
+    fn nsfw_threshold(&self) -> Option<f32> {
+        self.nsfw_filter.read().ok().and_then(|filter| *filter)
+    }
+
+    pub(super) fn append_nsfw_filter(&self, sql: &mut String, params: &mut Vec<Value>) {
+        if let Some(threshold) = self.nsfw_threshold() {
+            sql.push_str(" AND COALESCE(images.nsfw_score, 0) < ?");
+            params.push(Value::Real(threshold as f64));
+        }
+    }
+
+    /// Raises scores from a model pass; prompt-keyword scores are kept when higher.
+    pub fn raise_nsfw_scores(&self, scores: &[(i64, f32)]) -> SqlResult<()> {
+        if scores.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE images SET nsfw_score = MAX(COALESCE(nsfw_score, 0), ?1) WHERE id = ?2",
+            )?;
+            for (id, score) in scores {
+                stmt.execute(params![score, id])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Stored scores of `ids`; unscored images read as 0.
+    pub fn nsfw_scores(&self, ids: &[i64]) -> SqlResult<HashMap<i64, f32>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let conn = self.pool.get().map_err(pool_error)?;
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!(
+            "SELECT id, COALESCE(nsfw_score, 0) FROM images WHERE id IN ({})",
+            placeholders
+        );
+        let params: Vec<Value> = ids.iter().map(|id| Value::Integer(*id)).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)? as f32))
+        })?;
+        let mut scores = HashMap::new();
+        for row in rows {
+            let (id, score) = row?;
+            scores.insert(id, score);
+        }
+        Ok(scores)
+    }
+
+    /// Of `filepaths`, the ones the active NSFW filter hides.
+    pub fn nsfw_hidden_filepaths(&self, filepaths: &[String]) -> SqlResult<HashSet<String>> {
+        let Some(threshold) = self.nsfw_threshold() else {
+            return Ok(HashSet::new());
+        };
+        if filepaths.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let conn = self.pool.get().map_err(pool_error)?;
+        let placeholders = vec!["?"; filepaths.len()].join(", ");
+        let sql = format!(
+            "SELECT filepath FROM images
+             WHERE filepath IN ({}) AND COALESCE(nsfw_score, 0) >= ?",
+            placeholders
+        );
+        let mut params: Vec<Value> = filepaths
+            .iter()
+            .map(|filepath| Value::Text(filepath.clone()))
+            .collect();
+        params.push(Value::Real(threshold as f64));
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| row.get::<_, String>(0))?;
+        let mut hidden = HashSet::new();
+        for row in rows {
+            hidden.insert(row?);
+        }
+        Ok(hidden)
+    }
+}
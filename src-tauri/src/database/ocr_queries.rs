@@ -0,0 +1,43 @@
+use super::*;
+
+impl Database {
+    // ─────────────────────────── Image text ───────────────────────────
+
+    /// Images OCR has not read yet, as `(id, filepath)`.
+    pub fn images_missing_ocr_text(&self) -> SqlResult<Vec<(i64, String)>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = String::from(
+            "SELECT images.id, images.filepath FROM images
+             WHERE images.ocr_text IS NULL AND images.archived = 0",
+        );
+        let mut params_vec = Vec::new();
+        self.append_private_filter(&mut sql, &mut params_vec);
+        sql.push_str(" ORDER BY images.id DESC");
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut images = Vec::new();
+        for row in rows {
+            images.push(row?);
+        }
+        Ok(images)
+    }
+
+    /// Stores recognised text, re-indexing it for search through the FTS
+    /// triggers. An empty string marks an image read with no text found.
+    pub fn set_ocr_text(&self, texts: &[(i64, String)]) -> SqlResult<()> {
+        if texts.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached("UPDATE images SET ocr_text = ?1 WHERE id = ?2")?;
+            for (id, text) in texts {
+                stmt.execute(params![text, id])?;
+            }
+        }
+        tx.commit()
+    }
+}
@@ -0,0 +1,31 @@
+use super::private_queries::directory_prefix;
+use super::*;
+
+impl Database {
+    // ────────────────────────── Offline volumes ──────────────────────────
+
+    /// Library roots whose drive is currently unavailable.
+    pub fn set_offline_roots(&self, roots: &[String]) {
+        if let Ok(mut offline) = self.offline_roots.write() {
+            *offline = roots.iter().map(|root| directory_prefix(root)).collect();
+        }
+    }
+
+    pub fn is_offline_path(&self, filepath: &str) -> bool {
+        let Ok(offline) = self.offline_roots.read() else {
+            return false;
+        };
+        if offline.is_empty() {
+            return false;
+        }
+        let normalized = filepath.replace('\\', "/");
+        offline.iter().any(|prefix| normalized.starts_with(prefix))
+    }
+
+    /// Sets `offline` on gallery rows whose file sits on an unavailable root.
+    pub fn mark_offline(&self, items: &mut [GalleryImageRecord]) {
+        for item in items {
+            item.offline = self.is_offline_path(&item.filepath);
+        }
+    }
+}
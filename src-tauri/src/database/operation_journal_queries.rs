@@ -0,0 +1,161 @@
+use super::*;
+
+impl Database {
+    // ────────────────────────── Operation journal ──────────────────────────
+
+    /// Opens a journal for a bulk operation this process is about to run.
+    /// Returns its id.
+    pub fn begin_operation(
+        &self,
+        kind: OperationKind,
+        detail: &serde_json::Value,
+    ) -> SqlResult<i64> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let id = conn.query_row(
+            "INSERT INTO operation_journal (kind, started_at, detail) VALUES (?1, ?2, ?3)
+             RETURNING id",
+            params![
+                kind.as_str(),
+                chrono::Utc::now().timestamp(),
+                detail.to_string()
+            ],
+            |row| row.get(0),
+        )?;
+        if let Ok(mut running) = self.running_operations.lock() {
+            running.insert(id);
+        }
+        Ok(id)
+    }
+
+    /// Records `(image_id, source, destination)` steps before any of them
+    /// touches the disk.
+    pub fn journal_operation_steps(
+        &self,
+        operation_id: i64,
+        steps: &[(i64, &str, Option<&str>)],
+    ) -> SqlResult<()> {
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR REPLACE INTO operation_journal_steps
+                    (operation_id, image_id, source, destination)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (image_id, source, destination) in steps {
+                stmt.execute(params![operation_id, image_id, source, destination])?;
+            }
+        }
+        tx.commit()
+    }
+
+    pub fn mark_operation_steps_done(&self, operation_id: i64, image_ids: &[i64]) -> SqlResult<()> {
+        if image_ids.is_empty() {
+            return Ok(());
+        }
+        let conn = self.pool.get().map_err(pool_error)?;
+        conn.execute(
+            "UPDATE operation_journal_steps SET done = 1
+             WHERE operation_id = ?1 AND image_id IN (SELECT value FROM json_each(?2))",
+            params![
+                operation_id,
+                serde_json::to_string(image_ids).unwrap_or_default()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Closes a journal once its operation has run or been resumed.
+    pub fn finish_operation(&self, operation_id: i64) -> SqlResult<()> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        conn.execute(
+            "DELETE FROM operation_journal WHERE id = ?1",
+            params![operation_id],
+        )?;
+        self.release_operation(operation_id);
+        Ok(())
+    }
+
+    /// Keeps the journal but stops treating the operation as running, so it
+    /// shows as pending.
+    pub fn release_operation(&self, operation_id: i64) {
+        if let Ok(mut running) = self.running_operations.lock() {
+            running.remove(&operation_id);
+        }
+    }
+
+    /// Journaled operations left unfinished by an earlier run, oldest first.
+    pub fn get_pending_operations(&self) -> SqlResult<Vec<PendingOperation>> {
+        let running = self
+            .running_operations
+            .lock()
+            .map(|running| running.clone())
+            .unwrap_or_default();
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare(
+            "SELECT operation_journal.id, kind, started_at, detail,
+                    COUNT(operation_journal_steps.image_id),
+                    COALESCE(SUM(operation_journal_steps.done), 0)
+             FROM operation_journal
+             LEFT JOIN operation_journal_steps
+                ON operation_journal_steps.operation_id = operation_journal.id
+             GROUP BY operation_journal.id
+             ORDER BY operation_journal.id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, u32>(5)?,
+            ))
+        })?;
+        let mut operations = Vec::new();
+        for row in rows {
+            let (id, kind, started_at, detail, total_steps, done_steps) = row?;
+            if running.contains(&id) {
+                continue;
+            }
+            let Some(kind) = OperationKind::parse(&kind) else {
+                log::warn!(
+                    "Skipping journaled operation {} of unknown kind '{}'",
+                    id,
+                    kind
+                );
+                continue;
+            };
+            operations.push(PendingOperation {
+                id,
+                kind,
+                started_at,
+                detail: serde_json::from_str(&detail).unwrap_or_default(),
+                total_steps,
+                done_steps,
+            });
+        }
+        Ok(operations)
+    }
+
+    pub fn get_operation_steps(&self, operation_id: i64) -> SqlResult<Vec<OperationStep>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare(
+            "SELECT image_id, source, destination, done FROM operation_journal_steps
+             WHERE operation_id = ?1 ORDER BY rowid",
+        )?;
+        let rows = stmt.query_map(params![operation_id], |row| {
+            Ok(OperationStep {
+                image_id: row.get(0)?,
+                source: row.get(1)?,
+                destination: row.get(2)?,
+                done: row.get(3)?,
+            })
+        })?;
+        let mut steps = Vec::new();
+        for row in rows {
+            steps.push(row?);
+        }
+        Ok(steps)
+    }
+}
@@ -0,0 +1,118 @@
+use super::*;
+use rusqlite::OptionalExtension;
+
+impl Database {
+    // ────────────────────────── Perceptual hashes ──────────────────────────
+
+    /// Saves thumbnail perceptual hashes by source filepath. Paths that are
+    /// not indexed (yet) are ignored.
+    pub fn set_phashes(&self, phashes: &[(String, u64)]) -> SqlResult<usize> {
+        if phashes.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        let mut updated = 0;
+        {
+            let mut stmt = tx.prepare_cached("UPDATE images SET phash = ?1 WHERE filepath = ?2")?;
+            for (filepath, phash) in phashes {
+                // SQLite integers are signed; the bits are stored as is.
+                updated += stmt.execute(params![*phash as i64, filepath])?;
+            }
+        }
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    /// Filepaths of images whose perceptual hash has not been computed.
+    pub fn filepaths_missing_phash(&self) -> SqlResult<HashSet<String>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt =
+            conn.prepare("SELECT filepath FROM images WHERE phash IS NULL AND archived = 0")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut filepaths = HashSet::new();
+        for row in rows {
+            filepaths.insert(row?);
+        }
+        Ok(filepaths)
+    }
+
+    /// Images whose perceptual hash is within `max_distance` bits of
+    /// `image_id`'s, closest first. `None` when that image doesn't exist or
+    /// has no hash yet. Archived images and hidden folders are left out.
+    pub fn find_similar_images(
+        &self,
+        image_id: i64,
+        max_distance: u32,
+        limit: u32,
+    ) -> SqlResult<Option<Vec<SimilarImage>>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let target = conn
+            .query_row(
+                "SELECT phash FROM images WHERE id = ?1",
+                params![image_id],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .optional()?
+            .flatten();
+        let Some(target) = target else {
+            return Ok(None);
+        };
+
+        // Rank on the hashes alone; only the matches kept get a full record.
+        let mut sql = "SELECT images.id, images.phash
+             FROM images
+             WHERE images.archived = 0
+               AND images.phash IS NOT NULL
+               AND images.id != ?"
+            .to_string();
+        let mut params_vec = vec![Value::Integer(image_id)];
+        append_hidden_directory_filter(&mut sql, false);
+        self.append_nsfw_filter(&mut sql, &mut params_vec);
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+
+        let mut ranked = Vec::new();
+        {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+            })?;
+            for row in rows {
+                let (id, phash) = row?;
+                let distance = (phash ^ target).count_ones();
+                if distance <= max_distance {
+                    ranked.push((distance, id));
+                }
+            }
+        }
+        ranked.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1)));
+        ranked.truncate(limit as usize);
+        if ranked.is_empty() {
+            return Ok(Some(Vec::new()));
+        }
+
+        let placeholders = vec!["?"; ranked.len()].join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {GALLERY_COLUMNS} FROM images WHERE images.id IN ({placeholders})"
+        ))?;
+        let rows = stmt.query_map(params_from_iter(ranked.iter().map(|(_, id)| id)), |row| {
+            gallery_image_record_from_row(row)
+        })?;
+        let mut records = HashMap::with_capacity(ranked.len());
+        for row in rows {
+            let record = row?;
+            records.insert(record.id, record);
+        }
+        let matches = ranked
+            .into_iter()
+            .filter_map(|(distance, id)| {
+                Some(SimilarImage {
+                    record: records.remove(&id)?,
+                    distance,
+                })
+            })
+            .collect();
+        Ok(Some(matches))
+    }
+}
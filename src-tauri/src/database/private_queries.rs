@@ -0,0 +1,65 @@
+use super::*;
+
+/// `dir` with forward slashes and a trailing `/`, so both separator styles
+/// compare equal and `/a/b` does not match `/a/bc`.
+pub(super) fn directory_prefix(directory: &str) -> String {
+    format!("{}/", directory.replace('\\', "/").trim_end_matches('/'))
+}
+
+impl Database {
+    // ────────────────────────── Private folders ──────────────────────────
+
+    /// Sets the directories hidden from queries; pass an empty list once the
+    /// session is unlocked.
+    pub fn set_private_directories(&self, directories: &[String]) {
+        if let Ok(mut hidden) = self.private_directories.write() {
+            *hidden = directories
+                .iter()
+                .map(|directory| directory_prefix(directory))
+                .collect();
+        }
+    }
+
+    fn private_prefixes(&self) -> Vec<String> {
+        self.private_directories
+            .read()
+            .map(|hidden| hidden.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn is_private_path(&self, filepath: &str) -> bool {
+        let Ok(hidden) = self.private_directories.read() else {
+            return false;
+        };
+        if hidden.is_empty() {
+            return false;
+        }
+        let normalized = filepath.replace('\\', "/");
+        hidden.iter().any(|prefix| normalized.starts_with(prefix))
+    }
+
+    pub(super) fn append_private_filter(&self, sql: &mut String, params: &mut Vec<Value>) {
+        for prefix in self.private_prefixes() {
+            sql.push_str(" AND substr(REPLACE(images.filepath, char(92), '/'), 1, ?) != ?");
+            params.push(Value::Integer(prefix.chars().count() as i64));
+            params.push(Value::Text(prefix));
+        }
+    }
+
+    /// Drops records under a locked private directory.
+    pub(super) fn retain_public<T>(&self, items: &mut Vec<T>, filepath: impl Fn(&T) -> &str) {
+        if self.private_prefixes().is_empty() {
+            return;
+        }
+        items.retain(|item| !self.is_private_path(filepath(item)));
+    }
+
+    /// Of `filepaths`, the ones under a locked private directory.
+    pub fn private_filepaths(&self, filepaths: &[String]) -> HashSet<String> {
+        filepaths
+            .iter()
+            .filter(|filepath| self.is_private_path(filepath))
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,37 @@
+use super::private_queries::directory_prefix;
+use super::*;
+
+impl Database {
+    // ─────────────────────────── Projects ───────────────────────────
+
+    /// Confines gallery, facet and stats queries to `directories`; pass an
+    /// empty list to show the whole library again.
+    pub fn set_project_directories(&self, directories: &[String]) {
+        if let Ok(mut scope) = self.project_directories.write() {
+            *scope = directories
+                .iter()
+                .map(|directory| directory_prefix(directory))
+                .collect();
+        }
+    }
+
+    pub(super) fn append_project_filter(&self, sql: &mut String, params: &mut Vec<Value>) {
+        let prefixes = self
+            .project_directories
+            .read()
+            .map(|scope| scope.clone())
+            .unwrap_or_default();
+        if prefixes.is_empty() {
+            return;
+        }
+        let clauses: Vec<&str> = prefixes
+            .iter()
+            .map(|_| "substr(REPLACE(images.filepath, char(92), '/'), 1, ?) = ?")
+            .collect();
+        sql.push_str(&format!(" AND ({})", clauses.join(" OR ")));
+        for prefix in prefixes {
+            params.push(Value::Integer(prefix.chars().count() as i64));
+            params.push(Value::Text(prefix));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use super::*;
+use crate::prompt_clusters::ClusterSummary;
+
+impl Database {
+    // ────────────────────────── Prompt clusters ──────────────────────────
+
+    /// `(id, prompt)` of every image with a prompt, as input to a clustering
+    /// run. Locked private folders are left out so their prompts can't
+    /// surface in cluster labels.
+    pub fn prompts_for_clustering(&self) -> SqlResult<Vec<(i64, String)>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = String::from(
+            "SELECT images.id, images.prompt FROM images
+             WHERE images.archived = 0 AND images.prompt != ''",
+        );
+        let mut params_vec = Vec::new();
+        self.append_private_filter(&mut sql, &mut params_vec);
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut prompts = Vec::new();
+        for row in rows {
+            prompts.push(row?);
+        }
+        Ok(prompts)
+    }
+
+    /// Replaces the previous run's clusters. `clusters[n]` gets id `n + 1`;
+    /// `assignments` pairs image ids with an index into `clusters`, and
+    /// images left out lose their cluster.
+    pub fn replace_prompt_clusters(
+        &self,
+        clusters: &[ClusterSummary],
+        assignments: &[(i64, usize)],
+    ) -> SqlResult<()> {
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM prompt_clusters", [])?;
+        tx.execute(
+            "UPDATE images SET prompt_cluster = NULL WHERE prompt_cluster IS NOT NULL",
+            [],
+        )?;
+        {
+            let mut insert_stmt =
+                tx.prepare("INSERT INTO prompt_clusters (id, label, terms) VALUES (?1, ?2, ?3)")?;
+            for (index, cluster) in clusters.iter().enumerate() {
+                insert_stmt.execute(params![
+                    index as i64 + 1,
+                    cluster.label,
+                    cluster.terms.join("\n")
+                ])?;
+            }
+            let mut assign_stmt =
+                tx.prepare("UPDATE images SET prompt_cluster = ?1 WHERE id = ?2")?;
+            for (id, cluster) in assignments {
+                assign_stmt.execute(params![*cluster as i64 + 1, id])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Clusters from the last run with their image counts, largest first.
+    /// Clusters with no visible images are left out.
+    pub fn get_prompt_clusters(&self) -> SqlResult<Vec<PromptCluster>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = String::from(
+            "SELECT prompt_clusters.id, prompt_clusters.label, prompt_clusters.terms, COUNT(*)
+             FROM prompt_clusters
+             JOIN images ON images.prompt_cluster = prompt_clusters.id
+             WHERE images.archived = 0",
+        );
+        let mut params_vec = Vec::new();
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+        sql.push_str(" GROUP BY prompt_clusters.id ORDER BY COUNT(*) DESC, prompt_clusters.id");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            Ok(PromptCluster {
+                id: row.get(0)?,
+                label: row.get(1)?,
+                terms: row
+                    .get::<_, String>(2)?
+                    .lines()
+                    .map(str::to_string)
+                    .collect(),
+                count: row.get(3)?,
+            })
+        })?;
+        let mut clusters = Vec::new();
+        for row in rows {
+            clusters.push(row?);
+        }
+        Ok(clusters)
+    }
+}
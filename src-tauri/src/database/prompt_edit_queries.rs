@@ -0,0 +1,121 @@
+use super::*;
+
+fn normalized_prompt_tags(prompt: &str) -> HashSet<String> {
+    extract_tags(prompt)
+        .into_iter()
+        .map(|tag| tag.trim().to_ascii_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+impl Database {
+    // ────────────────────────── Prompt edits ──────────────────────────
+
+    /// Streams the prompts of every visible, non-archived image, newest
+    /// first, without holding the whole library in memory. Returns how many
+    /// rows were visited.
+    pub fn for_each_prompt(&self, mut visit: impl FnMut(StoredPrompt)) -> SqlResult<usize> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = String::from(
+            "SELECT images.id, images.filename, images.prompt, images.negative_prompt,
+                    images.is_locked
+             FROM images
+             WHERE images.archived = 0
+               AND (images.prompt != '' OR images.negative_prompt != '')",
+        );
+        let mut params_vec = Vec::new();
+        self.append_private_filter(&mut sql, &mut params_vec);
+        sql.push_str(" ORDER BY images.id DESC");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            Ok(StoredPrompt {
+                id: row.get(0)?,
+                filename: row.get(1)?,
+                prompt: row.get(2)?,
+                negative_prompt: row.get(3)?,
+                is_locked: row.get(4)?,
+            })
+        })?;
+        let mut visited = 0usize;
+        for row in rows {
+            visit(row?);
+            visited += 1;
+        }
+        Ok(visited)
+    }
+
+    /// Writes edited prompts in one transaction. Tags extracted from the old
+    /// prompt are swapped for the new prompt's, while sidecar, manual and
+    /// auto-tagged ones stay, and the LoRA weights and embeddings are
+    /// re-read; the FTS triggers re-index the rows.
+    pub fn rewrite_prompts(&self, rewrites: &[PromptRewrite]) -> SqlResult<usize> {
+        if rewrites.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        {
+            let mut update_stmt = tx.prepare_cached(
+                "UPDATE images
+                 SET prompt = ?1, negative_prompt = ?2, raw_metadata = ?3, nsfw_score = ?4
+                 WHERE id = ?5
+                 RETURNING extra_params",
+            )?;
+            let mut remove_tag_stmt = tx.prepare_cached(
+                "DELETE FROM image_tags
+                 WHERE image_id = ?1 AND tag_id = (SELECT id FROM tags WHERE tag = ?2)",
+            )?;
+            let mut upsert_tag_stmt = tx.prepare_cached(
+                "INSERT INTO tags(tag) VALUES (?1)
+                 ON CONFLICT(tag) DO UPDATE SET tag=excluded.tag
+                 RETURNING id",
+            )?;
+            let mut insert_tag_stmt = tx.prepare_cached(
+                "INSERT OR IGNORE INTO image_tags(image_id, tag_id) VALUES (?1, ?2)",
+            )?;
+
+            for rewrite in rewrites {
+                let extra_params: Option<String> = update_stmt.query_row(
+                    params![
+                        rewrite.prompt,
+                        rewrite.negative_prompt,
+                        rewrite.raw_metadata,
+                        nsfw::prompt_score(&rewrite.prompt),
+                        rewrite.id
+                    ],
+                    |row| row.get(0),
+                )?;
+
+                let old_tags = normalized_prompt_tags(&rewrite.old_prompt);
+                let new_tags = normalized_prompt_tags(&rewrite.prompt);
+                for tag in old_tags.difference(&new_tags) {
+                    remove_tag_stmt.execute(params![rewrite.id, tag])?;
+                }
+                for tag in new_tags.difference(&old_tags) {
+                    let tag_id: i64 = upsert_tag_stmt.query_row([tag], |row| row.get(0))?;
+                    insert_tag_stmt.execute(params![rewrite.id, tag_id])?;
+                }
+                let edited = GenerationParams {
+                    prompt: rewrite.prompt.clone(),
+                    negative_prompt: rewrite.negative_prompt.clone(),
+                    raw_metadata: rewrite.raw_metadata.clone(),
+                    extra_params: embedding_queries::stored_extra_params(extra_params),
+                    ..Default::default()
+                };
+                lora_queries::replace_image_loras(&tx, rewrite.id, &extract_loras(&edited))?;
+                embedding_queries::replace_image_embeddings(
+                    &tx,
+                    rewrite.id,
+                    &extract_embeddings(&edited),
+                )?;
+            }
+        }
+        let ids: Vec<i64> = rewrites.iter().map(|rewrite| rewrite.id).collect();
+        collection_queries::apply_collection_rules(&tx, &ids)?;
+        tx.commit()?;
+
+        self.invalidate_records(&ids);
+        Ok(rewrites.len())
+    }
+}
@@ -0,0 +1,57 @@
+use super::*;
+use rusqlite::OptionalExtension;
+
+fn prompt_style_from_row(row: &Row<'_>) -> SqlResult<PromptStyle> {
+    Ok(PromptStyle {
+        name: row.get(0)?,
+        prompt: row.get(1)?,
+        negative_prompt: row.get(2)?,
+    })
+}
+
+impl Database {
+    // ────────────────────────── Prompt styles ──────────────────────────
+
+    /// Replaces the imported styles in one transaction, keeping the file's
+    /// order; an empty slice clears them.
+    pub fn replace_prompt_styles(&self, styles: &[PromptStyle]) -> SqlResult<usize> {
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM prompt_styles", [])?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR REPLACE INTO prompt_styles (name, prompt, negative_prompt, position)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (position, style) in styles.iter().enumerate() {
+                stmt.execute(params![
+                    style.name,
+                    style.prompt,
+                    style.negative_prompt,
+                    position as i64
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(styles.len())
+    }
+
+    pub fn list_prompt_styles(&self) -> SqlResult<Vec<PromptStyle>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT name, prompt, negative_prompt FROM prompt_styles ORDER BY position, name",
+        )?;
+        let rows = stmt.query_map([], prompt_style_from_row)?;
+        rows.collect()
+    }
+
+    pub fn get_prompt_style(&self, name: &str) -> SqlResult<Option<PromptStyle>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        conn.query_row(
+            "SELECT name, prompt, negative_prompt FROM prompt_styles WHERE name = ?1",
+            [name],
+            prompt_style_from_row,
+        )
+        .optional()
+    }
+}
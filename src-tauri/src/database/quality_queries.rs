@@ -0,0 +1,49 @@
+use super::*;
+use crate::image_quality::ImageQuality;
+
+impl Database {
+    // ────────────────────────── Image quality ──────────────────────────
+
+    /// Saves thumbnail sharpness and exposure by source filepath. Paths that are not
+    /// indexed (yet) are ignored.
+    pub fn set_image_quality(&self, quality: &[(String, ImageQuality)]) -> SqlResult<usize> {
+        if quality.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        let mut updated = 0;
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE images SET sharpness = ?1, dark_fraction = ?2, bright_fraction = ?3
+                 WHERE filepath = ?4",
+            )?;
+            for (filepath, measured) in quality {
+                updated += stmt.execute(params![
+                    measured.sharpness as f64,
+                    measured.dark_fraction as f64,
+                    measured.bright_fraction as f64,
+                    filepath
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    /// Filepaths of images whose sharpness or exposure has not been
+    /// measured.
+    pub fn filepaths_missing_image_quality(&self) -> SqlResult<HashSet<String>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare(
+            "SELECT filepath FROM images
+             WHERE (sharpness IS NULL OR dark_fraction IS NULL) AND archived = 0",
+        )?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut filepaths = HashSet::new();
+        for row in rows {
+            filepaths.insert(row?);
+        }
+        Ok(filepaths)
+    }
+}
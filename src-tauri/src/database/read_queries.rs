@@ -3,9 +3,18 @@ use super::*;
 impl Database {
     // ────────────────────────── Tag queries ──────────────────────────
 
-    /// Lists tags for autocomplete.
+    /// Lists tags for autocomplete. Once a tag dictionary is imported this
+    /// includes its tags, ordered by popularity.
     pub fn list_tags(&self, prefix: Option<&str>, limit: u32) -> SqlResult<Vec<String>> {
         let conn = self.pool.get().map_err(pool_error)?;
+        if tag_dictionary_queries::has_tag_dictionary(&conn)? {
+            drop(conn);
+            return Ok(self
+                .list_tag_suggestions(prefix, limit)?
+                .into_iter()
+                .map(|suggestion| suggestion.tag)
+                .collect());
+        }
         let mut tags: Vec<String> = Vec::new();
 
         if let Some(prefix) = prefix {
@@ -37,19 +46,28 @@ impl Database {
         Ok(tags)
     }
 
-    /// Returns most common tags for quick filtering.
+    /// Returns most common tags for quick filtering. Images under a locked
+    /// private directory don't count.
     pub fn get_top_tags(&self, limit: u32) -> SqlResult<Vec<TagCount>> {
         let conn = self.pool.get().map_err(pool_error)?;
-        let mut stmt = conn.prepare(
+        let mut sql = String::from(
             "SELECT tags.tag, COUNT(*) as usage_count
              FROM tags
              JOIN image_tags ON image_tags.tag_id = tags.id
-             GROUP BY tags.id, tags.tag
+             JOIN images ON images.id = image_tags.image_id
+             WHERE 1=1",
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        sql.push_str(
+            " GROUP BY tags.id, tags.tag
              ORDER BY usage_count DESC, tags.tag ASC
-             LIMIT ?1",
-        )?;
+             LIMIT ?",
+        );
+        params.push(Value::Integer(i64::from(limit)));
 
-        let rows = stmt.query_map(params![limit], |row| {
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
             Ok(TagCount {
                 tag: row.get::<_, String>(0)?,
                 count: row.get::<_, u32>(1)?,
@@ -84,26 +102,34 @@ impl Database {
 
     // ────────────────────── Group-by queries ──────────────────────
 
-    /// Returns unique directories with image counts for group-by view.
+    /// Returns unique directories with image counts for group-by view,
+    /// pinned ones first.
     pub fn get_unique_directories(&self) -> SqlResult<Vec<DirectoryEntry>> {
         let conn = self.pool.get().map_err(pool_error)?;
         let mut stmt = conn.prepare(
-            "SELECT directory, COUNT(*) as cnt
-             FROM images
-             GROUP BY directory
-             ORDER BY cnt DESC, directory ASC",
+            "SELECT counts.directory, counts.cnt, COALESCE(meta.pinned, 0), meta.alias,
+                    COALESCE(meta.hidden, 0)
+             FROM (SELECT directory, COUNT(*) as cnt FROM images GROUP BY directory) AS counts
+             LEFT JOIN directory_meta AS meta ON meta.directory = counts.directory
+             ORDER BY COALESCE(meta.pinned, 0) DESC, counts.cnt DESC, counts.directory ASC",
         )?;
 
         let rows = stmt.query_map([], |row| {
             Ok(DirectoryEntry {
                 directory: row.get::<_, String>(0)?,
                 count: row.get::<_, u32>(1)?,
+                pinned: row.get::<_, bool>(2)?,
+                alias: row.get::<_, Option<String>>(3)?,
+                hidden: row.get::<_, bool>(4)?,
             })
         })?;
 
         let mut dirs = Vec::new();
         for row in rows {
-            dirs.push(row?);
+            let entry = row?;
+            if !self.is_private_path(&format!("{}/", entry.directory)) {
+                dirs.push(entry);
+            }
         }
         Ok(dirs)
     }
@@ -111,14 +137,16 @@ impl Database {
     /// Returns unique model names with image counts for group-by view.
     pub fn get_unique_models(&self) -> SqlResult<Vec<ModelEntry>> {
         let conn = self.pool.get().map_err(pool_error)?;
-        let mut stmt = conn.prepare(
+        let mut sql = String::from(
             "SELECT COALESCE(model_name, 'Unknown') as model, COUNT(*) as cnt
-             FROM images
-             GROUP BY model
-             ORDER BY cnt DESC, model ASC",
-        )?;
+             FROM images WHERE 1=1",
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        sql.push_str(" GROUP BY model ORDER BY cnt DESC, model ASC");
 
-        let rows = stmt.query_map([], |row| {
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
             Ok(ModelEntry {
                 model_name: row.get::<_, String>(0)?,
                 count: row.get::<_, u32>(1)?,
@@ -132,14 +160,166 @@ impl Database {
         Ok(models)
     }
 
+    /// Returns the tools that wrote the library's metadata with image
+    /// counts; the names are what `source_tools` accepts.
+    pub fn get_source_tools(&self) -> SqlResult<Vec<SourceToolEntry>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = String::from(
+            "SELECT COALESCE(source_tool, 'unknown') AS tool, COUNT(*) AS cnt
+             FROM images WHERE 1=1",
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(" GROUP BY tool ORDER BY cnt DESC, tool ASC");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok(SourceToolEntry {
+                source_tool: row.get::<_, String>(0)?,
+                count: row.get::<_, u32>(1)?,
+            })
+        })?;
+
+        let mut tools = Vec::new();
+        for row in rows {
+            tools.push(row?);
+        }
+        Ok(tools)
+    }
+
+    /// Returns samplers with image counts, case-insensitively merged.
+    /// Schedulers have their own facet, `get_unique_schedulers`.
+    pub fn get_unique_samplers(&self) -> SqlResult<Vec<SamplerEntry>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = format!(
+            "SELECT COALESCE(NULLIF(TRIM(sampler), ''), '{}') AS label, COUNT(*) AS cnt
+             FROM images WHERE 1=1",
+            UNKNOWN_SAMPLER
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(" GROUP BY label COLLATE NOCASE ORDER BY cnt DESC, label ASC");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok(SamplerEntry {
+                sampler: row.get::<_, String>(0)?,
+                count: row.get::<_, u32>(1)?,
+            })
+        })?;
+
+        let mut samplers = Vec::new();
+        for row in rows {
+            samplers.push(row?);
+        }
+        Ok(samplers)
+    }
+
+    /// Returns schedulers with image counts; the names are what
+    /// `scheduler_filters` accepts.
+    pub fn get_unique_schedulers(&self) -> SqlResult<Vec<SchedulerEntry>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = format!(
+            "SELECT {} AS scheduler, COUNT(*) AS cnt FROM images WHERE 1=1",
+            scheduler_label_sql(None)
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(" GROUP BY scheduler COLLATE NOCASE ORDER BY cnt DESC, scheduler ASC");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok(SchedulerEntry {
+                scheduler: row.get::<_, String>(0)?,
+                count: row.get::<_, u32>(1)?,
+            })
+        })?;
+
+        let mut schedulers = Vec::new();
+        for row in rows {
+            schedulers.push(row?);
+        }
+        Ok(schedulers)
+    }
+
+    /// Returns resolution buckets with image counts, most used first.
+    /// Images without a recorded size are left out.
+    pub fn get_resolution_buckets(&self) -> SqlResult<Vec<ResolutionBucket>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = format!(
+            "SELECT {} AS bucket, COUNT(*) AS cnt FROM images
+             WHERE width > 0 AND height > 0",
+            resolution_bucket_sql(Some("images"))
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(" GROUP BY bucket ORDER BY cnt DESC, bucket ASC");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?))
+        })?;
+
+        let mut buckets = Vec::new();
+        for row in rows {
+            let (bucket, count) = row?;
+            let Some((width, height)) = bucket
+                .split_once('x')
+                .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            else {
+                continue;
+            };
+            buckets.push(ResolutionBucket {
+                tier: resolution_tier(width, height).to_string(),
+                bucket,
+                width,
+                height,
+                count,
+            });
+        }
+        Ok(buckets)
+    }
+
     // ────────────────────────── By-id queries ──────────────────────────
 
-    /// Fetches records by explicit ids (used by export).
+    /// Fetches records by explicit ids (used by export), newest first.
+    /// Cached rows are served from memory; only the rest hit SQLite.
     pub fn get_images_by_ids(&self, ids: &[i64]) -> SqlResult<Vec<ImageRecord>> {
         if ids.is_empty() {
             return Ok(Vec::new());
         }
 
+        let mut results = Vec::with_capacity(ids.len());
+        let mut missing = Vec::new();
+        let mut seen = HashSet::with_capacity(ids.len());
+        for id in ids {
+            if !seen.insert(*id) {
+                continue;
+            }
+            match self.cached_record(*id) {
+                Some(record) => results.push(record),
+                None => missing.push(*id),
+            }
+        }
+
+        let generation = self.record_cache_generation();
+        let fetched = self.fetch_images_by_ids(&missing)?;
+        self.cache_records(&fetched, generation);
+        results.extend(fetched);
+        self.retain_public(&mut results, |record| &record.filepath);
+        results.sort_unstable_by_key(|record| std::cmp::Reverse(record.id));
+        Ok(results)
+    }
+
+    fn fetch_images_by_ids(&self, ids: &[i64]) -> SqlResult<Vec<ImageRecord>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
         let conn = self.pool.get().map_err(pool_error)?;
         let placeholders = vec!["?"; ids.len()].join(", ");
         let sql = format!(
@@ -163,12 +343,64 @@ impl Database {
         Ok(results)
     }
 
+    /// Fetches records with their tags in a single query, newest first.
+    pub fn get_image_details(&self, ids: &[i64]) -> SqlResult<Vec<ImageDetail>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.pool.get().map_err(pool_error)?;
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        // Unit separator: tags are free text and may contain commas.
+        let sql = format!(
+            "SELECT images.id, images.filepath, images.filename, images.directory,
+                    images.prompt, images.negative_prompt, images.steps, images.sampler,
+                    images.cfg_scale, images.seed, images.width, images.height,
+                    images.model_hash, images.model_name, images.raw_metadata,
+                    images.is_favorite, images.is_locked,
+                    GROUP_CONCAT(tags.tag, char(31)), images.workflow_status, images.ocr_text
+             FROM images
+             LEFT JOIN image_tags ON image_tags.image_id = images.id
+             LEFT JOIN tags ON tags.id = image_tags.tag_id
+             WHERE images.id IN ({})
+             GROUP BY images.id
+             ORDER BY images.id DESC",
+            placeholders
+        );
+
+        let params: Vec<Value> = ids.iter().map(|id| Value::Integer(*id)).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            let record = image_record_from_row(row)?;
+            let joined: Option<String> = row.get(17)?;
+            let mut tags: Vec<String> = joined
+                .map(|value| value.split('\u{1f}').map(str::to_string).collect())
+                .unwrap_or_default();
+            tags.sort_unstable();
+            Ok(ImageDetail {
+                record,
+                tags,
+                workflow_status: WorkflowStatus::from_stored(&row.get::<_, String>(18)?),
+                ocr_text: row.get(19)?,
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        self.retain_public(&mut results, |detail| &detail.record.filepath);
+        Ok(results)
+    }
+
     /// Returns total indexed image count.
     pub fn get_total_count(&self) -> SqlResult<u32> {
         let conn = self.pool.get().map_err(pool_error)?;
-        conn.query_row("SELECT COUNT(*) FROM images", [], |row| {
-            row.get::<_, u32>(0)
-        })
+        let mut sql = String::from("SELECT COUNT(*) FROM images WHERE 1=1");
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        conn.query_row(&sql, params_from_iter(params), |row| row.get::<_, u32>(0))
     }
 
     /// Returns all indexed source image paths ordered newest-first.
@@ -184,8 +416,122 @@ impl Database {
         Ok(filepaths)
     }
 
+    /// Returns `(id, filepath)` for every indexed image, newest first.
+    pub fn get_all_image_locations(&self) -> SqlResult<Vec<(i64, String)>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare("SELECT id, filepath FROM images ORDER BY id DESC")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut locations = Vec::new();
+        for row in rows {
+            locations.push(row?);
+        }
+        Ok(locations)
+    }
+
+    /// Like [`Self::get_all_image_locations`] but without archived rows,
+    /// whose filepath still names the original location the file left.
+    pub fn get_unarchived_image_locations(&self) -> SqlResult<Vec<(i64, String)>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt =
+            conn.prepare("SELECT id, filepath FROM images WHERE archived = 0 ORDER BY id DESC")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut locations = Vec::new();
+        for row in rows {
+            locations.push(row?);
+        }
+        Ok(locations)
+    }
+
+    /// Groups images whose quick hash collides. Each group has 2+ members,
+    /// oldest first so the original copy leads. Archived images and locked
+    /// private folders are left out.
+    pub fn find_quick_hash_duplicates(&self) -> SqlResult<Vec<DuplicateGroup>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare(
+            "SELECT quick_hash, id, filepath, is_locked OR is_favorite
+             FROM images
+             WHERE archived = 0 AND quick_hash IN (
+                SELECT quick_hash FROM images
+                WHERE quick_hash IS NOT NULL AND quick_hash != '' AND archived = 0
+                GROUP BY quick_hash
+                HAVING COUNT(*) > 1
+             )
+             ORDER BY quick_hash ASC, id ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, bool>(3)?,
+            ))
+        })?;
+
+        let mut groups: Vec<(DuplicateGroup, bool)> = Vec::new();
+        for row in rows {
+            let (quick_hash, id, filepath, keeper) = row?;
+            if self.is_private_path(&filepath) {
+                continue;
+            }
+            match groups.last_mut() {
+                Some((group, has_keeper)) if group.quick_hash == quick_hash => {
+                    group.ids.push(id);
+                    group.filepaths.push(filepath);
+                    if keeper && !*has_keeper {
+                        group.keep_id = id;
+                        *has_keeper = true;
+                    }
+                }
+                _ => groups.push((
+                    DuplicateGroup {
+                        quick_hash,
+                        sha256: None,
+                        ids: vec![id],
+                        filepaths: vec![filepath],
+                        keep_id: id,
+                    },
+                    keeper,
+                )),
+            }
+        }
+        Ok(groups
+            .into_iter()
+            .map(|(group, _)| group)
+            .filter(|group| group.ids.len() > 1)
+            .collect())
+    }
+
+    /// Id and path of every visible image with this quick hash, oldest first.
+    pub fn find_images_by_quick_hash(&self, quick_hash: &str) -> SqlResult<Vec<(i64, String)>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt =
+            conn.prepare("SELECT id, filepath FROM images WHERE quick_hash = ?1 ORDER BY id ASC")?;
+        let rows = stmt.query_map([quick_hash], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut matches = Vec::new();
+        for row in rows {
+            let (id, filepath) = row?;
+            if !self.is_private_path(&filepath) {
+                matches.push((id, filepath));
+            }
+        }
+        Ok(matches)
+    }
+
     /// Returns a single image by id.
     pub fn get_image_by_id(&self, id: i64) -> SqlResult<Option<ImageRecord>> {
+        if let Some(record) = self.cached_record(id) {
+            return Ok((!self.is_private_path(&record.filepath)).then_some(record));
+        }
+
+        let generation = self.record_cache_generation();
         let conn = self.pool.get().map_err(pool_error)?;
         let mut stmt = conn.prepare(
             "SELECT id, filepath, filename, directory, prompt, negative_prompt,
@@ -198,7 +544,10 @@ impl Database {
 
         let mut rows = stmt.query_map(params![id], image_record_from_row)?;
         match rows.next() {
-            Some(Ok(record)) => Ok(Some(record)),
+            Some(Ok(record)) => {
+                self.cache_records(std::slice::from_ref(&record), generation);
+                Ok((!self.is_private_path(&record.filepath)).then_some(record))
+            }
             _ => Ok(None),
         }
     }
@@ -0,0 +1,95 @@
+use super::*;
+
+/// Hot `ImageRecord`s kept in memory; sized for a detail panel plus a Forge batch.
+pub(super) const RECORD_CACHE_CAPACITY: usize = 512;
+
+/// Small LRU keyed by image id. Eviction scans for the oldest entry, which is
+/// cheap at this capacity and avoids a linked-list dependency.
+pub(super) struct RecordCache {
+    capacity: usize,
+    tick: u64,
+    /// Bumped by every invalidation so rows read before a write are not cached.
+    generation: u64,
+    entries: HashMap<i64, (ImageRecord, u64)>,
+}
+
+impl RecordCache {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tick: 0,
+            generation: 0,
+            entries: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub(super) fn get(&mut self, id: i64) -> Option<ImageRecord> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(&id).map(|(record, last_used)| {
+            *last_used = tick;
+            record.clone()
+        })
+    }
+
+    pub(super) fn insert(&mut self, record: ImageRecord) {
+        self.tick += 1;
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&record.id) {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(id, _)| *id)
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(record.id, (record, self.tick));
+    }
+
+    pub(super) fn remove(&mut self, ids: &[i64]) {
+        self.generation += 1;
+        for id in ids {
+            self.entries.remove(id);
+        }
+    }
+}
+
+impl Database {
+    pub(super) fn cached_record(&self, id: i64) -> Option<ImageRecord> {
+        let record = self
+            .record_cache
+            .lock()
+            .ok()
+            .and_then(|mut cache| cache.get(id));
+        crate::perf::record_record_cache(record.is_some());
+        record
+    }
+
+    /// Generation to pass to [`Database::cache_records`] for rows read afterwards.
+    pub(super) fn record_cache_generation(&self) -> u64 {
+        self.record_cache
+            .lock()
+            .map(|cache| cache.generation)
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Caches rows read at `generation`, unless a write happened since.
+    pub(super) fn cache_records(&self, records: &[ImageRecord], generation: u64) {
+        if let Ok(mut cache) = self.record_cache.lock() {
+            if cache.generation != generation {
+                return;
+            }
+            for record in records {
+                cache.insert(record.clone());
+            }
+        }
+    }
+
+    /// Drops cached rows after a write touching `ids`.
+    pub(super) fn invalidate_records(&self, ids: &[i64]) {
+        if let Ok(mut cache) = self.record_cache.lock() {
+            cache.remove(ids);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use super::*;
+
+/// Most images returned for one seed sweep.
+const MAX_SEED_NEIGHBORS: u32 = 500;
+
+impl Database {
+    // ────────────────────────── Seed sweeps ──────────────────────────
+
+    /// Images sharing `image_id`'s prompt, negative prompt and model whose
+    /// seed is within `window` of its seed, or that were generated from the
+    /// same variation seed. Sorted by seed, then variation strength, so a
+    /// sweep reads as a series; includes the image itself, and is empty when
+    /// it is unknown or hidden.
+    pub fn get_seed_neighbors(
+        &self,
+        image_id: i64,
+        window: u32,
+    ) -> SqlResult<Vec<GalleryImageRecord>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let variation_seed = extra_param_expr_on("images.extra_params", "Variation seed");
+        let anchor_variation_seed = extra_param_expr_on("anchor.extra_params", "Variation seed");
+        let variation_strength =
+            extra_param_expr_on("images.extra_params", "Variation seed strength");
+        let mut sql = format!(
+            "SELECT images.id, images.filepath, images.filename, images.directory,
+                    images.seed, images.width, images.height, images.model_name, images.is_favorite, images.is_locked
+             FROM images
+             JOIN images AS anchor ON anchor.id = ?
+             WHERE (images.id = anchor.id OR (
+                    anchor.prompt != ''
+                    AND images.prompt = anchor.prompt
+                    AND images.negative_prompt = anchor.negative_prompt
+                    AND images.model_name IS anchor.model_name
+                    AND images.archived = anchor.archived
+                    AND ((TRIM(COALESCE(anchor.seed, '')) != ''
+                          AND TRIM(COALESCE(images.seed, '')) != ''
+                          AND abs(CAST(images.seed AS INTEGER) - CAST(anchor.seed AS INTEGER)) <= ?)
+                         OR {variation_seed} = {anchor_variation_seed})))"
+        );
+        let mut params_vec = vec![Value::Integer(image_id), Value::Integer(window as i64)];
+        self.append_nsfw_filter(&mut sql, &mut params_vec);
+        self.append_private_filter(&mut sql, &mut params_vec);
+        sql.push_str(&format!(
+            " ORDER BY CAST(images.seed AS INTEGER), CAST({variation_strength} AS REAL), images.id
+              LIMIT ?"
+        ));
+        params_vec.push(Value::Integer(MAX_SEED_NEIGHBORS as i64));
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), gallery_image_record_from_row)?;
+        let mut neighbors = Vec::new();
+        for row in rows {
+            neighbors.push(row?);
+        }
+        Ok(neighbors)
+    }
+}
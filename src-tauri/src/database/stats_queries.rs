@@ -0,0 +1,355 @@
+use super::*;
+
+/// Most terms charted at once; each one is a scan of the images table.
+const MAX_TREND_TERMS: usize = 12;
+
+/// When an image was generated. File mtime is the generation time for Forge
+/// outputs; rows without one fall back to when they were indexed.
+pub(super) const IMAGE_TIMESTAMP: &str =
+    "COALESCE(images.file_mtime, strftime('%s', images.created_at))";
+
+const MODEL_NAME: &str = "COALESCE(NULLIF(images.model_name, ''), 'Unknown')";
+
+/// SQL for the start date of an image's period.
+fn period_expression(bucket: TrendBucket) -> String {
+    let modifiers = match bucket {
+        TrendBucket::Week => "'-6 days', 'weekday 1'",
+        TrendBucket::Month => "'start of month'",
+    };
+    format!("date({}, 'unixepoch', {})", IMAGE_TIMESTAMP, modifiers)
+}
+
+fn period_index(periods: &[String]) -> HashMap<&str, usize> {
+    periods
+        .iter()
+        .enumerate()
+        .map(|(position, period)| (period.as_str(), position))
+        .collect()
+}
+
+fn count_row(row: &Row<'_>) -> SqlResult<(Option<String>, u32)> {
+    Ok((row.get(0)?, row.get(1)?))
+}
+
+impl Database {
+    // ────────────────────────── Statistics ──────────────────────────
+
+    /// Every period with at least one image, oldest first, with its image count.
+    fn period_totals(&self, conn: &Connection, period: &str) -> SqlResult<(Vec<String>, Vec<u32>)> {
+        let mut sql = format!(
+            "SELECT {} AS period, COUNT(*) FROM images WHERE 1=1",
+            period
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(" GROUP BY period ORDER BY period ASC");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), count_row)?;
+        let mut periods = Vec::new();
+        let mut totals = Vec::new();
+        for row in rows {
+            if let (Some(period), total) = row? {
+                periods.push(period);
+                totals.push(total);
+            }
+        }
+        Ok((periods, totals))
+    }
+
+    /// Counts images per period whose prompt contains each term or that carry
+    /// it as a tag (case-insensitive).
+    pub fn get_keyword_trends(
+        &self,
+        terms: &[String],
+        bucket: TrendBucket,
+    ) -> SqlResult<KeywordTrends> {
+        let mut normalized: Vec<String> = Vec::new();
+        for term in terms {
+            let term = term.trim().to_lowercase();
+            if !term.is_empty() && !normalized.contains(&term) {
+                normalized.push(term);
+            }
+        }
+        normalized.truncate(MAX_TREND_TERMS);
+
+        let conn = self.pool.get().map_err(pool_error)?;
+        let period = period_expression(bucket);
+        let (periods, totals) = self.period_totals(&conn, &period)?;
+        let index = period_index(&periods);
+
+        let mut series = Vec::with_capacity(normalized.len());
+        for term in normalized {
+            let mut sql = format!(
+                "SELECT {} AS period, COUNT(*) FROM images
+                 WHERE (instr(lower(images.prompt), ?) > 0
+                        OR EXISTS (SELECT 1 FROM image_tags
+                                   JOIN tags ON tags.id = image_tags.tag_id
+                                   WHERE image_tags.image_id = images.id AND tags.tag = ?))",
+                period
+            );
+            let mut params = vec![Value::Text(term.clone()), Value::Text(term.clone())];
+            self.append_private_filter(&mut sql, &mut params);
+            self.append_project_filter(&mut sql, &mut params);
+            sql.push_str(" GROUP BY period");
+
+            let mut counts = vec![0u32; periods.len()];
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params_from_iter(params), count_row)?;
+            for row in rows {
+                let (period, count) = row?;
+                if let Some(position) = period.as_deref().and_then(|period| index.get(period)) {
+                    counts[*position] = count;
+                }
+            }
+            series.push(KeywordSeries { term, counts });
+        }
+
+        Ok(KeywordTrends {
+            bucket,
+            periods,
+            totals,
+            series,
+        })
+    }
+
+    /// Image counts per model and period, plus each model's average settings
+    /// and first/last use.
+    pub fn get_model_usage(&self, bucket: TrendBucket) -> SqlResult<ModelUsage> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let period = period_expression(bucket);
+        let (periods, _) = self.period_totals(&conn, &period)?;
+        let index = period_index(&periods);
+
+        let mut sql = format!(
+            "SELECT {model} AS model, COUNT(*),
+                    AVG(images.steps), AVG(images.cfg_scale),
+                    AVG(images.width), AVG(images.height),
+                    date(MIN({timestamp}), 'unixepoch'), date(MAX({timestamp}), 'unixepoch')
+             FROM images WHERE 1=1",
+            model = MODEL_NAME,
+            timestamp = IMAGE_TIMESTAMP
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(" GROUP BY model ORDER BY COUNT(*) DESC, model ASC");
+        let mut models = Vec::new();
+        {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params_from_iter(params), |row| {
+                Ok(ModelUsageSeries {
+                    model_name: row.get(0)?,
+                    total: row.get(1)?,
+                    counts: vec![0; periods.len()],
+                    avg_steps: row.get(2)?,
+                    avg_cfg_scale: row.get(3)?,
+                    avg_width: row.get(4)?,
+                    avg_height: row.get(5)?,
+                    first_used: row.get(6)?,
+                    last_used: row.get(7)?,
+                })
+            })?;
+            for row in rows {
+                models.push(row?);
+            }
+        }
+        let positions: HashMap<String, usize> = models
+            .iter()
+            .enumerate()
+            .map(|(position, usage)| (usage.model_name.clone(), position))
+            .collect();
+
+        let mut sql = format!(
+            "SELECT {} AS model, {} AS period, COUNT(*) FROM images WHERE 1=1",
+            MODEL_NAME, period
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(" GROUP BY model, period");
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, u32>(2)?,
+            ))
+        })?;
+        for row in rows {
+            let (model_name, period, count) = row?;
+            let model_position = positions.get(&model_name);
+            let period_position = period.as_deref().and_then(|period| index.get(period));
+            if let (Some(&model_position), Some(&period_position)) =
+                (model_position, period_position)
+            {
+                models[model_position].counts[period_position] = count;
+            }
+        }
+
+        Ok(ModelUsage {
+            bucket,
+            periods,
+            models,
+        })
+    }
+
+    /// Disk space by directory, model and generation type, plus the largest
+    /// grids. Each grouping keeps its `limit` biggest entries.
+    pub fn get_disk_usage_report(&self, limit: u32) -> SqlResult<DiskUsageReport> {
+        let conn = self.pool.get().map_err(pool_error)?;
+
+        let mut sql = String::from(
+            "SELECT COALESCE(SUM(images.file_size), 0), COUNT(*),
+                    COUNT(*) - COUNT(images.file_size)
+             FROM images WHERE 1=1",
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        let (total_bytes, total_images, unsized_images) =
+            conn.query_row(&sql, params_from_iter(params), |row| {
+                Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get(2)?))
+            })?;
+
+        let by_directory = self.disk_usage_by(&conn, "images.directory", limit)?;
+        let by_model = self.disk_usage_by(&conn, MODEL_NAME, limit)?;
+        let by_generation_type = self.disk_usage_by(
+            &conn,
+            "COALESCE(NULLIF(images.generation_type, ''), 'unknown')",
+            limit,
+        )?;
+
+        let mut sql = String::from(
+            "SELECT images.id, images.filepath, images.file_size
+             FROM images WHERE images.file_size IS NOT NULL",
+        );
+        let mut params: Vec<Value> = Vec::new();
+        append_generation_type_filter(&mut sql, &mut params, &["grid".to_string()]);
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(" ORDER BY images.file_size DESC LIMIT ?");
+        params.push(Value::Integer(limit as i64));
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok(DiskUsageFile {
+                id: row.get(0)?,
+                filepath: row.get(1)?,
+                bytes: row.get(2)?,
+            })
+        })?;
+        let mut largest_grids = Vec::new();
+        for row in rows {
+            largest_grids.push(row?);
+        }
+
+        Ok(DiskUsageReport {
+            total_bytes,
+            total_images,
+            unsized_images,
+            by_directory,
+            by_model,
+            by_generation_type,
+            largest_grids,
+        })
+    }
+
+    fn disk_usage_by(
+        &self,
+        conn: &Connection,
+        key: &str,
+        limit: u32,
+    ) -> SqlResult<Vec<DiskUsageEntry>> {
+        let mut sql = format!(
+            "SELECT {} AS usage_key, COALESCE(SUM(images.file_size), 0) AS bytes, COUNT(*)
+             FROM images WHERE 1=1",
+            key
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(" GROUP BY usage_key ORDER BY bytes DESC, usage_key ASC LIMIT ?");
+        params.push(Value::Integer(limit as i64));
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok(DiskUsageEntry {
+                key: row.get(0)?,
+                bytes: row.get(1)?,
+                count: row.get(2)?,
+            })
+        })?;
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row?);
+        }
+        Ok(entries)
+    }
+
+    /// Prompt, model and seed coverage, for finding images indexed from a
+    /// sidecar `.txt` or whose metadata failed to parse.
+    pub fn get_metadata_coverage(&self, limit: u32) -> SqlResult<MetadataCoverage> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = format!(
+            "SELECT COUNT(*),
+                    COUNT(*) FILTER (WHERE TRIM(COALESCE(images.prompt, '')) != ''),
+                    COUNT(*) FILTER (WHERE TRIM(COALESCE(images.model_name, '')) != ''),
+                    COUNT(*) FILTER (WHERE TRIM(COALESCE(images.seed, '')) != ''),
+                    COUNT(*) FILTER (WHERE NOT {missing}),
+                    COUNT(*) FILTER (WHERE TRIM(COALESCE(images.raw_metadata, '')) = '')
+             FROM images WHERE images.archived = 0",
+            missing = MISSING_METADATA_CONDITION
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        let counts = conn.query_row(&sql, params_from_iter(params), |row| {
+            Ok([
+                row.get::<_, u32>(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+            ])
+        })?;
+        let [total_images, with_prompt, with_model, with_seed, complete, without_metadata] = counts;
+
+        let mut sql = format!(
+            "SELECT images.directory, COUNT(*) FILTER (WHERE {missing}) AS incomplete, COUNT(*)
+             FROM images WHERE images.archived = 0",
+            missing = MISSING_METADATA_CONDITION
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(
+            " GROUP BY images.directory HAVING incomplete > 0
+              ORDER BY incomplete DESC, images.directory ASC LIMIT ?",
+        );
+        params.push(Value::Integer(limit as i64));
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok(MetadataGap {
+                directory: row.get(0)?,
+                incomplete: row.get(1)?,
+                total: row.get(2)?,
+            })
+        })?;
+        let mut incomplete_by_directory = Vec::new();
+        for row in rows {
+            incomplete_by_directory.push(row?);
+        }
+
+        Ok(MetadataCoverage {
+            total_images,
+            with_prompt,
+            with_model,
+            with_seed,
+            complete,
+            without_metadata,
+            incomplete_by_directory,
+        })
+    }
+}
@@ -0,0 +1,47 @@
+use super::*;
+use crate::spelling::Vocabulary;
+
+/// Tags considered when building "did you mean" suggestions.
+const SUGGESTION_VOCABULARY_TAGS: u32 = 5000;
+const MAX_SEARCH_SUGGESTIONS: usize = 5;
+
+impl Database {
+    // ────────────────────────── Search suggestions ──────────────────────────
+
+    /// Spelling corrections for a query that matched nothing, drawn from the
+    /// most used tags and the words inside them. Tags only found under a
+    /// locked private directory are never suggested.
+    pub fn search_suggestions(&self, query: &str) -> SqlResult<Vec<String>> {
+        let query = query.trim();
+        if !contains_search_token(query) {
+            return Ok(Vec::new());
+        }
+        let mut vocabulary = Vocabulary::default();
+        for tag in self.get_top_tags(SUGGESTION_VOCABULARY_TAGS)? {
+            let phrase = tag
+                .tag
+                .strip_prefix(crate::autotag::AUTO_TAG_PREFIX)
+                .unwrap_or(&tag.tag);
+            vocabulary.add_phrase(phrase, tag.count);
+        }
+        if vocabulary.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(vocabulary.suggest(query, MAX_SEARCH_SUGGESTIONS))
+    }
+
+    /// Fills `page.suggestions` when the first page of a search came back empty.
+    pub(super) fn with_search_suggestions(
+        &self,
+        mut page: CursorPage,
+        query: Option<&str>,
+        cursor: Option<&str>,
+    ) -> SqlResult<CursorPage> {
+        if page.items.is_empty() && cursor.is_none() {
+            if let Some(query) = query {
+                page.suggestions = self.search_suggestions(query)?;
+            }
+        }
+        Ok(page)
+    }
+}
@@ -0,0 +1,91 @@
+use super::*;
+
+/// Upper bound for a prefix range scan: sorts after every valid UTF-8 string
+/// starting with the prefix.
+fn prefix_upper_bound(prefix: &str) -> String {
+    format!("{}{}", prefix, char::MAX)
+}
+
+pub(super) fn has_tag_dictionary(conn: &Connection) -> SqlResult<bool> {
+    conn.query_row("SELECT EXISTS(SELECT 1 FROM tag_dictionary)", [], |row| {
+        row.get(0)
+    })
+}
+
+impl Database {
+    // ────────────────────────── Tag dictionary ──────────────────────────
+
+    /// Replaces the imported dictionary in one transaction; an empty slice
+    /// clears it.
+    pub fn replace_tag_dictionary(&self, entries: &[DictionaryTag]) -> SqlResult<usize> {
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM tag_dictionary", [])?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR IGNORE INTO tag_dictionary (tag, category, post_count)
+                 VALUES (?1, ?2, ?3)",
+            )?;
+            for entry in entries {
+                stmt.execute(params![entry.tag, entry.category, entry.post_count])?;
+            }
+        }
+        tx.commit()?;
+        Ok(entries.len())
+    }
+
+    /// Autocomplete candidates from both the library and the dictionary,
+    /// most popular first. Dictionary tags appear even when no image uses them.
+    pub fn list_tag_suggestions(
+        &self,
+        prefix: Option<&str>,
+        limit: u32,
+    ) -> SqlResult<Vec<TagSuggestion>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let prefix = prefix.unwrap_or("").trim().to_lowercase();
+        let upper = prefix_upper_bound(&prefix);
+
+        let mut stmt = conn.prepare(
+            "WITH library AS (
+                 SELECT tags.tag AS tag, COUNT(image_tags.image_id) AS library_count
+                 FROM tags
+                 LEFT JOIN image_tags ON image_tags.tag_id = tags.id
+                 WHERE tags.tag >= ?1 AND tags.tag < ?2
+                 GROUP BY tags.id
+             ),
+             dictionary AS (
+                 SELECT tag, category, post_count
+                 FROM tag_dictionary
+                 WHERE tag >= ?1 AND tag < ?2
+             )
+             SELECT tag, category, post_count, library_count FROM (
+                 SELECT library.tag AS tag, dictionary.category AS category,
+                        dictionary.post_count AS post_count, library.library_count AS library_count
+                 FROM library
+                 LEFT JOIN dictionary ON dictionary.tag = library.tag
+                 UNION ALL
+                 SELECT dictionary.tag, dictionary.category, dictionary.post_count, 0
+                 FROM dictionary
+                 WHERE dictionary.tag NOT IN (SELECT tag FROM library)
+             )
+             ORDER BY COALESCE(post_count, 0) DESC, library_count DESC, tag ASC
+             LIMIT ?3",
+        )?;
+        let rows = stmt.query_map(params![prefix, upper, limit], |row| {
+            Ok(TagSuggestion {
+                tag: row.get(0)?,
+                category: row
+                    .get::<_, Option<i64>>(1)?
+                    .map(|category| tag_dictionary::category_name(category).to_string()),
+                post_count: row.get(2)?,
+                library_count: row.get(3)?,
+            })
+        })?;
+
+        let mut suggestions = Vec::new();
+        for row in rows {
+            suggestions.push(row?);
+        }
+        Ok(suggestions)
+    }
+}
@@ -0,0 +1,138 @@
+use super::*;
+use crate::image_processing::{ThumbnailFailure, ThumbnailFailureKind};
+
+/// Decode failures in a row after which a source is marked corrupt. A single
+/// failure can be a file caught mid-write; three rarely are.
+const CORRUPT_AFTER_FAILED_DECODES: i64 = 3;
+
+impl Database {
+    // ────────────────────────── Thumbnail failures ──────────────────────────
+
+    /// Records failed thumbnails by source filepath, counting repeat
+    /// failures, and marks sources that keep failing to decode as corrupt.
+    /// Paths that are not indexed are ignored.
+    pub fn record_thumbnail_failures(&self, failures: &[ThumbnailFailure]) -> SqlResult<usize> {
+        if failures.is_empty() {
+            return Ok(0);
+        }
+        let failed_at = chrono::Utc::now().timestamp();
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        let mut recorded = 0;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO thumbnail_failures (image_id, kind, message, attempts, failed_at)
+                 SELECT id, ?2, ?3, 1, ?4 FROM images WHERE filepath = ?1
+                 ON CONFLICT(image_id) DO UPDATE SET
+                    kind = excluded.kind,
+                    message = excluded.message,
+                    attempts = attempts + 1,
+                    failed_at = excluded.failed_at",
+            )?;
+            for failure in failures {
+                recorded += stmt.execute(params![
+                    failure.filepath,
+                    failure.kind.as_str(),
+                    failure.message,
+                    failed_at
+                ])?;
+            }
+        }
+        tx.execute(
+            "UPDATE images SET is_corrupt = 1
+             WHERE is_corrupt = 0
+               AND id IN (SELECT image_id FROM thumbnail_failures
+                          WHERE kind = ?1 AND attempts >= ?2)",
+            params![
+                ThumbnailFailureKind::Decode.as_str(),
+                CORRUPT_AFTER_FAILED_DECODES
+            ],
+        )?;
+        tx.commit()?;
+        Ok(recorded)
+    }
+
+    /// Forgets earlier failures of sources whose thumbnail has now rendered,
+    /// including the corrupt mark.
+    pub fn clear_thumbnail_failures(&self, filepaths: &[String]) -> SqlResult<usize> {
+        if filepaths.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        // Nearly always empty; skip the per-path deletes then.
+        let any: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM thumbnail_failures)",
+            [],
+            |row| row.get(0),
+        )?;
+        if !any {
+            return Ok(0);
+        }
+        let tx = conn.transaction()?;
+        let mut cleared = 0;
+        {
+            let mut stmt = tx.prepare_cached(
+                "DELETE FROM thumbnail_failures
+                 WHERE image_id IN (SELECT id FROM images WHERE filepath = ?1)",
+            )?;
+            let mut uncorrupt = tx.prepare_cached(
+                "UPDATE images SET is_corrupt = 0 WHERE filepath = ?1 AND is_corrupt = 1",
+            )?;
+            for filepath in filepaths {
+                cleared += stmt.execute(params![filepath])?;
+                uncorrupt.execute(params![filepath])?;
+            }
+        }
+        tx.commit()?;
+        Ok(cleared)
+    }
+
+    /// Failed thumbnails, latest first, leaving out locked private folders.
+    /// `ids` narrows the result to those images.
+    pub fn get_thumbnail_failures(
+        &self,
+        ids: Option<&[i64]>,
+    ) -> SqlResult<Vec<ThumbnailFailureRecord>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare(
+            "SELECT f.image_id, images.filepath, f.kind, f.message, f.attempts, f.failed_at,
+                    images.is_corrupt
+             FROM thumbnail_failures f
+             JOIN images ON images.id = f.image_id
+             ORDER BY f.failed_at DESC, f.image_id DESC",
+        )?;
+        let wanted: Option<HashSet<i64>> = ids.map(|ids| ids.iter().copied().collect());
+        let rows = stmt.query_map([], |row| {
+            Ok(ThumbnailFailureRecord {
+                image_id: row.get(0)?,
+                filepath: row.get(1)?,
+                kind: ThumbnailFailureKind::parse(&row.get::<_, String>(2)?),
+                message: row.get(3)?,
+                attempts: row.get(4)?,
+                failed_at: row.get(5)?,
+                corrupt: row.get(6)?,
+            })
+        })?;
+        let mut failures = Vec::new();
+        for row in rows {
+            let failure = row?;
+            if wanted
+                .as_ref()
+                .is_some_and(|wanted| !wanted.contains(&failure.image_id))
+                || self.is_private_path(&failure.filepath)
+            {
+                continue;
+            }
+            failures.push(failure);
+        }
+        Ok(failures)
+    }
+
+    /// Images marked corrupt, latest failure first, leaving out locked
+    /// private folders.
+    pub fn get_corrupt_images(&self) -> SqlResult<Vec<ThumbnailFailureRecord>> {
+        let mut failures = self.get_thumbnail_failures(None)?;
+        failures.retain(|failure| failure.corrupt);
+        Ok(failures)
+    }
+}
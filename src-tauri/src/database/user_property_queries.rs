@@ -0,0 +1,126 @@
+use super::*;
+
+/// Values listed per key by [`Database::list_user_property_keys`].
+const MAX_VALUES_PER_KEY: usize = 10;
+
+/// Re-flattens `image_id`'s properties into `images.property_text`, which
+/// the FTS triggers index like the prompt.
+fn refresh_property_text(conn: &Connection, image_id: i64) -> SqlResult<()> {
+    conn.prepare_cached(
+        "UPDATE images SET property_text = (
+             SELECT group_concat(key || ' ' || value, char(10))
+             FROM (SELECT key, value FROM user_properties WHERE image_id = ?1 ORDER BY key)
+         )
+         WHERE id = ?1",
+    )?
+    .execute(params![image_id])?;
+    Ok(())
+}
+
+impl Database {
+    // ────────────────────────── User properties ──────────────────────────
+
+    /// Sets `key` to `value` on every image in `ids`, or removes it when
+    /// `value` is `None`. Keys match case-insensitively and keep the
+    /// spelling of the last write. Returns how many images changed.
+    pub fn set_user_property(
+        &self,
+        ids: &[i64],
+        key: &str,
+        value: Option<&str>,
+    ) -> SqlResult<usize> {
+        let mut conn = self.pool.get().map_err(pool_error)?;
+        let tx = conn.transaction()?;
+        let mut changed = Vec::new();
+        {
+            let mut upsert_stmt = tx.prepare(
+                "INSERT INTO user_properties (image_id, key, value)
+                 SELECT ?1, ?2, ?3 WHERE EXISTS(SELECT 1 FROM images WHERE id = ?1)
+                 ON CONFLICT(image_id, key) DO UPDATE SET key = excluded.key, value = excluded.value
+                 WHERE user_properties.key != excluded.key COLLATE BINARY OR user_properties.value != excluded.value",
+            )?;
+            let mut delete_stmt =
+                tx.prepare("DELETE FROM user_properties WHERE image_id = ?1 AND key = ?2")?;
+            for &id in ids {
+                let rows = match value {
+                    Some(value) => upsert_stmt.execute(params![id, key, value])?,
+                    None => delete_stmt.execute(params![id, key])?,
+                };
+                if rows > 0 {
+                    refresh_property_text(&tx, id)?;
+                    changed.push(id);
+                }
+            }
+        }
+        tx.commit()?;
+        self.invalidate_records(&changed);
+        Ok(changed.len())
+    }
+
+    /// Properties of one image, by key.
+    pub fn get_user_properties(&self, image_id: i64) -> SqlResult<Vec<UserProperty>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut stmt = conn.prepare(
+            "SELECT key, value FROM user_properties WHERE image_id = ?1 ORDER BY key, value",
+        )?;
+        let rows = stmt.query_map(params![image_id], |row| {
+            Ok(UserProperty {
+                key: row.get(0)?,
+                value: row.get(1)?,
+            })
+        })?;
+        let mut properties = Vec::new();
+        for row in rows {
+            properties.push(row?);
+        }
+        Ok(properties)
+    }
+
+    /// Property keys in use, most common first, each with its most common
+    /// values. Locked private folders are left out.
+    pub fn list_user_property_keys(&self, limit: u32) -> SqlResult<Vec<UserPropertyKey>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = String::from(
+            "SELECT user_properties.key, user_properties.value, COUNT(*)
+             FROM user_properties
+             JOIN images ON images.id = user_properties.image_id
+             WHERE 1=1",
+        );
+        let mut params: Vec<Value> = Vec::new();
+        self.append_private_filter(&mut sql, &mut params);
+        self.append_project_filter(&mut sql, &mut params);
+        sql.push_str(" GROUP BY user_properties.key, user_properties.value");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+            ))
+        })?;
+        let mut by_key: HashMap<String, UserPropertyKey> = HashMap::new();
+        for row in rows {
+            let (key, value, count) = row?;
+            let entry = by_key
+                .entry(key.to_lowercase())
+                .or_insert_with(|| UserPropertyKey {
+                    key,
+                    count: 0,
+                    values: Vec::new(),
+                });
+            entry.count += count;
+            entry.values.push(ExtraParamValue { value, count });
+        }
+
+        let mut keys: Vec<UserPropertyKey> = by_key.into_values().collect();
+        keys.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+        keys.truncate(limit as usize);
+        for key in &mut keys {
+            key.values
+                .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+            key.values.truncate(MAX_VALUES_PER_KEY);
+        }
+        Ok(keys)
+    }
+}
@@ -0,0 +1,71 @@
+use super::*;
+
+impl Database {
+    // ─────────────────────────── Workflow status ───────────────────────────
+
+    /// Moves `ids` to `status`, returning how many were elsewhere before.
+    pub fn set_workflow_status(&self, ids: &[i64], status: WorkflowStatus) -> SqlResult<usize> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let conn = self.pool.get().map_err(pool_error)?;
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!(
+            "UPDATE images SET workflow_status = ? WHERE workflow_status != ? AND id IN ({})",
+            placeholders
+        );
+        let mut params: Vec<Value> = Vec::with_capacity(ids.len() + 2);
+        params.push(Value::Text(status.as_str().to_string()));
+        params.push(Value::Text(status.as_str().to_string()));
+        params.extend(ids.iter().map(|id| Value::Integer(*id)));
+        let updated = conn.execute(&sql, params_from_iter(params))?;
+        collection_queries::apply_collection_rules(&conn, ids)?;
+        self.invalidate_records(ids);
+        Ok(updated)
+    }
+
+    /// Moves every image the filter matches to `status`, returning how many
+    /// changed.
+    pub fn set_workflow_status_by_filter(
+        &self,
+        params: FilterCursorParams<'_>,
+        status: WorkflowStatus,
+    ) -> SqlResult<usize> {
+        self.set_column_by_filter(
+            "workflow_status",
+            params,
+            Value::Text(status.as_str().to_string()),
+        )
+    }
+
+    /// Image count per workflow status, every status included, in board
+    /// order. Hidden directories and locked private folders are left out.
+    pub fn get_workflow_status_counts(&self) -> SqlResult<Vec<WorkflowStatusCount>> {
+        let conn = self.pool.get().map_err(pool_error)?;
+        let mut sql = String::from("SELECT images.workflow_status, COUNT(*) FROM images WHERE 1=1");
+        let mut params_vec: Vec<Value> = Vec::new();
+        append_hidden_directory_filter(&mut sql, false);
+        self.append_nsfw_filter(&mut sql, &mut params_vec);
+        self.append_private_filter(&mut sql, &mut params_vec);
+        self.append_project_filter(&mut sql, &mut params_vec);
+        sql.push_str(" GROUP BY images.workflow_status");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params_vec), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?))
+        })?;
+        let mut counts: Vec<WorkflowStatusCount> = WorkflowStatus::ALL
+            .into_iter()
+            .map(|status| WorkflowStatusCount { status, count: 0 })
+            .collect();
+        for row in rows {
+            let (stored, count) = row?;
+            let status = WorkflowStatus::from_stored(&stored);
+            if let Some(entry) = counts.iter_mut().find(|entry| entry.status == status) {
+                entry.count += count;
+            }
+        }
+        Ok(counts)
+    }
+}
@@ -0,0 +1,231 @@
+//! Writes generation parameters into re-encoded images.
+//!
+//! Decoding and re-encoding drops every ancillary chunk, so a PNG converted
+//! for export would no longer say how it was made. These helpers put the
+//! text back the way the WebUI itself stores it: a `parameters` text chunk
+//! in PNG, and an EXIF `UserComment` (`UNICODE` prefix, UTF-16BE) in JPEG,
+//! WebP and JPEG XL, which is what A1111's PNG Info tab and most galleries
+//! read. All of them work on already-encoded bytes and return the input
+//! unchanged when it isn't in the shape they expect.
+
+use flate2::Crc;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+const PARAMETERS_KEY: &str = "parameters";
+/// A JPEG segment length covers itself, so an APP1 payload tops out here.
+const MAX_JPEG_SEGMENT: usize = u16::MAX as usize - 2;
+const EXIF_HEADER: &[u8; 6] = b"Exif\0\0";
+const VP8X_EXIF_FLAG: u8 = 0x08;
+const VP8X_ALPHA_FLAG: u8 = 0x10;
+const JXL_CODESTREAM_SIGNATURE: [u8; 2] = [0xFF, 0x0A];
+const JXL_SIGNATURE_BOX: [u8; 12] = [
+    0x00, 0x00, 0x00, 0x0C, b'J', b'X', b'L', b' ', 0x0D, 0x0A, 0x87, 0x0A,
+];
+
+/// Adds a `parameters` text chunk right after `IHDR`; `iTXt` when the text
+/// isn't Latin-1, as the WebUI does.
+pub fn png_with_parameters(png: &[u8], parameters: &str) -> Vec<u8> {
+    // Signature, then IHDR: length, type, 13 data bytes, CRC.
+    let ihdr_end = PNG_SIGNATURE.len() + 4 + 4 + 13 + 4;
+    if !png.starts_with(&PNG_SIGNATURE)
+        || png.len() < ihdr_end
+        || &png[PNG_SIGNATURE.len() + 4..PNG_SIGNATURE.len() + 8] != b"IHDR"
+    {
+        return png.to_vec();
+    }
+
+    let mut data = PARAMETERS_KEY.as_bytes().to_vec();
+    data.push(0);
+    let chunk_type = if parameters.chars().all(|ch| (ch as u32) < 0x100) {
+        data.extend(parameters.chars().map(|ch| ch as u8));
+        b"tEXt"
+    } else {
+        // Uncompressed, no language tag, no translated keyword.
+        data.extend([0, 0, 0, 0]);
+        data.extend(parameters.as_bytes());
+        b"iTXt"
+    };
+
+    let mut crc = Crc::new();
+    crc.update(chunk_type);
+    crc.update(&data);
+    let mut output = Vec::with_capacity(png.len() + data.len() + 12);
+    output.extend(&png[..ihdr_end]);
+    output.extend((data.len() as u32).to_be_bytes());
+    output.extend(chunk_type);
+    output.extend(&data);
+    output.extend(crc.sum().to_be_bytes());
+    output.extend(&png[ihdr_end..]);
+    output
+}
+
+/// Adds an EXIF APP1 segment after SOI (and after JFIF's APP0, if present).
+/// Parameters too long for one segment are left out.
+pub fn jpeg_with_parameters(jpeg: &[u8], parameters: &str) -> Vec<u8> {
+    if !jpeg.starts_with(&[0xFF, 0xD8]) {
+        return jpeg.to_vec();
+    }
+    let exif = user_comment_exif(parameters);
+    if EXIF_HEADER.len() + exif.len() > MAX_JPEG_SEGMENT {
+        log::warn!("Parameters too long for a JPEG EXIF segment; exporting without them");
+        return jpeg.to_vec();
+    }
+
+    let mut insert_at = 2;
+    if jpeg.get(2..4) == Some(&[0xFF, 0xE0][..]) {
+        if let Some(length) = jpeg.get(4..6) {
+            insert_at = 4 + u16::from_be_bytes([length[0], length[1]]) as usize;
+        }
+    }
+    if insert_at > jpeg.len() {
+        return jpeg.to_vec();
+    }
+
+    let segment_length = (2 + EXIF_HEADER.len() + exif.len()) as u16;
+    let mut output = Vec::with_capacity(jpeg.len() + segment_length as usize + 2);
+    output.extend(&jpeg[..insert_at]);
+    output.extend([0xFF, 0xE1]);
+    output.extend(segment_length.to_be_bytes());
+    output.extend(EXIF_HEADER);
+    output.extend(&exif);
+    output.extend(&jpeg[insert_at..]);
+    output
+}
+
+/// Adds an `EXIF` chunk, switching a simple-format file to the extended
+/// format (`VP8X`) that allows one.
+pub fn webp_with_parameters(webp: &[u8], parameters: &str, width: u32, height: u32) -> Vec<u8> {
+    if webp.len() < 20 || &webp[..4] != b"RIFF" || &webp[8..12] != b"WEBP" {
+        return webp.to_vec();
+    }
+    let chunks = &webp[12..];
+
+    let mut body = b"WEBP".to_vec();
+    if chunks.starts_with(b"VP8X") {
+        body.extend(chunks);
+        body[4 + 8] |= VP8X_EXIF_FLAG;
+    } else {
+        let mut flags = VP8X_EXIF_FLAG;
+        // Lossless bitstreams carry their alpha flag in the header.
+        if chunks.starts_with(b"VP8L")
+            && chunks.get(9..13).is_some_and(|header| {
+                u32::from_le_bytes([header[0], header[1], header[2], header[3]]) >> 28 & 1 == 1
+            })
+        {
+            flags |= VP8X_ALPHA_FLAG;
+        }
+        let mut vp8x = [0u8; 10];
+        vp8x[0] = flags;
+        vp8x[4..7].copy_from_slice(&width.saturating_sub(1).to_le_bytes()[..3]);
+        vp8x[7..10].copy_from_slice(&height.saturating_sub(1).to_le_bytes()[..3]);
+        push_riff_chunk(&mut body, b"VP8X", &vp8x);
+        body.extend(chunks);
+    }
+    push_riff_chunk(&mut body, b"EXIF", &user_comment_exif(parameters));
+
+    let mut output = Vec::with_capacity(body.len() + 8);
+    output.extend(b"RIFF");
+    output.extend((body.len() as u32).to_le_bytes());
+    output.extend(body);
+    output
+}
+
+/// Wraps a bare JPEG XL codestream in the container format with an `Exif`
+/// box next to the image.
+pub fn jxl_with_parameters(codestream: &[u8], parameters: &str) -> Vec<u8> {
+    if !codestream.starts_with(&JXL_CODESTREAM_SIGNATURE) {
+        return codestream.to_vec();
+    }
+    let mut exif_box = 0u32.to_be_bytes().to_vec(); // TIFF header offset
+    exif_box.extend(user_comment_exif(parameters));
+
+    let mut output = JXL_SIGNATURE_BOX.to_vec();
+    push_jxl_box(&mut output, b"ftyp", b"jxl \0\0\0\0jxl ");
+    push_jxl_box(&mut output, b"Exif", &exif_box);
+    push_jxl_box(&mut output, b"jxlc", codestream);
+    output
+}
+
+/// Big-endian TIFF block with IFD0 pointing at an EXIF IFD holding only a
+/// `UserComment`.
+fn user_comment_exif(parameters: &str) -> Vec<u8> {
+    const IFD_SIZE: u32 = 2 + 12 + 4;
+    const EXIF_IFD_POINTER: u16 = 0x8769;
+    const USER_COMMENT: u16 = 0x9286;
+    const LONG: u16 = 4;
+    const UNDEFINED: u16 = 7;
+
+    let mut comment = b"UNICODE\0".to_vec();
+    comment.extend(parameters.encode_utf16().flat_map(u16::to_be_bytes));
+
+    let exif_ifd = 8 + IFD_SIZE;
+    let mut tiff = b"MM\0\x2A".to_vec();
+    tiff.extend(8u32.to_be_bytes());
+    for (tag, kind, count, value) in [
+        (EXIF_IFD_POINTER, LONG, 1, exif_ifd),
+        (
+            USER_COMMENT,
+            UNDEFINED,
+            comment.len() as u32,
+            exif_ifd + IFD_SIZE,
+        ),
+    ] {
+        tiff.extend(1u16.to_be_bytes());
+        tiff.extend(tag.to_be_bytes());
+        tiff.extend(kind.to_be_bytes());
+        tiff.extend(count.to_be_bytes());
+        tiff.extend(value.to_be_bytes());
+        tiff.extend(0u32.to_be_bytes());
+    }
+    tiff.extend(comment);
+    tiff
+}
+
+fn push_riff_chunk(output: &mut Vec<u8>, fourcc: &[u8; 4], data: &[u8]) {
+    output.extend(fourcc);
+    output.extend((data.len() as u32).to_le_bytes());
+    output.extend(data);
+    if data.len() % 2 == 1 {
+        output.push(0);
+    }
+}
+
+fn push_jxl_box(output: &mut Vec<u8>, box_type: &[u8; 4], data: &[u8]) {
+    output.extend(((data.len() + 8) as u32).to_be_bytes());
+    output.extend(box_type);
+    output.extend(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_comment_is_tagged_utf16_in_an_exif_ifd() {
+        let exif = user_comment_exif("a cat, Steps: 20");
+        assert_eq!(&exif[..8], b"MM\0\x2A\0\0\0\x08");
+        // IFD0 entry points at the EXIF IFD right after it.
+        assert_eq!(&exif[10..12], &[0x87, 0x69]);
+        assert_eq!(&exif[18..22], &26u32.to_be_bytes());
+        assert_eq!(&exif[28..30], &[0x92, 0x86]);
+        assert_eq!(&exif[44..52], b"UNICODE\0");
+        assert_eq!(&exif[52..54], &[0, b'a']);
+        assert_eq!(exif.len(), 52 + 2 * "a cat, Steps: 20".len());
+
+        let jxl = jxl_with_parameters(&[0xFF, 0x0A, 0x12, 0x34], "a cat");
+        assert_eq!(&jxl[..12], &JXL_SIGNATURE_BOX);
+        assert_eq!(&jxl[16..20], b"ftyp");
+        assert_eq!(&jxl[36..40], b"Exif");
+        let tail = jxl.len() - 12;
+        assert_eq!(
+            &jxl[tail..],
+            &[0, 0, 0, 12, b'j', b'x', b'l', b'c', 0xFF, 0x0A, 0x12, 0x34]
+        );
+
+        assert_eq!(png_with_parameters(b"not a png", "x"), b"not a png");
+        assert_eq!(
+            jxl_with_parameters(b"\0\0\0\x0cJXL ", "x"),
+            b"\0\0\0\x0cJXL "
+        );
+    }
+}
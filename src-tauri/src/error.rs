@@ -0,0 +1,227 @@
+//! Structured error type shared by commands and backend modules.
+//!
+//! Serializes to `{ "code": "...", "message": "..." }` so the frontend can
+//! branch on `code` (e.g. retry on `database_busy`, prompt for Forge settings
+//! on `forge_unreachable`) while still showing `message` to the user.
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt::Display;
+use std::path::Path;
+
+pub type AppResult<T> = Result<T, AppError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    /// Caller passed something unusable (bad path, empty selection, bad option).
+    #[error("{0}")]
+    InvalidInput(String),
+    /// A file or directory on disk does not exist.
+    #[error("{0}")]
+    FileMissing(String),
+    /// A library record (image id, tag, ...) does not exist.
+    #[error("{0}")]
+    NotFound(String),
+    /// The record changed since the operation was prepared against it.
+    #[error("{0}")]
+    Conflict(String),
+    /// SQLite is locked by another writer or the pool timed out.
+    #[error("{0}")]
+    DatabaseBusy(String),
+    #[error("{0}")]
+    Database(String),
+    #[error("{0}")]
+    PermissionDenied(String),
+    #[error("{0}")]
+    Io(String),
+    /// Forge could not be reached at all (connection refused, timeout, DNS).
+    #[error("{0}")]
+    ForgeUnreachable(String),
+    /// Forge answered, but with an error or an unexpected response.
+    #[error("{0}")]
+    Forge(String),
+    /// Decoding, encoding or transforming image data failed.
+    #[error("{0}")]
+    Image(String),
+    /// The operation was aborted on request.
+    #[error("{0}")]
+    Cancelled(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::FileMissing(_) => "file_missing",
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::DatabaseBusy(_) => "database_busy",
+            AppError::Database(_) => "database",
+            AppError::PermissionDenied(_) => "permission_denied",
+            AppError::Io(_) => "io",
+            AppError::ForgeUnreachable(_) => "forge_unreachable",
+            AppError::Forge(_) => "forge",
+            AppError::Image(_) => "image",
+            AppError::Cancelled(_) => "cancelled",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    pub fn file_missing(path: &Path) -> Self {
+        AppError::FileMissing(format!("File not found: {}", path.display()))
+    }
+
+    /// Prefixes the message with `context` while keeping the error code.
+    pub fn context(self, context: impl Display) -> Self {
+        let wrap = |message: String| format!("{}: {}", context, message);
+        match self {
+            AppError::InvalidInput(message) => AppError::InvalidInput(wrap(message)),
+            AppError::FileMissing(message) => AppError::FileMissing(wrap(message)),
+            AppError::NotFound(message) => AppError::NotFound(wrap(message)),
+            AppError::Conflict(message) => AppError::Conflict(wrap(message)),
+            AppError::DatabaseBusy(message) => AppError::DatabaseBusy(wrap(message)),
+            AppError::Database(message) => AppError::Database(wrap(message)),
+            AppError::PermissionDenied(message) => AppError::PermissionDenied(wrap(message)),
+            AppError::Io(message) => AppError::Io(wrap(message)),
+            AppError::ForgeUnreachable(message) => AppError::ForgeUnreachable(wrap(message)),
+            AppError::Forge(message) => AppError::Forge(wrap(message)),
+            AppError::Image(message) => AppError::Image(wrap(message)),
+            AppError::Cancelled(message) => AppError::Cancelled(wrap(message)),
+            AppError::Internal(message) => AppError::Internal(wrap(message)),
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Untyped messages from helpers that have not been classified.
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Internal(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Internal(message.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(error: rusqlite::Error) -> Self {
+        match error.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) => {
+                AppError::DatabaseBusy(format!("Library database is busy: {}", error))
+            }
+            _ => AppError::Database(format!("Database error: {}", error)),
+        }
+    }
+}
+
+impl From<r2d2::Error> for AppError {
+    // r2d2 only fails `get()` when no connection frees up before the timeout.
+    fn from(error: r2d2::Error) -> Self {
+        AppError::DatabaseBusy(format!("No database connection available: {}", error))
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::NotFound => AppError::FileMissing(error.to_string()),
+            std::io::ErrorKind::PermissionDenied => AppError::PermissionDenied(error.to_string()),
+            _ => AppError::Io(error.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_connect() || error.is_timeout() {
+            AppError::ForgeUnreachable(error.to_string())
+        } else {
+            AppError::Forge(error.to_string())
+        }
+    }
+}
+
+impl From<image::ImageError> for AppError {
+    fn from(error: image::ImageError) -> Self {
+        match error {
+            image::ImageError::IoError(io_error) => io_error.into(),
+            other => AppError::Image(other.to_string()),
+        }
+    }
+}
+
+impl From<csv::Error> for AppError {
+    fn from(error: csv::Error) -> Self {
+        AppError::Io(format!("CSV error: {}", error))
+    }
+}
+
+impl From<zip::result::ZipError> for AppError {
+    fn from(error: zip::result::ZipError) -> Self {
+        match error {
+            zip::result::ZipError::Io(io_error) => io_error.into(),
+            other => AppError::Io(format!("ZIP error: {}", other)),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::Internal(format!("JSON error: {}", error))
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(error: tauri::Error) -> Self {
+        AppError::Internal(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_and_message() {
+        let error = AppError::file_missing(Path::new("/missing.png")).context("Export failed");
+        let value = serde_json::to_value(&error).expect("error should serialize");
+        assert_eq!(value["code"], "file_missing");
+        assert_eq!(
+            value["message"],
+            "Export failed: File not found: /missing.png"
+        );
+    }
+
+    #[test]
+    fn io_errors_map_to_specific_codes() {
+        let missing: AppError = std::io::Error::from(std::io::ErrorKind::NotFound).into();
+        let denied: AppError = std::io::Error::from(std::io::ErrorKind::PermissionDenied).into();
+        let other: AppError = std::io::Error::other("disk full").into();
+        assert_eq!(missing.code(), "file_missing");
+        assert_eq!(denied.code(), "permission_denied");
+        assert_eq!(other.code(), "io");
+    }
+
+    #[test]
+    fn locked_database_maps_to_busy() {
+        let error: AppError = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            None,
+        )
+        .into();
+        assert_eq!(error.code(), "database_busy");
+    }
+}
@@ -0,0 +1,37 @@
+//! Progress events scoped to the window that started the work.
+//!
+//! All windows share one `AppState`, so a scan started in one window must not
+//! drive the progress bar in another. Commands scope their events to the
+//! calling window; work started from the tray is broadcast to every window.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, WebviewWindow};
+
+#[derive(Clone)]
+pub struct EventSink {
+    app: AppHandle,
+    window: Option<String>,
+}
+
+impl EventSink {
+    pub fn broadcast(app: &AppHandle) -> Self {
+        Self {
+            app: app.clone(),
+            window: None,
+        }
+    }
+
+    pub fn for_window(app: &AppHandle, window: &WebviewWindow) -> Self {
+        Self {
+            app: app.clone(),
+            window: Some(window.label().to_string()),
+        }
+    }
+
+    pub fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()> {
+        match &self.window {
+            Some(label) => self.app.emit_to(label.as_str(), event, payload),
+            None => self.app.emit(event, payload),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Puts file references (not pixels) on the system clipboard.
+//!
+//! Pasting into Explorer, Finder or a chat app then copies the original
+//! files with their metadata intact. There is no portable clipboard API for
+//! file lists, so each platform goes through its own tool: PowerShell's
+//! `Set-Clipboard` (CF_HDROP) on Windows, AppleScript file aliases on macOS,
+//! and a `text/uri-list` via `wl-copy` or `xclip` on Linux.
+
+use crate::error::{AppError, AppResult};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+pub fn copy_files(paths: &[PathBuf]) -> AppResult<()> {
+    if paths.is_empty() {
+        return Err(AppError::InvalidInput("No files to copy".to_string()));
+    }
+    copy_files_native(paths)
+}
+
+#[cfg(target_os = "windows")]
+fn copy_files_native(paths: &[PathBuf]) -> AppResult<()> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    // Paths go through stdin so large selections don't hit the command-line
+    // length limit.
+    let input: String = paths
+        .iter()
+        .map(|path| format!("{}\r\n", path.display()))
+        .collect();
+    let mut command = Command::new("powershell.exe");
+    command
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Set-Clipboard -LiteralPath @($input)",
+        ])
+        .creation_flags(CREATE_NO_WINDOW);
+    run_with_stdin(command, "powershell.exe", &input)
+}
+
+#[cfg(target_os = "macos")]
+fn copy_files_native(paths: &[PathBuf]) -> AppResult<()> {
+    let script = [
+        "on run argv",
+        "set fileList to {}",
+        "repeat with filePath in argv",
+        "set end of fileList to (POSIX file (filePath as text))",
+        "end repeat",
+        "set the clipboard to fileList",
+        "end run",
+    ];
+    let mut command = Command::new("osascript");
+    for line in script {
+        command.arg("-e").arg(line);
+    }
+    command.args(paths);
+    run_with_stdin(command, "osascript", "")
+}
+
+#[cfg(target_os = "linux")]
+fn copy_files_native(paths: &[PathBuf]) -> AppResult<()> {
+    let uri_list: String = paths
+        .iter()
+        .map(|path| format!("{}\r\n", file_uri(path)))
+        .collect();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let mut command = Command::new("wl-copy");
+        command.args(["--type", "text/uri-list"]);
+        if run_with_stdin(command, "wl-copy", &uri_list).is_ok() {
+            return Ok(());
+        }
+    }
+    let mut command = Command::new("xclip");
+    command.args(["-selection", "clipboard", "-target", "text/uri-list", "-in"]);
+    run_with_stdin(command, "xclip", &uri_list)
+        .map_err(|error| error.context("Copying files needs wl-copy or xclip"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn copy_files_native(_paths: &[PathBuf]) -> AppResult<()> {
+    Err(AppError::InvalidInput(
+        "Copying files is not supported on this platform".to_string(),
+    ))
+}
+
+/// Runs `command` with `input` on stdin. Only the exit status is awaited:
+/// xclip and wl-copy fork a child that keeps serving the selection, and it
+/// would hold piped stdout/stderr open until something else is copied.
+fn run_with_stdin(mut command: Command, program: &str, input: &str) -> AppResult<()> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|error| AppError::from(error).context(format!("Failed to start {}", program)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes()).map_err(|error| {
+            AppError::from(error).context(format!("Failed to write to {}", program))
+        })?;
+    }
+    let status = child.wait().map_err(|error| {
+        AppError::from(error).context(format!("Failed to wait for {}", program))
+    })?;
+    if status.success() {
+        return Ok(());
+    }
+    Err(AppError::Io(format!("{} exited with {}", program, status)))
+}
+
+/// `file://` URI with everything outside the unreserved set percent-encoded.
+#[cfg(any(target_os = "linux", test))]
+fn file_uri(path: &std::path::Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_uri_percent_encodes_spaces_and_unicode() {
+        assert_eq!(
+            file_uri(std::path::Path::new("/out/a b/%café.png")),
+            "file:///out/a%20b/%25caf%C3%A9.png"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Filename templates such as `{date}_{model}_{seed}_{n}`.
+//!
+//! Templates are validated against the tokens a caller supports before any
+//! file is written, so a typo fails the request instead of producing odd
+//! names. Token values are reduced to filename-safe characters. A token that
+//! renders empty also drops the separator in front of it, which lets
+//! `{stem}_{variant}_{n}` produce `stem_1` when there is no variant.
+
+use crate::error::{AppError, AppResult};
+
+pub const MAX_TEMPLATE_LEN: usize = 200;
+
+const SEPARATORS: &[char] = &['_', '-', ' ', '.'];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Token(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilenameTemplate {
+    segments: Vec<Segment>,
+}
+
+impl FilenameTemplate {
+    /// Parses `template`, accepting only `{token}` names listed in `tokens`.
+    pub fn parse(template: &str, tokens: &[&str]) -> AppResult<Self> {
+        let template = template.trim();
+        if template.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Filename template is empty".to_string(),
+            ));
+        }
+        if template.chars().count() > MAX_TEMPLATE_LEN {
+            return Err(AppError::InvalidInput(format!(
+                "Filename template is longer than {} characters",
+                MAX_TEMPLATE_LEN
+            )));
+        }
+
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+        while let Some(ch) = rest.chars().next() {
+            match ch {
+                '{' => {
+                    let end = rest.find('}').ok_or_else(|| {
+                        AppError::InvalidInput(format!(
+                            "Unclosed `{{` in filename template: {}",
+                            template
+                        ))
+                    })?;
+                    let name = rest[1..end].trim().to_ascii_lowercase();
+                    if !tokens.contains(&name.as_str()) {
+                        return Err(AppError::InvalidInput(format!(
+                            "Unknown filename token {{{}}}; expected one of {}",
+                            &rest[1..end],
+                            tokens
+                                .iter()
+                                .map(|token| format!("{{{}}}", token))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )));
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Token(name));
+                    rest = &rest[end + 1..];
+                }
+                '}' => {
+                    return Err(AppError::InvalidInput(format!(
+                        "Unmatched `}}` in filename template: {}",
+                        template
+                    )));
+                }
+                _ => {
+                    if ch.is_control()
+                        || matches!(ch, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+                    {
+                        return Err(AppError::InvalidInput(format!(
+                            "Filename template contains invalid character {:?}",
+                            ch
+                        )));
+                    }
+                    literal.push(ch);
+                    rest = &rest[ch.len_utf8()..];
+                }
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        if !segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Token(_)))
+        {
+            return Err(AppError::InvalidInput(
+                "Filename template needs at least one {token}".to_string(),
+            ));
+        }
+        Ok(Self { segments })
+    }
+
+    pub fn uses(&self, token: &str) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Token(name) if name == token))
+    }
+
+    /// Renders a file stem (no extension); `value` supplies each token.
+    pub fn render(&self, value: impl Fn(&str) -> Option<String>) -> String {
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => output.push_str(text),
+                Segment::Token(name) => {
+                    let rendered = value(name)
+                        .map(|raw| sanitize_component(&raw))
+                        .unwrap_or_default();
+                    if rendered.is_empty() {
+                        if output.ends_with(SEPARATORS) {
+                            output.pop();
+                        }
+                    } else {
+                        output.push_str(&rendered);
+                    }
+                }
+            }
+        }
+        let trimmed = output.trim_matches(SEPARATORS);
+        if trimmed.is_empty() {
+            "image".to_string()
+        } else {
+            trimmed.to_string()
+        }
+    }
+}
+
+/// Keeps ASCII letters, digits, `-`, `_` and `.`; whitespace becomes `_`.
+pub fn sanitize_component(value: &str) -> String {
+    let mut sanitized = String::with_capacity(value.len());
+    for ch in value.trim().chars() {
+        if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') {
+            sanitized.push(ch);
+        } else if ch.is_whitespace() {
+            sanitized.push('_');
+        }
+    }
+    sanitized.trim_matches(SEPARATORS).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKENS: &[&str] = &["stem", "seed", "variant", "n"];
+
+    #[test]
+    fn renders_tokens_and_drops_separators_of_empty_ones() {
+        let template = FilenameTemplate::parse("{stem}_forge_{variant}_{n}", TOKENS).unwrap();
+        let render = |variant: Option<&str>| {
+            template.render(|token| match token {
+                "stem" => Some("my image".to_string()),
+                "variant" => variant.map(str::to_string),
+                "n" => Some("2".to_string()),
+                _ => None,
+            })
+        };
+        assert_eq!(render(None), "my_image_forge_2");
+        assert_eq!(render(Some("adetailer")), "my_image_forge_adetailer_2");
+        assert!(template.uses("variant"));
+        assert!(!template.uses("seed"));
+
+        let template = FilenameTemplate::parse("{Seed}", TOKENS).unwrap();
+        assert_eq!(template.render(|_| None), "image");
+    }
+
+    #[test]
+    fn rejects_unknown_tokens_and_path_characters() {
+        assert!(FilenameTemplate::parse("{stem}_{modle}", TOKENS).is_err());
+        assert!(FilenameTemplate::parse("../{stem}", TOKENS).is_err());
+        assert!(FilenameTemplate::parse("{stem", TOKENS).is_err());
+        assert!(FilenameTemplate::parse("stem}", TOKENS).is_err());
+        assert!(FilenameTemplate::parse("fixed_name", TOKENS).is_err());
+        assert!(FilenameTemplate::parse("  ", TOKENS).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Subject focal points for cropping grid thumbnails.
+//!
+//! The grid fills square cells with `object-fit: cover`, which centre-crops
+//! tall portraits and often cuts off the character's head. The detector here
+//! is a cheap saliency estimate rather than a face model: it looks for edge
+//! detail, colourfulness and skin tones on a tiny copy of the image and
+//! returns the weighted centre of the busiest region, biased slightly towards
+//! the upper middle where faces usually sit. Coordinates are fractions of the
+//! width and height, ready for CSS `object-position`.
+
+use image::{imageops::FilterType, DynamicImage, RgbImage};
+use serde::{Deserialize, Serialize};
+
+/// Long side of the copy the detector works on.
+const ANALYSIS_SIZE: u32 = 48;
+/// Share of the most salient pixels averaged into the focal point.
+const SALIENT_FRACTION: f32 = 0.1;
+/// Face windows are this fraction of the short side.
+const FACE_WINDOW_DIVISOR: u32 = 5;
+/// Skin share of a window that counts as a certain face; eyes, hair and
+/// shading keep real faces well below full coverage.
+const FACE_FULL_DENSITY: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FocalPoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl FocalPoint {
+    pub const CENTER: FocalPoint = FocalPoint { x: 0.5, y: 0.5 };
+
+    /// Clamps into the unit square and rounds to three decimals.
+    pub fn new(x: f32, y: f32) -> Self {
+        let round = |value: f32| (value.clamp(0.0, 1.0) * 1000.0).round() / 1000.0;
+        Self {
+            x: round(x),
+            y: round(y),
+        }
+    }
+}
+
+pub fn detect(image: &DynamicImage) -> FocalPoint {
+    if image.width() == 0 || image.height() == 0 {
+        return FocalPoint::CENTER;
+    }
+    let small = image
+        .resize(ANALYSIS_SIZE, ANALYSIS_SIZE, FilterType::Triangle)
+        .to_rgb8();
+    detect_rgb(&small)
+}
+
+fn detect_rgb(image: &RgbImage) -> FocalPoint {
+    let (width, height) = image.dimensions();
+    if width < 3 || height < 3 {
+        return FocalPoint::CENTER;
+    }
+    let luma = |x: u32, y: u32| {
+        let [r, g, b] = image.get_pixel(x, y).0;
+        0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32
+    };
+
+    let mut scored: Vec<(f32, u32, u32)> = Vec::with_capacity((width * height) as usize);
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let gradient =
+                (luma(x + 1, y) - luma(x - 1, y)).abs() + (luma(x, y + 1) - luma(x, y - 1)).abs();
+            let [r, g, b] = image.get_pixel(x, y).0;
+            let max = r.max(g).max(b) as f32;
+            let min = r.min(g).min(b) as f32;
+            let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+            let skin = if is_skin_tone(r, g, b) { 60.0 } else { 0.0 };
+
+            let fx = (x as f32 + 0.5) / width as f32;
+            let fy = (y as f32 + 0.5) / height as f32;
+            let prior = 1.0 - 0.4 * ((fx - 0.5).powi(2) + (fy - 0.4).powi(2)).sqrt();
+            scored.push(((gradient + 40.0 * saturation + skin) * prior, x, y));
+        }
+    }
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let keep = ((scored.len() as f32 * SALIENT_FRACTION).ceil() as usize).max(1);
+    let (mut total, mut sum_x, mut sum_y) = (0.0f32, 0.0f32, 0.0f32);
+    for &(score, x, y) in scored.iter().take(keep) {
+        total += score;
+        sum_x += score * (x as f32 + 0.5);
+        sum_y += score * (y as f32 + 0.5);
+    }
+    if total <= f32::EPSILON {
+        return FocalPoint::CENTER;
+    }
+    FocalPoint::new(sum_x / total / width as f32, sum_y / total / height as f32)
+}
+
+/// How likely the image shows a face, 0–1: the skin-tone share of the
+/// densest face-sized window in its upper part. Like [`detect`] this is a
+/// colour heuristic, not a face model, so it is only good for ranking
+/// near-identical images against each other.
+pub fn face_confidence(image: &DynamicImage) -> f32 {
+    if image.width() == 0 || image.height() == 0 {
+        return 0.0;
+    }
+    let small = image
+        .resize(ANALYSIS_SIZE, ANALYSIS_SIZE, FilterType::Triangle)
+        .to_rgb8();
+    let (width, height) = small.dimensions();
+    let window = (width.min(height) / FACE_WINDOW_DIVISOR).max(1);
+    // Faces sit in the upper two thirds of nearly every generation.
+    let search_height = (height * 2 / 3).max(window);
+
+    let mut best = 0u32;
+    for top in 0..=search_height.saturating_sub(window) {
+        for left in 0..=width - window {
+            let skin = (top..top + window)
+                .flat_map(|y| (left..left + window).map(move |x| (x, y)))
+                .filter(|&(x, y)| {
+                    let [r, g, b] = small.get_pixel(x, y).0;
+                    is_skin_tone(r, g, b)
+                })
+                .count() as u32;
+            best = best.max(skin);
+        }
+    }
+    let density = best as f32 / (window * window) as f32;
+    (density / FACE_FULL_DENSITY).min(1.0)
+}
+
+/// The usual RGB skin rule; catches most photographic and anime skin.
+fn is_skin_tone(r: u8, g: u8, b: u8) -> bool {
+    let (r, g, b) = (r as i32, g as i32, b as i32);
+    r > 95
+        && g > 40
+        && b > 20
+        && r.max(g).max(b) - r.min(g).min(b) > 15
+        && (r - g).abs() > 15
+        && r > g
+        && r > b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    #[test]
+    fn focal_point_follows_the_subject() {
+        // A skin-toned blob near the top of a flat, tall background.
+        let mut portrait = RgbImage::from_pixel(60, 180, Rgb([40, 60, 90]));
+        for y in 20..50 {
+            for x in 15..45 {
+                portrait.put_pixel(x, y, Rgb([224, 172, 140]));
+            }
+        }
+        let point = detect(&DynamicImage::ImageRgb8(portrait));
+        assert!(point.y < 0.3, "{point:?}");
+        assert!((point.x - 0.5).abs() < 0.15, "{point:?}");
+
+        let flat = RgbImage::from_pixel(64, 64, Rgb([0, 0, 0]));
+        assert_eq!(detect(&DynamicImage::ImageRgb8(flat)), FocalPoint::CENTER);
+        assert_eq!(FocalPoint::new(1.7, -0.2), FocalPoint { x: 1.0, y: 0.0 });
+    }
+
+    #[test]
+    fn face_confidence_needs_a_skin_blob_near_the_top() {
+        let mut portrait = RgbImage::from_pixel(96, 96, Rgb([40, 60, 90]));
+        for y in 10..40 {
+            for x in 30..60 {
+                portrait.put_pixel(x, y, Rgb([224, 172, 140]));
+            }
+        }
+        let mut low = RgbImage::from_pixel(96, 96, Rgb([40, 60, 90]));
+        for y in 86..96 {
+            for x in 30..60 {
+                low.put_pixel(x, y, Rgb([224, 172, 140]));
+            }
+        }
+        let background = RgbImage::from_pixel(96, 96, Rgb([40, 60, 90]));
+
+        assert_eq!(face_confidence(&DynamicImage::ImageRgb8(portrait)), 1.0);
+        assert_eq!(face_confidence(&DynamicImage::ImageRgb8(low)), 0.0);
+        assert_eq!(face_confidence(&DynamicImage::ImageRgb8(background)), 0.0);
+    }
+}
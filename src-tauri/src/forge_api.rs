@@ -1,11 +1,11 @@
+use crate::error::{AppError, AppResult};
 use reqwest::{
     header::{HeaderMap, HeaderValue, AUTHORIZATION},
     StatusCode,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::error::Error;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ForgePayload {
@@ -26,6 +26,18 @@ pub struct ForgePayload {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub height: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_hr: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hr_scale: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hr_upscaler: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hr_second_pass_steps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub denoising_strength: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub styles: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub override_settings: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub send_images: Option<bool>,
@@ -35,10 +47,45 @@ pub struct ForgePayload {
     pub alwayson_scripts: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Why a connection test failed, so the UI can point at the right fix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForgeFailureKind {
+    /// The host name did not resolve.
+    Dns,
+    /// Nothing accepted the connection at that host and port.
+    Connect,
+    /// The HTTPS handshake failed, e.g. a bad certificate or `https://` for
+    /// a plain HTTP server.
+    Tls,
+    Timeout,
+    /// 401/403: the API key or `--api-auth` credentials were refused.
+    Unauthorized,
+    /// The WebUI answers but has no API routes; it was started without `--api`.
+    ApiDisabled,
+    /// 404 and nothing at the base URL either: wrong URL or port.
+    NotFound,
+    /// Any other HTTP error status.
+    Http,
+    Other,
+}
+
+/// Result of a connection test. Diagnostics are `None` when they could not
+/// be determined, e.g. the version of a server that is unreachable.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ForgeStatus {
     pub ok: bool,
     pub message: String,
+    pub failure: Option<ForgeFailureKind>,
+    pub http_status: Option<u16>,
+    /// Round trip of the test request.
+    pub latency_ms: Option<u64>,
+    /// WebUI version as shown in its footer, e.g. `f2.0.1v1.10.1-previous-…`.
+    pub version: Option<String>,
+    /// Whether the API routes are served, i.e. Forge runs with `--api`.
+    pub api_enabled: Option<bool>,
+    /// Whether the server was started with `--api-auth`.
+    pub api_auth: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,42 +105,201 @@ struct ForgeTxt2ImgResponse {
 
 const SDAPI_PREFIX: &str = "/sdapi/v1";
 const TEST_TIMEOUT_SECONDS: u64 = 60;
+/// Version and flag lookups after a successful test; skipped if slower.
+const DIAGNOSTIC_TIMEOUT_SECONDS: u64 = 10;
 const SEND_TIMEOUT_SECONDS: u64 = 600;
 const DEFAULT_ADETAILER_FACE_MODEL: &str = "face_yolov8n.pt";
 
-pub async fn test_connection(
-    base_url: &str,
-    api_key: Option<&str>,
-) -> Result<ForgeStatus, Box<dyn Error + Send + Sync>> {
+pub async fn test_connection(base_url: &str, api_key: Option<&str>) -> AppResult<ForgeStatus> {
     let client = build_client(api_key, TEST_TIMEOUT_SECONDS)?;
     let endpoint = build_sdapi_endpoint(base_url, "samplers");
 
-    let response = client.get(&endpoint).send().await?;
-    if response.status().is_success() {
+    let started = Instant::now();
+    let response = client.get(&endpoint).send().await;
+    let latency_ms = Some(started.elapsed().as_millis() as u64);
+    let response = match response {
+        Ok(response) => response,
+        Err(error) => {
+            let failure = classify_transport_error(&error);
+            return Ok(ForgeStatus {
+                ok: false,
+                message: connection_failure_message(failure, &endpoint, None, &error),
+                failure: Some(failure),
+                ..Default::default()
+            });
+        }
+    };
+
+    let status = response.status();
+    if status.is_success() {
+        let diagnostics = build_client(api_key, DIAGNOSTIC_TIMEOUT_SECONDS)?;
+        let flags =
+            get_optional_json(&diagnostics, &build_sdapi_endpoint(base_url, "cmd-flags")).await;
+        let version = get_optional_json(
+            &diagnostics,
+            &format!("{}/internal/sysinfo", normalize_base_url(base_url)),
+        )
+        .await
+        .as_ref()
+        .and_then(|info| info.get("Version"))
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_string);
+        let flag = |name: &str| flags.as_ref().and_then(|flags| flags.get(name));
+        let api_auth =
+            flag("api_auth").map(|auth| auth.as_str().is_some_and(|auth| !auth.is_empty()));
+
+        let mut details: Vec<String> = version.iter().cloned().collect();
+        details.extend(latency_ms.map(|ms| format!("{} ms", ms)));
         return Ok(ForgeStatus {
             ok: true,
-            message: "Connected to Forge/A1111 API".to_string(),
+            message: format!("Connected to Forge/A1111 API ({})", details.join(", ")),
+            http_status: Some(status.as_u16()),
+            latency_ms,
+            version,
+            api_enabled: Some(true),
+            api_auth,
+            ..Default::default()
         });
     }
 
-    let status = response.status();
-    let message = if status == StatusCode::NOT_FOUND {
-        format!(
-            "Connection failed with status {} at {}. Start Forge with --api and use a base URL like http://127.0.0.1:7860 (without /sdapi/v1).",
-            status, endpoint
-        )
-    } else {
-        format!("Connection failed with status {} at {}", status, endpoint)
+    let failure = match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ForgeFailureKind::Unauthorized,
+        StatusCode::NOT_FOUND if webui_answers(&client, base_url).await => {
+            ForgeFailureKind::ApiDisabled
+        }
+        StatusCode::NOT_FOUND => ForgeFailureKind::NotFound,
+        _ => ForgeFailureKind::Http,
+    };
+    let api_enabled = match failure {
+        ForgeFailureKind::Unauthorized => Some(true),
+        ForgeFailureKind::ApiDisabled => Some(false),
+        _ => None,
     };
+    Ok(ForgeStatus {
+        ok: false,
+        message: connection_failure_message(failure, &endpoint, Some(status), &status),
+        failure: Some(failure),
+        http_status: Some(status.as_u16()),
+        latency_ms,
+        api_enabled,
+        ..Default::default()
+    })
+}
 
-    Ok(ForgeStatus { ok: false, message })
+/// Whether something answers at the base URL itself, which tells a WebUI
+/// running without `--api` apart from a wrong address.
+async fn webui_answers(client: &reqwest::Client, base_url: &str) -> bool {
+    client
+        .get(normalize_base_url(base_url))
+        .send()
+        .await
+        .is_ok_and(|response| response.status().is_success())
+}
+
+/// Best-effort GET for diagnostics; failures are logged and ignored.
+async fn get_optional_json(client: &reqwest::Client, url: &str) -> Option<serde_json::Value> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status);
+    match response {
+        Ok(response) => response
+            .json()
+            .await
+            .map_err(|error| log::debug!("Unexpected response from {}: {}", url, error))
+            .ok(),
+        Err(error) => {
+            log::debug!("Forge diagnostics unavailable at {}: {}", url, error);
+            None
+        }
+    }
+}
+
+fn classify_transport_error(error: &reqwest::Error) -> ForgeFailureKind {
+    if error.is_timeout() {
+        return ForgeFailureKind::Timeout;
+    }
+    let mut chain = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        chain.push_str(": ");
+        chain.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    classify_transport_text(&chain, error.is_connect())
+}
+
+/// Classifies a transport error from its message chain; hyper and the TLS
+/// backends only expose these causes as text.
+fn classify_transport_text(chain: &str, is_connect: bool) -> ForgeFailureKind {
+    let chain = chain.to_ascii_lowercase();
+    let mentions = |needles: &[&str]| needles.iter().any(|needle| chain.contains(needle));
+    if mentions(&[
+        "dns error",
+        "failed to lookup address",
+        "name or service not known",
+        "no such host",
+        "nodename nor servname",
+    ]) {
+        ForgeFailureKind::Dns
+    } else if mentions(&["certificate", "tls", "ssl", "handshake", "corrupt message"]) {
+        ForgeFailureKind::Tls
+    } else if is_connect {
+        ForgeFailureKind::Connect
+    } else {
+        ForgeFailureKind::Other
+    }
+}
+
+fn connection_failure_message(
+    failure: ForgeFailureKind,
+    endpoint: &str,
+    status: Option<StatusCode>,
+    detail: &dyn std::fmt::Display,
+) -> String {
+    let status = status.map(|status| status.to_string()).unwrap_or_default();
+    match failure {
+        ForgeFailureKind::Dns => format!(
+            "Could not resolve the host in {}. Check the base URL for typos.",
+            endpoint
+        ),
+        ForgeFailureKind::Connect => format!(
+            "Nothing accepted the connection at {}. Check Forge is running and the port is right.",
+            endpoint
+        ),
+        ForgeFailureKind::Tls => format!(
+            "Secure connection to {} failed ({}). Use http:// unless Forge is served over HTTPS with a trusted certificate.",
+            endpoint, detail
+        ),
+        ForgeFailureKind::Timeout => format!(
+            "Forge did not answer at {} within {} seconds. It may be busy loading a model.",
+            endpoint, TEST_TIMEOUT_SECONDS
+        ),
+        ForgeFailureKind::Unauthorized => format!(
+            "Forge refused the request with status {} at {}. Check the API key matches the credentials Forge was started with (--api-auth).",
+            status, endpoint
+        ),
+        ForgeFailureKind::ApiDisabled => format!(
+            "The WebUI is running but its API is off ({} at {}). Restart Forge with --api.",
+            status, endpoint
+        ),
+        ForgeFailureKind::NotFound => format!(
+            "Connection failed with status {} at {}. Start Forge with --api and use a base URL like http://127.0.0.1:7860 (without /sdapi/v1).",
+            status, endpoint
+        ),
+        ForgeFailureKind::Http => {
+            format!("Connection failed with status {} at {}", status, endpoint)
+        }
+        ForgeFailureKind::Other => format!("Connection to {} failed: {}", endpoint, detail),
+    }
 }
 
 pub async fn send_to_forge(
     payload: &ForgePayload,
     base_url: &str,
     api_key: Option<&str>,
-) -> Result<ForgeSendResult, Box<dyn Error + Send + Sync>> {
+) -> AppResult<ForgeSendResult> {
     let client = build_client(api_key, SEND_TIMEOUT_SECONDS)?;
     let endpoint = build_sdapi_endpoint(base_url, "txt2img");
 
@@ -130,7 +336,12 @@ pub async fn send_to_forge(
         });
     }
 
-    let body: ForgeTxt2ImgResponse = response.json().await?;
+    let body: ForgeTxt2ImgResponse = response.json().await.map_err(|error| {
+        AppError::Forge(format!(
+            "Unexpected Forge response at {}: {}",
+            endpoint, error
+        ))
+    })?;
     Ok(ForgeSendResult {
         ok: true,
         images: body.images,
@@ -139,25 +350,298 @@ pub async fn send_to_forge(
     })
 }
 
-pub async fn list_samplers(
-    base_url: &str,
-    api_key: Option<&str>,
-) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+pub async fn list_samplers(base_url: &str, api_key: Option<&str>) -> AppResult<Vec<String>> {
     list_named_options(base_url, api_key, "samplers").await
 }
 
-pub async fn list_schedulers(
-    base_url: &str,
-    api_key: Option<&str>,
-) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+pub async fn list_schedulers(base_url: &str, api_key: Option<&str>) -> AppResult<Vec<String>> {
     list_named_options(base_url, api_key, "schedulers").await
 }
 
-pub async fn list_models(
+pub async fn list_styles(base_url: &str, api_key: Option<&str>) -> AppResult<Vec<String>> {
+    list_named_options(base_url, api_key, "prompt-styles").await
+}
+
+/// LoRA names and aliases known to the server.
+pub async fn list_loras(base_url: &str, api_key: Option<&str>) -> AppResult<Vec<String>> {
+    let raw = get_sdapi_json(base_url, api_key, "loras").await?;
+    let mut names = Vec::new();
+    for entry in raw.as_array().map(Vec::as_slice).unwrap_or_default() {
+        for key in ["name", "alias"] {
+            if let Some(name) = entry.get(key).and_then(|value| value.as_str()) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// Names of textual inversions the server has loaded.
+pub async fn list_embeddings(base_url: &str, api_key: Option<&str>) -> AppResult<Vec<String>> {
+    let raw = get_sdapi_json(base_url, api_key, "embeddings").await?;
+    Ok(raw
+        .get("loaded")
+        .and_then(|loaded| loaded.as_object())
+        .map(|loaded| loaded.keys().cloned().collect())
+        .unwrap_or_default())
+}
+
+pub async fn list_models(base_url: &str, api_key: Option<&str>) -> AppResult<Vec<String>> {
+    list_named_options(base_url, api_key, "sd-models").await
+}
+
+/// Hires-fix second pass; unset fields fall back to Forge's defaults.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ForgeHiresFix {
+    pub scale: Option<f32>,
+    pub upscaler: Option<String>,
+    /// `0` reuses the first-pass step count.
+    pub second_pass_steps: Option<u32>,
+    pub denoising_strength: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeResourceKind {
+    Model,
+    Lora,
+    Embedding,
+}
+
+/// A resource the payload references that the server doesn't have.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ForgeResourceWarning {
+    pub kind: ForgeResourceKind,
+    pub name: String,
+    pub message: String,
+}
+
+/// Resources a payload refers to by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ForgeResourceRefs {
+    pub model: Option<String>,
+    pub loras: Vec<String>,
+    pub embeddings: Vec<String>,
+}
+
+/// What the server reports as installed; a list is `None` when it couldn't
+/// be fetched, and that kind is then not checked.
+#[derive(Debug, Clone, Default)]
+pub struct ForgeServerResources {
+    pub models: Option<Vec<String>>,
+    pub loras: Option<Vec<String>>,
+    pub embeddings: Option<Vec<String>>,
+}
+
+pub async fn fetch_server_resources(base_url: &str, api_key: Option<&str>) -> ForgeServerResources {
+    let unavailable = |kind: &str, error: AppError| {
+        log::warn!("Forge {} list unavailable for validation: {}", kind, error);
+    };
+    ForgeServerResources {
+        models: list_models(base_url, api_key)
+            .await
+            .map_err(|error| unavailable("model", error))
+            .ok(),
+        loras: list_loras(base_url, api_key)
+            .await
+            .map_err(|error| unavailable("LoRA", error))
+            .ok(),
+        embeddings: list_embeddings(base_url, api_key)
+            .await
+            .map_err(|error| unavailable("embedding", error))
+            .ok(),
+    }
+}
+
+/// Share of RAM or VRAM in use at which the server counts as under memory
+/// pressure.
+const MEMORY_PRESSURE_RATIO: f64 = 0.9;
+
+/// Memory and activity reported by `/sdapi/v1/memory` and `/progress`.
+/// Sizes are bytes; a field is `None` when the server doesn't report it
+/// (e.g. no CUDA device).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ForgeServerStats {
+    pub ram_used: Option<u64>,
+    pub ram_total: Option<u64>,
+    pub vram_used: Option<u64>,
+    pub vram_total: Option<u64>,
+    /// Peak memory PyTorch allocated since the server started.
+    pub vram_peak: Option<u64>,
+    pub oom_events: Option<u64>,
+    /// Whether the server is generating right now.
+    pub busy: bool,
+    /// Jobs in the current request, e.g. the images of a batch.
+    pub job_count: Option<u32>,
+    pub memory_pressure: bool,
+    pub warnings: Vec<String>,
+    pub fetched_at: String,
+}
+
+/// Last stats fetched, per server, so several views polling at once don't
+/// hit Forge more than needed.
+#[derive(Default)]
+pub struct ForgeStatsCache {
+    entry: std::sync::Mutex<Option<(String, Instant, ForgeServerStats)>>,
+}
+
+impl ForgeStatsCache {
+    pub fn get(&self, base_url: &str, max_age: Duration) -> Option<ForgeServerStats> {
+        let entry = self.entry.lock().ok()?;
+        let (cached_url, fetched, stats) = entry.as_ref()?;
+        (*cached_url == normalize_base_url(base_url) && fetched.elapsed() <= max_age)
+            .then(|| stats.clone())
+    }
+
+    pub fn store(&self, base_url: &str, stats: &ForgeServerStats) {
+        if let Ok(mut entry) = self.entry.lock() {
+            *entry = Some((normalize_base_url(base_url), Instant::now(), stats.clone()));
+        }
+    }
+}
+
+pub async fn fetch_server_stats(
     base_url: &str,
     api_key: Option<&str>,
-) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
-    list_named_options(base_url, api_key, "sd-models").await
+) -> AppResult<ForgeServerStats> {
+    let memory = get_sdapi_json(base_url, api_key, "memory").await?;
+    let progress = get_sdapi_json(base_url, api_key, "progress?skip_current_image=true")
+        .await
+        .map_err(|error| log::debug!("Forge progress unavailable: {}", error))
+        .ok();
+    Ok(parse_server_stats(&memory, progress.as_ref()))
+}
+
+fn parse_server_stats(
+    memory: &serde_json::Value,
+    progress: Option<&serde_json::Value>,
+) -> ForgeServerStats {
+    let bytes = |path: &str| {
+        memory
+            .pointer(path)
+            .and_then(serde_json::Value::as_f64)
+            .filter(|value| *value >= 0.0)
+            .map(|value| value as u64)
+    };
+    let state = progress.and_then(|progress| progress.get("state"));
+    let job_count = state
+        .and_then(|state| state.get("job_count"))
+        .and_then(serde_json::Value::as_u64)
+        .map(|count| count as u32);
+    let busy = job_count.is_some_and(|count| count > 0)
+        || progress
+            .and_then(|progress| progress.get("progress"))
+            .and_then(serde_json::Value::as_f64)
+            .is_some_and(|progress| progress > 0.0);
+
+    let mut stats = ForgeServerStats {
+        ram_used: bytes("/ram/used"),
+        ram_total: bytes("/ram/total"),
+        vram_used: bytes("/cuda/system/used"),
+        vram_total: bytes("/cuda/system/total"),
+        vram_peak: bytes("/cuda/active/peak"),
+        oom_events: bytes("/cuda/events/oom"),
+        busy,
+        job_count,
+        fetched_at: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
+    };
+
+    let gib = |value: u64| value as f64 / (1u64 << 30) as f64;
+    for (label, used, total) in [
+        ("VRAM", stats.vram_used, stats.vram_total),
+        ("System RAM", stats.ram_used, stats.ram_total),
+    ] {
+        let (Some(used), Some(total)) = (used, total) else {
+            continue;
+        };
+        if total > 0 && used as f64 / total as f64 >= MEMORY_PRESSURE_RATIO {
+            stats.memory_pressure = true;
+            stats.warnings.push(format!(
+                "{} nearly full: {:.1} of {:.1} GiB in use",
+                label,
+                gib(used),
+                gib(total)
+            ));
+        }
+    }
+    if let Some(error) = memory
+        .pointer("/cuda/error")
+        .and_then(serde_json::Value::as_str)
+    {
+        stats
+            .warnings
+            .push(format!("GPU memory unavailable: {}", error));
+    }
+    if let Some(oom) = stats.oom_events.filter(|count| *count > 0) {
+        stats.warnings.push(format!(
+            "Forge has run out of GPU memory {} time{} since it started",
+            oom,
+            if oom == 1 { "" } else { "s" }
+        ));
+    }
+    stats
+}
+
+/// Comparable form of a resource name: lowercase file stem without folders,
+/// extension or a trailing ` [hash]`.
+fn resource_key(name: &str) -> String {
+    let name = name.split(" [").next().unwrap_or(name).replace('\\', "/");
+    let name = name
+        .rsplit('/')
+        .next()
+        .unwrap_or(&name)
+        .trim()
+        .to_lowercase();
+    for ext in [".safetensors", ".ckpt", ".pt", ".pth", ".bin", ".gguf"] {
+        if let Some(stem) = name.strip_suffix(ext) {
+            return stem.to_string();
+        }
+    }
+    name
+}
+
+pub fn find_missing_resources(
+    refs: &ForgeResourceRefs,
+    available: &ForgeServerResources,
+) -> Vec<ForgeResourceWarning> {
+    let mut warnings = Vec::new();
+    let mut check =
+        |kind: ForgeResourceKind, label: &str, names: &[String], known: &Option<Vec<String>>| {
+            let Some(known) = known else {
+                return;
+            };
+            let known: std::collections::HashSet<String> =
+                known.iter().map(|name| resource_key(name)).collect();
+            for name in names {
+                if !known.contains(&resource_key(name)) {
+                    warnings.push(ForgeResourceWarning {
+                        kind,
+                        name: name.clone(),
+                        message: format!("{} '{}' not found on server", label, name),
+                    });
+                }
+            }
+        };
+    check(
+        ForgeResourceKind::Model,
+        "Model",
+        refs.model.as_slice(),
+        &available.models,
+    );
+    check(
+        ForgeResourceKind::Lora,
+        "LoRA",
+        &refs.loras,
+        &available.loras,
+    );
+    check(
+        ForgeResourceKind::Embedding,
+        "Embedding",
+        &refs.embeddings,
+        &available.embeddings,
+    );
+    warnings
 }
 
 pub struct ForgePayloadBuildInput<'a> {
@@ -174,6 +658,7 @@ pub struct ForgePayloadBuildInput<'a> {
     pub include_seed: bool,
     pub adetailer_face_enabled: bool,
     pub adetailer_face_model: Option<&'a str>,
+    pub hires_fix: Option<ForgeHiresFix>,
 }
 
 pub fn build_payload_from_image_record(input: ForgePayloadBuildInput<'_>) -> ForgePayload {
@@ -191,6 +676,7 @@ pub fn build_payload_from_image_record(input: ForgePayloadBuildInput<'_>) -> For
         include_seed,
         adetailer_face_enabled,
         adetailer_face_model,
+        hires_fix,
     } = input;
     let sampler_name = parse_optional_text(sampler);
     let scheduler = parse_optional_text(scheduler);
@@ -209,6 +695,12 @@ pub fn build_payload_from_image_record(input: ForgePayloadBuildInput<'_>) -> For
         seed: if include_seed { parse_i64(seed) } else { None },
         width,
         height,
+        enable_hr: hires_fix.as_ref().map(|_| true),
+        hr_scale: hires_fix.as_ref().and_then(|hires| hires.scale),
+        hr_upscaler: hires_fix.as_ref().and_then(|hires| hires.upscaler.clone()),
+        hr_second_pass_steps: hires_fix.as_ref().and_then(|hires| hires.second_pass_steps),
+        denoising_strength: hires_fix.and_then(|hires| hires.denoising_strength),
+        styles: None,
         override_settings,
         send_images: Some(true),
         save_images: Some(true),
@@ -285,26 +777,39 @@ fn collect_named_options(entries: &[serde_json::Value]) -> Vec<String> {
     options
 }
 
-async fn list_named_options(
+async fn get_sdapi_json(
     base_url: &str,
     api_key: Option<&str>,
     endpoint_name: &str,
-) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+) -> AppResult<serde_json::Value> {
     let client = build_client(api_key, TEST_TIMEOUT_SECONDS)?;
     let endpoint = build_sdapi_endpoint(base_url, endpoint_name);
     let response = client.get(&endpoint).send().await?;
 
     if !response.status().is_success() {
-        return Err(std::io::Error::other(format!(
+        return Err(AppError::Forge(format!(
             "Request failed for {} with status {}",
             endpoint,
             response.status()
-        ))
-        .into());
+        )));
     }
 
-    let raw: Vec<serde_json::Value> = response.json().await?;
-    Ok(collect_named_options(&raw))
+    response.json().await.map_err(|error| {
+        AppError::Forge(format!(
+            "Unexpected Forge response at {}: {}",
+            endpoint, error
+        ))
+    })
+}
+
+async fn list_named_options(
+    base_url: &str,
+    api_key: Option<&str>,
+    endpoint_name: &str,
+) -> AppResult<Vec<String>> {
+    let raw = get_sdapi_json(base_url, api_key, endpoint_name).await?;
+    let entries = raw.as_array().map(Vec::as_slice).unwrap_or_default();
+    Ok(collect_named_options(entries))
 }
 
 fn build_sdapi_endpoint(base_url: &str, endpoint: &str) -> String {
@@ -357,16 +862,15 @@ fn format_send_transport_error(endpoint: &str, error: &reqwest::Error) -> String
     format!("Forge transport error at {}: {}", endpoint, error)
 }
 
-fn build_client(
-    api_key: Option<&str>,
-    timeout_seconds: u64,
-) -> Result<reqwest::Client, Box<dyn Error + Send + Sync>> {
+fn build_client(api_key: Option<&str>, timeout_seconds: u64) -> AppResult<reqwest::Client> {
     let mut headers = HeaderMap::new();
 
     if let Some(key) = api_key {
         let token = key.trim();
         if !token.is_empty() {
-            let value = HeaderValue::from_str(&format!("Bearer {}", token))?;
+            let value = HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|_| {
+                AppError::InvalidInput("Forge API key contains invalid characters".to_string())
+            })?;
             headers.insert(AUTHORIZATION, value);
         }
     }
@@ -379,7 +883,104 @@ fn build_client(
 
 #[cfg(test)]
 mod tests {
-    use super::{build_sdapi_endpoint, normalize_base_url};
+    use super::{
+        build_payload_from_image_record, build_sdapi_endpoint, classify_transport_text,
+        find_missing_resources, normalize_base_url, parse_server_stats, ForgeFailureKind,
+        ForgeHiresFix, ForgePayloadBuildInput, ForgeResourceKind, ForgeResourceRefs,
+        ForgeServerResources,
+    };
+    use serde_json::json;
+
+    fn payload_input(hires_fix: Option<ForgeHiresFix>) -> ForgePayloadBuildInput<'static> {
+        ForgePayloadBuildInput {
+            prompt: "castle",
+            negative_prompt: "",
+            steps: Some("30"),
+            sampler: None,
+            scheduler: None,
+            cfg_scale: None,
+            seed: None,
+            width: Some(832),
+            height: Some(1216),
+            model_name: None,
+            include_seed: true,
+            adetailer_face_enabled: false,
+            adetailer_face_model: None,
+            hires_fix,
+        }
+    }
+
+    #[test]
+    fn server_stats_flag_memory_pressure() {
+        let gib = 1u64 << 30;
+        let memory = json!({
+            "ram": {"free": 20 * gib, "used": 12 * gib, "total": 32 * gib},
+            "cuda": {
+                "system": {"free": gib / 2, "used": 23 * gib + gib / 2, "total": 24 * gib},
+                "active": {"current": 18 * gib, "peak": 22 * gib},
+                "events": {"retries": 0, "oom": 1}
+            }
+        });
+        let progress = json!({"progress": 0.4, "state": {"job_count": 4}});
+        let stats = parse_server_stats(&memory, Some(&progress));
+        assert_eq!(stats.vram_total, Some(24 * gib));
+        assert_eq!(stats.vram_peak, Some(22 * gib));
+        assert_eq!(stats.job_count, Some(4));
+        assert!(stats.busy);
+        assert!(stats.memory_pressure);
+        assert_eq!(stats.warnings.len(), 2);
+        assert_eq!(
+            stats.warnings[0],
+            "VRAM nearly full: 23.5 of 24.0 GiB in use"
+        );
+
+        let idle = parse_server_stats(
+            &json!({"ram": {"used": gib, "total": 32 * gib}, "cuda": {"error": "no CUDA"}}),
+            None,
+        );
+        assert_eq!(idle.vram_used, None);
+        assert!(!idle.busy && !idle.memory_pressure);
+        assert_eq!(idle.warnings, vec!["GPU memory unavailable: no CUDA"]);
+    }
+
+    #[test]
+    fn missing_resources_are_matched_by_file_stem() {
+        let refs = ForgeResourceRefs {
+            model: Some("ponyRealism".to_string()),
+            loras: vec!["styles/BetterFaces".to_string(), "foo".to_string()],
+            embeddings: vec!["easynegative".to_string()],
+        };
+        let available = ForgeServerResources {
+            models: Some(vec!["PonyRealism.safetensors [747bbe7d2d]".to_string()]),
+            loras: Some(vec!["betterfaces".to_string()]),
+            embeddings: None,
+        };
+        let warnings = find_missing_resources(&refs, &available);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, ForgeResourceKind::Lora);
+        assert_eq!(warnings[0].message, "LoRA 'foo' not found on server");
+    }
+
+    #[test]
+    fn hires_fix_fields_are_sent_only_when_enabled() {
+        let payload = build_payload_from_image_record(payload_input(None));
+        let json = serde_json::to_value(&payload).unwrap();
+        assert!(json.get("enable_hr").is_none());
+        assert!(json.get("denoising_strength").is_none());
+
+        let payload = build_payload_from_image_record(payload_input(Some(ForgeHiresFix {
+            scale: Some(1.5),
+            upscaler: Some("4x-UltraSharp".to_string()),
+            second_pass_steps: Some(15),
+            denoising_strength: None,
+        })));
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["enable_hr"], true);
+        assert_eq!(json["hr_scale"], 1.5);
+        assert_eq!(json["hr_upscaler"], "4x-UltraSharp");
+        assert_eq!(json["hr_second_pass_steps"], 15);
+        assert!(json.get("denoising_strength").is_none());
+    }
 
     #[test]
     fn normalize_base_url_strips_sdapi_suffixes() {
@@ -401,6 +1002,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn transport_errors_are_classified_from_their_cause_chain() {
+        let classify = classify_transport_text;
+        assert_eq!(
+            classify(
+                "error sending request for url (http://forge.lan:7860/sdapi/v1/samplers): client error (Connect): dns error: failed to lookup address information: Name or service not known",
+                true
+            ),
+            ForgeFailureKind::Dns
+        );
+        assert_eq!(
+            classify(
+                "error sending request: client error (Connect): invalid peer certificate: UnknownIssuer",
+                true
+            ),
+            ForgeFailureKind::Tls
+        );
+        assert_eq!(
+            classify(
+                "error sending request: client error (Connect): tcp connect error: Connection refused (os error 111)",
+                true
+            ),
+            ForgeFailureKind::Connect
+        );
+        assert_eq!(
+            classify("error decoding response body", false),
+            ForgeFailureKind::Other
+        );
+    }
+
     #[test]
     fn build_sdapi_endpoint_avoids_duplicate_prefix() {
         assert_eq!(
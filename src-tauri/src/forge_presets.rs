@@ -0,0 +1,152 @@
+//! Named generation-setting presets for Forge sends.
+//!
+//! A preset holds the knobs people tune per model family — steps, CFG,
+//! sampler, scheduler, size and the hires pass — and is referenced by name
+//! from a send request. Values are kept as the strings the send form uses,
+//! so a preset field behaves exactly like typing it into the override form.
+//! Presets live in `forge_presets.json` in the app data directory.
+
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForgePreset {
+    pub name: String,
+    pub steps: Option<String>,
+    pub cfg_scale: Option<String>,
+    pub sampler_name: Option<String>,
+    pub scheduler: Option<String>,
+    pub width: Option<String>,
+    pub height: Option<String>,
+    pub enable_hr: Option<bool>,
+    pub hr_scale: Option<String>,
+    pub hr_upscaler: Option<String>,
+    pub hr_second_pass_steps: Option<String>,
+    pub denoising_strength: Option<String>,
+}
+
+impl ForgePreset {
+    /// Trims the name and every value, dropping empty values so they don't
+    /// override the source image, and checks numeric fields parse.
+    pub fn normalized(mut self) -> AppResult<Self> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            return Err(AppError::InvalidInput("Give the preset a name".to_string()));
+        }
+        for value in [
+            &mut self.steps,
+            &mut self.cfg_scale,
+            &mut self.sampler_name,
+            &mut self.scheduler,
+            &mut self.width,
+            &mut self.height,
+            &mut self.hr_scale,
+            &mut self.hr_upscaler,
+            &mut self.hr_second_pass_steps,
+            &mut self.denoising_strength,
+        ] {
+            *value = value
+                .take()
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty());
+        }
+
+        let integers = [
+            ("steps", &self.steps),
+            ("width", &self.width),
+            ("height", &self.height),
+            ("hires steps", &self.hr_second_pass_steps),
+        ];
+        for (field, value) in integers {
+            if let Some(raw) = value {
+                raw.parse::<u32>()
+                    .map_err(|_| invalid_value(&self.name, field, raw))?;
+            }
+        }
+        let decimals = [
+            ("CFG scale", &self.cfg_scale),
+            ("hires scale", &self.hr_scale),
+            ("denoising strength", &self.denoising_strength),
+        ];
+        for (field, value) in decimals {
+            if let Some(raw) = value {
+                raw.parse::<f32>()
+                    .ok()
+                    .filter(|number| number.is_finite())
+                    .ok_or_else(|| invalid_value(&self.name, field, raw))?;
+            }
+        }
+        Ok(self)
+    }
+}
+
+fn invalid_value(preset: &str, field: &str, raw: &str) -> AppError {
+    AppError::InvalidInput(format!(
+        "Preset '{}' has an invalid {}: {}",
+        preset, field, raw
+    ))
+}
+
+/// Looks a preset up by name, ignoring case.
+pub fn find<'a>(presets: &'a [ForgePreset], name: &str) -> Option<&'a ForgePreset> {
+    let name = name.trim();
+    presets
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name))
+}
+
+/// Replaces the preset with the same name (ignoring case), or appends it.
+pub fn upsert(presets: &mut Vec<ForgePreset>, preset: ForgePreset) {
+    match presets
+        .iter_mut()
+        .find(|existing| existing.name.eq_ignore_ascii_case(&preset.name))
+    {
+        Some(existing) => *existing = preset,
+        None => presets.push(preset),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_normalize_and_replace_by_name() {
+        let preset = ForgePreset {
+            name: " SDXL quality ".to_string(),
+            steps: Some(" 30 ".to_string()),
+            cfg_scale: Some("".to_string()),
+            sampler_name: Some("DPM++ 2M".to_string()),
+            ..Default::default()
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(preset.name, "SDXL quality");
+        assert_eq!(preset.steps.as_deref(), Some("30"));
+        assert_eq!(preset.cfg_scale, None);
+
+        let mut presets = vec![preset];
+        upsert(
+            &mut presets,
+            ForgePreset {
+                name: "sdxl QUALITY".to_string(),
+                steps: Some("40".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(presets.len(), 1);
+        assert_eq!(
+            find(&presets, "SDXL quality").and_then(|p| p.steps.as_deref()),
+            Some("40")
+        );
+
+        let bad = ForgePreset {
+            name: "Broken".to_string(),
+            denoising_strength: Some("lots".to_string()),
+            ..Default::default()
+        };
+        assert!(bad.normalized().is_err());
+        assert!(ForgePreset::default().normalized().is_err());
+    }
+}
@@ -0,0 +1,275 @@
+//! Ordering of Forge sends.
+//!
+//! Forge renders one request at a time, so sends take turns through a
+//! [`ForgeQueue`]. Waiting sends are served by priority, then by arrival.
+//! Batches take a turn per image and keep the sequence number they got when
+//! they were queued, so an interactive single send (high priority by default)
+//! slips in between two batch images without two batches interleaving.
+//!
+//! A batch can also carry a [`RunWindow`] such as 01:00–07:00; outside it the
+//! batch waits without holding the queue.
+
+use crate::error::{AppError, AppResult};
+use chrono::{Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+/// How often waiting sends re-check whether they were cancelled.
+const WAIT_SLICE: Duration = Duration::from_millis(250);
+const RUN_WINDOW_POLL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForgePriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Ticket {
+    priority: ForgePriority,
+    sequence: u64,
+}
+
+impl Ticket {
+    /// Higher priority first, then earlier arrival.
+    fn goes_before(&self, other: &Ticket) -> bool {
+        (self.priority, std::cmp::Reverse(self.sequence))
+            > (other.priority, std::cmp::Reverse(other.sequence))
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    busy: bool,
+    waiting: Vec<Ticket>,
+    next_sequence: u64,
+}
+
+#[derive(Default)]
+pub struct ForgeQueue {
+    state: Mutex<QueueState>,
+    released: Condvar,
+}
+
+/// Holds the queue; the next waiting send goes once this is dropped.
+pub struct ForgeQueueGuard {
+    queue: Arc<ForgeQueue>,
+}
+
+impl Drop for ForgeQueueGuard {
+    fn drop(&mut self) {
+        self.queue.lock().busy = false;
+        self.queue.released.notify_all();
+    }
+}
+
+impl ForgeQueue {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Arrival order for a new send; a batch takes one and reuses it for
+    /// every image.
+    pub fn next_sequence(&self) -> u64 {
+        let mut state = self.lock();
+        state.next_sequence += 1;
+        state.next_sequence
+    }
+
+    /// Blocks until it is this send's turn. Returns `None` if `cancelled`
+    /// reports true while waiting.
+    pub fn acquire(
+        self: &Arc<Self>,
+        priority: ForgePriority,
+        sequence: u64,
+        cancelled: impl Fn() -> bool,
+    ) -> Option<ForgeQueueGuard> {
+        let ticket = Ticket { priority, sequence };
+        let mut state = self.lock();
+        state.waiting.push(ticket);
+        loop {
+            let first = state.waiting.iter().copied().reduce(|best, next| {
+                if next.goes_before(&best) {
+                    next
+                } else {
+                    best
+                }
+            });
+            if !state.busy && first == Some(ticket) {
+                remove_ticket(&mut state.waiting, ticket);
+                state.busy = true;
+                return Some(ForgeQueueGuard {
+                    queue: Arc::clone(self),
+                });
+            }
+            if cancelled() {
+                remove_ticket(&mut state.waiting, ticket);
+                drop(state);
+                // Someone behind this send may be first now.
+                self.released.notify_all();
+                return None;
+            }
+            state = self
+                .released
+                .wait_timeout(state, WAIT_SLICE)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    /// Sends waiting for their turn, not counting the one running.
+    pub fn waiting(&self) -> usize {
+        self.lock().waiting.len()
+    }
+}
+
+fn remove_ticket(waiting: &mut Vec<Ticket>, ticket: Ticket) {
+    if let Some(position) = waiting.iter().position(|queued| *queued == ticket) {
+        waiting.remove(position);
+    }
+}
+
+/// Local time-of-day range a batch may run in, as `HH:MM` strings. A window
+/// whose end is before its start runs past midnight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunWindow {
+    pub start: String,
+    pub end: String,
+}
+
+/// A validated [`RunWindow`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedRunWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl RunWindow {
+    pub fn parse(&self) -> AppResult<ParsedRunWindow> {
+        let parse = |value: &str| {
+            NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|_| {
+                AppError::InvalidInput(format!("Run window times must be HH:MM, got '{}'", value))
+            })
+        };
+        let window = ParsedRunWindow {
+            start: parse(&self.start)?,
+            end: parse(&self.end)?,
+        };
+        if window.start == window.end {
+            return Err(AppError::InvalidInput(
+                "Run window start and end must differ".to_string(),
+            ));
+        }
+        Ok(window)
+    }
+}
+
+impl ParsedRunWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.contains(Local::now().time())
+    }
+
+    /// Blocks until the window opens. Returns false if `cancelled` reports
+    /// true first.
+    pub fn wait_until_open(&self, cancelled: impl Fn() -> bool) -> bool {
+        while !self.is_open() {
+            if cancelled() {
+                return false;
+            }
+            std::thread::sleep(RUN_WINDOW_POLL);
+        }
+        true
+    }
+
+    pub fn label(&self) -> String {
+        format!(
+            "{}–{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn high_priority_sends_jump_ahead_of_waiting_batches() {
+        let queue = Arc::new(ForgeQueue::default());
+        let running = queue.acquire(ForgePriority::Normal, 1, || false).unwrap();
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let spawn = |priority, sequence, name: &'static str| {
+            let queue = Arc::clone(&queue);
+            let order = Arc::clone(&order);
+            std::thread::spawn(move || {
+                let _guard = queue.acquire(priority, sequence, || false).unwrap();
+                order.lock().unwrap().push(name);
+            })
+        };
+        let batch = spawn(ForgePriority::Low, 2, "batch");
+        let later = spawn(ForgePriority::Normal, 4, "normal");
+        let single = spawn(ForgePriority::High, 5, "single");
+        while queue.waiting() < 3 {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        drop(running);
+        for handle in [batch, later, single] {
+            handle.join().unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec!["single", "normal", "batch"]);
+
+        let _held = queue.acquire(ForgePriority::Normal, 6, || false).unwrap();
+        assert!(queue.acquire(ForgePriority::High, 7, || true).is_none());
+        assert_eq!(queue.waiting(), 0);
+    }
+
+    #[test]
+    fn run_windows_wrap_past_midnight() {
+        let at = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        let night = RunWindow {
+            start: "23:00".to_string(),
+            end: "07:00".to_string(),
+        }
+        .parse()
+        .unwrap();
+        assert!(night.contains(at(1, 30)));
+        assert!(night.contains(at(23, 0)));
+        assert!(!night.contains(at(7, 0)));
+        assert!(!night.contains(at(12, 0)));
+        assert_eq!(night.label(), "23:00–07:00");
+
+        let afternoon = RunWindow {
+            start: "13:00".to_string(),
+            end: "17:30".to_string(),
+        }
+        .parse()
+        .unwrap();
+        assert!(afternoon.contains(at(17, 29)));
+        assert!(!afternoon.contains(at(18, 0)));
+
+        let bad = |start: &str, end: &str| {
+            RunWindow {
+                start: start.to_string(),
+                end: end.to_string(),
+            }
+            .parse()
+            .is_err()
+        };
+        assert!(bad("1am", "07:00"));
+        assert!(bad("07:00", "07:00"));
+    }
+}
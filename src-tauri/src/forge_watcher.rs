@@ -0,0 +1,120 @@
+//! Indexes images the Forge WebUI writes to its outputs folder, for people
+//! who generate in the browser rather than through this app.
+//!
+//! The outputs folder is handled like any watched folder (see
+//! [`crate::watch_folders`]); on top of that, new images are tagged with
+//! the session day the WebUI filed them under. Settings live in
+//! `forge_watcher.json` in the app data directory.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Folders the WebUI writes into under `outputs`.
+const OUTPUT_SUBDIRS: [&str; 5] = [
+    "txt2img-images",
+    "img2img-images",
+    "extras-images",
+    "txt2img-grids",
+    "img2img-grids",
+];
+
+/// How many folders above a chosen one are searched for `outputs`, enough to
+/// get from `models/Stable-diffusion/SDXL` back to the WebUI root.
+const MAX_PARENT_DEPTH: usize = 3;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForgeWatcherSettings {
+    pub enabled: bool,
+    pub outputs_dir: Option<String>,
+}
+
+/// Finds the WebUI outputs folder from the outputs folder itself, the WebUI
+/// install folder, or a folder inside the install such as its models folder.
+pub fn resolve_outputs_dir(path: &Path) -> Option<PathBuf> {
+    let is_outputs = |dir: &Path| {
+        dir.is_dir()
+            && (dir.file_name().is_some_and(|name| name == "outputs")
+                || OUTPUT_SUBDIRS
+                    .iter()
+                    .any(|subdir| dir.join(subdir).is_dir()))
+    };
+    if is_outputs(path) {
+        return Some(path.to_path_buf());
+    }
+    path.ancestors()
+        .take(MAX_PARENT_DEPTH + 1)
+        .map(|dir| dir.join("outputs"))
+        .find(|candidate| is_outputs(candidate))
+}
+
+/// Day an output belongs to: the WebUI's `YYYY-MM-DD` folder when the image
+/// sits in one, otherwise the local date of its modification time.
+pub fn session_date(path: &Path, file_mtime: Option<i64>) -> Option<String> {
+    let folder = path
+        .parent()
+        .and_then(Path::file_name)
+        .and_then(|name| name.to_str())
+        .filter(|name| chrono::NaiveDate::parse_from_str(name, "%Y-%m-%d").is_ok());
+    if let Some(folder) = folder {
+        return Some(folder.to_string());
+    }
+    let modified = chrono::DateTime::from_timestamp(file_mtime?, 0)?;
+    Some(
+        modified
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d")
+            .to_string(),
+    )
+}
+
+pub fn session_tag(date: &str) -> String {
+    format!("session:{}", date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watch_folders::NewFileTracker;
+    use std::fs;
+
+    #[test]
+    fn outputs_are_reported_once_their_size_settles() {
+        let root = std::env::temp_dir().join(format!("forge_watcher_test_{}", std::process::id()));
+        let day = root.join("txt2img-images").join("2026-10-16");
+        fs::create_dir_all(&day).unwrap();
+        let mut tracker = NewFileTracker::default();
+
+        let new = day.join("00001-2.png");
+        fs::write(&new, b"half").unwrap();
+        fs::write(day.join("00001-2.txt"), b"not an image").unwrap();
+        tracker.note(&new);
+        tracker.note(&day.join("00001-2.txt"));
+        assert!(tracker.settle().is_empty());
+        fs::write(&new, b"half written").unwrap();
+        assert!(tracker.settle().is_empty());
+        assert_eq!(tracker.settle(), vec![new.clone()]);
+        assert!(tracker.settle().is_empty());
+
+        assert_eq!(resolve_outputs_dir(&root), Some(root.clone()));
+        assert_eq!(session_date(&new, None).as_deref(), Some("2026-10-16"));
+        assert_eq!(session_date(&root.join("loose.png"), None), None);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn webui_folders_resolve_to_their_outputs() {
+        let webui = std::env::temp_dir().join(format!("forge_webui_test_{}", std::process::id()));
+        let models = webui.join("models").join("Stable-diffusion");
+        fs::create_dir_all(&models).unwrap();
+        fs::create_dir_all(webui.join("outputs")).unwrap();
+
+        let outputs = Some(webui.join("outputs"));
+        assert_eq!(resolve_outputs_dir(&webui), outputs);
+        assert_eq!(resolve_outputs_dir(&models), outputs);
+        assert_eq!(resolve_outputs_dir(&webui.join("outputs")), outputs);
+
+        let _ = fs::remove_dir_all(webui);
+    }
+}
@@ -0,0 +1,397 @@
+//! User-configured integrations fired on library events.
+//!
+//! A hook either POSTs to a webhook URL or runs a local program. The body is
+//! rendered from an optional payload template; placeholders like `{{indexed}}`
+//! or `{{items.0.filename}}` resolve against the event payload, `{{event}}`,
+//! `{{timestamp}}` and `{{payload}}` (the whole payload as JSON) are always
+//! available. String values are JSON-escaped so templates can embed them in
+//! JSON bodies such as Discord's `{"content": "..."}`.
+
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::time::Duration;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(15);
+/// Scripts still running after this are killed, so a stuck one can't hold
+/// up the hooks queued behind it.
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(60);
+const SCRIPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How long to wait for stderr once the script is gone. Anything it left
+/// running in the background may keep the pipe open.
+const SCRIPT_STDERR_GRACE: Duration = Duration::from_secs(1);
+/// Bytes of stderr kept for the run message.
+const SCRIPT_STDERR_LIMIT: u64 = 4096;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    ScanComplete,
+    ImagesIndexed,
+    ForgeBatchFinished,
+}
+
+impl HookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookEvent::ScanComplete => "scan_complete",
+            HookEvent::ImagesIndexed => "images_indexed",
+            HookEvent::ForgeBatchFinished => "forge_batch_finished",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HookAction {
+    Webhook {
+        url: String,
+    },
+    /// Runs `program` with `args`; the rendered payload is written to stdin
+    /// and the event name is exported as `FORGE_HOOK_EVENT`.
+    Script {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HookConfig {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub events: Vec<HookEvent>,
+    pub action: HookAction,
+    #[serde(default)]
+    pub payload_template: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HookRunResult {
+    pub ok: bool,
+    pub message: String,
+}
+
+/// Renders the body sent for an event, falling back to
+/// `{"event": ..., "timestamp": ..., "data": payload}` without a template.
+pub fn render_payload(template: Option<&str>, event: HookEvent, payload: &Value) -> String {
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    let Some(template) = template.filter(|value| !value.trim().is_empty()) else {
+        return json!({
+            "event": event.as_str(),
+            "timestamp": timestamp,
+            "data": payload,
+        })
+        .to_string();
+    };
+
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            output.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+
+        let key = after_open[..end].trim();
+        match key {
+            "event" => output.push_str(event.as_str()),
+            "timestamp" => output.push_str(&timestamp),
+            "payload" => output.push_str(&payload.to_string()),
+            _ => {
+                if let Some(value) = lookup_path(payload, key) {
+                    output.push_str(&template_value(value));
+                }
+            }
+        }
+        rest = &after_open[end + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+fn lookup_path<'a>(payload: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(payload, |current, segment| match current {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|idx| items.get(idx)),
+            _ => None,
+        })
+}
+
+fn template_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => {
+            let quoted = Value::String(text.clone()).to_string();
+            quoted[1..quoted.len() - 1].to_string()
+        }
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Fires every enabled hook subscribed to `event` without blocking the caller.
+pub fn dispatch(hooks: &[HookConfig], event: HookEvent, payload: Value) {
+    let matching: Vec<HookConfig> = hooks
+        .iter()
+        .filter(|hook| hook.enabled && hook.events.contains(&event))
+        .cloned()
+        .collect();
+    if matching.is_empty() {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        for hook in matching {
+            match run_hook(&hook, event, &payload).await {
+                Ok(result) if result.ok => {
+                    log::info!(
+                        "Hook '{}' ({}) ran: {}",
+                        hook.name,
+                        event.as_str(),
+                        result.message
+                    )
+                }
+                Ok(result) => {
+                    log::warn!(
+                        "Hook '{}' ({}) failed: {}",
+                        hook.name,
+                        event.as_str(),
+                        result.message
+                    )
+                }
+                Err(error) => {
+                    log::warn!("Hook '{}' ({}) error: {}", hook.name, event.as_str(), error)
+                }
+            }
+        }
+    });
+}
+
+/// Runs one hook and reports the outcome.
+pub async fn run_hook(
+    hook: &HookConfig,
+    event: HookEvent,
+    payload: &Value,
+) -> AppResult<HookRunResult> {
+    let body = render_payload(hook.payload_template.as_deref(), event, payload);
+    match &hook.action {
+        HookAction::Webhook { url } => send_webhook(url, body).await,
+        HookAction::Script { program, args } => {
+            let program = program.clone();
+            let args = args.clone();
+            tauri::async_runtime::spawn_blocking(move || run_script(&program, &args, event, &body))
+                .await?
+        }
+    }
+}
+
+async fn send_webhook(url: &str, body: String) -> AppResult<HookRunResult> {
+    let url = url.trim();
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(AppError::InvalidInput(format!(
+            "Webhook URL must be http(s): {}",
+            url
+        )));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .map_err(|error| AppError::Internal(format!("Failed to build HTTP client: {}", error)))?;
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|error| AppError::Io(format!("Webhook request failed: {}", error)))?;
+
+    let status = response.status();
+    Ok(HookRunResult {
+        ok: status.is_success(),
+        message: format!("HTTP {}", status),
+    })
+}
+
+fn run_script(
+    program: &str,
+    args: &[String],
+    event: HookEvent,
+    body: &str,
+) -> AppResult<HookRunResult> {
+    run_script_with_timeout(program, args, event, body, SCRIPT_TIMEOUT)
+}
+
+fn run_script_with_timeout(
+    program: &str,
+    args: &[String],
+    event: HookEvent,
+    body: &str,
+    timeout: Duration,
+) -> AppResult<HookRunResult> {
+    let mut command = std::process::Command::new(program);
+    command
+        .args(args)
+        .env("FORGE_HOOK_EVENT", event.as_str())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|error| AppError::from(error).context(format!("Failed to start {}", program)))?;
+
+    // Both pipes are served from their own threads: a script that fills
+    // stderr before reading stdin would otherwise block on us while we
+    // block on it.
+    if let Some(mut stdin) = child.stdin.take() {
+        let body = body.to_string();
+        std::thread::spawn(move || {
+            // Scripts that ignore stdin close it early; that is not an error.
+            let _ = stdin.write_all(body.as_bytes());
+        });
+    }
+    let (stderr_tx, stderr_rx) = std::sync::mpsc::channel();
+    if let Some(stderr) = child.stderr.take() {
+        std::thread::spawn(move || {
+            let mut captured = Vec::new();
+            // Read to the end so the script never blocks on a full pipe.
+            let mut reader = stderr;
+            let _ = (&mut reader)
+                .take(SCRIPT_STDERR_LIMIT)
+                .read_to_end(&mut captured);
+            let _ = std::io::copy(&mut reader, &mut std::io::sink());
+            let _ = stderr_tx.send(captured);
+        });
+    }
+
+    let deadline = std::time::Instant::now() + timeout;
+    let status = loop {
+        let waited = child.try_wait().map_err(|error| {
+            AppError::from(error).context(format!("Failed to wait for {}", program))
+        })?;
+        if let Some(status) = waited {
+            break status;
+        }
+        if std::time::Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(HookRunResult {
+                ok: false,
+                message: format!("killed after {} seconds", timeout.as_secs()),
+            });
+        }
+        std::thread::sleep(SCRIPT_POLL_INTERVAL);
+    };
+
+    let stderr = stderr_rx
+        .recv_timeout(SCRIPT_STDERR_GRACE)
+        .unwrap_or_default();
+    let stderr = String::from_utf8_lossy(&stderr).trim().to_string();
+    Ok(HookRunResult {
+        ok: status.success(),
+        message: if stderr.is_empty() {
+            status.to_string()
+        } else {
+            format!("{}: {}", status, stderr)
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_payload_substitutes_nested_and_escaped_values() {
+        let payload = json!({
+            "indexed": 12,
+            "directory": "C:\\out \"final\"",
+            "items": [{"filename": "a.png"}],
+        });
+        let rendered = render_payload(
+            Some(
+                r#"{"content": "{{event}}: {{indexed}} from {{directory}} ({{items.0.filename}}){{missing}}"}"#,
+            ),
+            HookEvent::ScanComplete,
+            &payload,
+        );
+        let parsed: Value = serde_json::from_str(&rendered).expect("rendered JSON should parse");
+        assert_eq!(
+            parsed["content"],
+            "scan_complete: 12 from C:\\out \"final\" (a.png)"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scripts_are_killed_on_timeout_and_never_deadlock_on_pipes() {
+        let sh = |script: &str, body: &str, timeout: Duration| {
+            run_script_with_timeout(
+                "sh",
+                &["-c".to_string(), script.to_string()],
+                HookEvent::ScanComplete,
+                body,
+                timeout,
+            )
+            .expect("sh should start")
+        };
+
+        let started = std::time::Instant::now();
+        let stuck = sh("sleep 30", "", Duration::from_millis(200));
+        assert!(!stuck.ok);
+        assert!(stuck.message.starts_with("killed"));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        // 256 KiB of stderr before stdin is read fills both pipe buffers.
+        let body = "x".repeat(256 * 1024);
+        let chatty = sh(
+            "head -c 262144 /dev/zero | tr '\\0' e >&2; cat >/dev/null; exit 3",
+            &body,
+            Duration::from_secs(30),
+        );
+        assert!(!chatty.ok);
+        assert!(chatty.message.starts_with("exit status: 3: eee"));
+    }
+
+    #[test]
+    fn render_payload_defaults_to_event_envelope() {
+        let payload = json!({"total": 3});
+        let rendered = render_payload(None, HookEvent::ForgeBatchFinished, &payload);
+        let parsed: Value = serde_json::from_str(&rendered).expect("default payload is JSON");
+        assert_eq!(parsed["event"], "forge_batch_finished");
+        assert_eq!(parsed["data"]["total"], 3);
+    }
+
+    #[test]
+    fn hook_config_deserializes_tagged_action() {
+        let hook: HookConfig = serde_json::from_str(
+            r#"{"id":"1","name":"discord","events":["scan_complete"],
+                "action":{"kind":"webhook","url":"https://example.com/hook"}}"#,
+        )
+        .expect("hook config should parse");
+        assert!(hook.enabled);
+        assert_eq!(
+            hook.action,
+            HookAction::Webhook {
+                url: "https://example.com/hook".to_string()
+            }
+        );
+    }
+}
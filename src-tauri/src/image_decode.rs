@@ -1,6 +1,7 @@
-use image::DynamicImage;
+use image::{DynamicImage, ImageDecoder};
 use std::path::Path;
-use std::sync::Once;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Condvar, Mutex, Once, PoisonError};
 
 static JXL_DECODER_HOOK: Once = Once::new();
 
@@ -17,3 +18,558 @@ pub fn open_image(path: &Path) -> Result<DynamicImage, image::ImageError> {
     ensure_jxl_decoder_registered();
     image::open(path)
 }
+
+/// Like [`open_image`], also returning the embedded ICC profile, if any.
+pub fn open_image_with_profile(
+    path: &Path,
+) -> Result<(DynamicImage, Option<Vec<u8>>), image::ImageError> {
+    ensure_jxl_decoder_registered();
+    let mut decoder = image::ImageReader::open(path)?.into_decoder()?;
+    // A malformed profile shouldn't cost the picture.
+    let profile = decoder.icc_profile().ok().flatten();
+    image::Limits::default().reserve(decoder.total_bytes())?;
+    Ok((DynamicImage::from_decoder(decoder)?, profile))
+}
+
+/// Largest image, in megapixels, decoded whole for a preview. 64 MP is
+/// 256 MB as RGBA; XYZ grids can be several times that.
+pub const DEFAULT_PREVIEW_BUDGET_MEGAPIXELS: u32 = 64;
+/// 0 means "use the default".
+static PREVIEW_BUDGET_OVERRIDE: AtomicU32 = AtomicU32::new(0);
+
+pub fn set_preview_budget(megapixels: Option<u32>) {
+    PREVIEW_BUDGET_OVERRIDE.store(megapixels.unwrap_or(0), Ordering::Release);
+}
+
+pub fn preview_budget_pixels() -> u64 {
+    let megapixels = match PREVIEW_BUDGET_OVERRIDE.load(Ordering::Acquire) {
+        0 => DEFAULT_PREVIEW_BUDGET_MEGAPIXELS,
+        megapixels => megapixels,
+    };
+    megapixels as u64 * 1_000_000
+}
+
+/// Memory, in megabytes, that preview decodes may hold at once across all
+/// threads, so a pool full of 50 MP PNGs can't take a 16 GB machine down.
+pub const DEFAULT_DECODE_MEMORY_BUDGET_MB: u32 = 2048;
+/// 0 means "use the default".
+static DECODE_MEMORY_BUDGET_OVERRIDE: AtomicU32 = AtomicU32::new(0);
+static DECODE_BUDGET: DecodeBudget = DecodeBudget::new();
+
+pub fn set_decode_memory_budget(megabytes: Option<u32>) {
+    DECODE_MEMORY_BUDGET_OVERRIDE.store(megabytes.unwrap_or(0), Ordering::Release);
+    // Waiting decodes may fit under a larger budget.
+    DECODE_BUDGET.released.notify_all();
+}
+
+fn decode_memory_budget_bytes() -> u64 {
+    let megabytes = match DECODE_MEMORY_BUDGET_OVERRIDE.load(Ordering::Acquire) {
+        0 => DEFAULT_DECODE_MEMORY_BUDGET_MB,
+        megabytes => megabytes,
+    };
+    megabytes as u64 * 1024 * 1024
+}
+
+/// Bytes held by decodes in flight. Callers wait for room rather than
+/// being turned away, which throttles decoding independently of how many
+/// threads the thumbnail pool has.
+struct DecodeBudget {
+    in_flight: Mutex<u64>,
+    released: Condvar,
+}
+
+impl DecodeBudget {
+    const fn new() -> Self {
+        Self {
+            in_flight: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Waits until `bytes` fit under `limit()`. A decode bigger than the
+    /// whole budget still runs, but alone.
+    fn reserve(&self, bytes: u64, limit: impl Fn() -> u64) -> DecodeReservation<'_> {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        while *in_flight > 0 && *in_flight + bytes > limit() {
+            in_flight = self
+                .released
+                .wait(in_flight)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        *in_flight += bytes;
+        DecodeReservation {
+            budget: self,
+            bytes,
+        }
+    }
+}
+
+/// Memory claimed from the decode budget, returned when dropped.
+pub struct DecodeReservation<'a> {
+    budget: &'a DecodeBudget,
+    bytes: u64,
+}
+
+impl Drop for DecodeReservation<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self
+            .budget
+            .in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *in_flight = in_flight.saturating_sub(self.bytes);
+        self.budget.released.notify_all();
+    }
+}
+
+/// A decoded preview source. Its share of the decode budget is held until
+/// it is dropped, so drop it as soon as the preview has been made.
+pub struct PreviewSource {
+    pub image: DynamicImage,
+    pub icc_profile: Option<Vec<u8>>,
+    _reservation: DecodeReservation<'static>,
+}
+
+/// Decodes `path` for a preview no more than `max_edge` pixels on its long
+/// side, with the embedded ICC profile. The dimensions are read first:
+/// images within the preview budget are decoded whole, larger PNGs are
+/// streamed row by row into a box-filtered copy of at most `max_edge`, so
+/// a 200 MP grid never sits in memory at full size. Nothing in the tree
+/// can scale other formats while decoding, so those fail with a limit
+/// error instead of exhausting memory.
+pub fn open_image_for_preview(
+    path: &Path,
+    max_edge: u32,
+) -> Result<PreviewSource, image::ImageError> {
+    ensure_jxl_decoder_registered();
+    let mut decoder = image::ImageReader::open(path)?.into_decoder()?;
+    let (width, height) = decoder.dimensions();
+    let icc_profile = decoder.icc_profile().ok().flatten();
+    if width as u64 * height as u64 <= preview_budget_pixels() {
+        image::Limits::default().reserve(decoder.total_bytes())?;
+        // The decoded buffer plus the 8-bit or resized copy made from it.
+        let reservation =
+            DECODE_BUDGET.reserve(decoder.total_bytes() * 2, decode_memory_budget_bytes);
+        return Ok(PreviewSource {
+            image: DynamicImage::from_decoder(decoder)?,
+            icc_profile,
+            _reservation: reservation,
+        });
+    }
+    drop(decoder);
+
+    let is_png = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+    if !is_png {
+        return Err(image::ImageError::Limits(
+            image::error::LimitError::from_kind(image::error::LimitErrorKind::DimensionError),
+        ));
+    }
+    log::info!(
+        "{} is {}x{}; downscaling while decoding",
+        path.display(),
+        width,
+        height
+    );
+    let reservation = DECODE_BUDGET.reserve(
+        max_edge as u64 * max_edge as u64 * 4,
+        decode_memory_budget_bytes,
+    );
+    let image = downscale_png_rows(path, max_edge).map_err(|error| {
+        image::ImageError::Decoding(image::error::DecodingError::new(
+            image::ImageFormat::Png.into(),
+            error,
+        ))
+    })?;
+    Ok(PreviewSource {
+        image,
+        icc_profile,
+        _reservation: reservation,
+    })
+}
+
+/// Box-filters a PNG to fit `max_edge` while reading it one row at a time;
+/// only one output row of sums is held besides the result.
+fn downscale_png_rows(path: &Path, max_edge: u32) -> Result<DynamicImage, png::DecodingError> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let (width, height) = (reader.info().width, reader.info().height);
+    if reader.info().interlaced {
+        // Adam7 passes arrive out of row order.
+        return Err(png::DecodingError::LimitsExceeded);
+    }
+    let channels = match reader.output_color_type().0 {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::Rgb | png::ColorType::Indexed => 3,
+        png::ColorType::Rgba => 4,
+    };
+
+    let scale = (max_edge.max(1) as f64 / width.max(height) as f64).min(1.0);
+    let out_width = ((width as f64 * scale).round() as u32).max(1);
+    let out_height = ((height as f64 * scale).round() as u32).max(1);
+    let column_of: Vec<usize> = (0..width)
+        .map(|x| (x as u64 * out_width as u64 / width as u64) as usize)
+        .collect();
+    let row_stride = out_width as usize * channels;
+
+    let mut output = vec![0u8; row_stride * out_height as usize];
+    let mut sums = vec![0u64; row_stride];
+    let mut counts = vec![0u64; out_width as usize];
+    let mut current_row = 0usize;
+    let mut flush = |row: usize, sums: &mut [u64], counts: &mut [u64]| {
+        let target = &mut output[row * row_stride..(row + 1) * row_stride];
+        for (column, count) in counts.iter_mut().enumerate() {
+            for channel in 0..channels {
+                let index = column * channels + channel;
+                if let Some(mean) = (sums[index] + *count / 2).checked_div(*count) {
+                    target[index] = mean as u8;
+                }
+                sums[index] = 0;
+            }
+            *count = 0;
+        }
+    };
+
+    let mut y = 0u64;
+    while let Some(row) = reader.next_row()? {
+        let out_row = (y * out_height as u64 / height as u64) as usize;
+        if out_row != current_row {
+            flush(current_row, &mut sums, &mut counts);
+            current_row = out_row;
+        }
+        for (x, pixel) in row.data().chunks_exact(channels).enumerate() {
+            let column = column_of[x];
+            counts[column] += 1;
+            for (channel, value) in pixel.iter().enumerate() {
+                sums[column * channels + channel] += *value as u64;
+            }
+        }
+        y += 1;
+    }
+    flush(current_row, &mut sums, &mut counts);
+
+    let image = match channels {
+        1 => {
+            image::GrayImage::from_raw(out_width, out_height, output).map(DynamicImage::ImageLuma8)
+        }
+        2 => image::GrayAlphaImage::from_raw(out_width, out_height, output)
+            .map(DynamicImage::ImageLumaA8),
+        3 => image::RgbImage::from_raw(out_width, out_height, output).map(DynamicImage::ImageRgb8),
+        _ => {
+            image::RgbaImage::from_raw(out_width, out_height, output).map(DynamicImage::ImageRgba8)
+        }
+    };
+    image.ok_or(png::DecodingError::LimitsExceeded)
+}
+
+/// Converts an 8-bit RGB image from its embedded ICC profile to sRGB.
+/// Without this, wide-gamut sources such as Display P3 look washed out
+/// once the profile is dropped from the thumbnail. Images without a
+/// profile, with a non-RGB one, or with one that can't be parsed come back
+/// unchanged.
+pub fn convert_to_srgb(image: DynamicImage, icc_profile: Option<&[u8]>) -> DynamicImage {
+    let Some(source) =
+        icc_profile.and_then(|bytes| moxcms::ColorProfile::new_from_slice(bytes).ok())
+    else {
+        return image;
+    };
+    if source.color_space != moxcms::DataColorSpace::Rgb {
+        return image;
+    }
+    let layout = match &image {
+        DynamicImage::ImageRgb8(_) => moxcms::Layout::Rgb,
+        DynamicImage::ImageRgba8(_) => moxcms::Layout::Rgba,
+        _ => return image,
+    };
+    let transform = match source.create_transform_8bit(
+        layout,
+        &moxcms::ColorProfile::new_srgb(),
+        layout,
+        moxcms::TransformOptions::default(),
+    ) {
+        Ok(transform) => transform,
+        Err(error) => {
+            log::debug!("Ignoring unusable ICC profile: {}", error);
+            return image;
+        }
+    };
+
+    let (width, height) = (image.width(), image.height());
+    let source_pixels = image.as_bytes();
+    let mut converted = vec![0u8; source_pixels.len()];
+    if let Err(error) = transform.transform(source_pixels, &mut converted) {
+        log::debug!("ICC transform failed: {}", error);
+        return image;
+    }
+    let rebuilt = match layout {
+        moxcms::Layout::Rgba => {
+            image::RgbaImage::from_raw(width, height, converted).map(DynamicImage::ImageRgba8)
+        }
+        _ => image::RgbImage::from_raw(width, height, converted).map(DynamicImage::ImageRgb8),
+    };
+    rebuilt.unwrap_or(image)
+}
+
+/// 4×4 Bayer matrix for ordered dithering.
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+/// Share of the brightest pixels allowed to clip when tone mapping, so a
+/// few specular highlights don't darken the whole picture.
+const TONE_MAP_CLIP_FRACTION: f32 = 0.001;
+/// Luminance samples used to find the white point of a float image.
+const TONE_MAP_SAMPLES: usize = 65_536;
+
+/// Float images (EXR, Radiance HDR) hold linear light, possibly above 1.0.
+pub fn is_float_image(image: &DynamicImage) -> bool {
+    matches!(
+        image,
+        DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+    )
+}
+
+/// 8-bit version of `image` for thumbnails, display proxies and 8-bit
+/// formats. 16-bit images are dithered so smooth gradients don't band;
+/// float images are tone-mapped from linear light to sRGB. 8-bit images
+/// come back unchanged.
+pub fn to_display_8bit(image: DynamicImage) -> DynamicImage {
+    match image {
+        DynamicImage::ImageLuma16(_)
+        | DynamicImage::ImageLumaA16(_)
+        | DynamicImage::ImageRgb16(_)
+        | DynamicImage::ImageRgba16(_) => dither_to_8bit(&image),
+        DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => tone_map_to_8bit(&image),
+        other => other,
+    }
+}
+
+fn dither_to_8bit(image: &DynamicImage) -> DynamicImage {
+    let has_alpha = image.color().has_alpha();
+    let rgba = image.to_rgba16();
+    let mut output = image::RgbaImage::new(rgba.width(), rgba.height());
+    for (x, y, pixel) in rgba.enumerate_pixels() {
+        let threshold = (BAYER_4X4[(y % 4) as usize][(x % 4) as usize] as f32 + 0.5) / 16.0;
+        let mut dithered = [0u8; 4];
+        for (channel, value) in pixel.0.iter().enumerate() {
+            dithered[channel] = (*value as f32 / 257.0 + threshold).floor().min(255.0) as u8;
+        }
+        output.put_pixel(x, y, image::Rgba(dithered));
+    }
+    if has_alpha {
+        DynamicImage::ImageRgba8(output)
+    } else {
+        DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(output).to_rgb8())
+    }
+}
+
+/// Extended Reinhard on luminance with the white point at the brightest
+/// unclipped pixel. Images that never exceed 1.0 keep their values and
+/// only get the sRGB curve.
+fn tone_map_to_8bit(image: &DynamicImage) -> DynamicImage {
+    let has_alpha = image.color().has_alpha();
+    let rgba = image.to_rgba32f();
+    let luminance = |pixel: &image::Rgba<f32>| {
+        let [r, g, b, _] = pixel.0;
+        (0.2126 * r + 0.7152 * g + 0.0722 * b).max(0.0)
+    };
+
+    let stride = (rgba.pixels().len() / TONE_MAP_SAMPLES).max(1);
+    let mut samples: Vec<f32> = rgba
+        .pixels()
+        .step_by(stride)
+        .map(luminance)
+        .filter(|value| value.is_finite())
+        .collect();
+    samples.sort_unstable_by(f32::total_cmp);
+    let clipped = (samples.len() as f32 * TONE_MAP_CLIP_FRACTION) as usize;
+    let white = samples
+        .len()
+        .checked_sub(clipped + 1)
+        .map(|index| samples[index])
+        .unwrap_or(1.0)
+        .max(1.0);
+
+    let mut output = image::RgbaImage::new(rgba.width(), rgba.height());
+    for (x, y, pixel) in rgba.enumerate_pixels() {
+        let lum = luminance(pixel);
+        let scale = if lum > 0.0 && lum.is_finite() {
+            (1.0 + lum / (white * white)) / (1.0 + lum)
+        } else {
+            0.0
+        };
+        let [r, g, b, a] = pixel.0;
+        let encode =
+            |value: f32| (linear_to_srgb(value * scale).clamp(0.0, 1.0) * 255.0).round() as u8;
+        output.put_pixel(
+            x,
+            y,
+            image::Rgba([
+                encode(r),
+                encode(g),
+                encode(b),
+                (a.clamp(0.0, 1.0) * 255.0).round() as u8,
+            ]),
+        );
+    }
+    if has_alpha {
+        DynamicImage::ImageRgba8(output)
+    } else {
+        DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(output).to_rgb8())
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sixteen_bit_gradients_dither_instead_of_banding() {
+        // Halfway between two 8-bit levels: truncation would give a flat 100.
+        let value = 100 * 257 + 128;
+        let image = DynamicImage::ImageRgb16(image::ImageBuffer::from_pixel(
+            8,
+            8,
+            image::Rgb([value, value, value]),
+        ));
+        let dithered = to_display_8bit(image).to_rgb8();
+        let levels: Vec<u8> = dithered.pixels().map(|pixel| pixel.0[0]).collect();
+        assert!(levels.iter().all(|level| *level == 100 || *level == 101));
+        let mean = levels.iter().map(|level| *level as f32).sum::<f32>() / levels.len() as f32;
+        assert!((mean - 100.5).abs() < 0.1, "mean {}", mean);
+
+        let opaque = DynamicImage::ImageRgba16(image::ImageBuffer::from_pixel(
+            2,
+            2,
+            image::Rgba([u16::MAX, 0, 0, u16::MAX]),
+        ));
+        assert_eq!(
+            to_display_8bit(opaque).to_rgba8().get_pixel(1, 1).0,
+            [255, 0, 0, 255]
+        );
+    }
+
+    #[test]
+    fn decodes_wait_for_room_in_the_memory_budget() {
+        use std::sync::atomic::AtomicBool;
+        use std::sync::Arc;
+
+        static BUDGET: DecodeBudget = DecodeBudget::new();
+        let first = BUDGET.reserve(60, || 100);
+        let admitted = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let admitted = admitted.clone();
+            std::thread::spawn(move || {
+                let _second = BUDGET.reserve(60, || 100);
+                admitted.store(true, Ordering::Release);
+            })
+        };
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!admitted.load(Ordering::Acquire));
+        drop(first);
+        waiter.join().unwrap();
+        assert!(admitted.load(Ordering::Acquire));
+
+        // Larger than the whole budget: admitted once nothing else runs.
+        drop(BUDGET.reserve(500, || 100));
+        assert_eq!(*BUDGET.in_flight.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn oversized_pngs_are_box_filtered_row_by_row() {
+        let path = std::env::temp_dir().join(format!("grid_downscale_{}.png", std::process::id()));
+        image::RgbaImage::from_fn(300, 150, |x, _| {
+            if x < 150 {
+                image::Rgba([0, 0, 0, 255])
+            } else {
+                image::Rgba([255, 255, 255, 128])
+            }
+        })
+        .save(&path)
+        .unwrap();
+
+        let small = downscale_png_rows(&path, 60).unwrap();
+        assert_eq!((small.width(), small.height()), (60, 30));
+        let small = small.to_rgba8();
+        assert_eq!(small.get_pixel(10, 12).0, [0, 0, 0, 255]);
+        assert_eq!(small.get_pixel(50, 29).0, [255, 255, 255, 128]);
+
+        // Already within the edge: same size, same pixels.
+        let same = downscale_png_rows(&path, 1000).unwrap();
+        assert_eq!((same.width(), same.height()), (300, 150));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn display_p3_pixels_are_converted_to_srgb() {
+        let p3 = moxcms::ColorProfile::new_display_p3().encode().unwrap();
+        let image = || {
+            DynamicImage::ImageRgb8(image::ImageBuffer::from_pixel(
+                2,
+                2,
+                image::Rgb([200, 100, 100]),
+            ))
+        };
+
+        // The same numbers mean a more saturated red in the wider gamut.
+        let [r, g, b] = convert_to_srgb(image(), Some(&p3))
+            .to_rgb8()
+            .get_pixel(0, 0)
+            .0;
+        assert!(r > 200 && g < 100 && b < 100, "{:?}", [r, g, b]);
+
+        let untouched = image().to_rgb8().get_pixel(0, 0).0;
+        assert_eq!(
+            convert_to_srgb(image(), None).to_rgb8().get_pixel(0, 0).0,
+            untouched
+        );
+        assert_eq!(
+            convert_to_srgb(image(), Some(b"not a profile"))
+                .to_rgb8()
+                .get_pixel(0, 0)
+                .0,
+            untouched
+        );
+    }
+
+    #[test]
+    fn float_images_are_tone_mapped_to_srgb() {
+        let linear = |values: &[f32]| {
+            let image = image::Rgb32FImage::from_fn(values.len() as u32, 1, |x, _| {
+                let value = values[x as usize];
+                image::Rgb([value, value, value])
+            });
+            to_display_8bit(DynamicImage::ImageRgb32F(image)).to_rgb8()
+        };
+
+        // Within 0..1 only the sRGB curve applies.
+        let sdr = linear(&[0.0, 0.216, 1.0]);
+        assert_eq!(sdr.get_pixel(0, 0).0[0], 0);
+        assert_eq!(sdr.get_pixel(1, 0).0[0], 128);
+        assert_eq!(sdr.get_pixel(2, 0).0[0], 255);
+
+        // Highlights up to 8.0 are compressed instead of clipping at 1.0.
+        let hdr = linear(&[0.5, 1.0, 2.0, 8.0]);
+        let levels: Vec<u8> = hdr.pixels().map(|pixel| pixel.0[0]).collect();
+        assert!(
+            levels.windows(2).all(|pair| pair[0] < pair[1]),
+            "{:?}",
+            levels
+        );
+        assert_eq!(levels[3], 255);
+    }
+}
@@ -0,0 +1,129 @@
+//! Pixel-level comparison of two images.
+//!
+//! Produces a heatmap overlay the viewer can lay over either image, plus a
+//! score for how different they are. Differences are measured per pixel as
+//! the mean absolute RGB difference; unchanged pixels stay transparent in the
+//! heatmap and changed ones ramp from blue (slight) through yellow to red.
+
+use image::{imageops::FilterType, DynamicImage, Rgba, RgbaImage};
+
+/// Long side both images are scaled down to before comparing, so a pair of
+/// 4K upscales doesn't need gigabytes of buffers.
+const MAX_DIFF_SIZE: u32 = 2048;
+/// Per-pixel difference (0–255) that counts as changed: JPEG noise and
+/// dithering stay below it.
+const CHANGED_THRESHOLD: f32 = 8.0;
+
+pub struct ImageDiff {
+    pub heatmap: RgbaImage,
+    /// Mean absolute difference over all pixels, 0 (identical) to 1.
+    pub score: f32,
+    /// Share of pixels that differ by more than the noise threshold.
+    pub changed_fraction: f32,
+    /// The images had different dimensions and `b` was scaled to match `a`.
+    pub resized: bool,
+}
+
+/// Compares `b` against `a`; the heatmap has `a`'s aspect ratio.
+pub fn diff(a: &DynamicImage, b: &DynamicImage) -> ImageDiff {
+    let resized = a.width() != b.width() || a.height() != b.height();
+    let a = if a.width().max(a.height()) > MAX_DIFF_SIZE {
+        a.resize(MAX_DIFF_SIZE, MAX_DIFF_SIZE, FilterType::Triangle)
+    } else {
+        a.clone()
+    };
+    let (width, height) = (a.width(), a.height());
+    let b = if b.width() == width && b.height() == height {
+        b.to_rgb8()
+    } else {
+        b.resize_exact(width, height, FilterType::Triangle)
+            .to_rgb8()
+    };
+    let a = a.to_rgb8();
+
+    let mut heatmap = RgbaImage::new(width, height);
+    let mut total = 0.0f64;
+    let mut changed = 0u64;
+    for ((pixel_a, pixel_b), out) in a.pixels().zip(b.pixels()).zip(heatmap.pixels_mut()) {
+        let difference = pixel_a
+            .0
+            .iter()
+            .zip(pixel_b.0.iter())
+            .map(|(x, y)| x.abs_diff(*y) as f32)
+            .sum::<f32>()
+            / 3.0;
+        total += difference as f64;
+        if difference > CHANGED_THRESHOLD {
+            changed += 1;
+            *out = heat_color(difference / 255.0);
+        }
+    }
+
+    let pixels = (width as u64 * height as u64).max(1);
+    ImageDiff {
+        heatmap,
+        score: (total / pixels as f64 / 255.0) as f32,
+        changed_fraction: changed as f32 / pixels as f32,
+        resized,
+    }
+}
+
+/// Blue → yellow → red with rising opacity; differences are stretched since
+/// most real ones are well under half the range.
+fn heat_color(difference: f32) -> Rgba<u8> {
+    let t = (difference * 2.5).clamp(0.0, 1.0);
+    let (r, g, b) = if t < 0.5 {
+        let k = t * 2.0;
+        (k, k, 1.0 - k)
+    } else {
+        (1.0, 2.0 - t * 2.0, 0.0)
+    };
+    let channel = |value: f32| (value * 255.0).round() as u8;
+    Rgba([channel(r), channel(g), channel(b), channel(0.45 + 0.5 * t)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn diff_marks_only_changed_pixels() {
+        let base = RgbImage::from_pixel(40, 20, Rgb([100, 100, 100]));
+        let mut edited = base.clone();
+        for y in 0..10 {
+            for x in 0..10 {
+                edited.put_pixel(x, y, Rgb([250, 100, 100]));
+            }
+        }
+        // Below the noise threshold.
+        edited.put_pixel(30, 15, Rgb([104, 100, 100]));
+
+        let result = diff(
+            &DynamicImage::ImageRgb8(base.clone()),
+            &DynamicImage::ImageRgb8(edited),
+        );
+        assert!(!result.resized);
+        assert!((result.changed_fraction - 100.0 / 800.0).abs() < 1e-6);
+        assert!((result.score - 50.0 * 100.0 / 800.0 / 255.0).abs() < 1e-3);
+        assert_eq!(result.heatmap.get_pixel(30, 15).0[3], 0);
+        assert!(result.heatmap.get_pixel(5, 5).0[3] > 0);
+
+        let same = diff(
+            &DynamicImage::ImageRgb8(base.clone()),
+            &DynamicImage::ImageRgb8(base),
+        );
+        assert_eq!(same.score, 0.0);
+        assert_eq!(same.changed_fraction, 0.0);
+    }
+
+    #[test]
+    fn diff_scales_mismatched_sizes_to_the_first_image() {
+        let a = RgbImage::from_pixel(40, 20, Rgb([10, 10, 10]));
+        let b = RgbImage::from_pixel(80, 40, Rgb([10, 10, 10]));
+        let result = diff(&DynamicImage::ImageRgb8(a), &DynamicImage::ImageRgb8(b));
+        assert!(result.resized);
+        assert_eq!(result.heatmap.dimensions(), (40, 20));
+        assert_eq!(result.score, 0.0);
+    }
+}
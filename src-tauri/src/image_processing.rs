@@ -1,8 +1,14 @@
+use crate::color_stats::{self, ColorStats};
+use crate::concurrency::{self, ResizablePool};
+use crate::error::{AppError, AppResult};
 use crate::image_decode;
+use crate::image_quality::{self, ImageQuality};
+use crate::perf;
 use crate::StorageProfile;
 use image::codecs::jpeg::JpegEncoder;
 use image::imageops::FilterType;
 use rayon::prelude::*;
+use serde::Serialize;
 use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::io::BufWriter;
@@ -17,8 +23,149 @@ const THUMB_JPEG_QUALITY_DEFAULT: u8 = 90;
 const THUMB_CACHE_VERSION: &str = "thumb-v2-hq";
 const HDD_FRIENDLY_IO_THREADS: usize = 4;
 const SSD_FRIENDLY_IO_THREADS: usize = 12;
+/// Blur-up placeholders, small enough to inline as base64 in an IPC reply.
+/// They live in their own folder so the thumbnail index never lists them.
+const TINY_THUMB_SIZE: u32 = 64;
+const TINY_THUMB_JPEG_QUALITY: u8 = 70;
+const TINY_THUMB_DIR: &str = "tiny";
+/// Side of the greyscale copy the perceptual hash is taken from.
+const PHASH_SAMPLE_SIZE: usize = 32;
+/// Side of the block of low-frequency DCT coefficients kept, one bit each.
+const PHASH_BLOCK_SIZE: usize = 8;
 
-fn io_threads(profile: StorageProfile) -> usize {
+/// Why a source has no thumbnail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbnailFailureKind {
+    /// The source file is gone.
+    Missing,
+    /// The source file has no bytes, usually an interrupted copy or save.
+    EmptyFile,
+    /// The source exists but could not be read.
+    Unreadable,
+    /// No decoder handles this format.
+    Unsupported,
+    /// The data is corrupt or truncated.
+    Decode,
+    /// Over the preview pixel budget in a format that can't be downscaled
+    /// while decoding.
+    TooLarge,
+    /// The thumbnail could not be written to the cache.
+    Write,
+}
+
+impl ThumbnailFailureKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Missing => "missing",
+            Self::EmptyFile => "empty_file",
+            Self::Unreadable => "unreadable",
+            Self::Unsupported => "unsupported",
+            Self::Decode => "decode",
+            Self::TooLarge => "too_large",
+            Self::Write => "write",
+        }
+    }
+
+    /// Reads back `as_str`; unknown values count as decode failures.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "missing" => Self::Missing,
+            "empty_file" => Self::EmptyFile,
+            "unreadable" => Self::Unreadable,
+            "unsupported" => Self::Unsupported,
+            "too_large" => Self::TooLarge,
+            "write" => Self::Write,
+            _ => Self::Decode,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThumbnailFailure {
+    pub filepath: String,
+    pub kind: ThumbnailFailureKind,
+    pub message: String,
+}
+
+/// Sources whose thumbnail rendering succeeded or failed, by filepath.
+#[derive(Debug, Default)]
+pub struct ThumbnailOutcomes {
+    pub rendered: Vec<String>,
+    pub failed: Vec<ThumbnailFailure>,
+}
+
+/// What rendering thumbnails learned about their sources, keyed by source
+/// filepath, for the caller to save once it has a database handle.
+#[derive(Debug, Default)]
+pub struct ThumbnailResults {
+    pub color_stats: Vec<(String, ColorStats)>,
+    pub image_quality: Vec<(String, ImageQuality)>,
+    pub phashes: Vec<(String, u64)>,
+    pub outcomes: ThumbnailOutcomes,
+}
+
+impl ThumbnailResults {
+    fn merge(mut self, other: Self) -> Self {
+        self.color_stats.extend(other.color_stats);
+        self.image_quality.extend(other.image_quality);
+        self.phashes.extend(other.phashes);
+        self.outcomes.rendered.extend(other.outcomes.rendered);
+        self.outcomes.failed.extend(other.outcomes.failed);
+        self
+    }
+
+    fn record_outcome(
+        &mut self,
+        source: &Path,
+        failure: Option<(ThumbnailFailureKind, &AppError)>,
+    ) {
+        let filepath = source.to_string_lossy().to_string();
+        match failure {
+            None => self.outcomes.rendered.push(filepath),
+            Some((kind, error)) => self.outcomes.failed.push(ThumbnailFailure {
+                filepath,
+                kind,
+                message: error.to_string(),
+            }),
+        }
+    }
+
+    fn record_stats(&mut self, source: &Path, thumbnail: &image::DynamicImage) {
+        let filepath = source.to_string_lossy().to_string();
+        if let Some(phash) = perceptual_hash(thumbnail) {
+            self.phashes.push((filepath.clone(), phash));
+        }
+        if let Some(quality) = image_quality::compute(thumbnail) {
+            self.image_quality.push((filepath.clone(), quality));
+        }
+        if let Some(stats) = color_stats::compute(thumbnail) {
+            self.color_stats.push((filepath, stats));
+        }
+    }
+}
+
+/// Thumbnails rendered or found by [`generate_thumbnails`].
+#[derive(Debug, Default)]
+pub struct GeneratedThumbnails {
+    /// Source and cached thumbnail path for each source that has one.
+    pub thumbnails: Vec<(PathBuf, PathBuf)>,
+    pub results: ThumbnailResults,
+}
+
+impl GeneratedThumbnails {
+    fn merge(mut self, other: Self) -> Self {
+        self.thumbnails.extend(other.thumbnails);
+        self.results = self.results.merge(other.results);
+        self
+    }
+}
+
+/// Worker threads used for thumbnail generation under `profile`.
+pub fn io_threads(profile: StorageProfile) -> usize {
+    if let Some(threads) = concurrency::thumbnail_threads_override() {
+        return threads;
+    }
     if let Ok(raw) = std::env::var("FORGE_IO_THREADS") {
         if let Ok(parsed) = raw.parse::<usize>() {
             return parsed.clamp(1, 32);
@@ -34,31 +181,15 @@ fn io_threads(profile: StorageProfile) -> usize {
     }
 }
 
-fn io_pool(profile: StorageProfile) -> &'static rayon::ThreadPool {
-    static HDD_POOL: OnceLock<rayon::ThreadPool> = OnceLock::new();
-    static SSD_POOL: OnceLock<rayon::ThreadPool> = OnceLock::new();
+fn io_pool(profile: StorageProfile) -> std::sync::Arc<rayon::ThreadPool> {
+    static HDD_POOL: ResizablePool = ResizablePool::new("thumb-io-hdd");
+    static SSD_POOL: ResizablePool = ResizablePool::new("thumb-io-ssd");
 
     let pool = match profile {
         StorageProfile::Hdd => &HDD_POOL,
         StorageProfile::Ssd => &SSD_POOL,
     };
-
-    pool.get_or_init(move || {
-        let threads = io_threads(profile);
-        let profile_name = profile_label(profile);
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(threads)
-            .thread_name(move |idx| format!("thumb-io-{}-{}", profile_name, idx))
-            .build()
-            .expect("failed to create thumbnail IO threadpool")
-    })
-}
-
-fn profile_label(profile: StorageProfile) -> &'static str {
-    match profile {
-        StorageProfile::Hdd => "hdd",
-        StorageProfile::Ssd => "ssd",
-    }
+    pool.get(io_threads(profile))
 }
 
 fn thumb_jpeg_quality() -> u8 {
@@ -72,14 +203,12 @@ fn thumb_jpeg_quality() -> u8 {
     })
 }
 
-pub fn prepare_cache_dir(cache_dir: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+pub fn prepare_cache_dir(cache_dir: &Path) -> AppResult<()> {
     std::fs::create_dir_all(cache_dir).map_err(|e| {
-        format!(
-            "Failed to create thumbnail cache dir {}: {}",
-            cache_dir.display(),
-            e
-        )
-        .into()
+        AppError::from(e).context(format!(
+            "Failed to create thumbnail cache dir {}",
+            cache_dir.display()
+        ))
     })
 }
 
@@ -93,23 +222,25 @@ pub fn generate_thumbnails(
     paths: &[PathBuf],
     cache_dir: &Path,
     profile: StorageProfile,
-) -> Vec<(PathBuf, PathBuf)> {
+) -> GeneratedThumbnails {
     if let Err(e) = prepare_cache_dir(cache_dir) {
         log::error!("Failed to create thumbnail cache dir: {}", e);
-        return Vec::new();
+        return GeneratedThumbnails::default();
     }
 
     io_pool(profile).install(|| {
         paths
             .par_iter()
-            .filter_map(|path| match generate_single_thumbnail(path, cache_dir) {
-                Ok(thumb_path) => Some((path.clone(), thumb_path)),
-                Err(e) => {
-                    log::warn!("Thumbnail generation failed for {}: {}", path.display(), e);
-                    None
+            .fold(GeneratedThumbnails::default, |mut generated, path| {
+                match generate_single_thumbnail(path, cache_dir, &mut generated.results) {
+                    Ok(thumb_path) => generated.thumbnails.push((path.clone(), thumb_path)),
+                    Err(e) => {
+                        log::warn!("Thumbnail generation failed for {}: {}", path.display(), e)
+                    }
                 }
+                generated
             })
-            .collect()
+            .reduce(GeneratedThumbnails::default, GeneratedThumbnails::merge)
     })
 }
 
@@ -120,8 +251,10 @@ pub fn ensure_thumbnail(
     source: &Path,
     cache_dir: &Path,
     _profile: StorageProfile,
-) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
-    generate_single_thumbnail(source, cache_dir)
+) -> (AppResult<PathBuf>, ThumbnailResults) {
+    let mut results = ThumbnailResults::default();
+    let thumbnail = generate_single_thumbnail(source, cache_dir, &mut results);
+    (thumbnail, results)
 }
 
 /// Resolves thumbnail mappings for a batch of source filepaths.
@@ -130,43 +263,116 @@ pub fn resolve_thumbnail_paths(
     filepaths: &[String],
     cache_dir: &Path,
     profile: StorageProfile,
-) -> Vec<(String, String)> {
+) -> (Vec<(String, String)>, ThumbnailResults) {
     if let Err(e) = prepare_cache_dir(cache_dir) {
         log::error!("Thumbnail cache dir unavailable: {}", e);
-        return filepaths
+        let mappings = filepaths
             .iter()
             .map(|filepath| (filepath.clone(), filepath.clone()))
             .collect();
+        return (mappings, ThumbnailResults::default());
     }
 
     io_pool(profile).install(|| {
         filepaths
             .par_iter()
-            .map(|filepath| {
-                let source = Path::new(filepath);
-                let thumb = get_thumbnail_path(source, cache_dir);
-
-                if thumb.exists() {
-                    return (filepath.clone(), thumb.to_string_lossy().to_string());
-                }
+            .fold(
+                || (Vec::new(), ThumbnailResults::default()),
+                |(mut mappings, mut results), filepath| {
+                    let source = Path::new(filepath);
+                    let thumb = get_thumbnail_path(source, cache_dir);
+                    let resolved = if thumb.exists() {
+                        thumb.to_string_lossy().to_string()
+                    } else {
+                        match generate_single_thumbnail(source, cache_dir, &mut results) {
+                            Ok(generated) => generated.to_string_lossy().to_string(),
+                            Err(e) => {
+                                log::warn!("On-demand thumbnail failed for {}: {}", filepath, e);
+                                filepath.clone()
+                            }
+                        }
+                    };
+                    mappings.push((filepath.clone(), resolved));
+                    (mappings, results)
+                },
+            )
+            .reduce(
+                || (Vec::new(), ThumbnailResults::default()),
+                |(mut mappings, results), (more_mappings, more_results)| {
+                    mappings.extend(more_mappings);
+                    (mappings, results.merge(more_results))
+                },
+            )
+    })
+}
 
-                match generate_single_thumbnail(source, cache_dir) {
-                    Ok(generated) => (filepath.clone(), generated.to_string_lossy().to_string()),
-                    Err(e) => {
-                        log::warn!("On-demand thumbnail failed for {}: {}", filepath, e);
-                        (filepath.clone(), filepath.clone())
-                    }
+/// 64px JPEG placeholders for already rendered thumbnails, in input order.
+/// They are made from the thumbnail rather than the source, so a slow
+/// drive is not read again, and cached under `tiny/` next to it.
+pub fn tiny_thumbnails(thumb_paths: &[PathBuf], profile: StorageProfile) -> Vec<Option<Vec<u8>>> {
+    io_pool(profile).install(|| {
+        thumb_paths
+            .par_iter()
+            .map(|thumb_path| match tiny_thumbnail(thumb_path) {
+                Ok(bytes) => Some(bytes),
+                Err(error) => {
+                    log::debug!(
+                        "No placeholder for thumbnail {}: {}",
+                        thumb_path.display(),
+                        error
+                    );
+                    None
                 }
             })
             .collect()
     })
 }
 
-/// Generates a single thumbnail, returning the thumbnail path.
+fn tiny_thumbnail(thumb_path: &Path) -> AppResult<Vec<u8>> {
+    let (Some(cache_dir), Some(name)) = (thumb_path.parent(), thumb_path.file_name()) else {
+        return Err(AppError::InvalidInput(format!(
+            "{} is not a thumbnail path",
+            thumb_path.display()
+        )));
+    };
+    let tiny_dir = cache_dir.join(TINY_THUMB_DIR);
+    let tiny_path = tiny_dir.join(name);
+    if thumbnail_is_current(thumb_path, &tiny_path) {
+        if let Ok(bytes) = std::fs::read(&tiny_path) {
+            return Ok(bytes);
+        }
+    }
+
+    let tiny = image_decode::open_image(thumb_path)?
+        .resize(TINY_THUMB_SIZE, TINY_THUMB_SIZE, FilterType::Triangle)
+        .to_rgb8();
+    let mut bytes = Vec::new();
+    JpegEncoder::new_with_quality(&mut bytes, TINY_THUMB_JPEG_QUALITY).encode(
+        tiny.as_raw(),
+        tiny.width(),
+        tiny.height(),
+        image::ExtendedColorType::Rgb8,
+    )?;
+    // Caching is an optimisation; the placeholder is still returned.
+    if let Err(error) =
+        std::fs::create_dir_all(&tiny_dir).and_then(|_| std::fs::write(&tiny_path, &bytes))
+    {
+        log::debug!(
+            "Failed to cache placeholder {}: {}",
+            tiny_path.display(),
+            error
+        );
+    }
+    Ok(bytes)
+}
+
+/// Generates a single thumbnail, returning the thumbnail path. What the
+/// render learned about the source goes into `results`.
 fn generate_single_thumbnail(
     source: &Path,
     cache_dir: &Path,
-) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+    results: &mut ThumbnailResults,
+) -> AppResult<PathBuf> {
     let thumb_name = hash_path(source);
     let thumb_path = cache_dir.join(format!("{}.{}", thumb_name, THUMB_EXTENSION));
 
@@ -175,18 +381,169 @@ fn generate_single_thumbnail(
         return Ok(thumb_path);
     }
 
+    let started = std::time::Instant::now();
+    match render_thumbnail(source, &thumb_path) {
+        Ok(thumbnail) => {
+            perf::record_thumbnail_generated(started.elapsed());
+            results.record_stats(source, &thumbnail);
+            results.record_outcome(source, None);
+            Ok(thumb_path)
+        }
+        Err((kind, error)) => {
+            perf::record_thumbnail_failed();
+            results.record_outcome(source, Some((kind, &error)));
+            Err(error)
+        }
+    }
+}
+
+/// Renders and writes the thumbnail, returning the pixels it was encoded
+/// from.
+fn render_thumbnail(
+    source: &Path,
+    thumb_path: &Path,
+) -> Result<image::DynamicImage, (ThumbnailFailureKind, AppError)> {
+    match std::fs::metadata(source) {
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            return Err((ThumbnailFailureKind::Missing, error.into()));
+        }
+        Err(error) => return Err((ThumbnailFailureKind::Unreadable, error.into())),
+        Ok(metadata) if metadata.len() == 0 => {
+            return Err((
+                ThumbnailFailureKind::EmptyFile,
+                AppError::Image(format!("{} is an empty file", source.display())),
+            ));
+        }
+        Ok(_) => {}
+    }
+
     // Open and resize using the configured high-quality filter.
-    let img = image_decode::open_image(source)?;
-    let thumbnail = img.resize(THUMB_SIZE, THUMB_SIZE, THUMB_FILTER);
-    encode_jpeg_thumbnail(&thumbnail, &thumb_path)?;
+    // Oversized sources are decoded straight to twice the thumbnail size.
+    let preview =
+        image_decode::open_image_for_preview(source, THUMB_SIZE * 2).map_err(|error| {
+            let kind = match &error {
+                image::ImageError::Unsupported(_) => ThumbnailFailureKind::Unsupported,
+                image::ImageError::Limits(_) => ThumbnailFailureKind::TooLarge,
+                // Decoders report truncated data as an early end of file.
+                image::ImageError::IoError(io_error)
+                    if !matches!(
+                        io_error.kind(),
+                        std::io::ErrorKind::UnexpectedEof | std::io::ErrorKind::InvalidData
+                    ) =>
+                {
+                    ThumbnailFailureKind::Unreadable
+                }
+                _ => ThumbnailFailureKind::Decode,
+            };
+            (kind, AppError::from(error))
+        })?;
+    // Resized at full depth, then brought down to 8-bit sRGB for the JPEG.
+    let thumbnail = image_decode::convert_to_srgb(
+        image_decode::to_display_8bit(preview.image.resize(THUMB_SIZE, THUMB_SIZE, THUMB_FILTER)),
+        preview.icc_profile.as_deref(),
+    );
+    // Frees the full-size decode and its share of the memory budget.
+    drop(preview);
+    encode_jpeg_thumbnail(&thumbnail, thumb_path)
+        .map_err(|error| (ThumbnailFailureKind::Write, error))?;
+    Ok(thumbnail)
+}
+
+/// 64-bit DCT perceptual hash: the image is shrunk to a 32x32 greyscale
+/// copy, and each of the 8x8 lowest-frequency DCT coefficients sets its bit
+/// when it lies above their median. Resizing, re-encoding and upscaling
+/// barely move those coefficients, so variants of the same generation land
+/// a few bits apart while unrelated images differ in about half of them.
+pub fn perceptual_hash(image: &image::DynamicImage) -> Option<u64> {
+    if image.width() == 0 || image.height() == 0 {
+        return None;
+    }
+    let gray = image
+        .resize_exact(
+            PHASH_SAMPLE_SIZE as u32,
+            PHASH_SAMPLE_SIZE as u32,
+            FilterType::Triangle,
+        )
+        .to_luma8();
+    let pixels: Vec<f64> = gray.as_raw().iter().map(|&luma| luma as f64).collect();
+
+    // Separable DCT-II, keeping only the rows and columns of the block.
+    let n = PHASH_SAMPLE_SIZE;
+    let cosines: Vec<f64> = (0..PHASH_BLOCK_SIZE * n)
+        .map(|index| {
+            let (frequency, position) = (index / n, index % n);
+            (std::f64::consts::PI * (2 * position + 1) as f64 * frequency as f64 / (2 * n) as f64)
+                .cos()
+        })
+        .collect();
+    let cosine = |frequency: usize, position: usize| cosines[frequency * n + position];
+    let mut rows = vec![0.0; n * PHASH_BLOCK_SIZE];
+    for y in 0..n {
+        for u in 0..PHASH_BLOCK_SIZE {
+            rows[y * PHASH_BLOCK_SIZE + u] = (0..n).map(|x| pixels[y * n + x] * cosine(u, x)).sum();
+        }
+    }
+    let mut block = [0.0f64; PHASH_BLOCK_SIZE * PHASH_BLOCK_SIZE];
+    for v in 0..PHASH_BLOCK_SIZE {
+        for u in 0..PHASH_BLOCK_SIZE {
+            block[v * PHASH_BLOCK_SIZE + u] = (0..n)
+                .map(|y| rows[y * PHASH_BLOCK_SIZE + u] * cosine(v, y))
+                .sum();
+        }
+    }
 
-    Ok(thumb_path)
+    // The DC term is overall brightness and would swamp the median.
+    let mut ac: Vec<f64> = block[1..].to_vec();
+    ac.sort_by(f64::total_cmp);
+    let median = (ac[ac.len() / 2 - 1] + ac[ac.len() / 2]) / 2.0;
+    Some(
+        block
+            .iter()
+            .enumerate()
+            .filter(|(_, &coefficient)| coefficient > median)
+            .fold(0u64, |hash, (bit, _)| hash | 1 << bit),
+    )
+}
+
+/// Records colour stats, quality and perceptual hashes for sources whose
+/// thumbnail was cached before they existed, reading the thumbnail rather
+/// than the original. Returns how many thumbnails were read, with what was
+/// measured from them.
+pub fn backfill_thumbnail_stats(
+    filepaths: &[String],
+    cache_dir: &Path,
+    profile: StorageProfile,
+) -> (usize, ThumbnailResults) {
+    io_pool(profile).install(|| {
+        filepaths
+            .par_iter()
+            .fold(
+                || (0, ThumbnailResults::default()),
+                |(read, mut results), filepath| {
+                    let source = Path::new(filepath.as_str());
+                    let thumb_path = get_thumbnail_cache_path(source, cache_dir);
+                    match image_decode::open_image(&thumb_path) {
+                        Ok(thumbnail) => {
+                            results.record_stats(source, &thumbnail);
+                            (read + 1, results)
+                        }
+                        Err(e) => {
+                            log::debug!("Thumbnail stats skipped for {}: {}", filepath, e);
+                            (read, results)
+                        }
+                    }
+                },
+            )
+            .reduce(
+                || (0, ThumbnailResults::default()),
+                |(read, results), (more_read, more_results)| {
+                    (read + more_read, results.merge(more_results))
+                },
+            )
+    })
 }
 
-fn encode_jpeg_thumbnail(
-    thumbnail: &image::DynamicImage,
-    out_path: &Path,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+fn encode_jpeg_thumbnail(thumbnail: &image::DynamicImage, out_path: &Path) -> AppResult<()> {
     let rgb = thumbnail.to_rgb8();
     let file = File::create(out_path)?;
     let writer = BufWriter::with_capacity(64 * 1024, file);
@@ -219,6 +576,34 @@ fn hex_encode(bytes: &[u8]) -> String {
     output
 }
 
+/// False when `source` was modified after `derived` was written from it.
+/// Timestamps that can't be read count as current.
+fn thumbnail_is_current(source: &Path, derived: &Path) -> bool {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|meta| meta.modified());
+    match (modified(source), modified(derived)) {
+        (Ok(source_modified), Ok(derived_modified)) => source_modified <= derived_modified,
+        _ => true,
+    }
+}
+
+/// Deletes the cached thumbnail of `source`, which was edited in place.
+/// The cache key is the path alone, so the scan that notices the new
+/// `file_mtime` has to do this. Returns the thumbnail path so callers can
+/// drop it from the thumbnail index.
+pub fn discard_thumbnail(source: &Path, cache_dir: &Path) -> PathBuf {
+    let thumb_path = get_thumbnail_cache_path(source, cache_dir);
+    if let Err(error) = std::fs::remove_file(&thumb_path) {
+        if error.kind() != std::io::ErrorKind::NotFound {
+            log::warn!(
+                "Failed to remove stale thumbnail {}: {}",
+                thumb_path.display(),
+                error
+            );
+        }
+    }
+    thumb_path
+}
+
 /// Returns the expected thumbnail path for a given source image.
 pub fn get_thumbnail_path(source: &Path, cache_dir: &Path) -> PathBuf {
     get_thumbnail_cache_path(source, cache_dir)
@@ -229,3 +614,132 @@ pub fn get_thumbnail_cache_path(source: &Path, cache_dir: &Path) -> PathBuf {
     let thumb_name = hash_path(source);
     cache_dir.join(format!("{}.{}", thumb_name, THUMB_EXTENSION))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thumbnail_failures_are_classified_by_cause() {
+        let dir = std::env::temp_dir().join(format!("thumb_failure_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let thumb = dir.join("thumb.jpg");
+        let kind_of = |name: &str, bytes: Option<&[u8]>| {
+            let source = dir.join(name);
+            if let Some(bytes) = bytes {
+                std::fs::write(&source, bytes).unwrap();
+            }
+            render_thumbnail(&source, &thumb)
+                .map(|_| ())
+                .map_err(|(kind, _)| kind)
+        };
+
+        assert_eq!(
+            kind_of("gone.png", None),
+            Err(ThumbnailFailureKind::Missing)
+        );
+        assert_eq!(
+            kind_of("empty.png", Some(b"")),
+            Err(ThumbnailFailureKind::EmptyFile)
+        );
+        assert_eq!(
+            kind_of("truncated.png", Some(b"\x89PNG\r\n\x1a\n\0\0")),
+            Err(ThumbnailFailureKind::Decode)
+        );
+        assert_eq!(
+            kind_of("notes.xyz", Some(b"not an image")),
+            Err(ThumbnailFailureKind::Unsupported)
+        );
+        assert_eq!(
+            ThumbnailFailureKind::parse(ThumbnailFailureKind::EmptyFile.as_str()),
+            ThumbnailFailureKind::EmptyFile
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn discarded_thumbnails_are_rendered_again() {
+        let dir = std::env::temp_dir().join(format!("thumb_stale_test_{}", std::process::id()));
+        let cache_dir = dir.join("cache");
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        let source = dir.join("edited.png");
+        image::RgbImage::from_pixel(8, 8, image::Rgb([10, 20, 30]))
+            .save(&source)
+            .unwrap();
+
+        let thumb =
+            generate_single_thumbnail(&source, &cache_dir, &mut ThumbnailResults::default())
+                .unwrap();
+        let rendered = std::fs::read(&thumb).unwrap();
+
+        // Replaced by an edit whose timestamp is older than the thumbnail.
+        image::RgbImage::from_pixel(8, 8, image::Rgb([200, 20, 30]))
+            .save(&source)
+            .unwrap();
+        File::options()
+            .write(true)
+            .open(&source)
+            .unwrap()
+            .set_modified(std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(discard_thumbnail(&source, &cache_dir), thumb);
+        assert!(!thumb.exists());
+        assert_eq!(discard_thumbnail(&source, &cache_dir), thumb);
+
+        generate_single_thumbnail(&source, &cache_dir, &mut ThumbnailResults::default()).unwrap();
+        assert_ne!(std::fs::read(&thumb).unwrap(), rendered);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn tiny_placeholders_are_cached_beside_the_thumbnail() {
+        let dir = std::env::temp_dir().join(format!("tiny_thumb_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("wide.png");
+        image::RgbImage::from_pixel(800, 400, image::Rgb([30, 120, 200]))
+            .save(&source)
+            .unwrap();
+        let thumb = dir.join("thumb.jpg");
+        render_thumbnail(&source, &thumb).unwrap();
+
+        let tiny = tiny_thumbnails(std::slice::from_ref(&thumb), StorageProfile::Ssd);
+        let bytes = tiny[0].as_ref().unwrap();
+        let decoded = image::load_from_memory(bytes).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 32));
+        assert_eq!(
+            &std::fs::read(dir.join("tiny").join("thumb.jpg")).unwrap(),
+            bytes
+        );
+
+        let missing = tiny_thumbnails(&[dir.join("none.jpg")], StorageProfile::Ssd);
+        assert!(missing[0].is_none());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn perceptual_hashes_match_across_resizes_but_not_content() {
+        let scene = |width: u32, height: u32, flip: bool| {
+            image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(width, height, |x, y| {
+                let (fx, fy) = (x as f32 / width as f32, y as f32 / height as f32);
+                let fx = if flip { 1.0 - fx } else { fx };
+                let ring = (((fx - 0.3).powi(2) + (fy - 0.6).powi(2)).sqrt() * 12.0).sin();
+                let luma = (fx * 120.0 + ring * 80.0 + 50.0) as u8;
+                image::Rgb([luma, luma / 2, 255 - luma])
+            }))
+        };
+        let distance = |a: u64, b: u64| (a ^ b).count_ones();
+
+        let original = perceptual_hash(&scene(512, 512, false)).unwrap();
+        let upscale = perceptual_hash(&scene(1536, 1536, false)).unwrap();
+        let thumbnail = perceptual_hash(&scene(640, 640, false).thumbnail(96, 96)).unwrap();
+        let mirrored = perceptual_hash(&scene(512, 512, true)).unwrap();
+
+        assert!(distance(original, upscale) <= 4);
+        assert!(distance(original, thumbnail) <= 4);
+        assert!(distance(original, mirrored) > 16);
+        assert_eq!(perceptual_hash(&image::DynamicImage::new_rgb8(0, 0)), None);
+    }
+}
@@ -0,0 +1,126 @@
+//! Cheap image quality signals for culling failed generations.
+//!
+//! Like the colour stats they are taken from the freshly rendered thumbnail.
+//!
+//! Sharpness is the variance of a Laplacian filter over a greyscale copy
+//! scaled to a fixed size, so scores from a 512px render and a 2048px
+//! upscale of similar content land in the same range. Flat or smeared
+//! images score near zero; detailed, in-focus ones score in the hundreds.
+//!
+//! Exposure is the share of pixels crushed to black or blown to white. Some
+//! samplers fail by collapsing to an almost uniform black or white frame,
+//! which [`ImageQuality::likely_failed`] catches.
+
+use image::{imageops::FilterType, DynamicImage, GrayImage};
+use serde::{Deserialize, Serialize};
+
+/// Long side of the copy quality is measured on.
+const SAMPLE_SIZE: u32 = 256;
+/// Luma at or below which a pixel counts as crushed to black.
+const DARK_LUMA: u8 = 8;
+/// Luma at or above which a pixel counts as blown out.
+const BRIGHT_LUMA: u8 = 247;
+/// Share of crushed or blown pixels that marks a likely failed render.
+pub const FAILED_EXPOSURE_FRACTION: f32 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ImageQuality {
+    /// See [`sharpness`].
+    pub sharpness: f32,
+    /// Share of pixels at or below [`DARK_LUMA`], 0-1.
+    pub dark_fraction: f32,
+    /// Share of pixels at or above [`BRIGHT_LUMA`], 0-1.
+    pub bright_fraction: f32,
+}
+
+impl ImageQuality {
+    /// Nearly all black or nearly all white.
+    pub fn likely_failed(&self) -> bool {
+        self.dark_fraction >= FAILED_EXPOSURE_FRACTION
+            || self.bright_fraction >= FAILED_EXPOSURE_FRACTION
+    }
+}
+
+pub fn compute(image: &DynamicImage) -> Option<ImageQuality> {
+    if image.width() == 0 || image.height() == 0 {
+        return None;
+    }
+    let gray = luma_sample(image);
+    let pixels = gray.as_raw();
+    let share = |matches: usize| matches as f32 / pixels.len().max(1) as f32;
+    Some(ImageQuality {
+        sharpness: laplacian_variance(&gray),
+        dark_fraction: share(pixels.iter().filter(|&&luma| luma <= DARK_LUMA).count()),
+        bright_fraction: share(pixels.iter().filter(|&&luma| luma >= BRIGHT_LUMA).count()),
+    })
+}
+
+fn luma_sample(image: &DynamicImage) -> GrayImage {
+    image
+        .resize(SAMPLE_SIZE, SAMPLE_SIZE, FilterType::Triangle)
+        .to_luma8()
+}
+
+/// Variance of the 4-neighbour Laplacian over the image's luma.
+pub fn sharpness(image: &DynamicImage) -> f32 {
+    if image.width() == 0 || image.height() == 0 {
+        return 0.0;
+    }
+    laplacian_variance(&luma_sample(image))
+}
+
+fn laplacian_variance(gray: &GrayImage) -> f32 {
+    let (width, height) = gray.dimensions();
+    if width < 3 || height < 3 {
+        return 0.0;
+    }
+    let luma = |x: u32, y: u32| gray.get_pixel(x, y).0[0] as f32;
+
+    let (mut sum, mut sum_sq, mut count) = (0.0f64, 0.0f64, 0u32);
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let laplacian = luma(x - 1, y) + luma(x + 1, y) + luma(x, y - 1) + luma(x, y + 1)
+                - 4.0 * luma(x, y);
+            sum += laplacian as f64;
+            sum_sq += (laplacian as f64).powi(2);
+            count += 1;
+        }
+    }
+    let mean = sum / count as f64;
+    (sum_sq / count as f64 - mean * mean).max(0.0) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    #[test]
+    fn detail_scores_sharper_than_blur() {
+        let checker = GrayImage::from_fn(128, 128, |x, y| {
+            Luma([if (x / 4 + y / 4) % 2 == 0 { 0 } else { 255 }])
+        });
+        let sharp = DynamicImage::ImageLuma8(checker);
+        let blurred = sharp.blur(4.0);
+        let flat = DynamicImage::ImageLuma8(GrayImage::from_pixel(64, 64, Luma([128])));
+
+        assert!(sharpness(&sharp) > 10.0 * sharpness(&blurred));
+        assert_eq!(sharpness(&flat), 0.0);
+    }
+
+    #[test]
+    fn black_and_blown_frames_are_likely_failed() {
+        let frame =
+            |luma: u8| DynamicImage::ImageLuma8(GrayImage::from_pixel(64, 64, Luma([luma])));
+        let black = compute(&frame(2)).unwrap();
+        assert_eq!(black.dark_fraction, 1.0);
+        assert!(black.likely_failed());
+        assert!(compute(&frame(255)).unwrap().likely_failed());
+
+        let gradient =
+            DynamicImage::ImageLuma8(GrayImage::from_fn(256, 16, |x, _| Luma([x as u8])));
+        let exposed = compute(&gradient).unwrap();
+        assert!(exposed.dark_fraction < 0.1 && exposed.bright_fraction < 0.1);
+        assert!(!exposed.likely_failed());
+    }
+}
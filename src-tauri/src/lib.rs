@@ -1,34 +1,112 @@
+pub mod autotag;
+pub mod cli;
+pub mod collection_rules;
+pub mod color_stats;
+pub mod concurrency;
 pub mod database;
+pub mod embed_metadata;
+pub mod error;
+pub mod events;
+pub mod file_clipboard;
+pub mod filename_template;
+pub mod focal_point;
 pub mod forge_api;
+pub mod forge_presets;
+pub mod forge_queue;
+pub mod forge_watcher;
+pub mod hooks;
 pub mod image_decode;
+pub mod image_diff;
 pub mod image_processing;
+pub mod image_quality;
+pub mod nsfw;
+pub mod ocr;
 pub mod parser;
+pub mod perf;
+pub mod projects;
+pub mod prompt_clusters;
+pub mod prompt_edit;
+pub mod prompt_styles;
 pub mod scanner;
+pub mod scroll_warmup;
 pub mod sidecar;
+pub mod spelling;
+pub mod tag_dictionary;
+pub mod tasks;
+pub mod thumbnail_protocol;
+pub mod thumbnail_strip;
+pub mod volumes;
+pub mod watch_folders;
 
 mod commands;
+mod tray;
 
 use commands::{
-    delete_images, directory_exists, export_images, export_images_as_files, filter_images_cursor,
-    forge_get_options, forge_send_to_image, forge_send_to_images, forge_test_connection,
-    get_directories, get_display_image_path, get_forge_api_key, get_image_clipboard_payload,
-    get_image_detail, get_image_tags, get_images_cursor, get_models, get_sidecar_data,
-    get_storage_profile, get_thumbnail_path, get_thumbnail_paths, get_top_tags, get_total_count,
-    list_tags, move_images_to_directory, open_file_location, precache_all_thumbnails,
-    save_sidecar_tags, scan_directory, search_images_cursor, set_forge_api_key, set_image_favorite,
-    set_image_locked, set_images_favorite, set_images_locked, set_storage_profile,
+    add_watch_folder, apply_prompt_style, archive_images, autotag_images, bulk_edit_prompts,
+    bulk_update_fields, cancel_background_task, clear_finished_background_tasks,
+    clear_tag_dictionary, cluster_prompts, copy_files_to_clipboard, delete_collection,
+    delete_forge_preset, delete_images, delete_model_defaults, delete_project, detect_focal_points,
+    diff_images, directory_exists, estimate_export, export_images, export_images_as_files,
+    export_thumbnail_strip, filter_images_cursor, find_duplicate_images, find_similar_images,
+    forge_get_options, forge_get_server_stats, forge_send_to_image, forge_send_to_images,
+    forge_test_connection, get_activity_files, get_activity_log, get_autotag_status,
+    get_background_status, get_batch_members, get_collections, get_corrupt_images, get_directories,
+    get_disk_usage_report, get_display_image_path, get_embedding_usage, get_extra_param_keys,
+    get_field_edits, get_filter_counts, get_forge_api_key, get_forge_watcher_status,
+    get_grid_members, get_hooks, get_image_clipboard_payload, get_image_detail, get_image_details,
+    get_image_tags, get_images_by_ids_detail, get_images_cursor, get_keyword_trends,
+    get_lora_images_cursor, get_lora_usage, get_metadata_coverage, get_model_usage, get_models,
+    get_nsfw_settings, get_ocr_status, get_pending_operations, get_performance_report,
+    get_performance_settings, get_private_status, get_prompt_clusters, get_resolution_buckets,
+    get_samplers, get_schedulers, get_seed_neighbors, get_sidecar_data, get_slideshow_batch,
+    get_source_tools, get_storage_profile, get_thumbnail_failures, get_thumbnail_path,
+    get_thumbnail_paths, get_thumbnail_tiers, get_top_tags, get_total_count, get_user_properties,
+    get_volume_status, get_watch_folders, get_workflow_status_counts, hint_scroll_position,
+    import_from_clipboard, import_styles, import_tag_dictionary, list_background_tasks,
+    list_forge_presets, list_model_defaults, list_projects, list_prompt_styles,
+    list_tag_suggestions, list_tags, list_user_property_keys, load_view_state, lock_private,
+    move_images_to_directory, ocr_images, open_file_location, pause_background_task,
+    precache_all_thumbnails, preview_delete_images, preview_prompt_edit, quarantine_corrupt_images,
+    regenerate_thumbnails, remove_watch_folder, reparse_image, resume_pending_operations,
+    retry_failed_thumbnails, save_collection, save_forge_preset, save_project, save_sidecar_tags,
+    save_view_state, scan_directory, search_by_color, search_images_cursor, set_background_paused,
+    set_close_to_tray, set_directory_alias, set_directory_hidden, set_directory_pinned,
+    set_directory_private, set_favorite_by_filter, set_forge_api_key, set_forge_watcher, set_hooks,
+    set_image_favorite, set_image_locked, set_image_rating, set_images_favorite, set_images_locked,
+    set_locked_by_filter, set_model_defaults, set_nsfw_settings, set_performance_settings,
+    set_private_passcode, set_storage_profile, set_user_property, set_workflow_status,
+    set_workflow_status_by_filter, suggest_best_of_batch, summarize_selection, switch_project,
+    test_hook, unarchive_images, undo_field_edit, unlock_private, GalleryListing,
 };
 use database::Database;
+use error::{AppError, AppResult};
+use forge_presets::ForgePreset;
+use forge_watcher::ForgeWatcherSettings;
+use hooks::HookConfig;
+use projects::ProjectSettings;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, RwLock};
-use tauri::async_runtime::Mutex;
+use tasks::TaskRegistry;
 use tauri::Manager;
+use watch_folders::WatchFolderSettings;
 
 const STORAGE_PROFILE_FILE: &str = "storage_profile.json";
 const FORGE_API_KEY_FILE: &str = "forge_api_key.json";
+const HOOKS_FILE: &str = "hooks.json";
+const FORGE_PRESETS_FILE: &str = "forge_presets.json";
+const FORGE_WATCHER_FILE: &str = "forge_watcher.json";
+const WATCH_FOLDERS_FILE: &str = "watch_folders.json";
+const BACKGROUND_SETTINGS_FILE: &str = "background.json";
+const PERFORMANCE_SETTINGS_FILE: &str = "performance.json";
+const NSFW_SETTINGS_FILE: &str = "nsfw.json";
+const PRIVATE_SETTINGS_FILE: &str = "private.json";
+const VIEW_STATE_FILE: &str = "view_state.json";
+const PROJECTS_FILE: &str = "projects.json";
+const INBOX_DIR: &str = "inbox";
+const QUARANTINE_DIR: &str = "quarantine";
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -38,18 +116,322 @@ pub enum StorageProfile {
     Ssd,
 }
 
+/// Tray/background-mode preferences persisted across launches.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BackgroundSettings {
+    /// Hide to the tray instead of exiting when the main window closes.
+    #[serde(default)]
+    pub close_to_tray: bool,
+    /// Library roots scanned so far; "Rescan now" walks these again.
+    #[serde(default)]
+    pub scan_roots: Vec<String>,
+}
+
+impl BackgroundSettings {
+    /// Records a scanned directory, folding nested roots into their parent.
+    /// Returns false when the directory was already covered.
+    pub fn add_scan_root(&mut self, directory: &str) -> bool {
+        let candidate = Path::new(directory);
+        if self
+            .scan_roots
+            .iter()
+            .any(|root| candidate.starts_with(Path::new(root)))
+        {
+            return false;
+        }
+        self.scan_roots
+            .retain(|root| !Path::new(root).starts_with(candidate));
+        self.scan_roots.push(directory.to_string());
+        true
+    }
+}
+
+/// A 1000 MP image is 4 GB as RGBA, beyond what a preview should ever take.
+const MAX_PREVIEW_BUDGET_MEGAPIXELS: u32 = 1000;
+
+/// Accepted range for `decode_memory_budget_mb`.
+const DECODE_MEMORY_BUDGET_RANGE_MB: std::ops::RangeInclusive<u32> = 256..=65_536;
+
+/// Concurrency and decode overrides; `None` keeps the storage-profile/env
+/// default.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PerformanceSettings {
+    #[serde(default)]
+    pub scan_threads: Option<usize>,
+    #[serde(default)]
+    pub thumbnail_threads: Option<usize>,
+    /// Only takes effect after a restart; the pool is built once at startup.
+    #[serde(default)]
+    pub db_pool_size: Option<u32>,
+    /// Images larger than this many megapixels are downscaled while they
+    /// are decoded for thumbnails, or skipped when their format can't be.
+    #[serde(default)]
+    pub preview_budget_megapixels: Option<u32>,
+    /// Memory thumbnail decodes may hold at once, however many threads run.
+    #[serde(default)]
+    pub decode_memory_budget_mb: Option<u32>,
+}
+
+impl PerformanceSettings {
+    pub fn validate(&self) -> AppResult<()> {
+        let max = concurrency::MAX_THREADS;
+        let out_of_range = |value: usize| !(1..=max).contains(&value);
+        if self.scan_threads.is_some_and(out_of_range)
+            || self.thumbnail_threads.is_some_and(out_of_range)
+            || self
+                .db_pool_size
+                .is_some_and(|size| out_of_range(size as usize))
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Thread and pool sizes must be between 1 and {}",
+                max
+            )));
+        }
+        if self
+            .preview_budget_megapixels
+            .is_some_and(|megapixels| !(1..=MAX_PREVIEW_BUDGET_MEGAPIXELS).contains(&megapixels))
+        {
+            return Err(AppError::InvalidInput(format!(
+                "The preview budget must be between 1 and {} megapixels",
+                MAX_PREVIEW_BUDGET_MEGAPIXELS
+            )));
+        }
+        if self
+            .decode_memory_budget_mb
+            .is_some_and(|megabytes| !DECODE_MEMORY_BUDGET_RANGE_MB.contains(&megabytes))
+        {
+            return Err(AppError::InvalidInput(format!(
+                "The decode memory budget must be between {} and {} MB",
+                DECODE_MEMORY_BUDGET_RANGE_MB.start(),
+                DECODE_MEMORY_BUDGET_RANGE_MB.end()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Applies the thread overrides to the scan and thumbnail pools and the
+    /// preview budget to thumbnail decoding.
+    pub fn apply_overrides(&self) {
+        concurrency::set_thread_overrides(self.scan_threads, self.thumbnail_threads);
+        image_decode::set_preview_budget(self.preview_budget_megapixels);
+        image_decode::set_decode_memory_budget(self.decode_memory_budget_mb);
+    }
+}
+
+/// "Hide NSFW" switch for people who share their screen.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct NsfwSettings {
+    #[serde(default)]
+    pub hide_nsfw: bool,
+    /// Images with an `nsfw_score` at or above this are hidden.
+    #[serde(default = "default_nsfw_threshold")]
+    pub threshold: f32,
+}
+
+fn default_nsfw_threshold() -> f32 {
+    0.5
+}
+
+impl Default for NsfwSettings {
+    fn default() -> Self {
+        Self {
+            hide_nsfw: false,
+            threshold: default_nsfw_threshold(),
+        }
+    }
+}
+
+impl NsfwSettings {
+    pub fn validate(&self) -> AppResult<()> {
+        if !(self.threshold > 0.0 && self.threshold <= 1.0) {
+            return Err(AppError::InvalidInput(format!(
+                "NSFW threshold must be greater than 0 and at most 1, got {}",
+                self.threshold
+            )));
+        }
+        Ok(())
+    }
+
+    /// The score cut-off queries should apply, if hiding is on.
+    pub fn filter_threshold(&self) -> Option<f32> {
+        self.hide_nsfw.then_some(self.threshold)
+    }
+}
+
+/// Directories hidden until the session is unlocked with a passcode.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PrivateSettings {
+    #[serde(default)]
+    pub directories: Vec<String>,
+    /// Hex hash of the passcode with `passcode_salt`; the passcode itself
+    /// is never stored. See `passcode_kdf` for how it was made.
+    #[serde(default)]
+    passcode_hash: Option<String>,
+    #[serde(default)]
+    passcode_salt: Option<String>,
+    /// `argon2id`, or unset for hashes saved as one round of SHA-256 before
+    /// Argon2 was used. Those are replaced on the next successful unlock.
+    #[serde(default)]
+    passcode_kdf: Option<String>,
+}
+
+impl PrivateSettings {
+    pub const MIN_PASSCODE_LEN: usize = 4;
+    const PASSCODE_KDF: &'static str = "argon2id";
+
+    pub fn has_passcode(&self) -> bool {
+        self.passcode_hash.is_some()
+    }
+
+    pub fn set_passcode(&mut self, passcode: &str) -> AppResult<()> {
+        if passcode.chars().count() < Self::MIN_PASSCODE_LEN {
+            return Err(AppError::InvalidInput(format!(
+                "Passcode must be at least {} characters",
+                Self::MIN_PASSCODE_LEN
+            )));
+        }
+        let salt = new_passcode_salt();
+        self.passcode_hash = Some(hash_passcode(&salt, passcode)?);
+        self.passcode_salt = Some(salt);
+        self.passcode_kdf = Some(Self::PASSCODE_KDF.to_string());
+        Ok(())
+    }
+
+    pub fn verify_passcode(&self, passcode: &str) -> bool {
+        let (Some(hash), Some(salt)) = (&self.passcode_hash, &self.passcode_salt) else {
+            return false;
+        };
+        match self.passcode_kdf.as_deref() {
+            Some(Self::PASSCODE_KDF) => {
+                hash_passcode(salt, passcode).is_ok_and(|computed| computed == *hash)
+            }
+            None => legacy_hash_passcode(salt, passcode) == *hash,
+            Some(other) => {
+                log::warn!("Unknown passcode hash '{}'", other);
+                false
+            }
+        }
+    }
+
+    /// True when the stored hash predates Argon2 and should be replaced.
+    pub fn passcode_needs_rehash(&self) -> bool {
+        self.has_passcode() && self.passcode_kdf.is_none()
+    }
+}
+
+/// Hex Argon2id of `passcode`, at the crate's default cost (19 MiB, two
+/// passes), so guessing against a copied settings file takes real work.
+fn hash_passcode(salt: &str, passcode: &str) -> AppResult<String> {
+    let mut hash = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passcode.as_bytes(), salt.as_bytes(), &mut hash)
+        .map_err(|error| AppError::Internal(format!("Failed to hash passcode: {}", error)))?;
+    Ok(hash.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// SHA-256 of `salt:passcode`, only read to verify hashes saved before
+/// Argon2.
+fn legacy_hash_passcode(salt: &str, passcode: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(b":");
+    hasher.update(passcode.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Per-passcode salt; it only needs to be unique, not secret.
+fn new_passcode_salt() -> String {
+    use sha2::{Digest, Sha256};
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    let mut hasher = Sha256::new();
+    hasher.update(nanos.to_le_bytes());
+    hasher.update(std::process::id().to_le_bytes());
+    let mut salt = format!("{:x}", hasher.finalize());
+    salt.truncate(32);
+    salt
+}
+
+/// Where the gallery was when the app closed, restored on the next launch.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ViewState {
+    pub query: String,
+    pub tags_include: Vec<String>,
+    pub tags_exclude: Vec<String>,
+    pub generation_types: Vec<String>,
+    pub model_filter: Option<String>,
+    pub model_family_filters: Vec<String>,
+    pub sort_by: Option<String>,
+    /// Cursor of the first visible page, as returned by the cursor queries.
+    pub cursor: Option<String>,
+    pub selected_collection: Option<String>,
+    pub selected_image_id: Option<i64>,
+}
+
 /// Shared application state for Tauri commands.
 pub struct AppState {
     pub db: Database,
     pub cache_dir: PathBuf,
+    pub inbox_dir: PathBuf,
+    /// Where corrupt images are moved by `quarantine_corrupt_images`.
+    pub quarantine_dir: PathBuf,
     pub thumbnail_index: Arc<RwLock<HashSet<String>>>,
     pub failed_thumbnail_sources: Arc<RwLock<HashSet<String>>>,
     pub thumbnail_precache_running: Arc<AtomicBool>,
+    /// Directories with a scan in progress, so two windows can't scan one twice.
+    pub active_scans: Arc<RwLock<HashSet<String>>>,
     pub storage_profile: Arc<RwLock<StorageProfile>>,
     pub storage_profile_path: PathBuf,
     pub forge_api_key: Arc<RwLock<String>>,
     pub forge_api_key_path: PathBuf,
-    pub forge_send_queue: Arc<Mutex<()>>,
+    pub forge_send_queue: Arc<forge_queue::ForgeQueue>,
+    pub forge_server_stats: Arc<forge_api::ForgeStatsCache>,
+    pub forge_presets: Arc<RwLock<Vec<ForgePreset>>>,
+    pub forge_presets_path: PathBuf,
+    pub forge_watcher_settings: Arc<RwLock<ForgeWatcherSettings>>,
+    pub forge_watcher_settings_path: PathBuf,
+    pub forge_watcher: Arc<watch_folders::FolderWatcher>,
+    pub watch_folder_settings: Arc<RwLock<WatchFolderSettings>>,
+    pub watch_folder_settings_path: PathBuf,
+    pub watched_folders: Arc<watch_folders::WatchedFolders>,
+    pub display_proxy_inflight: Arc<RwLock<HashSet<String>>>,
+    pub hooks: Arc<RwLock<Vec<HookConfig>>>,
+    pub hooks_path: PathBuf,
+    pub background_settings: Arc<RwLock<BackgroundSettings>>,
+    pub background_settings_path: PathBuf,
+    /// Set from the tray or UI; warmup workers idle while it is true.
+    pub background_paused: Arc<AtomicBool>,
+    pub tasks: Arc<TaskRegistry>,
+    pub performance_settings: Arc<RwLock<PerformanceSettings>>,
+    pub performance_settings_path: PathBuf,
+    /// Where the optional auto-tag ONNX model and labels are installed.
+    pub autotag_model_dir: PathBuf,
+    /// Where the optional OCR detector, recogniser and keys are installed.
+    pub ocr_model_dir: PathBuf,
+    pub nsfw_settings: Arc<RwLock<NsfwSettings>>,
+    pub nsfw_settings_path: PathBuf,
+    pub private_settings: Arc<RwLock<PrivateSettings>>,
+    pub private_settings_path: PathBuf,
+    /// Private directories are visible until the app restarts or relocks.
+    pub private_unlocked: Arc<AtomicBool>,
+    /// Delays passcode checks after repeated wrong guesses.
+    pub passcode_throttle: Arc<commands::PasscodeThrottle>,
+    pub volumes: Arc<volumes::VolumeTracker>,
+    pub view_state: Arc<RwLock<ViewState>>,
+    pub view_state_path: PathBuf,
+    pub project_settings: Arc<RwLock<ProjectSettings>>,
+    pub project_settings_path: PathBuf,
+    /// Query behind the gallery's last page, for `hint_scroll_position`.
+    pub gallery_listing: Arc<RwLock<Option<GalleryListing>>>,
+    pub scroll_warmup: Arc<scroll_warmup::ScrollWarmup>,
+    /// Tokens `delete_images` accepts for deletions flagged as risky.
+    pub delete_confirmations: Arc<commands::DeleteConfirmations>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,6 +451,7 @@ pub struct ExportResult {
 pub fn run() {
     env_logger::init();
     image_decode::ensure_jxl_decoder_registered();
+    perf::init();
 
     let cpu_count = std::thread::available_parallelism()
         .map(|count| count.get())
@@ -89,6 +472,16 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
+        .register_asynchronous_uri_scheme_protocol(
+            thumbnail_protocol::THUMBNAIL_SCHEME,
+            |context, request, responder| {
+                // The handler runs on the main thread; the file read must not.
+                let cache_dir = context.app_handle().state::<AppState>().cache_dir.clone();
+                tauri::async_runtime::spawn_blocking(move || {
+                    responder.respond(thumbnail_protocol::respond(&cache_dir, &request));
+                });
+            },
+        )
         .setup(|app| {
             let app_data = app
                 .path()
@@ -100,69 +493,297 @@ pub fn run() {
             let storage_profile = Arc::new(RwLock::new(storage_profile_value));
             let forge_api_key_path = app_data.join(FORGE_API_KEY_FILE);
             let forge_api_key = Arc::new(RwLock::new(load_forge_api_key(&forge_api_key_path)));
+            let forge_presets_path = app_data.join(FORGE_PRESETS_FILE);
+            let forge_presets = Arc::new(RwLock::new(load_forge_presets(&forge_presets_path)));
+            let forge_watcher_settings_path = app_data.join(FORGE_WATCHER_FILE);
+            let forge_watcher_settings = Arc::new(RwLock::new(load_forge_watcher_settings(
+                &forge_watcher_settings_path,
+            )));
+            let watch_folder_settings_path = app_data.join(WATCH_FOLDERS_FILE);
+            let watch_folder_settings = Arc::new(RwLock::new(load_watch_folder_settings(
+                &watch_folder_settings_path,
+            )));
+            let hooks_path = app_data.join(HOOKS_FILE);
+            let hooks = Arc::new(RwLock::new(load_hooks(&hooks_path)));
+            let background_settings_path = app_data.join(BACKGROUND_SETTINGS_FILE);
+            let background_settings = Arc::new(RwLock::new(load_background_settings(
+                &background_settings_path,
+            )));
+            let performance_settings_path = app_data.join(PERFORMANCE_SETTINGS_FILE);
+            let performance_settings_value = load_performance_settings(&performance_settings_path);
+            performance_settings_value.apply_overrides();
+            let performance_settings = Arc::new(RwLock::new(performance_settings_value));
+
+            let nsfw_settings_path = app_data.join(NSFW_SETTINGS_FILE);
+            let nsfw_settings_value = load_nsfw_settings(&nsfw_settings_path);
+            let nsfw_settings = Arc::new(RwLock::new(nsfw_settings_value));
+
+            let view_state_path = app_data.join(VIEW_STATE_FILE);
+            let view_state = Arc::new(RwLock::new(load_view_state_file(&view_state_path)));
+
+            let project_settings_path = app_data.join(PROJECTS_FILE);
+            let project_settings_value = load_project_settings(&project_settings_path);
+
+            let private_settings_path = app_data.join(PRIVATE_SETTINGS_FILE);
+            let private_settings_value = load_private_settings(&private_settings_path);
 
             let db_path = app_data.join("ForgeMetaLink.db");
             let cache_dir = app_data.join("thumbnails");
             std::fs::create_dir_all(&cache_dir).ok();
+            let inbox_dir = app_data.join(INBOX_DIR);
+            let quarantine_dir = app_data.join(QUARANTINE_DIR);
+            let autotag_model_dir = autotag::model_dir(&app_data);
+            let ocr_model_dir = ocr::model_dir(&app_data);
             let thumbnail_index = Arc::new(RwLock::new(build_thumbnail_index(&cache_dir)));
             let failed_thumbnail_sources = Arc::new(RwLock::new(HashSet::new()));
             let thumbnail_precache_running = Arc::new(AtomicBool::new(false));
-            let forge_send_queue = Arc::new(Mutex::new(()));
+            let forge_send_queue = Arc::new(forge_queue::ForgeQueue::default());
+            let display_proxy_inflight = Arc::new(RwLock::new(HashSet::new()));
+            let background_paused = Arc::new(AtomicBool::new(false));
+            let tasks = Arc::new(TaskRegistry::new(background_paused.clone()));
 
             // R2D2 pool created here
-            let db = Database::new(&db_path, storage_profile_value)
-                .expect("Failed to initialize database");
+            let db = Database::with_pool_size(
+                &db_path,
+                storage_profile_value,
+                performance_settings_value.db_pool_size,
+            )
+            .expect("Failed to initialize database");
+            db.set_nsfw_filter(nsfw_settings_value.filter_threshold());
+            db.set_private_directories(&private_settings_value.directories);
+            db.set_project_directories(&project_settings_value.active_directories());
+            // Corrupt sources are not retried until they change or are
+            // regenerated by hand.
+            if let (Ok(corrupt), Ok(mut failed)) =
+                (db.get_corrupt_images(), failed_thumbnail_sources.write())
+            {
+                failed.extend(corrupt.into_iter().map(|failure| failure.filepath));
+            }
             app.manage(AppState {
                 db,
                 cache_dir,
+                inbox_dir,
+                quarantine_dir,
                 thumbnail_index,
                 failed_thumbnail_sources,
                 thumbnail_precache_running,
+                active_scans: Arc::new(RwLock::new(HashSet::new())),
                 storage_profile,
                 storage_profile_path,
                 forge_api_key,
                 forge_api_key_path,
                 forge_send_queue,
+                forge_server_stats: Arc::new(forge_api::ForgeStatsCache::default()),
+                forge_presets,
+                forge_presets_path,
+                forge_watcher_settings,
+                forge_watcher_settings_path,
+                forge_watcher: Arc::new(watch_folders::FolderWatcher::default()),
+                watch_folder_settings,
+                watch_folder_settings_path,
+                watched_folders: Arc::new(watch_folders::WatchedFolders::default()),
+                display_proxy_inflight,
+                hooks,
+                hooks_path,
+                background_settings,
+                background_settings_path,
+                background_paused,
+                tasks,
+                performance_settings,
+                performance_settings_path,
+                autotag_model_dir,
+                ocr_model_dir,
+                nsfw_settings,
+                nsfw_settings_path,
+                private_settings: Arc::new(RwLock::new(private_settings_value)),
+                private_settings_path,
+                private_unlocked: Arc::new(AtomicBool::new(false)),
+                passcode_throttle: Arc::new(commands::PasscodeThrottle::default()),
+                volumes: Arc::new(volumes::VolumeTracker::new()),
+                view_state,
+                view_state_path,
+                project_settings: Arc::new(RwLock::new(project_settings_value)),
+                project_settings_path,
+                gallery_listing: Arc::new(RwLock::new(None)),
+                scroll_warmup: Arc::new(scroll_warmup::ScrollWarmup::default()),
+                delete_confirmations: Arc::new(commands::DeleteConfirmations::default()),
             });
+            commands::register_display_prefetch_listener(app.handle());
+            commands::start_forge_watcher_from_settings(app.handle());
+            commands::start_watch_folders_from_settings(app.handle());
+            // Some Linux desktops have no tray host; the app still works without it.
+            if let Err(error) = tray::setup(app) {
+                log::warn!("System tray unavailable: {}", error);
+            }
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                let close_to_tray = window
+                    .state::<AppState>()
+                    .background_settings
+                    .read()
+                    .map(|settings| settings.close_to_tray)
+                    .unwrap_or(false);
+                if close_to_tray {
+                    let _ = window.hide();
+                    api.prevent_close();
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             scan_directory,
+            import_from_clipboard,
             get_images_cursor,
             search_images_cursor,
             filter_images_cursor,
+            get_filter_counts,
             list_tags,
+            list_tag_suggestions,
+            import_styles,
+            list_prompt_styles,
+            apply_prompt_style,
+            bulk_edit_prompts,
+            bulk_update_fields,
+            undo_field_edit,
+            set_workflow_status,
+            set_workflow_status_by_filter,
+            get_workflow_status_counts,
+            get_field_edits,
+            preview_prompt_edit,
+            import_tag_dictionary,
+            clear_tag_dictionary,
+            get_autotag_status,
+            get_nsfw_settings,
+            set_nsfw_settings,
+            get_private_status,
+            set_private_passcode,
+            set_directory_private,
+            unlock_private,
+            lock_private,
+            get_keyword_trends,
+            get_model_usage,
+            get_disk_usage_report,
+            get_metadata_coverage,
+            archive_images,
+            unarchive_images,
+            get_volume_status,
+            save_view_state,
+            list_projects,
+            save_project,
+            delete_project,
+            switch_project,
+            load_view_state,
+            autotag_images,
+            get_ocr_status,
+            ocr_images,
             get_top_tags,
+            get_extra_param_keys,
             get_image_tags,
             get_image_detail,
+            get_images_by_ids_detail,
+            get_image_details,
+            get_grid_members,
+            get_batch_members,
+            suggest_best_of_batch,
+            get_seed_neighbors,
+            cluster_prompts,
+            get_prompt_clusters,
+            get_collections,
+            save_collection,
+            delete_collection,
+            search_by_color,
+            find_similar_images,
+            summarize_selection,
             get_total_count,
             get_display_image_path,
+            get_slideshow_batch,
             get_image_clipboard_payload,
+            copy_files_to_clipboard,
             get_thumbnail_path,
             get_thumbnail_paths,
+            get_thumbnail_tiers,
+            hint_scroll_position,
+            get_thumbnail_failures,
+            retry_failed_thumbnails,
+            get_corrupt_images,
+            regenerate_thumbnails,
             precache_all_thumbnails,
+            reparse_image,
             get_directories,
+            set_directory_pinned,
+            set_directory_hidden,
+            set_directory_alias,
             get_models,
+            get_lora_usage,
+            get_embedding_usage,
+            get_lora_images_cursor,
+            get_samplers,
+            get_source_tools,
+            get_schedulers,
+            get_resolution_buckets,
             directory_exists,
             open_file_location,
             delete_images,
+            preview_delete_images,
+            get_pending_operations,
+            resume_pending_operations,
+            find_duplicate_images,
+            detect_focal_points,
+            diff_images,
             move_images_to_directory,
+            quarantine_corrupt_images,
             set_image_favorite,
             set_image_locked,
             set_images_favorite,
             set_images_locked,
+            set_favorite_by_filter,
+            set_locked_by_filter,
             export_images,
             export_images_as_files,
+            estimate_export,
+            export_thumbnail_strip,
             forge_test_connection,
+            forge_get_server_stats,
             forge_get_options,
             forge_send_to_image,
             forge_send_to_images,
+            list_forge_presets,
+            save_forge_preset,
+            delete_forge_preset,
+            get_forge_watcher_status,
+            set_forge_watcher,
+            get_watch_folders,
+            add_watch_folder,
+            remove_watch_folder,
+            list_model_defaults,
+            set_model_defaults,
+            delete_model_defaults,
             get_forge_api_key,
             set_forge_api_key,
             get_sidecar_data,
             save_sidecar_tags,
+            set_image_rating,
+            set_user_property,
+            get_user_properties,
+            list_user_property_keys,
             get_storage_profile,
             set_storage_profile,
+            get_hooks,
+            set_hooks,
+            test_hook,
+            get_activity_log,
+            get_activity_files,
+            get_background_status,
+            set_close_to_tray,
+            set_background_paused,
+            list_background_tasks,
+            pause_background_task,
+            cancel_background_task,
+            clear_finished_background_tasks,
+            get_performance_report,
+            get_performance_settings,
+            set_performance_settings,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -200,49 +821,301 @@ fn load_forge_api_key(path: &Path) -> String {
         .unwrap_or_default()
 }
 
-pub(crate) fn persist_storage_profile(path: &Path, profile: StorageProfile) -> Result<(), String> {
+fn load_hooks(path: &Path) -> Vec<HookConfig> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+
+    #[derive(Deserialize)]
+    struct HooksConfig {
+        hooks: Vec<HookConfig>,
+    }
+
+    match serde_json::from_str::<HooksConfig>(&content) {
+        Ok(config) => config.hooks,
+        Err(error) => {
+            log::warn!("Ignoring invalid hooks file {}: {}", path.display(), error);
+            Vec::new()
+        }
+    }
+}
+
+fn load_forge_presets(path: &Path) -> Vec<ForgePreset> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    #[derive(Deserialize)]
+    struct ForgePresetsConfig {
+        presets: Vec<ForgePreset>,
+    }
+
+    match serde_json::from_str::<ForgePresetsConfig>(&content) {
+        Ok(config) => config.presets,
+        Err(error) => {
+            log::warn!(
+                "Ignoring invalid Forge presets {}: {}",
+                path.display(),
+                error
+            );
+            Vec::new()
+        }
+    }
+}
+
+fn load_forge_watcher_settings(path: &Path) -> ForgeWatcherSettings {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn load_watch_folder_settings(path: &Path) -> WatchFolderSettings {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn load_background_settings(path: &Path) -> BackgroundSettings {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn load_performance_settings(path: &Path) -> PerformanceSettings {
+    let settings: PerformanceSettings = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    if let Err(error) = settings.validate() {
+        log::warn!("Ignoring {}: {}", path.display(), error);
+        return PerformanceSettings::default();
+    }
+    settings
+}
+
+fn load_nsfw_settings(path: &Path) -> NsfwSettings {
+    let settings: NsfwSettings = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    if let Err(error) = settings.validate() {
+        log::warn!("Ignoring {}: {}", path.display(), error);
+        return NsfwSettings::default();
+    }
+    settings
+}
+
+pub(crate) fn persist_storage_profile(path: &Path, profile: StorageProfile) -> AppResult<()> {
     #[derive(Serialize)]
     struct StorageProfileConfig {
         profile: StorageProfile,
     }
 
     let payload = serde_json::to_string_pretty(&StorageProfileConfig { profile })
-        .map_err(|error| format!("Failed to serialize storage profile: {}", error))?;
+        .map_err(|error| AppError::from(error).context("Failed to serialize storage profile"))?;
 
     std::fs::write(path, payload).map_err(|error| {
-        format!(
-            "Failed to save storage profile to {}: {}",
-            path.display(),
-            error
-        )
+        AppError::from(error).context(format!(
+            "Failed to save storage profile to {}",
+            path.display()
+        ))
     })
 }
 
-pub(crate) fn persist_forge_api_key(path: &Path, api_key: &str) -> Result<(), String> {
+pub(crate) fn persist_forge_api_key(path: &Path, api_key: &str) -> AppResult<()> {
     #[derive(Serialize)]
     struct ForgeApiKeyConfig<'a> {
         api_key: &'a str,
     }
 
     let payload = serde_json::to_string_pretty(&ForgeApiKeyConfig { api_key })
-        .map_err(|error| format!("Failed to serialize Forge API key: {}", error))?;
+        .map_err(|error| AppError::from(error).context("Failed to serialize Forge API key"))?;
 
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|error| {
-            format!(
-                "Failed to create Forge API key directory {}: {}",
-                parent.display(),
-                error
-            )
+            AppError::from(error).context(format!(
+                "Failed to create Forge API key directory {}",
+                parent.display()
+            ))
         })?;
     }
 
     std::fs::write(path, payload).map_err(|error| {
-        format!(
-            "Failed to save Forge API key to {}: {}",
-            path.display(),
-            error
-        )
+        AppError::from(error).context(format!(
+            "Failed to save Forge API key to {}",
+            path.display()
+        ))
+    })
+}
+
+pub(crate) fn persist_hooks(path: &Path, hooks: &[HookConfig]) -> AppResult<()> {
+    #[derive(Serialize)]
+    struct HooksConfig<'a> {
+        hooks: &'a [HookConfig],
+    }
+
+    let payload = serde_json::to_string_pretty(&HooksConfig { hooks })
+        .map_err(|error| AppError::from(error).context("Failed to serialize hooks"))?;
+
+    std::fs::write(path, payload).map_err(|error| {
+        AppError::from(error).context(format!("Failed to save hooks to {}", path.display()))
+    })
+}
+
+pub(crate) fn persist_forge_presets(path: &Path, presets: &[ForgePreset]) -> AppResult<()> {
+    #[derive(Serialize)]
+    struct ForgePresetsConfig<'a> {
+        presets: &'a [ForgePreset],
+    }
+
+    let payload = serde_json::to_string_pretty(&ForgePresetsConfig { presets })
+        .map_err(|error| AppError::from(error).context("Failed to serialize Forge presets"))?;
+
+    std::fs::write(path, payload).map_err(|error| {
+        AppError::from(error).context(format!(
+            "Failed to save Forge presets to {}",
+            path.display()
+        ))
+    })
+}
+
+pub(crate) fn persist_forge_watcher_settings(
+    path: &Path,
+    settings: &ForgeWatcherSettings,
+) -> AppResult<()> {
+    let payload = serde_json::to_string_pretty(settings).map_err(|error| {
+        AppError::from(error).context("Failed to serialize Forge watcher settings")
+    })?;
+
+    std::fs::write(path, payload).map_err(|error| {
+        AppError::from(error).context(format!(
+            "Failed to save Forge watcher settings to {}",
+            path.display()
+        ))
+    })
+}
+
+pub(crate) fn persist_watch_folder_settings(
+    path: &Path,
+    settings: &WatchFolderSettings,
+) -> AppResult<()> {
+    let payload = serde_json::to_string_pretty(settings)
+        .map_err(|error| AppError::from(error).context("Failed to serialize watched folders"))?;
+
+    std::fs::write(path, payload).map_err(|error| {
+        AppError::from(error).context(format!(
+            "Failed to save watched folders to {}",
+            path.display()
+        ))
+    })
+}
+
+pub(crate) fn persist_background_settings(
+    path: &Path,
+    settings: &BackgroundSettings,
+) -> AppResult<()> {
+    let payload = serde_json::to_string_pretty(settings).map_err(|error| {
+        AppError::from(error).context("Failed to serialize background settings")
+    })?;
+
+    std::fs::write(path, payload).map_err(|error| {
+        AppError::from(error).context(format!(
+            "Failed to save background settings to {}",
+            path.display()
+        ))
+    })
+}
+
+pub(crate) fn persist_performance_settings(
+    path: &Path,
+    settings: &PerformanceSettings,
+) -> AppResult<()> {
+    let payload = serde_json::to_string_pretty(settings).map_err(|error| {
+        AppError::from(error).context("Failed to serialize performance settings")
+    })?;
+
+    std::fs::write(path, payload).map_err(|error| {
+        AppError::from(error).context(format!(
+            "Failed to save performance settings to {}",
+            path.display()
+        ))
+    })
+}
+
+pub(crate) fn persist_nsfw_settings(path: &Path, settings: &NsfwSettings) -> AppResult<()> {
+    let payload = serde_json::to_string_pretty(settings)
+        .map_err(|error| AppError::from(error).context("Failed to serialize NSFW settings"))?;
+
+    std::fs::write(path, payload).map_err(|error| {
+        AppError::from(error).context(format!(
+            "Failed to save NSFW settings to {}",
+            path.display()
+        ))
+    })
+}
+
+fn load_private_settings(path: &Path) -> PrivateSettings {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return PrivateSettings::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|error| {
+        // Falling back would silently reveal private folders, so say so loudly.
+        log::error!("Ignoring invalid {}: {}", path.display(), error);
+        PrivateSettings::default()
+    })
+}
+
+pub(crate) fn persist_private_settings(path: &Path, settings: &PrivateSettings) -> AppResult<()> {
+    let payload = serde_json::to_string_pretty(settings)
+        .map_err(|error| AppError::from(error).context("Failed to serialize private settings"))?;
+
+    std::fs::write(path, payload).map_err(|error| {
+        AppError::from(error).context(format!(
+            "Failed to save private settings to {}",
+            path.display()
+        ))
+    })
+}
+
+fn load_view_state_file(path: &Path) -> ViewState {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return ViewState::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|error| {
+        log::warn!("Ignoring invalid {}: {}", path.display(), error);
+        ViewState::default()
+    })
+}
+
+pub(crate) fn persist_view_state(path: &Path, view_state: &ViewState) -> AppResult<()> {
+    let payload = serde_json::to_string_pretty(view_state)
+        .map_err(|error| AppError::from(error).context("Failed to serialize view state"))?;
+
+    std::fs::write(path, payload).map_err(|error| {
+        AppError::from(error).context(format!("Failed to save view state to {}", path.display()))
+    })
+}
+
+fn load_project_settings(path: &Path) -> ProjectSettings {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return ProjectSettings::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|error| {
+        log::warn!("Ignoring invalid {}: {}", path.display(), error);
+        ProjectSettings::default()
+    })
+}
+
+pub(crate) fn persist_project_settings(path: &Path, settings: &ProjectSettings) -> AppResult<()> {
+    let payload = serde_json::to_string_pretty(settings)
+        .map_err(|error| AppError::from(error).context("Failed to serialize projects"))?;
+
+    std::fs::write(path, payload).map_err(|error| {
+        AppError::from(error).context(format!("Failed to save projects to {}", path.display()))
     })
 }
 
@@ -283,7 +1156,11 @@ fn build_thumbnail_index(cache_dir: &std::path::Path) -> HashSet<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{load_forge_api_key, persist_forge_api_key};
+    use super::{
+        legacy_hash_passcode, load_forge_api_key, load_performance_settings, load_view_state_file,
+        persist_forge_api_key, persist_performance_settings, persist_view_state,
+        BackgroundSettings, PerformanceSettings, PrivateSettings, ViewState,
+    };
     use std::path::PathBuf;
     use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -298,6 +1175,27 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn view_state_round_trips_and_fills_missing_fields() {
+        let path = temp_config_path();
+        let view_state = ViewState {
+            query: "castle".to_string(),
+            tags_include: vec!["sunset".to_string()],
+            sort_by: Some("oldest".to_string()),
+            cursor: Some("1700000000:42".to_string()),
+            selected_image_id: Some(42),
+            ..ViewState::default()
+        };
+        persist_view_state(&path, &view_state).expect("persist should succeed");
+        assert_eq!(load_view_state_file(&path), view_state);
+
+        std::fs::write(&path, r#"{"sort_by":"newest"}"#).unwrap();
+        let partial = load_view_state_file(&path);
+        assert_eq!(partial.sort_by.as_deref(), Some("newest"));
+        assert!(partial.tags_include.is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+
     #[test]
     fn forge_api_key_round_trip_persists_and_loads() {
         let path = temp_config_path();
@@ -317,4 +1215,79 @@ mod tests {
         let loaded = load_forge_api_key(&path);
         assert_eq!(loaded, "");
     }
+
+    #[test]
+    fn add_scan_root_folds_nested_directories() {
+        let mut settings = BackgroundSettings::default();
+        assert!(settings.add_scan_root("/library/outputs/txt2img"));
+        assert!(settings.add_scan_root("/library/outputs"));
+        assert!(!settings.add_scan_root("/library/outputs/img2img"));
+        assert!(settings.add_scan_root("/library/other"));
+        assert_eq!(
+            settings.scan_roots,
+            vec!["/library/outputs".to_string(), "/library/other".to_string()]
+        );
+    }
+
+    #[test]
+    fn private_passcode_is_salted_and_verified() {
+        let mut settings = PrivateSettings::default();
+        assert!(!settings.verify_passcode(""));
+        assert!(settings.set_passcode("123").is_err());
+        settings.set_passcode("hunter22").unwrap();
+        assert!(settings.verify_passcode("hunter22"));
+        assert!(!settings.verify_passcode("hunter23"));
+
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(!json.contains("hunter22"));
+        let loaded: PrivateSettings = serde_json::from_str(&json).unwrap();
+        assert!(loaded.verify_passcode("hunter22"));
+        assert!(!loaded.passcode_needs_rehash());
+    }
+
+    #[test]
+    fn legacy_sha256_passcodes_still_verify_until_rehashed() {
+        let salt = "0123456789abcdef";
+        let legacy: PrivateSettings = serde_json::from_value(serde_json::json!({
+            "passcode_hash": legacy_hash_passcode(salt, "hunter22"),
+            "passcode_salt": salt,
+        }))
+        .unwrap();
+        assert!(legacy.verify_passcode("hunter22"));
+        assert!(!legacy.verify_passcode("hunter23"));
+        assert!(legacy.passcode_needs_rehash());
+    }
+
+    #[test]
+    fn performance_settings_round_trip_and_reject_out_of_range() {
+        let path = temp_config_path().with_extension("performance.json");
+        let settings = PerformanceSettings {
+            scan_threads: Some(6),
+            thumbnail_threads: None,
+            db_pool_size: Some(8),
+            preview_budget_megapixels: Some(128),
+            decode_memory_budget_mb: Some(4096),
+        };
+        persist_performance_settings(&path, &settings).expect("persist should succeed");
+        assert_eq!(load_performance_settings(&path), settings);
+        assert!(PerformanceSettings {
+            preview_budget_megapixels: Some(0),
+            ..settings
+        }
+        .validate()
+        .is_err());
+        assert!(PerformanceSettings {
+            decode_memory_budget_mb: Some(64),
+            ..settings
+        }
+        .validate()
+        .is_err());
+
+        std::fs::write(&path, r#"{"scan_threads": 0}"#).expect("write should succeed");
+        assert_eq!(
+            load_performance_settings(&path),
+            PerformanceSettings::default()
+        );
+        let _ = std::fs::remove_file(path);
+    }
 }
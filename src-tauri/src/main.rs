@@ -2,5 +2,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if forge_meta_link_lib::cli::is_cli_invocation(&args) {
+        forge_meta_link_lib::cli::attach_parent_console();
+        std::process::exit(forge_meta_link_lib::cli::run(&args));
+    }
     forge_meta_link_lib::run();
 }
@@ -0,0 +1,68 @@
+//! Prompt-keyword NSFW scoring.
+//!
+//! Every indexed image gets an `nsfw_score` in `0.0..=1.0` from its positive
+//! prompt (negative prompts routinely list `nsfw` to avoid it, so they are
+//! ignored). Auto-tagging can raise the score with the tagger's rating output.
+//! The "hide NSFW" setting filters on this score.
+
+/// Prompt terms and how strongly each implies NSFW content.
+const KEYWORDS: &[(&str, f32)] = &[
+    ("nsfw", 1.0),
+    ("explicit", 0.9),
+    ("rating:explicit", 1.0),
+    ("rating_explicit", 1.0),
+    ("rating:questionable", 0.6),
+    ("rating_questionable", 0.6),
+    ("sex", 1.0),
+    ("nude", 0.9),
+    ("naked", 0.9),
+    ("nudity", 0.9),
+    ("pussy", 1.0),
+    ("penis", 1.0),
+    ("nipples", 0.8),
+    ("topless", 0.8),
+    ("bottomless", 0.8),
+    ("hentai", 1.0),
+    ("porn", 1.0),
+    ("cum", 0.9),
+    ("lingerie", 0.5),
+    ("suggestive", 0.5),
+    ("underwear", 0.4),
+    ("cleavage", 0.3),
+    ("bikini", 0.3),
+];
+
+/// Scores a positive prompt; the strongest matching keyword wins.
+pub fn prompt_score(prompt: &str) -> f32 {
+    let lowered = prompt.to_lowercase();
+    lowered
+        .split(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == ':'))
+        .filter(|word| !word.is_empty())
+        .filter_map(|word| {
+            // `(nude:1.2)` leaves `nude:1.2` after splitting; drop the weight.
+            let word = match word.rsplit_once(':') {
+                Some((name, weight)) if weight.parse::<f32>().is_ok() => name,
+                _ => word,
+            };
+            KEYWORDS
+                .iter()
+                .find(|(keyword, _)| *keyword == word)
+                .map(|(_, weight)| *weight)
+        })
+        .fold(0.0, f32::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_keywords_on_word_boundaries() {
+        assert_eq!(prompt_score("1girl, castle, sunset"), 0.0);
+        assert_eq!(prompt_score("score_9, rating_explicit, 1girl"), 1.0);
+        assert_eq!(prompt_score("beach, (bikini:1.2), smile"), 0.3);
+        assert_eq!(prompt_score("Nude, bikini"), 0.9);
+        // Substrings such as "sextant" or "essex" must not match.
+        assert_eq!(prompt_score("sextant on a desk in essex"), 0.0);
+    }
+}
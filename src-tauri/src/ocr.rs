@@ -0,0 +1,478 @@
+//! Optional text-in-image recognition with local PaddleOCR-style models.
+//!
+//! The models live in `<app data>/models/ocr/`: `det.onnx` finds text
+//! regions, `rec.onnx` reads each region and `keys.txt` lists the characters
+//! the recogniser emits, one per line. Inference needs the `ocr` cargo
+//! feature (ONNX Runtime); without it the commands report that OCR is
+//! unavailable. Recognised lines go to `images.ocr_text`, which the search
+//! indexes cover alongside the prompt.
+
+use crate::error::{AppError, AppResult};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub const DETECTOR_FILE: &str = "det.onnx";
+pub const RECOGNIZER_FILE: &str = "rec.onnx";
+pub const KEYS_FILE: &str = "keys.txt";
+/// Longest text stored per image, in characters.
+pub const MAX_OCR_TEXT_LEN: usize = 4000;
+
+/// Detector probability at which a map pixel counts as text.
+const TEXT_THRESHOLD: f32 = 0.3;
+/// Smaller regions are specks, not glyphs.
+const MIN_REGION_PIXELS: usize = 16;
+/// DB detectors shrink regions to their core; grow them back by this share
+/// of their height on every side.
+const REGION_PADDING: f32 = 0.4;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OcrStatus {
+    /// Whether this build includes the ONNX runtime.
+    pub available: bool,
+    pub model_dir: String,
+    /// Whether the detector, recogniser and key files are all present.
+    pub model_present: bool,
+}
+
+pub fn status(model_dir: &Path) -> OcrStatus {
+    OcrStatus {
+        available: cfg!(feature = "ocr"),
+        model_dir: model_dir.to_string_lossy().to_string(),
+        model_present: [DETECTOR_FILE, RECOGNIZER_FILE, KEYS_FILE]
+            .iter()
+            .all(|file| model_dir.join(file).is_file()),
+    }
+}
+
+/// Parses `keys.txt`. Class 0 of the recogniser is the CTC blank and the
+/// last one a space, so key `n` is class `n + 1`.
+pub fn parse_keys(bytes: &[u8]) -> AppResult<Vec<String>> {
+    let mut keys: Vec<String> = String::from_utf8_lossy(bytes)
+        .lines()
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    if keys.is_empty() {
+        return Err(AppError::InvalidInput(
+            "OCR key file has no keys".to_string(),
+        ));
+    }
+    keys.push(" ".to_string());
+    Ok(keys)
+}
+
+/// A text region in detector map pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextRegion {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl TextRegion {
+    fn center_y(&self) -> usize {
+        self.y + self.height / 2
+    }
+}
+
+/// Boxes around the connected text pixels of a `width` x `height`
+/// probability map, padded and in reading order: lines top to bottom, each
+/// left to right.
+pub fn text_regions(probabilities: &[f32], width: usize, height: usize) -> Vec<TextRegion> {
+    if probabilities.len() < width * height {
+        return Vec::new();
+    }
+    let mut visited = vec![false; width * height];
+    let mut regions = Vec::new();
+    let mut stack = Vec::new();
+    for start in 0..width * height {
+        if visited[start] || probabilities[start] < TEXT_THRESHOLD {
+            continue;
+        }
+        visited[start] = true;
+        stack.push(start);
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (width, height, 0, 0);
+        let mut pixels = 0usize;
+        while let Some(index) = stack.pop() {
+            let (x, y) = (index % width, index / width);
+            min_x = min_x.min(x);
+            max_x = max_x.max(x);
+            min_y = min_y.min(y);
+            max_y = max_y.max(y);
+            pixels += 1;
+
+            let mut visit = |neighbour: usize| {
+                if !visited[neighbour] && probabilities[neighbour] >= TEXT_THRESHOLD {
+                    visited[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            };
+            if x > 0 {
+                visit(index - 1);
+            }
+            if x + 1 < width {
+                visit(index + 1);
+            }
+            if y > 0 {
+                visit(index - width);
+            }
+            if y + 1 < height {
+                visit(index + width);
+            }
+        }
+        if pixels < MIN_REGION_PIXELS {
+            continue;
+        }
+
+        let padding = ((max_y - min_y + 1) as f32 * REGION_PADDING).ceil() as usize;
+        let (x, y) = (min_x.saturating_sub(padding), min_y.saturating_sub(padding));
+        regions.push(TextRegion {
+            x,
+            y,
+            width: (max_x + padding + 1).min(width) - x,
+            height: (max_y + padding + 1).min(height) - y,
+        });
+    }
+
+    regions.sort_by_key(TextRegion::center_y);
+    let mut ordered = Vec::with_capacity(regions.len());
+    let mut line: Vec<TextRegion> = Vec::new();
+    for region in regions {
+        if line
+            .first()
+            .is_some_and(|first| region.center_y() >= first.y + first.height)
+        {
+            line.sort_by_key(|region| region.x);
+            ordered.append(&mut line);
+        }
+        line.push(region);
+    }
+    line.sort_by_key(|region| region.x);
+    ordered.append(&mut line);
+    ordered
+}
+
+/// Greedy CTC decoding of `[steps, classes]` recogniser scores. Returns the
+/// text and the mean score of its characters.
+pub fn ctc_decode(scores: &[f32], classes: usize, keys: &[String]) -> (String, f32) {
+    let mut text = String::new();
+    let mut confidence = 0.0f32;
+    let mut characters = 0usize;
+    let mut previous = 0usize;
+    for step in scores.chunks_exact(classes.max(1)) {
+        let (class, score) = step
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0));
+        if class != 0 && class != previous {
+            if let Some(key) = keys.get(class - 1) {
+                text.push_str(key);
+                confidence += score;
+                characters += 1;
+            }
+        }
+        previous = class;
+    }
+    let confidence = if characters == 0 {
+        0.0
+    } else {
+        confidence / characters as f32
+    };
+    (text, confidence)
+}
+
+/// Joins recognised lines for storage: runs of whitespace collapse, empty
+/// lines go and the result is capped at [`MAX_OCR_TEXT_LEN`] characters.
+pub fn join_lines<I: IntoIterator<Item = String>>(lines: I) -> String {
+    let joined = lines
+        .into_iter()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    match joined.char_indices().nth(MAX_OCR_TEXT_LEN) {
+        Some((end, _)) => joined[..end].to_string(),
+        None => joined,
+    }
+}
+
+pub struct OcrEngine {
+    #[cfg(feature = "ocr")]
+    keys: Vec<String>,
+    #[cfg(feature = "ocr")]
+    detector: ort::session::Session,
+    #[cfg(feature = "ocr")]
+    recognizer: ort::session::Session,
+    #[cfg(feature = "ocr")]
+    recognizer_height: u32,
+}
+
+#[cfg(not(feature = "ocr"))]
+impl OcrEngine {
+    pub fn load(_model_dir: &Path) -> AppResult<Self> {
+        Err(AppError::InvalidInput(
+            "Text recognition is not available in this build (enable the `ocr` feature)"
+                .to_string(),
+        ))
+    }
+
+    pub fn read_text(&mut self, _path: &Path) -> AppResult<String> {
+        Err(AppError::Internal(
+            "Text recognition is not available".to_string(),
+        ))
+    }
+}
+
+/// Long side the detector sees; larger images are scaled down.
+#[cfg(feature = "ocr")]
+const DETECTOR_MAX_SIDE: u32 = 960;
+/// Widest line crop the recogniser is given.
+#[cfg(feature = "ocr")]
+const RECOGNIZER_MAX_WIDTH: u32 = 480;
+/// Regions read per image; the rest are skipped.
+#[cfg(feature = "ocr")]
+const MAX_REGIONS: usize = 64;
+/// Lines read with less confidence than this are dropped as noise.
+#[cfg(feature = "ocr")]
+const MIN_LINE_CONFIDENCE: f32 = 0.5;
+
+#[cfg(feature = "ocr")]
+impl OcrEngine {
+    /// Loads both ONNX sessions and the keys from `model_dir`.
+    pub fn load(model_dir: &Path) -> AppResult<Self> {
+        for file in [DETECTOR_FILE, RECOGNIZER_FILE, KEYS_FILE] {
+            let path = model_dir.join(file);
+            if !path.is_file() {
+                return Err(AppError::file_missing(&path).context("OCR model is not installed"));
+            }
+        }
+        let keys = parse_keys(&std::fs::read(model_dir.join(KEYS_FILE))?)?;
+        let detector = load_session(&model_dir.join(DETECTOR_FILE))?;
+        let recognizer = load_session(&model_dir.join(RECOGNIZER_FILE))?;
+        // PaddleOCR recognisers take NCHW input, e.g. [-1, 3, 48, -1].
+        let recognizer_height = recognizer
+            .inputs
+            .first()
+            .and_then(|input| input.input_type.tensor_dimensions())
+            .and_then(|dims| dims.get(2).copied())
+            .filter(|height| *height > 0)
+            .unwrap_or(48) as u32;
+
+        Ok(Self {
+            keys,
+            detector,
+            recognizer,
+            recognizer_height,
+        })
+    }
+
+    /// Reads the visible text of the image at `path`, one line per region.
+    pub fn read_text(&mut self, path: &Path) -> AppResult<String> {
+        let image = crate::image_decode::open_image(path)?;
+        let (input, map_width, map_height) = detector_input(&image);
+        let probabilities = run_session(
+            &mut self.detector,
+            [1, 3, map_height as usize, map_width as usize],
+            input,
+        )?;
+        let regions = text_regions(&probabilities.1, map_width as usize, map_height as usize);
+
+        let scale_x = image.width() as f32 / map_width as f32;
+        let scale_y = image.height() as f32 / map_height as f32;
+        let mut lines = Vec::new();
+        for region in regions.into_iter().take(MAX_REGIONS) {
+            let x = (region.x as f32 * scale_x) as u32;
+            let y = (region.y as f32 * scale_y) as u32;
+            let width = ((region.width as f32 * scale_x).ceil() as u32)
+                .clamp(1, image.width().saturating_sub(x).max(1));
+            let height = ((region.height as f32 * scale_y).ceil() as u32)
+                .clamp(1, image.height().saturating_sub(y).max(1));
+            let crop = image.crop_imm(x, y, width, height);
+
+            let (input, input_width) = recognizer_input(&crop, self.recognizer_height);
+            let (shape, scores) = run_session(
+                &mut self.recognizer,
+                [1, 3, self.recognizer_height as usize, input_width],
+                input,
+            )?;
+            let classes = shape.last().copied().unwrap_or(0).max(0) as usize;
+            let (text, confidence) = ctc_decode(&scores, classes, &self.keys);
+            if confidence >= MIN_LINE_CONFIDENCE {
+                lines.push(text);
+            }
+        }
+        Ok(join_lines(lines))
+    }
+}
+
+#[cfg(feature = "ocr")]
+fn load_session(path: &Path) -> AppResult<ort::session::Session> {
+    use ort::session::{builder::GraphOptimizationLevel, Session};
+
+    let ort_error =
+        |error: ort::Error| AppError::Internal(format!("Failed to load OCR model: {}", error));
+    let threads = std::thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(4)
+        .min(8);
+    Session::builder()
+        .map_err(ort_error)?
+        .with_optimization_level(GraphOptimizationLevel::Level3)
+        .map_err(ort_error)?
+        .with_intra_threads(threads)
+        .map_err(ort_error)?
+        .commit_from_file(path)
+        .map_err(ort_error)
+}
+
+/// Runs a single-input session and copies out its first output.
+#[cfg(feature = "ocr")]
+fn run_session(
+    session: &mut ort::session::Session,
+    shape: [usize; 4],
+    input: Vec<f32>,
+) -> AppResult<(Vec<i64>, Vec<f32>)> {
+    let ort_error =
+        |error: ort::Error| AppError::Internal(format!("OCR inference failed: {}", error));
+    let input_name = session
+        .inputs
+        .first()
+        .map(|input| input.name.clone())
+        .ok_or_else(|| AppError::InvalidInput("OCR model has no inputs".to_string()))?;
+    let tensor = ort::value::Tensor::from_array((shape, input)).map_err(ort_error)?;
+    let outputs = session
+        .run(ort::inputs![input_name.as_str() => tensor].map_err(ort_error)?)
+        .map_err(ort_error)?;
+    let (shape, values) = outputs[0]
+        .try_extract_raw_tensor::<f32>()
+        .map_err(ort_error)?;
+    Ok((shape.to_vec(), values.to_vec()))
+}
+
+/// Scales so the long side fits [`DETECTOR_MAX_SIDE`] and both sides are
+/// multiples of 32, then normalises with the ImageNet mean and deviation
+/// into NCHW floats.
+#[cfg(feature = "ocr")]
+fn detector_input(image: &image::DynamicImage) -> (Vec<f32>, u32, u32) {
+    const MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+    const STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+    let scale = (DETECTOR_MAX_SIDE as f32 / image.width().max(image.height()) as f32).min(1.0);
+    let snap = |side: u32| (((side as f32 * scale) / 32.0).round() as u32).max(1) * 32;
+    let (width, height) = (snap(image.width()), snap(image.height()));
+    let resized = image
+        .resize_exact(width, height, image::imageops::FilterType::Triangle)
+        .to_rgb8();
+
+    let plane = (width * height) as usize;
+    let mut data = vec![0.0f32; plane * 3];
+    for (index, pixel) in resized.pixels().enumerate() {
+        for channel in 0..3 {
+            data[channel * plane + index] =
+                (pixel[channel] as f32 / 255.0 - MEAN[channel]) / STD[channel];
+        }
+    }
+    (data, width, height)
+}
+
+/// Scales a line crop to the recogniser's height, keeping its aspect up to
+/// [`RECOGNIZER_MAX_WIDTH`], into NCHW floats in -1..1.
+#[cfg(feature = "ocr")]
+fn recognizer_input(crop: &image::DynamicImage, height: u32) -> (Vec<f32>, usize) {
+    let width = ((crop.width() as f32 * height as f32 / crop.height().max(1) as f32).ceil() as u32)
+        .clamp(height / 2, RECOGNIZER_MAX_WIDTH);
+    let resized = crop
+        .resize_exact(width, height, image::imageops::FilterType::Triangle)
+        .to_rgb8();
+
+    let plane = (width * height) as usize;
+    let mut data = vec![0.0f32; plane * 3];
+    for (index, pixel) in resized.pixels().enumerate() {
+        for channel in 0..3 {
+            data[channel * plane + index] = pixel[channel] as f32 / 127.5 - 1.0;
+        }
+    }
+    (data, width as usize)
+}
+
+/// Default model location under the app data directory.
+pub fn model_dir(app_data: &Path) -> PathBuf {
+    app_data.join("models").join("ocr")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_regions_in_reading_order() {
+        let (width, height) = (40, 20);
+        let mut map = vec![0.0f32; width * height];
+        let mut fill = |x0: usize, y0: usize, w: usize, h: usize| {
+            for y in y0..y0 + h {
+                for x in x0..x0 + w {
+                    map[y * width + x] = 0.9;
+                }
+            }
+        };
+        // Second word of the first line sits slightly higher than the first.
+        fill(22, 2, 10, 3);
+        fill(4, 3, 10, 3);
+        fill(4, 13, 20, 3);
+        fill(36, 18, 1, 1);
+
+        let regions = text_regions(&map, width, height);
+        assert_eq!(regions.len(), 3);
+        assert_eq!(
+            regions[0],
+            TextRegion {
+                x: 2,
+                y: 1,
+                width: 14,
+                height: 7
+            }
+        );
+        assert_eq!(regions[1].x, 20);
+        assert_eq!(regions[2].y, 11);
+    }
+
+    #[test]
+    fn decodes_ctc_and_joins_lines() {
+        let keys = parse_keys(b"a\r\nb\nc\n").unwrap();
+        assert_eq!(keys, ["a", "b", "c", " "]);
+
+        // Classes: blank, a, b, c, space.
+        let step = |class: usize| {
+            let mut scores = [0.0f32; 5];
+            scores[class] = 0.9;
+            scores
+        };
+        let scores: Vec<f32> = [1, 1, 0, 1, 2, 4, 3, 3]
+            .into_iter()
+            .flat_map(step)
+            .collect();
+        let (text, confidence) = ctc_decode(&scores, 5, &keys);
+        assert_eq!(text, "aab c");
+        assert!((confidence - 0.9).abs() < 1e-6);
+        assert_eq!(ctc_decode(&step(0), 5, &keys), (String::new(), 0.0));
+
+        assert_eq!(
+            join_lines([
+                "  SALE \t 50% ".to_string(),
+                " ".to_string(),
+                "off".to_string()
+            ]),
+            "SALE 50%\noff"
+        );
+        assert_eq!(
+            join_lines(["x".repeat(MAX_OCR_TEXT_LEN + 10)])
+                .chars()
+                .count(),
+            MAX_OCR_TEXT_LEN
+        );
+        assert!(parse_keys(b"\n\n").is_err());
+    }
+}
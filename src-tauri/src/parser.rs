@@ -17,6 +17,10 @@ pub struct GenerationParams {
     pub model_hash: Option<String>,
     pub model_name: Option<String>,
     pub generation_type: Option<String>,
+    /// The generator, when the file named it outside the metadata text;
+    /// otherwise it is inferred from `raw_metadata`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_tool: Option<String>,
     /// All remaining key-value parameters not explicitly mapped
     pub extra_params: HashMap<String, String>,
     /// The raw, unparsed metadata string (as backup)
@@ -92,16 +96,92 @@ fn normalize_key(key: &str) -> &str {
     }
 }
 
+/// Where one parsed field was read from in the raw metadata, so a wrongly
+/// parsed value can be traced back to its source.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldSource {
+    /// `GenerationParams` field name; unmapped A1111 keys are reported as
+    /// `extra_params`.
+    pub field: &'static str,
+    #[serde(flatten)]
+    pub origin: SourceOrigin,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SourceOrigin {
+    /// A span of A1111/Forge text, as byte offsets into `raw_metadata`.
+    /// `key` is the parameter name, or `None` for the prompt sections.
+    Text {
+        key: Option<String>,
+        start: usize,
+        end: usize,
+    },
+    /// A top-level key of a JSON metadata object.
+    JsonPath { path: String },
+    /// An input of a ComfyUI prompt graph node.
+    ComfyNode {
+        node_id: String,
+        class_type: String,
+        input: String,
+    },
+    /// Not read from one place: guessed from the metadata as a whole.
+    Inferred { detail: &'static str },
+}
+
+/// Collects field sources when enabled; indexing parses without them.
+struct SourceLog(Option<Vec<FieldSource>>);
+
+impl SourceLog {
+    fn disabled() -> Self {
+        SourceLog(None)
+    }
+
+    fn enabled() -> Self {
+        SourceLog(Some(Vec::new()))
+    }
+
+    fn record(&mut self, field: &'static str, origin: impl FnOnce() -> SourceOrigin) {
+        if let Some(sources) = &mut self.0 {
+            sources.push(FieldSource {
+                field,
+                origin: origin(),
+            });
+        }
+    }
+
+    fn append(&mut self, other: SourceLog) {
+        if let (Some(sources), Some(more)) = (&mut self.0, other.0) {
+            sources.extend(more);
+        }
+    }
+}
+
 /// Parses generation metadata from both A1111/Forge-style text and JSON graph formats
 /// (e.g. ComfyUI prompt metadata).
 pub fn parse_generation_metadata(raw: &str) -> GenerationParams {
-    let mut params = if let Some(params) = parse_json_metadata(raw) {
+    parse_with_sources(raw, &mut SourceLog::disabled())
+}
+
+/// Like `parse_generation_metadata`, also reporting where each field was
+/// read from.
+pub fn parse_generation_metadata_with_sources(raw: &str) -> (GenerationParams, Vec<FieldSource>) {
+    let mut log = SourceLog::enabled();
+    let params = parse_with_sources(raw, &mut log);
+    (params, log.0.unwrap_or_default())
+}
+
+fn parse_with_sources(raw: &str, log: &mut SourceLog) -> GenerationParams {
+    let mut params = if let Some(params) = parse_json_metadata(raw, log) {
         params
     } else {
-        parse_a1111_metadata(raw)
+        parse_a1111_text(raw, log)
     };
 
     params.generation_type = Some(infer_generation_type(raw));
+    log.record("generation_type", || SourceOrigin::Inferred {
+        detail: "keywords anywhere in the metadata",
+    });
     params
 }
 
@@ -160,58 +240,139 @@ pub fn infer_generation_type(raw_metadata: &str) -> String {
     }
 }
 
-fn parse_json_metadata(raw: &str) -> Option<GenerationParams> {
+/// Names the tool that wrote `raw_metadata`: `comfyui`, `invokeai`,
+/// `swarmui`, `fooocus`, `novelai`, `forge`, `a1111`, `other` for metadata
+/// none of those claim, or `unknown` when there is none.
+pub fn infer_source_tool(raw_metadata: &str) -> String {
+    let raw = raw_metadata.trim();
+    if raw.is_empty() {
+        return "unknown".to_string();
+    }
+    let lowered = raw.to_ascii_lowercase();
+
+    if let Ok(value) = serde_json::from_str::<Value>(raw) {
+        let tool = if looks_like_comfy_prompt_graph(&value)
+            || (value.get("nodes").is_some() && value.get("links").is_some())
+        {
+            "comfyui"
+        } else if value.get("sui_image_params").is_some() {
+            "swarmui"
+        } else if lowered.contains("invokeai") || value.get("generation_mode").is_some() {
+            "invokeai"
+        } else if lowered.contains("fooocus") {
+            "fooocus"
+        } else {
+            "other"
+        };
+        return tool.to_string();
+    }
+
+    if lowered.contains("software: novelai") {
+        return "novelai".to_string();
+    }
+    let version = raw
+        .rsplit_once("Version: ")
+        .map(|(_, rest)| rest.split([',', '\n']).next().unwrap_or_default().trim());
+    // Forge tags its builds `f2.0.1v1.10.1-...`; Forge Classic and Neo
+    // spell their name out.
+    let is_forge = version.is_some_and(|version| {
+        let mut chars = version.chars();
+        (chars.next() == Some('f') && chars.next().is_some_and(|ch| ch.is_ascii_digit()))
+            || version.contains("classic")
+            || version.contains("neo")
+    });
+    if is_forge {
+        "forge".to_string()
+    } else if lowered.contains("steps: ") {
+        "a1111".to_string()
+    } else {
+        "other".to_string()
+    }
+}
+
+fn parse_json_metadata(raw: &str, log: &mut SourceLog) -> Option<GenerationParams> {
     let value: Value = serde_json::from_str(raw).ok()?;
     let mut params = GenerationParams {
         raw_metadata: raw.to_string(),
         ..Default::default()
     };
-
-    params.prompt = first_string_field(
-        &value,
-        &["prompt", "Prompt", "description", "Description", "caption"],
+    // Only kept when the JSON turns out to hold generation parameters.
+    let mut json_log = SourceLog(log.0.as_ref().map(|_| Vec::new()));
+    let log_ref = &mut json_log;
+
+    params.prompt = json_field(
+        first_string_field(
+            &value,
+            &["prompt", "Prompt", "description", "Description", "caption"],
+        ),
+        "prompt",
+        log_ref,
     )
     .unwrap_or_default();
-    params.negative_prompt = first_string_field(
-        &value,
-        &[
-            "negative_prompt",
-            "negativePrompt",
-            "negative",
-            "uc",
-            "Negative prompt",
-        ],
+    params.negative_prompt = json_field(
+        first_string_field(
+            &value,
+            &[
+                "negative_prompt",
+                "negativePrompt",
+                "negative",
+                "uc",
+                "Negative prompt",
+            ],
+        ),
+        "negative_prompt",
+        log_ref,
     )
     .unwrap_or_default();
-    params.steps = first_scalar_field(&value, &["steps", "num_inference_steps"]);
-    params.sampler = first_scalar_field(&value, &["sampler", "sampler_name", "samplerName"]);
-    params.schedule_type = first_scalar_field(&value, &["scheduler", "schedule_type", "schedule"]);
-    params.cfg_scale = first_scalar_field(
-        &value,
-        &[
-            "cfg",
-            "cfg_scale",
-            "guidance",
-            "distilled_cfg_scale",
-            "scale",
-        ],
+    params.steps = json_field(
+        first_scalar_field(&value, &["steps", "num_inference_steps"]),
+        "steps",
+        log_ref,
     );
-    params.seed = first_scalar_field(&value, &["seed"]);
-    params.model_name = first_scalar_field(
-        &value,
-        &[
-            "model",
-            "model_name",
-            "checkpoint",
-            "ckpt_name",
-            "unet_name",
-        ],
+    params.sampler = json_field(
+        first_scalar_field(&value, &["sampler", "sampler_name", "samplerName"]),
+        "sampler",
+        log_ref,
     );
-    params.width = first_u32_field(&value, &["width", "w"]);
-    params.height = first_u32_field(&value, &["height", "h"]);
+    params.schedule_type = json_field(
+        first_scalar_field(&value, &["scheduler", "schedule_type", "schedule"]),
+        "schedule_type",
+        log_ref,
+    );
+    params.cfg_scale = json_field(
+        first_scalar_field(
+            &value,
+            &[
+                "cfg",
+                "cfg_scale",
+                "guidance",
+                "distilled_cfg_scale",
+                "scale",
+            ],
+        ),
+        "cfg_scale",
+        log_ref,
+    );
+    params.seed = json_field(first_scalar_field(&value, &["seed"]), "seed", log_ref);
+    params.model_name = json_field(
+        first_scalar_field(
+            &value,
+            &[
+                "model",
+                "model_name",
+                "checkpoint",
+                "ckpt_name",
+                "unet_name",
+            ],
+        ),
+        "model_name",
+        log_ref,
+    );
+    params.width = json_field(first_u32_field(&value, &["width", "w"]), "width", log_ref);
+    params.height = json_field(first_u32_field(&value, &["height", "h"]), "height", log_ref);
 
     if looks_like_comfy_prompt_graph(&value) {
-        merge_comfy_graph_params(&value, &mut params);
+        merge_comfy_graph_params(&value, &mut params, log_ref);
     }
 
     if params.prompt.trim().is_empty() {
@@ -224,6 +385,9 @@ fn parse_json_metadata(raw: &str) -> Option<GenerationParams> {
             .collect::<Vec<_>>();
         if !reduced.is_empty() {
             params.prompt = reduced.join(", ");
+            log_ref.record("prompt", || SourceOrigin::Inferred {
+                detail: "prompt-like strings anywhere in the JSON",
+            });
         }
     }
 
@@ -238,61 +402,52 @@ fn parse_json_metadata(raw: &str) -> Option<GenerationParams> {
         || params.model_name.is_some();
 
     if has_any_content {
+        log.append(json_log);
         Some(params)
     } else {
         None
     }
 }
 
-fn first_string_field(value: &Value, keys: &[&str]) -> Option<String> {
+/// Records the JSON key a top-level field was read from.
+fn json_field<T>(found: Option<(T, &str)>, field: &'static str, log: &mut SourceLog) -> Option<T> {
+    let (value, key) = found?;
+    log.record(field, || SourceOrigin::JsonPath {
+        path: json_path(key),
+    });
+    Some(value)
+}
+
+fn json_path(key: &str) -> String {
+    if key
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+    {
+        format!("$.{}", key)
+    } else {
+        format!("$[{}]", Value::String(key.to_string()))
+    }
+}
+
+fn first_string_field<'k>(value: &Value, keys: &[&'k str]) -> Option<(String, &'k str)> {
     let object = value.as_object()?;
     for key in keys {
         if let Some(raw) = object.get(*key).and_then(Value::as_str) {
             let trimmed = raw.trim();
             if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
+                return Some((trimmed.to_string(), key));
             }
         }
     }
     None
 }
 
-fn first_scalar_field(value: &Value, keys: &[&str]) -> Option<String> {
-    let object = value.as_object()?;
-    for key in keys {
-        if let Some(raw) = object.get(*key) {
-            match raw {
-                Value::String(text) => {
-                    let trimmed = text.trim();
-                    if !trimmed.is_empty() {
-                        return Some(trimmed.to_string());
-                    }
-                }
-                Value::Number(number) => return Some(number.to_string()),
-                Value::Bool(boolean) => return Some(boolean.to_string()),
-                _ => {}
-            }
-        }
-    }
-    None
+fn first_scalar_field<'k>(value: &Value, keys: &[&'k str]) -> Option<(String, &'k str)> {
+    read_scalar_from_inputs(value.as_object()?, keys)
 }
 
-fn first_u32_field(value: &Value, keys: &[&str]) -> Option<u32> {
-    let object = value.as_object()?;
-    for key in keys {
-        if let Some(raw) = object.get(*key) {
-            if let Some(parsed) = raw.as_u64().and_then(|num| u32::try_from(num).ok()) {
-                return Some(parsed);
-            }
-            if let Some(parsed) = raw
-                .as_str()
-                .and_then(|text| text.trim().parse::<u32>().ok())
-            {
-                return Some(parsed);
-            }
-        }
-    }
-    None
+fn first_u32_field<'k>(value: &Value, keys: &[&'k str]) -> Option<(u32, &'k str)> {
+    read_u32_from_inputs(value.as_object()?, keys)
 }
 
 fn looks_like_comfy_prompt_graph(value: &Value) -> bool {
@@ -307,7 +462,14 @@ fn looks_like_comfy_prompt_graph(value: &Value) -> bool {
         .unwrap_or(false)
 }
 
-fn merge_comfy_graph_params(value: &Value, params: &mut GenerationParams) {
+/// A ComfyUI node whose `text` input feeds a prompt.
+struct TextNode<'a> {
+    id: &'a str,
+    class_type: &'a str,
+    text: &'a str,
+}
+
+fn merge_comfy_graph_params(value: &Value, params: &mut GenerationParams, log: &mut SourceLog) {
     let Some(nodes) = value.as_object() else {
         return;
     };
@@ -317,7 +479,7 @@ fn merge_comfy_graph_params(value: &Value, params: &mut GenerationParams) {
     let mut fallback_positive = Vec::new();
     let mut fallback_negative = Vec::new();
 
-    for node in nodes.values() {
+    for (node_id, node) in nodes {
         let Some(class_type) = node.get("class_type").and_then(Value::as_str) else {
             continue;
         };
@@ -330,21 +492,49 @@ fn merge_comfy_graph_params(value: &Value, params: &mut GenerationParams) {
             || (class_lower.contains("sampler") && class_lower.contains("advanced"))
         {
             if params.steps.is_none() {
-                params.steps = read_scalar_from_inputs(inputs, &["steps"]);
+                params.steps = comfy_field(
+                    read_scalar_from_inputs(inputs, &["steps"]),
+                    "steps",
+                    log,
+                    node_id,
+                    class_type,
+                );
             }
             if params.sampler.is_none() {
-                params.sampler = read_scalar_from_inputs(inputs, &["sampler_name", "sampler"]);
+                params.sampler = comfy_field(
+                    read_scalar_from_inputs(inputs, &["sampler_name", "sampler"]),
+                    "sampler",
+                    log,
+                    node_id,
+                    class_type,
+                );
             }
             if params.schedule_type.is_none() {
-                params.schedule_type =
-                    read_scalar_from_inputs(inputs, &["scheduler", "schedule_type"]);
+                params.schedule_type = comfy_field(
+                    read_scalar_from_inputs(inputs, &["scheduler", "schedule_type"]),
+                    "schedule_type",
+                    log,
+                    node_id,
+                    class_type,
+                );
             }
             if params.cfg_scale.is_none() {
-                params.cfg_scale =
-                    read_scalar_from_inputs(inputs, &["cfg", "guidance", "distilled_cfg_scale"]);
+                params.cfg_scale = comfy_field(
+                    read_scalar_from_inputs(inputs, &["cfg", "guidance", "distilled_cfg_scale"]),
+                    "cfg_scale",
+                    log,
+                    node_id,
+                    class_type,
+                );
             }
             if params.seed.is_none() {
-                params.seed = read_scalar_from_inputs(inputs, &["seed", "noise_seed"]);
+                params.seed = comfy_field(
+                    read_scalar_from_inputs(inputs, &["seed", "noise_seed"]),
+                    "seed",
+                    log,
+                    node_id,
+                    class_type,
+                );
             }
             if let Some(positive_ref) = inputs.get("positive").and_then(extract_node_reference) {
                 positive_refs.push(positive_ref);
@@ -355,17 +545,34 @@ fn merge_comfy_graph_params(value: &Value, params: &mut GenerationParams) {
         }
 
         if params.width.is_none() {
-            params.width = read_u32_from_inputs(inputs, &["width"]);
+            params.width = comfy_field(
+                read_u32_from_inputs(inputs, &["width"]),
+                "width",
+                log,
+                node_id,
+                class_type,
+            );
         }
         if params.height.is_none() {
-            params.height = read_u32_from_inputs(inputs, &["height"]);
+            params.height = comfy_field(
+                read_u32_from_inputs(inputs, &["height"]),
+                "height",
+                log,
+                node_id,
+                class_type,
+            );
         }
         if params.model_name.is_none() {
-            params.model_name =
-                read_scalar_from_inputs(inputs, &["ckpt_name", "model_name", "unet_name"]);
+            params.model_name = comfy_field(
+                read_scalar_from_inputs(inputs, &["ckpt_name", "model_name", "unet_name"]),
+                "model_name",
+                log,
+                node_id,
+                class_type,
+            );
         }
 
-        if let Some(text_value) = inputs
+        if let Some(text) = inputs
             .get("text")
             .and_then(Value::as_str)
             .map(str::trim)
@@ -378,10 +585,15 @@ fn merge_comfy_graph_params(value: &Value, params: &mut GenerationParams) {
                 .map(|title| title.to_ascii_lowercase().contains("negative"))
                 .unwrap_or(false);
 
+            let text_node = TextNode {
+                id: node_id,
+                class_type,
+                text,
+            };
             if is_negative {
-                fallback_negative.push(text_value.to_string());
+                fallback_negative.push(text_node);
             } else {
-                fallback_positive.push(text_value.to_string());
+                fallback_positive.push(text_node);
             }
         }
     }
@@ -390,37 +602,56 @@ fn merge_comfy_graph_params(value: &Value, params: &mut GenerationParams) {
     let negative_texts = resolve_referenced_texts(nodes, &negative_refs);
 
     if params.prompt.trim().is_empty() {
-        params.prompt = if !positive_texts.is_empty() {
-            join_unique_texts(&positive_texts)
+        let sources = if !positive_texts.is_empty() {
+            &positive_texts
         } else {
-            join_unique_texts(&fallback_positive)
+            &fallback_positive
         };
+        params.prompt = join_unique_texts(sources, "prompt", log);
     }
 
     if params.negative_prompt.trim().is_empty() {
-        params.negative_prompt = if !negative_texts.is_empty() {
-            join_unique_texts(&negative_texts)
+        let sources = if !negative_texts.is_empty() {
+            &negative_texts
         } else {
-            join_unique_texts(&fallback_negative)
+            &fallback_negative
         };
+        params.negative_prompt = join_unique_texts(sources, "negative_prompt", log);
     }
 }
 
-fn read_scalar_from_inputs(
+/// Records the graph node input a field was read from.
+fn comfy_field<T>(
+    found: Option<(T, &str)>,
+    field: &'static str,
+    log: &mut SourceLog,
+    node_id: &str,
+    class_type: &str,
+) -> Option<T> {
+    let (value, input) = found?;
+    log.record(field, || SourceOrigin::ComfyNode {
+        node_id: node_id.to_string(),
+        class_type: class_type.to_string(),
+        input: input.to_string(),
+    });
+    Some(value)
+}
+
+fn read_scalar_from_inputs<'k>(
     inputs: &serde_json::Map<String, Value>,
-    keys: &[&str],
-) -> Option<String> {
+    keys: &[&'k str],
+) -> Option<(String, &'k str)> {
     for key in keys {
         if let Some(raw) = inputs.get(*key) {
             match raw {
                 Value::String(text) => {
                     let trimmed = text.trim();
                     if !trimmed.is_empty() {
-                        return Some(trimmed.to_string());
+                        return Some((trimmed.to_string(), key));
                     }
                 }
-                Value::Number(number) => return Some(number.to_string()),
-                Value::Bool(boolean) => return Some(boolean.to_string()),
+                Value::Number(number) => return Some((number.to_string(), key)),
+                Value::Bool(boolean) => return Some((boolean.to_string(), key)),
                 _ => {}
             }
         }
@@ -428,17 +659,20 @@ fn read_scalar_from_inputs(
     None
 }
 
-fn read_u32_from_inputs(inputs: &serde_json::Map<String, Value>, keys: &[&str]) -> Option<u32> {
+fn read_u32_from_inputs<'k>(
+    inputs: &serde_json::Map<String, Value>,
+    keys: &[&'k str],
+) -> Option<(u32, &'k str)> {
     for key in keys {
         if let Some(raw) = inputs.get(*key) {
             if let Some(value) = raw.as_u64().and_then(|num| u32::try_from(num).ok()) {
-                return Some(value);
+                return Some((value, key));
             }
             if let Some(value) = raw
                 .as_str()
                 .and_then(|text| text.trim().parse::<u32>().ok())
             {
-                return Some(value);
+                return Some((value, key));
             }
         }
     }
@@ -466,31 +700,48 @@ fn extract_node_reference(value: &Value) -> Option<String> {
     None
 }
 
-fn resolve_referenced_texts(
-    nodes: &serde_json::Map<String, Value>,
+fn resolve_referenced_texts<'a>(
+    nodes: &'a serde_json::Map<String, Value>,
     refs: &[String],
-) -> Vec<String> {
+) -> Vec<TextNode<'a>> {
     refs.iter()
-        .filter_map(|node_id| nodes.get(node_id))
-        .filter_map(|node| node.get("inputs").and_then(Value::as_object))
-        .filter_map(|inputs| inputs.get("text"))
-        .filter_map(Value::as_str)
-        .map(str::trim)
-        .filter(|text| !text.is_empty())
-        .map(str::to_string)
+        .filter_map(|node_id| nodes.get_key_value(node_id))
+        .filter_map(|(id, node)| {
+            let text = node
+                .get("inputs")
+                .and_then(Value::as_object)?
+                .get("text")?
+                .as_str()?
+                .trim();
+            (!text.is_empty()).then(|| TextNode {
+                id,
+                class_type: node
+                    .get("class_type")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default(),
+                text,
+            })
+        })
         .collect()
 }
 
-fn join_unique_texts(texts: &[String]) -> String {
+/// Joins the distinct texts of `nodes`, recording each contributing node as
+/// a source of `field`.
+fn join_unique_texts(nodes: &[TextNode<'_>], field: &'static str, log: &mut SourceLog) -> String {
     let mut seen = HashSet::new();
     let mut ordered = Vec::new();
-    for text in texts {
-        let normalized = text.trim();
+    for node in nodes {
+        let normalized = node.text.trim();
         if normalized.is_empty() {
             continue;
         }
         if seen.insert(normalized.to_ascii_lowercase()) {
-            ordered.push(normalized.to_string());
+            ordered.push(normalized);
+            log.record(field, || SourceOrigin::ComfyNode {
+                node_id: node.id.to_string(),
+                class_type: node.class_type.to_string(),
+                input: "text".to_string(),
+            });
         }
     }
     ordered.join(", ")
@@ -532,6 +783,113 @@ fn is_likely_prompt_text(text: &str) -> bool {
     trimmed.chars().any(|ch| ch.is_alphabetic())
 }
 
+/// Widget values ComfyUI stores beside seeds and toggles; never worth
+/// searching for.
+const WORKFLOW_CONTROL_VALUES: &[&str] = &[
+    "randomize",
+    "fixed",
+    "increment",
+    "decrement",
+    "enable",
+    "disable",
+    "true",
+    "false",
+    "none",
+];
+/// Longer unbroken strings are embedded images or blobs, not names.
+const MAX_WORKFLOW_TOKEN_LEN: usize = 200;
+
+/// Searchable text of a ComfyUI graph, one entry per line: node types,
+/// node and group titles, and string widget values, without links, numbers,
+/// hashes or embedded data. Reads both the API prompt graph stored as
+/// `raw_metadata` and the editor `workflow` chunk, which is the only place
+/// custom node titles survive. Empty when neither is a ComfyUI graph.
+pub fn workflow_search_text(raw_metadata: &str, workflow: Option<&str>) -> String {
+    let graphs: Vec<Value> = std::iter::once(raw_metadata)
+        .chain(workflow)
+        .filter(|json| json.trim_start().starts_with('{'))
+        .filter_map(|json| serde_json::from_str(json).ok())
+        .collect();
+    let mut entries = Vec::new();
+    for value in &graphs {
+        if looks_like_comfy_prompt_graph(value) {
+            collect_prompt_graph_text(value, &mut entries);
+        } else if let Some(nodes) = value.get("nodes").and_then(Value::as_array) {
+            collect_editor_workflow_text(value, nodes, &mut entries);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    entries
+        .into_iter()
+        .map(str::trim)
+        .filter(|entry| is_searchable_workflow_text(entry))
+        .filter(|entry| seen.insert(entry.to_ascii_lowercase()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn collect_prompt_graph_text<'a>(value: &'a Value, entries: &mut Vec<&'a str>) {
+    let Some(nodes) = value.as_object() else {
+        return;
+    };
+    for node in nodes.values() {
+        entries.extend(node.get("class_type").and_then(Value::as_str));
+        entries.extend(node.pointer("/_meta/title").and_then(Value::as_str));
+        if let Some(inputs) = node.get("inputs").and_then(Value::as_object) {
+            // Links are `[node_id, slot]` arrays and are skipped with numbers.
+            entries.extend(inputs.values().filter_map(Value::as_str));
+        }
+    }
+}
+
+fn collect_editor_workflow_text<'a>(
+    value: &'a Value,
+    nodes: &'a [Value],
+    entries: &mut Vec<&'a str>,
+) {
+    for node in nodes {
+        entries.extend(node.get("type").and_then(Value::as_str));
+        entries.extend(node.get("title").and_then(Value::as_str));
+        entries.extend(
+            node.pointer("/properties/Node name for S&R")
+                .and_then(Value::as_str),
+        );
+        if let Some(widgets) = node.get("widgets_values") {
+            match widgets {
+                Value::Array(values) => entries.extend(values.iter().filter_map(Value::as_str)),
+                Value::Object(values) => entries.extend(values.values().filter_map(Value::as_str)),
+                _ => {}
+            }
+        }
+    }
+    if let Some(groups) = value.get("groups").and_then(Value::as_array) {
+        entries.extend(
+            groups
+                .iter()
+                .filter_map(|group| group.get("title").and_then(Value::as_str)),
+        );
+    }
+}
+
+fn is_searchable_workflow_text(text: &str) -> bool {
+    if text.is_empty() || !text.chars().any(char::is_alphabetic) {
+        return false;
+    }
+    if WORKFLOW_CONTROL_VALUES
+        .iter()
+        .any(|control| text.eq_ignore_ascii_case(control))
+    {
+        return false;
+    }
+    // Hashes and base64 payloads: long runs without a space.
+    let longest_word = text.split_whitespace().map(str::len).max().unwrap_or(0);
+    if longest_word > MAX_WORKFLOW_TOKEN_LEN {
+        return false;
+    }
+    !(text.len() >= 32 && text.bytes().all(|byte| byte.is_ascii_hexdigit()))
+}
+
 /// Parses a raw A1111/Forge metadata string into structured GenerationParams.
 ///
 /// Format: `{prompt}\nNegative prompt: {neg}\nSteps: N, Sampler: X, ...`
@@ -542,12 +900,16 @@ fn is_likely_prompt_text(text: &str) -> bool {
 /// - Missing negative prompt section
 /// - Metadata with only parameter block (no prompt text)
 pub fn parse_a1111_metadata(raw: &str) -> GenerationParams {
+    parse_a1111_text(raw, &mut SourceLog::disabled())
+}
+
+fn parse_a1111_text(raw_metadata: &str, log: &mut SourceLog) -> GenerationParams {
     let mut params = GenerationParams {
-        raw_metadata: raw.to_string(),
+        raw_metadata: raw_metadata.to_string(),
         ..Default::default()
     };
 
-    let raw = raw.trim();
+    let raw = raw_metadata.trim();
     if raw.is_empty() {
         return params;
     }
@@ -555,62 +917,79 @@ pub fn parse_a1111_metadata(raw: &str) -> GenerationParams {
     // Phase 1: Split into sections using specific delimiters
     // Look for "Negative prompt:" and "Steps:" as section boundaries
     let (prompt_section, neg_and_rest) = if let Some(neg_idx) = raw.find("Negative prompt:") {
-        let prompt = raw[..neg_idx].trim().to_string();
+        let prompt = raw[..neg_idx].trim();
         let rest = raw[neg_idx + "Negative prompt:".len()..].trim();
-        (prompt, Some(rest.to_string()))
+        (prompt, Some(rest))
     } else {
-        (raw.to_string(), None)
+        (raw, None)
     };
 
-    // Phase 2: Extract negative prompt and parameter block
-    let (negative_prompt, param_block) = if let Some(ref neg_rest) = neg_and_rest {
+    // Phase 2: Extract prompt, negative prompt and parameter block
+    let (prompt, negative_prompt, param_block) = if let Some(neg_rest) = neg_and_rest {
         if let Some(steps_idx) = neg_rest.find("\nSteps:") {
-            let neg = neg_rest[..steps_idx].trim().to_string();
-            let params_str = neg_rest[steps_idx + 1..].trim().to_string();
-            (neg, Some(params_str))
+            let neg = neg_rest[..steps_idx].trim();
+            (prompt_section, neg, Some(neg_rest[steps_idx + 1..].trim()))
         } else if let Some(steps_idx) = neg_rest.find("Steps:") {
             // Sometimes on same line
-            let neg = neg_rest[..steps_idx].trim().to_string();
-            let params_str = neg_rest[steps_idx..].trim().to_string();
-            (neg, Some(params_str))
+            let neg = neg_rest[..steps_idx].trim();
+            (prompt_section, neg, Some(neg_rest[steps_idx..].trim()))
         } else {
-            (neg_rest.to_string(), None)
+            (prompt_section, neg_rest, None)
         }
+    } else if let Some(steps_idx) = prompt_section.find("\nSteps:") {
+        // No negative prompt; Steps: follows the prompt
+        let prompt = prompt_section[..steps_idx].trim();
+        (prompt, "", Some(prompt_section[steps_idx + 1..].trim()))
+    } else if prompt_section.starts_with("Steps:") {
+        // Entire text is a parameter block
+        ("", "", Some(prompt_section))
     } else {
-        // No negative prompt; check if Steps: appears in prompt section
-        if let Some(steps_idx) = prompt_section.find("\nSteps:") {
-            let prompt = prompt_section[..steps_idx].trim().to_string();
-            let params_str = prompt_section[steps_idx + 1..].trim().to_string();
-            params.prompt = prompt;
-            (String::new(), Some(params_str))
-        } else if prompt_section.starts_with("Steps:") {
-            // Entire text is a parameter block
-            (String::new(), Some(prompt_section.clone()))
-        } else {
-            params.prompt = prompt_section;
-            return params;
-        }
+        (prompt_section, "", None)
     };
 
-    if params.prompt.is_empty() && !prompt_section.is_empty() && neg_and_rest.is_some() {
-        params.prompt = prompt_section;
+    if !prompt.is_empty() {
+        params.prompt = prompt.to_string();
+        log.record("prompt", || text_origin(raw_metadata, None, prompt));
+    }
+    if !negative_prompt.is_empty() {
+        params.negative_prompt = negative_prompt.to_string();
+        log.record("negative_prompt", || {
+            text_origin(raw_metadata, None, negative_prompt)
+        });
     }
-    params.negative_prompt = negative_prompt;
 
     // Phase 3: Parse comma-separated key-value pairs from parameter block
-    if let Some(ref param_block) = param_block {
-        parse_parameter_block(param_block, &mut params);
+    if let Some(param_block) = param_block {
+        parse_parameter_block(raw_metadata, param_block, &mut params, log);
     }
 
     params
 }
 
+/// Source span of `part`, which must be a slice of `raw_metadata`.
+fn text_origin(raw_metadata: &str, key: Option<&str>, part: &str) -> SourceOrigin {
+    let start = (part.as_ptr() as usize)
+        .checked_sub(raw_metadata.as_ptr() as usize)
+        .filter(|start| start + part.len() <= raw_metadata.len())
+        .unwrap_or_default();
+    SourceOrigin::Text {
+        key: key.map(str::to_string),
+        start,
+        end: start + part.len(),
+    }
+}
+
 /// Parses the `Steps: 20, Sampler: Euler a, ...` parameter block.
 ///
 /// Uses a smart split strategy: we split on `, ` followed by a known key pattern
 /// (capitalized word + colon) to avoid breaking on commas inside values like
 /// `Lora hashes: "name: hash, name2: hash2"`.
-fn parse_parameter_block(block: &str, params: &mut GenerationParams) {
+fn parse_parameter_block(
+    raw_metadata: &str,
+    block: &str,
+    params: &mut GenerationParams,
+    log: &mut SourceLog,
+) {
     let pairs = split_parameter_pairs(block);
 
     for pair in pairs {
@@ -620,26 +999,53 @@ fn parse_parameter_block(block: &str, params: &mut GenerationParams) {
             let value = pair[colon_pos + 1..].trim();
             let normalized = normalize_key(key);
 
-            match normalized.to_lowercase().as_str() {
-                "steps" => params.steps = Some(value.to_string()),
-                "sampler" => params.sampler = Some(value.to_string()),
-                "schedule type" => params.schedule_type = Some(value.to_string()),
-                "cfg scale" | "cfg_scale" => params.cfg_scale = Some(value.to_string()),
-                "seed" => params.seed = Some(value.to_string()),
-                "model hash" => params.model_hash = Some(value.to_string()),
-                "model" => params.model_name = Some(value.to_string()),
+            let field = match normalized.to_lowercase().as_str() {
+                "steps" => {
+                    params.steps = Some(value.to_string());
+                    Some("steps")
+                }
+                "sampler" => {
+                    params.sampler = Some(value.to_string());
+                    Some("sampler")
+                }
+                "schedule type" => {
+                    params.schedule_type = Some(value.to_string());
+                    Some("schedule_type")
+                }
+                "cfg scale" | "cfg_scale" => {
+                    params.cfg_scale = Some(value.to_string());
+                    Some("cfg_scale")
+                }
+                "seed" => {
+                    params.seed = Some(value.to_string());
+                    Some("seed")
+                }
+                "model hash" => {
+                    params.model_hash = Some(value.to_string());
+                    Some("model_hash")
+                }
+                "model" => {
+                    params.model_name = Some(value.to_string());
+                    Some("model_name")
+                }
                 "size" => {
                     // Format: "WxH"
-                    if let Some((w, h)) = value.split_once('x') {
+                    value.split_once('x').map(|(w, h)| {
                         params.width = w.trim().parse().ok();
                         params.height = h.trim().parse().ok();
-                    }
+                        log.record("width", || text_origin(raw_metadata, Some(key), value));
+                        "height"
+                    })
                 }
                 _ => {
                     params
                         .extra_params
                         .insert(key.to_string(), value.to_string());
+                    Some("extra_params")
                 }
+            };
+            if let Some(field) = field {
+                log.record(field, || text_origin(raw_metadata, Some(key), value));
             }
         }
     }
@@ -655,7 +1061,7 @@ fn parse_parameter_block(block: &str, params: &mut GenerationParams) {
 /// This avoids breaking values such as:
 /// - `Lora hashes: "a:111, b:222"`
 /// - `ADetailer prompt: foo, bar, baz`
-fn split_parameter_pairs(block: &str) -> Vec<String> {
+fn split_parameter_pairs(block: &str) -> Vec<&str> {
     let mut pairs = Vec::new();
     let mut start = 0usize;
     let mut in_quotes = false;
@@ -666,7 +1072,7 @@ fn split_parameter_pairs(block: &str) -> Vec<String> {
             ',' if !in_quotes && is_key_boundary_after_comma(block, idx + 1) => {
                 let segment = block[start..idx].trim();
                 if !segment.is_empty() {
-                    pairs.push(segment.to_string());
+                    pairs.push(segment);
                 }
                 start = idx + 1;
             }
@@ -676,7 +1082,7 @@ fn split_parameter_pairs(block: &str) -> Vec<String> {
 
     let tail = block[start..].trim();
     if !tail.is_empty() {
-        pairs.push(tail.to_string());
+        pairs.push(tail);
     }
 
     pairs
@@ -769,6 +1175,138 @@ fn extract_lora_tags(prompt: &str, tags: &mut HashSet<String>) {
     }
 }
 
+/// A LoRA applied to an image, with the weight it was applied at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoraUse {
+    /// Lowercased, as in the `lora:` tags.
+    pub name: String,
+    pub weight: f64,
+}
+
+/// LoRAs named in `<lora:name:weight>` prompt syntax and in ComfyUI
+/// LoRA loader nodes, first use of each name only. Prompt LoRAs without a
+/// weight count as 1; loader nodes use their model strength and are named
+/// by file stem, as the WebUI does.
+pub fn extract_loras(params: &GenerationParams) -> Vec<LoraUse> {
+    let mut loras = Vec::new();
+    let mut seen = HashSet::new();
+    let mut push = |name: &str, weight: f64| {
+        let name = name.trim().to_ascii_lowercase();
+        if !name.is_empty() && name.len() <= 96 && seen.insert(name.clone()) {
+            loras.push(LoraUse { name, weight });
+        }
+    };
+
+    let lower = params.prompt.to_ascii_lowercase();
+    let mut cursor = 0usize;
+    while let Some(found) = lower[cursor..].find("<lora:") {
+        let start = cursor + found + "<lora:".len();
+        let Some(length) = params.prompt[start..].find('>') else {
+            break;
+        };
+        let mut parts = params.prompt[start..start + length].split(':');
+        let name = parts.next().unwrap_or_default();
+        let weight = parts
+            .next()
+            .and_then(|weight| weight.trim().parse::<f64>().ok())
+            .unwrap_or(1.0);
+        push(name, weight);
+        cursor = start + length + 1;
+    }
+
+    let graph = Some(params.raw_metadata.as_str())
+        .filter(|raw| raw.trim_start().starts_with('{'))
+        .and_then(|raw| serde_json::from_str::<Value>(raw).ok())
+        .filter(looks_like_comfy_prompt_graph);
+    for node in graph
+        .iter()
+        .filter_map(Value::as_object)
+        .flat_map(|nodes| nodes.values())
+    {
+        let is_loader = node
+            .get("class_type")
+            .and_then(Value::as_str)
+            .is_some_and(|class_type| class_type.contains("LoraLoader"));
+        if !is_loader {
+            continue;
+        }
+        let Some(file) = node.pointer("/inputs/lora_name").and_then(Value::as_str) else {
+            continue;
+        };
+        let stem = file.rsplit(['/', '\\']).next().unwrap_or(file);
+        let stem = stem.rsplit_once('.').map_or(stem, |(stem, _)| stem);
+        let weight = node
+            .pointer("/inputs/strength_model")
+            .and_then(Value::as_f64)
+            .unwrap_or(1.0);
+        push(stem, weight);
+    }
+    loras
+}
+
+/// A textual-inversion embedding an image's prompts rely on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingUse {
+    /// Lowercased, as in the `embedding:` tags.
+    pub name: String,
+    /// Used in the negative prompt rather than the positive one.
+    pub negative: bool,
+}
+
+/// Embeddings named as `embedding:name` words (ComfyUI syntax) and those
+/// listed in A1111's `TI hashes` that appear as a word in either prompt.
+/// A name used in both prompts is reported once for each.
+pub fn extract_embeddings(params: &GenerationParams) -> Vec<EmbeddingUse> {
+    let known: Vec<String> = params
+        .extra_params
+        .get("TI hashes")
+        .map(|hashes| {
+            hashes
+                .trim_matches('"')
+                .split(',')
+                .filter_map(|entry| entry.split(':').next())
+                .map(|name| name.trim().to_ascii_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut embeddings = Vec::new();
+    for (prompt, negative) in [(&params.prompt, false), (&params.negative_prompt, true)] {
+        let mut names: Vec<String> = Vec::new();
+        for word in prompt.split(|ch: char| ch.is_whitespace() || ch == ',') {
+            let lowered = word
+                .to_ascii_lowercase()
+                .trim_start_matches(['(', '['])
+                .to_string();
+            let (name, explicit) = match lowered.strip_prefix("embedding:") {
+                Some(name) => (name, true),
+                None => (lowered.as_str(), false),
+            };
+            // `(name:1.2)` weights the embedding; the name ends at the colon.
+            let name = name
+                .split(':')
+                .next()
+                .unwrap_or_default()
+                .trim_matches(|ch: char| !ch.is_alphanumeric() && ch != '_' && ch != '-');
+            if name.is_empty()
+                || name.len() > 96
+                || !(explicit || known.iter().any(|known| known == name))
+                || names.iter().any(|seen| seen == name)
+            {
+                continue;
+            }
+            names.push(name.to_string());
+        }
+        embeddings.extend(
+            names
+                .into_iter()
+                .map(|name| EmbeddingUse { name, negative }),
+        );
+    }
+    embeddings
+}
+
 fn extract_embedding_tags(prompt: &str, tags: &mut HashSet<String>) {
     for word in prompt.split(|c: char| c.is_whitespace() || c == ',') {
         let lowered = word.to_ascii_lowercase();
@@ -938,4 +1476,189 @@ Steps: 20, Sampler: Euler a, Lora hashes: "foo:111, bar:222", ADetailer prompt:
         assert_eq!(params.seed.as_deref(), Some("987654"));
         assert_eq!(params.model_name.as_deref(), Some("flux1-dev.safetensors"));
     }
+
+    #[test]
+    fn test_workflow_search_text_keeps_titles_and_widget_strings() {
+        let prompt = r#"{
+            "3": {"class_type": "KSampler", "inputs": {"seed": 5, "sampler_name": "euler", "model": ["4", 0]}},
+            "9": {"class_type": "UltimateSDUpscale", "_meta": {"title": "Upscale x2"}, "inputs": {"mode_type": "Linear"}}
+        }"#;
+        let workflow = r#"{
+            "nodes": [
+                {"id": 9, "type": "UltimateSDUpscale", "title": "My Fancy Upscaler",
+                 "properties": {"Node name for S&R": "UltimateSDUpscale"},
+                 "widgets_values": [2, "randomize", "Linear", "0123456789abcdef0123456789abcdef"]},
+                {"id": 12, "type": "Note", "widgets_values": ["remember the face detailer"]}
+            ],
+            "groups": [{"title": "Hires pass"}],
+            "links": [[1, 4, 0, 3, 0, "MODEL"]]
+        }"#;
+
+        let text = workflow_search_text(prompt, Some(workflow));
+        let lines: Vec<&str> = text.lines().collect();
+        for expected in [
+            "KSampler",
+            "euler",
+            "UltimateSDUpscale",
+            "Upscale x2",
+            "My Fancy Upscaler",
+            "remember the face detailer",
+            "Hires pass",
+        ] {
+            assert!(
+                lines.contains(&expected),
+                "missing {expected:?} in {lines:?}"
+            );
+        }
+        assert_eq!(lines.iter().filter(|line| **line == "Linear").count(), 1);
+        assert!(!text.contains("randomize"));
+        assert!(!text.contains("0123456789abcdef"));
+        assert!(!text.contains("MODEL"));
+
+        assert_eq!(workflow_search_text(SAMPLE_WITH_NEGATIVE, None), "");
+    }
+
+    #[test]
+    fn test_infer_source_tool_from_metadata_markers() {
+        let a1111 = "cat\nSteps: 20, Sampler: Euler a, Version: v1.10.1";
+        assert_eq!(infer_source_tool(a1111), "a1111");
+        let forge = "cat\nSteps: 20, Version: f2.0.1v1.10.1-previous-659-gc055f2d4";
+        assert_eq!(infer_source_tool(forge), "forge");
+        assert_eq!(
+            infer_source_tool("cat\nSteps: 28, Sampler: k_euler, Software: NovelAI"),
+            "novelai"
+        );
+        assert_eq!(
+            infer_source_tool(r#"{"3":{"class_type":"KSampler","inputs":{"seed":1}}}"#),
+            "comfyui"
+        );
+        assert_eq!(
+            infer_source_tool(r#"{"positive_prompt":"cat","generation_mode":"txt2img"}"#),
+            "invokeai"
+        );
+        assert_eq!(infer_source_tool(r#"{"prompt":"cat"}"#), "other");
+        assert_eq!(infer_source_tool("  "), "unknown");
+    }
+
+    #[test]
+    fn test_extract_embeddings_from_syntax_and_ti_hashes() {
+        let params = parse_a1111_metadata(
+            r#"a cat, embedding:Ink-Style, easynegative
+Negative prompt: (EasyNegative:1.2), bad_hands, embedding:ink-style
+Steps: 20, Sampler: Euler a, TI hashes: "EasyNegative: c74b4e810b03, bad_hands: 1234abcd", Seed: 1"#,
+        );
+        let embedding = |name: &str, negative| EmbeddingUse {
+            name: name.to_string(),
+            negative,
+        };
+        assert_eq!(
+            extract_embeddings(&params),
+            [
+                embedding("ink-style", false),
+                embedding("easynegative", false),
+                embedding("easynegative", true),
+                embedding("bad_hands", true),
+                embedding("ink-style", true),
+            ]
+        );
+        assert!(extract_embeddings(&parse_a1111_metadata(SAMPLE_WITH_NEGATIVE)).is_empty());
+    }
+
+    #[test]
+    fn test_extract_loras_reads_prompt_weights_and_comfy_loaders() {
+        let loras = extract_loras(&parse_a1111_metadata(SAMPLE_METADATA));
+        assert_eq!(loras.len(), 4);
+        assert_eq!(
+            loras[0],
+            LoraUse {
+                name: "norman_rockwell_style_pony_v6-000040".to_string(),
+                weight: 0.6,
+            }
+        );
+        assert_eq!(loras[2].name, "age slider v2_alpha1.0_rank4_noxattn_last");
+        assert_eq!(loras[2].weight, -3.6);
+
+        let comfy = parse_generation_metadata(
+            r#"{
+                "10": {"class_type": "LoraLoader", "inputs": {"lora_name": "styles\\Ink.safetensors", "strength_model": 0.8, "model": ["4", 0]}},
+                "11": {"class_type": "LoraLoaderModelOnly", "inputs": {"lora_name": "detail.safetensors"}},
+                "6": {"class_type": "CLIPTextEncode", "inputs": {"text": "a cat <lora:ink:0.3>"}}
+            }"#,
+        );
+        let loras = extract_loras(&comfy);
+        assert_eq!(
+            loras,
+            [
+                LoraUse {
+                    name: "ink".to_string(),
+                    weight: 0.3,
+                },
+                LoraUse {
+                    name: "detail".to_string(),
+                    weight: 1.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_field_sources_point_at_a1111_spans_and_comfy_nodes() {
+        let (params, sources) = parse_generation_metadata_with_sources(SAMPLE_WITH_NEGATIVE);
+        let span_of = |field: &str| {
+            sources.iter().find_map(|source| match &source.origin {
+                SourceOrigin::Text { key, start, end } if source.field == field => {
+                    Some((key.clone(), &SAMPLE_WITH_NEGATIVE[*start..*end]))
+                }
+                _ => None,
+            })
+        };
+        assert_eq!(
+            span_of("prompt"),
+            Some((None, "masterpiece, best quality, 1girl, solo"))
+        );
+        assert_eq!(
+            span_of("negative_prompt"),
+            Some((None, params.negative_prompt.as_str()))
+        );
+        assert_eq!(span_of("seed"), Some((Some("Seed".to_string()), "12345")));
+        assert_eq!(
+            span_of("height"),
+            Some((Some("Size".to_string()), "512x768"))
+        );
+        assert_eq!(
+            span_of("model_name"),
+            Some((Some("Model".to_string()), "anything-v5"))
+        );
+
+        let raw = r#"{
+            "3": {"class_type": "KSampler", "inputs": {"noise_seed": 7, "positive": ["6", 0]}},
+            "6": {"class_type": "CLIPTextEncode", "inputs": {"text": "a lighthouse"}}
+        }"#;
+        let (_, sources) = parse_generation_metadata_with_sources(raw);
+        assert!(sources.contains(&FieldSource {
+            field: "seed",
+            origin: SourceOrigin::ComfyNode {
+                node_id: "3".to_string(),
+                class_type: "KSampler".to_string(),
+                input: "noise_seed".to_string(),
+            },
+        }));
+        assert!(sources.contains(&FieldSource {
+            field: "prompt",
+            origin: SourceOrigin::ComfyNode {
+                node_id: "6".to_string(),
+                class_type: "CLIPTextEncode".to_string(),
+                input: "text".to_string(),
+            },
+        }));
+
+        let (_, sources) =
+            parse_generation_metadata_with_sources(r#"{"Negative prompt": "blurry", "seed": 3}"#);
+        assert!(sources.contains(&FieldSource {
+            field: "negative_prompt",
+            origin: SourceOrigin::JsonPath {
+                path: r#"$["Negative prompt"]"#.to_string(),
+            },
+        }));
+    }
 }
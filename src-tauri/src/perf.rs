@@ -0,0 +1,336 @@
+//! In-process timing counters behind `get_performance_report`.
+//!
+//! Counters are plain atomics updated on the hot paths (queries, thumbnail
+//! generation, cache lookups, scan stages). They live only in memory and reset
+//! on restart; nothing is written to disk or sent anywhere.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct LatencyCounter {
+    count: AtomicU64,
+    errors: AtomicU64,
+    total_micros: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+impl LatencyCounter {
+    fn record(&self, elapsed: Duration, ok: bool) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        self.count.fetch_add(1, Ordering::Relaxed);
+        if !ok {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.max_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+struct HitCounter {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl HitCounter {
+    fn record(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+struct StageCounter {
+    total_micros: AtomicU64,
+    last_micros: AtomicU64,
+}
+
+impl StageCounter {
+    fn record(&self, elapsed: Duration) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.last_micros.store(micros, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    queries: RwLock<HashMap<&'static str, Arc<LatencyCounter>>>,
+    thumbnails_generated: AtomicU64,
+    thumbnails_failed: AtomicU64,
+    thumbnail_micros: AtomicU64,
+    thumbnail_cache: HitCounter,
+    display_cache: HitCounter,
+    record_cache: HitCounter,
+    scans: AtomicU64,
+    scan_files_processed: AtomicU64,
+    scan_discovery: StageCounter,
+    scan_filter: StageCounter,
+    scan_metadata: StageCounter,
+    scan_thumbnails: StageCounter,
+    scan_total: StageCounter,
+}
+
+fn counters() -> &'static Counters {
+    static COUNTERS: OnceLock<Counters> = OnceLock::new();
+    COUNTERS.get_or_init(Counters::default)
+}
+
+fn started_at() -> Instant {
+    static STARTED: OnceLock<Instant> = OnceLock::new();
+    *STARTED.get_or_init(Instant::now)
+}
+
+/// Records one call of a query command.
+pub fn record_query(endpoint: &'static str, elapsed: Duration, ok: bool) {
+    let counters = counters();
+    let existing = counters
+        .queries
+        .read()
+        .ok()
+        .and_then(|queries| queries.get(endpoint).cloned());
+    let counter = match existing {
+        Some(counter) => counter,
+        None => match counters.queries.write() {
+            Ok(mut queries) => queries.entry(endpoint).or_default().clone(),
+            Err(_) => return,
+        },
+    };
+    counter.record(elapsed, ok);
+}
+
+/// Records an actual thumbnail encode (cache-existing files are not counted).
+pub fn record_thumbnail_generated(elapsed: Duration) {
+    let counters = counters();
+    counters
+        .thumbnails_generated
+        .fetch_add(1, Ordering::Relaxed);
+    counters.thumbnail_micros.fetch_add(
+        elapsed.as_micros().min(u64::MAX as u128) as u64,
+        Ordering::Relaxed,
+    );
+}
+
+pub fn record_thumbnail_failed() {
+    counters().thumbnails_failed.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Thumbnail lookups answered from the index or disk vs. ones that had to generate.
+pub fn record_thumbnail_cache(hits: u64, misses: u64) {
+    let cache = &counters().thumbnail_cache;
+    cache.hits.fetch_add(hits, Ordering::Relaxed);
+    cache.misses.fetch_add(misses, Ordering::Relaxed);
+}
+
+/// JPEG XL display proxy lookups.
+pub fn record_display_cache(hit: bool) {
+    counters().display_cache.record(hit);
+}
+
+/// In-memory `ImageRecord` cache lookups in the database layer.
+pub fn record_record_cache(hit: bool) {
+    counters().record_cache.record(hit);
+}
+
+pub struct ScanTimings {
+    pub files_processed: usize,
+    pub discovery: Duration,
+    pub filter: Duration,
+    pub metadata: Duration,
+    pub thumbnails: Duration,
+    pub total: Duration,
+}
+
+pub fn record_scan(timings: &ScanTimings) {
+    let counters = counters();
+    counters.scans.fetch_add(1, Ordering::Relaxed);
+    counters
+        .scan_files_processed
+        .fetch_add(timings.files_processed as u64, Ordering::Relaxed);
+    counters.scan_discovery.record(timings.discovery);
+    counters.scan_filter.record(timings.filter);
+    counters.scan_metadata.record(timings.metadata);
+    counters.scan_thumbnails.record(timings.thumbnails);
+    counters.scan_total.record(timings.total);
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryLatency {
+    pub endpoint: String,
+    pub count: u64,
+    pub errors: u64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThumbnailThroughput {
+    pub generated: u64,
+    pub failed: u64,
+    pub avg_ms: f64,
+    /// Per worker thread; multiply by the thumbnail pool size for wall-clock rate.
+    pub images_per_second: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheHitRate {
+    pub name: String,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanStageDurations {
+    pub discovery_ms: f64,
+    pub filter_ms: f64,
+    pub metadata_ms: f64,
+    pub thumbnails_ms: f64,
+    pub total_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanStageReport {
+    pub runs: u64,
+    pub files_processed: u64,
+    pub last: Option<ScanStageDurations>,
+    pub average: Option<ScanStageDurations>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CounterReport {
+    pub uptime_seconds: u64,
+    pub queries: Vec<QueryLatency>,
+    pub thumbnails: ThumbnailThroughput,
+    pub caches: Vec<CacheHitRate>,
+    pub scans: ScanStageReport,
+}
+
+fn micros_to_ms(micros: u64) -> f64 {
+    micros as f64 / 1000.0
+}
+
+fn hit_rate(name: &str, counter: &HitCounter) -> CacheHitRate {
+    let hits = counter.hits.load(Ordering::Relaxed);
+    let misses = counter.misses.load(Ordering::Relaxed);
+    let lookups = hits + misses;
+    CacheHitRate {
+        name: name.to_string(),
+        hits,
+        misses,
+        hit_rate: (lookups > 0).then(|| hits as f64 / lookups as f64),
+    }
+}
+
+/// Snapshot of all counters; queries are sorted slowest-average first.
+pub fn snapshot() -> CounterReport {
+    let counters = counters();
+
+    let mut queries: Vec<QueryLatency> = counters
+        .queries
+        .read()
+        .map(|queries| {
+            queries
+                .iter()
+                .map(|(endpoint, counter)| {
+                    let count = counter.count.load(Ordering::Relaxed);
+                    let total = counter.total_micros.load(Ordering::Relaxed);
+                    QueryLatency {
+                        endpoint: endpoint.to_string(),
+                        count,
+                        errors: counter.errors.load(Ordering::Relaxed),
+                        avg_ms: if count > 0 {
+                            micros_to_ms(total) / count as f64
+                        } else {
+                            0.0
+                        },
+                        max_ms: micros_to_ms(counter.max_micros.load(Ordering::Relaxed)),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    queries.sort_by(|a, b| b.avg_ms.total_cmp(&a.avg_ms));
+
+    let generated = counters.thumbnails_generated.load(Ordering::Relaxed);
+    let thumbnail_micros = counters.thumbnail_micros.load(Ordering::Relaxed);
+    let thumbnails = ThumbnailThroughput {
+        generated,
+        failed: counters.thumbnails_failed.load(Ordering::Relaxed),
+        avg_ms: if generated > 0 {
+            micros_to_ms(thumbnail_micros) / generated as f64
+        } else {
+            0.0
+        },
+        images_per_second: if thumbnail_micros > 0 {
+            generated as f64 / (thumbnail_micros as f64 / 1_000_000.0)
+        } else {
+            0.0
+        },
+    };
+
+    let runs = counters.scans.load(Ordering::Relaxed);
+    let stage = |counter: &StageCounter, last: bool| {
+        if last {
+            micros_to_ms(counter.last_micros.load(Ordering::Relaxed))
+        } else {
+            micros_to_ms(counter.total_micros.load(Ordering::Relaxed)) / runs as f64
+        }
+    };
+    let durations = |last: bool| ScanStageDurations {
+        discovery_ms: stage(&counters.scan_discovery, last),
+        filter_ms: stage(&counters.scan_filter, last),
+        metadata_ms: stage(&counters.scan_metadata, last),
+        thumbnails_ms: stage(&counters.scan_thumbnails, last),
+        total_ms: stage(&counters.scan_total, last),
+    };
+    let scans = ScanStageReport {
+        runs,
+        files_processed: counters.scan_files_processed.load(Ordering::Relaxed),
+        last: (runs > 0).then(|| durations(true)),
+        average: (runs > 0).then(|| durations(false)),
+    };
+
+    CounterReport {
+        uptime_seconds: started_at().elapsed().as_secs(),
+        queries,
+        thumbnails,
+        caches: vec![
+            hit_rate("thumbnails", &counters.thumbnail_cache),
+            hit_rate("display_proxies", &counters.display_cache),
+            hit_rate("image_records", &counters.record_cache),
+        ],
+        scans,
+    }
+}
+
+/// Marks the start of the measurement window; called once at startup.
+pub fn init() {
+    let _ = started_at();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_latency_aggregates_per_endpoint() {
+        record_query("perf_test_endpoint", Duration::from_millis(4), true);
+        record_query("perf_test_endpoint", Duration::from_millis(8), false);
+
+        let report = snapshot();
+        let entry = report
+            .queries
+            .iter()
+            .find(|query| query.endpoint == "perf_test_endpoint")
+            .expect("endpoint should be tracked");
+        assert_eq!(entry.count, 2);
+        assert_eq!(entry.errors, 1);
+        assert!((entry.avg_ms - 6.0).abs() < 0.01);
+        assert!((entry.max_ms - 8.0).abs() < 0.01);
+    }
+}
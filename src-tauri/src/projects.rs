@@ -0,0 +1,198 @@
+//! Projects group the folders, saved searches and export presets that
+//! belong to one client or job.
+//!
+//! Switching to a project confines the gallery, facets and stats to its
+//! folders; a project without folders only bundles its searches and
+//! presets. Saved searches reuse [`ViewState`] so restoring one is the same
+//! as restoring the last session. Projects live in `projects.json` in the
+//! app data directory.
+
+use crate::error::{AppError, AppResult};
+use crate::ViewState;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedSearch {
+    pub name: String,
+    pub view: ViewState,
+}
+
+/// Settings for `export_images_as_files`, kept as the export dialog sends
+/// them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportPreset {
+    pub name: String,
+    pub format: String,
+    pub quality: Option<u8>,
+    pub max_edge: Option<u32>,
+    pub crop_aspect: Option<String>,
+    pub name_template: Option<String>,
+    pub output_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Project {
+    pub name: String,
+    pub directories: Vec<String>,
+    pub saved_searches: Vec<SavedSearch>,
+    pub export_presets: Vec<ExportPreset>,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+impl Project {
+    /// Trims names and folders, drops empty and repeated folders, and
+    /// rejects unnamed or duplicate searches and presets.
+    pub fn normalized(mut self) -> AppResult<Self> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Give the project a name".to_string(),
+            ));
+        }
+
+        let mut directories: Vec<String> = Vec::with_capacity(self.directories.len());
+        for directory in self.directories {
+            let directory = directory.trim().to_string();
+            if !directory.is_empty() && !directories.contains(&directory) {
+                directories.push(directory);
+            }
+        }
+        self.directories = directories;
+
+        for search in &mut self.saved_searches {
+            search.name = search.name.trim().to_string();
+        }
+        for preset in &mut self.export_presets {
+            preset.name = preset.name.trim().to_string();
+            preset.format = preset.format.trim().to_lowercase();
+            if preset.format.is_empty() {
+                return Err(AppError::InvalidInput(format!(
+                    "Export preset '{}' needs a format",
+                    preset.name
+                )));
+            }
+        }
+        check_names(
+            "saved search",
+            self.saved_searches
+                .iter()
+                .map(|search| search.name.as_str()),
+        )?;
+        check_names(
+            "export preset",
+            self.export_presets
+                .iter()
+                .map(|preset| preset.name.as_str()),
+        )?;
+        Ok(self)
+    }
+}
+
+fn check_names<'a>(kind: &str, names: impl Iterator<Item = &'a str>) -> AppResult<()> {
+    let mut seen: Vec<&str> = Vec::new();
+    for name in names {
+        if name.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "Give every {} a name",
+                kind
+            )));
+        }
+        if seen.iter().any(|other| other.eq_ignore_ascii_case(name)) {
+            return Err(AppError::InvalidInput(format!(
+                "There is more than one {} named '{}'",
+                kind, name
+            )));
+        }
+        seen.push(name);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectSettings {
+    pub projects: Vec<Project>,
+    /// Name of the project scoping the views; `None` shows the whole
+    /// library.
+    pub active: Option<String>,
+}
+
+impl ProjectSettings {
+    /// Looks a project up by name, ignoring case.
+    pub fn find(&self, name: &str) -> Option<&Project> {
+        let name = name.trim();
+        self.projects
+            .iter()
+            .find(|project| project.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn active_project(&self) -> Option<&Project> {
+        self.active.as_deref().and_then(|name| self.find(name))
+    }
+
+    /// Folders the active project confines queries to; empty when no
+    /// project is active or it has no folders.
+    pub fn active_directories(&self) -> Vec<String> {
+        self.active_project()
+            .map(|project| project.directories.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projects_normalize_and_resolve_the_active_scope() {
+        let project = Project {
+            name: " Acme ".to_string(),
+            directories: vec![
+                " D:/clients/acme ".to_string(),
+                "D:/clients/acme".to_string(),
+                "".to_string(),
+            ],
+            export_presets: vec![ExportPreset {
+                name: "Web".to_string(),
+                format: " WEBP ".to_string(),
+                max_edge: Some(2048),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(project.name, "Acme");
+        assert_eq!(project.directories, ["D:/clients/acme"]);
+        assert_eq!(project.export_presets[0].format, "webp");
+
+        let duplicate_searches = Project {
+            name: "Acme".to_string(),
+            saved_searches: vec![
+                SavedSearch {
+                    name: "Picks".to_string(),
+                    ..Default::default()
+                },
+                SavedSearch {
+                    name: "picks".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        assert!(duplicate_searches.normalized().is_err());
+        assert!(Project::default().normalized().is_err());
+
+        let mut settings = ProjectSettings {
+            projects: vec![project],
+            active: None,
+        };
+        assert!(settings.active_directories().is_empty());
+        settings.active = Some("ACME".to_string());
+        assert_eq!(settings.active_directories(), ["D:/clients/acme"]);
+    }
+}
@@ -0,0 +1,312 @@
+//! Groups prompts into themes with TF-IDF vectors and spherical k-means.
+//!
+//! Each distinct prompt becomes a vector over its tags (as `extract_tags`
+//! splits them), weighted by how rare the tag is across the library, and
+//! prompts are clustered by cosine similarity. Seeding is farthest-first
+//! from the most common prompt, so the same library always yields the same
+//! clusters. A cluster's label is its heaviest tags.
+
+use crate::parser::extract_tags;
+use std::collections::HashMap;
+
+/// Tags in more than this share of prompts (quality boilerplate) carry no
+/// theme and are left out.
+const MAX_DOCUMENT_SHARE: f32 = 0.5;
+/// Rarer tags can't link prompts together.
+const MIN_DOCUMENT_COUNT: u32 = 2;
+/// Largest vocabulary kept, most common tags first.
+const MAX_TERMS: usize = 20_000;
+/// Upper bound on k-means passes; most libraries settle well before.
+const MAX_ITERATIONS: usize = 25;
+/// Tags joined into a cluster label.
+const LABEL_TERMS: usize = 3;
+/// Tags listed per cluster.
+const SUMMARY_TERMS: usize = 8;
+pub const MAX_CLUSTERS: usize = 200;
+
+/// Cluster count for `prompts` distinct prompts when the caller gives none.
+pub fn default_cluster_count(prompts: usize) -> usize {
+    ((prompts as f64 / 2.0).sqrt().round() as usize).clamp(2, 64)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterSummary {
+    pub label: String,
+    pub terms: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clustering {
+    /// Largest cluster first.
+    pub clusters: Vec<ClusterSummary>,
+    /// Index into `clusters` per input prompt; `None` when a prompt shares
+    /// no tags with the rest of the library.
+    pub assignments: Vec<Option<usize>>,
+}
+
+/// Sparse unit vector: `(term, weight)` sorted by term.
+type SparseVector = Vec<(usize, f32)>;
+
+/// Clusters `prompts`, each weighted by how many images use it, into at
+/// most `cluster_count` groups. `keep_going` is polled between passes;
+/// returning false abandons the run and yields `None`.
+pub fn cluster_prompts(
+    prompts: &[(String, u32)],
+    cluster_count: usize,
+    mut keep_going: impl FnMut() -> bool,
+) -> Option<Clustering> {
+    let (terms, vectors) = tf_idf(prompts);
+    let weights: Vec<f32> = prompts.iter().map(|(_, count)| *count as f32).collect();
+    let candidates: Vec<usize> = (0..vectors.len())
+        .filter(|&index| !vectors[index].is_empty())
+        .collect();
+    let cluster_count = cluster_count.clamp(1, MAX_CLUSTERS).min(candidates.len());
+    if cluster_count == 0 {
+        return Some(Clustering {
+            clusters: Vec::new(),
+            assignments: vec![None; prompts.len()],
+        });
+    }
+
+    let mut centroids = farthest_first(&vectors, &weights, &candidates, cluster_count, terms.len());
+    let mut assignments: Vec<Option<usize>> = vec![None; prompts.len()];
+    for _ in 0..MAX_ITERATIONS {
+        if !keep_going() {
+            return None;
+        }
+        let mut changed = false;
+        for &index in &candidates {
+            let nearest = nearest_centroid(&centroids, &vectors[index]);
+            if assignments[index] != Some(nearest) {
+                assignments[index] = Some(nearest);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        let mut sums = vec![vec![0.0f32; terms.len()]; centroids.len()];
+        for &index in &candidates {
+            if let Some(cluster) = assignments[index] {
+                for &(term, value) in &vectors[index] {
+                    sums[cluster][term] += value * weights[index];
+                }
+            }
+        }
+        for (centroid, sum) in centroids.iter_mut().zip(sums) {
+            // An emptied cluster keeps its old centre and may win prompts back.
+            if sum.iter().any(|value| *value > 0.0) {
+                *centroid = normalized(sum);
+            }
+        }
+    }
+
+    let mut sizes = vec![0u64; centroids.len()];
+    for (index, cluster) in assignments.iter().enumerate() {
+        if let Some(cluster) = cluster {
+            sizes[*cluster] += weights[index] as u64;
+        }
+    }
+    let mut order: Vec<usize> = (0..centroids.len()).filter(|&c| sizes[c] > 0).collect();
+    order.sort_by(|a, b| sizes[*b].cmp(&sizes[*a]).then(a.cmp(b)));
+    let mut renumbered = vec![None; centroids.len()];
+    for (new_index, &old_index) in order.iter().enumerate() {
+        renumbered[old_index] = Some(new_index);
+    }
+
+    let clusters = order
+        .iter()
+        .map(|&cluster| {
+            let mut ranked: Vec<(usize, f32)> = centroids[cluster]
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|(_, weight)| *weight > 0.0)
+                .collect();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+            let terms: Vec<String> = ranked
+                .into_iter()
+                .take(SUMMARY_TERMS)
+                .map(|(term, _)| terms[term].clone())
+                .collect();
+            ClusterSummary {
+                label: terms[..terms.len().min(LABEL_TERMS)].join(", "),
+                terms,
+            }
+        })
+        .collect();
+    Some(Clustering {
+        clusters,
+        assignments: assignments
+            .into_iter()
+            .map(|cluster| cluster.and_then(|cluster| renumbered[cluster]))
+            .collect(),
+    })
+}
+
+/// Vocabulary and one unit TF-IDF vector per prompt. Tags count once per
+/// prompt; document frequency counts each distinct prompt once.
+fn tf_idf(prompts: &[(String, u32)]) -> (Vec<String>, Vec<SparseVector>) {
+    let tagged: Vec<Vec<String>> = prompts
+        .iter()
+        .map(|(prompt, _)| extract_tags(prompt))
+        .collect();
+    let mut document_counts: HashMap<&str, u32> = HashMap::new();
+    for tags in &tagged {
+        for tag in tags {
+            *document_counts.entry(tag.as_str()).or_insert(0) += 1;
+        }
+    }
+    let documents = prompts.len().max(1) as f32;
+    let max_count = ((documents * MAX_DOCUMENT_SHARE) as u32).max(MIN_DOCUMENT_COUNT);
+    let mut vocabulary: Vec<(&str, u32)> = document_counts
+        .into_iter()
+        .filter(|(_, count)| (MIN_DOCUMENT_COUNT..=max_count).contains(count))
+        .collect();
+    vocabulary.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    vocabulary.truncate(MAX_TERMS);
+    vocabulary.sort_by(|a, b| a.0.cmp(b.0));
+
+    let index: HashMap<&str, (usize, f32)> = vocabulary
+        .iter()
+        .enumerate()
+        .map(|(term, (tag, count))| (*tag, (term, (documents / *count as f32).ln() + 1.0)))
+        .collect();
+    let vectors = tagged
+        .iter()
+        .map(|tags| {
+            let mut vector: SparseVector = tags
+                .iter()
+                .filter_map(|tag| index.get(tag.as_str()).copied())
+                .collect();
+            vector.sort_by_key(|(term, _)| *term);
+            let norm = vector
+                .iter()
+                .map(|(_, weight)| weight * weight)
+                .sum::<f32>()
+                .sqrt();
+            for (_, weight) in &mut vector {
+                *weight /= norm;
+            }
+            vector
+        })
+        .collect();
+    let terms = vocabulary
+        .into_iter()
+        .map(|(tag, _)| tag.to_string())
+        .collect();
+    (terms, vectors)
+}
+
+/// Starts from the most used prompt, then repeatedly adds the prompt least
+/// similar to every centre chosen so far.
+fn farthest_first(
+    vectors: &[SparseVector],
+    weights: &[f32],
+    candidates: &[usize],
+    cluster_count: usize,
+    dimensions: usize,
+) -> Vec<Vec<f32>> {
+    let dense = |vector: &SparseVector| {
+        let mut centroid = vec![0.0f32; dimensions];
+        for &(term, value) in vector {
+            centroid[term] = value;
+        }
+        centroid
+    };
+    let first = candidates
+        .iter()
+        .copied()
+        .max_by(|a, b| weights[*a].total_cmp(&weights[*b]).then(b.cmp(a)))
+        .unwrap_or(0);
+    let mut centroids = vec![dense(&vectors[first])];
+    let mut closest: Vec<f32> = candidates
+        .iter()
+        .map(|&index| similarity(&centroids[0], &vectors[index]))
+        .collect();
+    while centroids.len() < cluster_count {
+        let Some((position, _)) = closest
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(b.1).then(a.0.cmp(&b.0)))
+        else {
+            break;
+        };
+        let centroid = dense(&vectors[candidates[position]]);
+        for (slot, &index) in closest.iter_mut().zip(candidates) {
+            *slot = slot.max(similarity(&centroid, &vectors[index]));
+        }
+        centroids.push(centroid);
+    }
+    centroids
+}
+
+fn nearest_centroid(centroids: &[Vec<f32>], vector: &SparseVector) -> usize {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(cluster, centroid)| (cluster, similarity(centroid, vector)))
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(cluster, _)| cluster)
+        .unwrap_or(0)
+}
+
+fn similarity(centroid: &[f32], vector: &SparseVector) -> f32 {
+    vector
+        .iter()
+        .map(|&(term, value)| centroid[term] * value)
+        .sum()
+}
+
+fn normalized(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in &mut vector {
+            *value /= norm;
+        }
+    }
+    vector
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separates_themes_and_labels_them_by_their_tags() {
+        let prompts: Vec<(String, u32)> = [
+            ("masterpiece, castle, mountains, snow", 3),
+            ("masterpiece, castle, mountains, sunset", 1),
+            ("masterpiece, castle, snow, night", 1),
+            ("masterpiece, cat, garden, flowers", 2),
+            ("masterpiece, cat, flowers, sunlight", 1),
+            ("masterpiece, cat, garden", 1),
+            ("masterpiece, spaceship", 1),
+        ]
+        .into_iter()
+        .map(|(prompt, count)| (prompt.to_string(), count))
+        .collect();
+
+        let clustering = cluster_prompts(&prompts, 2, || true).unwrap();
+        assert_eq!(clustering.clusters.len(), 2);
+        let castles = clustering.assignments[0].unwrap();
+        let cats = clustering.assignments[3].unwrap();
+        assert_ne!(castles, cats);
+        assert_eq!(clustering.assignments[1..3], [Some(castles); 2]);
+        assert_eq!(clustering.assignments[4..6], [Some(cats); 2]);
+        // No tag it shares with another prompt survives the boilerplate cut.
+        assert_eq!(clustering.assignments[6], None);
+
+        assert_eq!(castles, 0, "the larger theme comes first");
+        let label = &clustering.clusters[castles].label;
+        assert!(label.contains("castle"), "{label}");
+        assert!(!label.contains("masterpiece"), "{label}");
+        assert!(clustering.clusters[cats]
+            .terms
+            .contains(&"garden".to_string()));
+
+        assert_eq!(clustering, cluster_prompts(&prompts, 2, || true).unwrap());
+        assert!(cluster_prompts(&prompts, 2, || false).is_none());
+        assert_eq!(default_cluster_count(5000), 50);
+    }
+}
@@ -0,0 +1,143 @@
+//! Find/replace edits over stored prompts.
+//!
+//! An edit is either a literal substring or a regular expression (with `$1`
+//! style group references in the replacement). Prompts an edit changes are
+//! tidied afterwards so removing a term from a comma-separated prompt
+//! doesn't leave `a, , b` or a dangling leading comma behind.
+
+use crate::error::{AppError, AppResult};
+use regex::{Regex, RegexBuilder};
+
+/// Compiled pattern size cap; keeps a pathological expression from eating
+/// memory.
+const MAX_REGEX_SIZE: usize = 1 << 20;
+
+#[derive(Debug, Clone)]
+pub enum PromptEdit {
+    Literal { find: String, replace: String },
+    Pattern { regex: Regex, replace: String },
+}
+
+impl PromptEdit {
+    pub fn new(find: &str, replace: &str, regex: bool) -> AppResult<Self> {
+        if find.is_empty() {
+            return Err(AppError::InvalidInput("Enter the text to find".to_string()));
+        }
+        if !regex {
+            return Ok(PromptEdit::Literal {
+                find: find.to_string(),
+                replace: replace.to_string(),
+            });
+        }
+        let regex = RegexBuilder::new(find)
+            .size_limit(MAX_REGEX_SIZE)
+            .build()
+            .map_err(|error| AppError::InvalidInput(format!("Invalid pattern: {}", error)))?;
+        Ok(PromptEdit::Pattern {
+            regex,
+            replace: replace.to_string(),
+        })
+    }
+
+    /// The edited text, or `None` when the edit doesn't change it.
+    pub fn apply(&self, text: &str) -> Option<String> {
+        let edited = match self {
+            PromptEdit::Literal { find, replace } => {
+                if !text.contains(find.as_str()) {
+                    return None;
+                }
+                text.replace(find.as_str(), replace)
+            }
+            PromptEdit::Pattern { regex, replace } => {
+                if !regex.is_match(text) {
+                    return None;
+                }
+                regex.replace_all(text, replace.as_str()).into_owned()
+            }
+        };
+        let edited = tidy_separators(&edited);
+        (edited != text).then_some(edited)
+    }
+}
+
+/// Drops empty comma-separated segments, keeping the spacing of the rest.
+fn tidy_separators(prompt: &str) -> String {
+    prompt
+        .split(',')
+        .filter(|segment| !segment.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(",")
+        .trim()
+        .to_string()
+}
+
+/// Rewrites the prompt and negative prompt inside WebUI-style `parameters`
+/// text. Returns `None` when the text doesn't start with the old prompt (or
+/// lacks the old negative prompt line), i.e. it isn't safe to patch.
+pub fn rewrite_parameters(
+    raw: &str,
+    old_prompt: &str,
+    prompt: &str,
+    old_negative: &str,
+    negative: &str,
+) -> Option<String> {
+    let rest = raw.strip_prefix(old_prompt)?;
+    if old_negative == negative {
+        return Some(format!("{prompt}{rest}"));
+    }
+    let old_line = format!("\nNegative prompt: {old_negative}");
+    let position = rest.find(&old_line)?;
+    let new_line = if negative.is_empty() {
+        String::new()
+    } else {
+        format!("\nNegative prompt: {negative}")
+    };
+    Some(format!(
+        "{prompt}{}{new_line}{}",
+        &rest[..position],
+        &rest[position + old_line.len()..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_tidy_separators_and_support_regex_groups() {
+        let remove = PromptEdit::new("old_trigger", "", false).unwrap();
+        assert_eq!(
+            remove
+                .apply("old_trigger, a cat, old_trigger, sitting")
+                .as_deref(),
+            Some("a cat, sitting")
+        );
+        assert_eq!(remove.apply("a cat"), None);
+
+        let reweight = PromptEdit::new(r"<lora:(\w+):0\.\d+>", "<lora:$1:1>", true).unwrap();
+        assert_eq!(
+            reweight.apply("a cat, <lora:ink:0.6>").as_deref(),
+            Some("a cat, <lora:ink:1>")
+        );
+        assert!(PromptEdit::new("(", "", true).is_err());
+        assert!(PromptEdit::new("", "x", false).is_err());
+    }
+
+    #[test]
+    fn rewrite_parameters_patches_prompt_and_negative_lines() {
+        let raw = "a cat, trigger\nNegative prompt: blurry, trigger\nSteps: 20, Seed: 1";
+        assert_eq!(
+            rewrite_parameters(raw, "a cat, trigger", "a cat", "blurry, trigger", "blurry")
+                .as_deref(),
+            Some("a cat\nNegative prompt: blurry\nSteps: 20, Seed: 1")
+        );
+        assert_eq!(
+            rewrite_parameters(raw, "a cat, trigger", "a cat", "blurry, trigger", "").as_deref(),
+            Some("a cat\nSteps: 20, Seed: 1")
+        );
+        assert_eq!(
+            rewrite_parameters("{\"nodes\": []}", "a cat", "a dog", "", ""),
+            None
+        );
+    }
+}
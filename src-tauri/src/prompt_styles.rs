@@ -0,0 +1,114 @@
+//! Prompt styles imported from the WebUI's `styles.csv`.
+//!
+//! The file has a `name,prompt,negative_prompt` header; columns are located
+//! by name so older files without a negative column still load. Applying a
+//! style follows the WebUI: a style containing `{prompt}` wraps the existing
+//! prompt at that spot, any other style is appended after a comma.
+
+use serde::{Deserialize, Serialize};
+
+const PROMPT_PLACEHOLDER: &str = "{prompt}";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptStyle {
+    pub name: String,
+    pub prompt: String,
+    pub negative_prompt: String,
+}
+
+/// Parses a styles CSV. Returns the styles (deduplicated by name, last wins
+/// like the WebUI) and the number of rows that could not be read.
+pub fn parse_csv(bytes: &[u8]) -> (Vec<PromptStyle>, usize) {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(bytes);
+    let headers = reader.headers().cloned().unwrap_or_default();
+    let column = |name: &str, fallback: usize| {
+        headers
+            .iter()
+            .position(|header| header.trim() == name)
+            .unwrap_or(fallback)
+    };
+    let (name_column, prompt_column, negative_column) = (
+        column("name", 0),
+        column("prompt", 1),
+        column("negative_prompt", 2),
+    );
+
+    let mut styles: Vec<PromptStyle> = Vec::new();
+    let mut skipped = 0usize;
+    for row in reader.records() {
+        let Ok(row) = row else {
+            skipped += 1;
+            continue;
+        };
+        let name = row.get(name_column).map(str::trim).unwrap_or_default();
+        if name.is_empty() {
+            skipped += 1;
+            continue;
+        }
+        let style = PromptStyle {
+            name: name.to_string(),
+            prompt: row.get(prompt_column).unwrap_or_default().to_string(),
+            negative_prompt: row.get(negative_column).unwrap_or_default().to_string(),
+        };
+        match styles
+            .iter_mut()
+            .find(|existing| existing.name == style.name)
+        {
+            Some(existing) => *existing = style,
+            None => styles.push(style),
+        }
+    }
+    (styles, skipped)
+}
+
+/// Merges style text into a prompt the way the WebUI does.
+pub fn merge_prompt(style_text: &str, prompt: &str) -> String {
+    if style_text.contains(PROMPT_PLACEHOLDER) {
+        return style_text.replace(PROMPT_PLACEHOLDER, prompt);
+    }
+    [prompt.trim(), style_text.trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl PromptStyle {
+    /// Returns the positive and negative prompt with this style applied.
+    pub fn apply(&self, prompt: &str, negative_prompt: &str) -> (String, String) {
+        (
+            merge_prompt(&self.prompt, prompt),
+            merge_prompt(&self.negative_prompt, negative_prompt),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_webui_styles_and_applies_them_like_the_webui() {
+        let csv = "name,prompt,negative_prompt\n\
+                   Cinematic,\"{prompt}, film grain, anamorphic\",\"blurry, lowres\"\n\
+                   Painterly,oil painting,\n\
+                   ,orphan,\n\
+                   Painterly,\"oil painting, impasto\",\n";
+        let (styles, skipped) = parse_csv(csv.as_bytes());
+        assert_eq!(skipped, 1);
+        assert_eq!(styles.len(), 2);
+        assert_eq!(styles[1].prompt, "oil painting, impasto");
+
+        assert_eq!(
+            styles[0].apply("a cat", "bad hands"),
+            (
+                "a cat, film grain, anamorphic".to_string(),
+                "bad hands, blurry, lowres".to_string()
+            )
+        );
+        assert_eq!(
+            styles[1].apply("", "bad hands"),
+            ("oil painting, impasto".to_string(), "bad hands".to_string())
+        );
+    }
+}
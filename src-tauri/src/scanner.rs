@@ -97,7 +97,42 @@ pub fn extract_metadata(path: &Path) -> Result<Option<String>, Box<dyn std::erro
         return Ok(None);
     }
     let chunks = extract_text_chunks(path)?;
-    Ok(select_primary_metadata(&chunks))
+    Ok(select_primary_metadata(&chunks).0)
+}
+
+/// What [`extract_metadata_and_workflow`] found in a PNG's text chunks.
+#[derive(Debug, Default)]
+pub struct PngMetadata {
+    pub metadata: Option<String>,
+    /// ComfyUI's editor `workflow` chunk, when a different chunk was picked
+    /// as the metadata. Only the editor graph keeps node titles and notes.
+    pub workflow: Option<String>,
+    /// The generator, when the chunks name it but the metadata text can't:
+    /// NovelAI's is rebuilt in A1111 form.
+    pub source_tool: Option<&'static str>,
+}
+
+/// Like [`extract_metadata`], also returning ComfyUI's editor workflow and
+/// the generator the chunks name.
+pub fn extract_metadata_and_workflow(
+    path: &Path,
+) -> Result<PngMetadata, Box<dyn std::error::Error>> {
+    if !supports_png_metadata(path) {
+        return Ok(PngMetadata::default());
+    }
+    let chunks = extract_text_chunks(path)?;
+    let (metadata, source_tool) = select_primary_metadata(&chunks);
+    let workflow = ["workflow", "Workflow"]
+        .iter()
+        .find_map(|key| chunks.get(*key))
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty() && Some(*value) != metadata.as_deref())
+        .map(str::to_string);
+    Ok(PngMetadata {
+        metadata,
+        workflow,
+        source_tool,
+    })
 }
 
 fn supports_png_metadata(path: &Path) -> bool {
@@ -107,28 +142,32 @@ fn supports_png_metadata(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
-fn select_primary_metadata(chunks: &HashMap<String, String>) -> Option<String> {
+/// The metadata payload, with the generator when only the chunks tell it.
+fn select_primary_metadata(
+    chunks: &HashMap<String, String>,
+) -> (Option<String>, Option<&'static str>) {
     if let Some(novelai) = build_novelai_metadata(chunks) {
-        return Some(novelai);
+        return (Some(novelai), Some("novelai"));
     }
 
     for key in PRIMARY_METADATA_KEYS {
         if let Some(value) = chunks.get(*key) {
             let trimmed = value.trim();
             if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
+                return (Some(trimmed.to_string()), None);
             }
         }
     }
 
     // Deterministic fallback: pick the largest non-empty text payload.
-    chunks
+    let largest = chunks
         .values()
         .map(String::as_str)
         .map(str::trim)
         .filter(|value| !value.is_empty())
         .max_by_key(|value| value.len())
-        .map(str::to_string)
+        .map(str::to_string);
+    (largest, None)
 }
 
 fn build_novelai_metadata(chunks: &HashMap<String, String>) -> Option<String> {
@@ -286,8 +325,68 @@ fn decompress_zlib_to_string(data: &[u8]) -> Option<String> {
     Some(output)
 }
 
+/// Rewrites a PNG's text metadata under `keyword`, dropping every existing
+/// tEXt/zTXt/iTXt chunk with that keyword. The new chunk goes right after
+/// IHDR, as tEXt when the value is ASCII and uncompressed iTXt otherwise.
+/// The file is replaced atomically through a temporary sibling.
+pub fn replace_png_text_chunk(path: &Path, keyword: &str, value: &str) -> std::io::Result<()> {
+    let invalid = |message: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+    let bytes = std::fs::read(path)?;
+    if bytes.len() < PNG_SIGNATURE.len() || bytes[..PNG_SIGNATURE.len()] != PNG_SIGNATURE {
+        return Err(invalid("Not a valid PNG file"));
+    }
+
+    let (chunk_type, data) = if value.is_ascii() {
+        (
+            *b"tEXt",
+            [keyword.as_bytes(), &[0], value.as_bytes()].concat(),
+        )
+    } else {
+        (
+            *b"iTXt",
+            [keyword.as_bytes(), &[0, 0, 0, 0, 0], value.as_bytes()].concat(),
+        )
+    };
+    let mut output = Vec::with_capacity(bytes.len() + data.len() + 12);
+    output.extend_from_slice(&PNG_SIGNATURE);
+    let mut offset = PNG_SIGNATURE.len();
+    while offset + 8 <= bytes.len() {
+        let length = u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+        let end = offset + 12 + length;
+        if end > bytes.len() {
+            return Err(invalid("Truncated PNG chunk"));
+        }
+        let current_type = &bytes[offset + 4..offset + 8];
+        let current_data = &bytes[offset + 8..offset + 8 + length];
+        let replaced = matches!(current_type, b"tEXt" | b"zTXt" | b"iTXt")
+            && current_data.split(|&byte| byte == 0).next() == Some(keyword.as_bytes());
+        if !replaced {
+            output.extend_from_slice(&bytes[offset..end]);
+        }
+        if current_type == b"IHDR" {
+            output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            output.extend_from_slice(&chunk_type);
+            output.extend_from_slice(&data);
+            let mut crc = flate2::Crc::new();
+            crc.update(&chunk_type);
+            crc.update(&data);
+            output.extend_from_slice(&crc.sum().to_be_bytes());
+        }
+        offset = end;
+        if current_type == b"IEND" {
+            break;
+        }
+    }
+
+    let temp_path = path.with_extension("png.tmp");
+    std::fs::write(&temp_path, &output)?;
+    std::fs::rename(&temp_path, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp_path);
+    })
+}
+
 /// Supported image extensions for scanning.
-const SUPPORTED_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "avif", "gif", "jxl"];
+const SUPPORTED_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "avif", "gif", "jxl", "exr"];
 
 /// Recursively scans a directory for supported image files and returns their paths.
 pub fn scan_directory(dir: &Path) -> Vec<ScannedFile> {
@@ -303,27 +402,43 @@ pub fn scan_directory(dir: &Path) -> Vec<ScannedFile> {
         }
 
         let path = entry.path();
-        if let Some(ext) = path.extension() {
-            let ext_lower = ext.to_string_lossy().to_ascii_lowercase();
-            if SUPPORTED_EXTENSIONS.contains(&ext_lower.as_str()) {
-                let metadata = entry.metadata().ok();
-                let file_mtime = metadata
-                    .as_ref()
-                    .and_then(|metadata| metadata.modified().ok())
-                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
-                    .map(|duration| duration.as_secs() as i64);
-                let file_size = metadata.as_ref().map(|metadata| metadata.len() as i64);
-                paths.push(ScannedFile {
-                    path: path.to_path_buf(),
-                    file_mtime,
-                    file_size,
-                });
-            }
+        if is_supported_image_path(path) {
+            paths.push(scanned_file_from_metadata(path, entry.metadata().ok()));
         }
     }
     paths
 }
 
+/// Returns true when the path has one of the indexable image extensions.
+pub fn is_supported_image_path(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .is_some_and(|ext| SUPPORTED_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Stats a single file the same way `scan_directory` does for walked entries.
+pub fn scan_file(path: &Path) -> Option<ScannedFile> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+    Some(scanned_file_from_metadata(path, Some(metadata)))
+}
+
+fn scanned_file_from_metadata(path: &Path, metadata: Option<std::fs::Metadata>) -> ScannedFile {
+    let file_mtime = metadata
+        .as_ref()
+        .and_then(|metadata| metadata.modified().ok())
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs() as i64);
+    let file_size = metadata.as_ref().map(|metadata| metadata.len() as i64);
+    ScannedFile {
+        path: path.to_path_buf(),
+        file_mtime,
+        file_size,
+    }
+}
+
 /// Computes a fast, HDD-friendly content fingerprint from sampled bytes.
 ///
 /// Uses sampled SHA-256 over:
@@ -349,24 +464,59 @@ pub fn compute_quick_hash(path: &Path, file_size_hint: Option<i64>) -> Option<St
     }
 
     let mut reader = BufReader::with_capacity(PNG_READER_CAPACITY, file);
-    let mut hasher = Sha256::new();
-    hasher.update(size_u64.to_le_bytes());
-
     let mut head = vec![0u8; sample_len];
     reader.read_exact(&mut head).ok()?;
-    hasher.update(&head);
 
+    let mut tail = Vec::new();
     if size_u64 > QUICK_HASH_SAMPLE_BYTES as u64 {
         let tail_start = size_u64.saturating_sub(sample_len as u64);
         reader.seek(SeekFrom::Start(tail_start)).ok()?;
-        let mut tail = vec![0u8; sample_len];
+        tail = vec![0u8; sample_len];
         reader.read_exact(&mut tail).ok()?;
-        hasher.update(&tail);
     }
 
+    Some(quick_hash_samples(size_u64, &head, &tail))
+}
+
+/// [`compute_quick_hash`] for data still in memory, such as a generated image
+/// before it is written; equal to the hash of the file it would become.
+pub fn quick_hash_bytes(bytes: &[u8]) -> Option<String> {
+    if bytes.is_empty() {
+        return None;
+    }
+    let sample_len = bytes.len().min(QUICK_HASH_SAMPLE_BYTES);
+    let tail = if bytes.len() > QUICK_HASH_SAMPLE_BYTES {
+        &bytes[bytes.len() - sample_len..]
+    } else {
+        &[]
+    };
+    Some(quick_hash_samples(
+        bytes.len() as u64,
+        &bytes[..sample_len],
+        tail,
+    ))
+}
+
+/// The quick hash of a file of `size` bytes from its head and tail
+/// samples; `tail` is empty when the head already covers the whole file.
+fn quick_hash_samples(size: u64, head: &[u8], tail: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+    hasher.update(head);
+    hasher.update(tail);
     let mut hex = hex_encode(&hasher.finalize()[..]);
     hex.truncate(24);
-    Some(hex)
+    hex
+}
+
+/// SHA-256 of the whole file as hex, to confirm a quick-hash match is a
+/// byte-for-byte copy.
+pub fn compute_full_hash(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let mut reader = BufReader::with_capacity(PNG_READER_CAPACITY, file);
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut reader, &mut hasher).ok()?;
+    Some(hex_encode(&hasher.finalize()[..]))
 }
 
 fn hex_encode(bytes: &[u8]) -> String {
@@ -502,6 +652,28 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn test_replace_png_text_chunk_swaps_parameters_and_keeps_other_chunks() {
+        let mut old_parameters = b"parameters\0".to_vec();
+        old_parameters.extend_from_slice(b"old prompt\nSteps: 20");
+        let mut software = b"Software\0".to_vec();
+        software.extend_from_slice(b"Forge");
+        let png_bytes = build_test_png(vec![(*b"tEXt", old_parameters), (*b"tEXt", software)]);
+        let path = write_temp_png(&png_bytes);
+
+        replace_png_text_chunk(&path, "parameters", "new prompt\nSteps: 20").unwrap();
+        let chunks = extract_text_chunks(&path).expect("chunk extraction failed");
+        assert_eq!(chunks["parameters"], "new prompt\nSteps: 20");
+        assert_eq!(chunks["Software"], "Forge");
+
+        replace_png_text_chunk(&path, "parameters", "café\nSteps: 20").unwrap();
+        let chunks = extract_text_chunks(&path).expect("chunk extraction failed");
+        assert_eq!(chunks["parameters"], "café\nSteps: 20");
+        assert_eq!(chunks.len(), 2);
+
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn test_extracts_parameters_from_ztxt_chunk() {
         let metadata = "Steps: 30, Sampler: DPM++ 2M Karras";
@@ -565,6 +737,26 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn test_novelai_chunks_name_the_source_tool() {
+        let mut software = b"Software\0".to_vec();
+        software.extend_from_slice(b"NovelAI");
+        let mut description = b"Description\0".to_vec();
+        description.extend_from_slice(b"cinematic portrait of a hero");
+
+        let png_bytes = build_test_png(vec![(*b"tEXt", software), (*b"tEXt", description)]);
+        let path = write_temp_png(&png_bytes);
+
+        let extracted = extract_metadata_and_workflow(&path).expect("metadata extraction failed");
+        assert_eq!(
+            extracted.metadata.as_deref(),
+            Some("cinematic portrait of a hero")
+        );
+        assert_eq!(extracted.source_tool, Some("novelai"));
+
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn test_extracts_novelai_compatible_metadata_summary() {
         let mut software = b"Software\0".to_vec();
@@ -607,4 +799,35 @@ mod tests {
 
         let _ = fs::remove_file(path);
     }
+
+    #[test]
+    fn test_quick_hash_bytes_matches_file_hash() {
+        let bytes = build_test_png(vec![]);
+        let path = write_temp_png(&bytes);
+        assert_eq!(quick_hash_bytes(&bytes), compute_quick_hash(&path, None));
+
+        let mut large = bytes.clone();
+        large.extend((0..200_000u32).map(|value| (value % 251) as u8));
+        let large_path = write_temp_png(&large);
+        assert_eq!(
+            quick_hash_bytes(&large),
+            compute_quick_hash(&large_path, None)
+        );
+
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(large_path);
+    }
+
+    #[test]
+    fn test_scan_file_reports_size_and_mtime() {
+        let bytes = build_test_png(vec![]);
+        let path = write_temp_png(&bytes);
+
+        assert!(is_supported_image_path(&path));
+        let scanned = scan_file(&path).expect("expected scanned file");
+        assert_eq!(scanned.file_size, Some(bytes.len() as i64));
+        assert!(scanned.file_mtime.is_some());
+
+        let _ = fs::remove_file(path);
+    }
 }
@@ -0,0 +1,87 @@
+//! Thumbnails for the gallery pages the user is about to scroll into.
+//!
+//! The gallery reports where it is with `hint_scroll_position`; the sources
+//! of the next few pages in the same sort and filter are queued here and
+//! rendered ahead of any scan warmup, so fast scrolling meets finished
+//! thumbnails instead of placeholders. Only the latest position matters:
+//! each hint replaces whatever was still queued.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// Pages past the hinted cursor whose thumbnails are rendered ahead.
+pub const LOOKAHEAD_PAGES: usize = 3;
+
+#[derive(Default)]
+pub struct ScrollWarmup {
+    /// Sources still to render, nearest page first.
+    queue: Mutex<VecDeque<PathBuf>>,
+    worker_running: AtomicBool,
+}
+
+impl ScrollWarmup {
+    /// Replaces the queue with `paths`, dropping pages the user scrolled
+    /// away from.
+    pub fn prioritize(&self, paths: impl IntoIterator<Item = PathBuf>) {
+        let mut queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        queue.clear();
+        queue.extend(paths);
+    }
+
+    /// Takes up to `max` queued sources, nearest first.
+    pub fn take(&self, max: usize) -> Vec<PathBuf> {
+        let mut queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        let count = max.min(queue.len());
+        queue.drain(..count).collect()
+    }
+
+    pub fn pending(&self) -> usize {
+        self.queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Claims the worker slot; false when a worker is already draining.
+    pub fn try_start_worker(&self) -> bool {
+        self.worker_running
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// Releases the worker slot if nothing is queued. Checked under the
+    /// queue lock so a hint arriving meanwhile either sees the slot free or
+    /// leaves work this worker will still pick up.
+    pub fn finish_if_idle(&self) -> bool {
+        let queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        if !queue.is_empty() {
+            return false;
+        }
+        self.worker_running.store(false, Ordering::Release);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hints_replace_the_queue_and_the_worker_stops_when_idle() {
+        let warmup = ScrollWarmup::default();
+        warmup.prioritize(["a", "b", "c"].map(PathBuf::from));
+        assert!(warmup.try_start_worker());
+        assert!(!warmup.try_start_worker());
+        assert_eq!(warmup.take(2), ["a", "b"].map(PathBuf::from));
+
+        // Scrolled elsewhere before "c" was rendered.
+        warmup.prioritize(["x", "y"].map(PathBuf::from));
+        assert_eq!(warmup.pending(), 2);
+        assert!(!warmup.finish_if_idle());
+        assert_eq!(warmup.take(10), ["x", "y"].map(PathBuf::from));
+        assert!(warmup.finish_if_idle());
+        assert!(warmup.try_start_worker());
+    }
+}
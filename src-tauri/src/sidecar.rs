@@ -3,6 +3,7 @@
 //! Sidecar files sit next to the image on disk (e.g. `image.yaml`) and carry
 //! portable metadata that travels with the file when copied or shared.
 
+use crate::error::{AppError, AppResult};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
@@ -34,11 +35,12 @@ pub fn read_sidecar(image_path: &Path) -> Option<SidecarData> {
 /// Writes sidecar data as a YAML file next to the image.
 ///
 /// Creates `<image_stem>.yaml` in the same directory as the image.
-pub fn write_sidecar(image_path: &Path, data: &SidecarData) -> Result<PathBuf, String> {
+pub fn write_sidecar(image_path: &Path, data: &SidecarData) -> AppResult<PathBuf> {
     let sidecar_path = image_path.with_extension("yaml");
-    let yaml =
-        serde_yaml::to_string(data).map_err(|e| format!("YAML serialization error: {}", e))?;
-    std::fs::write(&sidecar_path, yaml).map_err(|e| format!("Failed to write sidecar: {}", e))?;
+    let yaml = serde_yaml::to_string(data)
+        .map_err(|e| AppError::Internal(format!("YAML serialization error: {}", e)))?;
+    std::fs::write(&sidecar_path, yaml)
+        .map_err(|e| AppError::from(e).context("Failed to write sidecar"))?;
     Ok(sidecar_path)
 }
 
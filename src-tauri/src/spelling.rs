@@ -0,0 +1,210 @@
+//! "Did you mean" suggestions for searches that matched nothing.
+//!
+//! The vocabulary is the library's own tags plus the individual words inside
+//! them, weighted by how many images use them. Query words are corrected
+//! against it by edit distance (transpositions count as one edit, as in
+//! SQLite's spellfix), so `blnde hiar` can come back as `blonde hair`.
+
+use std::collections::HashMap;
+
+/// Words shorter than this are never corrected; two letters match too much.
+const MIN_WORD_LEN: usize = 3;
+
+#[derive(Debug, Default)]
+pub struct Vocabulary {
+    words: HashMap<String, u32>,
+    phrases: Vec<(String, u32)>,
+}
+
+impl Vocabulary {
+    /// Adds a tag used by `count` images, along with each word inside it.
+    pub fn add_phrase(&mut self, phrase: &str, count: u32) {
+        let words = normalize_words(phrase);
+        if words.is_empty() {
+            return;
+        }
+        for word in &words {
+            if word.chars().count() >= MIN_WORD_LEN {
+                *self.words.entry(word.clone()).or_insert(0) += count;
+            }
+        }
+        if words.len() > 1 {
+            self.phrases.push((words.join(" "), count));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Up to `limit` corrected queries, best first. Empty when every word is
+    /// already known or nothing is close enough.
+    pub fn suggest(&self, query: &str, limit: usize) -> Vec<String> {
+        let words = normalize_words(query);
+        if words.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+        let original = words.join(" ");
+
+        let mut suggestions: Vec<String> = Vec::new();
+        let push = |candidate: String, suggestions: &mut Vec<String>| {
+            if candidate != original && !suggestions.contains(&candidate) {
+                suggestions.push(candidate);
+            }
+        };
+
+        let per_word: Vec<Vec<&str>> = words
+            .iter()
+            .map(|word| self.closest_words(word, limit))
+            .collect();
+        if per_word.iter().any(|candidates| !candidates.is_empty()) {
+            let corrected: Vec<&str> = words
+                .iter()
+                .zip(&per_word)
+                .map(|(word, candidates)| candidates.first().copied().unwrap_or(word))
+                .collect();
+            push(corrected.join(" "), &mut suggestions);
+        }
+
+        for phrase in self.closest_phrases(&original, limit) {
+            push(phrase.to_string(), &mut suggestions);
+        }
+
+        // Single-word queries also get the runner-up spellings.
+        if let [candidates] = per_word.as_slice() {
+            for candidate in candidates.iter().skip(1) {
+                push(candidate.to_string(), &mut suggestions);
+            }
+        }
+
+        suggestions.truncate(limit);
+        suggestions
+    }
+
+    fn closest_words(&self, word: &str, limit: usize) -> Vec<&str> {
+        if self.words.contains_key(word) {
+            return Vec::new();
+        }
+        let max = max_edits(word.chars().count());
+        if max == 0 {
+            return Vec::new();
+        }
+        let mut ranked: Vec<(usize, u32, &str)> = self
+            .words
+            .iter()
+            .filter_map(|(candidate, count)| {
+                let distance = bounded_distance(word, candidate, max)?;
+                Some((distance, *count, candidate.as_str()))
+            })
+            .collect();
+        ranked.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(b.2)));
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, word)| word)
+            .collect()
+    }
+
+    fn closest_phrases(&self, query: &str, limit: usize) -> Vec<&str> {
+        let max = max_edits(query.chars().count());
+        if max == 0 || !query.contains(' ') {
+            return Vec::new();
+        }
+        let mut ranked: Vec<(usize, u32, &str)> = self
+            .phrases
+            .iter()
+            .filter_map(|(phrase, count)| {
+                let distance = bounded_distance(query, phrase, max)?;
+                Some((distance, *count, phrase.as_str()))
+            })
+            .collect();
+        ranked.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(b.2)));
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, phrase)| phrase)
+            .collect()
+    }
+}
+
+/// Lowercased words, keeping letters, digits and `_` like the FTS tokenizer.
+fn normalize_words(text: &str) -> Vec<String> {
+    text.split(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How many edits a word of `len` characters may be away from a suggestion.
+fn max_edits(len: usize) -> usize {
+    match len {
+        0..=2 => 0,
+        3..=5 => 1,
+        6..=9 => 2,
+        _ => 3,
+    }
+}
+
+/// Optimal string alignment distance, or `None` once it exceeds `max`.
+pub fn bounded_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    let width = b.len() + 1;
+    let mut before_prev = vec![0usize; width];
+    let mut prev: Vec<usize> = (0..width).collect();
+    let mut current = vec![0usize; width];
+    for i in 1..=a.len() {
+        current[0] = i;
+        let mut row_min = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut value = (prev[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                value = value.min(before_prev[j - 2] + 1);
+            }
+            current[j] = value;
+            row_min = row_min.min(value);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut before_prev, &mut prev);
+        std::mem::swap(&mut prev, &mut current);
+    }
+    let distance = prev[b.len()];
+    (distance <= max).then_some(distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_counts_transpositions_as_one_edit() {
+        assert_eq!(bounded_distance("cta", "cat", 2), Some(1));
+        assert_eq!(bounded_distance("blnde", "blonde", 2), Some(1));
+        assert_eq!(bounded_distance("kitten", "sitting", 3), Some(3));
+        assert_eq!(bounded_distance("kitten", "sitting", 2), None);
+    }
+
+    #[test]
+    fn suggests_corrections_from_tags_and_their_words() {
+        let mut vocabulary = Vocabulary::default();
+        vocabulary.add_phrase("blonde hair", 40);
+        vocabulary.add_phrase("long hair", 25);
+        vocabulary.add_phrase("cat", 10);
+        vocabulary.add_phrase("car", 3);
+
+        assert_eq!(vocabulary.suggest("Blnde hiar", 3), vec!["blonde hair"]);
+        assert_eq!(vocabulary.suggest("cay", 3), vec!["cat", "car"]);
+        assert_eq!(vocabulary.suggest("long hair", 3), Vec::<String>::new());
+        assert_eq!(vocabulary.suggest("xq", 3), Vec::<String>::new());
+        assert_eq!(vocabulary.suggest("zzzzzz", 3), Vec::<String>::new());
+    }
+}
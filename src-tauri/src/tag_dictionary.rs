@@ -0,0 +1,188 @@
+//! Danbooru-style tag dictionaries for autocomplete.
+//!
+//! Accepts the CSV layout used by the a1111 tag-autocomplete extension:
+//! `tag,category,post_count[,"alias1,alias2"]`, with or without a header row.
+//! Tags are stored the way prompts are tokenized (lowercase, underscores as
+//! spaces) so they line up with tags extracted from the library.
+
+use crate::error::{AppError, AppResult};
+use std::time::Duration;
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+/// Refuse dictionaries beyond this size; the common danbooru dumps are ~10 MB.
+const MAX_DICTIONARY_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DictionaryTag {
+    pub tag: String,
+    pub category: i64,
+    pub post_count: i64,
+}
+
+/// Display name for a danbooru (0-5) or e621 (6-8) category id.
+pub fn category_name(category: i64) -> &'static str {
+    match category {
+        0 => "general",
+        1 => "artist",
+        3 => "copyright",
+        4 => "character",
+        5 => "meta",
+        6 => "species",
+        7 => "invalid",
+        8 => "lore",
+        _ => "other",
+    }
+}
+
+/// `long_hair` -> `long hair`; short emoticon tags such as `^_^` keep their underscores.
+fn normalize_tag(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let lowered = trimmed.to_lowercase();
+    if lowered.chars().count() <= 3 {
+        return Some(lowered);
+    }
+    Some(lowered.replace('_', " "))
+}
+
+/// Parses a dictionary CSV. Returns the entries (deduplicated, first wins)
+/// and the number of rows that could not be read.
+pub fn parse_csv(bytes: &[u8]) -> (Vec<DictionaryTag>, usize) {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(bytes);
+
+    let mut seen = std::collections::HashSet::new();
+    let mut entries = Vec::new();
+    let mut skipped = 0usize;
+    for (index, row) in reader.records().enumerate() {
+        let Ok(row) = row else {
+            skipped += 1;
+            continue;
+        };
+        let category = match row.get(1).map(str::trim) {
+            None | Some("") => 0,
+            Some(value) => match value.parse::<i64>() {
+                Ok(category) => category,
+                Err(_) => {
+                    // A non-numeric category on the first row is a header.
+                    if index > 0 {
+                        skipped += 1;
+                    }
+                    continue;
+                }
+            },
+        };
+        let post_count = row
+            .get(2)
+            .and_then(|count| count.trim().parse::<i64>().ok())
+            .unwrap_or(0);
+        let Some(tag) = row.get(0).and_then(normalize_tag) else {
+            skipped += 1;
+            continue;
+        };
+        if seen.insert(tag.clone()) {
+            entries.push(DictionaryTag {
+                tag,
+                category,
+                post_count,
+            });
+        }
+    }
+    (entries, skipped)
+}
+
+/// Reads a dictionary from a local path or downloads it from an http(s) URL.
+pub async fn read_source(source: &str) -> AppResult<Vec<u8>> {
+    let source = source.trim();
+    if source.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Tag dictionary path or URL is required".to_string(),
+        ));
+    }
+
+    if !(source.starts_with("http://") || source.starts_with("https://")) {
+        let bytes = std::fs::read(source).map_err(|error| {
+            AppError::from(error).context(format!("Failed to read tag dictionary {}", source))
+        })?;
+        if bytes.len() > MAX_DICTIONARY_BYTES {
+            return Err(AppError::InvalidInput(format!(
+                "Tag dictionary is larger than {} MB",
+                MAX_DICTIONARY_BYTES / (1024 * 1024)
+            )));
+        }
+        return Ok(bytes);
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|error| AppError::Internal(format!("Failed to build HTTP client: {}", error)))?;
+    let response = client
+        .get(source)
+        .send()
+        .await
+        .map_err(|error| AppError::Io(format!("Tag dictionary download failed: {}", error)))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(AppError::Io(format!(
+            "Tag dictionary download failed: HTTP {}",
+            status
+        )));
+    }
+    if response
+        .content_length()
+        .is_some_and(|length| length as usize > MAX_DICTIONARY_BYTES)
+    {
+        return Err(AppError::InvalidInput(format!(
+            "Tag dictionary is larger than {} MB",
+            MAX_DICTIONARY_BYTES / (1024 * 1024)
+        )));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|error| AppError::Io(format!("Tag dictionary download failed: {}", error)))?;
+    Ok(bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_autocomplete_csv_with_aliases_and_header() {
+        let csv = "name,category,post_count,aliases\n\
+                   long_hair,0,4500000,\"longhair,long_hairs\"\n\
+                   hatsune_miku,4,120000,\n\
+                   ^_^,0,3000\n\
+                   Long_Hair,0,1,\n\
+                   ,0,5\n";
+        let (entries, skipped) = parse_csv(csv.as_bytes());
+        assert_eq!(
+            entries,
+            vec![
+                DictionaryTag {
+                    tag: "long hair".to_string(),
+                    category: 0,
+                    post_count: 4_500_000,
+                },
+                DictionaryTag {
+                    tag: "hatsune miku".to_string(),
+                    category: 4,
+                    post_count: 120_000,
+                },
+                DictionaryTag {
+                    tag: "^_^".to_string(),
+                    category: 0,
+                    post_count: 3_000,
+                },
+            ]
+        );
+        assert_eq!(skipped, 1);
+        assert_eq!(category_name(4), "character");
+    }
+}
@@ -0,0 +1,395 @@
+//! Registry of long-running background work.
+//!
+//! Scans, thumbnail warmup/pre-cache, auto-tagging, Forge batches and metadata
+//! exports register a task here and report progress through their
+//! [`TaskHandle`]. Workers call [`TaskHandle::checkpoint`] between units of
+//! work; that is where pause and cancel requests from the activity panel (or
+//! the tray's global pause) take effect.
+
+use crate::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Finished tasks kept around so the panel can show recent results.
+const MAX_FINISHED_TASKS: usize = 20;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    Scan,
+    ThumbnailWarmup,
+    ThumbnailPrecache,
+    ForgeBatch,
+    Autotag,
+    Ocr,
+    PromptClusters,
+    FocalPoints,
+    Export,
+}
+
+impl TaskKind {
+    /// Forge batches and exports are started explicitly by the user, so the
+    /// tray's "Pause background work" toggle leaves them alone.
+    fn follows_background_pause(self) -> bool {
+        !matches!(self, TaskKind::ForgeBatch | TaskKind::Export)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    Paused,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl TaskStatus {
+    fn is_finished(self) -> bool {
+        matches!(
+            self,
+            TaskStatus::Completed | TaskStatus::Cancelled | TaskStatus::Failed
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskSnapshot {
+    pub id: u64,
+    pub kind: TaskKind,
+    pub label: String,
+    pub status: TaskStatus,
+    pub current: usize,
+    pub total: usize,
+    pub message: Option<String>,
+    pub pause_requested: bool,
+    pub cancel_requested: bool,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+struct TaskDetails {
+    status: TaskStatus,
+    message: Option<String>,
+    finished_at: Option<DateTime<Utc>>,
+}
+
+struct TaskEntry {
+    id: u64,
+    kind: TaskKind,
+    label: String,
+    started_at: DateTime<Utc>,
+    current: AtomicUsize,
+    total: AtomicUsize,
+    pause_requested: AtomicBool,
+    cancel_requested: AtomicBool,
+    background_paused: Arc<AtomicBool>,
+    details: Mutex<TaskDetails>,
+}
+
+impl TaskEntry {
+    fn status(&self) -> TaskStatus {
+        self.details
+            .lock()
+            .map(|details| details.status)
+            .unwrap_or(TaskStatus::Failed)
+    }
+
+    fn set_status(&self, status: TaskStatus) {
+        if let Ok(mut details) = self.details.lock() {
+            if details.status.is_finished() {
+                return;
+            }
+            details.status = status;
+            if status.is_finished() {
+                details.finished_at = Some(Utc::now());
+            }
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.pause_requested.load(Ordering::Acquire)
+            || (self.kind.follows_background_pause()
+                && self.background_paused.load(Ordering::Acquire))
+    }
+
+    fn checkpoint(&self) -> bool {
+        let mut waited = false;
+        loop {
+            if self.cancel_requested.load(Ordering::Acquire) {
+                self.set_status(TaskStatus::Cancelled);
+                return false;
+            }
+            if !self.is_paused() {
+                break;
+            }
+            if !waited {
+                self.set_status(TaskStatus::Paused);
+                waited = true;
+            }
+            std::thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+        if waited {
+            self.set_status(TaskStatus::Running);
+        }
+        true
+    }
+
+    fn snapshot(&self) -> TaskSnapshot {
+        let (status, message, finished_at) = match self.details.lock() {
+            Ok(details) => (
+                details.status,
+                details.message.clone(),
+                details.finished_at.map(|value| value.to_rfc3339()),
+            ),
+            Err(_) => (TaskStatus::Failed, None, None),
+        };
+        TaskSnapshot {
+            id: self.id,
+            kind: self.kind,
+            label: self.label.clone(),
+            status,
+            current: self.current.load(Ordering::Relaxed),
+            total: self.total.load(Ordering::Relaxed),
+            message,
+            pause_requested: self.pause_requested.load(Ordering::Acquire),
+            cancel_requested: self.cancel_requested.load(Ordering::Acquire),
+            started_at: self.started_at.to_rfc3339(),
+            finished_at,
+        }
+    }
+}
+
+pub struct TaskRegistry {
+    next_id: AtomicU64,
+    background_paused: Arc<AtomicBool>,
+    tasks: Mutex<Vec<Arc<TaskEntry>>>,
+}
+
+impl TaskRegistry {
+    /// `background_paused` is the shared tray/UI pause flag.
+    pub fn new(background_paused: Arc<AtomicBool>) -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            background_paused,
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Registers a running task; it finishes when the handle is dropped.
+    pub fn start(&self, kind: TaskKind, label: impl Into<String>) -> TaskHandle {
+        let entry = Arc::new(TaskEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            kind,
+            label: label.into(),
+            started_at: Utc::now(),
+            current: AtomicUsize::new(0),
+            total: AtomicUsize::new(0),
+            pause_requested: AtomicBool::new(false),
+            cancel_requested: AtomicBool::new(false),
+            background_paused: self.background_paused.clone(),
+            details: Mutex::new(TaskDetails {
+                status: TaskStatus::Running,
+                message: None,
+                finished_at: None,
+            }),
+        });
+
+        if let Ok(mut tasks) = self.tasks.lock() {
+            prune_finished(&mut tasks);
+            tasks.push(entry.clone());
+        }
+        TaskHandle { entry }
+    }
+
+    /// Active tasks first, then recently finished ones, newest first.
+    pub fn list(&self) -> Vec<TaskSnapshot> {
+        let mut snapshots: Vec<TaskSnapshot> = self
+            .tasks
+            .lock()
+            .map(|tasks| tasks.iter().map(|entry| entry.snapshot()).collect())
+            .unwrap_or_default();
+        snapshots.sort_by(|a, b| {
+            a.status
+                .is_finished()
+                .cmp(&b.status.is_finished())
+                .then(b.id.cmp(&a.id))
+        });
+        snapshots
+    }
+
+    pub fn set_paused(&self, id: u64, paused: bool) -> AppResult<()> {
+        let entry = self.active_entry(id)?;
+        entry.pause_requested.store(paused, Ordering::Release);
+        Ok(())
+    }
+
+    pub fn cancel(&self, id: u64) -> AppResult<()> {
+        let entry = self.active_entry(id)?;
+        entry.cancel_requested.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Drops finished tasks from the list.
+    pub fn clear_finished(&self) {
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.retain(|entry| !entry.status().is_finished());
+        }
+    }
+
+    fn active_entry(&self, id: u64) -> AppResult<Arc<TaskEntry>> {
+        let entry = self
+            .tasks
+            .lock()
+            .map_err(|_| AppError::Internal("Failed to read background tasks".to_string()))?
+            .iter()
+            .find(|entry| entry.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Background task not found: {}", id)))?;
+        if entry.status().is_finished() {
+            return Err(AppError::InvalidInput(format!(
+                "Background task {} has already finished",
+                id
+            )));
+        }
+        Ok(entry)
+    }
+}
+
+fn prune_finished(tasks: &mut Vec<Arc<TaskEntry>>) {
+    let finished = tasks
+        .iter()
+        .filter(|entry| entry.status().is_finished())
+        .count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED_TASKS - 1);
+    tasks.retain(|entry| {
+        if excess > 0 && entry.status().is_finished() {
+            excess -= 1;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+/// Worker-side view of a registered task.
+///
+/// Dropping the handle marks the task completed unless it was cancelled at a
+/// checkpoint or failed explicitly.
+pub struct TaskHandle {
+    entry: Arc<TaskEntry>,
+}
+
+impl TaskHandle {
+    pub fn id(&self) -> u64 {
+        self.entry.id
+    }
+
+    pub fn set_progress(&self, current: usize, total: usize) {
+        self.entry.current.store(current, Ordering::Relaxed);
+        self.entry.total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn set_message(&self, message: impl Into<String>) {
+        if let Ok(mut details) = self.entry.details.lock() {
+            details.message = Some(message.into());
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.entry.cancel_requested.load(Ordering::Acquire)
+    }
+
+    /// Cancellation check that can move onto the blocking pool.
+    pub fn cancel_check(&self) -> impl Fn() -> bool + Send + 'static {
+        let entry = self.entry.clone();
+        move || entry.cancel_requested.load(Ordering::Acquire)
+    }
+
+    /// Blocks while the task (or background work as a whole) is paused.
+    /// Returns false once cancellation was requested; the worker should stop.
+    pub fn checkpoint(&self) -> bool {
+        self.entry.checkpoint()
+    }
+
+    /// Async variant of [`checkpoint`](Self::checkpoint); waits on the
+    /// blocking pool only while actually paused.
+    pub async fn checkpoint_async(&self) -> bool {
+        if self.is_cancelled() || !self.entry.is_paused() {
+            return self.entry.checkpoint();
+        }
+        let entry = self.entry.clone();
+        tauri::async_runtime::spawn_blocking(move || entry.checkpoint())
+            .await
+            .unwrap_or(false)
+    }
+
+    pub fn fail(&self, message: impl Into<String>) {
+        self.set_message(message);
+        self.entry.set_status(TaskStatus::Failed);
+    }
+}
+
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        self.entry.set_status(TaskStatus::Completed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropped_handle_completes_and_cancel_stops_checkpoint() {
+        let registry = TaskRegistry::new(Arc::new(AtomicBool::new(false)));
+        let scan = registry.start(TaskKind::Scan, "/library");
+        let forge = registry.start(TaskKind::ForgeBatch, "Forge batch");
+        scan.set_progress(3, 10);
+
+        registry.cancel(forge.id()).expect("forge task is active");
+        assert!(!forge.checkpoint());
+        assert!(scan.checkpoint());
+        let scan_id = scan.id();
+        drop(scan);
+        drop(forge);
+
+        let tasks = registry.list();
+        let scan_snapshot = tasks.iter().find(|task| task.id == scan_id).unwrap();
+        assert_eq!(scan_snapshot.status, TaskStatus::Completed);
+        assert_eq!((scan_snapshot.current, scan_snapshot.total), (3, 10));
+        assert!(tasks
+            .iter()
+            .any(|task| task.status == TaskStatus::Cancelled));
+        assert!(registry.cancel(scan_id).is_err());
+    }
+
+    #[test]
+    fn background_pause_skips_forge_batches() {
+        let background_paused = Arc::new(AtomicBool::new(true));
+        let registry = TaskRegistry::new(background_paused);
+        let warmup = registry.start(TaskKind::ThumbnailWarmup, "warmup");
+        let forge = registry.start(TaskKind::ForgeBatch, "forge");
+
+        assert!(warmup.entry.is_paused());
+        assert!(!forge.entry.is_paused());
+        assert!(forge.checkpoint());
+    }
+
+    #[test]
+    fn finished_tasks_are_pruned() {
+        let registry = TaskRegistry::new(Arc::new(AtomicBool::new(false)));
+        for index in 0..MAX_FINISHED_TASKS + 5 {
+            drop(registry.start(TaskKind::Scan, format!("scan {}", index)));
+        }
+        let active = registry.start(TaskKind::Scan, "active");
+        let tasks = registry.list();
+        assert_eq!(tasks.len(), MAX_FINISHED_TASKS);
+        assert_eq!(tasks[0].id, active.id());
+    }
+}
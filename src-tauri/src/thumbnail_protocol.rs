@@ -0,0 +1,146 @@
+//! `thumb://` protocol serving cached thumbnails to the gallery.
+//!
+//! The grid used to load thumbnails through the generic asset protocol,
+//! which re-reads every file with no validators. Here the webview gets an
+//! `ETag` built from the file's size and modification time and answers
+//! later requests with `304 Not Modified` without touching the file again.
+//! Thumbnails rewritten in place (edited sources, `regenerate_thumbnails`)
+//! get a new tag, so the grid never shows a stale one.
+//!
+//! URLs name the cache file only, e.g. `thumb://localhost/<hash>.jpg`
+//! (`http://thumb.localhost/<hash>.jpg` on Windows); nothing outside the
+//! thumbnail cache can be requested.
+
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use tauri::http::{header, Request, Response, StatusCode};
+
+pub const THUMBNAIL_SCHEME: &str = "thumb";
+
+/// Revalidated on every use; a matching `ETag` makes that a cheap 304.
+const CACHE_CONTROL: &str = "no-cache";
+
+pub fn respond(cache_dir: &Path, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let name = request.uri().path().trim_start_matches('/');
+    if !is_thumbnail_name(name) {
+        return status_only(StatusCode::BAD_REQUEST);
+    }
+    let path = cache_dir.join(name);
+    let Ok(metadata) = std::fs::metadata(&path) else {
+        return status_only(StatusCode::NOT_FOUND);
+    };
+    let etag = entity_tag(&metadata);
+
+    let if_none_match = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok());
+    if if_none_match.is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == etag)) {
+        return Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, &etag)
+            .header(header::CACHE_CONTROL, CACHE_CONTROL)
+            .body(Vec::new())
+            .unwrap_or_else(|_| status_only(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    let body = match std::fs::read(&path) {
+        Ok(body) => body,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            return status_only(StatusCode::NOT_FOUND);
+        }
+        Err(error) => {
+            log::warn!("Failed to serve thumbnail {}: {}", path.display(), error);
+            return status_only(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "image/jpeg")
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, CACHE_CONTROL)
+        // Lets the grid sample thumbnail pixels on a canvas.
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(body)
+        .unwrap_or_else(|_| status_only(StatusCode::INTERNAL_SERVER_ERROR))
+}
+
+/// Cache files are a hex hash plus `.jpg`, which also rules out `..` and
+/// path separators.
+fn is_thumbnail_name(name: &str) -> bool {
+    name.strip_suffix(".jpg")
+        .is_some_and(|stem| !stem.is_empty() && stem.bytes().all(|byte| byte.is_ascii_hexdigit()))
+}
+
+fn entity_tag(metadata: &std::fs::Metadata) -> String {
+    let modified_ns = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    format!("\"{:x}-{:x}\"", metadata.len(), modified_ns)
+}
+
+fn status_only(status: StatusCode) -> Response<Vec<u8>> {
+    let mut response = Response::new(Vec::new());
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(cache_dir: &Path, path: &str, if_none_match: Option<&str>) -> Response<Vec<u8>> {
+        let mut request = Request::builder().uri(format!("thumb://localhost/{}", path));
+        if let Some(tag) = if_none_match {
+            request = request.header(header::IF_NONE_MATCH, tag);
+        }
+        respond(cache_dir, &request.body(Vec::new()).unwrap())
+    }
+
+    #[test]
+    fn thumbnails_are_served_with_etags_and_revalidated() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("thumb_protocol_test_{}", std::process::id()));
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        std::fs::write(cache_dir.join("0a1b2c.jpg"), b"jpeg bytes").unwrap();
+
+        let response = get(&cache_dir, "0a1b2c.jpg", None);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), b"jpeg bytes");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/jpeg");
+        let etag = response.headers()[header::ETAG]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let revalidated = get(
+            &cache_dir,
+            "0a1b2c.jpg",
+            Some(&format!("\"old\", {}", etag)),
+        );
+        assert_eq!(revalidated.status(), StatusCode::NOT_MODIFIED);
+        assert!(revalidated.body().is_empty());
+        assert_eq!(
+            get(&cache_dir, "0a1b2c.jpg", Some("\"old\"")).status(),
+            StatusCode::OK
+        );
+
+        assert_eq!(
+            get(&cache_dir, "ffff.jpg", None).status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            get(&cache_dir, "..%2Fsecret.jpg", None).status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            get(&cache_dir, "0a1b2c.png", None).status(),
+            StatusCode::BAD_REQUEST
+        );
+
+        let _ = std::fs::remove_dir_all(cache_dir);
+    }
+}
@@ -0,0 +1,78 @@
+//! Horizontal strips of images scaled to a common height.
+//!
+//! Used for storyboards and video-editor timelines built from a selected
+//! sequence: frames keep their aspect ratio and sit edge to edge in
+//! selection order, and the x offset of each one is reported so tools can
+//! slice the strip back apart.
+
+use image::{imageops::FilterType, DynamicImage, RgbImage};
+
+/// Widest strip we build; also JPEG's hard limit.
+pub const MAX_STRIP_WIDTH: u32 = 65_535;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StripOffset {
+    pub x: u32,
+    pub width: u32,
+}
+
+/// Width a frame takes in a strip of the given height, at least 1px.
+pub fn frame_width(source_width: u32, source_height: u32, height: u32) -> u32 {
+    if source_height == 0 {
+        return 1;
+    }
+    ((source_width as f64 * height as f64 / source_height as f64).round() as u32).max(1)
+}
+
+/// Scales one frame for the strip. Split out so callers can resize frames in
+/// parallel and drop the full-size decode straight away.
+pub fn scale_frame(image: &DynamicImage, height: u32) -> RgbImage {
+    let width = frame_width(image.width(), image.height(), height);
+    image
+        .resize_exact(width, height, FilterType::Triangle)
+        .to_rgb8()
+}
+
+/// Lays pre-scaled frames out left to right; every frame must already be
+/// `height` pixels tall.
+pub fn compose(frames: &[RgbImage], height: u32) -> (RgbImage, Vec<StripOffset>) {
+    let mut offsets = Vec::with_capacity(frames.len());
+    let mut x = 0u32;
+    for frame in frames {
+        offsets.push(StripOffset {
+            x,
+            width: frame.width(),
+        });
+        x += frame.width();
+    }
+    let mut strip = RgbImage::new(x.max(1), height);
+    for (frame, offset) in frames.iter().zip(&offsets) {
+        image::imageops::replace(&mut strip, frame, offset.x as i64, 0);
+    }
+    (strip, offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    #[test]
+    fn compose_places_scaled_frames_edge_to_edge() {
+        let wide = DynamicImage::ImageRgb8(RgbImage::from_pixel(200, 100, Rgb([255, 0, 0])));
+        let tall = DynamicImage::ImageRgb8(RgbImage::from_pixel(50, 100, Rgb([0, 0, 255])));
+        let frames = [scale_frame(&wide, 40), scale_frame(&tall, 40)];
+        let (strip, offsets) = compose(&frames, 40);
+
+        assert_eq!(strip.dimensions(), (100, 40));
+        assert_eq!(
+            offsets,
+            vec![
+                StripOffset { x: 0, width: 80 },
+                StripOffset { x: 80, width: 20 }
+            ]
+        );
+        assert_eq!(strip.get_pixel(79, 20).0, [255, 0, 0]);
+        assert_eq!(strip.get_pixel(80, 20).0, [0, 0, 255]);
+    }
+}
@@ -0,0 +1,107 @@
+//! System tray icon for background mode.
+//!
+//! When "close to tray" is enabled, closing the main window only hides it so
+//! scans and thumbnail warmup keep running; the tray menu brings it back,
+//! starts a rescan of the known library roots or pauses background work.
+
+use crate::AppState;
+use std::sync::atomic::Ordering;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{App, AppHandle, Emitter, Manager};
+
+const MAIN_WINDOW_LABEL: &str = "main";
+const MENU_SHOW: &str = "tray-show";
+const MENU_RESCAN: &str = "tray-rescan";
+const MENU_PAUSE: &str = "tray-pause";
+const MENU_QUIT: &str = "tray-quit";
+
+/// Keeps the pause toggle reachable so commands can mirror UI changes.
+struct TrayState {
+    pause_item: CheckMenuItem<tauri::Wry>,
+}
+
+pub fn setup(app: &App) -> tauri::Result<()> {
+    let paused = app
+        .state::<AppState>()
+        .background_paused
+        .load(Ordering::Acquire);
+
+    let show_item = MenuItem::with_id(app, MENU_SHOW, "Show ForgeMetaLink", true, None::<&str>)?;
+    let rescan_item = MenuItem::with_id(app, MENU_RESCAN, "Rescan now", true, None::<&str>)?;
+    let pause_item = CheckMenuItem::with_id(
+        app,
+        MENU_PAUSE,
+        "Pause background work",
+        true,
+        paused,
+        None::<&str>,
+    )?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let quit_item = MenuItem::with_id(app, MENU_QUIT, "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &show_item,
+            &rescan_item,
+            &pause_item,
+            &separator,
+            &quit_item,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id("main")
+        .tooltip("ForgeMetaLink")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            MENU_SHOW => show_main_window(app),
+            MENU_RESCAN => crate::commands::rescan_library_roots(app),
+            MENU_PAUSE => {
+                let state = app.state::<AppState>();
+                let paused = !state.background_paused.load(Ordering::Acquire);
+                set_background_paused(app, paused);
+            }
+            MENU_QUIT => app.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    app.manage(TrayState { pause_item });
+    Ok(())
+}
+
+/// Updates the shared pause flag, the tray checkbox and the UI.
+pub fn set_background_paused(app: &AppHandle, paused: bool) {
+    let state = app.state::<AppState>();
+    state.background_paused.store(paused, Ordering::Release);
+    if let Some(tray) = app.try_state::<TrayState>() {
+        let _ = tray.pause_item.set_checked(paused);
+    }
+    let _ = app.emit("background-paused-changed", paused);
+    log::info!(
+        "Background work {}",
+        if paused { "paused" } else { "resumed" }
+    );
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
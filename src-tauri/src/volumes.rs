@@ -0,0 +1,109 @@
+//! Availability of library roots on removable or network drives.
+//!
+//! Roots are re-checked at most once per interval so gallery queries don't
+//! stat every root on each page; a disconnected network share can take a
+//! while to answer.
+
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct VolumeStatus {
+    pub root: String,
+    pub online: bool,
+}
+
+#[derive(Default)]
+struct Snapshot {
+    checked_at: Option<Instant>,
+    statuses: Vec<VolumeStatus>,
+}
+
+#[derive(Default)]
+pub struct VolumeTracker {
+    snapshot: Mutex<Snapshot>,
+}
+
+impl VolumeTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Statuses for `roots`, re-checked when older than `max_age` or when the
+    /// root list changed. The flag is true when a check actually ran.
+    pub fn check(&self, roots: &[String], max_age: Duration) -> (Vec<VolumeStatus>, bool) {
+        let Ok(mut snapshot) = self.snapshot.lock() else {
+            return (Vec::new(), false);
+        };
+        let fresh = snapshot
+            .checked_at
+            .is_some_and(|checked_at| checked_at.elapsed() < max_age);
+        let same_roots = snapshot.statuses.len() == roots.len()
+            && snapshot
+                .statuses
+                .iter()
+                .zip(roots)
+                .all(|(status, root)| status.root == *root);
+        if fresh && same_roots {
+            return (snapshot.statuses.clone(), false);
+        }
+
+        let statuses: Vec<VolumeStatus> = roots
+            .iter()
+            .map(|root| VolumeStatus {
+                root: root.clone(),
+                online: Path::new(root).is_dir(),
+            })
+            .collect();
+        for status in &statuses {
+            let previous = snapshot
+                .statuses
+                .iter()
+                .find(|previous| previous.root == status.root);
+            if previous.is_some_and(|previous| previous.online != status.online)
+                || (previous.is_none() && !status.online)
+            {
+                log::info!(
+                    "Library root {} is {}",
+                    status.root,
+                    if status.online {
+                        "back online"
+                    } else {
+                        "offline"
+                    }
+                );
+            }
+        }
+        snapshot.checked_at = Some(Instant::now());
+        snapshot.statuses = statuses.clone();
+        (statuses, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_roots_offline_and_caches_results() {
+        let online = std::env::temp_dir().to_string_lossy().to_string();
+        let offline = std::env::temp_dir()
+            .join("forge_meta_link_unplugged_drive")
+            .to_string_lossy()
+            .to_string();
+        let roots = vec![online.clone(), offline.clone()];
+        let tracker = VolumeTracker::new();
+
+        let (statuses, checked) = tracker.check(&roots, Duration::from_secs(60));
+        assert!(checked);
+        assert!(statuses[0].online);
+        assert!(!statuses[1].online);
+
+        let (_, checked) = tracker.check(&roots, Duration::from_secs(60));
+        assert!(!checked);
+        let (_, checked) = tracker.check(&roots[..1], Duration::from_secs(60));
+        assert!(checked);
+    }
+}
@@ -0,0 +1,481 @@
+//! Keeps scanned folders indexed as new images land in them.
+//!
+//! New files are noticed through OS change notifications (`notify`), so an
+//! idle folder costs nothing. Network shares are the exception: changes
+//! made by other machines rarely produce notifications there, so those
+//! folders are walked every [`NETWORK_POLL_INTERVAL`] instead, as are
+//! folders the OS refuses to watch. Either way a new file is only reported
+//! once its size held steady across two checks, so a PNG still being
+//! written is never indexed half-finished. The Forge outputs watcher runs
+//! on the same machinery. The folder list lives in `watch_folders.json` in
+//! the app data directory.
+
+use crate::scanner;
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+/// How often files that are still being written are measured again.
+const SETTLE_INTERVAL: Duration = Duration::from_secs(2);
+/// How often a network share is walked for new files.
+pub const NETWORK_POLL_INTERVAL: Duration = Duration::from_secs(60);
+const STOP_CHECK: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchFolderSettings {
+    pub folders: Vec<String>,
+}
+
+impl WatchFolderSettings {
+    /// Adds `folder` unless it or a folder above it is already watched;
+    /// watched folders inside it are folded in. Returns false when nothing
+    /// changed.
+    pub fn add(&mut self, folder: &str) -> bool {
+        let candidate = Path::new(folder);
+        if self
+            .folders
+            .iter()
+            .any(|watched| candidate.starts_with(Path::new(watched)))
+        {
+            return false;
+        }
+        self.folders
+            .retain(|watched| !Path::new(watched).starts_with(candidate));
+        self.folders.push(folder.to_string());
+        true
+    }
+
+    /// Returns false when `folder` wasn't watched.
+    pub fn remove(&mut self, folder: &str) -> bool {
+        let before = self.folders.len();
+        self.folders
+            .retain(|watched| Path::new(watched) != Path::new(folder));
+        self.folders.len() != before
+    }
+}
+
+/// New files waiting for their size to settle.
+#[derive(Debug, Default)]
+pub struct NewFileTracker {
+    /// Candidates and the size they had at the last check, `None` before
+    /// the first one.
+    pending: HashMap<PathBuf, Option<u64>>,
+}
+
+impl NewFileTracker {
+    /// Starts following `path` if it is an image. A new directory (e.g.
+    /// one moved in) has its images followed instead.
+    pub fn note(&mut self, path: &Path) {
+        if path.is_dir() {
+            for entry in WalkDir::new(path).into_iter().filter_map(Result::ok) {
+                if entry.file_type().is_file() {
+                    self.note(entry.path());
+                }
+            }
+        } else if scanner::is_supported_image_path(path) {
+            self.pending.entry(path.to_path_buf()).or_insert(None);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Followed images that have stopped growing. Files that went away
+    /// are forgotten.
+    pub fn settle(&mut self) -> Vec<PathBuf> {
+        let mut ready = Vec::new();
+        self.pending.retain(|path, last_size| {
+            let Ok(size) = std::fs::metadata(path).map(|metadata| metadata.len()) else {
+                return false;
+            };
+            if size > 0 && *last_size == Some(size) {
+                ready.push(path.clone());
+                return false;
+            }
+            *last_size = Some(size);
+            true
+        });
+        ready.sort();
+        ready
+    }
+}
+
+/// How new files under a watched folder are discovered.
+enum Discovery {
+    Events {
+        // Dropping the watcher ends the notifications.
+        _watcher: notify::RecommendedWatcher,
+        events: Receiver<notify::Result<notify::Event>>,
+    },
+    /// Walks the folder, comparing against every file seen before.
+    Poll { next_walk: Instant },
+    /// The folder is missing, e.g. on an unplugged drive.
+    Offline,
+}
+
+type KnownFiles = Box<dyn FnOnce() -> Vec<PathBuf> + Send>;
+
+/// State of the thread watching one folder.
+struct FolderWatch {
+    root: PathBuf,
+    discovery: Discovery,
+    /// Files seen so far; only kept while polling.
+    known: Option<HashSet<PathBuf>>,
+    load_known: Option<KnownFiles>,
+    tracker: NewFileTracker,
+}
+
+impl FolderWatch {
+    fn new(root: PathBuf, load_known: KnownFiles) -> Self {
+        let mut watch = Self {
+            root,
+            discovery: Discovery::Offline,
+            known: None,
+            load_known: Some(load_known),
+            tracker: NewFileTracker::default(),
+        };
+        watch.restart();
+        watch
+    }
+
+    fn restart(&mut self) {
+        self.discovery = if !self.root.is_dir() {
+            Discovery::Offline
+        } else if is_network_share(&self.root) {
+            self.start_polling()
+        } else {
+            match watch_events(&self.root) {
+                Ok(discovery) => discovery,
+                Err(error) => {
+                    log::warn!(
+                        "Can't watch {} for changes, polling it instead: {}",
+                        self.root.display(),
+                        error
+                    );
+                    self.start_polling()
+                }
+            }
+        };
+    }
+
+    fn start_polling(&mut self) -> Discovery {
+        if self.known.is_none() {
+            let known = self
+                .load_known
+                .take()
+                .map(|load| load())
+                .unwrap_or_default();
+            self.known = Some(known.into_iter().collect());
+        }
+        Discovery::Poll {
+            next_walk: Instant::now(),
+        }
+    }
+
+    /// Picks up new paths; a drive that came back is watched again.
+    fn collect(&mut self) {
+        if matches!(self.discovery, Discovery::Offline) || !self.root.is_dir() {
+            self.restart();
+        }
+        match &mut self.discovery {
+            Discovery::Events { events, .. } => {
+                while let Ok(event) = events.try_recv() {
+                    match event {
+                        Ok(event) if is_new_file(&event.kind) => {
+                            for path in &event.paths {
+                                self.tracker.note(path);
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(error) => log::warn!(
+                            "Change notification for {} failed: {}",
+                            self.root.display(),
+                            error
+                        ),
+                    }
+                }
+            }
+            Discovery::Poll { next_walk } => {
+                if Instant::now() < *next_walk {
+                    return;
+                }
+                *next_walk = Instant::now() + NETWORK_POLL_INTERVAL;
+                let known = self.known.get_or_insert_with(HashSet::new);
+                for entry in WalkDir::new(&self.root).into_iter().filter_map(Result::ok) {
+                    let path = entry.path();
+                    if entry.file_type().is_file()
+                        && scanner::is_supported_image_path(path)
+                        && known.insert(path.to_path_buf())
+                    {
+                        self.tracker.note(path);
+                    }
+                }
+            }
+            Discovery::Offline => {}
+        }
+    }
+}
+
+fn watch_events(root: &Path) -> notify::Result<Discovery> {
+    let (sender, events) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(root, RecursiveMode::Recursive)?;
+    Ok(Discovery::Events {
+        _watcher: watcher,
+        events,
+    })
+}
+
+/// Files created, or moved or renamed into place. Later writes to those
+/// are picked up by [`NewFileTracker::settle`] measuring them.
+fn is_new_file(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_) | EventKind::Modify(notify::event::ModifyKind::Name(_))
+    )
+}
+
+/// Whether `path` lives on a network filesystem.
+#[cfg(target_os = "windows")]
+fn is_network_share(path: &Path) -> bool {
+    // UNC paths; mapped drive letters still get SMB change notifications.
+    let path = path.to_string_lossy();
+    path.starts_with(r"\\") || path.starts_with("//")
+}
+
+/// Whether `path` lives on a network filesystem.
+#[cfg(target_os = "linux")]
+fn is_network_share(path: &Path) -> bool {
+    std::fs::read_to_string("/proc/self/mounts")
+        .map(|mounts| mount_is_network(&mounts, path))
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn is_network_share(_path: &Path) -> bool {
+    false
+}
+
+/// Looks up the filesystem type of the deepest mount holding `path` in a
+/// `/proc/mounts` listing.
+#[cfg(any(target_os = "linux", test))]
+fn mount_is_network(mounts: &str, path: &Path) -> bool {
+    const NETWORK_FILESYSTEMS: &[&str] = &[
+        "nfs",
+        "nfs4",
+        "cifs",
+        "smb3",
+        "smbfs",
+        "9p",
+        "afs",
+        "ceph",
+        "glusterfs",
+        "fuse.sshfs",
+        "fuse.rclone",
+    ];
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.next().and(fields.next())?;
+            let fs_type = fields.next()?;
+            // Spaces in mount points are escaped as \040.
+            let mount_point = PathBuf::from(mount_point.replace("\\040", " "));
+            path.starts_with(&mount_point)
+                .then(|| (mount_point.components().count(), fs_type))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .is_some_and(|(_, fs_type)| NETWORK_FILESYSTEMS.contains(&fs_type))
+}
+
+/// The running watch thread for one folder, if any.
+#[derive(Default)]
+pub struct FolderWatcher {
+    stop: Mutex<Option<Arc<AtomicBool>>>,
+    indexed: Arc<AtomicUsize>,
+}
+
+impl FolderWatcher {
+    /// Watches `root` until stopped, passing newly finished images to
+    /// `on_new`, which returns how many it indexed. `known` lists the files
+    /// already indexed; it is only called if the folder has to be polled.
+    /// Replaces a running watch.
+    pub fn start(
+        &self,
+        root: PathBuf,
+        known: impl FnOnce() -> Vec<PathBuf> + Send + 'static,
+        paused: Arc<AtomicBool>,
+        mut on_new: impl FnMut(&Path, Vec<PathBuf>) -> usize + Send + 'static,
+    ) {
+        let stop = Arc::new(AtomicBool::new(false));
+        if let Some(previous) = self
+            .stop
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replace(stop.clone())
+        {
+            previous.store(true, Ordering::Release);
+        }
+        self.indexed.store(0, Ordering::Release);
+        let indexed = self.indexed.clone();
+        std::thread::spawn(move || {
+            log::info!("Watching {} for new images", root.display());
+            let mut watch = FolderWatch::new(root, Box::new(known));
+            while !stop.load(Ordering::Acquire) {
+                watch.collect();
+                if !paused.load(Ordering::Acquire) && !watch.tracker.is_empty() {
+                    let ready = watch.tracker.settle();
+                    if !ready.is_empty() {
+                        indexed.fetch_add(on_new(&watch.root, ready), Ordering::AcqRel);
+                    }
+                }
+                let mut waited = Duration::ZERO;
+                while waited < SETTLE_INTERVAL && !stop.load(Ordering::Acquire) {
+                    std::thread::sleep(STOP_CHECK);
+                    waited += STOP_CHECK;
+                }
+            }
+            log::info!("Stopped watching {}", watch.root.display());
+        });
+    }
+
+    pub fn stop(&self) {
+        if let Some(stop) = self
+            .stop
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+        {
+            stop.store(true, Ordering::Release);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.stop
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// Images indexed since the watch last started.
+    pub fn indexed(&self) -> usize {
+        self.indexed.load(Ordering::Acquire)
+    }
+}
+
+/// One watcher per watched folder, keyed by the folder as stored in
+/// [`WatchFolderSettings`].
+#[derive(Default)]
+pub struct WatchedFolders {
+    watchers: Mutex<HashMap<String, FolderWatcher>>,
+}
+
+impl WatchedFolders {
+    /// Starts watching `folder`, replacing a watch already running for it.
+    pub fn start(
+        &self,
+        folder: &str,
+        known: impl FnOnce() -> Vec<PathBuf> + Send + 'static,
+        paused: Arc<AtomicBool>,
+        on_new: impl FnMut(&Path, Vec<PathBuf>) -> usize + Send + 'static,
+    ) {
+        let mut watchers = self.watchers.lock().unwrap_or_else(PoisonError::into_inner);
+        watchers.entry(folder.to_string()).or_default().start(
+            PathBuf::from(folder),
+            known,
+            paused,
+            on_new,
+        );
+    }
+
+    /// Stops watching every folder not in `keep`.
+    pub fn retain(&self, keep: &[String]) {
+        let mut watchers = self.watchers.lock().unwrap_or_else(PoisonError::into_inner);
+        watchers.retain(|folder, watcher| {
+            let kept = keep.contains(folder);
+            if !kept {
+                watcher.stop();
+            }
+            kept
+        });
+    }
+
+    /// `(running, indexed since start)` for `folder`.
+    pub fn status(&self, folder: &str) -> (bool, usize) {
+        self.watchers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(folder)
+            .map(|watcher| (watcher.is_running(), watcher.indexed()))
+            .unwrap_or((false, 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watched_folders_fold_nested_folders_together() {
+        let mut settings = WatchFolderSettings::default();
+        assert!(settings.add("D:/renders/sdxl"));
+        assert!(settings.add("D:/renders/flux"));
+        assert!(!settings.add("D:/renders/sdxl/upscaled"));
+        assert!(settings.add("D:/renders"));
+        assert_eq!(settings.folders, ["D:/renders"]);
+
+        assert!(!settings.remove("D:/renders/sdxl"));
+        assert!(settings.remove("D:/renders"));
+        assert!(settings.folders.is_empty());
+    }
+
+    #[test]
+    fn new_images_are_reported_once_their_size_settles() {
+        let dir = std::env::temp_dir().join(format!("watch_settle_test_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("moved_in")).unwrap();
+        let mut watch = FolderWatch::new(dir.clone(), Box::new(Vec::new));
+        assert!(matches!(watch.discovery, Discovery::Events { .. }));
+
+        let render = dir.join("render.png");
+        std::fs::write(&render, b"partial").unwrap();
+        std::fs::write(dir.join("moved_in/upscaled.png"), b"done").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"not an image").unwrap();
+        // Notifications arrive asynchronously.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while watch.tracker.pending.len() < 2 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            watch.collect();
+        }
+        assert!(watch.tracker.settle().is_empty());
+
+        std::fs::write(&render, b"partial, now finished").unwrap();
+        assert_eq!(watch.tracker.settle(), [dir.join("moved_in/upscaled.png")]);
+        assert_eq!(watch.tracker.settle(), [render]);
+        assert!(watch.tracker.is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn network_mounts_are_found_by_their_deepest_mount_point() {
+        let mounts = "/dev/sda1 / ext4 rw 0 0\n\
+                      nas:/export /mnt/nas nfs4 rw 0 0\n\
+                      /dev/sdb1 /mnt/nas/local\\040disk ext4 rw 0 0\n\
+                      //server/renders /media/renders cifs rw 0 0\n";
+        assert!(mount_is_network(mounts, Path::new("/mnt/nas/sdxl")));
+        assert!(mount_is_network(mounts, Path::new("/media/renders")));
+        assert!(!mount_is_network(
+            mounts,
+            Path::new("/mnt/nas/local disk/out")
+        ));
+        assert!(!mount_is_network(mounts, Path::new("/home/me/renders")));
+    }
+}
@@ -24,11 +24,14 @@ import {
     exportImagesAsFiles,
     forgeSendToImages,
     forgeTestConnection,
+    getPendingOperations,
     getStorageProfile,
     moveImagesToDirectory,
     onThumbnailCacheComplete,
     onThumbnailCacheProgress,
     precacheAllThumbnails,
+    previewDeleteImages,
+    resumePendingOperations,
     setImageFavorite,
     setImageLocked,
     setImagesFavorite,
@@ -38,8 +41,10 @@ import {
 import type {
     DeleteMode,
     DeleteHistoryEntry,
+    DeletionSafetyReport,
     GalleryImageRecord,
     ImageExportFormat,
+    OperationKind,
     StorageProfile,
 } from "./types/metadata";
 
@@ -53,6 +58,14 @@ const queryClient = new QueryClient({
 });
 
 const DELETE_UNDO_WINDOW_MS = 6000;
+const PENDING_OPERATIONS_PROMPT_MS = 20000;
+
+const OPERATION_KIND_LABELS: Record<OperationKind, string> = {
+    move: "move",
+    quarantine: "quarantine",
+    delete: "delete",
+    convert: "export",
+};
 
 function parseBooruTagFilter(input: string): {
     include: string[];
@@ -151,6 +164,28 @@ function buildJpegPreferredViewerState(images: GalleryImageRecord[]): {
     };
 }
 
+/** One line per thing the deletion would put at risk, for the confirm prompt. */
+function describeDeletionRisks(report: DeletionSafetyReport): string[] {
+    const plural = (count: number, noun: string) =>
+        `${count} ${noun}${count === 1 ? "" : "s"}`;
+    const warnings: string[] = [];
+    if (report.large_selection) {
+        warnings.push(`This removes ${plural(report.requested, "image")} at once.`);
+    }
+    if (report.lineage_parent_ids.length > 0) {
+        warnings.push(
+            `${plural(report.lineage_parent_ids.length, "image")} other images were made from (img2img or upscales).`
+        );
+    }
+    if (report.in_collections > 0) {
+        const names = report.collections.map((collection) => collection.name);
+        warnings.push(
+            `${plural(report.in_collections, "image")} in collections: ${names.join(", ")}.`
+        );
+    }
+    return warnings;
+}
+
 interface PendingDeleteOperation {
     activityId: number;
     ids: number[];
@@ -158,6 +193,8 @@ interface PendingDeleteOperation {
     selectedBefore: number[];
     selectedImageIdBefore: number | null;
     mode: DeleteMode;
+    /** From the pre-flight report, for deletions it flagged as risky. */
+    confirmationToken: string | null;
     timerId: number;
 }
 
@@ -529,11 +566,73 @@ function AppContent() {
         queryClient.invalidateQueries({ queryKey: ["models"] });
     }, []);
 
+    const handleResumePendingOperations = useCallback(async () => {
+        clearToast();
+        try {
+            const results = await resumePendingOperations();
+            invalidateImageQueries();
+            const resolved = results.reduce((sum, result) => sum + result.resolved, 0);
+            const failed = results.reduce(
+                (sum, result) => sum + result.failed_paths.length,
+                0
+            );
+            if (failed > 0) {
+                pushToast(
+                    `Finished ${resolved} file${resolved === 1 ? "" : "s"}; ${failed} could not be recovered.`,
+                    { tone: "warning", durationMs: 5200 }
+                );
+            } else {
+                pushToast(
+                    `Finished ${resolved} file${resolved === 1 ? "" : "s"} from interrupted operations.`,
+                    { tone: "success" }
+                );
+            }
+        } catch (error) {
+            pushToast(`Resume failed: ${String(error)}`, { tone: "error" });
+        }
+    }, [clearToast, invalidateImageQueries, pushToast]);
+
+    // A crash mid-operation leaves a journal; offer to finish it on startup.
+    useEffect(() => {
+        let cancelled = false;
+
+        const checkPendingOperations = async () => {
+            try {
+                const pending = await getPendingOperations();
+                if (cancelled || pending.length === 0) {
+                    return;
+                }
+                const message =
+                    pending.length === 1
+                        ? `An interrupted ${OPERATION_KIND_LABELS[pending[0].kind]} of ${pending[0].total_steps} file${pending[0].total_steps === 1 ? "" : "s"} was found.`
+                        : `${pending.length} interrupted operations were found.`;
+                pushToast(message, {
+                    tone: "warning",
+                    durationMs: PENDING_OPERATIONS_PROMPT_MS,
+                    actionLabel: "Finish",
+                    onAction: () => void handleResumePendingOperations(),
+                });
+            } catch (error) {
+                console.warn("Failed to check for interrupted operations:", error);
+            }
+        };
+
+        checkPendingOperations();
+        return () => {
+            cancelled = true;
+        };
+    }, [handleResumePendingOperations, pushToast]);
+
     const finalizeDeleteOperation = useCallback(
         async (operation: PendingDeleteOperation) => {
             setIsDeletingImages(true);
             try {
-                const result = await deleteImages(operation.ids, operation.mode);
+                const result = await deleteImages(
+                    operation.ids,
+                    operation.mode,
+                    false,
+                    operation.confirmationToken
+                );
                 invalidateImageQueries();
 
                 const deletedLabel =
@@ -682,7 +781,21 @@ function AppContent() {
                 return;
             }
             await flushPendingDelete();
-            if (!window.confirm(confirmMessage)) {
+            let safety: DeletionSafetyReport;
+            try {
+                safety = await previewDeleteImages(requestedIds);
+            } catch (error) {
+                pushToast(`Delete failed: ${String(error)}`, { tone: "error" });
+                return;
+            }
+            const warnings = describeDeletionRisks(safety);
+            if (
+                !window.confirm(
+                    warnings.length > 0
+                        ? `${confirmMessage}\n\n${warnings.join("\n")}`
+                        : confirmMessage
+                )
+            ) {
                 return;
             }
 
@@ -781,6 +894,7 @@ function AppContent() {
                 selectedBefore,
                 selectedImageIdBefore,
                 mode: deleteMode,
+                confirmationToken: safety.confirmation_token,
                 timerId,
             };
 
@@ -8,7 +8,7 @@ import {
     type MouseEvent as ReactMouseEvent,
 } from "react";
 import { useVirtualizer } from "@tanstack/react-virtual";
-import type { GalleryImageRecord } from "../types/metadata";
+import type { FocalPoint, GalleryImageRecord } from "../types/metadata";
 import type { StorageProfile } from "../types/metadata";
 import { convertFileSrc } from "@tauri-apps/api/core";
 import { getThumbnailPaths } from "../services/commands";
@@ -95,6 +95,14 @@ function toAssetSrc(filepath: string): string {
     return convertFileSrc(filepath.replace(/\\/g, "/"));
 }
 
+/** Cache files go through the `thumb` protocol, which answers revalidation with 304s. */
+function toThumbnailSrc(thumbnailPath: string): string {
+    const name = thumbnailPath.split(/[\\/]/).pop() ?? "";
+    return /^[0-9a-f]+\.jpg$/.test(name)
+        ? convertFileSrc(name, "thumb")
+        : toAssetSrc(thumbnailPath);
+}
+
 export function Gallery({
     images,
     onSelect,
@@ -115,6 +123,7 @@ export function Gallery({
 }: GalleryProps) {
     const parentRef = useRef<HTMLDivElement>(null);
     const thumbnailCacheRef = useRef<Map<string, string>>(new Map());
+    const focalPointsRef = useRef<Map<string, FocalPoint>>(new Map());
     const thumbnailInFlightRef = useRef<Set<string>>(new Set());
     const scrollRafRef = useRef<number | null>(null);
     const thumbFlushRafRef = useRef<number | null>(null);
@@ -306,10 +315,13 @@ export function Gallery({
                     }
 
                     let changed = false;
-                    for (const { filepath, thumbnail_path } of mappings) {
+                    for (const { filepath, thumbnail_path, focal_point } of mappings) {
                         if (thumbnail_path === filepath) {
                             continue;
                         }
+                        if (focal_point) {
+                            focalPointsRef.current.set(filepath, focal_point);
+                        }
                         const existing = thumbnailCacheRef.current.get(filepath);
                         if (existing !== thumbnail_path) {
                             upsertThumbnailCache(
@@ -535,6 +547,10 @@ export function Gallery({
                                             key={image.id}
                                             image={image}
                                             thumbnailPath={thumbnailPath}
+                                            focalPoint={
+                                                focalPointsRef.current.get(image.filepath) ??
+                                                null
+                                            }
                                             isChecked={selectedIds.has(image.id)}
                                             onToggleChecked={() =>
                                                 onToggleSelected(image.id)
@@ -584,6 +600,7 @@ export function Gallery({
 interface GalleryItemProps {
     image: GalleryImageRecord;
     thumbnailPath: string | null;
+    focalPoint: FocalPoint | null;
     isChecked: boolean;
     onToggleChecked: () => void;
     isSelected: boolean;
@@ -594,6 +611,7 @@ interface GalleryItemProps {
 const GalleryItem = memo(function GalleryItem({
     image,
     thumbnailPath,
+    focalPoint,
     isChecked,
     onToggleChecked,
     isSelected,
@@ -601,7 +619,7 @@ const GalleryItem = memo(function GalleryItem({
     onContextMenu,
 }: GalleryItemProps) {
     const [loaded, setLoaded] = useState(false);
-    const imgSrc = thumbnailPath ? toAssetSrc(thumbnailPath) : null;
+    const imgSrc = thumbnailPath ? toThumbnailSrc(thumbnailPath) : null;
     const thumbnailLoading = !imgSrc || !loaded;
 
     return (
@@ -676,7 +694,12 @@ const GalleryItem = memo(function GalleryItem({
                         decoding="async"
                         onLoad={() => setLoaded(true)}
                         onError={() => setLoaded(true)}
-                        style={{ opacity: loaded ? 1 : 0 }}
+                        style={{
+                            opacity: loaded ? 1 : 0,
+                            objectPosition: focalPoint
+                                ? `${focalPoint.x * 100}% ${focalPoint.y * 100}%`
+                                : undefined,
+                        }}
                     />
                 )}
             </div>
@@ -3,6 +3,7 @@ import { convertFileSrc } from "@tauri-apps/api/core";
 import { open, save } from "@tauri-apps/plugin-dialog";
 import { useVirtualizer } from "@tanstack/react-virtual";
 import {
+    applyPromptStyle,
     exportImages,
     exportImagesAsFiles,
     forgeGetOptions,
@@ -13,6 +14,8 @@ import {
     getSidecarData,
     getThumbnailPath,
     getThumbnailPaths,
+    importStyles,
+    listPromptStyles,
     openFileLocation,
     saveSidecarTags,
 } from "../services/commands";
@@ -27,6 +30,7 @@ import type {
     GalleryImageRecord,
     ImageExportFormat,
     ImageRecord,
+    PromptStyle,
 } from "../types/metadata";
 import { usePersistedState } from "../hooks/usePersistedState";
 import type { ShowToastOptions } from "../hooks/useToast";
@@ -357,6 +361,8 @@ export function PhotoViewer({
     const [forgeSamplerOptions, setForgeSamplerOptions] = useState<string[]>([]);
     const [forgeSchedulerOptions, setForgeSchedulerOptions] = useState<string[]>([]);
     const [forgeOptionsWarning, setForgeOptionsWarning] = useState<string | null>(null);
+    const [promptStyles, setPromptStyles] = useState<PromptStyle[]>([]);
+    const [selectedPromptStyle, setSelectedPromptStyle] = useState("");
     const [isLoadingForgeOptions, setIsLoadingForgeOptions] = useState(false);
     const [forgePayloadPresets, setForgePayloadPresets] = usePersistedState<
         Record<string, ForgePayloadPreset>
@@ -1002,6 +1008,57 @@ export function PhotoViewer({
         [onShowToast]
     );
 
+    useEffect(() => {
+        listPromptStyles()
+            .then(setPromptStyles)
+            .catch(() => setPromptStyles([]));
+    }, []);
+
+    const handleImportPromptStyles = useCallback(async () => {
+        const selected = await open({
+            directory: false,
+            multiple: false,
+            title: "Select WebUI styles.csv",
+            filters: [{ name: "CSV", extensions: ["csv"] }],
+        });
+        if (!selected || typeof selected !== "string") {
+            return;
+        }
+        try {
+            const result = await importStyles(selected);
+            setPromptStyles(await listPromptStyles());
+            showViewerToast(`Imported ${result.imported} styles.`, "success", 2400);
+        } catch (error) {
+            showViewerToast(`Style import failed: ${String(error)}`, "error");
+        }
+    }, [showViewerToast]);
+
+    const insertPromptStyle = useCallback(async () => {
+        if (!selectedPromptStyle) {
+            showViewerToast("Select a style to insert.", "warning");
+            return;
+        }
+        try {
+            const applied = await applyPromptStyle(
+                selectedPromptStyle,
+                forgeOverrides.prompt,
+                forgeOverrides.negative_prompt
+            );
+            setForgeOverrides((prev) => ({
+                ...prev,
+                prompt: applied.prompt,
+                negative_prompt: applied.negative_prompt,
+            }));
+        } catch (error) {
+            showViewerToast(`Could not apply style: ${String(error)}`, "error");
+        }
+    }, [
+        forgeOverrides.negative_prompt,
+        forgeOverrides.prompt,
+        selectedPromptStyle,
+        showViewerToast,
+    ]);
+
     const applyForgePayloadPreset = useCallback(
         (name: string) => {
             const preset = forgePayloadPresets[name];
@@ -2430,6 +2487,42 @@ export function PhotoViewer({
                                                 {loraWeightValidationError}
                                             </div>
                                         )}
+                                        <div className="viewer-form-label">Style</div>
+                                        <div className="viewer-form-grid">
+                                            <select
+                                                className="viewer-input"
+                                                value={selectedPromptStyle}
+                                                onChange={(event) =>
+                                                    setSelectedPromptStyle(event.target.value)
+                                                }
+                                            >
+                                                <option value="">
+                                                    {promptStyles.length > 0
+                                                        ? "Select style"
+                                                        : "No styles imported"}
+                                                </option>
+                                                {promptStyles.map((style) => (
+                                                    <option key={style.name} value={style.name}>
+                                                        {style.name}
+                                                    </option>
+                                                ))}
+                                            </select>
+                                            <button
+                                                className="viewer-control-button"
+                                                onClick={insertPromptStyle}
+                                                disabled={!selectedPromptStyle}
+                                                type="button"
+                                            >
+                                                Insert
+                                            </button>
+                                            <button
+                                                className="viewer-control-button"
+                                                onClick={handleImportPromptStyles}
+                                                type="button"
+                                            >
+                                                Import
+                                            </button>
+                                        </div>
                                         <div className="viewer-form-label">Prompt</div>
                                         <textarea
                                             className="viewer-textarea"
@@ -32,6 +32,9 @@ const SORT_OPTIONS: { value: SortOption; label: string }[] = [
     { value: "name_desc", label: "Name Z-A" },
     { value: "model", label: "Model" },
     { value: "generation_type", label: "Gen Type" },
+    { value: "hue", label: "Color" },
+    { value: "brightness_desc", label: "Brightest" },
+    { value: "brightness_asc", label: "Darkest" },
 ];
 
 const GENERATION_TYPE_OPTIONS: {
@@ -45,6 +48,7 @@ const GENERATION_TYPE_OPTIONS: {
     { value: "grid", label: "grids" },
     { value: "upscale", label: "upscale" },
     { value: "unknown", label: "unknown" },
+    { value: "missing_metadata", label: "missing metadata" },
 ];
 
 export function SearchBar({
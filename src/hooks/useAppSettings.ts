@@ -14,6 +14,9 @@ const SORT_OPTIONS = new Set<SortOption>([
     "name_desc",
     "model",
     "generation_type",
+    "hue",
+    "brightness_asc",
+    "brightness_desc",
 ]);
 
 const GENERATION_TYPE_FILTER_OPTIONS = new Set<GenerationType | "all">([
@@ -24,6 +27,7 @@ const GENERATION_TYPE_FILTER_OPTIONS = new Set<GenerationType | "all">([
     "grid",
     "upscale",
     "unknown",
+    "missing_metadata",
 ]);
 
 const sortOptionStorage = {
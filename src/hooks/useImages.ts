@@ -16,6 +16,8 @@ import {
     searchImagesCursor,
     onScanProgress,
     onScanComplete,
+    onForgeOutputsIndexed,
+    onLibraryUpdated,
 } from "../services/commands";
 import type { ScanProgress, ScanComplete } from "../services/commands";
 import type { GenerationType, SortOption, StorageProfile } from "../types/metadata";
@@ -154,6 +156,8 @@ export function useScanProgress() {
     useEffect(() => {
         let unlistenProgress: (() => void) | undefined;
         let unlistenComplete: (() => void) | undefined;
+        let unlistenForgeOutputs: (() => void) | undefined;
+        let unlistenLibraryUpdated: (() => void) | undefined;
 
         const setupListeners = async () => {
             unlistenProgress = await onScanProgress((payload) => {
@@ -173,6 +177,20 @@ export function useScanProgress() {
                 queryClient.invalidateQueries({ queryKey: ["topTags"] });
                 queryClient.invalidateQueries({ queryKey: ["models"] });
             });
+
+            // Images generated in the WebUI and picked up by the outputs watcher.
+            unlistenForgeOutputs = await onForgeOutputsIndexed(() => {
+                queryClient.invalidateQueries({ queryKey: ["images"] });
+                queryClient.invalidateQueries({ queryKey: ["totalCount"] });
+                queryClient.invalidateQueries({ queryKey: ["topTags"] });
+            });
+
+            // New images picked up in a watched folder.
+            unlistenLibraryUpdated = await onLibraryUpdated(() => {
+                queryClient.invalidateQueries({ queryKey: ["images"] });
+                queryClient.invalidateQueries({ queryKey: ["totalCount"] });
+                queryClient.invalidateQueries({ queryKey: ["topTags"] });
+            });
         };
 
         setupListeners();
@@ -180,6 +198,8 @@ export function useScanProgress() {
         return () => {
             if (unlistenProgress) unlistenProgress();
             if (unlistenComplete) unlistenComplete();
+            if (unlistenForgeOutputs) unlistenForgeOutputs();
+            if (unlistenLibraryUpdated) unlistenLibraryUpdated();
         };
     }, [queryClient]);
 
@@ -1,30 +1,166 @@
-import { invoke } from "@tauri-apps/api/core";
-import { listen } from "@tauri-apps/api/event";
-import type { UnlistenFn } from "@tauri-apps/api/event";
+import { invoke as tauriInvoke } from "@tauri-apps/api/core";
+import { emit, listen } from "@tauri-apps/api/event";
+import type { EventCallback, UnlistenFn } from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 import type {
+    AppErrorCode,
+    AppErrorPayload,
+    AutotagComplete,
+    AutotagProgress,
+    AutotagStatus,
+    OcrComplete,
+    OcrProgress,
+    OcrStatus,
+    PromptCluster,
+    PromptClustersComplete,
+    Collection,
+    CollectionRule,
+    BatchSuggestion,
+    ColorMatch,
+    SimilarImage,
+    BackgroundStatus,
+    BackgroundTask,
+    FocalPointComplete,
+    FocalPointProgress,
     GalleryImageRecord,
     ImageRecord,
     TagCount,
+    ExtraParamFilter,
+    ExtraParamKey,
+    FilterCounts,
+    ImageFilter,
     ExportResult,
+    ExportProgress,
     FileExportResult,
+    ExportEstimate,
+    ExportTransform,
+    FileExportOptions,
     DeleteImagesResult,
+    DuplicateGroup,
     DeleteMode,
+    DeletionSafetyReport,
     MoveImagesResult,
+    QuarantineResult,
+    PendingOperation,
+    ResumeOperationResult,
+    ArchiveImagesResult,
+    UnarchiveImagesResult,
     ImageExportFormat,
+    ThumbnailFailureRecord,
     ThumbnailMapping,
+    ThumbnailRegenerateResult,
+    ThumbnailRetryResult,
+    ThumbnailTiers,
     ForgeStatus,
+    ForgeServerStats,
+    ForgeOutputsIndexed,
+    ForgeWatcherStatus,
+    LibraryUpdated,
+    WatchFolderStatus,
     ForgeSendResult,
     ForgeBatchSendResult,
     ForgeOptionsResult,
     ForgePayloadOverrides,
+    ForgePreset,
+    ForgePriority,
+    ForgeRunWindow,
+    ModelDefaults,
     CursorPage,
+    DiskUsageReport,
     SidecarData,
+    UserProperty,
+    UserPropertyKey,
     GenerationType,
+    HookConfig,
+    HookEvent,
+    ActivityEntry,
+    ActivityFilter,
+    HookRunResult,
+    ImageDetail,
+    ImageDiffResult,
+    ThumbnailStripResult,
+    KeywordTrends,
+    MetadataCoverage,
+    DirectoryEntry,
     ModelEntry,
+    LoraUsage,
+    EmbeddingUsage,
+    SamplerEntry,
+    SourceToolEntry,
+    WorkflowStatus,
+    WorkflowStatusCount,
+    SchedulerEntry,
+    ResolutionBucket,
+    ModelUsage,
+    NsfwSettings,
+    PerformanceReport,
+    PerformanceSettings,
+    PerformanceSettingsState,
+    PrivateStatus,
+    SelectionSummary,
+    SlideshowBatch,
+    SlideshowFilter,
     SortOption,
     StorageProfile,
+    TagDictionaryImportResult,
+    PromptStyle,
+    PromptStyleImportResult,
+    AppliedPromptStyle,
+    BulkEditPromptsResult,
+    BulkUpdateFieldsResult,
+    FieldEdit,
+    FieldUpdate,
+    UndoFieldEditResult,
+    PromptEditPreview,
+    TagSuggestion,
+    TrendBucket,
+    ViewState,
+    Project,
+    ProjectSettings,
+    VolumeStatus,
 } from "../types/metadata";
 
+// ── Errors ──────────────────────────────────────────────────────────────
+
+/**
+ * Error thrown for a failed command. `toString()` yields the backend message,
+ * so existing `String(error)` displays keep working while callers can branch
+ * on `code`.
+ */
+export class CommandError extends Error {
+    readonly code: AppErrorCode;
+
+    constructor(payload: AppErrorPayload) {
+        super(payload.message);
+        this.name = "CommandError";
+        this.code = payload.code;
+    }
+
+    override toString(): string {
+        return this.message;
+    }
+}
+
+function isAppErrorPayload(value: unknown): value is AppErrorPayload {
+    return (
+        typeof value === "object" &&
+        value !== null &&
+        typeof (value as AppErrorPayload).code === "string" &&
+        typeof (value as AppErrorPayload).message === "string"
+    );
+}
+
+async function invoke<T>(
+    command: string,
+    args?: Record<string, unknown>
+): Promise<T> {
+    try {
+        return await tauriInvoke<T>(command, args);
+    } catch (error) {
+        throw isAppErrorPayload(error) ? new CommandError(error) : error;
+    }
+}
+
 // ── Directory Scanning ──────────────────────────────────────────────────
 
 export interface ScanProgress {
@@ -60,6 +196,30 @@ export async function scanDirectory(directory: string): Promise<void> {
     return invoke<void>("scan_directory", { directory });
 }
 
+/** Re-reads one image's metadata with the current parser and re-indexes it. */
+export async function reparseImage(imageId: number): Promise<ImageRecord> {
+    return invoke<ImageRecord>("reparse_image", { imageId });
+}
+
+export interface ClipboardImportResult {
+    id: number;
+    filepath: string;
+    filename: string;
+    has_metadata: boolean;
+    tags: string[];
+}
+
+/** Saves pasted image bytes into the library inbox and indexes them. */
+export async function importFromClipboard(
+    base64: string,
+    suggestedName: string | null
+): Promise<ClipboardImportResult> {
+    return invoke<ClipboardImportResult>("import_from_clipboard", {
+        base64,
+        suggestedName,
+    });
+}
+
 export async function getStorageProfile(): Promise<StorageProfile> {
     return invoke<StorageProfile>("get_storage_profile");
 }
@@ -80,10 +240,18 @@ export async function precacheAllThumbnails(): Promise<void> {
     return invoke<void>("precache_all_thumbnails");
 }
 
+/**
+ * Listens for progress events the backend sends to this window only, so a
+ * second window's scan or tagging run doesn't move this window's progress bar.
+ */
+function listenToWindow<T>(event: string, handler: EventCallback<T>): Promise<UnlistenFn> {
+    return getCurrentWebviewWindow().listen<T>(event, handler);
+}
+
 export async function onScanProgress(
     callback: (progress: ScanProgress) => void
 ): Promise<UnlistenFn> {
-    return listen<ScanProgress>("scan-progress", (event) => {
+    return listenToWindow<ScanProgress>("scan-progress", (event) => {
         callback(event.payload);
     });
 }
@@ -91,7 +259,7 @@ export async function onScanProgress(
 export async function onScanComplete(
     callback: (result: ScanComplete) => void
 ): Promise<UnlistenFn> {
-    return listen<ScanComplete>("scan-complete", (event) => {
+    return listenToWindow<ScanComplete>("scan-complete", (event) => {
         callback(event.payload);
     });
 }
@@ -99,7 +267,7 @@ export async function onScanComplete(
 export async function onThumbnailCacheProgress(
     callback: (progress: ThumbnailCacheProgress) => void
 ): Promise<UnlistenFn> {
-    return listen<ThumbnailCacheProgress>("thumbnail-cache-progress", (event) => {
+    return listenToWindow<ThumbnailCacheProgress>("thumbnail-cache-progress", (event) => {
         callback(event.payload);
     });
 }
@@ -107,7 +275,7 @@ export async function onThumbnailCacheProgress(
 export async function onThumbnailCacheComplete(
     callback: (result: ThumbnailCacheComplete) => void
 ): Promise<UnlistenFn> {
-    return listen<ThumbnailCacheComplete>("thumbnail-cache-complete", (event) => {
+    return listenToWindow<ThumbnailCacheComplete>("thumbnail-cache-complete", (event) => {
         callback(event.payload);
     });
 }
@@ -120,15 +288,64 @@ export async function getImagesCursor(
     sortBy?: SortOption | null,
     generationTypes?: GenerationType[] | null,
     modelFilter?: string | null,
-    modelFamilyFilters?: string[] | null
+    modelFamilyFilters?: string[] | null,
+    collapseDuplicates?: boolean,
+    schedulerFilters?: string[] | null,
+    includeHidden?: boolean,
+    resolutionBuckets?: string[] | null,
+    sourceTools?: string[] | null,
+    workflowStatuses?: WorkflowStatus[] | null,
+    maxSharpness?: number | null,
+    failedRendersOnly?: boolean,
+    promptClusters?: number[] | null,
+    collections?: number[] | null
 ): Promise<CursorPage<GalleryImageRecord>> {
     return invoke<CursorPage<GalleryImageRecord>>("get_images_cursor", {
-        cursor,
-        limit,
-        sortBy: sortBy ?? null,
-        generationTypes: generationTypes ?? null,
-        modelFilter: modelFilter ?? null,
-        modelFamilyFilters: modelFamilyFilters ?? null,
+        request: {
+            cursor,
+            limit,
+            sortBy: sortBy ?? null,
+            generationTypes: generationTypes ?? null,
+            modelFilter: modelFilter ?? null,
+            modelFamilyFilters: modelFamilyFilters ?? null,
+            schedulerFilters: schedulerFilters ?? null,
+            includeHidden: includeHidden ?? null,
+            resolutionBuckets: resolutionBuckets ?? null,
+            sourceTools: sourceTools ?? null,
+            workflowStatuses: workflowStatuses ?? null,
+            maxSharpness: maxSharpness ?? null,
+            failedRendersOnly: failedRendersOnly ?? null,
+            promptClusters: promptClusters ?? null,
+            collections: collections ?? null,
+            collapseDuplicates: collapseDuplicates ?? null,
+        },
+    });
+}
+
+/** Images using `lora`, optionally within an inclusive weight range. */
+export async function getLoraImagesCursor(
+    lora: string,
+    minWeight: number | null,
+    maxWeight: number | null,
+    cursor: string | null,
+    limit: number,
+    sortBy?: SortOption | null,
+    generationTypes?: GenerationType[] | null,
+    modelFilter?: string | null,
+    includeHidden?: boolean
+): Promise<CursorPage<GalleryImageRecord>> {
+    return invoke<CursorPage<GalleryImageRecord>>("get_lora_images_cursor", {
+        request: {
+            lora,
+            minWeight,
+            maxWeight,
+            cursor,
+            limit,
+            sortBy: sortBy ?? null,
+            generationTypes: generationTypes ?? null,
+            modelFilter: modelFilter ?? null,
+            includeHidden: includeHidden ?? null,
+        },
     });
 }
 
@@ -139,7 +356,17 @@ export async function searchImagesCursor(
     generationTypes?: GenerationType[] | null,
     sortBy?: SortOption | null,
     modelFilter?: string | null,
-    modelFamilyFilters?: string[] | null
+    modelFamilyFilters?: string[] | null,
+    collapseDuplicates?: boolean,
+    schedulerFilters?: string[] | null,
+    includeHidden?: boolean,
+    resolutionBuckets?: string[] | null,
+    sourceTools?: string[] | null,
+    workflowStatuses?: WorkflowStatus[] | null,
+    maxSharpness?: number | null,
+    failedRendersOnly?: boolean,
+    promptClusters?: number[] | null,
+    collections?: number[] | null
 ): Promise<CursorPage<GalleryImageRecord>> {
     return invoke<CursorPage<GalleryImageRecord>>("search_images_cursor", {
         request: {
@@ -150,6 +377,16 @@ export async function searchImagesCursor(
             sortBy: sortBy ?? null,
             modelFilter: modelFilter ?? null,
             modelFamilyFilters: modelFamilyFilters ?? null,
+            schedulerFilters: schedulerFilters ?? null,
+            includeHidden: includeHidden ?? null,
+            resolutionBuckets: resolutionBuckets ?? null,
+            sourceTools: sourceTools ?? null,
+            workflowStatuses: workflowStatuses ?? null,
+            maxSharpness: maxSharpness ?? null,
+            failedRendersOnly: failedRendersOnly ?? null,
+            promptClusters: promptClusters ?? null,
+            collections: collections ?? null,
+            collapseDuplicates: collapseDuplicates ?? null,
         },
     });
 }
@@ -163,7 +400,20 @@ export async function filterImagesCursor(
     generationTypes?: GenerationType[] | null,
     sortBy?: SortOption | null,
     modelFilter?: string | null,
-    modelFamilyFilters?: string[] | null
+    modelFamilyFilters?: string[] | null,
+    refine?: string | null,
+    collapseDuplicates?: boolean,
+    extraParams?: ExtraParamFilter[],
+    schedulerFilters?: string[] | null,
+    includeHidden?: boolean,
+    embeddings?: string[],
+    resolutionBuckets?: string[] | null,
+    sourceTools?: string[] | null,
+    workflowStatuses?: WorkflowStatus[] | null,
+    maxSharpness?: number | null,
+    failedRendersOnly?: boolean,
+    promptClusters?: number[] | null,
+    collections?: number[] | null
 ): Promise<CursorPage<GalleryImageRecord>> {
     return invoke<CursorPage<GalleryImageRecord>>("filter_images_cursor", {
         request: {
@@ -176,10 +426,27 @@ export async function filterImagesCursor(
             sortBy: sortBy ?? null,
             modelFilter: modelFilter ?? null,
             modelFamilyFilters: modelFamilyFilters ?? null,
+            schedulerFilters: schedulerFilters ?? null,
+            includeHidden: includeHidden ?? null,
+            refine: refine ?? null,
+            collapseDuplicates: collapseDuplicates ?? null,
+            extraParams: extraParams ?? [],
+            embeddings: embeddings ?? [],
+            resolutionBuckets: resolutionBuckets ?? null,
+            sourceTools: sourceTools ?? null,
+            workflowStatuses: workflowStatuses ?? null,
+            maxSharpness: maxSharpness ?? null,
+            failedRendersOnly: failedRendersOnly ?? null,
+            promptClusters: promptClusters ?? null,
+            collections: collections ?? null,
         },
     });
 }
 
+export async function getFilterCounts(request: ImageFilter): Promise<FilterCounts> {
+    return invoke<FilterCounts>("get_filter_counts", { request });
+}
+
 // ── Tags ────────────────────────────────────────────────────────────────
 
 export async function listTags(
@@ -193,6 +460,212 @@ export async function getTopTags(limit: number): Promise<TagCount[]> {
     return invoke<TagCount[]>("get_top_tags", { limit });
 }
 
+export async function getExtraParamKeys(limit: number): Promise<ExtraParamKey[]> {
+    return invoke<ExtraParamKey[]>("get_extra_param_keys", { limit });
+}
+
+export async function listTagSuggestions(
+    prefix: string | null,
+    limit: number
+): Promise<TagSuggestion[]> {
+    return invoke<TagSuggestion[]>("list_tag_suggestions", { prefix, limit });
+}
+
+/** Imports a danbooru-style tag CSV from a local path or http(s) URL. */
+export async function importTagDictionary(
+    source: string
+): Promise<TagDictionaryImportResult> {
+    return invoke<TagDictionaryImportResult>("import_tag_dictionary", {
+        source,
+    });
+}
+
+export async function clearTagDictionary(): Promise<void> {
+    return invoke<void>("clear_tag_dictionary");
+}
+
+// ── Prompt styles ───────────────────────────────────────────────────────
+
+/** Imports the WebUI's styles.csv, replacing previously imported styles. */
+export async function importStyles(path: string): Promise<PromptStyleImportResult> {
+    return invoke<PromptStyleImportResult>("import_styles", { path });
+}
+
+export async function listPromptStyles(): Promise<PromptStyle[]> {
+    return invoke<PromptStyle[]>("list_prompt_styles");
+}
+
+export async function applyPromptStyle(
+    name: string,
+    prompt: string,
+    negativePrompt: string
+): Promise<AppliedPromptStyle> {
+    return invoke<AppliedPromptStyle>("apply_prompt_style", {
+        name,
+        prompt,
+        negativePrompt,
+    });
+}
+
+export interface BulkEditPromptsOptions {
+    regex?: boolean;
+    includeNegative?: boolean;
+    /** Also rewrite the parameters embedded in PNG files. */
+    rewriteFiles?: boolean;
+}
+
+/** Find/replace across the stored prompts of the given images. */
+export async function bulkEditPrompts(
+    ids: number[],
+    find: string,
+    replace: string,
+    options: BulkEditPromptsOptions = {}
+): Promise<BulkEditPromptsResult> {
+    return invoke<BulkEditPromptsResult>("bulk_edit_prompts", {
+        ids,
+        find,
+        replace,
+        regex: options.regex ?? false,
+        includeNegative: options.includeNegative ?? false,
+        rewriteFiles: options.rewriteFiles ?? false,
+    });
+}
+
+/** Overwrites typed metadata fields of `ids`, journaled for undo. */
+export async function bulkUpdateFields(
+    ids: number[],
+    fields: FieldUpdate
+): Promise<BulkUpdateFieldsResult> {
+    return invoke<BulkUpdateFieldsResult>("bulk_update_fields", { ids, fields });
+}
+
+export async function undoFieldEdit(editId: number): Promise<UndoFieldEditResult> {
+    return invoke<UndoFieldEditResult>("undo_field_edit", { editId });
+}
+
+export async function getFieldEdits(limit?: number): Promise<FieldEdit[]> {
+    return invoke<FieldEdit[]>("get_field_edits", { limit: limit ?? null });
+}
+
+/** Sets `key` on the given images; an empty or null value removes it. */
+export async function setUserProperty(
+    ids: number[],
+    key: string,
+    value: string | null
+): Promise<number> {
+    return invoke<number>("set_user_property", { ids, key, value });
+}
+
+export async function getUserProperties(imageId: number): Promise<UserProperty[]> {
+    return invoke<UserProperty[]>("get_user_properties", { imageId });
+}
+
+export async function listUserPropertyKeys(limit?: number): Promise<UserPropertyKey[]> {
+    return invoke<UserPropertyKey[]>("list_user_property_keys", {
+        limit: limit ?? null,
+    });
+}
+
+/** Dry run of a find/replace over every prompt in the library. */
+export async function previewPromptEdit(
+    find: string,
+    replace: string,
+    options: Omit<BulkEditPromptsOptions, "rewriteFiles"> = {}
+): Promise<PromptEditPreview> {
+    return invoke<PromptEditPreview>("preview_prompt_edit", {
+        find,
+        replace,
+        regex: options.regex ?? false,
+        includeNegative: options.includeNegative ?? false,
+    });
+}
+
+// ── Auto-tagging ────────────────────────────────────────────────────────
+
+export async function getAutotagStatus(): Promise<AutotagStatus> {
+    return invoke<AutotagStatus>("get_autotag_status");
+}
+
+/** Starts background auto-tagging; resolves to the background task id. */
+export async function autotagImages(
+    ids: number[],
+    threshold?: number | null
+): Promise<number> {
+    return invoke<number>("autotag_images", { ids, threshold: threshold ?? null });
+}
+
+export async function onAutotagProgress(
+    callback: (progress: AutotagProgress) => void
+): Promise<UnlistenFn> {
+    return listenToWindow<AutotagProgress>("autotag-progress", (event) => {
+        callback(event.payload);
+    });
+}
+
+export async function onAutotagComplete(
+    callback: (result: AutotagComplete) => void
+): Promise<UnlistenFn> {
+    return listenToWindow<AutotagComplete>("autotag-complete", (event) => {
+        callback(event.payload);
+    });
+}
+
+// ── Text in Images (OCR) ────────────────────────────────────────────────
+
+export async function getOcrStatus(): Promise<OcrStatus> {
+    return invoke<OcrStatus>("get_ocr_status");
+}
+
+/**
+ * Starts background text recognition over `ids`, or every image not read
+ * yet; resolves to the background task id.
+ */
+export async function ocrImages(ids?: number[] | null): Promise<number> {
+    return invoke<number>("ocr_images", { ids: ids ?? null });
+}
+
+export async function onOcrProgress(
+    callback: (progress: OcrProgress) => void
+): Promise<UnlistenFn> {
+    return listenToWindow<OcrProgress>("ocr-progress", (event) => {
+        callback(event.payload);
+    });
+}
+
+export async function onOcrComplete(
+    callback: (result: OcrComplete) => void
+): Promise<UnlistenFn> {
+    return listenToWindow<OcrComplete>("ocr-complete", (event) => {
+        callback(event.payload);
+    });
+}
+
+// ── Thumbnail Focal Points ──────────────────────────────────────────────
+
+/**
+ * Detects subject positions for grid cropping, for `ids` or every image
+ * without one; resolves to the background task id.
+ */
+export async function detectFocalPoints(ids?: number[] | null): Promise<number> {
+    return invoke<number>("detect_focal_points", { ids: ids ?? null });
+}
+
+export async function onFocalPointProgress(
+    callback: (progress: FocalPointProgress) => void
+): Promise<UnlistenFn> {
+    return listenToWindow<FocalPointProgress>("focal-points-progress", (event) => {
+        callback(event.payload);
+    });
+}
+
+export async function onFocalPointComplete(
+    callback: (result: FocalPointComplete) => void
+): Promise<UnlistenFn> {
+    return listenToWindow<FocalPointComplete>("focal-points-complete", (event) => {
+        callback(event.payload);
+    });
+}
+
 // ── Image Detail ────────────────────────────────────────────────────────
 
 export async function getImageDetail(
@@ -201,6 +674,73 @@ export async function getImageDetail(
     return invoke<ImageRecord | null>("get_image_detail", { id });
 }
 
+export async function getImagesByIdsDetail(
+    ids: number[]
+): Promise<ImageRecord[]> {
+    return invoke<ImageRecord[]>("get_images_by_ids_detail", { ids });
+}
+
+export async function getImageDetails(ids: number[]): Promise<ImageDetail[]> {
+    return invoke<ImageDetail[]>("get_image_details", { ids });
+}
+
+export async function getGridMembers(gridId: number): Promise<ImageRecord[]> {
+    return invoke<ImageRecord[]>("get_grid_members", { gridId });
+}
+
+export async function getBatchMembers(
+    imageId: number
+): Promise<GalleryImageRecord[]> {
+    return invoke<GalleryImageRecord[]>("get_batch_members", { imageId });
+}
+
+/** Ranks a batch (any member's id) best first, flagging likely keepers. */
+export async function suggestBestOfBatch(batchGroup: number): Promise<BatchSuggestion[]> {
+    return invoke<BatchSuggestion[]>("suggest_best_of_batch", { batchGroup });
+}
+
+export async function getSeedNeighbors(
+    imageId: number,
+    window?: number | null
+): Promise<GalleryImageRecord[]> {
+    return invoke<GalleryImageRecord[]>("get_seed_neighbors", {
+        imageId,
+        window: window ?? null,
+    });
+}
+
+/** Images dominated by a colour close to `hex`, closest first. */
+export async function searchByColor(
+    hex: string,
+    tolerance?: number | null,
+    limit?: number | null
+): Promise<ColorMatch[]> {
+    return invoke<ColorMatch[]>("search_by_color", {
+        hex,
+        tolerance: tolerance ?? null,
+        limit: limit ?? null,
+    });
+}
+
+/** Images that look like `imageId` (near-duplicates, upscales), closest first. */
+export async function findSimilarImages(
+    imageId: number,
+    maxDistance?: number | null,
+    limit?: number | null
+): Promise<SimilarImage[]> {
+    return invoke<SimilarImage[]>("find_similar_images", {
+        imageId,
+        maxDistance: maxDistance ?? null,
+        limit: limit ?? null,
+    });
+}
+
+export async function summarizeSelection(
+    ids: number[]
+): Promise<SelectionSummary> {
+    return invoke<SelectionSummary>("summarize_selection", { ids });
+}
+
 export async function getTotalCount(): Promise<number> {
     return invoke<number>("get_total_count");
 }
@@ -209,6 +749,26 @@ export async function getDisplayImagePath(filepath: string): Promise<string> {
     return invoke<string>("get_display_image_path", { filepath });
 }
 
+export async function diffImages(
+    idA: number,
+    idB: number
+): Promise<ImageDiffResult> {
+    return invoke<ImageDiffResult>("diff_images", { idA, idB });
+}
+
+/** Renders the images, in the given order, into one strip `height` px tall. */
+export async function exportThumbnailStrip(
+    ids: number[],
+    height: number,
+    outputPath: string
+): Promise<ThumbnailStripResult> {
+    return invoke<ThumbnailStripResult>("export_thumbnail_strip", {
+        ids,
+        height,
+        outputPath,
+    });
+}
+
 export interface ClipboardImagePayload {
     base64: string;
     mime: string;
@@ -220,6 +780,83 @@ export async function getImageClipboardPayload(
     return invoke<ClipboardImagePayload>("get_image_clipboard_payload", { filepath });
 }
 
+/** Copies the originals as files (not bitmaps); resolves to the file count. */
+export async function copyFilesToClipboard(ids: number[]): Promise<number> {
+    return invoke<number>("copy_files_to_clipboard", { ids });
+}
+
+// ── Prompt Clusters ─────────────────────────────────────────────────────
+
+/**
+ * Starts grouping the library's prompts into themes; resolves to the
+ * background task id.
+ */
+export async function clusterPrompts(clusterCount?: number | null): Promise<number> {
+    return invoke<number>("cluster_prompts", { clusterCount: clusterCount ?? null });
+}
+
+export async function getPromptClusters(): Promise<PromptCluster[]> {
+    return invoke<PromptCluster[]>("get_prompt_clusters");
+}
+
+export async function onPromptClustersComplete(
+    callback: (result: PromptClustersComplete) => void
+): Promise<UnlistenFn> {
+    return listenToWindow<PromptClustersComplete>("prompt-clusters-complete", (event) => {
+        callback(event.payload);
+    });
+}
+
+// ── Collections ─────────────────────────────────────────────────────────
+
+export async function getCollections(): Promise<Collection[]> {
+    return invoke<Collection[]>("get_collections");
+}
+
+/**
+ * Creates a rule-based collection, or updates collection `id`; scans and
+ * edits keep its members current afterwards.
+ */
+export async function saveCollection(
+    name: string,
+    rule: CollectionRule,
+    id?: number | null
+): Promise<Collection> {
+    return invoke<Collection>("save_collection", { id: id ?? null, name, rule });
+}
+
+export async function deleteCollection(id: number): Promise<void> {
+    return invoke<void>("delete_collection", { id });
+}
+
+// ── Slideshow ───────────────────────────────────────────────────────────
+
+export interface DisplayProxyReady {
+    filepath: string;
+    display_path: string;
+}
+
+export async function getSlideshowBatch(
+    filter: SlideshowFilter,
+    count: number,
+    shuffle: boolean
+): Promise<SlideshowBatch> {
+    return invoke<SlideshowBatch>("get_slideshow_batch", { filter, count, shuffle });
+}
+
+/** Asks the backend to warm display proxies for upcoming slides. */
+export async function prefetchDisplayProxies(filepaths: string[]): Promise<void> {
+    return emit("slideshow-prefetch", { filepaths });
+}
+
+export async function onDisplayProxyReady(
+    callback: (ready: DisplayProxyReady) => void
+): Promise<UnlistenFn> {
+    return listen<DisplayProxyReady>("display-proxy-ready", (event) => {
+        callback(event.payload);
+    });
+}
+
 // ── Thumbnails ──────────────────────────────────────────────────────────
 
 export async function getThumbnailPath(filepath: string): Promise<string> {
@@ -236,12 +873,116 @@ export async function getThumbnailPaths(
     return invoke<ThumbnailMapping[]>("get_thumbnail_paths", { filepaths });
 }
 
+/** Thumbnail paths plus inline 64px placeholders for blur-up loading. */
+export async function getThumbnailTiers(
+    filepaths: string[]
+): Promise<ThumbnailTiers[]> {
+    return invoke<ThumbnailTiers[]>("get_thumbnail_tiers", { filepaths });
+}
+
+/**
+ * Reports the `next_cursor` of the last page on screen so thumbnails for the
+ * next few pages render ahead. Resolves to how many were queued.
+ */
+export async function hintScrollPosition(cursor: string): Promise<number> {
+    return invoke<number>("hint_scroll_position", { cursor });
+}
+
+/** Images without a thumbnail and why, latest failure first. */
+export async function getThumbnailFailures(): Promise<ThumbnailFailureRecord[]> {
+    return invoke<ThumbnailFailureRecord[]>("get_thumbnail_failures");
+}
+
+/** Images whose files failed to decode repeatedly. */
+export async function getCorruptImages(): Promise<ThumbnailFailureRecord[]> {
+    return invoke<ThumbnailFailureRecord[]>("get_corrupt_images");
+}
+
+/** Moves every corrupt image, or only `ids`, to the quarantine folder. */
+export async function quarantineCorruptImages(
+    ids: number[] | null = null
+): Promise<QuarantineResult> {
+    return invoke<QuarantineResult>("quarantine_corrupt_images", { ids });
+}
+
+/** Retries every failed thumbnail, or only `ids`. */
+export async function retryFailedThumbnails(
+    ids: number[] | null = null
+): Promise<ThumbnailRetryResult> {
+    return invoke<ThumbnailRetryResult>("retry_failed_thumbnails", { ids });
+}
+
+/** Re-renders thumbnails of images edited outside the app. */
+export async function regenerateThumbnails(ids: number[]): Promise<ThumbnailRegenerateResult> {
+    return invoke<ThumbnailRegenerateResult>("regenerate_thumbnails", { ids });
+}
+
 // ── Group-by Queries ────────────────────────────────────────────────────
 
+export async function getDirectories(): Promise<DirectoryEntry[]> {
+    return invoke<DirectoryEntry[]>("get_directories");
+}
+
+export async function setDirectoryPinned(directory: string, pinned: boolean): Promise<void> {
+    return invoke<void>("set_directory_pinned", { directory, pinned });
+}
+
+export async function setDirectoryHidden(directory: string, hidden: boolean): Promise<void> {
+    return invoke<void>("set_directory_hidden", { directory, hidden });
+}
+
+export async function setDirectoryAlias(directory: string, alias: string | null): Promise<void> {
+    return invoke<void>("set_directory_alias", { directory, alias });
+}
+
 export async function getModels(): Promise<ModelEntry[]> {
     return invoke<ModelEntry[]>("get_models");
 }
 
+export async function getLoraUsage(): Promise<LoraUsage[]> {
+    return invoke<LoraUsage[]>("get_lora_usage");
+}
+
+export async function getEmbeddingUsage(): Promise<EmbeddingUsage[]> {
+    return invoke<EmbeddingUsage[]>("get_embedding_usage");
+}
+
+export async function getSourceTools(): Promise<SourceToolEntry[]> {
+    return invoke<SourceToolEntry[]>("get_source_tools");
+}
+
+/** Moves images to a triage stage; returns how many changed. */
+export async function setWorkflowStatus(
+    ids: number[],
+    status: WorkflowStatus
+): Promise<number> {
+    return invoke<number>("set_workflow_status", { ids, status });
+}
+
+/** Moves every image the filter matches to `status`. */
+export async function setWorkflowStatusByFilter(
+    filter: ImageFilter,
+    status: WorkflowStatus
+): Promise<number> {
+    return invoke<number>("set_workflow_status_by_filter", { filter, status });
+}
+
+export async function getWorkflowStatusCounts(): Promise<WorkflowStatusCount[]> {
+    return invoke<WorkflowStatusCount[]>("get_workflow_status_counts");
+}
+
+export async function getSamplers(): Promise<SamplerEntry[]> {
+    return invoke<SamplerEntry[]>("get_samplers");
+}
+
+export async function getSchedulers(): Promise<SchedulerEntry[]> {
+    return invoke<SchedulerEntry[]>("get_schedulers");
+}
+
+export async function getResolutionBuckets(): Promise<ResolutionBucket[]> {
+    return invoke<ResolutionBucket[]>("get_resolution_buckets");
+}
+
 // ── Shell / OS ──────────────────────────────────────────────────────────
 
 export async function openFileLocation(filepath: string): Promise<void> {
@@ -252,18 +993,41 @@ export async function directoryExists(path: string): Promise<boolean> {
     return invoke<boolean>("directory_exists", { path });
 }
 
+/**
+ * Favorites, locked images, collection members and lineage parents among
+ * `ids`; risky deletions come with the token `deleteImages` requires.
+ */
+export async function previewDeleteImages(
+    ids: number[],
+    force = false
+): Promise<DeletionSafetyReport> {
+    return invoke<DeletionSafetyReport>("preview_delete_images", { ids, force });
+}
+
 export async function deleteImages(
     ids: number[],
-    mode: DeleteMode
+    mode: DeleteMode,
+    force = false,
+    confirmationToken: string | null = null
 ): Promise<DeleteImagesResult> {
     return invoke<DeleteImagesResult>("delete_images", {
         request: {
             ids,
             mode,
+            force,
+            confirmationToken,
         },
     });
 }
 
+/**
+ * Groups of copies for a "keep one, delete the rest" pass; `verify` reads
+ * every candidate in full to rule out quick-hash collisions.
+ */
+export async function findDuplicateImages(verify = false): Promise<DuplicateGroup[]> {
+    return invoke<DuplicateGroup[]>("find_duplicate_images", { verify });
+}
+
 export async function setImageFavorite(
     imageId: number,
     isFavorite: boolean
@@ -302,18 +1066,60 @@ export async function setImagesLocked(
     });
 }
 
+export async function setFavoriteByFilter(
+    filter: ImageFilter,
+    value: boolean
+): Promise<number> {
+    return invoke<number>("set_favorite_by_filter", { filter, value });
+}
+
+export async function setLockedByFilter(
+    filter: ImageFilter,
+    value: boolean
+): Promise<number> {
+    return invoke<number>("set_locked_by_filter", { filter, value });
+}
+
 export async function moveImagesToDirectory(
     ids: number[],
-    destinationDirectory: string
+    destinationDirectory: string,
+    force = false
 ): Promise<MoveImagesResult> {
     return invoke<MoveImagesResult>("move_images_to_directory", {
         request: {
             ids,
             destinationDirectory,
+            force,
+        },
+    });
+}
+
+/** Moves files to cold storage; `compress` packs them into one zip. */
+export async function archiveImages(
+    ids: number[],
+    target: string,
+    compress = false,
+    force = false
+): Promise<ArchiveImagesResult> {
+    return invoke<ArchiveImagesResult>("archive_images", {
+        request: {
+            ids,
+            target,
+            compress,
+            force,
         },
     });
 }
 
+export async function unarchiveImages(ids: number[]): Promise<UnarchiveImagesResult> {
+    return invoke<UnarchiveImagesResult>("unarchive_images", { ids });
+}
+
+/** Re-checks whether each library root's drive is connected. */
+export async function getVolumeStatus(): Promise<VolumeStatus[]> {
+    return invoke<VolumeStatus[]>("get_volume_status");
+}
+
 // ── Export ───────────────────────────────────────────────────────────────
 
 export async function exportImages(
@@ -328,20 +1134,75 @@ export async function exportImages(
     });
 }
 
+/** Exports every image matching `filter` without sending ids over IPC. */
+export async function exportImagesByFilter(
+    filter: ImageFilter,
+    format: string,
+    outputPath: string
+): Promise<ExportResult> {
+    return invoke<ExportResult>("export_images", {
+        filter,
+        format,
+        outputPath,
+    });
+}
+
+/** Metadata exports run as a cancellable background task. */
+export async function onExportProgress(
+    callback: (progress: ExportProgress) => void
+): Promise<UnlistenFn> {
+    return listenToWindow<ExportProgress>("export-progress", (event) => {
+        callback(event.payload);
+    });
+}
+
 export async function exportImagesAsFiles(
     ids: number[],
     format: ImageExportFormat,
     quality: number | null,
-    outputPath: string
+    outputPath: string,
+    options: FileExportOptions | null = null
 ): Promise<FileExportResult> {
     return invoke<FileExportResult>("export_images_as_files", {
         ids,
         format,
         quality,
+        options,
+        outputPath,
+    });
+}
+
+export async function exportImagesAsFilesByFilter(
+    filter: ImageFilter,
+    format: ImageExportFormat,
+    quality: number | null,
+    outputPath: string,
+    options: FileExportOptions | null = null
+): Promise<FileExportResult> {
+    return invoke<FileExportResult>("export_images_as_files", {
+        filter,
+        format,
+        quality,
+        options,
         outputPath,
     });
 }
 
+/** Pass either `ids` or a `filter`, as with the export itself. */
+export async function estimateExport(
+    selection: { ids: number[] } | { filter: ImageFilter },
+    format: ImageExportFormat,
+    quality: number | null,
+    transform: ExportTransform | null = null
+): Promise<ExportEstimate> {
+    return invoke<ExportEstimate>("estimate_export", {
+        ...selection,
+        format,
+        quality,
+        transform,
+    });
+}
+
 // ── Forge API Integration ───────────────────────────────────────────────
 
 export async function forgeTestConnection(
@@ -351,6 +1212,42 @@ export async function forgeTestConnection(
     return invoke<ForgeStatus>("forge_test_connection", { baseUrl, apiKey });
 }
 
+/** Cached for a couple of seconds; pass `maxAgeMs: 0` to force a fetch. */
+export async function forgeGetServerStats(
+    baseUrl: string,
+    apiKey: string | null,
+    maxAgeMs: number | null = null
+): Promise<ForgeServerStats> {
+    return invoke<ForgeServerStats>("forge_get_server_stats", {
+        baseUrl,
+        apiKey,
+        maxAgeMs,
+    });
+}
+
+export async function getForgeWatcherStatus(): Promise<ForgeWatcherStatus> {
+    return invoke<ForgeWatcherStatus>("get_forge_watcher_status");
+}
+
+/**
+ * `outputsDir` may be the WebUI folder, its outputs folder or a folder inside
+ * the install; `null` keeps the saved one.
+ */
+export async function setForgeWatcher(
+    enabled: boolean,
+    outputsDir: string | null = null
+): Promise<ForgeWatcherStatus> {
+    return invoke<ForgeWatcherStatus>("set_forge_watcher", { enabled, outputsDir });
+}
+
+export async function onForgeOutputsIndexed(
+    callback: (result: ForgeOutputsIndexed) => void
+): Promise<UnlistenFn> {
+    return listen<ForgeOutputsIndexed>("forge-outputs-indexed", (event) => {
+        callback(event.payload);
+    });
+}
+
 export async function forgeGetOptions(
     baseUrl: string,
     apiKey: string | null,
@@ -369,6 +1266,12 @@ export async function forgeGetOptions(
     });
 }
 
+/**
+ * `filenameTemplate` names saved outputs, e.g. `{date}_{model}_{seed}_{n}`.
+ * Tokens: stem, seed, model, date, time, variant, stamp, n.
+ * `styles` are prompt style names from `forgeGetOptions().styles`.
+ * `preset` names a saved `ForgePreset`; non-blank `overrides` fields win.
+ */
 export async function forgeSendToImage(
     imageId: number,
     baseUrl: string,
@@ -379,11 +1282,16 @@ export async function forgeSendToImage(
     adetailerFaceModel: string | null,
     loraTokens: string[] | null,
     loraWeight: number | null,
-    overrides: ForgePayloadOverrides | null
+    overrides: ForgePayloadOverrides | null,
+    filenameTemplate: string | null = null,
+    styles: string[] | null = null,
+    preset: string | null = null,
+    priority: ForgePriority | null = null
 ): Promise<ForgeSendResult> {
     return invoke<ForgeSendResult>("forge_send_to_image", {
         request: {
             imageId,
+            priority,
             options: {
                 baseUrl,
                 apiKey,
@@ -394,6 +1302,9 @@ export async function forgeSendToImage(
                 loraTokens,
                 loraWeight,
                 overrides,
+                filenameTemplate,
+                styles,
+                preset,
             },
         },
     });
@@ -409,11 +1320,17 @@ export async function forgeSendToImages(
     adetailerFaceModel: string | null,
     loraTokens: string[] | null,
     loraWeight: number | null,
-    overrides: ForgePayloadOverrides | null
+    overrides: ForgePayloadOverrides | null,
+    filenameTemplate: string | null = null,
+    styles: string[] | null = null,
+    preset: string | null = null,
+    queue: { priority?: ForgePriority; runWindow?: ForgeRunWindow } = {}
 ): Promise<ForgeBatchSendResult> {
     return invoke<ForgeBatchSendResult>("forge_send_to_images", {
         request: {
             imageIds,
+            priority: queue.priority ?? null,
+            runWindow: queue.runWindow ?? null,
             options: {
                 baseUrl,
                 apiKey,
@@ -424,11 +1341,68 @@ export async function forgeSendToImages(
                 loraTokens,
                 loraWeight,
                 overrides,
+                filenameTemplate,
+                styles,
+                preset,
             },
         },
     });
 }
 
+export async function listForgePresets(): Promise<ForgePreset[]> {
+    return invoke<ForgePreset[]>("list_forge_presets");
+}
+
+/** Creates or replaces a preset; pass `previousName` to rename one. */
+export async function saveForgePreset(
+    preset: ForgePreset,
+    previousName: string | null = null
+): Promise<ForgePreset[]> {
+    return invoke<ForgePreset[]>("save_forge_preset", { preset, previousName });
+}
+
+export async function deleteForgePreset(name: string): Promise<ForgePreset[]> {
+    return invoke<ForgePreset[]>("delete_forge_preset", { name });
+}
+
+export async function listModelDefaults(): Promise<ModelDefaults[]> {
+    return invoke<ModelDefaults[]>("list_model_defaults");
+}
+
+/** Creates or replaces the defaults for `defaults.model`. */
+export async function setModelDefaults(
+    defaults: ModelDefaults
+): Promise<ModelDefaults[]> {
+    return invoke<ModelDefaults[]>("set_model_defaults", { defaults });
+}
+
+export async function deleteModelDefaults(model: string): Promise<ModelDefaults[]> {
+    return invoke<ModelDefaults[]>("delete_model_defaults", { model });
+}
+
+// ── Watched Folders ─────────────────────────────────────────────────────
+
+export async function getWatchFolders(): Promise<WatchFolderStatus[]> {
+    return invoke<WatchFolderStatus[]>("get_watch_folders");
+}
+
+/** Watches a previously scanned folder and indexes images as they land. */
+export async function addWatchFolder(directory: string): Promise<WatchFolderStatus[]> {
+    return invoke<WatchFolderStatus[]>("add_watch_folder", { directory });
+}
+
+export async function removeWatchFolder(directory: string): Promise<WatchFolderStatus[]> {
+    return invoke<WatchFolderStatus[]>("remove_watch_folder", { directory });
+}
+
+export async function onLibraryUpdated(
+    callback: (result: LibraryUpdated) => void
+): Promise<UnlistenFn> {
+    return listen<LibraryUpdated>("library-updated", (event) => {
+        callback(event.payload);
+    });
+}
+
 // ── Sidecar Metadata ────────────────────────────────────────────────────
 
 export async function getSidecarData(
@@ -444,3 +1418,230 @@ export async function saveSidecarTags(
 ): Promise<void> {
     return invoke<void>("save_sidecar_tags", { filepath, tags, notes });
 }
+
+/** Sets a 1-5 star rating in the image's sidecar, or clears it with null. */
+export async function setImageRating(filepath: string, rating: number | null): Promise<void> {
+    return invoke<void>("set_image_rating", { filepath, rating });
+}
+
+// ── Hooks ───────────────────────────────────────────────────────────────
+
+export async function getHooks(): Promise<HookConfig[]> {
+    return invoke<HookConfig[]>("get_hooks");
+}
+
+export async function setHooks(hooks: HookConfig[]): Promise<void> {
+    return invoke<void>("set_hooks", { hooks });
+}
+
+export async function testHook(
+    hook: HookConfig,
+    event?: HookEvent
+): Promise<HookRunResult> {
+    return invoke<HookRunResult>("test_hook", { hook, event: event ?? null });
+}
+
+// ── Activity Log ────────────────────────────────────────────────────────
+
+/** Logged scans, deletes, moves, exports and settings changes, newest first. */
+export async function getActivityLog(
+    filter?: ActivityFilter
+): Promise<ActivityEntry[]> {
+    return invoke<ActivityEntry[]>("get_activity_log", { filter: filter ?? null });
+}
+
+export async function getActivityFiles(entryId: number): Promise<string[]> {
+    return invoke<string[]>("get_activity_files", { entryId });
+}
+
+// ── Interrupted Operations ──────────────────────────────────────────────
+
+/** Moves, quarantines, deletes and converting exports left half done by a crash. */
+export async function getPendingOperations(): Promise<PendingOperation[]> {
+    return invoke<PendingOperation[]>("get_pending_operations");
+}
+
+/**
+ * Finishes every pending operation, or with `rollBack` puts moved files back.
+ * Deleted files can't return, so a rolled-back delete only keeps what's left.
+ * An interrupted export is written again, or its partial ZIP removed.
+ */
+export async function resumePendingOperations(
+    rollBack = false
+): Promise<ResumeOperationResult[]> {
+    return invoke<ResumeOperationResult[]>("resume_pending_operations", { rollBack });
+}
+
+// ── Background Mode ─────────────────────────────────────────────────────
+
+export async function getBackgroundStatus(): Promise<BackgroundStatus> {
+    return invoke<BackgroundStatus>("get_background_status");
+}
+
+export async function setCloseToTray(enabled: boolean): Promise<void> {
+    return invoke<void>("set_close_to_tray", { enabled });
+}
+
+export async function setBackgroundPaused(paused: boolean): Promise<void> {
+    return invoke<void>("set_background_paused", { paused });
+}
+
+export async function onBackgroundPausedChanged(
+    callback: (paused: boolean) => void
+): Promise<UnlistenFn> {
+    return listen<boolean>("background-paused-changed", (event) => {
+        callback(event.payload);
+    });
+}
+
+// ── Background Tasks ────────────────────────────────────────────────────
+
+export async function listBackgroundTasks(): Promise<BackgroundTask[]> {
+    return invoke<BackgroundTask[]>("list_background_tasks");
+}
+
+export async function pauseBackgroundTask(
+    taskId: number,
+    paused: boolean
+): Promise<void> {
+    return invoke<void>("pause_background_task", { taskId, paused });
+}
+
+export async function cancelBackgroundTask(taskId: number): Promise<void> {
+    return invoke<void>("cancel_background_task", { taskId });
+}
+
+export async function clearFinishedBackgroundTasks(): Promise<void> {
+    return invoke<void>("clear_finished_background_tasks");
+}
+
+// ── Performance ─────────────────────────────────────────────────────────
+
+export async function getPerformanceReport(): Promise<PerformanceReport> {
+    return invoke<PerformanceReport>("get_performance_report");
+}
+
+export async function getPerformanceSettings(): Promise<PerformanceSettingsState> {
+    return invoke<PerformanceSettingsState>("get_performance_settings");
+}
+
+export async function setPerformanceSettings(
+    settings: PerformanceSettings
+): Promise<PerformanceSettingsState> {
+    return invoke<PerformanceSettingsState>("set_performance_settings", {
+        settings,
+    });
+}
+
+// ── View State ──────────────────────────────────────────────────────────
+
+export async function loadViewState(): Promise<ViewState> {
+    return invoke<ViewState>("load_view_state");
+}
+
+/** Persists where the gallery is, so the next launch can restore it. */
+export async function saveViewState(viewState: ViewState): Promise<void> {
+    return invoke<void>("save_view_state", { viewState });
+}
+
+// ── Projects ────────────────────────────────────────────────────────────
+
+export async function listProjects(): Promise<ProjectSettings> {
+    return invoke<ProjectSettings>("list_projects");
+}
+
+/** Creates or replaces a project; pass `previousName` to rename one. */
+export async function saveProject(
+    project: Project,
+    previousName?: string
+): Promise<ProjectSettings> {
+    return invoke<ProjectSettings>("save_project", {
+        project,
+        previousName: previousName ?? null,
+    });
+}
+
+export async function deleteProject(name: string): Promise<ProjectSettings> {
+    return invoke<ProjectSettings>("delete_project", { name });
+}
+
+/** Scopes the gallery and stats to a project; null shows the whole library. */
+export async function switchProject(name: string | null): Promise<ProjectSettings> {
+    return invoke<ProjectSettings>("switch_project", { name });
+}
+
+// ── NSFW Filter ─────────────────────────────────────────────────────────
+
+export async function getNsfwSettings(): Promise<NsfwSettings> {
+    return invoke<NsfwSettings>("get_nsfw_settings");
+}
+
+/** Applies immediately to grid queries and thumbnail lookups. */
+export async function setNsfwSettings(
+    settings: NsfwSettings
+): Promise<NsfwSettings> {
+    return invoke<NsfwSettings>("set_nsfw_settings", { settings });
+}
+
+// ── Private Folders ─────────────────────────────────────────────────────
+
+export async function getPrivateStatus(): Promise<PrivateStatus> {
+    return invoke<PrivateStatus>("get_private_status");
+}
+
+export async function setPrivatePasscode(
+    passcode: string,
+    currentPasscode?: string
+): Promise<PrivateStatus> {
+    return invoke<PrivateStatus>("set_private_passcode", {
+        passcode,
+        currentPasscode: currentPasscode ?? null,
+    });
+}
+
+export async function setDirectoryPrivate(
+    directory: string,
+    isPrivate: boolean
+): Promise<PrivateStatus> {
+    return invoke<PrivateStatus>("set_directory_private", {
+        directory,
+        private: isPrivate,
+    });
+}
+
+/** Reveals private folders until the app restarts or `lockPrivate` is called. */
+export async function unlockPrivate(passcode: string): Promise<PrivateStatus> {
+    return invoke<PrivateStatus>("unlock_private", { passcode });
+}
+
+export async function lockPrivate(): Promise<PrivateStatus> {
+    return invoke<PrivateStatus>("lock_private");
+}
+
+// ── Statistics ──────────────────────────────────────────────────────────
+
+/** How often each term appears per week/month, by file date. */
+export async function getKeywordTrends(
+    terms: string[],
+    bucket: TrendBucket
+): Promise<KeywordTrends> {
+    return invoke<KeywordTrends>("get_keyword_trends", { terms, bucket });
+}
+
+/** Per-model image counts over time with average settings, most used first. */
+export async function getModelUsage(bucket: TrendBucket): Promise<ModelUsage> {
+    return invoke<ModelUsage>("get_model_usage", { bucket });
+}
+
+/** Disk space by directory, model and generation type, plus the largest grids. */
+export async function getDiskUsageReport(limit?: number): Promise<DiskUsageReport> {
+    return invoke<DiskUsageReport>("get_disk_usage_report", {
+        limit: limit ?? null,
+    });
+}
+
+export async function getMetadataCoverage(limit?: number): Promise<MetadataCoverage> {
+    return invoke<MetadataCoverage>("get_metadata_coverage", {
+        limit: limit ?? null,
+    });
+}
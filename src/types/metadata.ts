@@ -11,6 +11,83 @@ export interface GalleryImageRecord {
     model_name: string | null;
     is_favorite: boolean;
     is_locked: boolean;
+    /** Set on gallery rows whose drive is unplugged; only the cached thumbnail loads. */
+    offline?: boolean;
+    /** Group size when the query collapsed identical prompt+model+size rows. */
+    duplicate_count?: number;
+}
+
+export interface PromptCluster {
+    id: number;
+    label: string;
+    /** Heaviest tags, label tags first. */
+    terms: string[];
+    count: number;
+}
+
+export interface PromptClustersComplete {
+    task_id: number;
+    clustered: number;
+    clusters: number;
+    cancelled: boolean;
+    error: string | null;
+}
+
+export type CollectionRuleField =
+    | "model_family"
+    | "model"
+    | "sampler"
+    | "scheduler"
+    | "source_tool"
+    | "generation_type"
+    | "directory"
+    | "prompt"
+    | "workflow_status"
+    | "tag"
+    | "rating"
+    | "steps"
+    | "cfg_scale"
+    | "width"
+    | "height"
+    | "nsfw_score"
+    | "sharpness"
+    | "favorite";
+
+export type CollectionRuleOp = "eq" | "ne" | "gt" | "gte" | "lt" | "lte" | "contains";
+
+export interface CollectionRuleCondition {
+    field: CollectionRuleField;
+    op: CollectionRuleOp;
+    value: string | number | boolean;
+}
+
+export interface CollectionRule {
+    /** "all" joins conditions with AND, "any" with OR. */
+    match: "all" | "any";
+    conditions: CollectionRuleCondition[];
+}
+
+export interface Collection {
+    id: number;
+    name: string;
+    rule: CollectionRule;
+    /** Visible members. */
+    count: number;
+}
+
+export interface ColorMatch extends GalleryImageRecord {
+    /** Distance from the searched colour, 0 (same) to 1. */
+    distance: number;
+}
+
+export interface SimilarImage extends GalleryImageRecord {
+    /** Bits in which its perceptual hash differs from the searched image's, 0-64. */
+    distance: number;
+}
+
+export interface VolumeStatus {
+    root: string;
+    online: boolean;
 }
 
 export interface ImageRecord extends GalleryImageRecord {
@@ -22,6 +99,66 @@ export interface ImageRecord extends GalleryImageRecord {
     model_hash: string | null;
     raw_metadata: string;
     file_mtime: number | null;
+    /** Where each parsed field was read from; only set by `get_image_detail`. */
+    field_sources?: FieldSource[];
+}
+
+/** Where a parsed field came from in `raw_metadata`. */
+export type FieldSource = { field: string } & (
+    | {
+          kind: "text";
+          /** A1111 parameter name; null for the prompt sections. */
+          key: string | null;
+          /** Byte offsets into `raw_metadata`. */
+          start: number;
+          end: number;
+      }
+    | { kind: "json_path"; path: string }
+    | { kind: "comfy_node"; node_id: string; class_type: string; input: string }
+    | { kind: "inferred"; detail: string }
+);
+
+export interface BatchSuggestion {
+    id: number;
+    filepath: string;
+    /** 0–1, higher is a likelier keeper. */
+    score: number;
+    /** Laplacian variance; null when the image could not be read. */
+    sharpness: number | null;
+    face_confidence: number | null;
+    nsfw_score: number;
+    suggested: boolean;
+}
+
+export interface ImageDetail extends ImageRecord {
+    tags: string[];
+    workflow_status: WorkflowStatus;
+    /** Text read by `ocrImages`; null until it has run. */
+    ocr_text: string | null;
+}
+
+export type SelectionParameterField =
+    | "model"
+    | "sampler"
+    | "steps"
+    | "cfg_scale"
+    | "size";
+
+export interface SelectionValueCount {
+    value: string | null;
+    count: number;
+}
+
+export interface SelectionParameter {
+    field: SelectionParameterField;
+    shared: boolean;
+    value: string | null;
+    values: SelectionValueCount[];
+}
+
+export interface SelectionSummary {
+    count: number;
+    parameters: SelectionParameter[];
 }
 
 export interface ScanResult {
@@ -35,17 +172,187 @@ export interface TagCount {
     count: number;
 }
 
+/** Filters on an unmapped A1111 parameter, e.g. `{ key: "VAE", value: "..." }`. */
+export interface ExtraParamFilter {
+    key: string;
+    value: string;
+}
+
+export interface ExtraParamKey {
+    key: string;
+    count: number;
+    values: { value: string; count: number }[];
+}
+
+export type TagCategory =
+    | "general"
+    | "artist"
+    | "copyright"
+    | "character"
+    | "meta"
+    | "species"
+    | "invalid"
+    | "lore"
+    | "other";
+
+export interface TagSuggestion {
+    tag: string;
+    category: TagCategory | null;
+    post_count: number | null;
+    library_count: number;
+}
+
+export interface TagDictionaryImportResult {
+    imported: number;
+    skipped: number;
+}
+
+export interface BulkEditPromptsResult {
+    requested: number;
+    updated: number;
+    updated_ids: number[];
+    skipped_locked_ids: number[];
+    files_rewritten: number;
+    failed_paths: string[];
+}
+
+/** Fields for `bulkUpdateFields`; omitted fields are kept, `""` clears. */
+export interface FieldUpdate {
+    modelName?: string;
+    modelHash?: string;
+    seed?: string;
+    steps?: string;
+    sampler?: string;
+    scheduleType?: string;
+    cfgScale?: string;
+    notes?: string;
+}
+
+export interface BulkUpdateFieldsResult {
+    requested: number;
+    updated: number;
+    updated_ids: number[];
+    skipped_locked_ids: number[];
+    edit_id: number | null;
+    failed_paths: string[];
+}
+
+export interface UndoFieldEditResult {
+    restored: number;
+    failed_paths: string[];
+}
+
+export interface FieldEdit {
+    id: number;
+    /** Unix seconds. */
+    edited_at: number;
+    fields: string[];
+    image_count: number;
+    undone: boolean;
+}
+
+/** A custom key/value set on an image, e.g. `{ key: "client", value: "Acme" }`. */
+export interface UserProperty {
+    key: string;
+    value: string;
+}
+
+export interface UserPropertyKey {
+    key: string;
+    count: number;
+    values: { value: string; count: number }[];
+}
+
+export interface PromptEditSample {
+    id: number;
+    filename: string;
+    field: "prompt" | "negative_prompt";
+    before: string;
+    after: string;
+}
+
+export interface PromptEditPreview {
+    scanned: number;
+    affected: number;
+    affected_prompts: number;
+    affected_negative_prompts: number;
+    locked: number;
+    samples: PromptEditSample[];
+}
+
+export interface PromptStyle {
+    name: string;
+    prompt: string;
+    negative_prompt: string;
+}
+
+export interface PromptStyleImportResult {
+    imported: number;
+    skipped: number;
+}
+
+export interface AppliedPromptStyle {
+    prompt: string;
+    negative_prompt: string;
+}
+
 export interface ExportResult {
     exported_count: number;
     output_path: string;
 }
 
+export interface ExportProgress {
+    task_id: number;
+    current: number;
+    total: number;
+}
+
 export interface FileExportResult {
     exported_count: number;
     output_path: string;
     total_bytes: number;
 }
 
+/** Crop and resize applied to converted exports; the crop happens first. */
+export interface ExportTransform {
+    /** Longest edge in pixels; smaller images keep their size. */
+    max_edge?: number | null;
+    /** Center crop aspect ratio such as "1:1" or "3:2". */
+    crop_aspect?: string | null;
+}
+
+export interface FileExportOptions {
+    transform?: ExportTransform | null;
+    /**
+     * Names inside the ZIP, e.g. "{seed}_{model}_{index}". Tokens: stem, id,
+     * index, seed, model, sampler, steps, width, height, folder. Clashing
+     * names get _1, _2, ... appended.
+     */
+    name_template?: string | null;
+}
+
+/** Projected export size from a few trial encodes (before ZIP compression). */
+export interface ExportEstimate {
+    image_count: number;
+    sampled: number;
+    source_bytes: number;
+    estimated_bytes: number;
+    average_bytes: number;
+    size_ratio: number;
+    average_encode_ms: number;
+}
+
+/** Images that are copies of each other, oldest first. */
+export interface DuplicateGroup {
+    quick_hash: string;
+    /** Full-file SHA-256 when the group was verified byte for byte. */
+    sha256: string | null;
+    ids: number[];
+    filepaths: string[];
+    /** Copy to keep: a locked or favourite one if any, else the oldest. */
+    keep_id: number;
+}
+
 export interface DeleteImagesResult {
     requested: number;
     removed_from_db: number;
@@ -62,6 +369,29 @@ export interface DeleteImagesResult {
 
 export type DeleteMode = "trash" | "permanent";
 
+export interface CollectionMembership {
+    id: number;
+    name: string;
+    /** How many of the images to delete it holds. */
+    count: number;
+}
+
+/** What a deletion would put at risk, from `previewDeleteImages`. */
+export interface DeletionSafetyReport {
+    requested: number;
+    favorites: number;
+    locked: number;
+    /** Favorites and locked images skipped without `force`; not in the counts below. */
+    skipped_protected: number;
+    in_collections: number;
+    collections: CollectionMembership[];
+    /** Images that img2img passes or upscales in the library were made from. */
+    lineage_parent_ids: number[];
+    large_selection: boolean;
+    /** Set when `deleteImages` needs it to go ahead. */
+    confirmation_token: string | null;
+}
+
 export interface MovedImageRecord {
     id: number;
     filepath: string;
@@ -77,10 +407,31 @@ export interface MoveImagesResult {
     moved_items: MovedImageRecord[];
     skipped_missing: number;
     skipped_same_directory: number;
+    skipped_locked: number;
+    skipped_locked_ids: number[];
     failed: number;
     failed_paths: string[];
 }
 
+export interface ArchiveImagesResult {
+    requested: number;
+    archived_ids: number[];
+    /** The zip written when compressing. */
+    archive_file: string | null;
+    already_archived: number;
+    skipped_locked: number;
+    skipped_locked_ids: number[];
+    skipped_missing: number;
+    failed_paths: string[];
+}
+
+export interface UnarchiveImagesResult {
+    requested: number;
+    restored_ids: number[];
+    not_archived: number;
+    failed_paths: string[];
+}
+
 export type DeleteHistoryStatus = "pending" | "finalized" | "undone" | "failed";
 
 export interface DeleteHistoryEntry {
@@ -95,14 +446,138 @@ export interface DeleteHistoryEntry {
 
 export type ImageExportFormat = "original" | "png" | "jpeg" | "webp" | "jxl";
 
+export interface SlideshowFilter {
+    query?: string | null;
+    tagsInclude?: string[];
+    tagsExclude?: string[];
+    cursor?: string | null;
+    generationTypes?: GenerationType[] | null;
+    sortBy?: SortOption | null;
+    modelFilter?: string | null;
+    modelFamilyFilters?: string[] | null;
+    schedulerFilters?: string[] | null;
+    resolutionBuckets?: string[] | null;
+    sourceTools?: string[] | null;
+    workflowStatuses?: WorkflowStatus[] | null;
+    promptClusters?: number[] | null;
+    collections?: number[] | null;
+    maxSharpness?: number | null;
+    /** Nearly black or blown-out frames only. */
+    failedRendersOnly?: boolean;
+    includeHidden?: boolean;
+    refine?: string | null;
+    extraParams?: ExtraParamFilter[];
+    embeddings?: string[];
+}
+
+export interface SlideshowItem {
+    id: number;
+    filepath: string;
+    filename: string;
+    width: number | null;
+    height: number | null;
+    display_path: string;
+    display_ready: boolean;
+}
+
+export interface SlideshowBatch {
+    items: SlideshowItem[];
+    next_cursor: string | null;
+}
+
+export interface FocalPoint {
+    /** Fractions of the image width and height. */
+    x: number;
+    y: number;
+}
+
 export interface ThumbnailMapping {
     filepath: string;
     thumbnail_path: string;
+    /** Subject position for cropping, once `detectFocalPoints` has run. */
+    focal_point?: FocalPoint;
+}
+
+export interface ThumbnailTiers {
+    filepath: string;
+    /** `data:` URI of a 64px JPEG to blur up from; absent without a thumbnail. */
+    tiny?: string;
+    thumbnail_path: string;
+    focal_point?: FocalPoint;
+}
+
+export type ThumbnailFailureKind =
+    | "missing"
+    | "empty_file"
+    | "unreadable"
+    | "unsupported"
+    | "decode"
+    | "too_large"
+    | "write";
+
+export interface ThumbnailFailureRecord {
+    image_id: number;
+    filepath: string;
+    kind: ThumbnailFailureKind;
+    message: string;
+    /** Failed attempts since the thumbnail last rendered. */
+    attempts: number;
+    /** Unix seconds of the latest failure. */
+    failed_at: number;
+    /** Failed to decode repeatedly; skipped by retries. */
+    corrupt: boolean;
+}
+
+export interface QuarantineResult {
+    requested: number;
+    quarantined: number;
+    quarantined_ids: number[];
+    skipped_locked: number;
+    failed: number;
+    failed_paths: string[];
+    quarantine_dir: string;
+}
+
+export type OperationKind = "move" | "quarantine" | "delete" | "convert";
+
+/** A bulk file operation the app closed or crashed in the middle of. */
+export interface PendingOperation {
+    id: number;
+    kind: OperationKind;
+    /** Unix seconds. */
+    started_at: number;
+    /** What the operation ran with, such as the delete mode. */
+    detail: Record<string, unknown>;
+    total_steps: number;
+    done_steps: number;
+}
+
+export interface ResumeOperationResult {
+    operation_id: number;
+    kind: OperationKind;
+    rolled_back: boolean;
+    /** Files brought to their finished state, or back where they were. */
+    resolved: number;
+    failed_paths: string[];
+}
+
+export interface ThumbnailRetryResult {
+    retried: number;
+    generated: number;
+    failed: number;
+}
+
+export interface ThumbnailRegenerateResult {
+    requested: number;
+    regenerated: number;
+    failed: number;
 }
 
 export interface CursorPage<T = GalleryImageRecord> {
     items: T[];
     next_cursor: string | null;
+    /** Spelling corrections, present only when a search found nothing. */
+    suggestions?: string[];
 }
 
 export interface SidecarData {
@@ -122,9 +597,80 @@ export interface ForgePayload {
     height?: number;
 }
 
+export type ForgeFailureKind =
+    | "dns"
+    | "connect"
+    | "tls"
+    | "timeout"
+    | "unauthorized"
+    | "api_disabled"
+    | "not_found"
+    | "http"
+    | "other";
+
+/** Connection test result; diagnostics are `null` when unknown. */
 export interface ForgeStatus {
     ok: boolean;
     message: string;
+    failure: ForgeFailureKind | null;
+    http_status: number | null;
+    latency_ms: number | null;
+    version: string | null;
+    api_enabled: boolean | null;
+    api_auth: boolean | null;
+}
+
+/** Forge server memory (bytes) and activity; `null` when not reported. */
+export interface ForgeServerStats {
+    ram_used: number | null;
+    ram_total: number | null;
+    vram_used: number | null;
+    vram_total: number | null;
+    vram_peak: number | null;
+    oom_events: number | null;
+    busy: boolean;
+    job_count: number | null;
+    memory_pressure: boolean;
+    warnings: string[];
+    fetched_at: string;
+}
+
+export interface ForgeWatcherStatus {
+    enabled: boolean;
+    outputs_dir: string | null;
+    running: boolean;
+    /** Images indexed since the watcher last started. */
+    indexed: number;
+}
+
+/** Payload of `forge-outputs-indexed`; `sessions` are `YYYY-MM-DD` days. */
+export interface ForgeOutputsIndexed {
+    count: number;
+    filepaths: string[];
+    sessions: string[];
+}
+
+export interface WatchFolderStatus {
+    folder: string;
+    running: boolean;
+    /** Images indexed since the folder's watcher last started. */
+    indexed: number;
+}
+
+/** Payload of `library-updated`, sent when a watched folder gains images. */
+export interface LibraryUpdated {
+    directory: string;
+    count: number;
+    filepaths: string[];
+}
+
+export type ForgeResourceKind = "model" | "lora" | "embedding";
+
+/** A referenced resource the Forge server doesn't have installed. */
+export interface ForgeResourceWarning {
+    kind: ForgeResourceKind;
+    name: string;
+    message: string;
 }
 
 export interface ForgeSendResult {
@@ -133,6 +679,14 @@ export interface ForgeSendResult {
     output_dir: string;
     generated_count: number;
     saved_paths: string[];
+    warnings: ForgeResourceWarning[];
+    skipped_duplicates: ForgeSkippedDuplicate[];
+}
+
+/** Generated image identical to a library image, so not saved again. */
+export interface ForgeSkippedDuplicate {
+    image_id: number;
+    filepath: string;
 }
 
 export interface ForgeBatchItemResult {
@@ -142,6 +696,8 @@ export interface ForgeBatchItemResult {
     message: string;
     generated_count: number;
     saved_paths: string[];
+    warnings: ForgeResourceWarning[];
+    skipped_duplicates: ForgeSkippedDuplicate[];
 }
 
 export interface ForgeBatchSendResult {
@@ -158,6 +714,7 @@ export interface ForgeOptionsResult {
     loras: string[];
     samplers: string[];
     schedulers: string[];
+    styles: string[];
     models_scan_dir: string | null;
     loras_scan_dir: string | null;
     warnings: string[];
@@ -174,6 +731,120 @@ export interface ForgePayloadOverrides {
     width: string;
     height: string;
     model_name: string;
+    /** Omit to keep the source image's hires pass; `false` disables it. */
+    enable_hr?: boolean;
+    hr_scale?: string;
+    hr_upscaler?: string;
+    hr_second_pass_steps?: string;
+    denoising_strength?: string;
+}
+
+/** Named generation settings applied to Forge sends; unset fields keep the source image's. */
+export interface ForgePreset {
+    name: string;
+    steps: string | null;
+    cfg_scale: string | null;
+    sampler_name: string | null;
+    scheduler: string | null;
+    width: string | null;
+    height: string | null;
+    enable_hr: boolean | null;
+    hr_scale: string | null;
+    hr_upscaler: string | null;
+    hr_second_pass_steps: string | null;
+    denoising_strength: string | null;
+}
+
+/** Order in the Forge queue; single sends default to high, batches to normal. */
+export type ForgePriority = "low" | "normal" | "high";
+
+/** Local `HH:MM` times; an end before the start runs past midnight. */
+export interface ForgeRunWindow {
+    start: string;
+    end: string;
+}
+
+/** Send defaults for a checkpoint name or a model family such as `sdxl`. */
+export interface ModelDefaults {
+    model: string;
+    sampler: string | null;
+    scheduler: string | null;
+    steps: number | null;
+    cfg_scale: number | null;
+    width: number | null;
+    height: number | null;
+}
+
+export type HookEvent =
+    | "scan_complete"
+    | "images_indexed"
+    | "forge_batch_finished";
+
+export type HookAction =
+    | { kind: "webhook"; url: string }
+    | { kind: "script"; program: string; args: string[] };
+
+export interface HookConfig {
+    id: string;
+    name: string;
+    enabled: boolean;
+    events: HookEvent[];
+    action: HookAction;
+    /** Body template; `{{field}}` placeholders resolve against the event payload. */
+    payload_template: string | null;
+}
+
+export interface HookRunResult {
+    ok: boolean;
+    message: string;
+}
+
+export type ActivityKind =
+    | "scan"
+    | "import"
+    | "delete"
+    | "move"
+    | "quarantine"
+    | "archive"
+    | "unarchive"
+    | "export"
+    | "forge_batch"
+    | "settings";
+
+export interface ActivityEntry {
+    id: number;
+    /** Unix seconds. */
+    occurred_at: number;
+    kind: ActivityKind;
+    summary: string;
+    /** Counts, modes and targets particular to the kind. */
+    detail: Record<string, unknown>;
+    /** Files the event touched; list them with `getActivityFiles`. */
+    file_count: number;
+}
+
+/** Narrows the activity log; omitted fields don't filter. */
+export interface ActivityFilter {
+    kinds?: ActivityKind[];
+    /** Unix seconds, inclusive. */
+    since?: number;
+    /** Unix seconds, inclusive. */
+    until?: number;
+    /** Only events that touched one of these files. */
+    filepaths?: string[];
+    /** Only events that touched a file in this folder or below it. */
+    directory?: string;
+    /** Case-insensitive text in the summary. */
+    search?: string;
+    limit?: number;
+}
+
+export interface DirectoryEntry {
+    directory: string;
+    count: number;
+    pinned: boolean;
+    alias: string | null;
+    hidden: boolean;
 }
 
 export interface ModelEntry {
@@ -181,13 +852,103 @@ export interface ModelEntry {
     count: number;
 }
 
+export interface LoraUsage {
+    name: string;
+    count: number;
+    avg_weight: number;
+    min_weight: number;
+    max_weight: number;
+    /** `YYYY-MM-DD` of the newest image using it. */
+    last_used: string | null;
+}
+
+export interface EmbeddingUsage {
+    name: string;
+    count: number;
+    positive_count: number;
+    negative_count: number;
+    /** `YYYY-MM-DD` of the newest image using it. */
+    last_used: string | null;
+}
+
+export interface ImageFilter {
+    query?: string | null;
+    tagsInclude?: string[];
+    tagsExclude?: string[];
+    generationTypes?: GenerationType[] | null;
+    modelFilter?: string | null;
+    modelFamilyFilters?: string[] | null;
+    schedulerFilters?: string[] | null;
+    resolutionBuckets?: string[] | null;
+    sourceTools?: string[] | null;
+    workflowStatuses?: WorkflowStatus[] | null;
+    promptClusters?: number[] | null;
+    collections?: number[] | null;
+    maxSharpness?: number | null;
+    /** Nearly black or blown-out frames only. */
+    failedRendersOnly?: boolean;
+    includeHidden?: boolean;
+    refine?: string | null;
+    extraParams?: ExtraParamFilter[];
+    embeddings?: string[];
+}
+
+export interface FilterCount {
+    value: string;
+    count: number;
+}
+
+/** Badge counts; each facet ignores its own selection but keeps the other. */
+export interface FilterCounts {
+    total: number;
+    generation_types: FilterCount[];
+    model_families: FilterCount[];
+}
+
+/** Triage stage of an image; new images start in the inbox. */
+export type WorkflowStatus = "inbox" | "picked" | "edited" | "published" | "rejected";
+
+export interface WorkflowStatusCount {
+    status: WorkflowStatus;
+    count: number;
+}
+
+export interface SourceToolEntry {
+    /** `comfyui`, `forge`, `a1111`, `novelai`, ... or `unknown`. */
+    source_tool: string;
+    count: number;
+}
+
+export interface SamplerEntry {
+    sampler: string;
+    count: number;
+}
+
+export interface SchedulerEntry {
+    scheduler: string;
+    count: number;
+}
+
+export interface ResolutionBucket {
+    /** `WIDTHxHEIGHT`, snapped to a 64px grid. */
+    bucket: string;
+    width: number;
+    height: number;
+    count: number;
+    tier: "sd15" | "sdxl" | "large";
+}
+
 export type GenerationType =
     | "txt2img"
     | "img2img"
     | "inpaint"
     | "grid"
     | "upscale"
-    | "unknown";
+    | "unknown"
+    /** Shows archived images only; they are hidden from every other view. */
+    | "archived"
+    /** Narrows to images lacking a prompt, model or seed. */
+    | "missing_metadata";
 
 export type SortOption =
     | "newest"
@@ -195,5 +956,366 @@ export type SortOption =
     | "name_asc"
     | "name_desc"
     | "model"
-    | "generation_type";
+    | "generation_type"
+    /** Colour wheel order, greyscale images last. */
+    | "hue"
+    | "brightness_asc"
+    | "brightness_desc"
+    /** Blurriest first; unmeasured images last. */
+    | "sharpness_asc"
+    | "sharpness_desc";
 export type StorageProfile = "hdd" | "ssd";
+
+export interface BackgroundStatus {
+    close_to_tray: boolean;
+    paused: boolean;
+    scan_roots: string[];
+}
+
+export interface AutotagStatus {
+    available: boolean;
+    model_dir: string;
+    model_present: boolean;
+}
+
+export interface AutotagProgress {
+    task_id: number;
+    current: number;
+    total: number;
+    tagged: number;
+    failed: number;
+}
+
+export interface AutotagComplete {
+    task_id: number;
+    total: number;
+    tagged: number;
+    failed: number;
+    cancelled: boolean;
+    error: string | null;
+}
+
+export interface OcrStatus {
+    available: boolean;
+    model_dir: string;
+    model_present: boolean;
+}
+
+export interface OcrProgress {
+    task_id: number;
+    current: number;
+    total: number;
+    read: number;
+    failed: number;
+}
+
+export interface OcrComplete {
+    task_id: number;
+    total: number;
+    read: number;
+    failed: number;
+    cancelled: boolean;
+    error: string | null;
+}
+
+export interface FocalPointProgress {
+    task_id: number;
+    current: number;
+    total: number;
+    detected: number;
+    failed: number;
+}
+
+export interface FocalPointComplete {
+    task_id: number;
+    total: number;
+    detected: number;
+    failed: number;
+    cancelled: boolean;
+    error: string | null;
+}
+
+export interface StripFrame {
+    id: number;
+    filename: string;
+    /** Left edge of the frame in the strip, in pixels. */
+    x: number;
+    width: number;
+}
+
+export interface ThumbnailStripResult {
+    output_path: string;
+    offsets_path: string;
+    width: number;
+    height: number;
+    frames: StripFrame[];
+    skipped_ids: number[];
+}
+
+export interface ImageDiffResult {
+    /** PNG overlay in the display cache; transparent where nothing changed. */
+    heatmap_path: string;
+    width: number;
+    height: number;
+    /** Mean absolute pixel difference, 0 (identical) to 1. */
+    score: number;
+    changed_fraction: number;
+    /** The images differed in size and the second was scaled to fit. */
+    resized: boolean;
+}
+
+export type BackgroundTaskKind =
+    | "scan"
+    | "thumbnail_warmup"
+    | "thumbnail_precache"
+    | "forge_batch"
+    | "autotag"
+    | "ocr"
+    | "prompt_clusters"
+    | "focal_points"
+    | "export";
+
+export type BackgroundTaskStatus =
+    | "running"
+    | "paused"
+    | "completed"
+    | "cancelled"
+    | "failed";
+
+export interface BackgroundTask {
+    id: number;
+    kind: BackgroundTaskKind;
+    label: string;
+    status: BackgroundTaskStatus;
+    current: number;
+    total: number;
+    message: string | null;
+    pause_requested: boolean;
+    cancel_requested: boolean;
+    started_at: string;
+    finished_at: string | null;
+}
+
+export interface QueryLatency {
+    endpoint: string;
+    count: number;
+    errors: number;
+    avg_ms: number;
+    max_ms: number;
+}
+
+export interface CacheHitRate {
+    name: string;
+    hits: number;
+    misses: number;
+    hit_rate: number | null;
+}
+
+export interface ScanStageDurations {
+    discovery_ms: number;
+    filter_ms: number;
+    metadata_ms: number;
+    thumbnails_ms: number;
+    total_ms: number;
+}
+
+export interface PerformanceReport {
+    storage_profile: StorageProfile;
+    cpu_count: number;
+    scan_threads: number;
+    thumbnail_threads: number;
+    db_pool_size: number;
+    uptime_seconds: number;
+    queries: QueryLatency[];
+    thumbnails: {
+        generated: number;
+        failed: number;
+        avg_ms: number;
+        images_per_second: number;
+    };
+    caches: CacheHitRate[];
+    scans: {
+        runs: number;
+        files_processed: number;
+        last: ScanStageDurations | null;
+        average: ScanStageDurations | null;
+    };
+}
+
+/** `null` keeps the storage-profile default. */
+export interface PerformanceSettings {
+    scan_threads: number | null;
+    thumbnail_threads: number | null;
+    db_pool_size: number | null;
+    /** Megapixels decoded whole for thumbnails; larger PNGs are downscaled while decoding. */
+    preview_budget_megapixels: number | null;
+    /** Memory thumbnail decodes may hold at once, in MB. */
+    decode_memory_budget_mb: number | null;
+}
+
+/** Gallery position restored on the next launch. */
+export interface ViewState {
+    query: string;
+    tags_include: string[];
+    tags_exclude: string[];
+    generation_types: string[];
+    model_filter: string | null;
+    model_family_filters: string[];
+    sort_by: SortOption | null;
+    /** Cursor of the first visible page, as returned by the cursor queries. */
+    cursor: string | null;
+    selected_collection: string | null;
+    selected_image_id: number | null;
+}
+
+export interface SavedSearch {
+    name: string;
+    view: ViewState;
+}
+
+/** Settings for `exportImagesAsFiles`, kept as the export dialog sends them. */
+export interface ExportPreset {
+    name: string;
+    format: string;
+    quality: number | null;
+    max_edge: number | null;
+    crop_aspect: string | null;
+    name_template: string | null;
+    output_dir: string | null;
+}
+
+/** A client or job: its folders, saved searches and export presets. */
+export interface Project {
+    name: string;
+    /** Folders the gallery and stats are confined to while active; empty leaves them unscoped. */
+    directories: string[];
+    saved_searches: SavedSearch[];
+    export_presets: ExportPreset[];
+    /** Unix seconds. */
+    created_at: number;
+}
+
+export interface ProjectSettings {
+    projects: Project[];
+    /** Active project name; null shows the whole library. */
+    active: string | null;
+}
+
+export interface NsfwSettings {
+    hide_nsfw: boolean;
+    /** Images with an nsfw_score at or above this (0-1] are hidden. */
+    threshold: number;
+}
+
+export interface PerformanceSettingsState {
+    settings: PerformanceSettings;
+    effective_scan_threads: number;
+    effective_thumbnail_threads: number;
+    active_db_pool_size: number;
+    restart_required: boolean;
+}
+
+export interface PrivateStatus {
+    has_passcode: boolean;
+    unlocked: boolean;
+    /** Empty while locked. */
+    directories: string[];
+    directory_count: number;
+}
+
+export type AppErrorCode =
+    | "invalid_input"
+    | "file_missing"
+    | "not_found"
+    | "conflict"
+    | "database_busy"
+    | "database"
+    | "permission_denied"
+    | "io"
+    | "forge_unreachable"
+    | "forge"
+    | "image"
+    | "cancelled"
+    | "internal";
+
+/** Shape of every error rejected by a backend command. */
+export interface AppErrorPayload {
+    code: AppErrorCode;
+    message: string;
+}
+
+export type TrendBucket = "week" | "month";
+
+export interface KeywordSeries {
+    term: string;
+    counts: number[];
+}
+
+/** `periods`, `totals` and each series' `counts` are index-aligned. */
+export interface KeywordTrends {
+    bucket: TrendBucket;
+    /** Bucket start dates (YYYY-MM-DD), oldest first. */
+    periods: string[];
+    totals: number[];
+    series: KeywordSeries[];
+}
+
+export interface ModelUsageSeries {
+    model_name: string;
+    total: number;
+    /** Index-aligned with ModelUsage.periods. */
+    counts: number[];
+    first_used: string | null;
+    last_used: string | null;
+    avg_steps: number | null;
+    avg_cfg_scale: number | null;
+    avg_width: number | null;
+    avg_height: number | null;
+}
+
+export interface ModelUsage {
+    bucket: TrendBucket;
+    periods: string[];
+    models: ModelUsageSeries[];
+}
+
+export interface DiskUsageEntry {
+    key: string;
+    bytes: number;
+    count: number;
+}
+
+export interface DiskUsageFile {
+    id: number;
+    filepath: string;
+    bytes: number;
+}
+
+export interface DiskUsageReport {
+    total_bytes: number;
+    total_images: number;
+    /** Images indexed without a recorded size. */
+    unsized_images: number;
+    by_directory: DiskUsageEntry[];
+    by_model: DiskUsageEntry[];
+    by_generation_type: DiskUsageEntry[];
+    largest_grids: DiskUsageFile[];
+}
+
+export interface MetadataGap {
+    directory: string;
+    incomplete: number;
+    total: number;
+}
+
+export interface MetadataCoverage {
+    total_images: number;
+    with_prompt: number;
+    with_model: number;
+    with_seed: number;
+    /** Images with a prompt, a model and a seed. */
+    complete: number;
+    /** Images indexed without any metadata text. */
+    without_metadata: number;
+    incomplete_by_directory: MetadataGap[];
+}